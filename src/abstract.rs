@@ -5,14 +5,22 @@
 use crate::{
     blob_tree::FragmentationMap, compaction::CompactionStrategy, config::TreeType,
     iter_guard::IterGuardImpl, table::Table, tree::inner::MemtableId, version::Version,
-    vlog::BlobFile, AnyTree, BlobTree, Config, Guard, InternalValue, KvPair, Memtable, SeqNo,
-    SequenceNumberCounter, TableId, Tree, TreeId, UserKey, UserValue,
+    vlog::BlobFile, AnyTree, BlobTree, Config, ExportFormat, Guard, InternalValue, KvPair,
+    Memtable, ReadOptions, SeqNo, SequenceNumberCounter, TableId, Tree, TreeId, UserKey, UserValue,
+    ValueType,
 };
 use enum_dispatch::enum_dispatch;
-use std::{ops::RangeBounds, sync::Arc};
+use std::{
+    ops::RangeBounds,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 pub type RangeItem = crate::Result<KvPair>;
 
+/// Raw version of an item: (key, value, seqno, value type), see [`AbstractTree::raw_range`]
+pub type RawItem = (UserKey, UserValue, SeqNo, ValueType);
+
 /// Generic Tree API
 #[enum_dispatch]
 pub trait AbstractTree {
@@ -25,6 +33,11 @@ pub trait AbstractTree {
     #[doc(hidden)]
     fn get_internal_entry(&self, key: &[u8], seqno: SeqNo) -> crate::Result<Option<InternalValue>>;
 
+    /// Returns the sequence number of the newest write to a key,
+    /// including tombstones and range tombstones.
+    #[doc(hidden)]
+    fn get_key_seqno(&self, key: &[u8]) -> crate::Result<Option<SeqNo>>;
+
     #[doc(hidden)]
     fn current_version(&self) -> Version;
 
@@ -41,9 +54,38 @@ pub trait AbstractTree {
     #[doc(hidden)]
     fn flush_active_memtable(&self, seqno_threshold: SeqNo) -> crate::Result<Option<Table>>;
 
+    /// Writes out and fsyncs the journal, making all previous writes durable,
+    /// regardless of the configured [`Durability`](crate::Durability).
+    ///
+    /// Does nothing if the journal is disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Durability};
+    ///
+    /// let tree = Config::new(folder, Default::default())
+    ///     .use_journal(true)
+    ///     .durability(Durability::None)
+    ///     .open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.sync()?;
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn sync(&self) -> crate::Result<()>;
+
     /// Returns an iterator that scans through the entire tree.
     ///
     /// Avoid using this function, or limit it as otherwise it may scan a lot of items.
+    ///
+    /// Reads a consistent snapshot at `seqno`, see [`AbstractTree::range`].
     fn iter(
         &self,
         seqno: SeqNo,
@@ -54,7 +96,33 @@ pub trait AbstractTree {
 
     /// Returns an iterator over a prefixed set of items.
     ///
+    /// The range is bounded correctly for all prefixes, including prefixes
+    /// that end with `0xFF` bytes.
+    ///
     /// Avoid using an empty prefix as it may scan a lot of items (unless limited).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Guard, SeqNo};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    ///
+    /// tree.insert("user1/1", "a", 0);
+    /// tree.insert("user1/2", "b", 1);
+    /// tree.insert("user2/1", "c", 2);
+    ///
+    /// let keys = tree
+    ///     .prefix("user1/", SeqNo::MAX, None)
+    ///     .map(Guard::key)
+    ///     .collect::<lsm_tree::Result<Vec<_>>>()?;
+    ///
+    /// assert_eq!(2, keys.len());
+    /// assert_eq!(b"user1/2", &*keys[1]);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
     fn prefix<K: AsRef<[u8]>>(
         &self,
         prefix: K,
@@ -65,6 +133,14 @@ pub trait AbstractTree {
     /// Returns an iterator over a range of items.
     ///
     /// Avoid using full or unbounded ranges as they may scan a lot of items (unless limited).
+    ///
+    /// The iterator is double-ended, so descending scans (e.g. `.rev().take(n)`)
+    /// start reading at the end of the range, without going through the range forward.
+    ///
+    /// Only items with a sequence number lower than `seqno` are visible, so the iterator
+    /// reads a consistent snapshot of the tree, even if the tree is written to, flushed
+    /// or compacted while iterating.
+    /// `index` is an optional ephemeral memtable that is read on top of the snapshot, pass `None` otherwise.
     fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
         &self,
         range: R,
@@ -72,6 +148,295 @@ pub trait AbstractTree {
         index: Option<Arc<Memtable>>,
     ) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static>;
 
+    /// Returns an iterator over a range of items, with the given [`ReadOptions`].
+    ///
+    /// The range is intersected with the bounds of the read options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Guard, ReadOptions, SeqNo};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "1", 0);
+    /// tree.insert("b", "2", 1);
+    /// tree.insert("c", "3", 2);
+    /// tree.insert("d", "4", 3);
+    ///
+    /// let options = ReadOptions::default().upper_bound("d").limit(2);
+    ///
+    /// let keys = tree
+    ///     .range_with_options("b".., SeqNo::MAX, &options)
+    ///     .map(Guard::key)
+    ///     .collect::<lsm_tree::Result<Vec<_>>>()?;
+    ///
+    /// assert_eq!(2, keys.len());
+    /// assert_eq!(b"c", &*keys[1]);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn range_with_options<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+        options: &ReadOptions,
+    ) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static>;
+
+    /// Returns an iterator over a prefixed set of items, with the given [`ReadOptions`],
+    /// see [`AbstractTree::prefix`] and [`AbstractTree::range_with_options`].
+    fn prefix_with_options<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+        seqno: SeqNo,
+        options: &ReadOptions,
+    ) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static> {
+        let range = crate::range::prefix_to_range(prefix.as_ref());
+        self.range_with_options(range, seqno, options)
+    }
+
+    /// Returns an iterator that scans through the entire tree, with the given [`ReadOptions`],
+    /// see [`AbstractTree::range_with_options`].
+    fn iter_with_options(
+        &self,
+        seqno: SeqNo,
+        options: &ReadOptions,
+    ) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static> {
+        self.range_with_options::<&[u8], _>(.., seqno, options)
+    }
+
+    /// Returns an iterator over all keys of the tree.
+    ///
+    /// Values are never read; for a key-value separated tree, keys are read from
+    /// the index tree only, without accessing blob files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SeqNo};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "def", 1);
+    ///
+    /// let keys = tree.keys(SeqNo::MAX, None).collect::<lsm_tree::Result<Vec<_>>>()?;
+    /// assert_eq!(2, keys.len());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn keys(
+        &self,
+        seqno: SeqNo,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + Send + 'static> {
+        self.range_keys::<&[u8], _>(.., seqno, index)
+    }
+
+    /// Returns an iterator over the keys of a range of items, see [`AbstractTree::keys`].
+    fn range_keys<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + Send + 'static> {
+        Box::new(self.range(range, seqno, index).map(Guard::key))
+    }
+
+    /// Returns an iterator over all versions of a range of items, including tombstones.
+    ///
+    /// Versions are ordered by key ascending, then sequence number descending,
+    /// and they are returned as they are stored: merge operands are not folded,
+    /// and for a key-value separated tree, separated values are returned
+    /// as their encoded blob indirection ([`ValueType::Indirection`]).
+    ///
+    /// Range tombstones are not returned, and do not hide the versions they cover.
+    ///
+    /// This is meant for replication and debugging tools, use [`AbstractTree::range`] to read user data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SeqNo, ValueType};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "1", 0);
+    /// tree.insert("a", "2", 1);
+    /// tree.remove("a", 2);
+    ///
+    /// let versions = tree
+    ///     .iter_internal(SeqNo::MAX)
+    ///     .map(|item| item.map(|(_, _, seqno, value_type)| (seqno, value_type)))
+    ///     .collect::<lsm_tree::Result<Vec<_>>>()?;
+    ///
+    /// assert_eq!(
+    ///     vec![(2, ValueType::Tombstone), (1, ValueType::Value), (0, ValueType::Value)],
+    ///     versions,
+    /// );
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn raw_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<RawItem>> + Send + 'static>;
+
+    /// Returns an iterator over all versions of all items, see [`AbstractTree::raw_range`].
+    fn iter_internal(
+        &self,
+        seqno: SeqNo,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<RawItem>> + Send + 'static> {
+        self.raw_range::<&[u8], _>(.., seqno)
+    }
+
+    /// Exports a range of items of a snapshot into a writer.
+    ///
+    /// Returns the amount of exported items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, ExportFormat};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "def", 1);
+    ///
+    /// let mut csv = vec![];
+    /// tree.export(.."b", ExportFormat::Csv, 2, &mut csv)?;
+    /// assert_eq!(b"key,value\na,abc\n", &*csv);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or an item
+    /// cannot be represented in the export format.
+    fn export<K: AsRef<[u8]>, R: RangeBounds<K>, W: std::io::Write>(
+        &self,
+        range: R,
+        format: ExportFormat,
+        seqno: SeqNo,
+        writer: W,
+    ) -> crate::Result<u64> {
+        crate::export::export_items(self.range(range, seqno, None), format, writer)
+    }
+
+    /// Exports a key range into table files that can be bulk loaded into another tree,
+    /// see [`AbstractTree::ingest_tables`].
+    ///
+    /// The table files are written into `folder`, splitting them once they contain
+    /// `target_size` bytes of keys and values. Values of a blob tree are stored in
+    /// the table files, so they do not depend on any file of the tree.
+    ///
+    /// Returns the paths of the written table files, in key order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+    /// #
+    /// # let folder = tempfile::tempdir()?;
+    /// # let other_folder = tempfile::tempdir()?;
+    /// # let export_folder = tempfile::tempdir()?;
+    /// let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "def", 1);
+    /// tree.insert("c", "ghi", 2);
+    ///
+    /// let paths = tree.export_tables("b".., SeqNo::MAX, &export_folder, 64_000_000)?;
+    ///
+    /// let seqno = SequenceNumberCounter::default();
+    /// let other = Config::new(&other_folder, seqno.clone()).open()?;
+    /// other.ingest_tables(&paths, &seqno, &SequenceNumberCounter::default())?;
+    /// assert_eq!(2, other.len(SeqNo::MAX, None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn export_tables<K: AsRef<[u8]>, R: RangeBounds<K>, P: AsRef<Path>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+        folder: P,
+        target_size: u64,
+    ) -> crate::Result<Vec<PathBuf>> {
+        crate::export::export_tables(
+            self.range(range, seqno, None),
+            self.tree_config(),
+            folder.as_ref(),
+            target_size,
+        )
+    }
+
+    /// Exports a snapshot in parallel, split into key range shards.
+    ///
+    /// `n` (ascending) boundaries split the key space into `n + 1` shards,
+    /// which are exported on their own threads. The writer of the i-th shard
+    /// is created by calling `make_writer(i)`.
+    ///
+    /// On WASM targets, which do not support threads, shards are exported one after another.
+    ///
+    /// Returns the amount of exported items.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or an item
+    /// cannot be represented in the export format.
+    fn export_sharded<W, F>(
+        &self,
+        boundaries: &[UserKey],
+        format: ExportFormat,
+        seqno: SeqNo,
+        make_writer: F,
+    ) -> crate::Result<u64>
+    where
+        Self: Sync,
+        W: std::io::Write,
+        F: Fn(usize) -> crate::Result<W> + Sync,
+    {
+        let shards = crate::export::shard_ranges(boundaries);
+
+        #[cfg(target_family = "wasm")]
+        return shards
+            .into_iter()
+            .enumerate()
+            .map(|(idx, range)| self.export(range, format, seqno, make_writer(idx)?))
+            .sum();
+
+        #[cfg(not(target_family = "wasm"))]
+        std::thread::scope(|scope| {
+            let handles = shards
+                .into_iter()
+                .enumerate()
+                .map(|(idx, range)| {
+                    let make_writer = &make_writer;
+
+                    scope.spawn(move || {
+                        let writer = make_writer(idx)?;
+                        self.export(range, format, seqno, writer)
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .sum()
+        })
+    }
+
     /// Ingests a sorted stream of key-value pairs into the tree.
     ///
     /// Can only be called on a new fresh, empty tree.
@@ -93,6 +458,49 @@ pub trait AbstractTree {
         visible_seqno: &SequenceNumberCounter,
     ) -> crate::Result<()>;
 
+    /// Bulk loads table files that were written by a [`SstBuilder`](crate::SstBuilder).
+    ///
+    /// Tables that do not overlap any data of the tree are copied into the last level,
+    /// without decoding their blocks. Tables that overlap are rewritten into L0 using a new
+    /// sequence number, so their items shadow older versions of the same keys.
+    ///
+    /// The input files are not modified and can be deleted afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter, SstBuilder};
+    /// #
+    /// # let folder = tempfile::tempdir()?;
+    /// # let sst_folder = tempfile::tempdir()?;
+    /// let seqno = SequenceNumberCounter::default();
+    /// let visible_seqno = SequenceNumberCounter::default();
+    ///
+    /// let config = Config::new(&folder, seqno.clone());
+    ///
+    /// let path = sst_folder.path().join("0.sst");
+    /// let mut builder = SstBuilder::new(&path, &config)?;
+    /// builder.insert("a", "abc")?;
+    /// builder.finish()?;
+    ///
+    /// let tree = config.open()?;
+    /// tree.ingest_tables(&[path], &seqno, &visible_seqno)?;
+    /// assert!(tree.contains_key("a", SeqNo::MAX)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or [`crate::Error::InvalidIngestion`]
+    /// if the tables overlap each other.
+    fn ingest_tables<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+        seqno_generator: &SequenceNumberCounter,
+        visible_seqno: &SequenceNumberCounter,
+    ) -> crate::Result<()>;
+
     /// Returns the approximate number of tombstones in the tree.
     fn tombstone_count(&self) -> u64;
 
@@ -122,75 +530,265 @@ pub trait AbstractTree {
     /// Will return `Err` if an IO error occurs.
     fn major_compact(&self, target_size: u64, seqno_threshold: SeqNo) -> crate::Result<()>;
 
-    /// Returns the disk space used by stale blobs.
-    fn stale_blob_bytes(&self) -> u64 {
-        0
-    }
-
-    /// Gets the space usage of all filters in the tree.
+    /// Compacts all tables that overlap with a given range into the last level,
+    /// blocking the caller until it's done.
     ///
-    /// May not correspond to the actual memory size because filter blocks may be paged out.
-    fn filter_size(&self) -> usize;
-
-    /// Gets the memory usage of all pinned filters in the tree.
-    fn pinned_filter_size(&self) -> usize;
-
-    /// Gets the memory usage of all pinned index blocks in the tree.
-    fn pinned_block_index_size(&self) -> usize;
-
-    /// Gets the length of the version free list.
-    fn version_free_list_len(&self) -> usize;
-
-    /// Returns the metrics structure.
-    #[cfg(feature = "metrics")]
-    fn metrics(&self) -> &Arc<crate::Metrics>;
-
-    /// Synchronously flushes a memtable to a table.
+    /// Unlike [`AbstractTree::major_compact`], tables that do not overlap with the range
+    /// (or with the tables that are merged) are left untouched,
+    /// which makes this a cheap way to reclaim the space of a deleted key range,
+    /// e.g. after removing all keys of a prefix.
     ///
-    /// This method will not make the table immediately available,
-    /// use [`AbstractTree::register_tables`] for that.
+    /// Accepts any `RangeBounds`, including unbounded or exclusive endpoints.
+    /// If the normalized lower bound is greater than the upper bound, the
+    /// method returns without performing any work.
     ///
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
-    #[warn(clippy::type_complexity)]
-    fn flush_memtable(
+    fn compact_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
         &self,
-        table_id: TableId, // TODO: remove?
-        memtable: &Arc<Memtable>,
+        range: R,
+        target_size: u64,
         seqno_threshold: SeqNo,
-    ) -> crate::Result<Option<(Table, Option<BlobFile>)>>;
+    ) -> crate::Result<()>;
 
-    /// Atomically registers flushed tables into the tree, removing their associated sealed memtables.
+    /// Rewrites all tables using the current config, blocking the caller until it's done.
+    ///
+    /// Settings like block sizes, compression or index and filter layouts only apply
+    /// to newly written tables, so an existing tree can be reopened with different settings.
+    /// This migrates the existing tables to the new settings, one table at a time.
+    /// Other compactions can run in between, and the tables stay in their level.
     ///
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
-    fn register_tables(
-        &self,
-        tables: &[Table],
-        blob_files: Option<&[BlobFile]>,
-        frag_map: Option<FragmentationMap>,
-    ) -> crate::Result<()>;
+    fn rewrite_all_tables(&self, seqno_threshold: SeqNo) -> crate::Result<()>;
 
-    /// Clears the active memtable atomically.
-    fn clear_active_memtable(&self);
-
-    /// Sets the active memtable.
+    /// Pauses the background workers of the tree, see [`Config::background_threads`](crate::Config::background_threads).
     ///
-    /// May be used to restore the LSM-tree's in-memory state from a write-ahead log
-    /// after tree recovery.
-    fn set_active_memtable(&self, memtable: Memtable);
+    /// Blocks until running flushes and compactions are done, so no background work
+    /// happens until [`AbstractTree::resume_background_work`] is called.
+    /// Writes are still accepted, so the active memtable can grow past its maximum size.
+    ///
+    /// Does nothing if the tree has no background workers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    fn pause_background_work(&self);
 
-    /// Returns the number of sealed memtables.
-    fn sealed_memtable_count(&self) -> usize;
+    /// Resumes the background workers of the tree after [`AbstractTree::pause_background_work`],
+    /// catching up on flushes and compactions that became necessary in the meantime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    fn resume_background_work(&self);
 
-    /// Adds a sealed memtables.
+    /// Returns whether writes are currently delayed, see [`crate::WriteStallOptions`].
     ///
-    /// May be used to restore the LSM-tree's in-memory state from some journals.
-    fn add_sealed_memtable(&self, id: MemtableId, memtable: Arc<Memtable>);
+    /// The condition is updated by writes, so it may be outdated if there were no recent writes.
+    fn write_stall_condition(&self) -> crate::WriteStallCondition;
 
-    /// Performs compaction on the tree's levels, blocking the caller until it's done.
+    /// Freezes the files of the tree until the returned guard is dropped.
+    ///
+    /// While the guard is held, no table or blob file is deleted and the manifest is not changed,
+    /// so external tools (e.g. `rsync` or filesystem snapshots) can safely copy the tree folder.
+    /// Reads and writes to the memtable continue as usual.
+    ///
+    /// Flushes and compactions wait until the guard is dropped, so they must not be run
+    /// by the thread that holds the guard, otherwise it deadlocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+    ///
+    /// let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// {
+    ///     let _guard = tree.freeze_files();
+    ///
+    ///     // Copy the tree folder, e.g. using rsync
+    /// }
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    fn freeze_files(&self) -> crate::FreezeGuard<'_>;
+
+    /// Creates a checkpoint of the tree in a new folder.
+    ///
+    /// The active memtable is flushed first. Then, all tables and blob files of the current
+    /// version are hard linked (or copied, if the folder is on a different filesystem) into the
+    /// folder, together with the manifest and the version, so the checkpoint can be opened as a
+    /// tree of its own. Writes can continue while the checkpoint is created,
+    /// but flushes and compactions wait until it is done.
+    ///
+    /// Writes that are made after the flush are not part of the checkpoint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// # let backup_folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SeqNo};
+    ///
+    /// let tree = Config::new(&folder, Default::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    ///
+    /// let path = backup_folder.path().join("checkpoint");
+    /// tree.checkpoint(&path)?;
+    ///
+    /// let checkpoint = Config::new(&path, Default::default()).open()?;
+    /// assert!(checkpoint.contains_key("a", SeqNo::MAX)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the folder already exists, an IO error occurs,
+    /// or [`crate::Error::InvalidConfig`] if the tree is ephemeral.
+    fn checkpoint<P: AsRef<Path>>(&self, path: P) -> crate::Result<()>;
+
+    /// Catches up a secondary instance with the primary tree, see [`Config::open_secondary`].
+    ///
+    /// The current version of the primary tree is loaded, so flushes and compactions
+    /// of the primary tree become visible. Tables and blob files that are already loaded are reused.
+    /// If the journal is enabled, writes of the primary tree that are not flushed yet
+    /// are read from its journal, replacing the active memtable.
+    ///
+    /// Reads that are running while catching up keep seeing the previous state.
+    ///
+    /// The primary tree deletes files it does not need anymore. A secondary instance keeps
+    /// the tables it loaded open, so they stay readable after the primary tree compacts them away,
+    /// and if the version is replaced while catching up, the newer version is loaded instead.
+    /// Blob files are not kept open, so reading a blob value of a secondary instance that is
+    /// behind may fail with an IO error after blob garbage collection; catch up again in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SeqNo};
+    ///
+    /// let primary = Config::new(&folder, Default::default()).open()?;
+    /// let secondary = Config::new(&folder, Default::default()).open_secondary()?;
+    ///
+    /// primary.insert("a", "abc", 0);
+    /// primary.flush_active_memtable(0)?;
+    /// assert!(!secondary.contains_key("a", SeqNo::MAX)?);
+    ///
+    /// secondary.try_catch_up()?;
+    /// assert!(secondary.contains_key("a", SeqNo::MAX)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or [`crate::Error::InvalidConfig`]
+    /// if the tree is not a secondary instance.
+    fn try_catch_up(&self) -> crate::Result<()>;
+
+    /// Returns the disk space used by stale blobs.
+    ///
+    /// Blobs that are shadowed by a tombstone or a newer version only become stale
+    /// once compaction merges the tables containing them, not when the tombstone is written
+    /// or flushed.
+    fn stale_blob_bytes(&self) -> u64 {
+        0
+    }
+
+    /// Gets the space usage of all filters in the tree.
+    ///
+    /// May not correspond to the actual memory size because filter blocks may be paged out.
+    fn filter_size(&self) -> usize;
+
+    /// Gets the memory usage of all pinned filters in the tree.
+    fn pinned_filter_size(&self) -> usize;
+
+    /// Gets the memory usage of all pinned index blocks in the tree.
+    fn pinned_block_index_size(&self) -> usize;
+
+    /// Loads the block indexes and filters of all tables.
+    ///
+    /// When a tree is opened, only the metadata of its tables is read, and the
+    /// block index and filters of each table are loaded when it is first accessed.
+    /// Preloading moves that cost to a point of choice, e.g. right after startup,
+    /// so the first reads do not have to wait for it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn preload(&self) -> crate::Result<()> {
+        for table in self.current_version().iter_tables() {
+            table.preload()?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the length of the version free list.
+    fn version_free_list_len(&self) -> usize;
+
+    /// Returns the metrics structure.
+    #[cfg(feature = "metrics")]
+    fn metrics(&self) -> &Arc<crate::Metrics>;
+
+    /// Synchronously flushes a memtable to a table.
+    ///
+    /// This method will not make the table immediately available,
+    /// use [`AbstractTree::register_tables`] for that.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    #[warn(clippy::type_complexity)]
+    fn flush_memtable(
+        &self,
+        table_id: TableId, // TODO: remove?
+        memtable: &Arc<Memtable>,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<Option<(Table, Option<BlobFile>)>>;
+
+    /// Atomically registers flushed tables into the tree, removing their associated sealed memtables.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn register_tables(
+        &self,
+        tables: &[Table],
+        blob_files: Option<&[BlobFile]>,
+        frag_map: Option<FragmentationMap>,
+    ) -> crate::Result<()>;
+
+    /// Clears the active memtable atomically.
+    fn clear_active_memtable(&self);
+
+    /// Sets the active memtable.
+    ///
+    /// May be used to restore the LSM-tree's in-memory state from a write-ahead log
+    /// after tree recovery.
+    fn set_active_memtable(&self, memtable: Memtable);
+
+    /// Returns the number of sealed memtables.
+    fn sealed_memtable_count(&self) -> usize;
+
+    /// Adds a sealed memtables.
+    ///
+    /// May be used to restore the LSM-tree's in-memory state from some journals.
+    fn add_sealed_memtable(&self, id: MemtableId, memtable: Arc<Memtable>);
+
+    /// Performs compaction on the tree's levels, blocking the caller until it's done.
     ///
     /// # Errors
     ///
@@ -201,6 +799,17 @@ pub trait AbstractTree {
         seqno_threshold: SeqNo,
     ) -> crate::Result<()>;
 
+    /// Performs compaction on the tree's levels using the configured compaction strategy
+    /// (see [`Config::compaction_strategy`]), blocking the caller until it's done.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn run_compaction(&self, seqno_threshold: SeqNo) -> crate::Result<()> {
+        let strategy = self.tree_config().compaction_strategy.clone();
+        self.compact(strategy, seqno_threshold)
+    }
+
     /// Returns the next table's ID.
     fn get_next_table_id(&self) -> TableId;
 
@@ -208,6 +817,32 @@ pub trait AbstractTree {
     fn tree_config(&self) -> &Config;
 
     /// Returns the highest sequence number.
+    ///
+    /// After reopening a tree, this is recovered from the table metadata, without scanning the tree.
+    /// The sequence number counter of the tree's config is also advanced past it
+    /// (and past the highest journaled sequence number), so external sequence number allocation
+    /// can resume at [`SequenceNumberCounter::get`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+    ///
+    /// {
+    ///     let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    ///     tree.insert("a", "a", 5);
+    ///     tree.flush_active_memtable(0)?;
+    /// }
+    ///
+    /// let seqno = SequenceNumberCounter::default();
+    /// let tree = Config::new(&folder, seqno.clone()).open()?;
+    ///
+    /// assert_eq!(Some(5), tree.get_highest_seqno());
+    /// assert_eq!(6, seqno.get());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
     fn get_highest_seqno(&self) -> Option<SeqNo> {
         let memtable_seqno = self.get_highest_memtable_seqno();
         let table_seqno = self.get_highest_persisted_seqno();
@@ -228,11 +863,141 @@ pub trait AbstractTree {
     /// Returns the number of tables currently in the tree.
     fn table_count(&self) -> usize;
 
+    /// Returns `true` if any key with the given derived key may exist,
+    /// see [`Config::with_key_extractor`].
+    ///
+    /// Only probes in-memory structures and never touches the disk, but may return false positives.
+    /// False negatives are not possible, so `false` means there definitely is no such key.
+    ///
+    /// If no key extractor is configured, always returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, FixedPrefixExtractor};
+    /// use std::sync::Arc;
+    ///
+    /// let tree = Config::new(folder, Default::default())
+    ///     .with_key_extractor(Some(Arc::new(FixedPrefixExtractor::new(3))))
+    ///     .open()?;
+    ///
+    /// tree.insert("abc:1", "my_value", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// assert!(tree.derived_key_may_exist("abc")?);
+    /// # assert!(!tree.derived_key_may_exist("def")?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a filter is corrupted.
+    fn derived_key_may_exist<K: AsRef<[u8]>>(&self, derived_key: K) -> crate::Result<bool>;
+
+    /// Returns `true` if the key may exist.
+    ///
+    /// Only consults the memtables, and the key ranges and filters of tables,
+    /// so data blocks are never read. May return false positives,
+    /// but `false` means the key definitely does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    ///
+    /// tree.insert("a", "my_value", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// assert!(tree.key_may_exist("a")?);
+    /// # assert!(!tree.key_may_exist("b")?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn key_may_exist<K: AsRef<[u8]>>(&self, key: K) -> crate::Result<bool>;
+
     /// Returns the number of tables in `levels[idx]`.
     ///
-    /// Returns `None` if the level does not exist (if idx >= 7).
+    /// Returns `None` if the level does not exist (if idx >= level count).
     fn level_table_count(&self, idx: usize) -> Option<usize>;
 
+    /// Returns the shape of every level of the tree, starting at L0.
+    ///
+    /// The report is built from table metadata, so no data is read.
+    /// Data in memtables is not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "abc", 1);
+    /// tree.remove("a", 2);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let levels = tree.levels();
+    /// assert_eq!(7, levels.len());
+    /// assert_eq!(1, levels[0].table_count);
+    /// assert_eq!(1, levels[0].tombstone_count);
+    /// assert_eq!(Some((0, 2)), levels[0].seqnos);
+    /// assert!(levels[1].is_empty());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn levels(&self) -> Vec<crate::LevelInfo> {
+        self.current_version()
+            .iter_levels()
+            .map(crate::LevelInfo::from_level)
+            .collect()
+    }
+
+    /// Returns the metadata of every table of the tree, ordered by level.
+    ///
+    /// Can be used to map keys to table files, e.g. in operational tooling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("c", "abc", 1);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let tables = tree.tables();
+    /// assert_eq!(1, tables.len());
+    /// assert!(tables[0].key_range.contains_key(b"b"));
+    /// assert!(tables[0].path.exists());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn tables(&self) -> Vec<crate::TableInfo> {
+        self.current_version()
+            .iter_levels()
+            .enumerate()
+            .flat_map(|(idx, level)| {
+                level
+                    .iter()
+                    .flat_map(|run| run.iter())
+                    .map(|table| crate::TableInfo::new(table, idx))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
     /// Returns the number of disjoint runs in L0.
     ///
     /// Can be used to determine whether to write stall.
@@ -244,194 +1009,689 @@ pub trait AbstractTree {
     }
 
     /// Approximates the number of items in the tree.
+    ///
+    /// The estimate sums the item counts from table metadata and memtables, so no data
+    /// is read, and the cost only depends on the number of tables, unlike [`AbstractTree::len`].
+    ///
+    /// Every version and tombstone is counted, so the estimate is too high
+    /// for trees with many updates or deletes, and converges after compaction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SeqNo};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("a", "def", 1);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// assert_eq!(2, tree.approximate_len());
+    /// assert_eq!(1, tree.len(SeqNo::MAX, None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
     fn approximate_len(&self) -> usize;
 
     /// Returns the disk space usage.
     fn disk_space(&self) -> u64;
 
-    /// Returns the highest sequence number of the active memtable.
-    fn get_highest_memtable_seqno(&self) -> Option<SeqNo>;
+    /// Returns the disk space usage, broken down into levels, blob files and journal files.
+    ///
+    /// Unlike [`AbstractTree::disk_space`], this includes the journal,
+    /// so the file system is queried for the size of the journal files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let usage = tree.disk_usage()?;
+    /// assert_eq!(tree.disk_space(), usage.table_bytes());
+    /// assert!(usage.levels[0] > 0);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn disk_usage(&self) -> crate::Result<crate::DiskUsage>;
 
-    /// Returns the highest sequence number that is flushed to disk.
-    fn get_highest_persisted_seqno(&self) -> Option<SeqNo>;
+    /// Returns the memory usage of memtables, caches and pinned table metadata.
+    fn memory_usage(&self) -> crate::MemoryUsage;
 
-    /// Scans the entire tree, returning the number of items.
+    /// Approximates the on-disk size of a key range.
     ///
-    /// ###### Caution
+    /// The estimate is based on the tables' block indexes, so no data blocks are read.
+    /// Blocks that cross the range's boundaries are counted fully,
+    /// and data that is still in memtables is not included.
+    ///
+    /// Useful for choosing split points or enforcing quotas.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    ///
+    /// for x in 0..1_000u32 {
+    ///     tree.insert(x.to_be_bytes(), "a".repeat(100), x.into());
+    /// }
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let half = tree.approximate_size(..500u32.to_be_bytes())?;
+    /// assert!(half > 0);
+    /// assert!(half < tree.approximate_size::<&[u8], _>(..)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn approximate_size<K: AsRef<[u8]>, R: RangeBounds<K>>(&self, range: R) -> crate::Result<u64>;
+
+    /// Returns the highest sequence number of the active memtable.
+    fn get_highest_memtable_seqno(&self) -> Option<SeqNo>;
+
+    /// Returns the highest sequence number that is flushed to disk.
+    fn get_highest_persisted_seqno(&self) -> Option<SeqNo>;
+
+    /// Scans the entire tree, returning the number of items.
+    ///
+    /// ###### Caution
+    ///
+    /// This operation scans the entire tree: O(n) complexity!
+    ///
+    /// Never, under any circumstances, use .`len()` == 0 to check
+    /// if the tree is empty, use [`Tree::is_empty`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lsm_tree::Error as TreeError;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let folder = tempfile::tempdir()?;
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    ///
+    /// assert_eq!(tree.len(0, None)?, 0);
+    /// tree.insert("1", "abc", 0);
+    /// tree.insert("3", "abc", 1);
+    /// tree.insert("5", "abc", 2);
+    /// assert_eq!(tree.len(3, None)?, 3);
+    /// #
+    /// # Ok::<(), TreeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn len(&self, seqno: SeqNo, index: Option<Arc<Memtable>>) -> crate::Result<usize> {
+        let mut count = 0;
+
+        for item in self.iter(seqno, index) {
+            let _ = item.key()?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Returns `true` if the tree is empty.
+    ///
+    /// This operation has O(log N) complexity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// assert!(tree.is_empty(0, None)?);
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// assert!(!tree.is_empty(1, None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn is_empty(&self, seqno: SeqNo, index: Option<Arc<Memtable>>) -> crate::Result<bool> {
+        self.first_key_value(seqno, index).map(|x| x.is_none())
+    }
+
+    /// Returns the first key-value pair in the tree.
+    /// The key in this pair is the minimum key in the tree.
+    ///
+    /// The tree is iterated lazily, so only the start of each memtable and table is read,
+    /// plus whatever tombstones need to be skipped, which makes this cheap enough to use
+    /// the tree as a queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lsm_tree::Error as TreeError;
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// # let folder = tempfile::tempdir()?;
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    ///
+    /// tree.insert("1", "abc", 0);
+    /// tree.insert("3", "abc", 1);
+    /// tree.insert("5", "abc", 2);
+    ///
+    /// let (key, _) = tree.first_key_value(3, None)?.expect("item should exist");
+    /// assert_eq!(&*key, "1".as_bytes());
+    /// #
+    /// # Ok::<(), TreeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn first_key_value(
+        &self,
+        seqno: SeqNo,
+        index: Option<Arc<Memtable>>,
+    ) -> crate::Result<Option<KvPair>> {
+        self.iter(seqno, index)
+            .next()
+            .map(Guard::into_inner)
+            .transpose()
+    }
+
+    /// Returns the last key-value pair in the tree.
+    /// The key in this pair is the maximum key in the tree.
+    ///
+    /// The tree is iterated lazily, so only the end of each memtable and table is read,
+    /// plus whatever tombstones need to be skipped, which makes this cheap enough to use
+    /// the tree as a queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lsm_tree::Error as TreeError;
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// # let folder = tempfile::tempdir()?;
+    /// # let tree = Config::new(folder, Default::default()).open()?;
+    /// #
+    /// tree.insert("1", "abc", 0);
+    /// tree.insert("3", "abc", 1);
+    /// tree.insert("5", "abc", 2);
+    ///
+    /// let (key, _) = tree.last_key_value(3, None)?.expect("item should exist");
+    /// assert_eq!(&*key, "5".as_bytes());
+    /// #
+    /// # Ok::<(), TreeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn last_key_value(
+        &self,
+        seqno: SeqNo,
+        index: Option<Arc<Memtable>>,
+    ) -> crate::Result<Option<KvPair>> {
+        self.iter(seqno, index)
+            .next_back()
+            .map(Guard::into_inner)
+            .transpose()
+    }
+
+    /// Returns the size of a value if it exists.
+    ///
+    /// For a key-value separated tree, the size of a separated value is read from
+    /// the index tree, without reading the value from its blob file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "my_value", 0);
+    ///
+    /// let size = tree.size_of("a", 1)?.unwrap_or_default();
+    /// assert_eq!("my_value".len() as u32, size);
+    ///
+    /// let size = tree.size_of("b", 1)?.unwrap_or_default();
+    /// assert_eq!(0, size);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn size_of<K: AsRef<[u8]>>(&self, key: K, seqno: SeqNo) -> crate::Result<Option<u32>>;
+
+    /// Retrieves an item from the tree.
+    ///
+    /// Returns the newest version of the item with a sequence number lower than `seqno`.
+    /// Reading at an older `seqno` returns the version that was visible back then,
+    /// as long as it has not been garbage collected: compactions only keep all versions
+    /// that are visible at or above the `seqno_threshold` they are run with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "my_value", 0);
+    ///
+    /// let item = tree.get("a", 1)?;
+    /// assert_eq!(Some("my_value".as_bytes().into()), item);
+    ///
+    /// tree.insert("a", "my_new_value", 1);
+    /// assert_eq!(Some("my_new_value".as_bytes().into()), tree.get("a", 2)?);
+    /// assert_eq!(Some("my_value".as_bytes().into()), tree.get("a", 1)?);
+    /// assert_eq!(None, tree.get("a", 0)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn get<K: AsRef<[u8]>>(&self, key: K, seqno: SeqNo) -> crate::Result<Option<UserValue>>;
+
+    /// Retrieves multiple items from the tree, returning the values in the order of the keys.
+    ///
+    /// All keys are read from the same version of the tree.
+    /// For a blob tree, blobs are read sorted by their position in the value log,
+    /// and blobs that are close to each other are read using a single read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "my_value", 0);
+    /// tree.insert("c", "my_value2", 1);
+    ///
+    /// let items = tree.multi_get(["c", "b", "a"], 2)?;
+    /// assert_eq!(
+    ///     vec![
+    ///         Some("my_value2".as_bytes().into()),
+    ///         None,
+    ///         Some("my_value".as_bytes().into()),
+    ///     ],
+    ///     items,
+    /// );
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn multi_get<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(
+        &self,
+        keys: I,
+        seqno: SeqNo,
+    ) -> crate::Result<Vec<Option<UserValue>>>;
+
+    /// Returns the versions of a key that are still stored in the tree, newest first.
+    ///
+    /// Each version is returned as its sequence number, value type and value
+    /// (tombstones have empty values). Versions that were already dropped by
+    /// compactions, or that are deleted by a range tombstone, are not returned.
+    /// Expired values (see [`AbstractTree::insert_with_ttl`]) are returned as tombstones.
+    ///
+    /// At most `limit` versions are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, ValueType};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "old", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.insert("a", "new", 1);
+    /// tree.remove("a", 2);
+    ///
+    /// let versions = tree.get_versions("a", 2)?;
+    /// assert_eq!(2, versions.len());
+    /// assert_eq!((2, ValueType::Tombstone), (versions[0].0, versions[0].1));
+    /// assert_eq!((1, ValueType::Value), (versions[1].0, versions[1].1));
+    /// assert_eq!(b"new", &*versions[1].2);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn get_versions<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        limit: usize,
+    ) -> crate::Result<Vec<(SeqNo, ValueType, UserValue)>>;
+
+    /// Returns `true` if the tree contains the specified key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// assert!(!tree.contains_key("a", 0)?);
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// assert!(tree.contains_key("a", 1)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn contains_key<K: AsRef<[u8]>>(&self, key: K, seqno: SeqNo) -> crate::Result<bool> {
+        self.get(key, seqno).map(|x| x.is_some())
+    }
+
+    /// Inserts a key-value pair into the tree.
     ///
-    /// This operation scans the entire tree: O(n) complexity!
+    /// If the key already exists, the item will be overwritten.
     ///
-    /// Never, under any circumstances, use .`len()` == 0 to check
-    /// if the tree is empty, use [`Tree::is_empty`] instead.
+    /// Returns the added item's size and new size of the memtable.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use lsm_tree::Error as TreeError;
+    /// # let folder = tempfile::tempdir()?;
     /// use lsm_tree::{AbstractTree, Config, Tree};
     ///
-    /// let folder = tempfile::tempdir()?;
     /// let tree = Config::new(folder, Default::default()).open()?;
-    ///
-    /// assert_eq!(tree.len(0, None)?, 0);
-    /// tree.insert("1", "abc", 0);
-    /// tree.insert("3", "abc", 1);
-    /// tree.insert("5", "abc", 2);
-    /// assert_eq!(tree.len(3, None)?, 3);
+    /// tree.insert("a", "abc", 0);
     /// #
-    /// # Ok::<(), TreeError>(())
+    /// # Ok::<(), lsm_tree::Error>(())
     /// ```
     ///
-    /// # Errors
+    /// # Panics
     ///
-    /// Will return `Err` if an IO error occurs.
-    fn len(&self, seqno: SeqNo, index: Option<Arc<Memtable>>) -> crate::Result<usize> {
-        let mut count = 0;
-
-        for item in self.iter(seqno, index) {
-            let _ = item.key()?;
-            count += 1;
-        }
-
-        Ok(count)
+    /// Panics if the write could not be journaled, see [`AbstractTree::try_insert`].
+    fn insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+    ) -> (u64, u64) {
+        #[expect(clippy::expect_used, reason = "acknowledging a lost write is worse")]
+        self.try_insert(key, value, seqno)
+            .expect("write should be journaled")
     }
 
-    /// Returns `true` if the tree is empty.
+    /// Inserts a key-value pair into the tree, see [`AbstractTree::insert`].
     ///
-    /// This operation has O(log N) complexity.
+    /// # Errors
+    ///
+    /// Will return `Err` if the write could not be journaled, in which case it is not applied.
+    fn try_insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+    ) -> crate::Result<(u64, u64)>;
+
+    /// Inserts a key-value pair into the tree that expires after the given TTL.
+    ///
+    /// Once expired, the item is treated as deleted by reads,
+    /// and physically dropped by compactions.
+    /// The expiry timestamp is taken from the tree's clock (see [`crate::Config::with_clock`]).
+    ///
+    /// If the key already exists, the item will be overwritten.
+    ///
+    /// Returns the added item's size and new size of the memtable.
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
     /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// use std::time::Duration;
     ///
     /// let tree = Config::new(folder, Default::default()).open()?;
-    /// assert!(tree.is_empty(0, None)?);
+    /// tree.insert_with_ttl("session", "abc", 0, Duration::from_secs(60));
+    /// assert!(tree.contains_key("session", 1)?);
     ///
-    /// tree.insert("a", "abc", 0);
-    /// assert!(!tree.is_empty(1, None)?);
+    /// tree.insert_with_ttl("expired", "abc", 1, Duration::ZERO);
+    /// assert!(!tree.contains_key("expired", 2)?);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
     ///
-    /// # Errors
+    /// # Panics
     ///
-    /// Will return `Err` if an IO error occurs.
-    fn is_empty(&self, seqno: SeqNo, index: Option<Arc<Memtable>>) -> crate::Result<bool> {
-        self.first_key_value(seqno, index).map(|x| x.is_none())
+    /// Panics if the write could not be journaled, see [`AbstractTree::try_insert_with_ttl`].
+    fn insert_with_ttl<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+        ttl: std::time::Duration,
+    ) -> (u64, u64) {
+        #[expect(clippy::expect_used, reason = "acknowledging a lost write is worse")]
+        self.try_insert_with_ttl(key, value, seqno, ttl)
+            .expect("write should be journaled")
     }
 
-    /// Returns the first key-value pair in the tree.
-    /// The key in this pair is the minimum key in the tree.
+    /// Inserts a key-value pair that expires after the given TTL, see [`AbstractTree::insert_with_ttl`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the write could not be journaled, in which case it is not applied.
+    fn try_insert_with_ttl<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+        ttl: std::time::Duration,
+    ) -> crate::Result<(u64, u64)>;
+
+    /// Inserts a key-value pair into the tree, using the next sequence number
+    /// of the tree's sequence number generator (see [`crate::Config::new`]).
+    ///
+    /// The generator continues after the highest persisted sequence number when
+    /// the tree is reopened, so this is an alternative to managing sequence numbers manually.
+    /// Explicit sequence numbers should not be mixed with this, unless they are also taken
+    /// from the same generator.
+    ///
+    /// Returns the sequence number of the write.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use lsm_tree::Error as TreeError;
-    /// # use lsm_tree::{AbstractTree, Config, Tree};
-    /// #
     /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SeqNo};
+    ///
     /// let tree = Config::new(folder, Default::default()).open()?;
     ///
-    /// tree.insert("1", "abc", 0);
-    /// tree.insert("3", "abc", 1);
-    /// tree.insert("5", "abc", 2);
+    /// let seqno = tree.insert_auto("a", "abc");
+    /// assert!(tree.contains_key("a", seqno + 1)?);
+    /// assert!(!tree.contains_key("a", seqno)?);
     ///
-    /// let (key, _) = tree.first_key_value(3, None)?.expect("item should exist");
-    /// assert_eq!(&*key, "1".as_bytes());
+    /// tree.remove_auto("a");
+    /// assert!(!tree.contains_key("a", SeqNo::MAX)?);
     /// #
-    /// # Ok::<(), TreeError>(())
+    /// # Ok::<(), lsm_tree::Error>(())
     /// ```
-    ///
-    /// # Errors
-    ///
-    /// Will return `Err` if an IO error occurs.
-    fn first_key_value(
-        &self,
-        seqno: SeqNo,
-        index: Option<Arc<Memtable>>,
-    ) -> crate::Result<Option<KvPair>> {
-        self.iter(seqno, index)
-            .next()
-            .map(Guard::into_inner)
-            .transpose()
+    fn insert_auto<K: Into<UserKey>, V: Into<UserValue>>(&self, key: K, value: V) -> SeqNo {
+        let seqno = self.tree_config().seqno.next();
+        self.insert(key, value, seqno);
+        seqno
     }
 
-    /// Returns the last key-value pair in the tree.
-    /// The key in this pair is the maximum key in the tree.
+    /// Adds a merge operand for a key.
+    ///
+    /// Reads fold all merge operands of the key into its newest value, using the
+    /// [`MergeOperator`](crate::MergeOperator) of the tree, see [`crate::Config::with_merge_operator`].
+    ///
+    /// Returns the added item's size and new size of the memtable.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use lsm_tree::Error as TreeError;
-    /// # use lsm_tree::{AbstractTree, Config, Tree};
-    /// #
     /// # let folder = tempfile::tempdir()?;
-    /// # let tree = Config::new(folder, Default::default()).open()?;
+    /// # use lsm_tree::{AbstractTree, Config, MergeOperator, UserValue};
+    /// # use std::sync::Arc;
     /// #
-    /// tree.insert("1", "abc", 0);
-    /// tree.insert("3", "abc", 1);
-    /// tree.insert("5", "abc", 2);
-    ///
-    /// let (key, _) = tree.last_key_value(3, None)?.expect("item should exist");
-    /// assert_eq!(&*key, "5".as_bytes());
+    /// #[derive(Debug)]
+    /// struct Append;
+    ///
+    /// impl MergeOperator for Append {
+    ///     fn merge(&self, _key: &[u8], older: &[u8], newer: &[u8]) -> UserValue {
+    ///         [older, newer].concat().into()
+    ///     }
+    /// }
+    ///
+    /// # let tree = Config::new(folder, Default::default())
+    /// #     .with_merge_operator(Some(Arc::new(Append)))
+    /// #     .open()?;
+    /// tree.add_merge("a", "abc", 0);
+    /// tree.add_merge("a", "def", 1);
+    ///
+    /// let item = tree.get("a", 2)?.expect("should have item");
+    /// assert_eq!("abcdef".as_bytes(), &*item);
     /// #
-    /// # Ok::<(), TreeError>(())
+    /// # Ok::<(), lsm_tree::Error>(())
     /// ```
     ///
+    /// # Panics
+    ///
+    /// Panics if the write could not be journaled, see [`AbstractTree::try_add_merge`].
+    fn add_merge<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        operand: V,
+        seqno: SeqNo,
+    ) -> (u64, u64) {
+        #[expect(clippy::expect_used, reason = "acknowledging a lost write is worse")]
+        self.try_add_merge(key, operand, seqno)
+            .expect("write should be journaled")
+    }
+
+    /// Adds a merge operand for a key, see [`AbstractTree::add_merge`].
+    ///
     /// # Errors
     ///
-    /// Will return `Err` if an IO error occurs.
-    fn last_key_value(
+    /// Will return `Err` if the write could not be journaled, in which case it is not applied.
+    fn try_add_merge<K: Into<UserKey>, V: Into<UserValue>>(
         &self,
+        key: K,
+        operand: V,
         seqno: SeqNo,
-        index: Option<Arc<Memtable>>,
-    ) -> crate::Result<Option<KvPair>> {
-        self.iter(seqno, index)
-            .next_back()
-            .map(Guard::into_inner)
-            .transpose()
+    ) -> crate::Result<(u64, u64)>;
+
+    /// Adds a merge operand for a key, using the next sequence number
+    /// of the tree's sequence number generator, see [`AbstractTree::insert_auto`].
+    ///
+    /// Returns the sequence number of the write.
+    fn add_merge_auto<K: Into<UserKey>, V: Into<UserValue>>(&self, key: K, operand: V) -> SeqNo {
+        let seqno = self.tree_config().seqno.next();
+        self.add_merge(key, operand, seqno);
+        seqno
     }
 
-    /// Returns the size of a value if it exists.
+    /// Removes an item from the tree.
+    ///
+    /// Returns the added item's size and new size of the memtable.
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// # let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "abc", 0);
     ///
-    /// let tree = Config::new(folder, Default::default()).open()?;
-    /// tree.insert("a", "my_value", 0);
+    /// let item = tree.get("a", 1)?.expect("should have item");
+    /// assert_eq!("abc".as_bytes(), &*item);
     ///
-    /// let size = tree.size_of("a", 1)?.unwrap_or_default();
-    /// assert_eq!("my_value".len() as u32, size);
+    /// tree.remove("a", 1);
     ///
-    /// let size = tree.size_of("b", 1)?.unwrap_or_default();
-    /// assert_eq!(0, size);
+    /// let item = tree.get("a", 2)?;
+    /// assert_eq!(None, item);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
     ///
+    /// # Panics
+    ///
+    /// Panics if the write could not be journaled, see [`AbstractTree::try_remove`].
+    fn remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u64, u64) {
+        #[expect(clippy::expect_used, reason = "acknowledging a lost write is worse")]
+        self.try_remove(key, seqno)
+            .expect("write should be journaled")
+    }
+
+    /// Removes an item from the tree, see [`AbstractTree::remove`].
+    ///
     /// # Errors
     ///
-    /// Will return `Err` if an IO error occurs.
-    fn size_of<K: AsRef<[u8]>>(&self, key: K, seqno: SeqNo) -> crate::Result<Option<u32>>;
+    /// Will return `Err` if the write could not be journaled, in which case it is not applied.
+    fn try_remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u64, u64)>;
 
-    /// Retrieves an item from the tree.
+    /// Removes an item from the tree, using the next sequence number
+    /// of the tree's sequence number generator, see [`AbstractTree::insert_auto`].
+    ///
+    /// Returns the sequence number of the write.
+    fn remove_auto<K: Into<UserKey>>(&self, key: K) -> SeqNo {
+        let seqno = self.tree_config().seqno.next();
+        self.remove(key, seqno);
+        seqno
+    }
+
+    /// Removes an item from the tree, if its current value matches `expected`.
+    ///
+    /// The newest version of the key is compared, and only if its value equals `expected`,
+    /// a tombstone is written with `seqno`.
+    ///
+    /// Conditional writes are serialized against all other writes to the tree
+    /// (including unconditional writes, e.g. [`AbstractTree::insert`]), so no write
+    /// can land between their read and their write.
+    ///
+    /// Returns `true` if the item was removed.
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// # let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("a", "abc", 0);
     ///
-    /// let tree = Config::new(folder, Default::default()).open()?;
-    /// tree.insert("a", "my_value", 0);
+    /// assert!(!tree.remove_if("a", "def", 1)?);
+    /// assert!(tree.contains_key("a", 2)?);
     ///
-    /// let item = tree.get("a", 1)?;
-    /// assert_eq!(Some("my_value".as_bytes().into()), item);
+    /// assert!(tree.remove_if("a", "abc", 2)?);
+    /// assert!(!tree.contains_key("a", 3)?);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
@@ -439,21 +1699,43 @@ pub trait AbstractTree {
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
-    fn get<K: AsRef<[u8]>>(&self, key: K, seqno: SeqNo) -> crate::Result<Option<UserValue>>;
+    fn remove_if<K: Into<UserKey>, V: AsRef<[u8]>>(
+        &self,
+        key: K,
+        expected: V,
+        seqno: SeqNo,
+    ) -> crate::Result<bool>;
 
-    /// Returns `true` if the tree contains the specified key.
+    /// Replaces the value of an item, if its current value matches `expected`.
+    ///
+    /// `expected = None` means the key must not exist, and `new = None` removes the item,
+    /// so this can be used to atomically create, update or delete a single key.
+    ///
+    /// The newest version of the key is compared, and only if it matches, `new` is written.
+    ///
+    /// Conditional writes are serialized, see [`AbstractTree::remove_if`]. The write uses
+    /// the next sequence number of the tree's sequence number generator, which is taken while
+    /// holding the lock, so concurrent conditional writes are ordered the same way as their
+    /// sequence numbers, see [`AbstractTree::insert_auto`].
+    ///
+    /// Returns `true` if the write was applied.
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// # use lsm_tree::{AbstractTree, Config, Tree};
-    /// #
+    /// use lsm_tree::{AbstractTree, Config, SeqNo};
+    ///
     /// let tree = Config::new(folder, Default::default()).open()?;
-    /// assert!(!tree.contains_key("a", 0)?);
     ///
-    /// tree.insert("a", "abc", 0);
-    /// assert!(tree.contains_key("a", 1)?);
+    /// assert!(tree.compare_and_swap("a", None::<&str>, Some("abc"))?);
+    /// assert!(!tree.compare_and_swap("a", None::<&str>, Some("def"))?);
+    ///
+    /// assert!(tree.compare_and_swap("a", Some("abc"), Some("def"))?);
+    /// assert_eq!(Some("def".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+    ///
+    /// assert!(tree.compare_and_swap("a", Some("def"), None::<&str>)?);
+    /// assert!(!tree.contains_key("a", SeqNo::MAX)?);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
@@ -461,24 +1743,38 @@ pub trait AbstractTree {
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
-    fn contains_key<K: AsRef<[u8]>>(&self, key: K, seqno: SeqNo) -> crate::Result<bool> {
-        self.get(key, seqno).map(|x| x.is_some())
-    }
+    fn compare_and_swap<K: Into<UserKey>, E: AsRef<[u8]>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        expected: Option<E>,
+        new: Option<V>,
+    ) -> crate::Result<bool>;
 
-    /// Inserts a key-value pair into the tree.
+    /// Atomically updates the value of an item using a function, and returns the previous value.
     ///
-    /// If the key already exists, the item will be overwritten.
+    /// `f` is called with the newest value of the key (or `None` if it does not exist),
+    /// and returns the new value, or `None` to remove the item.
     ///
-    /// Returns the added item's size and new size of the memtable.
+    /// Like [`AbstractTree::compare_and_swap`], the read and write happen while holding
+    /// the write lock of the tree exclusively, so `f` is called exactly once, and no update
+    /// is lost to a concurrent write.
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// use lsm_tree::{AbstractTree, Config, SeqNo, UserValue};
     ///
     /// let tree = Config::new(folder, Default::default()).open()?;
-    /// tree.insert("a", "abc", 0);
+    ///
+    /// let increment = |value: Option<&UserValue>| {
+    ///     let count = value.map_or(0, |v| u64::from_be_bytes((**v).try_into().unwrap_or_default()));
+    ///     Some((count + 1).to_be_bytes().into())
+    /// };
+    ///
+    /// assert!(tree.fetch_update("counter", increment)?.is_none());
+    /// assert!(tree.fetch_update("counter", increment)?.is_some());
+    /// assert_eq!(Some(2u64.to_be_bytes().into()), tree.get("counter", SeqNo::MAX)?);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
@@ -486,16 +1782,22 @@ pub trait AbstractTree {
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
-    fn insert<K: Into<UserKey>, V: Into<UserValue>>(
+    fn fetch_update<K: Into<UserKey>, F: FnOnce(Option<&UserValue>) -> Option<UserValue>>(
         &self,
         key: K,
-        value: V,
-        seqno: SeqNo,
-    ) -> (u64, u64);
+        f: F,
+    ) -> crate::Result<Option<UserValue>>;
 
-    /// Removes an item from the tree.
+    /// Removes all items in a key range from the tree.
     ///
-    /// Returns the added item's size and new size of the memtable.
+    /// Instead of writing a tombstone per key, a single range tombstone is written,
+    /// which shadows all versions in the range that are older than `seqno`.
+    /// Compactions drop the shadowed versions, and eventually the range tombstone itself.
+    ///
+    /// Range tombstones are written to the journal, and show up in the
+    /// change feed of [`Tree::cdc_reader`](crate::Tree::cdc_reader) as [`crate::ChangeOp::RemoveRange`].
+    ///
+    /// Returns the added size and new size of the memtable.
     ///
     /// # Examples
     ///
@@ -504,30 +1806,64 @@ pub trait AbstractTree {
     /// # use lsm_tree::{AbstractTree, Config, Tree};
     /// #
     /// # let tree = Config::new(folder, Default::default()).open()?;
-    /// tree.insert("a", "abc", 0);
+    /// tree.insert("tenant1:a", "abc", 0);
+    /// tree.insert("tenant1:b", "abc", 1);
+    /// tree.insert("tenant2:a", "abc", 2);
     ///
-    /// let item = tree.get("a", 1)?.expect("should have item");
-    /// assert_eq!("abc".as_bytes(), &*item);
+    /// tree.remove_range("tenant1:".."tenant2:", 3);
     ///
-    /// tree.remove("a", 1);
+    /// assert_eq!(1, tree.len(4, None)?);
+    /// assert!(tree.contains_key("tenant2:a", 4)?);
     ///
-    /// let item = tree.get("a", 2)?;
-    /// assert_eq!(None, item);
+    /// // NOTE: Older snapshots are not affected
+    /// assert_eq!(3, tree.len(3, None)?);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
     ///
+    /// # Panics
+    ///
+    /// Panics if the write could not be journaled, see [`AbstractTree::try_remove_range`].
+    fn remove_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+    ) -> (u64, u64) {
+        #[expect(clippy::expect_used, reason = "acknowledging a lost write is worse")]
+        self.try_remove_range(range, seqno)
+            .expect("write should be journaled")
+    }
+
+    /// Removes all items in a key range from the tree, see [`AbstractTree::remove_range`].
+    ///
     /// # Errors
     ///
-    /// Will return `Err` if an IO error occurs.
-    fn remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u64, u64);
+    /// Will return `Err` if the write could not be journaled, in which case it is not applied.
+    fn try_remove_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+    ) -> crate::Result<(u64, u64)>;
 
-    /// Removes an item from the tree.
+    /// Removes all items in a key range from the tree, using the next sequence number
+    /// of the tree's sequence number generator, see [`AbstractTree::insert_auto`].
+    ///
+    /// Returns the sequence number of the write.
+    fn remove_range_auto<K: AsRef<[u8]>, R: RangeBounds<K>>(&self, range: R) -> SeqNo {
+        let seqno = self.tree_config().seqno.next();
+        self.remove_range(range, seqno);
+        seqno
+    }
+
+    /// Removes an item from the tree, using a weak tombstone (single delete).
     ///
     /// The tombstone marker of this delete operation will vanish when it
     /// collides with its corresponding insertion.
     /// This may cause older versions of the value to be resurrected, so it should
-    /// only be used and preferred in scenarios where a key is only ever written once.
+    /// only be used and preferred in scenarios where a key is only ever written once,
+    /// e.g. queues, where each key is inserted once and deleted once.
+    /// Once the weak tombstone and the value it deletes meet in a flush or compaction,
+    /// both are dropped, so tombstones do not accumulate.
     ///
     /// Returns the added item's size and new size of the memtable.
     ///
@@ -551,9 +1887,57 @@ pub trait AbstractTree {
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
     ///
+    /// # Panics
+    ///
+    /// Panics if the write could not be journaled, see [`AbstractTree::try_remove_weak`].
+    fn remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u64, u64) {
+        #[expect(clippy::expect_used, reason = "acknowledging a lost write is worse")]
+        self.try_remove_weak(key, seqno)
+            .expect("write should be journaled")
+    }
+
+    /// Removes an item from the tree using a weak tombstone, see [`AbstractTree::remove_weak`].
+    ///
     /// # Errors
     ///
-    /// Will return `Err` if an IO error occurs.
-    #[doc(hidden)]
-    fn remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u64, u64);
+    /// Will return `Err` if the write could not be journaled, in which case it is not applied.
+    fn try_remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u64, u64)>;
+
+    /// Removes an item from the tree using a weak tombstone, using the next sequence number
+    /// of the tree's sequence number generator, see [`AbstractTree::insert_auto`].
+    ///
+    /// Returns the sequence number of the write.
+    fn remove_weak_auto<K: Into<UserKey>>(&self, key: K) -> SeqNo {
+        let seqno = self.tree_config().seqno.next();
+        self.remove_weak(key, seqno);
+        seqno
+    }
+
+    /// Creates a new write batch, see [`crate::Batch`].
+    ///
+    /// The batch's operations are committed atomically, under a single sequence number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// tree.insert("from", "10", 0);
+    ///
+    /// let mut batch = tree.batch();
+    /// batch.remove("from");
+    /// batch.insert("to", "10");
+    /// batch.commit(1)?;
+    ///
+    /// assert!(tree.contains_key("from", 1)?);
+    /// assert!(!tree.contains_key("to", 1)?);
+    ///
+    /// assert!(!tree.contains_key("from", 2)?);
+    /// assert!(tree.contains_key("to", 2)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn batch(&self) -> crate::Batch;
 }
@@ -2,8 +2,9 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{BlobTree, Tree};
+use crate::{path::absolute_path, AbstractTree, BlobTree, Tree};
 use enum_dispatch::enum_dispatch;
+use std::{path::Path, sync::Arc};
 
 /// May be a standard [`Tree`] or a [`BlobTree`]
 #[derive(Clone)]
@@ -15,3 +16,87 @@ pub enum AnyTree {
     /// Key-value separated LSM-tree, see [`BlobTree`]
     Blob(BlobTree),
 }
+
+impl AnyTree {
+    /// Moves the tree to another folder, returning the tree reopened at its new location.
+    ///
+    /// The active memtable is flushed first, then the tree folder is renamed.
+    /// If the new folder is on a different filesystem, the tree is copied
+    /// and the old folder is removed afterwards.
+    ///
+    /// All settings of the tree are kept. A [`Config::temporary`](crate::Config::temporary)
+    /// tree becomes a persistent tree, as it is no longer stored in its temporary folder.
+    ///
+    /// ```
+    /// # use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+    /// #
+    /// # let folder = tempfile::tempdir()?;
+    /// let tree = Config::new(folder.path().join("old"), SequenceNumberCounter::default()).open()?;
+    /// tree.insert("a", "abc", 0);
+    ///
+    /// let tree = tree.relocate(folder.path().join("new"))?;
+    /// assert!(tree.contains_key("a", SeqNo::MAX)?);
+    /// assert!(!folder.path().join("old").try_exists()?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, e.g. if the new folder already exists.
+    ///
+    /// Will return [`crate::Error::AlreadyLocked`] if there are other handles to the tree
+    /// (e.g. clones or open iterators), as they would keep using the old folder,
+    /// and [`crate::Error::InvalidConfig`] if the tree is ephemeral.
+    pub fn relocate<P: AsRef<Path>>(self, path: P) -> crate::Result<Self> {
+        let index = match &self {
+            Self::Standard(tree) => tree,
+            Self::Blob(tree) => &tree.index,
+        };
+
+        if index.config.ephemeral {
+            return Err(crate::Error::InvalidConfig(
+                "ephemeral trees cannot be relocated",
+            ));
+        }
+
+        if Arc::strong_count(&index.0) > 1 {
+            return Err(crate::Error::AlreadyLocked);
+        }
+
+        let mut config = index.config.clone();
+        let old_path = config.path.clone();
+        config.path = absolute_path(path.as_ref());
+
+        let fs = config.fs.clone();
+
+        if fs.exists(&config.path)? {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", config.path.display()),
+            )
+            .into());
+        }
+
+        self.flush_active_memtable(0)?;
+
+        log::info!(
+            "Relocating LSM-tree from {} to {}",
+            old_path.display(),
+            config.path.display(),
+        );
+
+        // NOTE: Close the tree, so its lock is released and no file is written anymore
+        drop(self);
+
+        if let Some(parent) = config.path.parent() {
+            fs.create_dir_all(parent)?;
+        }
+        crate::fs::move_dir(&*fs, &old_path, &config.path)?;
+
+        config.temporary = false;
+        config.temporary_folder = None;
+
+        config.open_existing()
+    }
+}
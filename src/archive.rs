@@ -0,0 +1,171 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{Checksum, FileHandle};
+use byteorder::{WriteBytesExt, LE};
+use std::io::{BufWriter, Seek, Write};
+use xxhash_rust::xxh3::Xxh3Default;
+
+const TOC_MAGIC: &[u8] = b"TOC!";
+const TRAILER_MAGIC: &[u8] = b"SFA!";
+
+/// A section of an archive file, see [`sfa::TocEntry`]
+struct Section {
+    name: Vec<u8>,
+    pos: u64,
+    len: u64,
+}
+
+/// Writes an archive file, which can be read using [`sfa::Reader`]
+///
+/// [`sfa::Writer`] only writes into [`std::fs::File`]s, this writer writes into any
+/// [`FileHandle`], so archives can be stored in every [`crate::Filesystem`].
+pub struct Writer {
+    inner: BufWriter<Box<dyn FileHandle>>,
+    hasher: Xxh3Default,
+    last_section_pos: u64,
+    section_name: Vec<u8>,
+    toc: Vec<Section>,
+}
+
+impl Writer {
+    /// Creates a new writer with the given I/O writer.
+    #[must_use]
+    pub fn from_writer(writer: BufWriter<Box<dyn FileHandle>>) -> Self {
+        Self {
+            inner: writer,
+            hasher: Xxh3Default::new(),
+            last_section_pos: 0,
+            section_name: Vec::new(),
+            toc: Vec::new(),
+        }
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Bytes written through it are not included in the checksum of the file.
+    pub fn get_mut(&mut self) -> impl Write + Seek + '_ {
+        &mut self.inner
+    }
+
+    /// Starts the next named section.
+    pub fn start(&mut self, name: impl Into<Vec<u8>>) -> std::io::Result<()> {
+        self.append_toc_entry()?;
+        self.section_name = name.into();
+        Ok(())
+    }
+
+    fn append_toc_entry(&mut self) -> std::io::Result<()> {
+        let file_pos = self.inner.stream_position()?;
+
+        if file_pos > 0 {
+            self.toc.push(Section {
+                name: std::mem::take(&mut self.section_name),
+                pos: self.last_section_pos,
+                len: file_pos - self.last_section_pos,
+            });
+        }
+
+        self.last_section_pos = file_pos;
+
+        Ok(())
+    }
+
+    /// Finishes the file, writing the table of contents and trailer, and fsyncs it.
+    ///
+    /// Returns a full-file checksum.
+    pub fn finish(mut self) -> crate::Result<Checksum> {
+        self.append_toc_entry()?;
+
+        let toc_pos = self.inner.stream_position()?;
+
+        let toc_checksum = {
+            let mut toc = vec![];
+            toc.write_all(TOC_MAGIC)?;
+
+            #[expect(
+                clippy::expect_used,
+                reason = "table of contents should not have 4 billion or more entries"
+            )]
+            toc.write_u32::<LE>(u32::try_from(self.toc.len()).expect("should fit into u32"))?;
+
+            for section in &self.toc {
+                toc.write_u64::<LE>(section.pos)?;
+                toc.write_u64::<LE>(section.len)?;
+
+                #[expect(clippy::expect_used, reason = "section names are short constants")]
+                toc.write_u16::<LE>(
+                    u16::try_from(section.name.len()).expect("section name should fit into u16"),
+                )?;
+                toc.write_all(&section.name)?;
+            }
+
+            self.write_all(&toc)?;
+
+            xxhash_rust::xxh3::xxh3_128(&toc)
+        };
+
+        let toc_len = self.inner.stream_position()? - toc_pos;
+
+        self.write_all(TRAILER_MAGIC)?;
+        self.write_u8(0x1)?; // Version
+        self.write_u8(0x0)?; // Checksum type, xxh3 = 0x0
+        self.write_u128::<LE>(toc_checksum)?;
+        self.write_u64::<LE>(toc_pos)?;
+        self.write_u64::<LE>(toc_len)?;
+
+        self.flush()?;
+        self.inner.get_ref().sync_all()?;
+
+        Ok(Checksum::from_raw(self.hasher.digest128()))
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let bytes_written = self.inner.write(buf)?;
+
+        if let Some(written) = buf.get(..bytes_written) {
+            self.hasher.update(written);
+        }
+
+        Ok(bytes_written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fs::MemFilesystem, Filesystem};
+    use std::path::Path;
+    use test_log::test;
+
+    #[test]
+    fn archive_roundtrip() -> crate::Result<()> {
+        let fs = MemFilesystem::default();
+        let path = Path::new("archive");
+
+        let mut writer = Writer::from_writer(BufWriter::new(fs.create_new(path)?));
+        writer.start("a")?;
+        writer.write_all(b"hello")?;
+        writer.start("b")?;
+        writer.write_all(b"world!")?;
+        writer.finish()?;
+
+        let reader = crate::fs::read_archive(&fs, path)?;
+        let toc = reader.toc();
+
+        let a = toc.section(b"a").expect("should exist");
+        assert_eq!((0, 5), (a.pos(), a.len()));
+
+        let b = toc.section(b"b").expect("should exist");
+        assert_eq!((5, 6), (b.pos(), b.len()));
+
+        Ok(())
+    }
+}
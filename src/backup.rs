@@ -0,0 +1,504 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Incremental backups
+//!
+//! Tables and blob files are immutable, so a [`BackupEngine`] only copies files
+//! that no previous backup contains. All backups share the copied files:
+//!
+//! ```text
+//! <backup folder>/
+//!     tables/<table ID>_<checksum>
+//!     blobs/<blob file ID>_<checksum>
+//!     backups/<backup ID>/
+//!         manifest
+//!         current
+//!         v<version ID>
+//! ```
+//!
+//! A backup records the manifest and the version of the tree, so [`BackupEngine::restore`]
+//! can rebuild a tree folder that can be opened like any other tree.
+//!
+//! ```
+//! # use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+//! # use lsm_tree::backup::BackupEngine;
+//! #
+//! # let folder = tempfile::tempdir()?;
+//! # let backup_folder = tempfile::tempdir()?;
+//! # let restore_folder = tempfile::tempdir()?;
+//! let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+//! let backups = BackupEngine::open(&backup_folder)?;
+//!
+//! tree.insert("a", "abc", 0);
+//! backups.create_backup(&tree)?;
+//!
+//! // NOTE: Only copies the table that was written since the last backup
+//! tree.insert("b", "def", 1);
+//! backups.create_backup(&tree)?;
+//! assert_eq!(2, backups.list_backups()?.len());
+//!
+//! let path = restore_folder.path().join("tree");
+//! backups.restore(&path)?;
+//!
+//! let restored = Config::new(&path, SequenceNumberCounter::default()).open()?;
+//! assert_eq!(2, restored.len(SeqNo::MAX, None)?);
+//! #
+//! # Ok::<(), lsm_tree::Error>(())
+//! ```
+
+use crate::{
+    file::{sharded_path, BLOBS_FOLDER, MANIFEST_FILE, TABLES_FOLDER},
+    fs::{link_or_copy, read_archive, remove_dir_all},
+    manifest::Manifest,
+    version::{persist_version, recovery::recover},
+    AbstractTree, Checksum, Filesystem, StdFilesystem, TreeType,
+};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Folder that contains the metadata of every backup
+const BACKUPS_FOLDER: &str = "backups";
+
+/// Backup ID
+///
+/// Backup IDs are monotonically increasing integers.
+pub type BackupId = u64;
+
+/// Information about a backup
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BackupInfo {
+    /// Backup ID
+    pub id: BackupId,
+
+    /// Amount of tables in the backup
+    pub table_count: usize,
+
+    /// Amount of blob files in the backup
+    pub blob_file_count: usize,
+
+    /// Size of all tables and blob files of the backup, including files that are shared with other backups
+    pub size: u64,
+}
+
+/// Creates and restores incremental backups of trees, see the [module docs](self)
+pub struct BackupEngine {
+    fs: Arc<dyn Filesystem>,
+    path: PathBuf,
+}
+
+/// Returns the name of a backed up table or blob file.
+///
+/// The checksum is part of the name, so files of different trees do not collide.
+fn file_name(id: u64, checksum: Checksum) -> String {
+    format!("{id}_{:032x}", checksum.into_u128())
+}
+
+fn file_len(fs: &dyn Filesystem, path: &Path) -> crate::Result<u64> {
+    Ok(fs.open(path)?.len()?)
+}
+
+/// Copies a file into the backup folder, so it either exists completely, or not at all.
+fn copy_atomic(
+    from_fs: &dyn Filesystem,
+    from: &Path,
+    to_fs: &dyn Filesystem,
+    to: &Path,
+) -> crate::Result<u64> {
+    let tmp_path = to.with_extension("tmp");
+
+    if to_fs.exists(&tmp_path)? {
+        to_fs.remove_file(&tmp_path)?;
+    }
+
+    let mut reader = from_fs.open(from)?;
+    let mut writer = to_fs.create_new(&tmp_path)?;
+    let size = std::io::copy(&mut reader, &mut writer)?;
+    writer.sync_all()?;
+    drop(writer);
+
+    to_fs.rename(&tmp_path, to)?;
+
+    Ok(size)
+}
+
+impl BackupEngine {
+    /// Opens the backup folder, creating it if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn open<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        Self::open_with_filesystem(Arc::new(StdFilesystem), path)
+    }
+
+    /// Opens the backup folder using the given storage backend, creating it if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn open_with_filesystem<P: AsRef<Path>>(
+        fs: Arc<dyn Filesystem>,
+        path: P,
+    ) -> crate::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        for folder in [TABLES_FOLDER, BLOBS_FOLDER, BACKUPS_FOLDER] {
+            fs.create_dir_all(&path.join(folder))?;
+        }
+        fs.sync_directory(&path)?;
+
+        Ok(Self { fs, path })
+    }
+
+    fn backup_path(&self, id: BackupId) -> PathBuf {
+        self.path.join(BACKUPS_FOLDER).join(id.to_string())
+    }
+
+    /// Returns the IDs of all backups, in ascending order.
+    fn backup_ids(&self) -> crate::Result<Vec<BackupId>> {
+        let mut ids = self
+            .fs
+            .read_dir(&self.path.join(BACKUPS_FOLDER))?
+            .into_iter()
+            .filter_map(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.parse::<BackupId>().ok())
+            })
+            .collect::<Vec<_>>();
+
+        ids.sort_unstable();
+
+        Ok(ids)
+    }
+
+    /// Returns the names of all tables and blob files of a backup.
+    fn backup_files(&self, id: BackupId) -> crate::Result<(Vec<String>, Vec<String>)> {
+        let recovery = recover(&*self.fs, &self.backup_path(id))?;
+
+        let tables = recovery
+            .table_ids
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|&(id, checksum)| file_name(id, checksum))
+            .collect();
+
+        let blob_files = recovery
+            .blob_file_ids
+            .iter()
+            .map(|&(id, checksum)| file_name(id, checksum))
+            .collect();
+
+        Ok((tables, blob_files))
+    }
+
+    /// Backs up the tree.
+    ///
+    /// The active memtable is flushed first, then all tables and blob files that are
+    /// not part of a previous backup are copied. Writes can continue while the backup
+    /// is created, but flushes and compactions wait until it is done.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs,
+    /// or [`crate::Error::InvalidConfig`] if the tree is ephemeral.
+    pub fn create_backup(&self, tree: &impl AbstractTree) -> crate::Result<BackupInfo> {
+        let config = tree.tree_config();
+
+        if config.ephemeral {
+            return Err(crate::Error::InvalidConfig(
+                "ephemeral trees can not be backed up",
+            ));
+        }
+
+        // NOTE: Flush first, so the backup contains all writes made before it
+        tree.flush_active_memtable(0)?;
+
+        // IMPORTANT: Block flushes and compactions, so no file of the version
+        // is deleted while it is copied
+        let _guard = tree.freeze_files();
+        let version = tree.current_version();
+
+        let id = self.backup_ids()?.last().map_or(0, |id| id + 1);
+
+        log::info!("Creating backup {id} in {}", self.path.display());
+
+        let mut info = BackupInfo {
+            id,
+            table_count: 0,
+            blob_file_count: 0,
+            size: 0,
+        };
+
+        let mut copied_bytes = 0;
+
+        for table in version.iter_tables() {
+            let dest = self
+                .path
+                .join(TABLES_FOLDER)
+                .join(file_name(table.id(), table.checksum()));
+
+            if !self.fs.exists(&dest)? {
+                copied_bytes += copy_atomic(&*config.fs, &table.path, &*self.fs, &dest)?;
+            }
+
+            info.table_count += 1;
+            info.size += file_len(&*self.fs, &dest)?;
+        }
+
+        for blob_file in version.blob_files.iter() {
+            let dest = self
+                .path
+                .join(BLOBS_FOLDER)
+                .join(file_name(blob_file.id(), blob_file.checksum()));
+
+            if !self.fs.exists(&dest)? {
+                copied_bytes += copy_atomic(&*config.fs, blob_file.path(), &*self.fs, &dest)?;
+            }
+
+            info.blob_file_count += 1;
+            info.size += file_len(&*self.fs, &dest)?;
+        }
+
+        self.fs.sync_directory(&self.path.join(TABLES_FOLDER))?;
+        self.fs.sync_directory(&self.path.join(BLOBS_FOLDER))?;
+
+        // NOTE: Write the metadata into a temporary folder first, so a backup becomes visible atomically
+        let tmp_path = self.path.join(BACKUPS_FOLDER).join(format!("{id}.tmp"));
+
+        if self.fs.exists(&tmp_path)? {
+            remove_dir_all(&*self.fs, &tmp_path)?;
+        }
+
+        self.fs.create_dir_all(&tmp_path)?;
+
+        copy_atomic(
+            &*config.fs,
+            &config.path.join(MANIFEST_FILE),
+            &*self.fs,
+            &tmp_path.join(MANIFEST_FILE),
+        )?;
+
+        persist_version(&*self.fs, &tmp_path, &version)?;
+
+        self.fs.rename(&tmp_path, &self.backup_path(id))?;
+        self.fs.sync_directory(&self.path.join(BACKUPS_FOLDER))?;
+
+        log::info!(
+            "Created backup {id} with {} tables and {} blob files, copied {copied_bytes} bytes",
+            info.table_count,
+            info.blob_file_count,
+        );
+
+        Ok(info)
+    }
+
+    /// Returns information about all backups, in ascending order.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn list_backups(&self) -> crate::Result<Vec<BackupInfo>> {
+        self.backup_ids()?
+            .into_iter()
+            .map(|id| {
+                let (tables, blob_files) = self.backup_files(id)?;
+
+                let mut size = 0;
+
+                for name in &tables {
+                    size += file_len(&*self.fs, &self.path.join(TABLES_FOLDER).join(name))?;
+                }
+
+                for name in &blob_files {
+                    size += file_len(&*self.fs, &self.path.join(BLOBS_FOLDER).join(name))?;
+                }
+
+                Ok(BackupInfo {
+                    id,
+                    table_count: tables.len(),
+                    blob_file_count: blob_files.len(),
+                    size,
+                })
+            })
+            .collect()
+    }
+
+    /// Deletes a backup, and all files that no other backup needs.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn delete_backup(&self, id: BackupId) -> crate::Result<()> {
+        log::info!("Deleting backup {id} in {}", self.path.display());
+
+        remove_dir_all(&*self.fs, &self.backup_path(id))?;
+        self.fs.sync_directory(&self.path.join(BACKUPS_FOLDER))?;
+
+        self.delete_unreferenced_files()
+    }
+
+    /// Deletes all but the newest `count` backups.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn purge_old_backups(&self, count: usize) -> crate::Result<()> {
+        let ids = self.backup_ids()?;
+
+        for &id in ids.iter().rev().skip(count) {
+            remove_dir_all(&*self.fs, &self.backup_path(id))?;
+        }
+
+        self.fs.sync_directory(&self.path.join(BACKUPS_FOLDER))?;
+
+        self.delete_unreferenced_files()
+    }
+
+    /// Deletes all tables and blob files that are not part of any backup.
+    fn delete_unreferenced_files(&self) -> crate::Result<()> {
+        let mut referenced = HashSet::new();
+
+        for id in self.backup_ids()? {
+            let (tables, blob_files) = self.backup_files(id)?;
+            referenced.extend(tables.into_iter().map(|name| (TABLES_FOLDER, name)));
+            referenced.extend(blob_files.into_iter().map(|name| (BLOBS_FOLDER, name)));
+        }
+
+        for folder in [TABLES_FOLDER, BLOBS_FOLDER] {
+            for path in self.fs.read_dir(&self.path.join(folder))? {
+                let name = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or_default();
+
+                if !referenced.contains(&(folder, name.to_owned())) {
+                    log::debug!("Deleting unreferenced backup file {}", path.display());
+                    self.fs.remove_file(&path)?;
+                }
+            }
+
+            self.fs.sync_directory(&self.path.join(folder))?;
+        }
+
+        Ok(())
+    }
+
+    /// Restores the newest backup into a new tree folder.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if there is no backup, the folder already exists, or an IO error occurs.
+    pub fn restore<P: AsRef<Path>>(&self, path: P) -> crate::Result<()> {
+        let Some(&id) = self.backup_ids()?.last() else {
+            return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+        };
+
+        self.restore_backup(id, path)
+    }
+
+    /// Restores a backup into a new tree folder.
+    ///
+    /// Files are hard linked from the backup folder if possible.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the backup does not exist, the folder already exists,
+    /// or an IO error occurs.
+    pub fn restore_backup<P: AsRef<Path>>(&self, id: BackupId, path: P) -> crate::Result<()> {
+        let path = path.as_ref();
+        let fs = &*self.fs;
+
+        let backup_path = self.backup_path(id);
+
+        if !fs.exists(&backup_path)? {
+            return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+        }
+
+        if fs.exists(path)? {
+            return Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists).into());
+        }
+
+        log::info!("Restoring backup {id} into {}", path.display());
+
+        let manifest = {
+            let manifest_path = backup_path.join(MANIFEST_FILE);
+            let reader = read_archive(fs, &manifest_path)?;
+            Manifest::decode_from(fs, &manifest_path, &reader)?
+        };
+
+        let recovery = recover(fs, &backup_path)?;
+        let shards = manifest.directory_shards;
+
+        let table_folder = path.join(TABLES_FOLDER);
+        fs.create_dir_all(&table_folder)?;
+        crate::file::create_shard_folders(fs, &table_folder, shards)?;
+
+        for &(table_id, checksum) in recovery.table_ids.iter().flatten().flatten() {
+            link_or_copy(
+                fs,
+                &self
+                    .path
+                    .join(TABLES_FOLDER)
+                    .join(file_name(table_id, checksum)),
+                &sharded_path(&table_folder, table_id, shards),
+            )?;
+        }
+
+        if manifest.tree_type == TreeType::Blob {
+            let blob_folder = path.join(BLOBS_FOLDER);
+            fs.create_dir_all(&blob_folder)?;
+            crate::file::create_shard_folders(fs, &blob_folder, shards)?;
+
+            for &(blob_file_id, checksum) in &recovery.blob_file_ids {
+                link_or_copy(
+                    fs,
+                    &self
+                        .path
+                        .join(BLOBS_FOLDER)
+                        .join(file_name(blob_file_id, checksum)),
+                    &sharded_path(&blob_folder, blob_file_id, shards),
+                )?;
+            }
+
+            sync_tree_folder(fs, &blob_folder)?;
+        }
+
+        sync_tree_folder(fs, &table_folder)?;
+
+        // NOTE: Copy the version last, so an interrupted restore can not be opened
+        for file in [
+            MANIFEST_FILE.to_owned(),
+            format!("v{}", recovery.curr_version_id),
+            "current".to_owned(),
+        ] {
+            link_or_copy(fs, &backup_path.join(&file), &path.join(&file))?;
+        }
+
+        fs.sync_directory(path)?;
+
+        if let Some(parent) = path.parent() {
+            fs.sync_directory(parent)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Persists the entries of a table or blob folder, and its shard folders.
+fn sync_tree_folder(fs: &dyn Filesystem, folder: &Path) -> crate::Result<()> {
+    for path in fs.read_dir(folder)? {
+        if fs.is_dir(&path)? {
+            fs.sync_directory(&path)?;
+        }
+    }
+
+    fs.sync_directory(folder)?;
+
+    Ok(())
+}
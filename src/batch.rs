@@ -0,0 +1,177 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{AbstractTree, InternalValue, SeqNo, UserKey, UserValue, ValueType};
+use std::collections::HashMap;
+
+/// An atomic write batch for a single tree, see [`AbstractTree::batch`]
+///
+/// Operations are staged in memory; on commit, all of them are written into
+/// the active memtable under a single sequence number, while holding the tree's
+/// version lock, so they cannot be split across memtables (or journal files).
+///
+/// Because all operations share one sequence number, a read at a sequence number
+/// up to (including) the batch's sequence number sees none of them, and a read
+/// at a higher sequence number sees all of them (once the batch is committed).
+///
+/// For a key-value separated tree, values are only separated when the memtable is flushed,
+/// so large values are moved into blob files together with the rest of the memtable.
+///
+/// To atomically write to multiple trees, open them as partitions of a [`crate::keyspace::Keyspace`],
+/// and use a [`crate::keyspace::WriteBatch`], which shares one sequence number and journal record
+/// across all partitions.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SequenceNumberCounter};
+///
+/// let seqno = SequenceNumberCounter::default();
+///
+/// let tree = Config::new(folder, seqno.clone())
+///     .with_kv_separation(Some(KvSeparationOptions::default()))
+///     .open()?;
+///
+/// let mut batch = tree.batch();
+/// batch.insert("big", "a".repeat(10_000));
+/// batch.insert("meta", "small");
+/// batch.remove("stale");
+///
+/// let batch_seqno = seqno.next();
+/// batch.commit(batch_seqno)?;
+///
+/// assert!(!tree.contains_key("meta", batch_seqno)?);
+/// assert!(tree.contains_key("big", batch_seqno + 1)?);
+/// assert!(tree.contains_key("meta", batch_seqno + 1)?);
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[must_use = "a batch does nothing unless committed"]
+pub struct Batch {
+    tree: crate::Tree,
+    ops: Vec<(ValueType, UserKey, UserValue)>,
+}
+
+impl Batch {
+    pub(crate) fn new(tree: crate::Tree) -> Self {
+        Self {
+            tree,
+            ops: Vec::new(),
+        }
+    }
+
+    /// Inserts a key-value pair.
+    pub fn insert<K: Into<UserKey>, V: Into<UserValue>>(&mut self, key: K, value: V) {
+        self.ops.push((ValueType::Value, key.into(), value.into()));
+    }
+
+    /// Removes a key.
+    pub fn remove<K: Into<UserKey>>(&mut self, key: K) {
+        self.ops
+            .push((ValueType::Tombstone, key.into(), UserValue::empty()));
+    }
+
+    /// Removes a key using a weak tombstone, see [`crate::AbstractTree::remove_weak`].
+    pub fn remove_weak<K: Into<UserKey>>(&mut self, key: K) {
+        self.ops
+            .push((ValueType::WeakTombstone, key.into(), UserValue::empty()));
+    }
+
+    /// Adds a merge operand for a key, see [`crate::AbstractTree::add_merge`].
+    ///
+    /// Because all operations of a batch share a sequence number, operands are combined
+    /// with earlier operations on the same key of the batch when the batch is committed,
+    /// using the tree's merge operator.
+    pub fn add_merge<K: Into<UserKey>, V: Into<UserValue>>(&mut self, key: K, operand: V) {
+        self.ops
+            .push((ValueType::MergeOperand, key.into(), operand.into()));
+    }
+
+    /// Returns the number of operations in the batch.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if the batch contains no operations.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Atomically applies all operations, using the given sequence number.
+    ///
+    /// Returns the added size and new size of the memtable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the batch could not be journaled, in which case none of the operations are applied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn commit(self, seqno: SeqNo) -> crate::Result<(u64, u64)> {
+        if self.ops.is_empty() {
+            return Ok((0, self.tree.active_memtable_size()));
+        }
+
+        let merge_operator = self.tree.config.merge_operator.clone();
+
+        // NOTE: Operations on the same key share the sequence number, so only one of them
+        // can be written; later operations replace earlier ones, and merge operands are folded
+        let mut values: Vec<InternalValue> = Vec::with_capacity(self.ops.len());
+        let mut positions: HashMap<UserKey, usize> = HashMap::with_capacity(self.ops.len());
+
+        for (value_type, key, value) in self.ops {
+            let Some(&idx) = positions.get(&key) else {
+                positions.insert(key.clone(), values.len());
+                values.push(InternalValue::from_components(
+                    key, value, seqno, value_type,
+                ));
+                continue;
+            };
+
+            let Some(prev) = values.get_mut(idx) else {
+                continue;
+            };
+
+            *prev = match (&merge_operator, value_type, prev.key.value_type) {
+                (
+                    Some(op),
+                    ValueType::MergeOperand,
+                    prev_type @ (ValueType::Value | ValueType::MergeOperand),
+                ) => {
+                    let merged = op.merge(&key, &prev.value, &value);
+                    InternalValue::from_components(key, merged, seqno, prev_type)
+                }
+                (Some(_), ValueType::MergeOperand, _) => {
+                    // NOTE: The operand is applied on top of a deletion, so it becomes the value
+                    InternalValue::from_components(key, value, seqno, ValueType::Value)
+                }
+                _ => InternalValue::from_components(key, value, seqno, value_type),
+            };
+        }
+
+        self.tree.append_entries(values)
+    }
+
+    /// Atomically applies all operations, using the next sequence number
+    /// of the tree's sequence number generator, see [`AbstractTree::insert_auto`].
+    ///
+    /// Returns the sequence number of the batch.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the batch could not be journaled, in which case none of the operations are applied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn commit_auto(self) -> crate::Result<SeqNo> {
+        let seqno = self.tree.config.seqno.next();
+        self.commit(seqno)?;
+        Ok(seqno)
+    }
+}
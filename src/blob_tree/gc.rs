@@ -138,8 +138,15 @@ impl ExpiredKvCallback for FragmentationMap {
         if kv.key.value_type.is_indirection() {
             let mut reader = &kv.value[..];
 
-            let vptr =
-                BlobIndirection::decode_from(&mut reader).expect("should parse BlobIndirection");
+            // NOTE: A corrupt indirection cannot be attributed to a blob file,
+            // so it cannot be counted as stale
+            let vptr = match BlobIndirection::decode_from(&mut reader) {
+                Ok(vptr) => vptr,
+                Err(e) => {
+                    log::error!("Failed to decode blob indirection of {:?}: {e:?}", kv.key);
+                    return;
+                }
+            };
 
             let size = u64::from(vptr.size);
 
@@ -148,6 +155,7 @@ impl ExpiredKvCallback for FragmentationMap {
                 .and_modify(|counter| {
                     counter.len += 1;
                     counter.bytes += size;
+                    counter.on_disk_bytes += u64::from(vptr.vhandle.on_disk_size);
                 })
                 .or_insert_with(|| FragmentationEntry {
                     bytes: size,
@@ -4,26 +4,38 @@
 
 mod gc;
 pub mod handle;
+mod snapshot;
+mod stats;
+mod verify;
 
 #[doc(hidden)]
 pub use gc::{FragmentationEntry, FragmentationMap};
 
+pub use snapshot::Snapshot;
+pub use stats::Stats;
+pub use verify::VerifyReport;
+
 use crate::{
     coding::{Decode, Encode},
     compaction::stream::CompactionStream,
-    file::{fsync_directory, BLOBS_FOLDER},
+    file::BLOBS_FOLDER,
     iter_guard::{IterGuard, IterGuardImpl},
-    r#abstract::{AbstractTree, RangeItem},
+    r#abstract::{AbstractTree, RangeItem, RawItem},
     table::Table,
     tree::inner::MemtableId,
     value::InternalValue,
     version::Version,
-    vlog::{Accessor, BlobFile, BlobFileWriter, ValueHandle},
-    Cache, Config, DescriptorTable, Memtable, SeqNo, SequenceNumberCounter, TableId, TreeId,
-    UserKey, UserValue,
+    vlog::{Accessor, BlobFile, BlobFileWriter, BlobReader, ValueHandle},
+    Cache, Config, DescriptorTable, Filesystem, Memtable, ReadOptions, SeqNo,
+    SequenceNumberCounter, TableId, TreeId, UserKey, UserValue, ValueType,
 };
 use handle::BlobIndirection;
-use std::{io::Cursor, ops::RangeBounds, path::PathBuf, sync::Arc};
+use std::{
+    io::Cursor,
+    ops::RangeBounds,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 pub struct Guard {
     tree: crate::BlobTree,
@@ -51,8 +63,8 @@ impl IterGuard for Guard {
     fn into_inner(self) -> crate::Result<(UserKey, UserValue)> {
         resolve_value_handle(
             self.tree.id(),
-            self.tree.blobs_folder.as_path(),
-            &self.tree.index.config.cache,
+            &*self.tree.index.config.fs,
+            self.tree.index.config.blob_cache(),
             &self.tree.index.config.descriptor_table,
             &self.version,
             self.kv?,
@@ -62,7 +74,7 @@ impl IterGuard for Guard {
 
 fn resolve_value_handle(
     tree_id: TreeId,
-    blobs_folder: &std::path::Path,
+    fs: &dyn Filesystem,
     cache: &Arc<Cache>,
     descriptor_table: &Arc<DescriptorTable>,
     version: &Version,
@@ -75,9 +87,9 @@ fn resolve_value_handle(
         // Resolve indirection using value log
         match Accessor::new(&version.blob_files).get(
             tree_id,
-            blobs_folder,
             &item.key.user_key,
             &vptr.vhandle,
+            fs,
             cache,
             descriptor_table,
         ) {
@@ -86,11 +98,13 @@ fn resolve_value_handle(
                 Ok((k, v))
             }
             Ok(None) => {
-                panic!(
-                    "value handle ({:?} => {:?}) did not match any blob - this is a bug; version={}",
-                    item.key.user_key, vptr.vhandle,
+                log::error!(
+                    "Value handle ({:?} => {:?}) did not match any blob; version={}",
+                    item.key.user_key,
+                    vptr.vhandle,
                     version.id(),
                 );
+                Err(crate::Error::Corrupted("BlobIndirection"))
             }
             Err(e) => Err(e),
         }
@@ -106,13 +120,51 @@ fn resolve_value_handle(
 /// This tree is a composite structure, consisting of an
 /// index tree (LSM-tree) and a log-structured value log
 /// to reduce write amplification.
+///
+/// Like [`Tree`](crate::Tree), a `BlobTree` is a cheap handle: all its state is
+/// reference counted, so clones refer to the same tree and can be sent to,
+/// and used concurrently by, other threads (it is `Clone + Send + Sync`).
+///
+/// A `BlobTree` is opened using [`Config`] with [key-value separation](Config::with_kv_separation)
+/// enabled, so the index tree is tuned like any other tree, and the value log
+/// is tuned using [`KvSeparationOptions`](crate::KvSeparationOptions).
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{
+///     config::{BlockSizePolicy, BloomConstructionPolicy, FilterPolicy, FilterPolicyEntry},
+///     AbstractTree, AnyTree, Cache, Config, KvSeparationOptions, SequenceNumberCounter,
+/// };
+/// use std::sync::Arc;
+///
+/// let tree = Config::new(folder, SequenceNumberCounter::default())
+///     .data_block_size_policy(BlockSizePolicy::all(16 * 1_024))
+///     .filter_policy(FilterPolicy::all(FilterPolicyEntry::Bloom(
+///         BloomConstructionPolicy::FalsePositiveRate(0.001),
+///     )))
+///     .use_cache(Arc::new(Cache::with_capacity_bytes(16 * 1_024 * 1_024)))
+///     .with_kv_separation(Some(
+///         KvSeparationOptions::default()
+///             .separation_threshold(1_024)
+///             .file_target_size(64 * 1_024 * 1_024),
+///     ))
+///     .open()?;
+///
+/// let AnyTree::Blob(tree) = tree else {
+///     unreachable!("KV separation is enabled");
+/// };
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
 #[derive(Clone)]
 pub struct BlobTree {
     /// Index tree that holds value handles or small inline values
     #[doc(hidden)]
     pub index: crate::Tree,
 
-    blobs_folder: Arc<PathBuf>,
+    pub(crate) blobs_folder: Arc<PathBuf>,
 }
 
 impl BlobTree {
@@ -120,8 +172,18 @@ impl BlobTree {
         let index = crate::Tree::open(config)?;
 
         let blobs_folder = index.config.path.join(BLOBS_FOLDER);
-        std::fs::create_dir_all(&blobs_folder)?;
-        fsync_directory(&blobs_folder)?;
+
+        // NOTE: Ephemeral trees keep all values inline, so no blob files are ever written,
+        // and secondary instances never write to the folder of the primary tree
+        if !index.config.ephemeral && !index.config.is_secondary() {
+            index.config.fs.create_dir_all(&blobs_folder)?;
+            crate::file::create_shard_folders(
+                &*index.config.fs,
+                &blobs_folder,
+                index.config.directory_shards,
+            )?;
+            index.config.fs.sync_directory(&blobs_folder)?;
+        }
 
         let blob_file_id_to_continue_with = index
             .current_version()
@@ -141,6 +203,399 @@ impl BlobTree {
             blobs_folder: Arc::new(blobs_folder),
         })
     }
+
+    fn kv_separation_opts(&self) -> crate::Result<&crate::KvSeparationOptions> {
+        self.index
+            .config
+            .kv_separation_opts
+            .as_ref()
+            .ok_or(crate::Error::InvalidConfig(
+                "key-value separation is not enabled",
+            ))
+    }
+
+    /// Applies a batch of pre-sequenced entries, as received from a replication leader.
+    ///
+    /// See [`crate::Tree::apply_replicated_batch`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if sequence numbers are not monotonically increasing, or
+    /// an entry is a blob indirection.
+    pub fn apply_replicated_batch<I: IntoIterator<Item = InternalValue>>(
+        &self,
+        entries: I,
+    ) -> crate::Result<(u64, u64)> {
+        self.index.apply_replicated_batch(entries)
+    }
+
+    /// Opens a read-only snapshot of the tree at the given sequence number, see [`Snapshot`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Guard, KvSeparationOptions};
+    ///
+    /// let tree = Config::new(folder, Default::default())
+    ///     .with_kv_separation(Some(KvSeparationOptions::default()))
+    ///     .open()?;
+    /// # let lsm_tree::AnyTree::Blob(tree) = tree else { unreachable!() };
+    ///
+    /// tree.insert("a", "old", 0);
+    ///
+    /// let snapshot = tree.snapshot(1);
+    ///
+    /// tree.insert("a", "new", 1);
+    /// tree.insert("b", "new", 2);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// assert_eq!(b"old", &*snapshot.get("a")?.expect("should exist"));
+    /// assert_eq!(1, snapshot.iter().count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn snapshot(&self, seqno: SeqNo) -> Snapshot {
+        Snapshot::new(self.clone(), seqno)
+    }
+
+    /// Returns a reader that streams the value of a key, see [`BlobReader`].
+    ///
+    /// Unlike [`AbstractTree::get`], large blobs are not loaded into memory as a whole,
+    /// unless they are compressed or encrypted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, KvSeparationOptions};
+    /// use std::io::Read;
+    ///
+    /// let tree = Config::new(folder, Default::default())
+    ///     .with_kv_separation(Some(KvSeparationOptions::default()))
+    ///     .open()?;
+    /// # let lsm_tree::AnyTree::Blob(tree) = tree else { unreachable!() };
+    ///
+    /// tree.insert("a", "a".repeat(100_000), 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let mut reader = tree.get_reader("a", 1)?.expect("should exist");
+    ///
+    /// let mut buf = vec![0; 4_096];
+    /// reader.read_exact(&mut buf)?;
+    /// assert!(buf.iter().all(|&b| b == b'a'));
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn get_reader<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        seqno: SeqNo,
+    ) -> crate::Result<Option<BlobReader>> {
+        let key = key.as_ref();
+        let super_version = self.index.get_version_for_snapshot(seqno);
+
+        let Some(item) = self
+            .index
+            .get_internal_entry_in(&super_version, key, seqno)?
+        else {
+            return Ok(None);
+        };
+
+        if !item.key.value_type.is_indirection() {
+            return Ok(Some(BlobReader::from_value(item.value)));
+        }
+
+        let vhandle = BlobIndirection::decode_from(&mut Cursor::new(&item.value))?.vhandle;
+
+        let reader = Accessor::new(&super_version.version.blob_files).get_reader(
+            self.id(),
+            key,
+            &vhandle,
+            &*self.index.config.fs,
+            self.index.config.blob_cache(),
+            &self.index.config.descriptor_table,
+        )?;
+
+        reader.map(Some).ok_or_else(|| {
+            log::error!(
+                "Value handle ({key:?} => {vhandle:?}) did not match any blob; version={}",
+                super_version.version.id(),
+            );
+            crate::Error::Corrupted("BlobIndirection")
+        })
+    }
+
+    /// Reads up to `len` bytes of the value of a key, starting at `offset`.
+    ///
+    /// If the range exceeds the value, the returned slice is shorter than `len`.
+    ///
+    /// Blobs that are stored uncompressed and unencrypted are read partially,
+    /// so their checksum is not verified.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn get_range<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        seqno: SeqNo,
+        offset: u64,
+        len: usize,
+    ) -> crate::Result<Option<UserValue>> {
+        let key = key.as_ref();
+        let super_version = self.index.get_version_for_snapshot(seqno);
+
+        let Some(item) = self
+            .index
+            .get_internal_entry_in(&super_version, key, seqno)?
+        else {
+            return Ok(None);
+        };
+
+        if !item.key.value_type.is_indirection() {
+            let value = item.value;
+
+            #[expect(clippy::cast_possible_truncation, reason = "values are u32 max length")]
+            let lo = offset.min(value.len() as u64) as usize;
+            let hi = lo.saturating_add(len).min(value.len());
+
+            return Ok(Some(value.slice(lo..hi)));
+        }
+
+        let vhandle = BlobIndirection::decode_from(&mut Cursor::new(&item.value))?.vhandle;
+
+        let value = Accessor::new(&super_version.version.blob_files).get_range(
+            self.id(),
+            key,
+            &vhandle,
+            offset,
+            len,
+            &*self.index.config.fs,
+            self.index.config.blob_cache(),
+            &self.index.config.descriptor_table,
+        )?;
+
+        value.map(Some).ok_or_else(|| {
+            log::error!(
+                "Value handle ({key:?} => {vhandle:?}) did not match any blob; version={}",
+                super_version.version.id(),
+            );
+            crate::Error::Corrupted("BlobIndirection")
+        })
+    }
+
+    /// Returns a change data capture reader that reads all writes,
+    /// starting at the given sequence number.
+    ///
+    /// See [`crate::Tree::cdc_reader`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`crate::Error::InvalidConfig`] if the journal is not enabled.
+    pub fn cdc_reader(&self, from_seqno: SeqNo) -> crate::Result<crate::CdcReader> {
+        self.index.cdc_reader(from_seqno)
+    }
+
+    /// Returns space statistics of the value log and index tree, see [`Stats`].
+    ///
+    /// The statistics can be used to decide when to run [`BlobTree::gc`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, KvSeparationOptions};
+    ///
+    /// let tree = Config::new(folder, Default::default())
+    ///     .with_kv_separation(Some(KvSeparationOptions::default()))
+    ///     .open()?;
+    /// # let lsm_tree::AnyTree::Blob(tree) = tree else { unreachable!() };
+    ///
+    /// tree.insert("a", "a".repeat(10_000), 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let stats = tree.stats();
+    /// assert_eq!(1, stats.blob_file_count);
+    /// assert_eq!(0, stats.stale_blob_bytes);
+    /// assert!(stats.space_amp() < 1.01);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        let version = self.current_version();
+
+        Stats {
+            blob_file_count: version.blob_file_count(),
+            blob_bytes: version.blob_files.on_disk_size(),
+            stale_blob_bytes: version.gc_stats().stale_bytes(),
+            index_bytes: version.iter_levels().map(crate::version::Level::size).sum(),
+        }
+    }
+
+    /// Checks that every value handle of the index tree points to a readable blob
+    /// with a valid checksum, and looks for orphaned blob files, see [`VerifyReport`].
+    ///
+    /// Every blob is read from disk, so this is expensive for large value logs.
+    ///
+    /// Blob files that are written while the tree is verified (by flushes, compactions
+    /// or garbage collection) may be reported as orphaned, so this is best used on a tree
+    /// that is not written to, e.g. after an unclean shutdown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, KvSeparationOptions};
+    ///
+    /// let tree = Config::new(folder, Default::default())
+    ///     .with_kv_separation(Some(KvSeparationOptions::default()))
+    ///     .open()?;
+    /// # let lsm_tree::AnyTree::Blob(tree) = tree else { unreachable!() };
+    ///
+    /// tree.insert("a", "a".repeat(10_000), 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let report = tree.verify()?;
+    /// assert!(report.is_ok());
+    /// assert_eq!(1, report.checked_blob_count);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn verify(&self) -> crate::Result<VerifyReport> {
+        verify::verify(self)
+    }
+
+    /// Rewrites the most fragmented blob files, until the space amplification
+    /// of the value log (on-disk blob bytes divided by live blob bytes) is at most
+    /// `space_amp_target`.
+    ///
+    /// See [`BlobTree::gc_with_staleness_threshold`] for how blob files are rewritten.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or [`crate::Error::InvalidConfig`]
+    /// if the target is below 1.0.
+    pub fn gc(&self, space_amp_target: f32, seqno_threshold: SeqNo) -> crate::Result<()> {
+        if space_amp_target.is_nan() || space_amp_target < 1.0 {
+            return Err(crate::Error::InvalidConfig(
+                "space amplification target must be at least 1.0",
+            ));
+        }
+
+        let version = self.current_version();
+        let gc_stats = version.gc_stats();
+
+        let total_bytes = version.blob_files.on_disk_size();
+        let live_bytes = total_bytes.saturating_sub(gc_stats.stale_bytes());
+
+        let mut candidates = version
+            .blob_files
+            .iter()
+            .filter(|blob_file| !blob_file.is_dead(gc_stats))
+            .filter_map(|blob_file| {
+                let ratio = blob_file.stale_ratio(gc_stats)?;
+                let stale_bytes = gc_stats.get(&blob_file.id())?.on_disk_bytes;
+                Some((ratio, blob_file.id(), stale_bytes))
+            })
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut remaining_bytes = total_bytes;
+        let mut blob_file_ids = vec![];
+        let mut staleness_threshold = 1.0;
+
+        for (ratio, blob_file_id, stale_bytes) in candidates {
+            #[expect(
+                clippy::cast_precision_loss,
+                reason = "ratio does not need to be exact"
+            )]
+            let space_amp = remaining_bytes as f32 / live_bytes.max(1) as f32;
+
+            if space_amp <= space_amp_target {
+                break;
+            }
+
+            remaining_bytes -= stale_bytes;
+            blob_file_ids.push(blob_file_id);
+            staleness_threshold = ratio;
+        }
+
+        self.rewrite_blob_files(&blob_file_ids, staleness_threshold, seqno_threshold)
+    }
+
+    /// Rewrites all blob files whose ratio of stale bytes is at least `staleness_threshold`.
+    ///
+    /// The tables that reference a blob file are compacted, and the live blobs are
+    /// relocated into new blob files, so the index is updated in the same version change.
+    /// Unlike blob relocation during regular compactions (see [`crate::KvSeparationOptions`]),
+    /// blob files are rewritten regardless of their age.
+    ///
+    /// `seqno_threshold` is the MVCC watermark, see [`AbstractTree::major_compact`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or [`crate::Error::InvalidConfig`]
+    /// if the threshold is not in [0, 1].
+    pub fn gc_with_staleness_threshold(
+        &self,
+        staleness_threshold: f32,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<()> {
+        if !(0.0..=1.0).contains(&staleness_threshold) {
+            return Err(crate::Error::InvalidConfig(
+                "staleness threshold must be in [0, 1]",
+            ));
+        }
+
+        let version = self.current_version();
+        let gc_stats = version.gc_stats();
+
+        let blob_file_ids = version
+            .blob_files
+            .iter()
+            .filter(|blob_file| blob_file.is_stale(gc_stats, staleness_threshold))
+            .filter(|blob_file| !blob_file.is_dead(gc_stats))
+            .map(BlobFile::id)
+            .collect::<Vec<_>>();
+
+        self.rewrite_blob_files(&blob_file_ids, staleness_threshold, seqno_threshold)
+    }
+
+    fn rewrite_blob_files(
+        &self,
+        blob_file_ids: &[crate::vlog::BlobFileId],
+        staleness_threshold: f32,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<()> {
+        log::debug!("Rewriting blob files {blob_file_ids:?}");
+
+        for &blob_file_id in blob_file_ids {
+            // NOTE: The blob file may have already been rewritten together with another one
+            if !self.current_version().blob_files.contains_key(blob_file_id) {
+                continue;
+            }
+
+            self.index.compact_blob_files(
+                Arc::new(crate::compaction::blob_gc::Strategy::new(blob_file_id)),
+                staleness_threshold,
+                seqno_threshold,
+            )?;
+        }
+
+        Ok(())
+    }
 }
 
 impl AbstractTree for BlobTree {
@@ -152,6 +607,10 @@ impl AbstractTree for BlobTree {
         self.index.id()
     }
 
+    fn get_key_seqno(&self, key: &[u8]) -> crate::Result<Option<SeqNo>> {
+        self.index.get_key_seqno(key)
+    }
+
     fn get_internal_entry(&self, key: &[u8], seqno: SeqNo) -> crate::Result<Option<InternalValue>> {
         self.index.get_internal_entry(key, seqno)
     }
@@ -161,6 +620,12 @@ impl AbstractTree for BlobTree {
     }
 
     fn flush_active_memtable(&self, eviction_seqno: SeqNo) -> crate::Result<Option<Table>> {
+        if self.index.config.ephemeral {
+            return self.index.flush_active_memtable(eviction_seqno);
+        }
+
+        self.index.config.ensure_writable()?;
+
         let Some((table_id, yanked_memtable)) = self.index.rotate_memtable() else {
             return Ok(None);
         };
@@ -197,12 +662,24 @@ impl AbstractTree for BlobTree {
         use crate::range::prefix_to_range;
 
         let range = prefix_to_range(prefix.as_ref());
-        let version = self.index.get_version_for_snapshot(seqno).version;
+        self.range(range, seqno, index)
+    }
+
+    fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static> {
+        // NOTE: Resolve indirections in the same version that is iterated, so a concurrent
+        // flush or compaction can not cause them to be looked up in a different version
+        let super_version = self.index.get_version_for_snapshot(seqno);
+        let version = super_version.version.clone();
         let tree = self.clone();
 
         Box::new(
             self.index
-                .create_internal_range(&range, seqno, index)
+                .create_internal_range_in(super_version, &range, seqno, index)
                 .map(move |kv| {
                     IterGuardImpl::Blob(Guard {
                         tree: tree.clone(),
@@ -213,26 +690,54 @@ impl AbstractTree for BlobTree {
         )
     }
 
-    fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+    fn range_with_options<K: AsRef<[u8]>, R: RangeBounds<K>>(
         &self,
         range: R,
         seqno: SeqNo,
-        index: Option<Arc<Memtable>>,
+        options: &ReadOptions,
     ) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static> {
-        let version = self.index.get_version_for_snapshot(seqno);
+        let Some(bounds) = options.bounds(&range) else {
+            return Box::new(std::iter::empty());
+        };
+
+        // NOTE: See `BlobTree::range`
+        let super_version = self.index.get_version_for_snapshot(seqno);
+        let version = super_version.version.clone();
         let tree = self.clone();
+        let key_only = options.is_key_only();
 
-        Box::new(
-            self.index
-                .create_internal_range(&range, seqno, index)
-                .map(move |kv| {
-                    IterGuardImpl::Blob(Guard {
-                        tree: tree.clone(),
-                        version: version.clone().version,
-                        kv,
-                    })
-                }),
-        )
+        let iter = self
+            .index
+            .create_internal_range_with(
+                super_version,
+                &bounds,
+                seqno,
+                None,
+                options.is_fill_cache(),
+            )
+            .map(move |kv| {
+                if key_only {
+                    // NOTE: Does not resolve the value, so no blob file is read
+                    let kv = kv.map(|kv| (kv.key.user_key, UserValue::empty()));
+                    return IterGuardImpl::Standard(crate::tree::Guard(kv));
+                }
+
+                IterGuardImpl::Blob(Guard {
+                    tree: tree.clone(),
+                    version: version.clone(),
+                    kv,
+                })
+            });
+
+        options.limit_iter(Box::new(iter))
+    }
+
+    fn raw_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<RawItem>> + Send + 'static> {
+        self.index.raw_range(range, seqno)
     }
 
     fn tombstone_count(&self) -> u64 {
@@ -260,42 +765,31 @@ impl AbstractTree for BlobTree {
         use crate::{compaction::MoveDown, tree::ingest::Ingestion};
         use std::time::Instant;
 
+        if self.index.config.ephemeral {
+            return self.index.ingest(iter, seqno_generator, visible_seqno);
+        }
+
         let seqno = seqno_generator.next();
 
-        let blob_file_size = self
-            .index
-            .config
-            .kv_separation_opts
-            .as_ref()
-            .expect("kv separation options should exist")
-            .file_target_size;
+        let blob_file_size = self.kv_separation_opts()?.file_target_size;
 
         let mut table_writer = Ingestion::new(&self.index)?.with_seqno(seqno);
         let mut blob_writer = BlobFileWriter::new(
+            self.index.config.fs.clone(),
             self.index.0.blob_file_id_generator.clone(),
             blob_file_size,
             self.index.config.path.join(BLOBS_FOLDER),
+            self.index.config.directory_shards,
         )?
-        .use_compression(
-            self.index
-                .config
-                .kv_separation_opts
-                .as_ref()
-                .expect("blob options should exist")
-                .compression,
-        );
+        .use_clock(self.index.config.clock.clone())
+        .use_encryption(self.index.config.encryptor.clone())
+        .use_compression(self.kv_separation_opts()?.compression);
 
         let start = Instant::now();
         let mut count = 0;
         let mut last_key = None;
 
-        let separation_threshold = self
-            .index
-            .config
-            .kv_separation_opts
-            .as_ref()
-            .expect("kv separation options should exist")
-            .separation_threshold;
+        let separation_threshold = self.kv_separation_opts()?.separation_threshold;
 
         for (key, value) in iter {
             if let Some(last_key) = &last_key {
@@ -341,13 +835,15 @@ impl AbstractTree for BlobTree {
             .into_iter()
             .map(|(table_id, checksum)| -> crate::Result<Table> {
                 Table::recover(
-                    self.index
-                        .config
-                        .path
-                        .join(crate::file::TABLES_FOLDER)
-                        .join(table_id.to_string()),
+                    crate::file::sharded_path(
+                        &self.index.config.path.join(crate::file::TABLES_FOLDER),
+                        table_id,
+                        self.index.config.directory_shards,
+                    ),
                     checksum,
                     self.index.id,
+                    self.index.config.fs.clone(),
+                    self.index.config.encryptor.clone(),
                     self.index.config.cache.clone(),
                     self.index.config.descriptor_table.clone(),
                     pin_filter,
@@ -371,6 +867,60 @@ impl AbstractTree for BlobTree {
         Ok(())
     }
 
+    fn ingest_tables<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+        seqno_generator: &SequenceNumberCounter,
+        visible_seqno: &SequenceNumberCounter,
+    ) -> crate::Result<()> {
+        // NOTE: Flush first, so writes made before the ingestion are considered when checking for overlaps
+        self.flush_active_memtable(0)?;
+
+        let paths = paths.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+        self.index
+            .ingest_external(&paths, seqno_generator, visible_seqno)
+    }
+
+    fn rewrite_all_tables(&self, seqno_threshold: SeqNo) -> crate::Result<()> {
+        self.index.rewrite_all_tables(seqno_threshold)
+    }
+
+    fn checkpoint<P: AsRef<Path>>(&self, path: P) -> crate::Result<()> {
+        // NOTE: Flush first, so the checkpoint contains all writes made before it
+        self.flush_active_memtable(0)?;
+        self.index.write_checkpoint(path.as_ref())
+    }
+
+    fn try_catch_up(&self) -> crate::Result<()> {
+        self.index.catch_up()
+    }
+
+    fn freeze_files(&self) -> crate::tree::FreezeGuard<'_> {
+        self.index.freeze_files()
+    }
+
+    fn compact_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        target_size: u64,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<()> {
+        self.index
+            .compact_range(range, target_size, seqno_threshold)
+    }
+
+    fn pause_background_work(&self) {
+        self.index.pause_background_work();
+    }
+
+    fn resume_background_work(&self) {
+        self.index.resume_background_work();
+    }
+
+    fn write_stall_condition(&self) -> crate::WriteStallCondition {
+        self.index.write_stall_condition()
+    }
+
     fn major_compact(&self, target_size: u64, seqno_threshold: SeqNo) -> crate::Result<()> {
         self.index.major_compact(target_size, seqno_threshold)
     }
@@ -426,6 +976,7 @@ impl AbstractTree for BlobTree {
         self.index.sealed_memtable_count()
     }
 
+    #[expect(clippy::too_many_lines)]
     fn flush_memtable(
         &self,
         table_id: TableId,
@@ -434,54 +985,64 @@ impl AbstractTree for BlobTree {
     ) -> crate::Result<Option<(Table, Option<BlobFile>)>> {
         use crate::{file::TABLES_FOLDER, table::Writer as TableWriter};
 
+        if self.index.config.ephemeral {
+            return self
+                .index
+                .flush_memtable(table_id, memtable, eviction_seqno);
+        }
+
+        self.index.config.ensure_writable()?;
+
         let table_folder = self.index.config.path.join(TABLES_FOLDER);
 
         log::debug!("Flushing memtable & performing key-value separation");
         log::debug!("=> to table in {}", table_folder.display());
         log::debug!("=> to blob file at {}", self.blobs_folder.display());
 
-        let mut table_writer =
-            TableWriter::new(table_folder.join(table_id.to_string()), table_id, 0)?
-                // TODO: apply other policies
-                .use_data_block_compression(self.index.config.data_block_compression_policy.get(0))
-                .use_bloom_policy({
-                    use crate::config::FilterPolicyEntry::{Bloom, None};
-                    use crate::table::filter::BloomConstructionPolicy;
-
-                    match self.index.config.filter_policy.get(0) {
-                        Bloom(policy) => policy,
-                        None => BloomConstructionPolicy::BitsPerKey(0.0),
-                    }
-                });
+        let mut table_writer = TableWriter::new(
+            self.index.config.fs.clone(),
+            crate::file::sharded_path(&table_folder, table_id, self.index.config.directory_shards),
+            table_id,
+            0,
+        )?
+        .use_clock(self.index.config.clock.clone())
+        .use_encryption(self.index.config.encryptor.clone())
+        .use_rate_limiter(self.index.config.rate_limiter.clone())
+        .use_key_extractor(self.index.config.key_extractor.clone())
+        // TODO: apply other policies
+        .use_data_block_compression(self.index.config.data_block_compression_policy.get(0))
+        .use_bloom_policy({
+            use crate::config::FilterPolicyEntry::{Bloom, None};
+            use crate::table::filter::BloomConstructionPolicy;
+
+            match self.index.config.filter_policy.get(0) {
+                Bloom(policy) => policy,
+                None => BloomConstructionPolicy::BitsPerKey(0.0),
+            }
+        });
 
         let mut blob_writer = BlobFileWriter::new(
+            self.index.config.fs.clone(),
             self.index.0.blob_file_id_generator.clone(),
             u64::MAX,
             self.index.config.path.join(BLOBS_FOLDER),
+            self.index.config.directory_shards,
         )?
-        .use_compression(
-            self.index
-                .config
-                .kv_separation_opts
-                .as_ref()
-                .expect("blob options should exist")
-                .compression,
-        );
+        .use_clock(self.index.config.clock.clone())
+        .use_encryption(self.index.config.encryptor.clone())
+        .use_rate_limiter(self.index.config.rate_limiter.clone())
+        .use_compression(self.kv_separation_opts()?.compression);
 
         let iter = memtable.iter().map(Ok);
-        let compaction_stream = CompactionStream::new(iter, eviction_seqno);
+        let compaction_stream = CompactionStream::new(iter, eviction_seqno)
+            .with_range_tombstones(memtable.range_tombstones())
+            .expire_values(self.index.config.clock.now());
 
         let mut blob_bytes_referenced = 0;
         let mut blob_on_disk_bytes_referenced = 0;
         let mut blobs_referenced_count = 0;
 
-        let separation_threshold = self
-            .index
-            .config
-            .kv_separation_opts
-            .as_ref()
-            .expect("kv separation options should exist")
-            .separation_threshold;
+        let separation_threshold = self.kv_separation_opts()?.separation_threshold;
 
         for item in compaction_stream {
             let item = item?;
@@ -498,7 +1059,9 @@ impl AbstractTree for BlobTree {
             #[expect(clippy::cast_possible_truncation, reason = "values are u32 length max")]
             let value_size = value.len() as u32;
 
-            if value_size >= separation_threshold {
+            // NOTE: Expiring values stay in the index tree, so their expiry timestamp
+            // is available to reads and compactions
+            if value_size >= separation_threshold && !item.key.value_type.is_expiring_value() {
                 let offset = blob_writer.offset();
                 let blob_file_id = blob_writer.blob_file_id();
                 let on_disk_size = blob_writer.write(&item.key.user_key, item.key.seqno, &value)?;
@@ -547,6 +1110,13 @@ impl AbstractTree for BlobTree {
 
         let table = self.index.consume_writer(table_writer)?;
 
+        if table.is_none() {
+            self.index.release_empty_memtable(table_id)?;
+        }
+
+        // NOTE: The table and blob file are durable at this point, but only become
+        // visible once they are registered in a new version, which publishes both atomically.
+        // If we crash before that, they are orphans and are deleted on recovery
         Ok(table.map(|table| (table, blob_file)))
     }
 
@@ -603,6 +1173,14 @@ impl AbstractTree for BlobTree {
         self.index.table_count()
     }
 
+    fn derived_key_may_exist<K: AsRef<[u8]>>(&self, derived_key: K) -> crate::Result<bool> {
+        self.index.derived_key_may_exist(derived_key)
+    }
+
+    fn key_may_exist<K: AsRef<[u8]>>(&self, key: K) -> crate::Result<bool> {
+        self.index.key_may_exist(key)
+    }
+
     fn level_table_count(&self, idx: usize) -> Option<usize> {
         self.index.level_table_count(idx)
     }
@@ -634,6 +1212,50 @@ impl AbstractTree for BlobTree {
         self.index.disk_space() + version.blob_files.on_disk_size()
     }
 
+    fn sync(&self) -> crate::Result<()> {
+        self.index.sync()
+    }
+
+    fn disk_usage(&self) -> crate::Result<crate::DiskUsage> {
+        let mut usage = self.index.disk_usage()?;
+        usage.blob_bytes = self.current_version().blob_files.on_disk_size();
+        Ok(usage)
+    }
+
+    fn memory_usage(&self) -> crate::MemoryUsage {
+        let mut usage = self.index.memory_usage();
+
+        let config = &self.index.config;
+        if !Arc::ptr_eq(config.blob_cache(), &config.cache) {
+            usage.blob_cache_bytes = config.blob_cache().size();
+        }
+
+        usage
+    }
+
+    fn approximate_size<K: AsRef<[u8]>, R: RangeBounds<K>>(&self, range: R) -> crate::Result<u64> {
+        let index_size = self.index.approximate_size(range)?;
+
+        // NOTE: Blob files are not ordered by key, so we assume values are spread
+        // across the key space the same way their index entries are
+        let total_index_size = self.index.disk_space();
+        if total_index_size == 0 {
+            return Ok(0);
+        }
+
+        let blob_size = self.current_version().blob_files.on_disk_size();
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss,
+            reason = "this is an estimate"
+        )]
+        let blob_share = (blob_size as f64 * (index_size as f64 / total_index_size as f64)) as u64;
+
+        Ok(index_size + blob_share)
+    }
+
     fn get_highest_memtable_seqno(&self) -> Option<SeqNo> {
         self.index.get_highest_memtable_seqno()
     }
@@ -642,13 +1264,23 @@ impl AbstractTree for BlobTree {
         self.index.get_highest_persisted_seqno()
     }
 
-    fn insert<K: Into<UserKey>, V: Into<UserValue>>(
+    fn try_insert<K: Into<UserKey>, V: Into<UserValue>>(
         &self,
         key: K,
         value: V,
         seqno: SeqNo,
-    ) -> (u64, u64) {
-        self.index.insert(key, value.into(), seqno)
+    ) -> crate::Result<(u64, u64)> {
+        self.index.try_insert(key, value.into(), seqno)
+    }
+
+    fn try_insert_with_ttl<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+        ttl: std::time::Duration,
+    ) -> crate::Result<(u64, u64)> {
+        self.index.try_insert_with_ttl(key, value, seqno, ttl)
     }
 
     fn get<K: AsRef<[u8]>>(&self, key: K, seqno: SeqNo) -> crate::Result<Option<crate::UserValue>> {
@@ -662,8 +1294,8 @@ impl AbstractTree for BlobTree {
 
         let (_, v) = resolve_value_handle(
             self.id(),
-            self.blobs_folder.as_path(),
-            &self.index.config.cache,
+            &*self.index.config.fs,
+            self.index.config.blob_cache(),
             &self.index.config.descriptor_table,
             &version,
             item,
@@ -672,11 +1304,242 @@ impl AbstractTree for BlobTree {
         Ok(Some(v))
     }
 
-    fn remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u64, u64) {
-        self.index.remove(key, seqno)
+    fn multi_get<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(
+        &self,
+        keys: I,
+        seqno: SeqNo,
+    ) -> crate::Result<Vec<Option<UserValue>>> {
+        let super_version = self.index.get_version_for_snapshot(seqno);
+
+        let items = keys
+            .into_iter()
+            .map(|key| {
+                self.index
+                    .get_internal_entry_in(&super_version, key.as_ref(), seqno)
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let indirections = items
+            .iter()
+            .flatten()
+            .filter(|item| item.key.value_type.is_indirection())
+            .map(|item| {
+                let mut cursor = Cursor::new(&item.value);
+                Ok((
+                    &*item.key.user_key,
+                    BlobIndirection::decode_from(&mut cursor)?.vhandle,
+                ))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let mut blobs = Accessor::new(&super_version.version.blob_files)
+            .multi_get(
+                self.id(),
+                &indirections
+                    .iter()
+                    .map(|(key, vhandle)| (*key, vhandle))
+                    .collect::<Vec<_>>(),
+                &*self.index.config.fs,
+                self.index.config.blob_cache(),
+                &self.index.config.descriptor_table,
+            )?
+            .into_iter()
+            .zip(&indirections);
+
+        items
+            .iter()
+            .map(|item| {
+                let Some(item) = item else {
+                    return Ok(None);
+                };
+
+                if !item.key.value_type.is_indirection() {
+                    return Ok(Some(item.value.clone()));
+                }
+
+                match blobs.next() {
+                    Some((Some(value), _)) => Ok(Some(value)),
+                    Some((None, (key, vhandle))) => {
+                        log::error!(
+                            "Value handle ({key:?} => {vhandle:?}) did not match any blob; version={}",
+                            super_version.version.id(),
+                        );
+                        Err(crate::Error::Corrupted("BlobIndirection"))
+                    }
+                    None => unreachable!("every indirection should be resolved"),
+                }
+            })
+            .collect()
+    }
+
+    fn get_versions<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        limit: usize,
+    ) -> crate::Result<Vec<(SeqNo, crate::ValueType, UserValue)>> {
+        let versions = self.index.get_internal_versions(key.as_ref(), limit)?;
+        let version = self.current_version();
+
+        versions
+            .into_iter()
+            .map(|item| {
+                let seqno = item.key.seqno;
+
+                // NOTE: Indirections are an implementation detail, so the value is resolved
+                let value_type = if item.key.value_type.is_indirection() {
+                    crate::ValueType::Value
+                } else {
+                    item.key.value_type
+                };
+
+                if item.is_tombstone() {
+                    return Ok((seqno, value_type, item.value));
+                }
+
+                let (_, value) = resolve_value_handle(
+                    self.id(),
+                    &*self.index.config.fs,
+                    self.index.config.blob_cache(),
+                    &self.index.config.descriptor_table,
+                    &version,
+                    item,
+                )?;
+
+                Ok((seqno, value_type, value))
+            })
+            .collect()
+    }
+
+    fn try_add_merge<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        operand: V,
+        seqno: SeqNo,
+    ) -> crate::Result<(u64, u64)> {
+        // NOTE: Merge operators are not supported with key-value separation, so
+        // the operand is stored like a value, and the newest operand is read as value
+        self.index.try_add_merge(key, operand, seqno)
+    }
+
+    fn try_remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u64, u64)> {
+        self.index.try_remove(key, seqno)
+    }
+
+    fn remove_if<K: Into<UserKey>, V: AsRef<[u8]>>(
+        &self,
+        key: K,
+        expected: V,
+        seqno: SeqNo,
+    ) -> crate::Result<bool> {
+        let key = key.into();
+
+        let _write_lock = self.index.write_lock.write().expect("lock is poisoned");
+
+        // NOTE: Values are compared after resolving their blob indirection
+        if self
+            .get(&key, SeqNo::MAX)?
+            .is_none_or(|value| *value != *expected.as_ref())
+        {
+            return Ok(false);
+        }
+
+        self.index
+            .write_entry(InternalValue::new_tombstone(key, seqno))?;
+
+        Ok(true)
+    }
+
+    fn compare_and_swap<K: Into<UserKey>, E: AsRef<[u8]>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        expected: Option<E>,
+        new: Option<V>,
+    ) -> crate::Result<bool> {
+        let key = key.into();
+
+        let _write_lock = self.index.write_lock.write().expect("lock is poisoned");
+
+        // NOTE: Values are compared after resolving their blob indirection
+        let current = self.get(&key, SeqNo::MAX)?;
+
+        let is_match = match (&current, &expected) {
+            (Some(current), Some(expected)) => **current == *expected.as_ref(),
+            (None, None) => true,
+            _ => false,
+        };
+
+        if !is_match {
+            return Ok(false);
+        }
+
+        match new {
+            Some(value) => {
+                let seqno = self.index.config.seqno.next();
+                self.index.write_entry(InternalValue::from_components(
+                    key,
+                    value,
+                    seqno,
+                    ValueType::Value,
+                ))?;
+            }
+            None if current.is_some() => {
+                let seqno = self.index.config.seqno.next();
+                self.index
+                    .write_entry(InternalValue::new_tombstone(key, seqno))?;
+            }
+            None => {}
+        }
+
+        Ok(true)
+    }
+
+    fn fetch_update<K: Into<UserKey>, F: FnOnce(Option<&UserValue>) -> Option<UserValue>>(
+        &self,
+        key: K,
+        f: F,
+    ) -> crate::Result<Option<UserValue>> {
+        let key = key.into();
+
+        let _write_lock = self.index.write_lock.write().expect("lock is poisoned");
+
+        let current = self.get(&key, SeqNo::MAX)?;
+
+        match f(current.as_ref()) {
+            Some(value) => {
+                let seqno = self.index.config.seqno.next();
+                self.index.write_entry(InternalValue::from_components(
+                    key,
+                    value,
+                    seqno,
+                    ValueType::Value,
+                ))?;
+            }
+            None if current.is_some() => {
+                let seqno = self.index.config.seqno.next();
+                self.index
+                    .write_entry(InternalValue::new_tombstone(key, seqno))?;
+            }
+            None => {}
+        }
+
+        Ok(current)
+    }
+
+    fn try_remove_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+    ) -> crate::Result<(u64, u64)> {
+        self.index.try_remove_range(range, seqno)
+    }
+
+    fn try_remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u64, u64)> {
+        self.index.try_remove_weak(key, seqno)
     }
 
-    fn remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u64, u64) {
-        self.index.remove_weak(key, seqno)
+    fn batch(&self) -> crate::Batch {
+        // NOTE: Values are separated when the memtable is flushed, so the batch
+        // can be written into the index tree's memtable directly
+        self.index.batch()
     }
 }
@@ -0,0 +1,124 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{resolve_value_handle, BlobTree, Guard};
+use crate::{
+    iter_guard::IterGuardImpl, range::prefix_to_range, version::SuperVersion, AbstractTree, SeqNo,
+    UserKey, UserValue,
+};
+use std::ops::RangeBounds;
+
+/// A consistent, read-only view of a [`BlobTree`] at some sequence number
+///
+/// The snapshot holds on to the tree version (memtables, tables and blob files) that was
+/// current at its sequence number, so blob files it may read from are not deleted,
+/// even if they are rewritten by compaction or garbage collection, until the snapshot is dropped.
+///
+/// Holding on to snapshots for a long time prevents disk space from being reclaimed.
+pub struct Snapshot {
+    tree: BlobTree,
+    version: SuperVersion,
+    seqno: SeqNo,
+}
+
+impl Snapshot {
+    pub(super) fn new(tree: BlobTree, seqno: SeqNo) -> Self {
+        let version = tree.index.get_version_for_snapshot(seqno);
+
+        Self {
+            tree,
+            version,
+            seqno,
+        }
+    }
+
+    /// Returns the sequence number of the snapshot.
+    ///
+    /// The snapshot only sees writes with a lower sequence number.
+    #[must_use]
+    pub fn seqno(&self) -> SeqNo {
+        self.seqno
+    }
+
+    /// Retrieves an item from the snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> crate::Result<Option<UserValue>> {
+        let Some(item) =
+            self.tree
+                .index
+                .get_internal_entry_in(&self.version, key.as_ref(), self.seqno)?
+        else {
+            return Ok(None);
+        };
+
+        let (_, value) = resolve_value_handle(
+            self.tree.id(),
+            &*self.tree.index.config.fs,
+            self.tree.index.config.blob_cache(),
+            &self.tree.index.config.descriptor_table,
+            &self.version.version,
+            item,
+        )?;
+
+        Ok(Some(value))
+    }
+
+    /// Returns `true` if the snapshot contains the specified key.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn contains_key<K: AsRef<[u8]>>(&self, key: K) -> crate::Result<bool> {
+        Ok(self
+            .tree
+            .index
+            .get_internal_entry_in(&self.version, key.as_ref(), self.seqno)?
+            .is_some())
+    }
+
+    /// Returns an iterator over a range of items of the snapshot.
+    #[must_use]
+    pub fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static> {
+        let tree = self.tree.clone();
+        let version = self.version.version.clone();
+
+        Box::new(
+            self.tree
+                .index
+                .create_internal_range_in(self.version.clone(), &range, self.seqno, None)
+                .map(move |kv| {
+                    IterGuardImpl::Blob(Guard {
+                        tree: tree.clone(),
+                        version: version.clone(),
+                        kv,
+                    })
+                }),
+        )
+    }
+
+    /// Returns an iterator over all items of the snapshot with the given prefix.
+    #[must_use]
+    pub fn prefix<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+    ) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static> {
+        self.range(prefix_to_range(prefix.as_ref()))
+    }
+
+    /// Returns an iterator over all items of the snapshot.
+    #[must_use]
+    #[expect(
+        clippy::iter_without_into_iter,
+        reason = "the items are guards, like the iterators of the tree itself"
+    )]
+    pub fn iter(&self) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static> {
+        self.range::<UserKey, _>(..)
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Space statistics of a [`BlobTree`](super::BlobTree), see [`BlobTree::stats`](super::BlobTree::stats)
+///
+/// All sizes are on-disk sizes, so they are affected by blob compression.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Stats {
+    /// Number of blob files in the value log
+    pub blob_file_count: usize,
+
+    /// Size of all blob files
+    pub blob_bytes: u64,
+
+    /// Size of blobs that are not referenced by the index tree anymore,
+    /// and can be freed by garbage collection
+    pub stale_blob_bytes: u64,
+
+    /// Size of the tables of the index tree
+    pub index_bytes: u64,
+}
+
+impl Stats {
+    /// Returns the size of blobs that are still referenced by the index tree.
+    #[must_use]
+    pub fn live_blob_bytes(&self) -> u64 {
+        self.blob_bytes.saturating_sub(self.stale_blob_bytes)
+    }
+
+    /// Returns the ratio of stale blob bytes in the value log, in [0, 1].
+    ///
+    /// Returns 0.0 if the value log is empty.
+    #[must_use]
+    pub fn stale_ratio(&self) -> f32 {
+        if self.blob_bytes == 0 {
+            return 0.0;
+        }
+
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "ratio does not need to be exact"
+        )]
+        let ratio = self.stale_blob_bytes.min(self.blob_bytes) as f32 / self.blob_bytes as f32;
+
+        ratio
+    }
+
+    /// Returns the space amplification of the value log
+    /// (size of all blob files divided by the size of live blobs).
+    ///
+    /// This is the metric that [`BlobTree::gc`](super::BlobTree::gc) targets.
+    ///
+    /// Returns 1.0 if the value log is empty.
+    #[must_use]
+    pub fn space_amp(&self) -> f32 {
+        if self.blob_bytes == 0 {
+            return 1.0;
+        }
+
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "ratio does not need to be exact"
+        )]
+        let space_amp = self.blob_bytes as f32 / self.live_blob_bytes().max(1) as f32;
+
+        space_amp
+    }
+
+    /// Returns the size of the index tree and value log.
+    #[must_use]
+    pub fn disk_space(&self) -> u64 {
+        self.index_bytes + self.blob_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn blob_tree_stats_empty() {
+        let stats = Stats::default();
+        assert_eq!(0, stats.live_blob_bytes());
+        assert!(stats.stale_ratio().abs() < f32::EPSILON);
+        assert!((stats.space_amp() - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn blob_tree_stats_ratios() {
+        let stats = Stats {
+            blob_file_count: 2,
+            blob_bytes: 1_000,
+            stale_blob_bytes: 750,
+            index_bytes: 100,
+        };
+        assert_eq!(250, stats.live_blob_bytes());
+        assert!((stats.stale_ratio() - 0.75).abs() < f32::EPSILON);
+        assert!((stats.space_amp() - 4.0).abs() < f32::EPSILON);
+        assert_eq!(1_100, stats.disk_space());
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{handle::BlobIndirection, BlobTree};
+use crate::{
+    coding::Decode, vlog::blob_file::reader::Reader, AbstractTree, InternalValue, SeqNo, UserKey,
+};
+use std::{io::Cursor, path::PathBuf};
+
+/// Result of [`BlobTree::verify`]
+///
+/// Blobs are identified by the key and sequence number of the index entry that points to them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VerifyReport {
+    /// Number of blobs that were read and checked
+    pub checked_blob_count: usize,
+
+    /// Index entries whose value handle does not point into any blob file
+    /// of the current version, or that cannot be decoded
+    pub dangling_handles: Vec<(UserKey, SeqNo)>,
+
+    /// Blobs that could not be read back, e.g. because of a checksum mismatch
+    pub corrupted_blobs: Vec<(UserKey, SeqNo)>,
+
+    /// Files in the blobs folder that are not part of the current version
+    pub orphaned_blob_files: Vec<PathBuf>,
+}
+
+impl VerifyReport {
+    /// Returns `true` if no problems were found.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.dangling_handles.is_empty()
+            && self.corrupted_blobs.is_empty()
+            && self.orphaned_blob_files.is_empty()
+    }
+}
+
+fn is_corruption(e: &crate::Error) -> bool {
+    match e {
+        crate::Error::Decompress(_)
+        | crate::Error::ChecksumMismatch { .. }
+        | crate::Error::InvalidHeader(_)
+        | crate::Error::Corrupted(_) => true,
+        crate::Error::Io(e) => matches!(
+            e.kind(),
+            std::io::ErrorKind::InvalidData | std::io::ErrorKind::UnexpectedEof,
+        ),
+        _ => false,
+    }
+}
+
+pub(super) fn verify(tree: &BlobTree) -> crate::Result<VerifyReport> {
+    let super_version = tree.index.get_version_for_snapshot(SeqNo::MAX);
+    let version = &super_version.version;
+
+    let mut report = VerifyReport::default();
+
+    let mut check = |item: InternalValue| -> crate::Result<()> {
+        if !item.key.value_type.is_indirection() {
+            return Ok(());
+        }
+
+        let id = (item.key.user_key.clone(), item.key.seqno);
+
+        let Ok(indirection) = BlobIndirection::decode_from(&mut Cursor::new(&item.value)) else {
+            report.dangling_handles.push(id);
+            return Ok(());
+        };
+        let vhandle = indirection.vhandle;
+
+        let Some(blob_file) = version.blob_files.get(vhandle.blob_file_id) else {
+            report.dangling_handles.push(id);
+            return Ok(());
+        };
+
+        // NOTE: Bypass the blob cache and descriptor table, we want to read from disk
+        let file = tree.index.config.fs.open(blob_file.path())?;
+
+        match Reader::new(blob_file, &*file).get(&item.key.user_key, &vhandle) {
+            Ok(value) if value.len() == indirection.size as usize => {}
+            Ok(_) => report.corrupted_blobs.push(id),
+            Err(e) if is_corruption(&e) => {
+                log::error!("Blob {vhandle:?} of {id:?} is corrupted: {e:?}");
+                report.corrupted_blobs.push(id);
+            }
+            Err(e) => return Err(e),
+        }
+
+        report.checked_blob_count += 1;
+
+        Ok(())
+    };
+
+    for item in super_version.active_memtable.iter() {
+        check(item)?;
+    }
+
+    for (_, memtable) in super_version.sealed_memtables.iter() {
+        for item in memtable.iter() {
+            check(item)?;
+        }
+    }
+
+    for table in version.iter_tables() {
+        for item in table.scan()? {
+            check(item?)?;
+        }
+    }
+
+    let fs = &*tree.index.config.fs;
+
+    if fs.exists(&tree.blobs_folder)? {
+        for path in crate::file::read_sharded_dir(fs, &tree.blobs_folder)? {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+            // NOTE: Same as recovery, see `crate::vlog::recover_blob_files`
+            if file_name == ".DS_Store" || file_name.starts_with("._") {
+                continue;
+            }
+
+            let is_live = file_name
+                .parse()
+                .is_ok_and(|blob_file_id| version.blob_files.contains_key(blob_file_id));
+
+            if !is_live {
+                report.orphaned_blob_files.push(path);
+            }
+        }
+    }
+
+    log::debug!(
+        "Verified {} blobs of tree #{}: {} dangling, {} corrupted, {} orphaned blob files",
+        report.checked_blob_count,
+        tree.id(),
+        report.dangling_handles.len(),
+        report.corrupted_blobs.len(),
+        report.orphaned_blob_files.len(),
+    );
+
+    Ok(report)
+}
@@ -0,0 +1,193 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    journal::{entry::Entry, reader::Reader, Journal, JournalFileId},
+    SeqNo, UserKey, UserValue, ValueType,
+};
+use std::{sync::Arc, time::Duration};
+
+/// Type of a change
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ChangeOp {
+    /// A value was inserted (or overwritten)
+    Insert,
+
+    /// A key was removed
+    Remove,
+
+    /// A key was removed using a weak tombstone
+    RemoveWeak,
+
+    /// A range of keys was removed, see [`crate::AbstractTree::remove_range`]
+    ///
+    /// The key of the event is the (inclusive) start of the range, and the value
+    /// is the (exclusive) end, which is empty if the range is unbounded.
+    RemoveRange,
+
+    /// A merge operand was added, see [`crate::AbstractTree::add_merge`]
+    Merge,
+
+    /// A value with a TTL was inserted (or overwritten), see [`crate::AbstractTree::insert_with_ttl`]
+    ///
+    /// Holds the expiry timestamp (as duration since the unix epoch).
+    InsertWithTtl(Duration),
+}
+
+/// A committed write, as read from the journal
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChangeEvent {
+    /// Sequence number of the write
+    pub seqno: SeqNo,
+
+    /// Written key
+    pub key: UserKey,
+
+    /// Type of change
+    pub op: ChangeOp,
+
+    /// Written value (empty for removals)
+    pub value: UserValue,
+}
+
+/// Change data capture reader
+///
+/// Reads committed writes from the journal, in the order they were written,
+/// starting at some sequence number.
+///
+/// When caught up with the journal, the iterator returns `None`, but can
+/// be polled again later to receive new writes.
+///
+/// Journal files are retained (even after their data is flushed) until all
+/// live readers have acknowledged their entries using [`CdcReader::ack`].
+pub struct CdcReader {
+    journal: Arc<Journal>,
+    cursor_id: u64,
+    from_seqno: SeqNo,
+    file_id: Option<JournalFileId>,
+    reader: Option<Reader>,
+}
+
+impl CdcReader {
+    pub(crate) fn new(journal: Arc<Journal>, from_seqno: SeqNo) -> Self {
+        let cursor_id = journal.register_cursor(from_seqno.checked_sub(1));
+
+        Self {
+            journal,
+            cursor_id,
+            from_seqno,
+            file_id: None,
+            reader: None,
+        }
+    }
+
+    /// Acknowledges that all writes up to (and including) `seqno` have been consumed,
+    /// so the journal does not need to retain them for this reader anymore.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn ack(&self, seqno: SeqNo) -> crate::Result<()> {
+        self.journal.ack(self.cursor_id, seqno)
+    }
+
+    /// Opens the next journal file, returns `false` if there is none.
+    fn open_next_file(&mut self) -> crate::Result<bool> {
+        let next_ids = self
+            .journal
+            .file_ids()
+            .into_iter()
+            .filter(|id| self.file_id.is_none_or(|current| *id > current));
+
+        for id in next_ids {
+            match self.journal.open_reader(id) {
+                Ok(reader) => {
+                    self.file_id = Some(id);
+                    self.reader = Some(reader);
+                    return Ok(true);
+                }
+                Err(crate::Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                    // NOTE: The journal file was deleted in the meantime,
+                    // which means we do not need its data
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl Iterator for CdcReader {
+    type Item = crate::Result<ChangeEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(reader) = &mut self.reader else {
+                if fail_iter!(self.open_next_file()) {
+                    continue;
+                }
+                return None;
+            };
+
+            // NOTE: Check before reading, because once a journal file is sealed,
+            // it will not be appended to anymore
+            let is_sealed = self.file_id != Some(self.journal.active_file_id());
+
+            let Some(entry) = fail_iter!(reader.next_entry()) else {
+                if is_sealed {
+                    self.reader = None;
+                    continue;
+                }
+
+                // NOTE: We are caught up with the active journal file
+                return None;
+            };
+
+            if entry.seqno() < self.from_seqno {
+                continue;
+            }
+
+            let item = match entry {
+                Entry::Value(item) => item,
+                Entry::RangeTombstone(rt) => {
+                    return Some(Ok(ChangeEvent {
+                        seqno: rt.seqno,
+                        key: rt.start,
+                        op: ChangeOp::RemoveRange,
+                        value: rt.end.unwrap_or_else(UserValue::empty),
+                    }));
+                }
+            };
+
+            let (op, value) = match item.key.value_type {
+                ValueType::Value | ValueType::Indirection => (ChangeOp::Insert, item.value),
+                ValueType::Tombstone => (ChangeOp::Remove, item.value),
+                ValueType::WeakTombstone => (ChangeOp::RemoveWeak, item.value),
+                ValueType::MergeOperand => (ChangeOp::Merge, item.value),
+                ValueType::ExpiringValue => {
+                    let (expires_at, value) = fail_iter!(crate::ttl::decode_value(&item.value)
+                        .ok_or_else(|| {
+                            crate::Error::InvalidTag(("ExpiringValue", item.key.value_type.into()))
+                        }));
+
+                    (ChangeOp::InsertWithTtl(expires_at), value)
+                }
+            };
+
+            return Some(Ok(ChangeEvent {
+                seqno: item.key.seqno,
+                key: item.key.user_key,
+                op,
+                value,
+            }));
+        }
+    }
+}
+
+impl Drop for CdcReader {
+    fn drop(&mut self) {
+        self.journal.unregister_cursor(self.cursor_id);
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{Choice, CompactionStrategy, Input as CompactionInput};
+use crate::{
+    compaction::state::CompactionState, config::Config, table::Table, version::Version,
+    vlog::BlobFileId, HashSet, KeyRange,
+};
+
+/// Compacts the tables that reference a blob file, so the blob file can be rewritten
+///
+/// A blob file can only be rewritten if all tables that reference it are compacted
+/// together. If those tables are spread across levels, all overlapping tables of the levels
+/// in between are compacted as well, and the result is written into the deepest level.
+pub struct Strategy {
+    /// Blob file to rewrite
+    blob_file_id: BlobFileId,
+}
+
+impl Strategy {
+    /// Configures a new `BlobGc` compaction strategy.
+    #[must_use]
+    pub fn new(blob_file_id: BlobFileId) -> Self {
+        Self { blob_file_id }
+    }
+
+    /// Returns the tables that reference a blob file.
+    fn referencing_tables(
+        version: &Version,
+        blob_file_id: BlobFileId,
+    ) -> crate::Result<Vec<(u8, &Table)>> {
+        let mut tables = vec![];
+
+        for (level_idx, level) in version.iter_levels().enumerate() {
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "there are less than 256 levels"
+            )]
+            let level_idx = level_idx as u8;
+
+            for table in level.iter().flat_map(|run| run.iter()) {
+                let references = table.list_blob_file_references()?.unwrap_or_default();
+
+                if references.iter().any(|x| x.blob_file_id == blob_file_id) {
+                    tables.push((level_idx, table));
+                }
+            }
+        }
+
+        Ok(tables)
+    }
+
+    fn choose_for_blob_file(
+        version: &Version,
+        state: &CompactionState,
+        blob_file_id: BlobFileId,
+    ) -> crate::Result<Option<Choice>> {
+        let referencing_tables = Self::referencing_tables(version, blob_file_id)?;
+
+        // NOTE: Unreferenced blob files are dropped anyway
+        let (Some(min_level), Some(max_level)) = (
+            referencing_tables.iter().map(|(idx, _)| *idx).min(),
+            referencing_tables.iter().map(|(idx, _)| *idx).max(),
+        ) else {
+            return Ok(None);
+        };
+
+        let mut table_ids: HashSet<_> = referencing_tables
+            .iter()
+            .map(|(_, table)| table.id())
+            .collect();
+
+        let mut key_range = KeyRange::aggregate(
+            referencing_tables
+                .iter()
+                .map(|(_, table)| &table.metadata.key_range),
+        );
+
+        // NOTE: Pull in overlapping tables until the key range is stable,
+        // so no newer version of a key stays above the destination level
+        loop {
+            let mut added = vec![];
+
+            for (level_idx, level) in version
+                .iter_levels()
+                .enumerate()
+                .skip(min_level.into())
+                .take((max_level - min_level + 1).into())
+            {
+                let is_l0 = level_idx == 0;
+
+                for table in level.iter().flat_map(|run| run.iter()) {
+                    if table_ids.contains(&table.id()) {
+                        continue;
+                    }
+
+                    // NOTE: L0 runs may overlap, so L0 is always compacted as a whole
+                    if is_l0 || table.metadata.key_range.overlaps_with_key_range(&key_range) {
+                        added.push(table);
+                    }
+                }
+            }
+
+            if added.is_empty() {
+                break;
+            }
+
+            for table in added {
+                table_ids.insert(table.id());
+                key_range =
+                    KeyRange::aggregate([&key_range, &table.metadata.key_range].into_iter());
+            }
+        }
+
+        if state.hidden_set().is_blocked(table_ids.iter().copied()) {
+            return Ok(Some(Choice::DoNothing));
+        }
+
+        // NOTE: Keep the tables roughly as big as they were
+        let target_size = version
+            .iter_tables()
+            .filter(|table| table_ids.contains(&table.id()))
+            .map(Table::file_size)
+            .max()
+            .unwrap_or(u64::MAX);
+
+        Ok(Some(Choice::Merge(CompactionInput {
+            table_ids,
+            dest_level: max_level,
+            canonical_level: max_level,
+            target_size,
+        })))
+    }
+}
+
+impl CompactionStrategy for Strategy {
+    fn get_name(&self) -> &'static str {
+        "BlobGcCompaction"
+    }
+
+    fn choose(&self, version: &Version, _: &Config, state: &CompactionState) -> Choice {
+        let blob_file_id = self.blob_file_id;
+
+        if !version.blob_files.contains_key(blob_file_id) {
+            return Choice::DoNothing;
+        }
+
+        match Self::choose_for_blob_file(version, state, blob_file_id) {
+            Ok(choice) => choice.unwrap_or(Choice::DoNothing),
+            Err(e) => {
+                log::warn!("Failed to list references of blob file {blob_file_id}: {e:?}");
+                Choice::DoNothing
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{drop_range::OwnedBounds, Choice, CompactionStrategy, Input as CompactionInput};
+use crate::{
+    compaction::state::CompactionState, config::Config, version::Version, HashSet, KeyRange, Table,
+};
+use std::ops::RangeBounds;
+
+/// Compacts all tables that overlap with a key range into the last level
+pub struct Strategy {
+    bounds: OwnedBounds,
+    target_size: u64,
+}
+
+impl Strategy {
+    /// Configures a new `CompactRange` compaction strategy.
+    #[must_use]
+    pub fn new(bounds: OwnedBounds, target_size: u64) -> Self {
+        Self {
+            bounds,
+            target_size,
+        }
+    }
+}
+
+impl CompactionStrategy for Strategy {
+    fn get_name(&self) -> &'static str {
+        "CompactRangeCompaction"
+    }
+
+    fn choose(&self, version: &Version, cfg: &Config, state: &CompactionState) -> Choice {
+        let bounds = (
+            self.bounds.start_bound().map(AsRef::as_ref),
+            self.bounds.end_bound().map(AsRef::as_ref),
+        );
+
+        let mut tables: Vec<&Table> = version
+            .iter_tables()
+            .filter(|table| table.metadata.key_range.overlaps_with_bounds(&bounds))
+            .collect();
+
+        if tables.is_empty() {
+            return Choice::DoNothing;
+        }
+
+        // NOTE: Tombstones are evicted when writing into the last level, so we need to
+        // merge all versions of every key the merged tables contain, otherwise we would
+        // resurrect data. Tables that overlap the range only partially widen the key range,
+        // so keep adding overlapping tables until the key range does not grow anymore.
+        loop {
+            let key_range =
+                KeyRange::aggregate(tables.iter().map(|table| &table.metadata.key_range));
+
+            let overlapping: Vec<&Table> = version
+                .iter_tables()
+                .filter(|table| table.metadata.key_range.overlaps_with_key_range(&key_range))
+                .collect();
+
+            if overlapping.len() == tables.len() {
+                break;
+            }
+
+            tables = overlapping;
+        }
+
+        let table_ids: HashSet<_> = tables.iter().map(|table| table.id()).collect();
+
+        // NOTE: This should generally not occur because of the
+        // tree-level major compaction lock
+        // But just as a fail-safe...
+        let some_hidden = table_ids.iter().any(|&id| state.hidden_set().is_hidden(id));
+
+        if some_hidden {
+            Choice::DoNothing
+        } else {
+            let last_level_idx = cfg.level_count - 1;
+
+            Choice::Merge(CompactionInput {
+                table_ids,
+                dest_level: last_level_idx,
+                canonical_level: last_level_idx,
+                target_size: self.target_size,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Strategy;
+    use crate::{
+        compaction::{drop_range::OwnedBounds, PullDown},
+        AbstractTree, Config, SequenceNumberCounter, Slice,
+    };
+    use std::{ops::Bound, sync::Arc};
+    use test_log::test;
+
+    fn bounds(lo: &str, hi: &str) -> OwnedBounds {
+        OwnedBounds {
+            start: Bound::Included(Slice::from(lo)),
+            end: Bound::Excluded(Slice::from(hi)),
+        }
+    }
+
+    #[test]
+    fn compact_range_no_overlap() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tree = Config::new(dir.path(), SequenceNumberCounter::default()).open()?;
+
+        tree.insert("a", "v", 0);
+        tree.flush_active_memtable(0)?;
+
+        tree.compact(Arc::new(Strategy::new(bounds("b", "c"), u64::MAX)), 0)?;
+
+        assert_eq!(Some(1), tree.level_table_count(0));
+        Ok(())
+    }
+
+    #[test]
+    fn compact_range_widens_key_range() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tree = Config::new(dir.path(), SequenceNumberCounter::default()).open()?;
+
+        // [a, c] in the last level
+        tree.insert("a", "v", 0);
+        tree.insert("c", "v", 1);
+        tree.flush_active_memtable(0)?;
+        tree.compact(Arc::new(PullDown(0, 6)), 0)?;
+
+        // [b, d] and [x, y] in L0
+        tree.insert("b", "v", 2);
+        tree.insert("d", "v", 3);
+        tree.flush_active_memtable(0)?;
+        tree.insert("x", "v", 4);
+        tree.insert("y", "v", 5);
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Only [b, d] overlaps with the range, but it pulls in [a, c]
+        tree.compact(Arc::new(Strategy::new(bounds("d", "e"), u64::MAX)), 0)?;
+
+        assert_eq!(Some(1), tree.level_table_count(0));
+        assert_eq!(Some(1), tree.level_table_count(6));
+        assert_eq!(6, tree.len(crate::SeqNo::MAX, None)?);
+
+        Ok(())
+    }
+}
@@ -4,8 +4,7 @@
 
 use super::{Choice, CompactionStrategy};
 use crate::{
-    compaction::state::CompactionState, config::Config, time::unix_timestamp, version::Version,
-    HashSet, KvPair,
+    compaction::state::CompactionState, config::Config, version::Version, HashSet, KvPair,
 };
 
 #[doc(hidden)]
@@ -28,6 +27,32 @@ pub const NAME: &str = "FifoCompaction";
 /// 1) You only want to store recent data (unimportant logs, ...)
 /// 2) Your keyspace grows monotonically (e.g. time series)
 /// 3) You only insert new data (no updates)
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{compaction::Fifo, AbstractTree, Config, SequenceNumberCounter};
+/// use std::sync::Arc;
+///
+/// // Keep metrics for a week, but never more than 10 GiB
+/// let retention = Fifo::new(10 * 1_024 * 1_024 * 1_024, Some(7 * 24 * 60 * 60));
+///
+/// let seqno = SequenceNumberCounter::default();
+/// let tree = Config::new(folder, seqno.clone())
+///     .compaction_strategy(Arc::new(retention))
+///     .open()?;
+///
+/// let timestamp = 1_700_000_000u64;
+/// tree.insert(timestamp.to_be_bytes(), "cpu=0.7", seqno.next());
+/// tree.flush_active_memtable(0)?;
+///
+/// // NOTE: Tables are only ever dropped as a whole, never rewritten
+/// tree.run_compaction(seqno.get())?;
+/// assert_eq!(1, tree.table_count());
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
 #[derive(Clone)]
 pub struct Strategy {
     /// Data set size limit in bytes
@@ -71,7 +96,7 @@ impl CompactionStrategy for Strategy {
         ]
     }
 
-    fn choose(&self, version: &Version, _: &Config, state: &CompactionState) -> Choice {
+    fn choose(&self, version: &Version, config: &Config, state: &CompactionState) -> Choice {
         let first_level = version.l0();
 
         // Early return avoids unnecessary work and keeps FIFO a no-op when there is nothing to do.
@@ -95,7 +120,9 @@ impl CompactionStrategy for Strategy {
         // accumulate their sizes. Also collect non-expired tables for possible size-based drops.
         let ttl_cutoff = match self.ttl_seconds {
             Some(s) if s > 0 => Some(
-                unix_timestamp()
+                config
+                    .clock
+                    .now()
                     .as_nanos()
                     .saturating_sub(u128::from(s) * 1_000_000_000u128),
             ),
@@ -10,8 +10,10 @@ use crate::compaction::Input as CompactionPayload;
 use crate::file::TABLES_FOLDER;
 use crate::table::multi_writer::MultiWriter;
 use crate::version::{SuperVersions, Version};
+use crate::vlog::blob_file::{compress_blob, decompress_blob};
 use crate::vlog::{BlobFileId, BlobFileMergeScanner, BlobFileWriter};
-use crate::{BlobFile, HashSet, InternalValue, Table};
+use crate::{BlobFile, CompressionType, HashSet, InternalValue, Table};
+use std::borrow::Cow;
 use std::iter::Peekable;
 use std::time::Instant;
 
@@ -47,11 +49,17 @@ pub(super) fn prepare_table_writer(
     );
 
     let mut table_writer = MultiWriter::new(
+        opts.config.fs.clone(),
         table_base_folder,
+        opts.config.directory_shards,
         opts.table_id_generator.clone(),
         payload.target_size,
         payload.dest_level,
-    )?;
+    )?
+    .use_clock(opts.config.clock.clone())
+    .use_encryption(opts.config.encryptor.clone())
+    .use_key_extractor(opts.config.key_extractor.clone())
+    .use_rate_limiter(opts.config.rate_limiter.clone());
 
     if index_partitioning {
         table_writer = table_writer.use_partitioned_index();
@@ -210,10 +218,31 @@ impl CompactionFlavour for RelocatingCompaction {
 
                 log::trace!("RELOCATE to {indirection:?}");
 
-                self.blob_writer.write_raw(
+                let source_compression = self
+                    .rewriting_blob_files
+                    .iter()
+                    .find(|bf| bf.id() == blob_file_id)
+                    .map_or(CompressionType::None, |bf| bf.0.meta.compression);
+
+                let target_compression = self.blob_writer.passthrough_compression();
+
+                // NOTE: Blobs are passed through as-is, unless the blob file was written
+                // with another compression type (because the tree config was changed)
+                let value = if source_compression == target_compression {
+                    Cow::Borrowed(&*blob_entry.value)
+                } else {
+                    let value = decompress_blob(
+                        source_compression,
+                        blob_entry.value.clone(),
+                        blob_entry.uncompressed_len as usize,
+                    )?;
+                    Cow::Owned(compress_blob(target_compression, &value)?.into_owned())
+                };
+
+                indirection.vhandle.on_disk_size = self.blob_writer.write_raw(
                     &item.key.user_key,
                     item.key.seqno,
-                    &blob_entry.value,
+                    &value,
                     blob_entry.uncompressed_len,
                 )?;
 
@@ -269,25 +298,29 @@ impl CompactionFlavour for RelocatingCompaction {
         }
 
         super_version.upgrade_version(
+            &*opts.config.fs,
             &opts.config.path,
             |current| {
                 let mut copy = current.clone();
 
-                copy.version = copy.version.with_merge(
-                    &payload.table_ids.iter().copied().collect::<Vec<_>>(),
-                    &created_tables,
-                    payload.dest_level as usize,
-                    if blob_frag_map_diff.is_empty() {
-                        None
-                    } else {
-                        Some(blob_frag_map_diff)
-                    },
-                    created_blob_files,
-                    blob_files_to_drop
-                        .iter()
-                        .map(BlobFile::id)
-                        .collect::<HashSet<_>>(),
-                );
+                copy.version = copy
+                    .version
+                    .with_merge(
+                        &payload.table_ids.iter().copied().collect::<Vec<_>>(),
+                        &created_tables,
+                        payload.dest_level as usize,
+                        if blob_frag_map_diff.is_empty() {
+                            None
+                        } else {
+                            Some(blob_frag_map_diff)
+                        },
+                        created_blob_files,
+                        blob_files_to_drop
+                            .iter()
+                            .map(BlobFile::id)
+                            .collect::<HashSet<_>>(),
+                    )
+                    .with_obsolete_range_tombstones_dropped(opts.mvcc_gc_watermark)?;
 
                 Ok(copy)
             },
@@ -314,6 +347,9 @@ pub struct StandardCompaction {
     start: Instant,
     table_writer: MultiWriter,
     tables_to_rewrite: Vec<Table>,
+
+    /// Writers of subcompactions of the following key ranges
+    subcompaction_writers: Vec<MultiWriter>,
 }
 
 impl StandardCompaction {
@@ -322,6 +358,25 @@ impl StandardCompaction {
             start: Instant::now(),
             table_writer,
             tables_to_rewrite,
+            subcompaction_writers: Vec::new(),
+        }
+    }
+
+    /// Appends a subcompaction of the following key range,
+    /// so its tables are committed together with the tables of this compaction.
+    pub fn append_subcompaction(&mut self, other: Self) {
+        self.subcompaction_writers.push(other.table_writer);
+        self.subcompaction_writers
+            .extend(other.subcompaction_writers);
+        self.tables_to_rewrite.extend(other.tables_to_rewrite);
+    }
+
+    /// Deletes all tables written so far, including those of appended subcompactions.
+    pub fn abort(self) {
+        self.table_writer.abort();
+
+        for writer in self.subcompaction_writers {
+            writer.abort();
         }
     }
 
@@ -331,14 +386,25 @@ impl StandardCompaction {
         let pin_filter = opts.config.filter_block_pinning_policy.get(dst_lvl);
         let pin_index = opts.config.filter_block_pinning_policy.get(dst_lvl);
 
-        self.table_writer
-            .finish()?
+        let mut created_tables = self.table_writer.finish()?;
+
+        for writer in self.subcompaction_writers {
+            created_tables.extend(writer.finish()?);
+        }
+
+        created_tables
             .into_iter()
             .map(|(table_id, checksum)| -> crate::Result<Table> {
                 Table::recover(
-                    table_base_folder.join(table_id.to_string()),
+                    crate::file::sharded_path(
+                        &table_base_folder,
+                        table_id,
+                        opts.config.directory_shards,
+                    ),
                     checksum,
                     opts.tree_id,
+                    opts.config.fs.clone(),
+                    opts.config.encryptor.clone(),
                     opts.config.cache.clone(),
                     opts.config.descriptor_table.clone(),
                     pin_filter,
@@ -396,25 +462,29 @@ impl CompactionFlavour for StandardCompaction {
         }
 
         super_version.upgrade_version(
+            &*opts.config.fs,
             &opts.config.path,
             |current| {
                 let mut copy = current.clone();
 
-                copy.version = copy.version.with_merge(
-                    &payload.table_ids.iter().copied().collect::<Vec<_>>(),
-                    &created_tables,
-                    payload.dest_level as usize,
-                    if blob_frag_map.is_empty() {
-                        None
-                    } else {
-                        Some(blob_frag_map)
-                    },
-                    Vec::default(),
-                    blob_files_to_drop
-                        .iter()
-                        .map(BlobFile::id)
-                        .collect::<HashSet<_>>(),
-                );
+                copy.version = copy
+                    .version
+                    .with_merge(
+                        &payload.table_ids.iter().copied().collect::<Vec<_>>(),
+                        &created_tables,
+                        payload.dest_level as usize,
+                        if blob_frag_map.is_empty() {
+                            None
+                        } else {
+                            Some(blob_frag_map)
+                        },
+                        Vec::default(),
+                        blob_files_to_drop
+                            .iter()
+                            .map(BlobFile::id)
+                            .collect::<HashSet<_>>(),
+                    )
+                    .with_obsolete_range_tombstones_dropped(opts.mvcc_gc_watermark)?;
 
                 Ok(copy)
             },
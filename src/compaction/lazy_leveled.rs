@@ -0,0 +1,161 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{Choice, CompactionStrategy};
+use crate::{compaction::state::CompactionState, config::Config, version::Version, KvPair};
+
+#[doc(hidden)]
+pub const NAME: &str = "LazyLeveledCompaction";
+
+/// Lazy leveling compaction strategy (hybrid of tiering and leveling)
+///
+/// All levels except the last one are tiered like in [`SizeTiered`](super::SizeTiered):
+/// when a level has `level_ratio` runs, they are merged into a single run in the next level.
+///
+/// The last level is leveled: it is kept as a single sorted run, and runs are only merged
+/// with the tables of the last level that they overlap with.
+///
+/// Because most data lives in the last level, space amplification and read cost stay close
+/// to leveled compaction, while write amplification in the upper levels is much lower.
+///
+/// More info: "Dostoevsky: Better Space-Time Trade-Offs for LSM-Tree Based Key-Value Stores"
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{compaction::LazyLeveled, Config};
+/// use std::sync::Arc;
+///
+/// let tree = Config::new(folder, Default::default())
+///     .compaction_strategy(Arc::new(LazyLeveled::default()))
+///     .open()?;
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Strategy {
+    /// Number of runs a (non-last) level can hold before they are merged into the next level
+    ///
+    /// Values below 2 are treated as 2.
+    ///
+    /// Default = 4
+    pub level_ratio: u8,
+
+    /// The target table size as disk (possibly compressed).
+    ///
+    /// Default = 64 MiB
+    pub target_size: u64,
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Self {
+            level_ratio: 4,
+            target_size:/* 64 Mib */ 64 * 1_024 * 1_024,
+        }
+    }
+}
+
+impl Strategy {
+    /// Sets the number of runs a level can hold before they are merged into the next level.
+    #[must_use]
+    pub fn with_level_ratio(mut self, ratio: u8) -> Self {
+        self.level_ratio = ratio;
+        self
+    }
+
+    /// Sets the target table size.
+    #[must_use]
+    pub fn with_target_size(mut self, bytes: u64) -> Self {
+        self.target_size = bytes;
+        self
+    }
+}
+
+impl CompactionStrategy for Strategy {
+    fn get_name(&self) -> &'static str {
+        NAME
+    }
+
+    fn get_config(&self) -> Vec<KvPair> {
+        vec![
+            (
+                crate::UserKey::from("lazy_leveled_level_ratio"),
+                crate::UserValue::from(self.level_ratio.to_le_bytes()),
+            ),
+            (
+                crate::UserKey::from("lazy_leveled_target_size"),
+                crate::UserValue::from(self.target_size.to_le_bytes()),
+            ),
+        ]
+    }
+
+    fn choose(&self, version: &Version, _: &Config, state: &CompactionState) -> Choice {
+        super::tiered::choose(version, state, self.level_ratio, self.target_size, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Strategy;
+    use crate::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+    use std::sync::Arc;
+    use test_log::test;
+
+    #[test]
+    #[expect(clippy::indexing_slicing)]
+    fn lazy_leveled_last_level_merges_overlapping() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tree = Config::new(dir.path(), SequenceNumberCounter::default())
+            .level_count(2)
+            .open()?;
+
+        let strategy = Arc::new(Strategy::default().with_level_ratio(2));
+        let mut seqno = 0;
+
+        // NOTE: Two overlapping flushes per key range, so L0 has two runs
+        for (lo, hi) in [("a", "b"), ("c", "d"), ("e", "f")] {
+            for _ in 0..2 {
+                tree.insert(lo, "v", seqno);
+                tree.insert(hi, "v", seqno);
+                seqno += 1;
+                tree.flush_active_memtable(0)?;
+            }
+            tree.compact(strategy.clone(), seqno)?;
+        }
+
+        assert_eq!(Some(0), tree.level_table_count(0));
+        assert_eq!(Some(3), tree.level_table_count(1));
+        assert_eq!(1, tree.levels()[1].run_count);
+
+        tree.insert("a", "v2", seqno);
+        tree.insert("b", "v2", seqno);
+        seqno += 1;
+        tree.flush_active_memtable(0)?;
+        tree.remove("b", seqno);
+        seqno += 1;
+        tree.flush_active_memtable(0)?;
+
+        let before = tree.tables();
+        tree.compact(strategy, seqno)?;
+        let after = tree.tables();
+
+        // NOTE: Only the table of "a" and "b" was rewritten
+        let untouched = before
+            .iter()
+            .filter(|table| table.level == 1 && after.contains(table))
+            .count();
+        assert_eq!(2, untouched);
+
+        assert_eq!(Some(0), tree.level_table_count(0));
+        assert_eq!(1, tree.levels()[1].run_count);
+        assert_eq!(0, tree.tombstone_count());
+        assert_eq!(Some("v2".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+        assert!(!tree.contains_key("b", SeqNo::MAX)?);
+        assert_eq!(5, tree.len(SeqNo::MAX, None)?);
+
+        Ok(())
+    }
+}
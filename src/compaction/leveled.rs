@@ -8,7 +8,7 @@ use crate::{
     config::Config,
     slice_windows::{GrowingWindowsExt, ShrinkingWindowsExt},
     table::{util::aggregate_run_key_range, Table},
-    version::{Run, Version},
+    version::{Level, Run, Version},
     HashSet, TableId,
 };
 
@@ -101,6 +101,33 @@ fn pick_minimal_compaction(
     }
 }
 
+/// Finds L0 tables that can be trivially moved into L1, even if the entire L0 cannot.
+///
+/// A table can only be moved if it overlaps neither L1 nor any other table in L0,
+/// otherwise an older version of a key could end up shadowing a newer one.
+fn pick_l0_trivial_moves(first_level: &Level, target_level: &Level) -> HashSet<TableId> {
+    let tables = first_level
+        .iter()
+        .flat_map(|run| run.iter())
+        .collect::<Vec<_>>();
+
+    tables
+        .iter()
+        .filter(|table| {
+            let key_range = &table.metadata.key_range;
+
+            target_level
+                .iter()
+                .all(|run| run.get_overlapping(key_range).is_empty())
+                && tables.iter().all(|other| {
+                    other.id() == table.id()
+                        || !other.metadata.key_range.overlaps_with_key_range(key_range)
+                })
+        })
+        .map(|table| table.id())
+        .collect()
+}
+
 /// Levelled compaction strategy (LCS)
 ///
 /// When a level reaches some threshold size, parts of it are merged into overlapping tables in the next level.
@@ -136,6 +163,14 @@ pub struct Strategy {
     ///
     /// Default = 10
     pub level_ratio_policy: Vec<f32>,
+
+    /// When the ratio of tombstones in a table reaches this threshold,
+    /// the table is compacted even if its level is not over capacity.
+    ///
+    /// Default = None (disabled)
+    ///
+    /// Similar to the `CompactOnDeletionCollector` in `RocksDB`.
+    pub tombstone_compaction_threshold: Option<f32>,
 }
 
 impl Default for Strategy {
@@ -144,6 +179,7 @@ impl Default for Strategy {
             l0_threshold: 4,
             target_size:/* 64 Mib */ 64 * 1_024 * 1_024,
             level_ratio_policy: vec![10.0],
+            tombstone_compaction_threshold: None,
         }
     }
 }
@@ -156,6 +192,183 @@ impl Strategy {
         self
     }
 
+    /// Sets the ratio of tombstones at which a table is compacted.
+    ///
+    /// Tables with many tombstones make range scans over deleted regions slow,
+    /// because every tombstone has to be skipped.
+    /// Such tables are merged into the next level when there is no other compaction to do,
+    /// and tables in the last level are rewritten, which drops their tombstones.
+    ///
+    /// The ratio is clamped to `(0.0, 1.0]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{compaction::Leveled, Config};
+    /// use std::sync::Arc;
+    ///
+    /// // Compact tables that consist of at least 50% tombstones
+    /// let strategy = Leveled::default().with_tombstone_compaction_threshold(Some(0.5));
+    ///
+    /// let tree = Config::new(folder, Default::default())
+    ///     .compaction_strategy(Arc::new(strategy))
+    ///     .open()?;
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_tombstone_compaction_threshold(mut self, ratio: Option<f32>) -> Self {
+        self.tombstone_compaction_threshold = ratio.map(|ratio| ratio.clamp(f32::EPSILON, 1.0));
+        self
+    }
+
+    /// Chooses a compaction for the table with the highest tombstone ratio
+    /// that reaches the tombstone compaction threshold.
+    ///
+    /// Tables in L1+ are merged with their overlapping tables in the next level,
+    /// tables in the last level are rewritten into the last level.
+    fn choose_tombstone_compaction(
+        &self,
+        version: &Version,
+        state: &CompactionState,
+        level_shift: usize,
+    ) -> Choice {
+        let Some(threshold) = self.tombstone_compaction_threshold else {
+            return Choice::DoNothing;
+        };
+
+        let last_level_idx = version.level_count() - 1;
+
+        // NOTE: L0 is compacted by count, so only look at L1+
+        let Some((level_idx, table)) = version
+            .iter_levels()
+            .enumerate()
+            .skip(1)
+            .flat_map(|(idx, level)| {
+                level
+                    .iter()
+                    .flat_map(|run| run.iter())
+                    .map(move |table| (idx, table))
+            })
+            .filter(|(_, table)| {
+                table.tombstone_count() > 0 && table.tombstone_ratio() >= threshold
+            })
+            .filter(|(_, table)| !state.hidden_set().is_hidden(table.id()))
+            .max_by(|(_, a), (_, b)| {
+                a.tombstone_ratio()
+                    .partial_cmp(&b.tombstone_ratio())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        else {
+            return Choice::DoNothing;
+        };
+
+        // NOTE: Level count is 255 max
+        #[expect(clippy::cast_possible_truncation)]
+        let dest_level = (level_idx + 1).min(last_level_idx) as u8;
+
+        let mut table_ids: HashSet<_> = std::iter::once(table.id()).collect();
+
+        if usize::from(dest_level) != level_idx {
+            let Some(next_level) = version.level(usize::from(dest_level)) else {
+                return Choice::DoNothing;
+            };
+
+            table_ids.extend(
+                next_level
+                    .iter()
+                    .flat_map(|run| run.get_overlapping(&table.metadata.key_range))
+                    .map(Table::id),
+            );
+
+            if state.hidden_set().is_blocked(table_ids.iter().copied()) {
+                return Choice::DoNothing;
+            }
+        }
+
+        log::debug!(
+            "Compacting table {} in L{level_idx} because of its tombstone ratio of {}",
+            table.id(),
+            table.tombstone_ratio(),
+        );
+
+        let choice = CompactionInput {
+            table_ids,
+            dest_level,
+            #[expect(clippy::cast_possible_truncation)]
+            canonical_level: usize::from(dest_level).saturating_sub(level_shift) as u8,
+            target_size: self.target_size,
+        };
+
+        // NOTE: Moving the table down would not drop any tombstones, but the tombstones
+        // will be dropped once the table is rewritten in the last level
+        if choice.table_ids.len() == 1 && usize::from(dest_level) != level_idx {
+            return Choice::Move(choice);
+        }
+        Choice::Merge(choice)
+    }
+
+    /// Chooses a rewrite of the oldest table that exceeds the periodic compaction interval.
+    ///
+    /// The table is rewritten in its level, L0 is rewritten as a whole, because its runs may overlap.
+    fn choose_periodic_compaction(
+        &self,
+        version: &Version,
+        config: &Config,
+        state: &CompactionState,
+        level_shift: usize,
+    ) -> Choice {
+        let Some(interval) = config.periodic_compaction_interval else {
+            return Choice::DoNothing;
+        };
+
+        let cutoff = config.clock.now().saturating_sub(interval).as_nanos();
+
+        let Some((level_idx, table)) = version
+            .iter_levels()
+            .enumerate()
+            .flat_map(|(idx, level)| {
+                level
+                    .iter()
+                    .flat_map(|run| run.iter())
+                    .map(move |table| (idx, table))
+            })
+            .filter(|(_, table)| u128::from(table.metadata.created_at) <= cutoff)
+            .filter(|(_, table)| !state.hidden_set().is_hidden(table.id()))
+            .min_by_key(|(_, table)| table.metadata.created_at)
+        else {
+            return Choice::DoNothing;
+        };
+
+        let table_ids: HashSet<_> = if level_idx == 0 {
+            if version.level_is_busy(0, state.hidden_set()) {
+                return Choice::DoNothing;
+            }
+
+            version.l0().list_ids()
+        } else {
+            std::iter::once(table.id()).collect()
+        };
+
+        log::debug!(
+            "Compacting table {} in L{level_idx} because it is older than {interval:?}",
+            table.id(),
+        );
+
+        // NOTE: Level count is 255 max
+        #[expect(clippy::cast_possible_truncation)]
+        let (dest_level, canonical_level) =
+            (level_idx as u8, level_idx.saturating_sub(level_shift) as u8);
+
+        Choice::Merge(CompactionInput {
+            table_ids,
+            dest_level,
+            canonical_level,
+            target_size: self.target_size,
+        })
+    }
+
     /// Calculates the size of L1.
     fn level_base_size(&self) -> u64 {
         self.target_size * u64::from(self.l0_threshold)
@@ -234,9 +447,7 @@ impl CompactionStrategy for Strategy {
     }
 
     #[expect(clippy::too_many_lines)]
-    fn choose(&self, version: &Version, _: &Config, state: &CompactionState) -> Choice {
-        assert!(version.level_count() == 7, "should have exactly 7 levels");
-
+    fn choose(&self, version: &Version, config: &Config, state: &CompactionState) -> Choice {
         // Find the level that corresponds to L1
         #[expect(clippy::map_unwrap_or)]
         let mut canonical_l1_idx = version
@@ -318,7 +529,7 @@ impl CompactionStrategy for Strategy {
         }
 
         // Scoring
-        let mut scores = [(/* score */ 0.0, /* overshoot */ 0u64); 7];
+        let mut scores = vec![(/* score */ 0.0, /* overshoot */ 0u64); version.level_count()];
 
         {
             // TODO(weak-tombstone-rewrite): incorporate `Table::weak_tombstone_count` and
@@ -330,6 +541,8 @@ impl CompactionStrategy for Strategy {
             let first_level = version.l0();
 
             // TODO: use run_count instead? but be careful because of version free list GC thingy
+            // NOTE: L0 always exists
+            #[expect(clippy::indexing_slicing)]
             if first_level.table_count() >= usize::from(self.l0_threshold) {
                 let ratio = (first_level.table_count() as f64) / f64::from(self.l0_threshold);
                 scores[0] = (ratio, 0);
@@ -371,8 +584,8 @@ impl CompactionStrategy for Strategy {
             }
 
             // NOTE: Never score Lmax
-            {
-                scores[6] = (0.0, 0);
+            if let Some(lmax_score) = scores.last_mut() {
+                *lmax_score = (0.0, 0);
             }
         }
 
@@ -388,7 +601,12 @@ impl CompactionStrategy for Strategy {
             .expect("should have highest score somewhere");
 
         if score < 1.0 {
-            return Choice::DoNothing;
+            return match self.choose_tombstone_compaction(version, state, level_shift) {
+                Choice::DoNothing => {
+                    self.choose_periodic_compaction(version, config, state, level_shift)
+                }
+                choice => choice,
+            };
         }
 
         // We choose L0->L1 compaction
@@ -436,6 +654,18 @@ impl CompactionStrategy for Strategy {
             if target_level_overlapping_table_ids.is_empty() && first_level.is_disjoint() {
                 return Choice::Move(choice);
             }
+
+            // NOTE: Move the tables that do not need to be merged first, instead of rewriting them,
+            // the remaining tables are merged once L0 is over its threshold again
+            let trivial_move_table_ids = pick_l0_trivial_moves(first_level, target_level);
+
+            if !trivial_move_table_ids.is_empty() {
+                return Choice::Move(CompactionInput {
+                    table_ids: trivial_move_table_ids,
+                    ..choice
+                });
+            }
+
             return Choice::Merge(choice);
         }
 
@@ -4,7 +4,10 @@
 
 //! Contains compaction strategies
 
+pub(crate) mod blob_gc;
+pub(crate) mod compact_range;
 pub(crate) mod fifo;
+pub(crate) mod lazy_leveled;
 pub(crate) mod leveled;
 // pub(crate) mod maintenance;
 pub(crate) mod drop_range;
@@ -12,16 +15,23 @@ mod flavour;
 pub(crate) mod major;
 pub(crate) mod movedown;
 pub(crate) mod pulldown;
+pub mod remote;
+pub(crate) mod rewrite;
 pub(crate) mod state;
 pub(crate) mod stream;
-// pub(crate) mod tiered;
+mod subcompaction;
+pub(crate) mod tiered;
 pub(crate) mod worker;
 
 pub use fifo::Strategy as Fifo;
+pub use lazy_leveled::Strategy as LazyLeveled;
 pub use leveled::Strategy as Leveled;
-// pub use tiered::Strategy as SizeTiered;
+pub use tiered::Strategy as SizeTiered;
 
-pub use {fifo::NAME as FIFO_COMPACTION_NAME, leveled::NAME as LEVELED_COMPACTION_NAME};
+pub use {
+    fifo::NAME as FIFO_COMPACTION_NAME, lazy_leveled::NAME as LAZY_LEVELED_COMPACTION_NAME,
+    leveled::NAME as LEVELED_COMPACTION_NAME, tiered::NAME as SIZE_TIERED_COMPACTION_NAME,
+};
 
 /// Alias for `Leveled`
 pub type Levelled = Leveled;
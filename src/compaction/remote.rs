@@ -0,0 +1,794 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Offloading of compactions to external workers.
+//!
+//! A [`CompactionJob`] is prepared using [`crate::Tree::prepare_remote_compaction`]. It describes
+//! the input tables and how the output tables are written.
+//! The job can be encoded (see [`crate::coding::Encode`]), and sent to another machine,
+//! together with the input table files (found in the tree's `tables` folder).
+//!
+//! There, [`CompactionJob::execute`] merges the input tables into new table files,
+//! and returns a [`CompactionOutput`], which is sent back together with the new table files.
+//!
+//! Lastly, [`crate::Tree::install_remote_compaction`] atomically replaces the input tables
+//! with the output tables in the tree's manifest.
+//!
+//! While a job is running, its input tables are not picked up by other compactions.
+//! If a job fails, [`crate::Tree::abort_remote_compaction`] releases the input tables again.
+//!
+//! Remote compactions do not relocate blobs, and do not support encrypted trees.
+
+use super::{
+    stream::CompactionStream,
+    worker::{drop_tables, move_tables, CompactionReader, Options},
+    Choice,
+};
+use crate::{
+    blob_tree::{handle::BlobIndirection, FragmentationMap},
+    coding::{Decode, Encode},
+    file::TABLES_FOLDER,
+    merge::Merger,
+    table::{filter::BloomConstructionPolicy, multi_writer::MultiWriter},
+    version::SuperVersions,
+    BlobFile, Cache, Checksum, CompressionType, DescriptorTable, Filesystem, HashSet, SeqNo,
+    SequenceNumberCounter, StdFilesystem, Table, TableId,
+};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use std::{
+    io::{Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+/// Settings of the table writer of a compaction job
+#[derive(Clone, Debug, PartialEq)]
+struct WriterSettings {
+    data_block_size: u32,
+    data_block_restart_interval: u8,
+    index_block_restart_interval: u8,
+    data_block_compression: CompressionType,
+    index_block_compression: CompressionType,
+    data_block_hash_ratio: f32,
+    index_partitioning: bool,
+    filter_partitioning: bool,
+    bloom_policy: BloomConstructionPolicy,
+}
+
+/// Description of a compaction that can be run by an external worker
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactionJob {
+    /// Input tables and their checksums
+    tables: Vec<(TableId, Checksum)>,
+
+    /// Level to put the created tables into
+    dest_level: u8,
+
+    /// Table target size
+    target_size: u64,
+
+    /// Evicts items that are older than this seqno (MVCC GC)
+    mvcc_gc_watermark: SeqNo,
+
+    /// Whether tombstones can be evicted (no data beneath the compaction)
+    evict_tombstones: bool,
+
+    /// First table ID reserved for the output tables
+    first_table_id: TableId,
+
+    /// Amount of table IDs reserved for the output tables
+    reserved_table_ids: u64,
+
+    settings: WriterSettings,
+}
+
+/// Result of a [`CompactionJob`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactionOutput {
+    /// Created tables and their checksums
+    tables: Vec<(TableId, Checksum)>,
+
+    /// Blob fragmentation caused by the compaction
+    fragmentation: FragmentationMap,
+}
+
+impl CompactionOutput {
+    /// Returns the IDs of the created tables.
+    ///
+    /// The table files are named after their ID.
+    pub fn table_ids(&self) -> impl Iterator<Item = TableId> + '_ {
+        self.tables.iter().map(|(id, _)| *id)
+    }
+}
+
+impl CompactionJob {
+    /// Returns the IDs of the input tables.
+    ///
+    /// The table files are named after their ID.
+    pub fn table_ids(&self) -> impl Iterator<Item = TableId> + '_ {
+        self.tables.iter().map(|(id, _)| *id)
+    }
+
+    /// Returns the level the output tables are put into.
+    #[must_use]
+    pub fn dest_level(&self) -> u8 {
+        self.dest_level
+    }
+
+    /// Runs the compaction, reading the input tables from `input_folder`,
+    /// and writing the output tables into `output_folder`.
+    ///
+    /// Both folders are flat, even if the tree shards its tables into subfolders
+    /// (see [`crate::Config::directory_shards`]).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn execute<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        input_folder: P,
+        output_folder: Q,
+    ) -> crate::Result<CompactionOutput> {
+        let input_folder = input_folder.as_ref();
+        let output_folder = output_folder.as_ref();
+
+        let start = std::time::Instant::now();
+
+        log::debug!(
+            "Executing remote compaction of tables {:?} into L{}",
+            self.table_ids().collect::<Vec<_>>(),
+            self.dest_level,
+        );
+
+        let fs: Arc<dyn Filesystem> = Arc::new(StdFilesystem);
+        let cache = Arc::new(Cache::with_capacity_bytes(0));
+        let descriptor_table = Arc::new(DescriptorTable::new(self.tables.len() + 1));
+
+        #[cfg(feature = "metrics")]
+        let metrics = Arc::new(crate::Metrics::default());
+
+        let mut readers: Vec<CompactionReader<'_>> = Vec::with_capacity(self.tables.len());
+
+        for &(table_id, checksum) in &self.tables {
+            let table = Table::recover(
+                input_folder.join(table_id.to_string()),
+                checksum,
+                0,
+                fs.clone(),
+                None,
+                cache.clone(),
+                descriptor_table.clone(),
+                false,
+                false,
+                #[cfg(feature = "metrics")]
+                metrics.clone(),
+            )?;
+
+            readers.push(Box::new(table.scan()?));
+        }
+
+        let mut fragmentation = FragmentationMap::default();
+
+        let merge_iter = CompactionStream::new(Merger::new(readers), self.mvcc_gc_watermark)
+            .evict_tombstones(self.evict_tombstones)
+            .with_expiration_callback(&mut fragmentation);
+
+        fs.create_dir_all(output_folder)?;
+
+        let settings = &self.settings;
+
+        let mut table_writer = MultiWriter::new(
+            fs.clone(),
+            output_folder.to_path_buf(),
+            1,
+            SequenceNumberCounter::new(self.first_table_id),
+            self.target_size,
+            self.dest_level,
+        )?
+        .use_data_block_restart_interval(settings.data_block_restart_interval)
+        .use_index_block_restart_interval(settings.index_block_restart_interval)
+        .use_data_block_compression(settings.data_block_compression)
+        .use_data_block_size(settings.data_block_size)
+        .use_data_block_hash_ratio(settings.data_block_hash_ratio)
+        .use_index_block_compression(settings.index_block_compression)
+        .use_bloom_policy(settings.bloom_policy);
+
+        if settings.index_partitioning {
+            table_writer = table_writer.use_partitioned_index();
+        }
+        if settings.filter_partitioning {
+            table_writer = table_writer.use_partitioned_filter();
+        }
+
+        for item in merge_iter {
+            let item = item?;
+
+            let indirection = if item.key.value_type.is_indirection() {
+                let mut reader = &item.value[..];
+                Some(BlobIndirection::decode_from(&mut reader)?)
+            } else {
+                None
+            };
+
+            table_writer.write(item)?;
+
+            if let Some(indirection) = indirection {
+                table_writer.register_blob(indirection);
+            }
+        }
+
+        let tables = table_writer.finish()?;
+
+        if tables
+            .iter()
+            .any(|&(id, _)| id >= self.first_table_id + self.reserved_table_ids)
+        {
+            log::error!(
+                "Remote compaction created {} tables, but only {} table IDs were reserved",
+                tables.len(),
+                self.reserved_table_ids,
+            );
+
+            return Err(crate::Error::Io(std::io::Error::other(
+                "remote compaction ran out of reserved table IDs",
+            )));
+        }
+
+        fs.sync_directory(output_folder)?;
+
+        log::debug!(
+            "Remote compaction created {} tables in {:?}",
+            tables.len(),
+            start.elapsed(),
+        );
+
+        Ok(CompactionOutput {
+            tables,
+            fragmentation,
+        })
+    }
+}
+
+/// Asks the compaction strategy for a compaction, and creates a job for it.
+///
+/// Moving and dropping tables does not rewrite data, so it is done locally.
+#[expect(clippy::significant_drop_tightening, clippy::too_many_lines)]
+pub(crate) fn prepare(opts: &Options) -> crate::Result<Option<CompactionJob>> {
+    opts.config.ensure_writable()?;
+
+    if opts.config.encryptor.is_some() {
+        return Err(crate::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "remote compaction does not support encryption",
+        )));
+    }
+
+    let mut compaction_state = opts.compaction_state.lock().expect("lock is poisoned");
+    let version_history_lock = opts.version_history.read().expect("lock is poisoned");
+
+    let current_version = version_history_lock.latest_version().version;
+
+    let payload = match opts
+        .strategy
+        .choose(&current_version, &opts.config, &compaction_state)
+    {
+        Choice::Merge(payload) => payload,
+        Choice::Move(payload) => {
+            drop(version_history_lock);
+            move_tables(compaction_state, opts, &payload)?;
+            return Ok(None);
+        }
+        Choice::Drop(payload) => {
+            drop(version_history_lock);
+            drop_tables(
+                compaction_state,
+                opts,
+                &payload.into_iter().collect::<Vec<_>>(),
+            )?;
+            return Ok(None);
+        }
+        Choice::DoNothing => return Ok(None),
+    };
+
+    drop(version_history_lock);
+
+    if compaction_state
+        .hidden_set()
+        .should_decline_compaction(payload.table_ids.iter().copied())
+    {
+        log::warn!(
+            "Compaction task created by {:?} contained hidden tables, declining to run it",
+            opts.strategy.get_name(),
+        );
+        return Ok(None);
+    }
+
+    let Some(tables) = payload
+        .table_ids
+        .iter()
+        .map(|&id| current_version.get_table(id).cloned())
+        .collect::<Option<Vec<_>>>()
+    else {
+        log::warn!(
+            "Compaction task created by {:?} contained tables not referenced in the level manifest",
+            opts.strategy.get_name(),
+        );
+        return Ok(None);
+    };
+
+    let dst_lvl = usize::from(payload.canonical_level);
+
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "there are always less than 256 levels"
+    )]
+    let last_level = (current_version.level_count() - 1) as u8;
+    let is_last_level = payload.dest_level == last_level;
+
+    let settings = WriterSettings {
+        data_block_size: opts.config.data_block_size_policy.get(dst_lvl),
+        data_block_restart_interval: opts.config.data_block_restart_interval_policy.get(dst_lvl),
+        index_block_restart_interval: opts.config.index_block_restart_interval_policy.get(dst_lvl),
+        data_block_compression: opts.config.data_block_compression_policy.get(dst_lvl),
+        index_block_compression: opts.config.index_block_compression_policy.get(dst_lvl),
+        data_block_hash_ratio: opts.config.data_block_hash_ratio_policy.get(dst_lvl),
+        index_partitioning: opts.config.index_block_partitioning_policy.get(dst_lvl),
+        filter_partitioning: opts.config.filter_block_partitioning_policy.get(dst_lvl),
+        bloom_policy: {
+            use crate::config::FilterPolicyEntry::{Bloom, None};
+
+            if is_last_level && opts.config.expect_point_read_hits {
+                BloomConstructionPolicy::BitsPerKey(0.0)
+            } else {
+                match opts
+                    .config
+                    .filter_policy
+                    .get(usize::from(payload.dest_level))
+                {
+                    Bloom(policy) => policy,
+                    None => BloomConstructionPolicy::BitsPerKey(0.0),
+                }
+            }
+        },
+    };
+
+    // NOTE: Compactions do not grow data (much), so the amount of output tables is bounded
+    // by the input size, but give the worker some leeway, in case compression differs
+    let input_size = tables.iter().map(Table::file_size).sum::<u64>();
+    let reserved_table_ids =
+        2 * (input_size / payload.target_size.max(1) + 1) + tables.len() as u64;
+
+    let first_table_id = opts.table_id_generator.reserve(reserved_table_ids);
+
+    compaction_state
+        .hidden_set_mut()
+        .hide(payload.table_ids.iter().copied());
+
+    Ok(Some(CompactionJob {
+        tables: tables
+            .iter()
+            .map(|table| (table.id(), table.checksum()))
+            .collect(),
+        dest_level: payload.dest_level,
+        target_size: payload.target_size,
+        mvcc_gc_watermark: opts.mvcc_gc_watermark,
+        evict_tombstones: super::worker::can_evict_tombstones(&current_version, &payload),
+        first_table_id,
+        reserved_table_ids,
+        settings,
+    }))
+}
+
+/// Makes the input tables of a job available to other compactions again.
+pub(crate) fn abort(tree: &crate::Tree, job: &CompactionJob) {
+    let mut compaction_state = tree.compaction_state.lock().expect("lock is poisoned");
+
+    compaction_state.hidden_set_mut().show(job.table_ids());
+}
+
+/// Moves the output tables of a job into the tree, and replaces the input tables with them.
+///
+/// The output is checked against the job first, so a faulty worker cannot corrupt the tree.
+/// If the output is rejected, or cannot be moved into the tree, the moved tables are
+/// removed again.
+///
+/// Either way, the input tables are made available to other compactions again.
+pub(crate) fn install(
+    tree: &crate::Tree,
+    job: &CompactionJob,
+    output: &CompactionOutput,
+    output_folder: &Path,
+    mvcc_gc_watermark: SeqNo,
+) -> crate::Result<()> {
+    let mut compaction_state = tree.compaction_state.lock().expect("lock is poisoned");
+    let mut version_history_lock = tree.version_history.write().expect("lock is poisoned");
+
+    let result = install_tables(
+        tree,
+        job,
+        output,
+        output_folder,
+        mvcc_gc_watermark,
+        &mut version_history_lock,
+    );
+
+    compaction_state.hidden_set_mut().show(job.table_ids());
+
+    drop(version_history_lock);
+    drop(compaction_state);
+
+    result
+}
+
+fn invalid_output(msg: &'static str) -> crate::Error {
+    log::error!("Rejected remote compaction output: {msg}");
+    crate::Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, msg))
+}
+
+/// Checks that the output tables only contain what the input tables contained.
+fn validate_output(inputs: &[Table], outputs: &[(TableId, Table)]) -> crate::Result<()> {
+    let max_input_seqno = inputs
+        .iter()
+        .map(Table::get_highest_seqno)
+        .max()
+        .unwrap_or_default();
+
+    for (table_id, table) in outputs {
+        if table.id() != *table_id {
+            return Err(invalid_output("table ID does not match its file name"));
+        }
+
+        // NOTE: The output tables only contain keys of the input tables,
+        // so both their first and last key lie in the key range of some input table
+        let key_range = &table.metadata.key_range;
+
+        let in_input = |key: &[u8]| {
+            inputs
+                .iter()
+                .any(|input| input.metadata.key_range.contains_key(key))
+        };
+
+        if !in_input(key_range.min()) || !in_input(key_range.max()) {
+            return Err(invalid_output("table is outside of the input key range"));
+        }
+
+        if table.get_highest_seqno() > max_input_seqno {
+            return Err(invalid_output("table contains seqnos newer than its input"));
+        }
+    }
+
+    Ok(())
+}
+
+#[expect(clippy::too_many_lines)]
+fn install_tables(
+    tree: &crate::Tree,
+    job: &CompactionJob,
+    output: &CompactionOutput,
+    output_folder: &Path,
+    mvcc_gc_watermark: SeqNo,
+    version_history_lock: &mut SuperVersions,
+) -> crate::Result<()> {
+    let config = &tree.config;
+
+    let current_version = version_history_lock.latest_version().version;
+
+    let Some(tables_to_delete) = job
+        .table_ids()
+        .map(|id| current_version.get_table(id).cloned())
+        .collect::<Option<Vec<_>>>()
+    else {
+        log::error!("Remote compaction input tables are not referenced in the level manifest");
+
+        return Err(crate::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "remote compaction input tables do not exist anymore",
+        )));
+    };
+
+    if output
+        .table_ids()
+        .any(|id| id < job.first_table_id || id >= job.first_table_id + job.reserved_table_ids)
+    {
+        return Err(invalid_output("table was not reserved by its job"));
+    }
+
+    if output.table_ids().collect::<HashSet<_>>().len() != output.tables.len() {
+        return Err(invalid_output("table IDs are not unique"));
+    }
+
+    let table_base_folder = config.path.join(TABLES_FOLDER);
+    let dst_lvl = usize::from(job.dest_level);
+
+    let pin_filter = config.filter_block_pinning_policy.get(dst_lvl);
+    let pin_index = config.index_block_pinning_policy.get(dst_lvl);
+
+    let mut moved_paths = Vec::with_capacity(output.tables.len());
+    let mut created_tables = Vec::with_capacity(output.tables.len());
+
+    let result = (|| -> crate::Result<()> {
+        for &(table_id, checksum) in &output.tables {
+            let path =
+                crate::file::sharded_path(&table_base_folder, table_id, config.directory_shards);
+
+            config
+                .fs
+                .rename(&output_folder.join(table_id.to_string()), &path)?;
+
+            moved_paths.push(path.clone());
+
+            let table = Table::recover(
+                path,
+                checksum,
+                tree.id,
+                config.fs.clone(),
+                None,
+                config.cache.clone(),
+                config.descriptor_table.clone(),
+                pin_filter,
+                pin_index,
+                #[cfg(feature = "metrics")]
+                tree.metrics.clone(),
+            )?;
+
+            created_tables.push((table_id, table));
+        }
+
+        validate_output(&tables_to_delete, &created_tables)?;
+
+        config.fs.sync_directory(&table_base_folder)?;
+
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        // NOTE: The tables are not referenced by the tree, so they can simply be removed again
+        drop(created_tables);
+
+        for path in moved_paths {
+            if let Err(e) = config.fs.remove_file(&path) {
+                log::warn!(
+                    "Failed to remove rejected table at {}: {e:?}",
+                    path.display()
+                );
+            }
+        }
+
+        return Err(e);
+    }
+
+    let created_tables = created_tables
+        .into_iter()
+        .map(|(_, table)| table)
+        .collect::<Vec<_>>();
+
+    let mut blob_files_to_drop = Vec::default();
+
+    for blob_file in current_version.blob_files.iter() {
+        if blob_file.is_dead(current_version.gc_stats()) {
+            blob_files_to_drop.push(blob_file.clone());
+        }
+    }
+
+    version_history_lock.upgrade_version(
+        &*config.fs,
+        &config.path,
+        |current| {
+            let mut copy = current.clone();
+
+            copy.version = copy.version.with_merge(
+                &job.table_ids().collect::<Vec<_>>(),
+                &created_tables,
+                dst_lvl,
+                if output.fragmentation.is_empty() {
+                    None
+                } else {
+                    Some(output.fragmentation.clone())
+                },
+                Vec::default(),
+                blob_files_to_drop
+                    .iter()
+                    .map(BlobFile::id)
+                    .collect::<HashSet<_>>(),
+            );
+
+            Ok(copy)
+        },
+        &config.seqno,
+    )?;
+
+    // NOTE: If the application were to crash >here< it's fine
+    // The tables are not referenced anymore, and will be
+    // cleaned up upon recovery
+    for table in tables_to_delete {
+        table.mark_as_deleted();
+    }
+
+    for blob_file in blob_files_to_drop {
+        blob_file.mark_as_deleted();
+    }
+
+    version_history_lock
+        .maintenance(&*config.fs, &config.path, mvcc_gc_watermark)
+        .inspect_err(|e| {
+            log::error!("Manifest maintenance failed: {e:?}");
+        })?;
+
+    log::debug!(
+        "Installed remote compaction of tables {:?} into L{}",
+        job.table_ids().collect::<Vec<_>>(),
+        job.dest_level,
+    );
+
+    Ok(())
+}
+
+fn encode_table_list<W: Write>(
+    writer: &mut W,
+    tables: &[(TableId, Checksum)],
+) -> Result<(), crate::Error> {
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "there are always less than 4 billion tables in a compaction"
+    )]
+    writer.write_u32::<LE>(tables.len() as u32)?;
+
+    for (table_id, checksum) in tables {
+        writer.write_u64::<LE>(*table_id)?;
+        writer.write_u128::<LE>(checksum.into_u128())?;
+    }
+
+    Ok(())
+}
+
+fn decode_table_list<R: Read>(reader: &mut R) -> Result<Vec<(TableId, Checksum)>, crate::Error> {
+    let len = reader.read_u32::<LE>()?;
+
+    (0..len)
+        .map(|_| {
+            let table_id = reader.read_u64::<LE>()?;
+            let checksum = Checksum::from_raw(reader.read_u128::<LE>()?);
+            Ok((table_id, checksum))
+        })
+        .collect()
+}
+
+impl Encode for CompactionJob {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), crate::Error> {
+        encode_table_list(writer, &self.tables)?;
+
+        writer.write_u8(self.dest_level)?;
+        writer.write_u64::<LE>(self.target_size)?;
+        writer.write_u64::<LE>(self.mvcc_gc_watermark)?;
+        writer.write_u8(u8::from(self.evict_tombstones))?;
+        writer.write_u64::<LE>(self.first_table_id)?;
+        writer.write_u64::<LE>(self.reserved_table_ids)?;
+
+        let settings = &self.settings;
+        writer.write_u32::<LE>(settings.data_block_size)?;
+        writer.write_u8(settings.data_block_restart_interval)?;
+        writer.write_u8(settings.index_block_restart_interval)?;
+        settings.data_block_compression.encode_into(writer)?;
+        settings.index_block_compression.encode_into(writer)?;
+        writer.write_f32::<LE>(settings.data_block_hash_ratio)?;
+        writer.write_u8(u8::from(settings.index_partitioning))?;
+        writer.write_u8(u8::from(settings.filter_partitioning))?;
+
+        match settings.bloom_policy {
+            BloomConstructionPolicy::BitsPerKey(bpk) => {
+                writer.write_u8(0)?;
+                writer.write_f32::<LE>(bpk)?;
+            }
+            BloomConstructionPolicy::FalsePositiveRate(fpr) => {
+                writer.write_u8(1)?;
+                writer.write_f32::<LE>(fpr)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Decode for CompactionJob {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, crate::Error> {
+        let tables = decode_table_list(reader)?;
+
+        let dest_level = reader.read_u8()?;
+        let target_size = reader.read_u64::<LE>()?;
+        let mvcc_gc_watermark = reader.read_u64::<LE>()?;
+        let evict_tombstones = reader.read_u8()? != 0;
+        let first_table_id = reader.read_u64::<LE>()?;
+        let reserved_table_ids = reader.read_u64::<LE>()?;
+
+        let data_block_size = reader.read_u32::<LE>()?;
+        let data_block_restart_interval = reader.read_u8()?;
+        let index_block_restart_interval = reader.read_u8()?;
+        let data_block_compression = CompressionType::decode_from(reader)?;
+        let index_block_compression = CompressionType::decode_from(reader)?;
+        let data_block_hash_ratio = reader.read_f32::<LE>()?;
+        let index_partitioning = reader.read_u8()? != 0;
+        let filter_partitioning = reader.read_u8()? != 0;
+
+        let bloom_policy = match reader.read_u8()? {
+            0 => BloomConstructionPolicy::BitsPerKey(reader.read_f32::<LE>()?),
+            1 => BloomConstructionPolicy::FalsePositiveRate(reader.read_f32::<LE>()?),
+            tag => return Err(crate::Error::InvalidTag(("BloomConstructionPolicy", tag))),
+        };
+
+        Ok(Self {
+            tables,
+            dest_level,
+            target_size,
+            mvcc_gc_watermark,
+            evict_tombstones,
+            first_table_id,
+            reserved_table_ids,
+            settings: WriterSettings {
+                data_block_size,
+                data_block_restart_interval,
+                index_block_restart_interval,
+                data_block_compression,
+                index_block_compression,
+                data_block_hash_ratio,
+                index_partitioning,
+                filter_partitioning,
+                bloom_policy,
+            },
+        })
+    }
+}
+
+impl Encode for CompactionOutput {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), crate::Error> {
+        encode_table_list(writer, &self.tables)?;
+        self.fragmentation.encode_into(writer)
+    }
+}
+
+impl Decode for CompactionOutput {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, crate::Error> {
+        let tables = decode_table_list(reader)?;
+        let fragmentation = FragmentationMap::decode_from(reader)?;
+
+        Ok(Self {
+            tables,
+            fragmentation,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn remote_compaction_job_roundtrip() -> crate::Result<()> {
+        let job = CompactionJob {
+            tables: vec![(1, Checksum::from_raw(5)), (2, Checksum::from_raw(7))],
+            dest_level: 6,
+            target_size: 64_000_000,
+            mvcc_gc_watermark: 100,
+            evict_tombstones: true,
+            first_table_id: 10,
+            reserved_table_ids: 6,
+            settings: WriterSettings {
+                data_block_size: 4_096,
+                data_block_restart_interval: 16,
+                index_block_restart_interval: 1,
+                data_block_compression: CompressionType::None,
+                index_block_compression: CompressionType::Custom(3),
+                data_block_hash_ratio: 0.5,
+                index_partitioning: false,
+                filter_partitioning: true,
+                bloom_policy: BloomConstructionPolicy::FalsePositiveRate(0.01),
+            },
+        };
+
+        let bytes = job.encode_into_vec();
+        assert_eq!(job, CompactionJob::decode_from(&mut &bytes[..])?);
+
+        Ok(())
+    }
+}
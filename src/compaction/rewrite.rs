@@ -0,0 +1,83 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{Choice, CompactionStrategy, Input as CompactionInput};
+use crate::{
+    compaction::state::CompactionState, config::Config, table::Table, version::Version, HashSet,
+    TableId,
+};
+
+/// Rewrites all tables that are older than a given table ID, keeping them in their level
+///
+/// Every choice rewrites a single table, so the tables are migrated to the current
+/// config (e.g. block size or compression) incrementally.
+/// L0 is rewritten as a whole, because its runs may overlap.
+pub struct Strategy {
+    /// Tables with a lower ID are rewritten
+    table_id_watermark: TableId,
+}
+
+impl Strategy {
+    /// Configures a new `Rewrite` compaction strategy.
+    #[must_use]
+    pub fn new(table_id_watermark: TableId) -> Self {
+        Self { table_id_watermark }
+    }
+}
+
+impl CompactionStrategy for Strategy {
+    fn get_name(&self) -> &'static str {
+        "RewriteCompaction"
+    }
+
+    fn choose(&self, version: &Version, _: &Config, state: &CompactionState) -> Choice {
+        let is_old = |table: &&Table| table.id() < self.table_id_watermark;
+
+        for (level_idx, level) in version.iter_levels().enumerate() {
+            let mut tables = level.iter().flat_map(|run| run.iter());
+
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "there are less than 256 levels"
+            )]
+            let level_idx = level_idx as u8;
+
+            let table_ids: HashSet<_> = if level_idx == 0 {
+                if !tables.any(|table| is_old(&table)) {
+                    continue;
+                }
+
+                let table_ids: HashSet<_> = level
+                    .iter()
+                    .flat_map(|run| run.iter())
+                    .map(Table::id)
+                    .collect();
+
+                if table_ids.iter().any(|&id| state.hidden_set().is_hidden(id)) {
+                    return Choice::DoNothing;
+                }
+
+                table_ids
+            } else {
+                let Some(table) = tables
+                    .filter(is_old)
+                    .find(|table| !state.hidden_set().is_hidden(table.id()))
+                else {
+                    continue;
+                };
+
+                std::iter::once(table.id()).collect()
+            };
+
+            return Choice::Merge(CompactionInput {
+                table_ids,
+                dest_level: level_idx,
+                canonical_level: level_idx,
+                target_size: u64::MAX,
+            });
+        }
+
+        Choice::DoNothing
+    }
+}
@@ -2,8 +2,11 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{InternalValue, SeqNo, UserKey, ValueType};
-use std::iter::Peekable;
+use crate::{
+    range_tombstone::{RangeTombstone, RangeTombstoneIndex},
+    InternalValue, MergeOperator, SeqNo, UserKey, ValueType,
+};
+use std::{iter::Peekable, sync::Arc, time::Duration};
 
 type Item = crate::Result<InternalValue>;
 
@@ -29,6 +32,15 @@ pub struct CompactionStream<'a, I: Iterator<Item = Item>> {
     expiration_callback: Option<&'a mut dyn ExpiredKvCallback>,
 
     evict_tombstones: bool,
+
+    /// Collapses merge operands that are not visible to any snapshot
+    merge_operator: Option<Arc<dyn MergeOperator>>,
+
+    /// Range tombstones that are visible to all snapshots
+    range_tombstones: RangeTombstoneIndex,
+
+    /// Current time, to turn expired values into tombstones
+    now: Option<Duration>,
 }
 
 impl<'a, I: Iterator<Item = Item>> CompactionStream<'a, I> {
@@ -42,6 +54,9 @@ impl<'a, I: Iterator<Item = Item>> CompactionStream<'a, I> {
             gc_seqno_threshold,
             expiration_callback: None,
             evict_tombstones: false,
+            merge_operator: None,
+            range_tombstones: RangeTombstoneIndex::default(),
+            now: None,
         }
     }
 
@@ -50,6 +65,41 @@ impl<'a, I: Iterator<Item = Item>> CompactionStream<'a, I> {
         self
     }
 
+    /// Installs a merge operator that collapses expired merge operands.
+    pub fn with_merge_operator(mut self, merge_operator: Option<Arc<dyn MergeOperator>>) -> Self {
+        self.merge_operator = merge_operator;
+        self
+    }
+
+    /// Drops all versions that are deleted by the given range tombstones.
+    ///
+    /// Only tombstones below the MVCC watermark are applied, as snapshots may still
+    /// read the versions beneath the others.
+    pub fn with_range_tombstones(mut self, mut range_tombstones: Vec<RangeTombstone>) -> Self {
+        range_tombstones.retain(|rt| rt.seqno < self.gc_seqno_threshold);
+        self.range_tombstones = range_tombstones.into();
+        self
+    }
+
+    fn is_range_deleted(&self, kv: &InternalValue) -> bool {
+        self.range_tombstones
+            .is_range_deleted(&kv.key.user_key, kv.key.seqno, SeqNo::MAX)
+    }
+
+    /// Turns values that are expired at `now` into tombstones,
+    /// so they are dropped like deleted values (see [`crate::AbstractTree::insert_with_ttl`]).
+    pub fn expire_values(mut self, now: Duration) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    fn expire(&self, kv: InternalValue) -> InternalValue {
+        match self.now {
+            Some(now) => crate::ttl::expire(kv, now),
+            None => kv,
+        }
+    }
+
     /// Installs a callback that receives all expired KVs.
     pub fn with_expiration_callback(mut self, cb: &'a mut dyn ExpiredKvCallback) -> Self {
         self.expiration_callback = Some(cb);
@@ -82,12 +132,89 @@ impl<'a, I: Iterator<Item = Item>> CompactionStream<'a, I> {
     }
 }
 
+impl<I: Iterator<Item = Item>> CompactionStream<'_, I> {
+    /// Folds the older versions of the key into the merge operand `head`,
+    /// which is below the MVCC watermark, so none of its versions are visible to snapshots.
+    ///
+    /// If a value or tombstone is reached (or there are no older versions at all),
+    /// the result is a value, otherwise it is still a merge operand.
+    fn collapse_merge_operands(
+        &mut self,
+        merge_operator: &dyn MergeOperator,
+        mut head: InternalValue,
+    ) -> Item {
+        loop {
+            let Some(next) = self.inner.next_if(|kv| {
+                if let Ok(kv) = kv {
+                    kv.key.user_key == head.key.user_key
+                } else {
+                    true
+                }
+            }) else {
+                // NOTE: Only when evicting tombstones, there are no older versions anywhere
+                if self.evict_tombstones {
+                    head.key.value_type = ValueType::Value;
+                }
+                return Ok(head);
+            };
+
+            let older = next?;
+
+            // NOTE: Merge operands are folded into the actual value
+            let older = match self.now {
+                Some(now) => crate::ttl::resolve(older, now),
+                None => older,
+            };
+
+            if let Some(watcher) = &mut self.expiration_callback {
+                watcher.on_expired(&older);
+            }
+
+            if older.is_tombstone() || self.is_range_deleted(&older) {
+                head.key.value_type = ValueType::Value;
+                break;
+            }
+
+            head.value = merge_operator.merge(&head.key.user_key, &older.value, &head.value);
+
+            if !older.key.value_type.is_merge_operand() {
+                head.key.value_type = ValueType::Value;
+                break;
+            }
+        }
+
+        self.drain_key(&head.key.user_key)?;
+
+        Ok(head)
+    }
+}
+
 impl<I: Iterator<Item = Item>> Iterator for CompactionStream<'_, I> {
     type Item = Item;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             let head = fail_iter!(self.inner.next()?);
+            let head = self.expire(head);
+
+            if self.is_range_deleted(&head) {
+                if let Some(watcher) = &mut self.expiration_callback {
+                    watcher.on_expired(&head);
+                }
+                continue;
+            }
+
+            if head.key.value_type.is_merge_operand() {
+                if let Some(merge_operator) = self.merge_operator.clone() {
+                    if head.key.seqno < self.gc_seqno_threshold {
+                        return Some(self.collapse_merge_operands(&*merge_operator, head));
+                    }
+
+                    // NOTE: The operand is visible to snapshots, so its older versions
+                    // need to be kept, they are collapsed once they are processed
+                    return Some(Ok(head));
+                }
+            }
 
             if let Some(peeked) = self.inner.peek() {
                 let Ok(peeked) = peeked else {
@@ -117,8 +244,10 @@ impl<I: Iterator<Item = Item>> Iterator for CompactionStream<'_, I> {
 
                     // NOTE: If next item is an actual value, and current value is weak tombstone,
                     // drop the tombstone
-                    let drop_weak_tombstone = peeked.key.value_type == ValueType::Value
-                        && head.key.value_type == ValueType::WeakTombstone;
+                    let drop_weak_tombstone = matches!(
+                        peeked.key.value_type,
+                        ValueType::Value | ValueType::ExpiringValue
+                    ) && head.key.value_type == ValueType::WeakTombstone;
 
                     // NOTE: Next item is expired,
                     // so the tail of this user key is entirely expired, so drain it all
@@ -167,6 +296,7 @@ mod tests {
                     "V" => ValueType::Value,
                     "T" => ValueType::Tombstone,
                     "W" => ValueType::WeakTombstone,
+                    "M" => ValueType::MergeOperand,
                     _ => panic!("Unknown value type"),
                 };
 
@@ -543,4 +673,85 @@ mod tests {
 
         Ok(())
     }
+
+    #[derive(Debug)]
+    struct Append;
+
+    impl MergeOperator for Append {
+        fn merge(&self, _key: &[u8], older: &[u8], newer: &[u8]) -> crate::UserValue {
+            [older, newer].concat().into()
+        }
+    }
+
+    #[test]
+    #[expect(clippy::unwrap_used)]
+    fn compaction_stream_merge_operands() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "c", "M",
+          "a", "b", "M",
+          "a", "a", "V",
+          "a", "old", "V",
+          "b", "c", "M",
+          "b", "b", "M",
+          "c", "c", "M",
+          "c", "b", "M",
+          "c", "", "T",
+          "c", "old", "V",
+        ];
+
+        let iter = vec.iter().cloned().map(Ok);
+        let mut iter =
+            CompactionStream::new(iter, 1_000).with_merge_operator(Some(Arc::new(Append)));
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"abc", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        // NOTE: Older versions may exist in other tables
+        assert_eq!(
+            InternalValue::from_components(*b"b", *b"bc", 999, ValueType::MergeOperand),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"c", *b"bc", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
+
+    #[test]
+    #[expect(clippy::unwrap_used)]
+    fn compaction_stream_merge_operands_snapshot() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "d", "M",
+          "a", "c", "M",
+          "a", "b", "M",
+          "a", "a", "V",
+        ];
+
+        let iter = vec.iter().cloned().map(Ok);
+        let mut iter = CompactionStream::new(iter, 998)
+            .evict_tombstones(true)
+            .with_merge_operator(Some(Arc::new(Append)));
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"d", 999, ValueType::MergeOperand),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"c", 998, ValueType::MergeOperand),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"ab", 997, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
 }
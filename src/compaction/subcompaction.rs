@@ -0,0 +1,269 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{
+    drop_range::OwnedBounds,
+    flavour::{CompactionFlavour, StandardCompaction},
+    stream::CompactionStream,
+    worker::CompactionReader,
+};
+use crate::{
+    blob_tree::FragmentationMap, merge::Merger, merge_operator::MergeOperator,
+    range_tombstone::RangeTombstone, stop_signal::StopSignal, table::multi_writer::MultiWriter,
+    SeqNo, Table,
+};
+use std::{
+    ops::{Bound, RangeBounds},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Splits the key space of a compaction into disjoint key ranges (subcompactions)
+/// that can be compacted in parallel.
+///
+/// The input is split into at most `max_subcompactions` ranges, but never into more ranges
+/// than tables of `target_size` it is going to write, so small compactions are not split.
+/// The ranges are split at the first keys of the input tables.
+///
+/// Returns a single unbounded range if the compaction should not be split.
+pub(super) fn split_key_space(
+    tables: &[Table],
+    target_size: u64,
+    max_subcompactions: usize,
+) -> Vec<OwnedBounds> {
+    let input_size: u64 = tables.iter().map(Table::file_size).sum();
+
+    let count = usize::try_from(input_size.div_ceil(target_size.max(1)))
+        .unwrap_or(usize::MAX)
+        .min(max_subcompactions);
+
+    let mut keys = tables
+        .iter()
+        .map(|table| table.metadata.key_range.min())
+        .collect::<Vec<_>>();
+
+    keys.sort();
+    keys.dedup();
+
+    // NOTE: Splitting at the smallest key would result in an empty range
+    let mut boundaries = (1..count)
+        .map(|idx| idx * keys.len() / count)
+        .filter(|&idx| idx > 0)
+        .filter_map(|idx| keys.get(idx))
+        .collect::<Vec<_>>();
+
+    boundaries.dedup();
+
+    let mut key_ranges = Vec::with_capacity(boundaries.len() + 1);
+    let mut start = Bound::Unbounded;
+
+    // NOTE: Ranges are split at user keys, so all versions of a key end up in the same range
+    for &key in boundaries {
+        key_ranges.push(OwnedBounds {
+            start,
+            end: Bound::Excluded(key.clone()),
+        });
+
+        start = Bound::Included(key.clone());
+    }
+
+    key_ranges.push(OwnedBounds {
+        start,
+        end: Bound::Unbounded,
+    });
+
+    key_ranges
+}
+
+/// Settings that are shared between subcompactions
+pub(super) struct Settings {
+    pub mvcc_gc_watermark: SeqNo,
+    pub evict_tombstones: bool,
+    pub merge_operator: Option<Arc<dyn MergeOperator>>,
+    pub range_tombstones: Vec<RangeTombstone>,
+    pub track_blob_fragmentation: bool,
+    pub stop_signal: StopSignal,
+    pub now: Duration,
+}
+
+/// Compacts every key range on its own thread, writing into its own table writer.
+///
+/// The subcompactions are combined into a single compaction (in key order),
+/// so the compaction result is committed as a single version change.
+pub(super) fn run(
+    tables: &[Table],
+    key_ranges: &[OwnedBounds],
+    table_writers: Vec<MultiWriter>,
+    settings: &Settings,
+) -> crate::Result<(StandardCompaction, FragmentationMap)> {
+    log::debug!(
+        "Splitting compaction of {} tables into {} subcompactions",
+        tables.len(),
+        key_ranges.len(),
+    );
+
+    let compactors = table_writers
+        .into_iter()
+        .enumerate()
+        .map(|(idx, table_writer)| {
+            // NOTE: The first subcompaction owns the input tables,
+            // so they are deleted once the compaction is done
+            let tables_to_rewrite = if idx == 0 {
+                tables.to_vec()
+            } else {
+                Vec::new()
+            };
+
+            Mutex::new(Some(StandardCompaction::new(
+                table_writer,
+                tables_to_rewrite,
+            )))
+        })
+        .collect::<Vec<_>>();
+
+    let (spawn_error, results) = std::thread::scope(|scope| {
+        let mut handles = Vec::with_capacity(key_ranges.len());
+        let mut spawn_error = None;
+
+        for (idx, (key_range, compactor)) in key_ranges.iter().zip(&compactors).enumerate() {
+            let handle = std::thread::Builder::new()
+                .name(format!("lsm-tree-subcompaction-{idx}"))
+                .spawn_scoped(scope, move || {
+                    let compactor = compactor
+                        .lock()
+                        .expect("lock is poisoned")
+                        .take()
+                        .expect("subcompaction should only run once");
+
+                    run_subcompaction(tables, key_range, compactor, settings)
+                });
+
+            match handle {
+                Ok(handle) => handles.push(handle),
+                Err(e) => {
+                    spawn_error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        // NOTE: Join every subcompaction, even if one failed, so no written table is lost
+        let results = handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .expect("subcompaction thread should not panic")
+            })
+            .collect::<Vec<_>>();
+
+        (spawn_error, results)
+    });
+
+    // NOTE: Subcompactions that were never spawned still own their table writer
+    for compactor in compactors {
+        if let Some(compactor) = compactor.into_inner().expect("lock is poisoned") {
+            compactor.abort();
+        }
+    }
+
+    let mut first_error = spawn_error.map(crate::Error::from);
+    let mut subcompactions = Vec::with_capacity(results.len());
+
+    for result in results {
+        match result {
+            Ok(subcompaction) => subcompactions.push(subcompaction),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    if let Some(e) = first_error {
+        for (compactor, _) in subcompactions {
+            compactor.abort();
+        }
+        return Err(e);
+    }
+
+    let mut subcompactions = subcompactions.into_iter();
+
+    let (mut compactor, mut blob_frag_map) = subcompactions
+        .next()
+        .expect("should have at least one key range");
+
+    for (subcompaction, frag_map) in subcompactions {
+        compactor.append_subcompaction(subcompaction);
+        frag_map.merge_into(&mut blob_frag_map);
+    }
+
+    Ok((compactor, blob_frag_map))
+}
+
+/// Runs a single subcompaction, deleting its written tables if it fails.
+fn run_subcompaction(
+    tables: &[Table],
+    key_range: &OwnedBounds,
+    mut compactor: StandardCompaction,
+    settings: &Settings,
+) -> crate::Result<(StandardCompaction, FragmentationMap)> {
+    let mut blob_frag_map = FragmentationMap::default();
+
+    match write_subcompaction(
+        tables,
+        key_range,
+        &mut compactor,
+        settings,
+        &mut blob_frag_map,
+    ) {
+        Ok(()) => Ok((compactor, blob_frag_map)),
+        Err(e) => {
+            compactor.abort();
+            Err(e)
+        }
+    }
+}
+
+fn write_subcompaction(
+    tables: &[Table],
+    key_range: &OwnedBounds,
+    compactor: &mut StandardCompaction,
+    settings: &Settings,
+    blob_frag_map: &mut FragmentationMap,
+) -> crate::Result<()> {
+    let bounds = (
+        key_range.start_bound().map(AsRef::as_ref),
+        key_range.end_bound().map(AsRef::as_ref),
+    );
+
+    let readers = tables
+        .iter()
+        .filter(|table| table.check_key_range_overlap(&bounds))
+        .map(|table| {
+            // NOTE: Don't pollute the block cache with compaction reads
+            Box::new(table.range_with_fill_cache(key_range.clone(), false)) as CompactionReader<'_>
+        })
+        .collect::<Vec<_>>();
+
+    let mut merge_iter = CompactionStream::new(Merger::new(readers), settings.mvcc_gc_watermark)
+        .evict_tombstones(settings.evict_tombstones)
+        .with_merge_operator(settings.merge_operator.clone())
+        .with_range_tombstones(settings.range_tombstones.clone())
+        .expire_values(settings.now);
+
+    if settings.track_blob_fragmentation {
+        merge_iter = merge_iter.with_expiration_callback(blob_frag_map);
+    }
+
+    for (idx, item) in merge_iter.enumerate() {
+        compactor.write(item?)?;
+
+        if idx % 1_000_000 == 0 && settings.stop_signal.is_stopped() {
+            log::debug!("Stopping amidst subcompaction because of stop signal");
+            break;
+        }
+    }
+
+    Ok(())
+}
@@ -2,342 +2,286 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use super::{Choice, CompactionStrategy};
-use crate::{compaction::state::CompactionState, version::Version, Config};
+use super::{Choice, CompactionStrategy, Input as CompactionInput};
+use crate::{
+    compaction::state::CompactionState, config::Config, version::Version, HashSet, KeyRange,
+    KvPair, Table,
+};
 
-// fn desired_level_size_in_bytes(level_idx: u8, ratio: u8, base_size: u32) -> usize {
-//     (ratio as usize).pow(u32::from(level_idx + 1)) * (base_size as usize)
-// }
+#[doc(hidden)]
+pub const NAME: &str = "SizeTieredCompaction";
 
 /// Size-tiered compaction strategy (STCS)
 ///
-/// If a level reaches a threshold, it is merged into a larger table to the next level.
+/// Every level holds a number of sorted runs ("tiers") of similar size.
+/// When a level has `level_ratio` runs, they are merged into a single, larger run in the next level,
+/// so each item is only rewritten about once per level.
 ///
-/// STCS suffers from high read and temporary doubled space amplification, but has good write amplification.
+/// To be able to drop tombstones safely, runs that are merged into the last level
+/// are merged together with the entire last level.
+///
+/// STCS has good write amplification, which makes it a good fit for write-heavy
+/// (ingestion) workloads, but suffers from higher read and space amplification
+/// than leveled compaction, because a key may be stored in many runs.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{compaction::SizeTiered, Config};
+/// use std::sync::Arc;
+///
+/// let tree = Config::new(folder, Default::default())
+///     .compaction_strategy(Arc::new(SizeTiered::default().with_level_ratio(8)))
+///     .open()?;
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
 #[derive(Clone)]
 pub struct Strategy {
-    /// Base size
-    pub base_size: u32,
-
-    /// Size ratio between levels of the LSM tree (a.k.a fanout, growth rate).
+    /// Number of runs a level can hold before they are merged into the next level
     ///
-    /// This is the exponential growth of the from one
-    /// level to the next
+    /// Because every merge combines that many runs, runs grow by roughly this ratio
+    /// from one level to the next. Values below 2 are treated as 2.
     ///
-    /// A level target size is: base_size * level_ratio.pow(#level + 1)
-    #[expect(clippy::doc_markdown)]
+    /// Default = 4
     pub level_ratio: u8,
-}
 
-impl Strategy {
-    /// Creates a new STCS strategy with custom base size
-    #[must_use]
-    pub fn new(base_size: u32, level_ratio: u8) -> Self {
-        Self {
-            base_size,
-            level_ratio,
-        }
-    }
+    /// The target table size as disk (possibly compressed).
+    ///
+    /// Default = 64 MiB
+    pub target_size: u64,
 }
 
 impl Default for Strategy {
     fn default() -> Self {
         Self {
-            base_size: 64 * 1_024 * 1_024,
             level_ratio: 4,
+            target_size:/* 64 Mib */ 64 * 1_024 * 1_024,
         }
     }
 }
 
-impl CompactionStrategy for Strategy {
-    fn get_name(&self) -> &'static str {
-        "TieredStrategy"
+impl Strategy {
+    /// Sets the number of runs a level can hold before they are merged into the next level.
+    #[must_use]
+    pub fn with_level_ratio(mut self, ratio: u8) -> Self {
+        self.level_ratio = ratio;
+        self
     }
 
-    fn choose(&self, version: &Version, _config: &Config, state: &CompactionState) -> Choice {
-        unimplemented!()
+    /// Sets the target table size.
+    #[must_use]
+    pub fn with_target_size(mut self, bytes: u64) -> Self {
+        self.target_size = bytes;
+        self
     }
 }
-/*
-#[cfg(test)]
-mod tests {
-    use super::Strategy;
-    use crate::{
-        cache::Cache,
-        compaction::{Choice, CompactionStrategy, Input as CompactionInput},
-        config::Config,
-        descriptor_table::FileDescriptorTable,
-        file::LEVELS_MANIFEST_FILE,
-        level_manifest::LevelManifest,
-        segment::{
-            block::offset::BlockOffset,
-            block_index::{two_level_index::TwoLevelBlockIndex, BlockIndexImpl},
-            file_offsets::FileOffsets,
-            meta::{Metadata, SegmentId},
-            SegmentInner,
-        },
-        super_segment::Segment,
-        HashSet, KeyRange, SeqNo,
-    };
-    use std::sync::{atomic::AtomicBool, Arc};
-    use test_log::test;
 
-    #[allow(clippy::expect_used)]
-    fn fixture_segment(id: SegmentId, size_mib: u64, max_seqno: SeqNo) -> Segment {
-        todo!()
-
-        /* let cache = Arc::new(Cache::with_capacity_bytes(10 * 1_024 * 1_024));
-
-        let block_index = TwoLevelBlockIndex::new((0, id).into(), cache.clone());
-        let block_index = Arc::new(BlockIndexImpl::TwoLevel(block_index));
-
-        SegmentInner {
-            tree_id: 0,
-            descriptor_table: Arc::new(FileDescriptorTable::new(512, 1)),
-            block_index,
-
-            offsets: FileOffsets {
-                bloom_ptr: BlockOffset(0),
-                range_filter_ptr: BlockOffset(0),
-                index_block_ptr: BlockOffset(0),
-                metadata_ptr: BlockOffset(0),
-                range_tombstones_ptr: BlockOffset(0),
-                tli_ptr: BlockOffset(0),
-                pfx_ptr: BlockOffset(0),
-            },
-
-            metadata: Metadata {
-                data_block_count: 0,
-                index_block_count: 0,
-                data_block_size: 4_096,
-                index_block_size: 4_096,
-                created_at: 0,
-                id,
-                file_size: size_mib * 1_024 * 1_024,
-                compression: crate::segment::meta::CompressionType::None,
-                table_type: crate::segment::meta::TableType::Block,
-                item_count: 0,
-                key_count: 0,
-                key_range: KeyRange::new((vec![].into(), vec![].into())),
-                tombstone_count: 0,
-                range_tombstone_count: 0,
-                uncompressed_size: size_mib * 1_024 * 1_024,
-                seqnos: (0, max_seqno),
-            },
-            cache,
-
-            bloom_filter: Some(BloomFilter::with_fp_rate(1, 0.1)),
-
-            path: "a".into(),
-            is_deleted: AtomicBool::default(),
-        }
-        .into() */
+impl CompactionStrategy for Strategy {
+    fn get_name(&self) -> &'static str {
+        NAME
     }
 
-    #[test]
-    fn tiered_empty_levels() -> crate::Result<()> {
-        let tempdir = tempfile::tempdir()?;
-
-        let compactor = Strategy {
-            base_size: 8 * 1_024 * 1_024,
-            level_ratio: 8,
-        };
-
-        let levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
-
-        assert_eq!(
-            compactor.choose(&levels, &Config::default()),
-            Choice::DoNothing
-        );
-
-        Ok(())
+    fn get_config(&self) -> Vec<KvPair> {
+        vec![
+            (
+                crate::UserKey::from("tiered_level_ratio"),
+                crate::UserValue::from(self.level_ratio.to_le_bytes()),
+            ),
+            (
+                crate::UserKey::from("tiered_target_size"),
+                crate::UserValue::from(self.target_size.to_le_bytes()),
+            ),
+        ]
     }
 
-    #[test]
-    fn tiered_default_l0() -> crate::Result<()> {
-        let tempdir = tempfile::tempdir()?;
+    fn choose(&self, version: &Version, _: &Config, state: &CompactionState) -> Choice {
+        choose(version, state, self.level_ratio, self.target_size, false)
+    }
+}
 
-        let compactor = Strategy {
-            base_size: 8 * 1_024 * 1_024,
-            level_ratio: 4,
+/// Chooses the oldest runs of the deepest level that has too many runs,
+/// and merges them into the next level.
+///
+/// If `leveled_last_level` is set, only tables of the last level that overlap
+/// with the merged runs are merged as well, so the last level stays a single run
+/// (lazy leveling). Otherwise, the entire last level is merged.
+pub(super) fn choose(
+    version: &Version,
+    state: &CompactionState,
+    level_ratio: u8,
+    target_size: u64,
+    leveled_last_level: bool,
+) -> Choice {
+    let merge_width = usize::from(level_ratio.max(2));
+    let last_level_idx = version.level_count() - 1;
+
+    // NOTE: Check the deepest levels first, so they make room for the levels above them
+    for idx in (0..last_level_idx).rev() {
+        let Some(level) = version.level(idx) else {
+            continue;
         };
-        let config = Config::default();
-
-        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
 
-        levels.add(fixture_segment(1, 8, 5));
-        assert_eq!(compactor.choose(&levels, &config), Choice::DoNothing);
+        if level.run_count() < merge_width {
+            continue;
+        }
 
-        levels.add(fixture_segment(2, 8, 6));
-        assert_eq!(compactor.choose(&levels, &config), Choice::DoNothing);
+        let dest_level = idx + 1;
 
-        levels.add(fixture_segment(3, 8, 7));
-        assert_eq!(compactor.choose(&levels, &config), Choice::DoNothing);
+        if version.level_is_busy(idx, state.hidden_set())
+            || version.level_is_busy(dest_level, state.hidden_set())
+        {
+            continue;
+        }
 
-        levels.add(fixture_segment(4, 8, 8));
+        // NOTE: Runs are ordered from newest to oldest. We merge the oldest runs,
+        // so the level above the destination never holds older data than it
+        let tables = level
+            .iter()
+            .rev()
+            .take(merge_width)
+            .flat_map(|run| run.iter())
+            .collect::<Vec<_>>();
+
+        let mut table_ids: HashSet<_> = tables.iter().map(|table| table.id()).collect();
+
+        // NOTE: Tombstones are evicted when writing into the last level, so we need to
+        // merge all versions of the keys in the last level, otherwise we would resurrect data
+        if dest_level == last_level_idx {
+            if let Some(last_level) = version.level(last_level_idx) {
+                if leveled_last_level && last_level.run_count() <= 1 {
+                    let key_range =
+                        KeyRange::aggregate(tables.iter().map(|table| &table.metadata.key_range));
+
+                    table_ids.extend(last_level.get_overlapping(&key_range).map(Table::id));
+                } else {
+                    table_ids.extend(last_level.list_ids());
+                }
+            }
+        }
 
-        assert_eq!(
-            compactor.choose(&levels, &config),
-            Choice::Merge(CompactionInput {
-                dest_level: 1,
-                segment_ids: set![1, 2, 3, 4],
-                target_size: u64::MAX,
-            })
-        );
+        // NOTE: Level count is 255 max
+        #[expect(clippy::cast_possible_truncation)]
+        let dest_level = dest_level as u8;
 
-        Ok(())
+        return Choice::Merge(CompactionInput {
+            table_ids,
+            dest_level,
+            canonical_level: dest_level,
+            target_size,
+        });
     }
 
-    #[test]
-    fn tiered_ordering() -> crate::Result<()> {
-        let tempdir = tempfile::tempdir()?;
-
-        let compactor = Strategy {
-            base_size: 8 * 1_024 * 1_024,
-            level_ratio: 2,
-        };
-        let config = Config::default();
+    Choice::DoNothing
+}
 
-        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+#[cfg(test)]
+mod tests {
+    use super::Strategy;
+    use crate::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+    use std::sync::Arc;
+    use test_log::test;
 
-        levels.add(fixture_segment(1, 8, 0));
-        levels.add(fixture_segment(2, 8, 1));
-        levels.add(fixture_segment(3, 8, 2));
-        levels.add(fixture_segment(4, 8, 3));
+    #[test]
+    fn tiered_empty_levels() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tree = Config::new(dir.path(), SequenceNumberCounter::default()).open()?;
 
-        assert_eq!(
-            compactor.choose(&levels, &config),
-            Choice::Merge(CompactionInput {
-                dest_level: 1,
-                segment_ids: set![1, 2],
-                target_size: u64::MAX,
-            })
-        );
+        tree.compact(Arc::new(Strategy::default()), 0)?;
 
+        assert_eq!(0, tree.table_count());
         Ok(())
     }
 
     #[test]
-    fn tiered_more_than_min() -> crate::Result<()> {
-        let tempdir = tempfile::tempdir()?;
+    fn tiered_below_ratio() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tree = Config::new(dir.path(), SequenceNumberCounter::default()).open()?;
 
-        let compactor = Strategy {
-            base_size: 8 * 1_024 * 1_024,
-            level_ratio: 4,
-        };
-        let config = Config::default();
-
-        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
-        levels.add(fixture_segment(1, 8, 5));
-        levels.add(fixture_segment(2, 8, 6));
-        levels.add(fixture_segment(3, 8, 7));
-        levels.add(fixture_segment(4, 8, 8));
-
-        levels.insert_into_level(1, fixture_segment(5, 8 * 4, 9));
-        levels.insert_into_level(1, fixture_segment(6, 8 * 4, 10));
-        levels.insert_into_level(1, fixture_segment(7, 8 * 4, 11));
-        levels.insert_into_level(1, fixture_segment(8, 8 * 4, 12));
-
-        assert_eq!(
-            compactor.choose(&levels, &config),
-            Choice::Merge(CompactionInput {
-                dest_level: 2,
-                segment_ids: set![5, 6, 7, 8],
-                target_size: u64::MAX,
-            })
-        );
+        for i in 0..3u8 {
+            tree.insert("a", [i], u64::from(i));
+            tree.flush_active_memtable(0)?;
+        }
+
+        tree.compact(Arc::new(Strategy::default()), 0)?;
 
+        assert_eq!(3, tree.table_count());
+        assert_eq!(Some(3), tree.level_table_count(0));
         Ok(())
     }
 
     #[test]
-    fn tiered_many_segments() -> crate::Result<()> {
-        let tempdir = tempfile::tempdir()?;
+    fn tiered_merges_into_next_level() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tree = Config::new(dir.path(), SequenceNumberCounter::default()).open()?;
+
+        for i in 0..4u8 {
+            tree.insert("a", [i], u64::from(i));
+            tree.insert([b'k', i].as_slice(), "v", u64::from(i));
+            tree.flush_active_memtable(0)?;
+        }
 
-        let compactor = Strategy {
-            base_size: 8 * 1_024 * 1_024,
-            level_ratio: 2,
-        };
-        let config = Config::default();
-
-        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
-        levels.add(fixture_segment(1, 8, 5));
-        levels.add(fixture_segment(2, 8, 6));
-        levels.add(fixture_segment(3, 8, 7));
-        levels.add(fixture_segment(4, 8, 8));
-
-        assert_eq!(
-            compactor.choose(&levels, &config),
-            Choice::Merge(CompactionInput {
-                dest_level: 1,
-                segment_ids: set![1, 2],
-                target_size: u64::MAX,
-            })
-        );
+        tree.compact(Arc::new(Strategy::default()), 0)?;
 
-        Ok(())
-    }
+        assert_eq!(Some(0), tree.level_table_count(0));
+        assert_eq!(Some(1), tree.level_table_count(1));
+        assert_eq!(5, tree.len(SeqNo::MAX, None)?);
+        assert_eq!(Some([3].into()), tree.get("a", SeqNo::MAX)?);
 
-    #[test]
-    fn tiered_deeper_level() -> crate::Result<()> {
-        let tempdir = tempfile::tempdir()?;
+        // NOTE: Newer runs stay in L0 until there are enough of them
+        tree.insert("a", [4], 4);
+        tree.flush_active_memtable(0)?;
+        tree.compact(Arc::new(Strategy::default()), 0)?;
 
-        let compactor = Strategy {
-            base_size: 8 * 1_024 * 1_024,
-            level_ratio: 2,
-        };
-        let config = Config::default();
-
-        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
-        levels.add(fixture_segment(1, 8, 5));
-
-        levels.insert_into_level(1, fixture_segment(2, 8 * 2, 6));
-        levels.insert_into_level(1, fixture_segment(3, 8 * 2, 7));
-
-        assert_eq!(
-            compactor.choose(&levels, &config),
-            Choice::Merge(CompactionInput {
-                dest_level: 2,
-                segment_ids: set![2, 3],
-                target_size: u64::MAX,
-            })
-        );
-
-        let tempdir = tempfile::tempdir()?;
-        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
-
-        levels.insert_into_level(2, fixture_segment(2, 8 * 4, 5));
-        levels.insert_into_level(2, fixture_segment(3, 8 * 4, 6));
-
-        assert_eq!(
-            compactor.choose(&levels, &config),
-            Choice::Merge(CompactionInput {
-                dest_level: 3,
-                segment_ids: set![2, 3],
-                target_size: u64::MAX,
-            })
-        );
+        assert_eq!(Some(1), tree.level_table_count(0));
+        assert_eq!(Some(1), tree.level_table_count(1));
+        assert_eq!(Some([4].into()), tree.get("a", SeqNo::MAX)?);
 
         Ok(())
     }
 
     #[test]
-    fn tiered_last_level() -> crate::Result<()> {
-        let tempdir = tempfile::tempdir()?;
+    fn tiered_last_level_drops_tombstones() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tree = Config::new(dir.path(), SequenceNumberCounter::default())
+            .level_count(3)
+            .open()?;
+
+        let strategy = Arc::new(Strategy::default().with_level_ratio(2));
+        let mut seqno = 0;
+
+        for _ in 0..8 {
+            tree.insert("a", "v", seqno);
+            tree.insert("b", "v", seqno);
+            seqno += 1;
+            tree.flush_active_memtable(0)?;
+
+            for _ in 0..3 {
+                tree.compact(strategy.clone(), seqno)?;
+            }
+        }
 
-        let compactor = Strategy {
-            base_size: 8 * 1_024 * 1_024,
-            level_ratio: 2,
-        };
-        let config = Config::default();
+        assert_eq!(Some(1), tree.level_table_count(2));
 
-        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
-        levels.insert_into_level(3, fixture_segment(2, 8, 5));
-        levels.insert_into_level(3, fixture_segment(3, 8, 5));
+        for _ in 0..4 {
+            tree.remove("a", seqno);
+            seqno += 1;
+            tree.flush_active_memtable(0)?;
+
+            for _ in 0..3 {
+                tree.compact(strategy.clone(), seqno)?;
+            }
+        }
 
-        assert_eq!(compactor.choose(&levels, &config), Choice::DoNothing);
+        // NOTE: The tombstones were merged into the last level (together with all its data)
+        assert_eq!(Some(0), tree.level_table_count(0));
+        assert_eq!(Some(0), tree.level_table_count(1));
+        assert_eq!(Some(1), tree.level_table_count(2));
+        assert_eq!(0, tree.tombstone_count());
+        assert!(!tree.contains_key("a", SeqNo::MAX)?);
+        assert!(tree.contains_key("b", SeqNo::MAX)?);
 
         Ok(())
     }
 }
- */
@@ -6,6 +6,7 @@ use super::{CompactionStrategy, Input as CompactionPayload};
 use crate::{
     blob_tree::FragmentationMap,
     compaction::{
+        drop_range::OwnedBounds,
         flavour::{RelocatingCompaction, StandardCompaction},
         state::CompactionState,
         stream::CompactionStream,
@@ -18,7 +19,8 @@ use crate::{
     tree::inner::TreeId,
     version::{SuperVersions, Version},
     vlog::{BlobFileMergeScanner, BlobFileScanner, BlobFileWriter},
-    BlobFile, Config, HashSet, InternalValue, SeqNo, SequenceNumberCounter, TableId,
+    BlobFile, Config, HashSet, InternalValue, KeyRange, SeqNo, SequenceNumberCounter, Table,
+    TableId,
 };
 use std::{
     sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard},
@@ -86,6 +88,8 @@ impl Options {
 ///
 /// This will block until the compactor is fully finished.
 pub fn do_compaction(opts: &Options) -> crate::Result<()> {
+    opts.config.ensure_writable()?;
+
     let compaction_state = opts.compaction_state.lock().expect("lock is poisoned");
 
     let version_history_lock = opts.version_history.read().expect("lock is poisoned");
@@ -189,7 +193,7 @@ fn create_compaction_stream<'a>(
     })
 }
 
-fn move_tables(
+pub fn move_tables(
     compaction_state: MutexGuard<'_, CompactionState>,
     opts: &Options,
     payload: &CompactionPayload,
@@ -211,6 +215,7 @@ fn move_tables(
     let table_ids = payload.table_ids.iter().copied().collect::<Vec<_>>();
 
     version_history_lock.upgrade_version(
+        &*opts.config.fs,
         &opts.config.path,
         |current| {
             let mut copy = current.clone();
@@ -224,7 +229,11 @@ fn move_tables(
         &opts.global_seqno,
     )?;
 
-    if let Err(e) = version_history_lock.maintenance(&opts.config.path, opts.mvcc_gc_watermark) {
+    if let Err(e) = version_history_lock.maintenance(
+        &*opts.config.fs,
+        &opts.config.path,
+        opts.mvcc_gc_watermark,
+    ) {
         log::error!("Manifest maintenance failed: {e:?}");
         return Err(e);
     }
@@ -232,14 +241,54 @@ fn move_tables(
     Ok(())
 }
 
+/// Returns `true` if no older versions of the compacted keys can exist beneath the compaction,
+/// so tombstones can be evicted without resurrecting data.
+///
+/// This is always the case when compacting into the last level, otherwise no other table
+/// in or below the level of the shallowest input table may overlap with the compacted key range.
+pub(super) fn can_evict_tombstones(version: &Version, payload: &CompactionPayload) -> bool {
+    let last_level = version.level_count() - 1;
+
+    if usize::from(payload.dest_level) == last_level {
+        return true;
+    }
+
+    let input_tables = version
+        .iter_levels()
+        .enumerate()
+        .flat_map(|(idx, level)| {
+            level
+                .iter()
+                .flat_map(|run| run.iter())
+                .map(move |table| (idx, table))
+        })
+        .filter(|(_, table)| payload.table_ids.contains(&table.id()))
+        .collect::<Vec<_>>();
+
+    let Some(shallowest_level) = input_tables.iter().map(|(idx, _)| *idx).min() else {
+        return false;
+    };
+
+    let key_range = KeyRange::aggregate(
+        input_tables
+            .iter()
+            .map(|(_, table)| &table.metadata.key_range),
+    );
+
+    !version
+        .iter_levels()
+        .skip(shallowest_level)
+        .flat_map(|level| level.iter())
+        .flat_map(|run| run.get_overlapping(&key_range))
+        .any(|table| !payload.table_ids.contains(&table.id()))
+}
+
 /// Picks blob files to rewrite (defragment)
 fn pick_blob_files_to_rewrite(
     picked_tables: &HashSet<TableId>,
     current_version: &Version,
     blob_opts: &crate::KvSeparationOptions,
 ) -> crate::Result<Vec<BlobFile>> {
-    use crate::Table;
-
     // We start off by getting all the blob files that are referenced by the tables
     // that we want to compact.
     let linked_blob_files = picked_tables
@@ -366,6 +415,35 @@ fn merge_tables(
         return Ok(());
     };
 
+    let blob_files_to_rewrite = match &opts.config.kv_separation_opts {
+        Some(blob_opts) => pick_blob_files_to_rewrite(
+            &payload.table_ids,
+            &current_super_version.version,
+            blob_opts,
+        )?,
+        None => Vec::new(),
+    };
+
+    // NOTE: Blob files are relocated in key order, so relocating compactions cannot be split
+    if blob_files_to_rewrite.is_empty() {
+        let key_ranges = super::subcompaction::split_key_space(
+            &tables,
+            payload.target_size,
+            opts.config.max_subcompactions,
+        );
+
+        if key_ranges.len() > 1 {
+            return merge_tables_in_subcompactions(
+                compaction_state,
+                version_history_lock,
+                opts,
+                payload,
+                &tables,
+                &key_ranges,
+            );
+        }
+    }
+
     let mut blob_frag_map = FragmentationMap::default();
 
     let Some(mut merge_iter) = create_compaction_stream(
@@ -381,13 +459,16 @@ fn merge_tables(
     };
 
     let dst_lvl = payload.canonical_level.into();
-    let last_level = opts.config.level_count - 1;
 
-    // NOTE: Only evict tombstones when reaching the last level,
+    // NOTE: Only evict tombstones when there is no data beneath them,
     // That way we don't resurrect data beneath the tombstone
-    let is_last_level = payload.dest_level == last_level;
+    let evict_tombstones = can_evict_tombstones(&current_super_version.version, payload);
 
-    merge_iter = merge_iter.evict_tombstones(is_last_level);
+    merge_iter = merge_iter
+        .evict_tombstones(evict_tombstones)
+        .with_merge_operator(opts.config.merge_operator.clone())
+        .with_range_tombstones(current_super_version.version.range_tombstones().to_vec())
+        .expire_values(opts.config.clock.now());
 
     let table_writer =
         super::flavour::prepare_table_writer(&current_super_version.version, opts, payload)?;
@@ -398,12 +479,6 @@ fn merge_tables(
         Some(blob_opts) => {
             merge_iter = merge_iter.with_expiration_callback(&mut blob_frag_map);
 
-            let blob_files_to_rewrite = pick_blob_files_to_rewrite(
-                &payload.table_ids,
-                &current_super_version.version,
-                blob_opts,
-            )?;
-
             if blob_files_to_rewrite.is_empty() {
                 log::debug!("No blob relocation needed");
 
@@ -421,15 +496,23 @@ fn merge_tables(
                 let scanner = BlobFileMergeScanner::new(
                     blob_files_to_rewrite
                         .iter()
-                        .map(|bf| BlobFileScanner::new(&bf.0.path, bf.id()))
+                        .map(|bf| {
+                            BlobFileScanner::new(&*opts.config.fs, &bf.0.path, bf.id())
+                                .map(|scanner| scanner.use_encryption(bf.0.encryptor.clone()))
+                        })
                         .collect::<crate::Result<Vec<_>>>()?,
                 );
 
                 let writer = BlobFileWriter::new(
+                    opts.config.fs.clone(),
                     opts.blob_file_id_generator.clone(),
                     blob_opts.file_target_size,
                     opts.config.path.join(BLOBS_FOLDER),
+                    opts.config.directory_shards,
                 )?
+                .use_clock(opts.config.clock.clone())
+                .use_encryption(opts.config.encryptor.clone())
+                .use_rate_limiter(opts.config.rate_limiter.clone())
                 .use_passthrough_compression(blob_opts.compression);
 
                 let inner = StandardCompaction::new(table_writer, tables);
@@ -473,6 +556,80 @@ fn merge_tables(
         Ok(())
     })?;
 
+    commit_compaction(opts, payload, compactor, dst_lvl, blob_frag_map)
+}
+
+/// Compacts the key ranges of a compaction in parallel, see [`Config::max_subcompactions`]
+fn merge_tables_in_subcompactions(
+    mut compaction_state: MutexGuard<'_, CompactionState>,
+    version_history_lock: RwLockReadGuard<'_, SuperVersions>,
+    opts: &Options,
+    payload: &CompactionPayload,
+    tables: &[Table],
+    key_ranges: &[OwnedBounds],
+) -> crate::Result<()> {
+    let current_super_version = version_history_lock.latest_version();
+
+    let dst_lvl = payload.canonical_level.into();
+
+    let settings = super::subcompaction::Settings {
+        mvcc_gc_watermark: opts.mvcc_gc_watermark,
+
+        // NOTE: Only evict tombstones when there is no data beneath them,
+        // That way we don't resurrect data beneath the tombstone
+        evict_tombstones: can_evict_tombstones(&current_super_version.version, payload),
+
+        merge_operator: opts.config.merge_operator.clone(),
+        range_tombstones: current_super_version.version.range_tombstones().to_vec(),
+        track_blob_fragmentation: opts.config.kv_separation_opts.is_some(),
+        stop_signal: opts.stop_signal.clone(),
+        now: opts.config.clock.now(),
+    };
+
+    let table_writers = key_ranges
+        .iter()
+        .map(|_| {
+            super::flavour::prepare_table_writer(&current_super_version.version, opts, payload)
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    drop(version_history_lock);
+
+    {
+        compaction_state
+            .hidden_set_mut()
+            .hide(payload.table_ids.iter().copied());
+    }
+
+    // IMPORTANT: Unlock exclusive compaction lock as we are now doing the actual (CPU-intensive) compaction
+    drop(compaction_state);
+
+    let mut result = None;
+
+    hidden_guard(payload, opts, || {
+        result = Some(super::subcompaction::run(
+            tables,
+            key_ranges,
+            table_writers,
+            &settings,
+        )?);
+
+        Ok(())
+    })?;
+
+    let (compactor, blob_frag_map) = result.expect("subcompactions should have run");
+
+    commit_compaction(opts, payload, Box::new(compactor), dst_lvl, blob_frag_map)
+}
+
+/// Installs the tables written by a compaction into a new version.
+fn commit_compaction(
+    opts: &Options,
+    payload: &CompactionPayload,
+    compactor: Box<dyn super::flavour::CompactionFlavour + '_>,
+    dst_lvl: usize,
+    blob_frag_map: FragmentationMap,
+) -> crate::Result<()> {
     let mut compaction_state = opts.compaction_state.lock().expect("lock is poisoned");
 
     log::trace!("Acquiring super version write lock");
@@ -504,7 +661,7 @@ fn merge_tables(
         .show(payload.table_ids.iter().copied());
 
     version_history_lock
-        .maintenance(&opts.config.path, opts.mvcc_gc_watermark)
+        .maintenance(&*opts.config.fs, &opts.config.path, opts.mvcc_gc_watermark)
         .inspect_err(|e| {
             log::error!("Manifest maintenance failed: {e:?}");
         })?;
@@ -517,7 +674,7 @@ fn merge_tables(
     Ok(())
 }
 
-fn drop_tables(
+pub fn drop_tables(
     compaction_state: MutexGuard<'_, CompactionState>,
     opts: &Options,
     ids_to_drop: &[TableId],
@@ -561,6 +718,7 @@ fn drop_tables(
     // IMPORTANT: Write the manifest with the removed tables first
     // Otherwise the table files are deleted, but are still referenced!
     version_history_lock.upgrade_version(
+        &*opts.config.fs,
         &opts.config.path,
         |current| {
             let mut copy = current.clone();
@@ -574,7 +732,11 @@ fn drop_tables(
         &opts.global_seqno,
     )?;
 
-    if let Err(e) = version_history_lock.maintenance(&opts.config.path, opts.mvcc_gc_watermark) {
+    if let Err(e) = version_history_lock.maintenance(
+        &*opts.config.fs,
+        &opts.config.path,
+        opts.mvcc_gc_watermark,
+    ) {
         log::error!("Manifest maintenance failed: {e:?}");
         return Err(e);
     }
@@ -4,7 +4,99 @@
 
 use crate::coding::{Decode, Encode};
 use byteorder::{ReadBytesExt, WriteBytesExt};
-use std::io::{Read, Write};
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+    sync::{Arc, RwLock},
+};
+
+/// Custom compression codec
+///
+/// Codecs are registered process-wide using [`register_compressor`], under an ID that is
+/// stored in the table and blob file metadata, and are then selected using [`CompressionType::Custom`].
+///
+/// Because the ID is persisted, a codec needs to stay registered under the same ID
+/// for as long as any data that was compressed with it exists,
+/// otherwise that data cannot be read.
+pub trait Compressor: std::fmt::Debug + Send + Sync + 'static {
+    /// Compresses a block of data.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data could not be compressed.
+    fn compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>>;
+
+    /// Decompresses a block of data that was compressed using [`Compressor::compress`].
+    ///
+    /// `uncompressed_len` is the length of the original data.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data could not be decompressed.
+    fn decompress(&self, data: &[u8], uncompressed_len: usize) -> std::io::Result<Vec<u8>>;
+}
+
+static COMPRESSORS: RwLock<BTreeMap<u8, Arc<dyn Compressor>>> = RwLock::new(BTreeMap::new());
+
+/// Registers a custom compression codec under the given ID, replacing any codec
+/// that was previously registered under it.
+///
+/// The codec can then be used with [`CompressionType::Custom`].
+///
+/// # Panics
+///
+/// Panics if the lock is poisoned.
+pub fn register_compressor(id: u8, compressor: Arc<dyn Compressor>) {
+    COMPRESSORS
+        .write()
+        .expect("lock is poisoned")
+        .insert(id, compressor);
+}
+
+fn get_compressor(id: u8) -> Option<Arc<dyn Compressor>> {
+    COMPRESSORS
+        .read()
+        .expect("lock is poisoned")
+        .get(&id)
+        .cloned()
+}
+
+/// Compresses data using a registered custom codec.
+pub fn compress_custom(id: u8, data: &[u8]) -> crate::Result<Vec<u8>> {
+    let Some(compressor) = get_compressor(id) else {
+        log::error!("Compressor with ID {id} is not registered");
+
+        return Err(crate::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("compressor with ID {id} is not registered"),
+        )));
+    };
+
+    Ok(compressor.compress(data)?)
+}
+
+/// Decompresses data using a registered custom codec.
+pub fn decompress_custom(id: u8, data: &[u8], uncompressed_len: usize) -> crate::Result<Vec<u8>> {
+    let Some(compressor) = get_compressor(id) else {
+        log::error!("Compressor with ID {id} is not registered");
+        return Err(crate::Error::Decompress(CompressionType::Custom(id)));
+    };
+
+    let data = compressor.decompress(data, uncompressed_len).map_err(|e| {
+        log::error!("Decompression with compressor {id} failed: {e:?}");
+        crate::Error::Decompress(CompressionType::Custom(id))
+    })?;
+
+    if data.len() != uncompressed_len {
+        log::error!(
+            "Compressor {id} returned {}B, expected {uncompressed_len}B",
+            data.len(),
+        );
+        return Err(crate::Error::Decompress(CompressionType::Custom(id)));
+    }
+
+    Ok(data)
+}
 
 /// Compression algorithm to use
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -20,6 +112,11 @@ pub enum CompressionType {
     /// on speed over compression ratio.
     #[cfg(feature = "lz4")]
     Lz4,
+
+    /// Custom compression, using the codec registered under the given ID
+    ///
+    /// See [`register_compressor`].
+    Custom(u8),
 }
 
 impl Encode for CompressionType {
@@ -33,6 +130,11 @@ impl Encode for CompressionType {
             Self::Lz4 => {
                 writer.write_u8(1)?;
             }
+
+            Self::Custom(id) => {
+                writer.write_u8(2)?;
+                writer.write_u8(*id)?;
+            }
         }
 
         Ok(())
@@ -49,6 +151,8 @@ impl Decode for CompressionType {
             #[cfg(feature = "lz4")]
             1 => Ok(Self::Lz4),
 
+            2 => Ok(Self::Custom(reader.read_u8()?)),
+
             tag => Err(crate::Error::InvalidTag(("CompressionType", tag))),
         }
     }
@@ -56,16 +160,14 @@ impl Decode for CompressionType {
 
 impl std::fmt::Display for CompressionType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::None => "none",
-
-                #[cfg(feature = "lz4")]
-                Self::Lz4 => "lz4",
-            }
-        )
+        match self {
+            Self::None => write!(f, "none"),
+
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => write!(f, "lz4"),
+
+            Self::Custom(id) => write!(f, "custom#{id}"),
+        }
     }
 }
 
@@ -80,6 +182,26 @@ mod tests {
         assert_eq!(1, serialized.len());
     }
 
+    #[test]
+    fn compression_serialize_custom() -> crate::Result<()> {
+        let serialized = CompressionType::Custom(7).encode_into_vec();
+        assert_eq!(2, serialized.len());
+
+        let deserialized = CompressionType::decode_from(&mut &serialized[..])?;
+        assert_eq!(CompressionType::Custom(7), deserialized);
+
+        Ok(())
+    }
+
+    #[test]
+    fn compression_custom_unregistered() {
+        assert!(compress_custom(254, b"abc").is_err());
+        assert!(matches!(
+            decompress_custom(254, b"abc", 3),
+            Err(crate::Error::Decompress(CompressionType::Custom(254))),
+        ));
+    }
+
     #[cfg(feature = "lz4")]
     mod lz4 {
         use super::*;
@@ -0,0 +1,36 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Durability of journaled writes, see [`Config::durability`](crate::Config::durability)
+///
+/// Regardless of the durability level, [`AbstractTree::sync`](crate::AbstractTree::sync)
+/// can be used to make all previous writes durable.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Durability {
+    /// Writes are buffered in memory, and only written to the journal file
+    /// when the buffer is full, or the journal is synced or rotated.
+    ///
+    /// Writes may be lost if the process crashes.
+    /// Change data capture readers only see writes once they are written to the journal file.
+    None,
+
+    /// Every write is written to the journal file (the OS page cache) before being acknowledged,
+    /// but not fsynced.
+    ///
+    /// Writes survive a process crash, but may be lost on power loss or an OS crash.
+    #[default]
+    Buffer,
+
+    /// Every write is written to the journal file and fsynced before being acknowledged.
+    ///
+    /// Writes survive power loss, but every write pays the latency of an fsync.
+    /// Concurrent writers share fsyncs (group commit), so not every write needs its own fsync.
+    SyncEveryWrite,
+
+    /// Like [`Durability::Buffer`], but the journal is fsynced in the background
+    /// every `n` milliseconds.
+    ///
+    /// At most the writes of the last interval may be lost on power loss or an OS crash.
+    SyncInterval(u64),
+}
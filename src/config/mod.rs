@@ -4,6 +4,7 @@
 
 mod block_size;
 mod compression;
+mod durability;
 mod filter;
 mod hash_ratio;
 mod pinning;
@@ -11,6 +12,7 @@ mod restart_interval;
 
 pub use block_size::BlockSizePolicy;
 pub use compression::CompressionPolicy;
+pub use durability::Durability;
 pub use filter::{BloomConstructionPolicy, FilterPolicy, FilterPolicyEntry};
 pub use hash_ratio::HashRatioPolicy;
 pub use pinning::PinningPolicy;
@@ -20,12 +22,17 @@ pub use restart_interval::RestartIntervalPolicy;
 pub type PartioningPolicy = PinningPolicy;
 
 use crate::{
-    path::absolute_path, version::DEFAULT_LEVEL_COUNT, AnyTree, BlobTree, Cache, CompressionType,
-    DescriptorTable, SequenceNumberCounter, Tree,
+    compaction::{CompactionStrategy, Leveled},
+    path::absolute_path,
+    version::DEFAULT_LEVEL_COUNT,
+    AnyTree, BlobTree, Cache, Clock, CompressionType, DescriptorTable, Encryptor, Env, Filesystem,
+    KeyExtractor, MergeOperator, RateLimiter, SequenceNumberCounter, StdFilesystem, SystemClock,
+    Tree, WriteStallListener, WriteStallOptions,
 };
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 /// LSM-tree type
@@ -59,6 +66,23 @@ impl TryFrom<u8> for TreeType {
     }
 }
 
+/// How a tree folder is opened, see [`Config::open`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum OpenMode {
+    /// Creates a new tree, failing if one exists
+    CreateNew,
+
+    /// Opens an existing tree, failing if none exists
+    OpenExisting,
+
+    /// Opens an existing tree, or creates a new one
+    #[default]
+    OpenOrCreate,
+
+    /// Opens an existing tree as a secondary instance, without locking or modifying it
+    Secondary,
+}
+
 const DEFAULT_FILE_FOLDER: &str = ".lsm.data";
 
 /// Options for key-value separation
@@ -159,6 +183,7 @@ impl KvSeparationOptions {
 }
 
 #[derive(Clone)]
+#[expect(clippy::struct_excessive_bools)]
 /// Tree configuration builder
 pub struct Config {
     /// Folder path
@@ -169,6 +194,9 @@ pub struct Config {
     #[doc(hidden)]
     pub cache: Arc<Cache>,
 
+    /// Cache for blobs of key-value separated trees, see [`Config::use_blob_cache`]
+    pub(crate) blob_cache: Option<Arc<Cache>>,
+
     /// Descriptor table to use
     #[doc(hidden)]
     pub descriptor_table: Arc<DescriptorTable>,
@@ -178,6 +206,35 @@ pub struct Config {
     /// Once set, the level count is fixed (in the "manifest" file)
     pub level_count: u8,
 
+    /// Number of subfolders that table and blob files are spread across
+    ///
+    /// Once set, the shard count is fixed (in the "manifest" file)
+    pub directory_shards: u16,
+
+    /// Size of the active memtable (write buffer) in bytes after which it should be flushed
+    pub max_memtable_size: u64,
+
+    /// Compaction strategy, see [`Config::compaction_strategy`]
+    pub compaction_strategy: Arc<dyn CompactionStrategy + Send + Sync>,
+
+    /// Number of background threads used for flushing memtables
+    pub flush_workers: usize,
+
+    /// Number of background threads used for compaction
+    pub compaction_workers: usize,
+
+    /// Number of background threads that flush and compact a standalone tree
+    pub background_threads: usize,
+
+    /// MVCC watermark of the background threads, see [`Config::background_gc_watermark`]
+    pub(crate) background_gc_watermark: Option<SequenceNumberCounter>,
+
+    /// Maximum number of threads a single compaction is split into
+    pub max_subcompactions: usize,
+
+    /// Age after which tables are forced through compaction
+    pub periodic_compaction_interval: Option<Duration>,
+
     /// What type of compression is used for data blocks
     pub data_block_compression_policy: CompressionPolicy,
 
@@ -230,6 +287,48 @@ pub struct Config {
     #[doc(hidden)]
     pub kv_separation_opts: Option<KvSeparationOptions>,
 
+    /// If `true`, all writes are appended to a journal
+    pub(crate) journal: bool,
+
+    /// Durability of journaled writes
+    pub(crate) durability: Durability,
+
+    /// Storage backend used for all file access
+    pub(crate) fs: Arc<dyn Filesystem>,
+
+    /// If `true`, the tree is kept in memory only, see [`Config::ephemeral`]
+    pub(crate) ephemeral: bool,
+
+    /// If `true`, the tree is stored in a temporary folder, see [`Config::temporary`]
+    pub(crate) temporary: bool,
+
+    /// Temporary folder, which is deleted once the last config referencing it is dropped
+    pub(crate) temporary_folder: Option<Arc<tempfile::TempDir>>,
+
+    /// Whether the tree is created or opened, see [`Config::open`]
+    pub(crate) open_mode: OpenMode,
+
+    /// Time source used for timestamps and time-based compaction
+    pub(crate) clock: Arc<dyn Clock>,
+
+    /// Encryption-at-rest, see [`Config::with_encryption`]
+    pub(crate) encryptor: Option<Arc<dyn Encryptor>>,
+
+    /// Key extractor for existence checks, see [`Config::with_key_extractor`]
+    pub(crate) key_extractor: Option<Arc<dyn KeyExtractor>>,
+
+    /// Merge operator for merge operands, see [`Config::with_merge_operator`]
+    pub(crate) merge_operator: Option<Arc<dyn MergeOperator>>,
+
+    /// Write throughput limit of flushes and compactions, see [`Config::with_rate_limiter`]
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+
+    /// Write stall thresholds, see [`Config::with_write_stall`]
+    pub(crate) write_stall: Option<WriteStallOptions>,
+
+    /// Listener for write stall changes, see [`Config::with_write_stall_listener`]
+    pub(crate) write_stall_listener: Option<Arc<dyn WriteStallListener>>,
+
     /// The global sequence number generator
     ///
     /// Should be shared between multple trees of a database
@@ -242,6 +341,7 @@ impl Default for Config {
         Self {
             path: absolute_path(Path::new(DEFAULT_FILE_FOLDER)),
             descriptor_table: Arc::new(DescriptorTable::new(256)),
+            blob_cache: None,
             seqno: SequenceNumberCounter::default(),
 
             cache: Arc::new(Cache::with_capacity_bytes(
@@ -252,6 +352,24 @@ impl Default for Config {
             index_block_restart_interval_policy: RestartIntervalPolicy::all(1),
 
             level_count: DEFAULT_LEVEL_COUNT,
+            directory_shards: 1,
+
+            max_memtable_size: /* 64 MiB */ 64 * 1_024 * 1_024,
+
+            compaction_strategy: Arc::new(Leveled::default()),
+
+            flush_workers: 1,
+
+            compaction_workers: std::thread::available_parallelism()
+                .map_or(1, usize::from)
+                .min(4),
+
+            background_threads: 0,
+            background_gc_watermark: None,
+
+            max_subcompactions: 1,
+
+            periodic_compaction_interval: None,
 
             data_block_size_policy: BlockSizePolicy::all(4_096),
 
@@ -287,12 +405,40 @@ impl Default for Config {
             expect_point_read_hits: false,
 
             kv_separation_opts: None,
+
+            journal: false,
+
+            durability: Durability::default(),
+
+            fs: Arc::new(StdFilesystem),
+
+            ephemeral: false,
+
+            temporary: false,
+            temporary_folder: None,
+
+            open_mode: OpenMode::default(),
+
+            clock: Arc::new(SystemClock),
+
+            encryptor: None,
+
+            key_extractor: None,
+            merge_operator: None,
+
+            rate_limiter: None,
+
+            write_stall: None,
+            write_stall_listener: None,
         }
     }
 }
 
 impl Config {
     /// Initializes a new config
+    ///
+    /// The sequence number generator is advanced past the highest persisted
+    /// sequence number when the tree is recovered, see [`crate::AbstractTree::insert_auto`].
     pub fn new<P: AsRef<Path>>(path: P, seqno: SequenceNumberCounter) -> Self {
         Self {
             path: absolute_path(path.as_ref()),
@@ -301,6 +447,39 @@ impl Config {
         }
     }
 
+    /// Initializes a new config for a tree that is kept in memory only.
+    ///
+    /// The tree never touches the disk: flushing a memtable merges it into
+    /// the other sealed memtables instead of writing a table, and the journal is disabled.
+    /// Values of key-value separated trees are kept inline.
+    ///
+    /// All data is lost when the tree is dropped, so this is meant for tests and
+    /// caches that want LSM semantics (MVCC snapshots, tombstones, ...) without persistence.
+    #[must_use]
+    pub fn ephemeral(seqno: SequenceNumberCounter) -> Self {
+        Self {
+            seqno,
+            ephemeral: true,
+            ..Default::default()
+        }
+    }
+
+    /// If `true`, the tree is stored in a new folder inside the system's temporary folder
+    /// (see [`std::env::temp_dir`]), instead of the configured path.
+    ///
+    /// The folder is deleted when the tree is dropped.
+    ///
+    /// Unlike [`Config::ephemeral`] trees, temporary trees behave exactly like persistent trees
+    /// (flushes write tables, values are separated, ...), so this is meant for tests and scratch
+    /// computations that need more data than fits into memory.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn temporary(mut self, enabled: bool) -> Self {
+        self.temporary = enabled;
+        self
+    }
+
     /// Sets the global cache.
     ///
     /// You can create a global [`Cache`] and share it between multiple
@@ -313,6 +492,39 @@ impl Config {
         self
     }
 
+    /// Sets a dedicated cache for blobs.
+    ///
+    /// By default, values that are read from the blob files of a key-value separated tree
+    /// are cached in the block cache (see [`Config::use_cache`]).
+    /// A dedicated blob cache keeps large, hot values from evicting index and data blocks,
+    /// and the other way around.
+    ///
+    /// Like the block cache, the blob cache can be shared between multiple trees.
+    #[must_use]
+    pub fn use_blob_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.blob_cache = Some(cache);
+        self
+    }
+
+    /// Returns the cache that blobs are cached in.
+    pub(crate) fn blob_cache(&self) -> &Arc<Cache> {
+        self.blob_cache.as_ref().unwrap_or(&self.cache)
+    }
+
+    /// Uses the shared resources of an environment.
+    ///
+    /// This sets the block cache, file descriptor table, storage backend and time source,
+    /// see [`Env`].
+    #[must_use]
+    pub fn with_env(mut self, env: &Env) -> Self {
+        self.cache = env.cache.clone();
+        self.descriptor_table = env.descriptor_table.clone();
+        self.fs = env.fs.clone();
+        self.clock = env.clock.clone();
+        self.rate_limiter.clone_from(&env.rate_limiter);
+        self
+    }
+
     #[must_use]
     #[doc(hidden)]
     pub fn use_descriptor_table(mut self, descriptor_table: Arc<DescriptorTable>) -> Self {
@@ -384,6 +596,28 @@ impl Config {
     // }
 
     /// Sets the filter construction policy.
+    ///
+    /// Filters are chosen per tree at runtime, so trees with and without filters
+    /// can be used in the same program.
+    ///
+    /// Each level can use a different false positive rate (or bits per key) using
+    /// [`FilterPolicy::new`], trading filter memory for accuracy.
+    ///
+    /// Defaults to bloom filters with 10 bits per key in every level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{config::FilterPolicy, AbstractTree, Config, SequenceNumberCounter};
+    ///
+    /// // NOTE: Only useful if the tree is never point read
+    /// let tree = Config::new(folder, SequenceNumberCounter::default())
+    ///     .filter_policy(FilterPolicy::disabled())
+    ///     .open()?;
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
     #[must_use]
     pub fn filter_policy(mut self, policy: FilterPolicy) -> Self {
         self.filter_policy = policy;
@@ -404,23 +638,238 @@ impl Config {
         self
     }
 
-    // TODO: level count is fixed to 7 right now
-    // /// Sets the number of levels of the LSM tree (depth of tree).
-    // ///
-    // /// Defaults to 7, like `LevelDB` and `RocksDB`.
-    // ///
-    // /// Cannot be changed once set.
-    // ///
-    // /// # Panics
-    // ///
-    // /// Panics if `n` is 0.
-    // #[must_use]
-    // pub fn level_count(mut self, n: u8) -> Self {
-    //     assert!(n > 0);
+    /// Sets the number of levels of the LSM tree (depth of tree).
+    ///
+    /// Defaults to 7, like `LevelDB` and `RocksDB`.
+    ///
+    /// Cannot be changed once set: when an existing tree is opened,
+    /// its persisted level count is used.
+    ///
+    /// Must be at least 2.
+    #[must_use]
+    pub fn level_count(mut self, n: u8) -> Self {
+        self.level_count = n;
+        self
+    }
 
-    //     self.level_count = n;
-    //     self
-    // }
+    /// Sets the number of subfolders that table and blob files are spread across.
+    ///
+    /// Some filesystems slow down when a single folder contains thousands of files,
+    /// so files can be sharded into subfolders by the hash of their ID.
+    ///
+    /// Defaults to 1 (no sharding).
+    ///
+    /// Cannot be changed once set: when an existing tree is opened,
+    /// its persisted shard count is used.
+    #[must_use]
+    pub fn directory_shards(mut self, n: u16) -> Self {
+        self.directory_shards = n;
+        self
+    }
+
+    /// Sets the size of the active memtable (write buffer) in bytes
+    /// after which it should be flushed.
+    ///
+    /// Unless [`Config::background_threads`] is set, the tree itself never flushes
+    /// on its own - this is the threshold the flush workers compare [`AbstractTree::active_memtable_size`](crate::AbstractTree::active_memtable_size) against.
+    ///
+    /// Defaults to 64 MiB.
+    #[must_use]
+    pub fn max_memtable_size(mut self, bytes: u64) -> Self {
+        self.max_memtable_size = bytes;
+        self
+    }
+
+    /// Sets the compaction strategy that is used by
+    /// [`AbstractTree::run_compaction`](crate::AbstractTree::run_compaction).
+    ///
+    /// The size ratio between levels is configured on the strategy,
+    /// see [`Leveled::with_level_ratio_policy`].
+    ///
+    /// Defaults to [`Leveled`] compaction.
+    #[must_use]
+    pub fn compaction_strategy(
+        mut self,
+        strategy: Arc<dyn CompactionStrategy + Send + Sync>,
+    ) -> Self {
+        self.compaction_strategy = strategy;
+        self
+    }
+
+    /// Sets the block cache capacity in bytes.
+    ///
+    /// Data blocks, index blocks, filter blocks and blobs share the same cache.
+    ///
+    /// Replaces the cache with a new one, so this should not be used together
+    /// with [`Config::use_cache`].
+    ///
+    /// Defaults to 16 MiB.
+    #[must_use]
+    pub fn cache_size(self, bytes: u64) -> Self {
+        self.use_cache(Arc::new(Cache::with_capacity_bytes(bytes)))
+    }
+
+    /// Sets the number of background threads used for flushing memtables.
+    ///
+    /// Defaults to 1.
+    #[must_use]
+    pub fn flush_workers(mut self, n: usize) -> Self {
+        self.flush_workers = n;
+        self
+    }
+
+    /// Sets the number of background threads used for compaction.
+    ///
+    /// Defaults to the number of CPU cores, but at most 4.
+    #[must_use]
+    pub fn compaction_workers(mut self, n: usize) -> Self {
+        self.compaction_workers = n;
+        self
+    }
+
+    /// Sets the number of background threads that are owned by the tree,
+    /// and flush and compact it automatically.
+    ///
+    /// The active memtable is flushed once it reaches [`Config::max_memtable_size`], and
+    /// the compaction strategy runs after every flush, so the caller does not need to
+    /// orchestrate flushes and compactions.
+    /// Old versions of keys are only dropped below [`Config::background_gc_watermark`],
+    /// which needs to be set as well.
+    /// The workers are paused and resumed using
+    /// [`AbstractTree::pause_background_work`](crate::AbstractTree::pause_background_work)
+    /// and [`AbstractTree::resume_background_work`](crate::AbstractTree::resume_background_work),
+    /// and stop once the tree is dropped.
+    ///
+    /// Partitions of a [`Keyspace`](crate::keyspace::Keyspace) are flushed and compacted by the
+    /// keyspace's workers instead (see [`Config::flush_workers`] and [`Config::compaction_workers`]),
+    /// so this setting is ignored for them.
+    ///
+    /// Defaults to 0, so flushes and compactions need to be run by the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+    ///
+    /// let seqno = SequenceNumberCounter::default();
+    ///
+    /// // NOTE: Without snapshots, old versions can be dropped right away
+    /// let tree = Config::new(folder, seqno.clone())
+    ///     .max_memtable_size(/* 8 MiB */ 8 * 1_024 * 1_024)
+    ///     .background_threads(2)
+    ///     .background_gc_watermark(seqno)
+    ///     .open()?;
+    ///
+    /// tree.insert_auto("a", "hello");
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn background_threads(mut self, n: usize) -> Self {
+        self.background_threads = n;
+        self
+    }
+
+    /// Sets the MVCC watermark that the background threads flush and compact with
+    /// (see [`Config::background_threads`]).
+    ///
+    /// Older versions of a key that are shadowed by a version below the watermark are dropped,
+    /// so the watermark must not be above the sequence number of any snapshot that
+    /// is still read from (e.g. using [`AbstractTree::get`](crate::AbstractTree::get)
+    /// or [`BlobTree::snapshot`](crate::BlobTree::snapshot)).
+    /// If snapshots are never used, this can be the sequence number generator of the tree.
+    ///
+    /// Needs to be set if [`Config::background_threads`] is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+    ///
+    /// let seqno = SequenceNumberCounter::default();
+    ///
+    /// // NOTE: Raised by the application once the oldest snapshot is released
+    /// let watermark = SequenceNumberCounter::default();
+    ///
+    /// let tree = Config::new(folder, seqno.clone())
+    ///     .background_threads(2)
+    ///     .background_gc_watermark(watermark.clone())
+    ///     .open()?;
+    ///
+    /// tree.insert("a", "hello", seqno.next());
+    /// watermark.fetch_max(seqno.get());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn background_gc_watermark(mut self, watermark: SequenceNumberCounter) -> Self {
+        self.background_gc_watermark = Some(watermark);
+        self
+    }
+
+    /// Sets the maximum number of threads a single compaction is split into (subcompactions).
+    ///
+    /// Large compactions (especially into the last level) are split into disjoint key ranges,
+    /// which are merged in parallel into their own tables.
+    /// A compaction is not split into more subcompactions than tables it writes,
+    /// and compactions that relocate blob files are never split.
+    ///
+    /// Values below 1 are treated as 1.
+    ///
+    /// Defaults to 1, so compactions are not split.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::Config;
+    ///
+    /// let tree = Config::new(folder, Default::default())
+    ///     .max_subcompactions(4)
+    ///     .open()?;
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn max_subcompactions(mut self, n: usize) -> Self {
+        self.max_subcompactions = n.max(1);
+        self
+    }
+
+    /// Sets the age after which tables are forced through compaction (periodic compaction).
+    ///
+    /// Tables in cold key ranges may never be picked by compaction,
+    /// so expired values (see [`AbstractTree::insert_with_ttl`](crate::AbstractTree::insert_with_ttl))
+    /// and merge operands would stay on disk forever.
+    /// When there is no other compaction to do, the oldest table whose age exceeds the interval
+    /// is rewritten in its level.
+    /// The age of a table is measured with the configured [`Clock`].
+    ///
+    /// Only [`Leveled`] compaction honors this setting.
+    ///
+    /// Defaults to `None` (disabled).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::Config;
+    /// use std::time::Duration;
+    ///
+    /// // Rewrite tables that are older than 30 days
+    /// let tree = Config::new(folder, Default::default())
+    ///     .periodic_compaction_interval(Some(Duration::from_secs(30 * 24 * 60 * 60)))
+    ///     .open()?;
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn periodic_compaction_interval(mut self, interval: Option<Duration>) -> Self {
+        self.periodic_compaction_interval = interval;
+        self
+    }
 
     /// Sets the data block size policy.
     #[must_use]
@@ -446,16 +895,438 @@ impl Config {
         self
     }
 
-    /// Opens a tree using the config.
+    /// Toggles the journal (write-ahead log).
+    ///
+    /// If enabled, all writes are appended to a journal before they are inserted
+    /// into the active memtable. When the tree is reopened, all journaled writes
+    /// that have not been flushed to tables yet are replayed into the active memtable,
+    /// so they are not lost when the process crashes (see [`Config::durability`]).
+    ///
+    /// If a write to or fsync of the journal fails, the write is not applied, and the tree refuses
+    /// all further writes, because it is unknown which writes made it into the journal.
+    /// Writes that return a `Result` (e.g. [`crate::Batch::commit`] or
+    /// [`AbstractTree::try_insert`](crate::AbstractTree::try_insert)) return the error, later ones
+    /// [`crate::Error::Poisoned`]; other writes (e.g. [`AbstractTree::insert`](crate::AbstractTree::insert))
+    /// panic. The tree needs to be reopened to accept writes again.
+    ///
+    /// The journal is also required for change data capture (see [`Tree::cdc_reader`]).
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SeqNo};
+    ///
+    /// {
+    ///     let tree = Config::new(&folder, Default::default())
+    ///         .use_journal(true)
+    ///         .open()?;
+    ///
+    ///     tree.insert("a", "abc", 0);
+    /// }
+    ///
+    /// // NOTE: The write was never flushed, but is recovered from the journal
+    /// let tree = Config::new(&folder, Default::default())
+    ///     .use_journal(true)
+    ///     .open()?;
+    ///
+    /// assert!(tree.contains_key("a", SeqNo::MAX)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn use_journal(mut self, enabled: bool) -> Self {
+        self.journal = enabled;
+        self
+    }
+
+    /// Sets the durability of journaled writes.
+    ///
+    /// Trades write latency for the amount of writes that may be lost on a crash,
+    /// see [`Durability`]. Has no effect if the journal is disabled.
+    ///
+    /// Defaults to [`Durability::Buffer`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Durability};
+    ///
+    /// let tree = Config::new(&folder, Default::default())
+    ///     .use_journal(true)
+    ///     .durability(Durability::SyncInterval(100))
+    ///     .open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    ///
+    /// // NOTE: Makes the write durable right away
+    /// tree.sync()?;
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Sets the storage backend that is used for all file access.
+    ///
+    /// Defaults to [`StdFilesystem`].
+    #[must_use]
+    pub fn with_filesystem(mut self, fs: Arc<dyn Filesystem>) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Sets the time source that is used for timestamps and time-based compaction.
+    ///
+    /// Defaults to [`SystemClock`].
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the encryptor that is used to encrypt all table blocks, blobs
+    /// and journal entries at rest.
+    ///
+    /// The encryptor needs to be set every time the tree is opened,
+    /// otherwise its data cannot be read.
+    ///
+    /// See [`Encryptor`] for how to rotate keys.
+    ///
+    /// Defaults to `None`.
+    #[must_use]
+    pub fn with_encryption(mut self, encryptor: Option<Arc<dyn Encryptor>>) -> Self {
+        self.encryptor = encryptor;
+        self
+    }
+
+    /// Sets the key extractor that is used to build in-memory filters over derived keys
+    /// (e.g. key prefixes), see [`KeyExtractor`].
+    ///
+    /// Only tables that are written while the extractor is set contain such a filter,
+    /// other tables always need to be assumed to contain any derived key.
+    ///
+    /// Defaults to `None`.
+    #[must_use]
+    pub fn with_key_extractor(mut self, extractor: Option<Arc<dyn KeyExtractor>>) -> Self {
+        self.key_extractor = extractor;
+        self
+    }
+
+    /// Sets the merge operator that combines merge operands, see [`MergeOperator`].
+    ///
+    /// Without a merge operator, the newest merge operand of a key is read as its value.
+    ///
+    /// Merge operators are not supported for key-value separated trees.
+    ///
+    /// Defaults to `None`.
+    #[must_use]
+    pub fn with_merge_operator(mut self, merge_operator: Option<Arc<dyn MergeOperator>>) -> Self {
+        self.merge_operator = merge_operator;
+        self
+    }
+
+    /// Sets the rate limiter that limits the write throughput of flushes and compactions,
+    /// see [`RateLimiter`].
+    ///
+    /// The rate limiter can be shared between trees to limit their combined throughput.
+    ///
+    /// Defaults to `None`, so background work is not limited.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Toggles write stalls, see [`WriteStallOptions`].
+    ///
+    /// Defaults to `None`, so writes are never delayed.
+    #[must_use]
+    pub fn with_write_stall(mut self, opts: Option<WriteStallOptions>) -> Self {
+        self.write_stall = opts;
+        self
+    }
+
+    /// Sets the listener that is notified when writes start or stop being delayed,
+    /// see [`WriteStallListener`].
+    ///
+    /// Defaults to `None`.
+    #[must_use]
+    pub fn with_write_stall_listener(
+        mut self,
+        listener: Option<Arc<dyn WriteStallListener>>,
+    ) -> Self {
+        self.write_stall_listener = listener;
+        self
+    }
+
+    /// Tunes the config for point reads.
+    ///
+    /// - small (4 KiB) data blocks with a hash index
+    /// - more precise filters (15 bits per key)
+    /// - filter and index blocks are pinned in all levels
+    /// - [`Leveled`] compaction
+    #[must_use]
+    pub fn optimized_for_point_lookups(self) -> Self {
+        self.data_block_size_policy(BlockSizePolicy::all(4 * 1_024))
+            .data_block_hash_ratio_policy(HashRatioPolicy::all(0.75))
+            .filter_policy(FilterPolicy::all(FilterPolicyEntry::Bloom(
+                BloomConstructionPolicy::BitsPerKey(15.0),
+            )))
+            .filter_block_pinning_policy(PinningPolicy::all(true))
+            .index_block_pinning_policy(PinningPolicy::all(true))
+            .compaction_strategy(Arc::new(Leveled::default()))
+    }
+
+    /// Tunes the config for range scans.
+    ///
+    /// - large (32 KiB) data blocks, without a hash index
+    /// - no filter in the last level, which holds most of the data
+    /// - only index blocks of the first levels are pinned, so the cache is free for data blocks
+    /// - [`Leveled`] compaction
+    #[must_use]
+    pub fn optimized_for_range_scans(self) -> Self {
+        self.data_block_size_policy(BlockSizePolicy::all(32 * 1_024))
+            .data_block_hash_ratio_policy(HashRatioPolicy::all(0.0))
+            .expect_point_read_hits(true)
+            .filter_block_pinning_policy(PinningPolicy::new([true, false]))
+            .index_block_pinning_policy(PinningPolicy::new([true, true, false]))
+            .compaction_strategy(Arc::new(Leveled::default()))
+    }
+
+    /// Tunes the config for ingesting lots of data.
+    ///
+    /// - large (256 MiB) memtables
+    /// - large (64 KiB) data blocks
+    /// - [`Leveled`] compaction with a higher L0 threshold and larger tables,
+    ///   trading read amplification for less write amplification
+    /// - nothing is pinned, so the cache is not polluted by freshly written tables
+    #[must_use]
+    pub fn optimized_for_bulk_load(self) -> Self {
+        self.max_memtable_size(/* 256 MiB */ 256 * 1_024 * 1_024)
+            .data_block_size_policy(BlockSizePolicy::all(64 * 1_024))
+            .data_block_hash_ratio_policy(HashRatioPolicy::all(0.0))
+            .filter_block_pinning_policy(PinningPolicy::all(false))
+            .index_block_pinning_policy(PinningPolicy::all(false))
+            .compaction_strategy(Arc::new(Leveled {
+                l0_threshold: 8,
+                target_size: /* 128 MiB */ 128 * 1_024 * 1_024,
+                ..Default::default()
+            }))
+    }
+
+    /// Checks that the config values are consistent.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`crate::Error::InvalidConfig`] if some value is out of range.
+    pub fn validate(&self) -> crate::Result<()> {
+        use crate::Error::InvalidConfig;
+
+        if self.level_count < 2 {
+            return Err(InvalidConfig("level count must be at least 2"));
+        }
+
+        if self.max_memtable_size == 0 {
+            return Err(InvalidConfig("max memtable size must not be 0"));
+        }
+
+        if self.flush_workers == 0 {
+            return Err(InvalidConfig("flush worker count must not be 0"));
+        }
+
+        if self.compaction_workers == 0 {
+            return Err(InvalidConfig("compaction worker count must not be 0"));
+        }
+
+        if self.durability == Durability::SyncInterval(0) {
+            return Err(InvalidConfig("journal sync interval must not be 0"));
+        }
+
+        if let Some(opts) = &self.write_stall {
+            if opts.l0_slowdown > opts.l0_stop {
+                return Err(InvalidConfig(
+                    "L0 slowdown threshold must not exceed stop threshold",
+                ));
+            }
+
+            if opts.sealed_memtable_slowdown > opts.sealed_memtable_stop {
+                return Err(InvalidConfig(
+                    "sealed memtable slowdown threshold must not exceed stop threshold",
+                ));
+            }
+        }
+
+        if let Some(opts) = &self.kv_separation_opts {
+            if self.merge_operator.is_some() {
+                return Err(InvalidConfig(
+                    "merge operators are not supported with key-value separation",
+                ));
+            }
+
+            if opts.file_target_size == 0 {
+                return Err(InvalidConfig("blob file target size must not be 0"));
+            }
+
+            if !(0.0..=1.0).contains(&opts.staleness_threshold) {
+                return Err(InvalidConfig("staleness threshold must be in [0, 1]"));
+            }
+
+            if !(0.0..=1.0).contains(&opts.age_cutoff) {
+                return Err(InvalidConfig("age cutoff must be in [0, 1]"));
+            }
+        }
+
+        if self.is_secondary() && (self.ephemeral || self.temporary) {
+            return Err(InvalidConfig(
+                "secondary instances can not be ephemeral or temporary",
+            ));
+        }
+
+        if self.background_threads > 0 && self.background_gc_watermark.is_none() {
+            return Err(InvalidConfig(
+                "background threads need a GC watermark, see Config::background_gc_watermark",
+            ));
+        }
+
+        // NOTE: WASM runtimes are single-threaded, so everything that needs a thread is rejected
+        #[cfg(target_family = "wasm")]
+        {
+            if self.background_threads > 0 {
+                return Err(InvalidConfig(
+                    "background threads are not supported on wasm",
+                ));
+            }
+
+            if self.max_subcompactions > 1 {
+                return Err(InvalidConfig("subcompactions are not supported on wasm"));
+            }
+
+            if matches!(self.durability, Durability::SyncInterval(_)) {
+                return Err(InvalidConfig(
+                    "journal sync interval is not supported on wasm",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Opens a tree using the config, creating it if it does not exist.
+    ///
+    /// The tree folder is locked until the tree is dropped.
+    ///
+    /// A new tree is only created if the folder is empty (or only contains
+    /// leftovers of a previously interrupted tree creation), so an unrelated folder
+    /// is never turned into a tree.
     ///
     /// # Errors
     ///
-    /// Will return `Err` if an IO error occurs.
+    /// Will return `Err` if an IO error occurs, [`crate::Error::AlreadyLocked`]
+    /// if the tree is already opened, [`crate::Error::ForeignFolder`] if the folder
+    /// contains unrelated files, or [`crate::Error::InvalidConfig`]
+    /// if the config is invalid (see [`Config::validate`]).
     pub fn open(self) -> crate::Result<AnyTree> {
-        Ok(if self.kv_separation_opts.is_some() {
+        self.open_or_create()
+    }
+
+    /// Opens a tree using the config, creating it if it does not exist.
+    ///
+    /// Same as [`Config::open`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Config::open`].
+    pub fn open_or_create(self) -> crate::Result<AnyTree> {
+        self.open_with_mode(OpenMode::OpenOrCreate)
+    }
+
+    /// Creates a new tree using the config.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`crate::Error::TreeAlreadyExists`] if a tree already exists
+    /// in the folder, see [`Config::open`] for other errors.
+    pub fn create_new(self) -> crate::Result<AnyTree> {
+        self.open_with_mode(OpenMode::CreateNew)
+    }
+
+    /// Opens an existing tree using the config.
+    ///
+    /// The folder is not created if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`crate::Error::TreeNotFound`] if there is no tree
+    /// in the folder, see [`Config::open`] for other errors.
+    pub fn open_existing(self) -> crate::Result<AnyTree> {
+        self.open_with_mode(OpenMode::OpenExisting)
+    }
+
+    /// Opens an existing tree as a secondary instance.
+    ///
+    /// A secondary instance does not lock the folder, so it can be opened next to the
+    /// (primary) tree that owns the folder, e.g. from another process.
+    /// It never writes to disk: flushes, compactions and ingestions fail with
+    /// [`crate::Error::ReadOnly`], and writes are only kept in memory until the next catch-up.
+    ///
+    /// The secondary instance sees the state of the primary tree at the time of opening.
+    /// Use [`crate::AbstractTree::try_catch_up`] to observe flushes and compactions of the primary tree
+    /// that happened afterwards. If the journal is enabled (see [`Config::use_journal`]),
+    /// writes of the primary tree that are not flushed yet are read from its journal.
+    ///
+    /// No background threads are spawned for a secondary instance. It keeps a file descriptor
+    /// open for each table of its current version, so tables stay readable after the primary
+    /// tree deletes them.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`crate::Error::TreeNotFound`] if there is no tree
+    /// in the folder, see [`Config::open`] for other errors.
+    pub fn open_secondary(self) -> crate::Result<AnyTree> {
+        self.open_with_mode(OpenMode::Secondary)
+    }
+
+    /// Returns `true` if the tree is opened as a secondary instance.
+    pub(crate) fn is_secondary(&self) -> bool {
+        self.open_mode == OpenMode::Secondary
+    }
+
+    /// Fails if the tree is opened as a secondary instance, which never writes to disk.
+    pub(crate) fn ensure_writable(&self) -> crate::Result<()> {
+        if self.is_secondary() {
+            return Err(crate::Error::ReadOnly);
+        }
+        Ok(())
+    }
+
+    fn open_with_mode(mut self, mode: OpenMode) -> crate::Result<AnyTree> {
+        self.open_mode = mode;
+        self.validate()?;
+
+        // NOTE: Background threads would flush and compact, which a secondary instance never does
+        let background_threads = if mode == OpenMode::Secondary {
+            0
+        } else {
+            self.background_threads
+        };
+
+        let tree = if self.kv_separation_opts.is_some() {
             AnyTree::Blob(BlobTree::open(self)?)
         } else {
             AnyTree::Standard(Tree::open(self)?)
-        })
+        };
+
+        if background_threads > 0 {
+            crate::tree::background::spawn(&tree, background_threads)?;
+        }
+
+        Ok(tree)
     }
 }
@@ -0,0 +1,193 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{iter_guard::IterGuardImpl, AbstractTree, Guard, KvPair, SeqNo, UserKey};
+use std::ops::Bound;
+
+type Iter = Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static>;
+
+enum Position {
+    /// Before the first item
+    Start,
+
+    /// At an item
+    At(UserKey),
+
+    /// After the last item
+    End,
+}
+
+/// A cursor over the items of a tree that can be moved in both directions
+/// and repositioned using [`Cursor::seek`] and [`Cursor::seek_for_prev`]
+///
+/// Moving the cursor in the same direction repeatedly continues the underlying
+/// range iterator, so a new range is only created when the cursor is repositioned,
+/// or changes direction.
+///
+/// Like all reads, the cursor reads the tree as of its sequence number,
+/// so writes with a higher sequence number are not seen.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{AbstractTree, Config, Cursor};
+///
+/// let tree = Config::new(folder, Default::default()).open()?;
+/// tree.insert("a", "1", 0);
+/// tree.insert("c", "2", 1);
+/// tree.insert("e", "3", 2);
+///
+/// let mut cursor = Cursor::new(tree, 3);
+///
+/// let (key, _) = cursor.seek("b")?.expect("should exist");
+/// assert_eq!(b"c", &*key);
+///
+/// let (key, _) = cursor.next()?.expect("should exist");
+/// assert_eq!(b"e", &*key);
+///
+/// let (key, _) = cursor.prev()?.expect("should exist");
+/// assert_eq!(b"c", &*key);
+///
+/// let (key, _) = cursor.seek_for_prev("b")?.expect("should exist");
+/// assert_eq!(b"a", &*key);
+/// assert!(cursor.prev()?.is_none());
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub struct Cursor<T: AbstractTree> {
+    tree: T,
+    seqno: SeqNo,
+
+    position: Position,
+
+    /// Iterator over the items after the current position, if any
+    forward: Option<Iter>,
+
+    /// Iterator over the items before the current position, if any
+    backward: Option<Iter>,
+}
+
+impl<T: AbstractTree> Cursor<T> {
+    /// Creates a new cursor that is positioned before the first item.
+    #[must_use]
+    pub fn new(tree: T, seqno: SeqNo) -> Self {
+        Self {
+            tree,
+            seqno,
+            position: Position::Start,
+            forward: None,
+            backward: None,
+        }
+    }
+
+    /// Returns the key the cursor is positioned at.
+    #[must_use]
+    pub fn key(&self) -> Option<&UserKey> {
+        match &self.position {
+            Position::At(key) => Some(key),
+            Position::Start | Position::End => None,
+        }
+    }
+
+    /// Positions the cursor at the first item whose key is greater or equal to `key`, and returns it.
+    ///
+    /// If there is no such item, the cursor is positioned after the last item.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn seek<K: AsRef<[u8]>>(&mut self, key: K) -> crate::Result<Option<KvPair>> {
+        let key = UserKey::from(key.as_ref());
+
+        self.forward = Some(self.range((Bound::Included(key), Bound::Unbounded)));
+        self.backward = None;
+
+        self.next()
+    }
+
+    /// Positions the cursor at the last item whose key is less or equal to `key`, and returns it.
+    ///
+    /// If there is no such item, the cursor is positioned before the first item.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn seek_for_prev<K: AsRef<[u8]>>(&mut self, key: K) -> crate::Result<Option<KvPair>> {
+        let key = UserKey::from(key.as_ref());
+
+        self.backward = Some(self.range((Bound::Unbounded, Bound::Included(key))));
+        self.forward = None;
+
+        self.prev()
+    }
+
+    /// Moves the cursor to the next item, and returns it.
+    ///
+    /// If the cursor is positioned before the first item, the first item is returned.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    #[expect(
+        clippy::should_implement_trait,
+        reason = "the cursor can move in both directions, so it is not an iterator"
+    )]
+    pub fn next(&mut self) -> crate::Result<Option<KvPair>> {
+        let mut iter = match self.forward.take() {
+            Some(iter) => iter,
+            None => match &self.position {
+                Position::Start => self.range::<UserKey>((Bound::Unbounded, Bound::Unbounded)),
+                Position::At(key) => self.range((Bound::Excluded(key.clone()), Bound::Unbounded)),
+                Position::End => return Ok(None),
+            },
+        };
+
+        self.backward = None;
+
+        let Some(kv) = iter.next().map(Guard::into_inner).transpose()? else {
+            self.position = Position::End;
+            return Ok(None);
+        };
+
+        self.position = Position::At(kv.0.clone());
+        self.forward = Some(iter);
+
+        Ok(Some(kv))
+    }
+
+    /// Moves the cursor to the previous item, and returns it.
+    ///
+    /// If the cursor is positioned after the last item, the last item is returned.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn prev(&mut self) -> crate::Result<Option<KvPair>> {
+        let mut iter = match self.backward.take() {
+            Some(iter) => iter,
+            None => match &self.position {
+                Position::Start => return Ok(None),
+                Position::At(key) => self.range((Bound::Unbounded, Bound::Excluded(key.clone()))),
+                Position::End => self.range::<UserKey>((Bound::Unbounded, Bound::Unbounded)),
+            },
+        };
+
+        self.forward = None;
+
+        let Some(kv) = iter.next_back().map(Guard::into_inner).transpose()? else {
+            self.position = Position::Start;
+            return Ok(None);
+        };
+
+        self.position = Position::At(kv.0.clone());
+        self.backward = Some(iter);
+
+        Ok(Some(kv))
+    }
+
+    fn range<K: AsRef<[u8]>>(&self, bounds: (Bound<K>, Bound<K>)) -> Iter {
+        self.tree.range(bounds, self.seqno, None)
+    }
+}
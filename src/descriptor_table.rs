@@ -2,14 +2,17 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::GlobalTableId;
+use crate::{FileHandle, GlobalTableId};
 use quick_cache::{sync::Cache as QuickCache, UnitWeighter};
-use std::{fs::File, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 
 const TAG_BLOCK: u8 = 0;
 const TAG_BLOB: u8 = 1;
 
-type Item = Arc<File>;
+type Item = Arc<dyn FileHandle>;
 
 #[derive(Eq, std::hash::Hash, PartialEq)]
 struct CacheKey(u8, u64, u64);
@@ -17,6 +20,9 @@ struct CacheKey(u8, u64, u64);
 /// Caches file descriptors to tables and blob files
 pub struct DescriptorTable {
     inner: QuickCache<CacheKey, Item, UnitWeighter, rustc_hash::FxBuildHasher>,
+
+    /// File descriptors that are never evicted, see [`DescriptorTable::pin_table`]
+    pinned: RwLock<HashMap<CacheKey, Item, rustc_hash::FxBuildHasher>>,
 }
 
 impl DescriptorTable {
@@ -32,7 +38,10 @@ impl DescriptorTable {
             DefaultLifecycle::default(),
         );
 
-        Self { inner: quick_cache }
+        Self {
+            inner: quick_cache,
+            pinned: RwLock::default(),
+        }
     }
 
     #[doc(hidden)]
@@ -41,9 +50,32 @@ impl DescriptorTable {
     }
 
     #[must_use]
-    pub fn access_for_table(&self, id: &GlobalTableId) -> Option<Arc<File>> {
+    pub fn access_for_table(&self, id: &GlobalTableId) -> Option<Arc<dyn FileHandle>> {
         let key = CacheKey(TAG_BLOCK, id.tree_id(), id.table_id());
-        self.inner.get(&key)
+
+        self.inner.get(&key).or_else(|| {
+            self.pinned
+                .read()
+                .expect("lock is poisoned")
+                .get(&key)
+                .cloned()
+        })
+    }
+
+    /// Keeps the file descriptor of a table open until [`DescriptorTable::unpin_table`] is called.
+    ///
+    /// An open file descriptor keeps the table readable, even after its file is deleted.
+    pub(crate) fn pin_table(&self, id: GlobalTableId, item: Item) {
+        let key = CacheKey(TAG_BLOCK, id.tree_id(), id.table_id());
+        self.pinned
+            .write()
+            .expect("lock is poisoned")
+            .insert(key, item);
+    }
+
+    pub(crate) fn unpin_table(&self, id: &GlobalTableId) {
+        let key = CacheKey(TAG_BLOCK, id.tree_id(), id.table_id());
+        self.pinned.write().expect("lock is poisoned").remove(&key);
     }
 
     pub fn insert_for_table(&self, id: GlobalTableId, item: Item) {
@@ -52,7 +84,7 @@ impl DescriptorTable {
     }
 
     #[must_use]
-    pub fn access_for_blob_file(&self, id: &GlobalTableId) -> Option<Arc<File>> {
+    pub fn access_for_blob_file(&self, id: &GlobalTableId) -> Option<Arc<dyn FileHandle>> {
         let key = CacheKey(TAG_BLOB, id.tree_id(), id.table_id());
         self.inner.get(&key)
     }
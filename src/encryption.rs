@@ -0,0 +1,68 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Encryption-at-rest of a tree
+///
+/// If set, every table block (data, index, filter and meta blocks), every blob
+/// and every journal entry is passed through the encryptor before being written,
+/// after compression. Checksums are computed over the encrypted bytes, so
+/// corruption can still be detected without decrypting.
+///
+/// The file trailers (table of contents), the manifest and the version files
+/// are not encrypted; they only contain file IDs, offsets and sizes.
+///
+/// # Nonces
+///
+/// Each call of [`Encryptor::encrypt`] needs to use a unique nonce (e.g. a random or
+/// counter-based one), and embed it in the returned ciphertext, together with
+/// any authentication tag. File and block IDs are not unique across trees or restores,
+/// so they must not be used as nonces on their own.
+///
+/// # Key rotation
+///
+/// To support key rotation, the ciphertext should also embed an identifier of the key
+/// that was used, so [`Encryptor::decrypt`] can still decrypt data written with older keys,
+/// while [`Encryptor::encrypt`] always uses the newest key.
+///
+/// Compactions rewrite tables (and blob files, when they are relocated), so old data is
+/// gradually re-encrypted with the new key.
+/// To force rewriting all tables, run a major compaction.
+///
+/// Blob files of a [`crate::BlobTree`] are only re-encrypted when blob GC relocates them
+/// (see [`crate::KvSeparationOptions`] and [`crate::BlobTree::gc_with_staleness_threshold`]),
+/// which a major compaction only does for stale blob files, and blob files without
+/// stale blobs are never relocated. So for a blob tree, an old key may only be retired once blob GC has
+/// relocated (or dropped) every blob file that was written using it.
+pub trait Encryptor: std::fmt::Debug + Send + Sync + 'static {
+    /// Encrypts a block of data.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data could not be encrypted.
+    fn encrypt(&self, plaintext: &[u8]) -> std::io::Result<Vec<u8>>;
+
+    /// Decrypts a block of data that was encrypted using [`Encryptor::encrypt`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data could not be decrypted, e.g. because
+    /// its key is unknown or the authentication tag does not match.
+    fn decrypt(&self, ciphertext: &[u8]) -> std::io::Result<Vec<u8>>;
+}
+
+/// Insecure [`Encryptor`] for tests, which XORs all bytes with a key byte
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct XorEncryptor(pub u8);
+
+#[cfg(test)]
+impl Encryptor for XorEncryptor {
+    fn encrypt(&self, plaintext: &[u8]) -> std::io::Result<Vec<u8>> {
+        Ok(plaintext.iter().map(|byte| byte ^ self.0).collect())
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> std::io::Result<Vec<u8>> {
+        self.encrypt(ciphertext)
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{Cache, Clock, DescriptorTable, Filesystem, RateLimiter, StdFilesystem, SystemClock};
+use std::sync::Arc;
+
+/// Resources that are shared by multiple trees
+///
+/// Applications that embed many trees can create a single environment and pass it
+/// to every [`crate::Config`] (see [`crate::Config::with_env`]), so memory and file descriptor
+/// usage is capped globally, instead of each tree allocating its own resources.
+///
+/// An environment holds:
+///
+/// - the block cache, which also caches filter and index blocks, and blobs
+/// - the file descriptor table
+/// - the storage backend
+/// - the time source
+/// - the rate limiter of flushes and compactions, if any
+///
+/// Background workers are owned by a [`crate::keyspace::Keyspace`], which
+/// already shares them between all of its partitions, or by a single tree
+/// (see [`crate::Config::background_threads`]).
+///
+/// Cloning an environment is cheap, and the clone refers to the same resources.
+///
+/// # Examples
+///
+/// ```
+/// # use lsm_tree::{Config, Env, SequenceNumberCounter};
+/// #
+/// // Provide 64 MB of cache capacity and 512 open files for all trees
+/// let env = Env::new(64 * 1_000 * 1_000, 512);
+///
+/// # let folder = tempfile::tempdir()?;
+/// let tree1 = Config::new(folder, SequenceNumberCounter::default())
+///     .with_env(&env)
+///     .open()?;
+/// # let folder = tempfile::tempdir()?;
+/// let tree2 = Config::new(folder, SequenceNumberCounter::default())
+///     .with_env(&env)
+///     .open()?;
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Env {
+    pub(crate) cache: Arc<Cache>,
+    pub(crate) descriptor_table: Arc<DescriptorTable>,
+    pub(crate) fs: Arc<dyn Filesystem>,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Self::new(/* 16 MiB */ 16 * 1_024 * 1_024, 256)
+    }
+}
+
+impl Env {
+    /// Creates a new environment with roughly `cache_capacity` bytes of block cache,
+    /// and up to `max_open_files` cached file descriptors.
+    #[must_use]
+    pub fn new(cache_capacity: u64, max_open_files: usize) -> Self {
+        Self {
+            cache: Arc::new(Cache::with_capacity_bytes(cache_capacity)),
+            descriptor_table: Arc::new(DescriptorTable::new(max_open_files)),
+            fs: Arc::new(StdFilesystem),
+            clock: Arc::new(SystemClock),
+            rate_limiter: None,
+        }
+    }
+
+    /// Sets the block cache.
+    #[must_use]
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Sets the storage backend, see [`crate::Config::with_filesystem`].
+    #[must_use]
+    pub fn with_filesystem(mut self, fs: Arc<dyn Filesystem>) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Sets the time source, see [`crate::Config::with_clock`].
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the rate limiter, see [`crate::Config::with_rate_limiter`].
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Returns the block cache.
+    #[must_use]
+    pub fn cache(&self) -> &Arc<Cache> {
+        &self.cache
+    }
+
+    /// Returns the file descriptor table.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn descriptor_table(&self) -> &Arc<DescriptorTable> {
+        &self.descriptor_table
+    }
+
+    /// Returns the storage backend.
+    #[must_use]
+    pub fn filesystem(&self) -> &Arc<dyn Filesystem> {
+        &self.fs
+    }
+
+    /// Returns the time source.
+    #[must_use]
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Returns the rate limiter, if any.
+    #[must_use]
+    pub fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.rate_limiter.as_ref()
+    }
+}
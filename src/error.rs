@@ -2,7 +2,7 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{Checksum, CompressionType};
+use crate::{Checksum, CompressionType, SeqNo};
 
 /// Represents errors that can occur in the LSM-tree
 #[derive(Debug)]
@@ -40,6 +40,49 @@ pub enum Error {
 
     /// UTF-8 error
     Utf8(std::str::Utf8Error),
+
+    /// Replicated entries were not applied in sequence number order
+    NonMonotonicSeqNo {
+        /// Sequence number of the rejected entry
+        got: SeqNo,
+
+        /// Highest sequence number that was already applied
+        highest: SeqNo,
+    },
+
+    /// The tree folder is already opened, by this or another process
+    AlreadyLocked,
+
+    /// A tree already exists in the folder, see [`crate::Config::create_new`]
+    TreeAlreadyExists,
+
+    /// No tree exists in the folder, see [`crate::Config::open_existing`]
+    TreeNotFound,
+
+    /// The folder contains files that do not belong to a tree,
+    /// so no tree is created in it
+    ForeignFolder,
+
+    /// Some config value is out of range, see [`crate::Config::validate`]
+    InvalidConfig(&'static str),
+
+    /// Persisted data is inconsistent, e.g. a blob indirection points to a blob
+    /// that does not exist, or a required metadata property is missing
+    Corrupted(&'static str),
+
+    /// Table files can not be ingested, because they overlap each other,
+    /// were not written by a [`crate::SstBuilder`], or are imported into a non-empty tree
+    InvalidIngestion(&'static str),
+
+    /// The tree is a secondary instance, which never writes to disk,
+    /// see [`crate::Config::open_secondary`]
+    ReadOnly,
+
+    /// A write to or fsync of the journal failed earlier,
+    /// so all further writes are refused, see [`crate::Config::use_journal`]
+    ///
+    /// Also returned if a thread panicked while holding a lock of the tree.
+    Poisoned,
 }
 
 impl std::fmt::Display for Error {
@@ -84,6 +127,13 @@ impl From<sfa::Error> for Error {
     }
 }
 
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for Error {
+    fn from(value: parquet::errors::ParquetError) -> Self {
+        Self::Io(std::io::Error::other(value))
+    }
+}
+
 impl From<std::io::Error> for Error {
     fn from(value: std::io::Error) -> Self {
         Self::Io(value)
@@ -0,0 +1,339 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{iter_guard::IterGuardImpl, Config, Guard, SstBuilder, UserKey};
+use std::{
+    io::Write,
+    ops::Bound,
+    path::{Path, PathBuf},
+};
+
+/// Output format of a tree export
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ExportFormat {
+    /// RFC 4180 CSV with a `key,value` header
+    ///
+    /// Keys and values are written as UTF-8 text, so
+    /// exporting binary data will fail with [`crate::Error::Utf8`].
+    Csv,
+
+    /// RFC 4180 CSV with a `key,value` header
+    ///
+    /// Keys and values are written as lowercase hex strings.
+    CsvHex,
+
+    /// Parquet file with a `key` and a `value` column of type `BYTE_ARRAY`
+    ///
+    /// Items are written in row groups of about 16 MiB of keys and values,
+    /// so the export does not need to fit into memory.
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+fn write_hex<W: Write>(writer: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+    for byte in bytes {
+        write!(writer, "{byte:02x}")?;
+    }
+    Ok(())
+}
+
+fn write_csv_field<W: Write>(writer: &mut W, bytes: &[u8]) -> crate::Result<()> {
+    let text = std::str::from_utf8(bytes).map_err(crate::Error::Utf8)?;
+
+    if text.contains([',', '"', '\n', '\r']) {
+        writer.write_all(b"\"")?;
+        writer.write_all(text.replace('"', "\"\"").as_bytes())?;
+        writer.write_all(b"\"")?;
+    } else {
+        writer.write_all(bytes)?;
+    }
+
+    Ok(())
+}
+
+/// Writes all items of an iterator into the writer in the given format.
+///
+/// Returns the amount of exported items.
+pub fn export_items<W: Write>(
+    iter: impl Iterator<Item = IterGuardImpl>,
+    format: ExportFormat,
+    mut writer: W,
+) -> crate::Result<u64> {
+    #[cfg(feature = "parquet")]
+    if format == ExportFormat::Parquet {
+        return export_parquet(iter, writer);
+    }
+
+    let mut count = 0;
+
+    writer.write_all(b"key,value\n")?;
+
+    for guard in iter {
+        let (key, value) = guard.into_inner()?;
+
+        match format {
+            ExportFormat::Csv => {
+                write_csv_field(&mut writer, &key)?;
+                writer.write_all(b",")?;
+                write_csv_field(&mut writer, &value)?;
+            }
+            ExportFormat::CsvHex => {
+                write_hex(&mut writer, &key)?;
+                writer.write_all(b",")?;
+                write_hex(&mut writer, &value)?;
+            }
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => unreachable!("parquet is exported above"),
+        }
+
+        writer.write_all(b"\n")?;
+
+        count += 1;
+    }
+
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/// Size of keys and values after which a Parquet row group is written
+#[cfg(feature = "parquet")]
+const PARQUET_ROW_GROUP_SIZE: usize = 16 * 1_024 * 1_024;
+
+/// In-memory buffer the Parquet writer writes into
+///
+/// [`parquet::file::writer::SerializedFileWriter`] requires a [`Send`] writer,
+/// so it writes into this buffer, which is drained into the actual writer after every row group.
+#[cfg(feature = "parquet")]
+#[derive(Clone, Default)]
+struct ParquetBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(feature = "parquet")]
+impl ParquetBuffer {
+    fn drain_into<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let buf = std::mem::take(&mut *self.0.lock().expect("lock is poisoned"));
+        writer.write_all(&buf)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl Write for ParquetBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .expect("lock is poisoned")
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn write_row_group(
+    file_writer: &mut parquet::file::writer::SerializedFileWriter<ParquetBuffer>,
+    keys: &[parquet::data_type::ByteArray],
+    values: &[parquet::data_type::ByteArray],
+) -> crate::Result<()> {
+    use parquet::data_type::ByteArrayType;
+
+    let mut row_group = file_writer.next_row_group()?;
+
+    for column in [keys, values] {
+        let Some(mut column_writer) = row_group.next_column()? else {
+            unreachable!("schema has two columns");
+        };
+        column_writer
+            .typed::<ByteArrayType>()
+            .write_batch(column, None, None)?;
+        column_writer.close()?;
+    }
+
+    row_group.close()?;
+
+    Ok(())
+}
+
+/// Writes all items of an iterator into the writer as a Parquet file.
+///
+/// Returns the amount of exported items.
+#[cfg(feature = "parquet")]
+fn export_parquet<W: Write>(
+    iter: impl Iterator<Item = IterGuardImpl>,
+    mut writer: W,
+) -> crate::Result<u64> {
+    use parquet::{
+        data_type::ByteArray,
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+    use std::sync::Arc;
+
+    let schema = parse_message_type(
+        "message export { REQUIRED BYTE_ARRAY key; REQUIRED BYTE_ARRAY value; }",
+    )?;
+
+    let buffer = ParquetBuffer::default();
+
+    let mut file_writer = SerializedFileWriter::new(
+        buffer.clone(),
+        Arc::new(schema),
+        Arc::new(WriterProperties::builder().build()),
+    )?;
+
+    let mut count = 0;
+    let mut keys = vec![];
+    let mut values = vec![];
+    let mut size = 0;
+
+    for guard in iter {
+        let (key, value) = guard.into_inner()?;
+
+        size += key.len() + value.len();
+        keys.push(ByteArray::from(key.to_vec()));
+        values.push(ByteArray::from(value.to_vec()));
+
+        if size >= PARQUET_ROW_GROUP_SIZE {
+            write_row_group(&mut file_writer, &keys, &values)?;
+            buffer.drain_into(&mut writer)?;
+
+            keys.clear();
+            values.clear();
+            size = 0;
+        }
+
+        count += 1;
+    }
+
+    if !keys.is_empty() {
+        write_row_group(&mut file_writer, &keys, &values)?;
+    }
+
+    file_writer.close()?;
+    buffer.drain_into(&mut writer)?;
+
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/// Writes all items of an iterator into table files in the given folder.
+///
+/// A new table file is started once the previous one contains `target_size` bytes of keys and values.
+///
+/// Returns the paths of the written table files, in key order.
+pub fn export_tables(
+    iter: impl Iterator<Item = IterGuardImpl>,
+    config: &Config,
+    folder: &Path,
+    target_size: u64,
+) -> crate::Result<Vec<PathBuf>> {
+    config.fs.create_dir_all(folder)?;
+
+    let mut paths = vec![];
+    let mut builder: Option<SstBuilder> = None;
+    let mut size = 0;
+
+    for guard in iter {
+        let (key, value) = guard.into_inner()?;
+
+        let builder = match &mut builder {
+            Some(builder) if size < target_size => builder,
+            _ => {
+                if let Some(builder) = builder.take() {
+                    builder.finish()?;
+                }
+
+                let path = folder.join(format!("{}.sst", paths.len()));
+                paths.push(path.clone());
+                size = 0;
+
+                builder.insert(SstBuilder::new(path, config)?)
+            }
+        };
+
+        size += (key.len() + value.len()) as u64;
+        builder.insert(key, value)?;
+    }
+
+    if let Some(builder) = builder {
+        builder.finish()?;
+    }
+
+    config.fs.sync_directory(folder)?;
+
+    Ok(paths)
+}
+
+/// Splits the key space into shards at the given (ascending) boundaries.
+///
+/// `n` boundaries result in `n + 1` shards, the first and last shard being unbounded.
+pub fn shard_ranges(boundaries: &[UserKey]) -> Vec<(Bound<UserKey>, Bound<UserKey>)> {
+    let mut lo = Bound::Unbounded;
+    let mut shards = Vec::with_capacity(boundaries.len() + 1);
+
+    for boundary in boundaries {
+        shards.push((lo, Bound::Excluded(boundary.clone())));
+        lo = Bound::Included(boundary.clone());
+    }
+
+    shards.push((lo, Bound::Unbounded));
+
+    shards
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn export_csv_escape() -> crate::Result<()> {
+        let mut buf = vec![];
+        write_csv_field(&mut buf, b"a,\"b\"")?;
+        assert_eq!(b"\"a,\"\"b\"\"\"", &*buf);
+
+        let mut buf = vec![];
+        write_csv_field(&mut buf, b"abc")?;
+        assert_eq!(b"abc", &*buf);
+
+        assert!(matches!(
+            write_csv_field(&mut vec![], &[0xFF]),
+            Err(crate::Error::Utf8(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn export_hex() -> crate::Result<()> {
+        let mut buf = vec![];
+        write_hex(&mut buf, &[0x00, 0xAB, 0x7F])?;
+        assert_eq!(b"00ab7f", &*buf);
+        Ok(())
+    }
+
+    #[test]
+    fn export_shard_ranges() {
+        assert_eq!(
+            vec![(Bound::Unbounded, Bound::Unbounded)],
+            shard_ranges(&[]),
+        );
+
+        assert_eq!(
+            vec![
+                (Bound::Unbounded, Bound::Excluded(UserKey::from("c"))),
+                (
+                    Bound::Included(UserKey::from("c")),
+                    Bound::Excluded(UserKey::from("f"))
+                ),
+                (Bound::Included(UserKey::from("f")), Bound::Unbounded),
+            ],
+            shard_ranges(&["c".into(), "f".into()]),
+        );
+    }
+}
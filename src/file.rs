@@ -2,96 +2,91 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::Slice;
-use std::{fs::File, io::Write, path::Path};
+use crate::{FileHandle, Filesystem, Slice};
+use std::path::{Path, PathBuf};
 
 pub const MAGIC_BYTES: [u8; 4] = [b'L', b'S', b'M', 3];
 
 pub const MANIFEST_FILE: &str = "manifest";
+pub const LOCK_FILE: &str = "lock";
 pub const TABLES_FOLDER: &str = "tables";
 pub const BLOBS_FOLDER: &str = "blobs";
+pub const JOURNAL_FOLDER: &str = "journal";
+pub const PARTITIONS_FOLDER: &str = "partitions";
+
+/// Returns the path of a table or blob file inside its folder.
+///
+/// If the folder is sharded, files are spread across `shard_count` subfolders
+/// by the hash of their ID, otherwise they are placed in the folder directly.
+#[must_use]
+pub fn sharded_path(folder: &Path, id: u64, shard_count: u16) -> PathBuf {
+    if shard_count <= 1 {
+        return folder.join(id.to_string());
+    }
 
-/// Reads bytes from a file using `pread`.
-pub fn read_exact(file: &File, offset: u64, size: usize) -> std::io::Result<Slice> {
-    // SAFETY: This slice builder starts uninitialized, but we know its length
-    //
-    // We use read_at/seek_read which give us the number of bytes read
-    // If that number does not match the slice length, the function panics (for now),
-    // so the (partially) uninitialized buffer is discarded
-    //
-    // Additionally, generally, block loads furthermore do a checksum check which
-    // would likely catch the buffer being wrong somehow
-    #[expect(unsafe_code, reason = "see safety")]
-    let mut builder = unsafe { Slice::builder_unzeroed(size) };
+    let shard = crate::hash::hash64(&id.to_le_bytes()) % u64::from(shard_count);
+    folder.join(shard.to_string()).join(id.to_string())
+}
 
-    {
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::FileExt;
+/// Creates the shard subfolders of a table or blob folder.
+pub fn create_shard_folders(
+    fs: &dyn Filesystem,
+    folder: &Path,
+    shard_count: u16,
+) -> std::io::Result<()> {
+    if shard_count <= 1 {
+        return Ok(());
+    }
 
-            let bytes_read = file.read_at(&mut builder, offset)?;
+    for shard in 0..shard_count {
+        let path = folder.join(shard.to_string());
 
-            assert_eq!(
-                bytes_read,
-                size,
-                "not enough bytes read: file has length {}",
-                file.metadata()?.len(),
-            );
+        if !fs.exists(&path)? {
+            fs.create_dir_all(&path)?;
         }
+    }
 
-        #[cfg(windows)]
-        {
-            use std::os::windows::fs::FileExt;
-
-            let bytes_read = file.seek_read(&mut builder, offset)?;
+    fs.sync_directory(folder)
+}
 
-            assert_eq!(
-                bytes_read,
-                size,
-                "not enough bytes read: file has length {}",
-                file.metadata()?.len(),
-            );
-        }
+/// Lists the files of a table or blob folder, including the files of its shard subfolders.
+pub fn read_sharded_dir(fs: &dyn Filesystem, folder: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = vec![];
 
-        #[cfg(not(any(unix, windows)))]
-        {
-            compile_error!("unsupported OS");
-            unimplemented!();
+    for path in fs.read_dir(folder)? {
+        if fs.is_dir(&path)? {
+            paths.extend(fs.read_dir(&path)?);
+        } else {
+            paths.push(path);
         }
     }
 
-    Ok(builder.freeze().into())
+    Ok(paths)
 }
 
-/// Atomically rewrites a file.
-pub fn rewrite_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
-    #[expect(
-        clippy::expect_used,
-        reason = "every file should have a parent directory"
-    )]
-    let folder = path.parent().expect("should have a parent");
-
-    let mut temp_file = tempfile::NamedTempFile::new_in(folder)?;
-    temp_file.write_all(content)?;
-    temp_file.flush()?;
-    temp_file.as_file_mut().sync_all()?;
-    temp_file.persist(path)?;
-
-    // TODO: not sure why it fails on Windows...
-    #[cfg(not(target_os = "windows"))]
-    {
-        let file = std::fs::File::open(path)?;
-        file.sync_all()?;
-
-        #[expect(
-            clippy::expect_used,
-            reason = "files should always have a parent directory"
-        )]
-        let folder = path.parent().expect("should have parent folder");
-        fsync_directory(folder)?;
-    }
+/// Reads bytes from a file using positional reads, see [`FileHandle::read_at`].
+pub fn read_exact(file: &dyn FileHandle, offset: u64, size: usize) -> std::io::Result<Slice> {
+    // SAFETY: This slice builder starts uninitialized, but we know its length
+    //
+    // We use read_at which gives us the number of bytes read
+    // If that number does not match the slice length, the function panics (for now),
+    // so the (partially) uninitialized buffer is discarded
+    //
+    // Additionally, generally, block loads furthermore do a checksum check which
+    // would likely catch the buffer being wrong somehow
+    #[expect(unsafe_code, reason = "see safety")]
+    let mut builder = unsafe { Slice::builder_unzeroed(size) };
 
-    Ok(())
+    let bytes_read = file.read_at(&mut builder, offset)?;
+
+    assert_eq!(
+        bytes_read,
+        size,
+        "not enough bytes read: file has length {}",
+        file.len()?,
+    );
+
+    Ok(builder.freeze().into())
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -110,25 +105,28 @@ pub fn fsync_directory(path: &Path) -> std::io::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-    use std::io::Write;
     use test_log::test;
 
     #[test]
-    fn atomic_rewrite() -> crate::Result<()> {
-        let dir = tempfile::tempdir()?;
+    fn sharded_path_unsharded() {
+        let folder = Path::new("tables");
+        assert_eq!(folder.join("7"), sharded_path(folder, 7, 0));
+        assert_eq!(folder.join("7"), sharded_path(folder, 7, 1));
+    }
 
-        let path = dir.path().join("test.txt");
-        {
-            let mut file = File::create(&path)?;
-            write!(file, "asdasdasdasdasd")?;
-        }
+    #[test]
+    fn sharded_path_spread() {
+        let folder = Path::new("tables");
 
-        rewrite_atomic(&path, b"newcontent")?;
+        let mut shards = std::collections::HashSet::new();
 
-        let content = std::fs::read_to_string(&path)?;
-        assert_eq!("newcontent", content);
+        for id in 0..1_000 {
+            let path = sharded_path(folder, id, 16);
+            assert_eq!(Some(folder), path.parent().and_then(Path::parent));
+            assert_eq!(path, sharded_path(folder, id, 16));
+            shards.insert(path.parent().map(Path::to_path_buf));
+        }
 
-        Ok(())
+        assert_eq!(16, shards.len());
     }
 }
@@ -0,0 +1,1064 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions, TryLockError},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+
+/// An open file of a [`Filesystem`]
+///
+/// Besides streaming reads and writes, files need to support positional reads,
+/// so they can be read concurrently after being cached in the [`crate::DescriptorTable`].
+#[expect(
+    clippy::len_without_is_empty,
+    reason = "the length of a file is only used to find its end, like std::fs::Metadata::len"
+)]
+pub trait FileHandle: Read + Write + Seek + std::fmt::Debug + Send + Sync + 'static {
+    /// Reads bytes starting at the given offset, without moving the cursor of the file.
+    ///
+    /// Returns the number of bytes read.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<usize>;
+
+    /// Returns the length of the file in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn len(&self) -> std::io::Result<u64>;
+
+    /// Creates a new handle to the same file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn try_clone(&self) -> std::io::Result<Box<dyn FileHandle>>;
+
+    /// Truncates or extends the file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn set_len(&self, len: u64) -> std::io::Result<()>;
+
+    /// Persists the content and metadata of the file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn sync_all(&self) -> std::io::Result<()>;
+
+    /// Persists the content of the file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn sync_data(&self) -> std::io::Result<()> {
+        self.sync_all()
+    }
+
+    /// Tries to take an exclusive lock on the file, which is released when the file is closed.
+    ///
+    /// The default implementation does not support locking.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file is already locked, or an IO error occurs.
+    fn try_lock(&self) -> Result<(), TryLockError> {
+        Err(TryLockError::Error(std::io::ErrorKind::Unsupported.into()))
+    }
+}
+
+impl FileHandle for File {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::FileExt::read_at(self, buf, offset)
+        }
+
+        #[cfg(windows)]
+        {
+            std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+        }
+
+        // NOTE: WASI has positional reads, but `std::os::wasi::fs::FileExt` is unstable,
+        // so seek the shared file cursor instead, which is fine on single-threaded targets
+        #[cfg(not(any(unix, windows)))]
+        {
+            let mut file = self;
+            file.seek(SeekFrom::Start(offset))?;
+            file.read(buf)
+        }
+    }
+
+    fn len(&self) -> std::io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn try_clone(&self) -> std::io::Result<Box<dyn FileHandle>> {
+        Ok(Box::new(Self::try_clone(self)?))
+    }
+
+    fn set_len(&self, len: u64) -> std::io::Result<()> {
+        Self::set_len(self, len)
+    }
+
+    fn sync_all(&self) -> std::io::Result<()> {
+        Self::sync_all(self)
+    }
+
+    fn sync_data(&self) -> std::io::Result<()> {
+        Self::sync_data(self)
+    }
+
+    fn try_lock(&self) -> Result<(), TryLockError> {
+        Self::try_lock(self)
+    }
+}
+
+/// Storage backend of a tree
+///
+/// All file access of a tree (tables, blob files, journal, manifest and versions)
+/// goes through this trait, so it can be replaced, e.g. by a fault-injecting wrapper for tests,
+/// or a custom OS interface.
+///
+/// Opened files are handed out as [`FileHandle`]s.
+///
+/// The default implementation is [`StdFilesystem`], [`MemFilesystem`] keeps all files in memory.
+pub trait Filesystem: std::fmt::Debug + Send + Sync + 'static {
+    /// Opens an existing file for reading.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn open(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>>;
+
+    /// Opens an existing file for writing.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn open_writable(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>>;
+
+    /// Creates a new file for writing, failing if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn create_new(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>>;
+
+    /// Recursively creates a directory and all of its parents.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Returns the paths of all entries of a directory.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>>;
+
+    /// Removes a file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn remove_file(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Removes an empty directory.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Renames a file, replacing the destination if it exists.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()>;
+
+    /// Returns `true` if the path exists.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn exists(&self, path: &Path) -> std::io::Result<bool>;
+
+    /// Returns `true` if the path is a directory.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn is_dir(&self, path: &Path) -> std::io::Result<bool>;
+
+    /// Persists the entries of a directory.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn sync_directory(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Creates a new hard link to a file, failing if the destination already exists.
+    ///
+    /// The default implementation copies the file instead, which does not save
+    /// disk space, but works for every backend.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn hard_link(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        copy_file(self, from, to)
+    }
+}
+
+/// [`Filesystem`] implementation using [`std::fs`]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StdFilesystem;
+
+impl Filesystem for StdFilesystem {
+    fn open(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        Ok(Box::new(File::open(path)?))
+    }
+
+    fn open_writable(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        Ok(Box::new(OpenOptions::new().write(true).open(path)?))
+    }
+
+    fn create_new(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        Ok(Box::new(File::create_new(path)?))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        std::fs::read_dir(path)?
+            .map(|dirent| dirent.map(|dirent| dirent.path()))
+            .collect()
+    }
+
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::remove_dir(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        std::fs::rename(from, to)
+    }
+
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        path.try_exists()
+    }
+
+    fn is_dir(&self, path: &Path) -> std::io::Result<bool> {
+        Ok(std::fs::metadata(path)?.is_dir())
+    }
+
+    fn sync_directory(&self, path: &Path) -> std::io::Result<()> {
+        crate::file::fsync_directory(path)
+    }
+
+    fn hard_link(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        std::fs::hard_link(from, to)
+    }
+}
+
+/// [`Filesystem`] implementation that keeps all files in memory
+///
+/// Files survive reopening a tree with the same filesystem, and are lost when
+/// the filesystem is dropped, which makes it useful for tests.
+///
+/// # Examples
+///
+/// ```
+/// # use lsm_tree::{AbstractTree, Config, Filesystem, MemFilesystem, SequenceNumberCounter};
+/// # use std::sync::Arc;
+/// let fs = Arc::new(MemFilesystem::default());
+///
+/// let tree = Config::new("/db", SequenceNumberCounter::default())
+///     .with_filesystem(fs.clone())
+///     .open()?;
+///
+/// tree.insert("a", "abc", 0);
+/// tree.flush_active_memtable(0)?;
+///
+/// assert!(fs.exists("/db/manifest".as_ref())?);
+/// assert!(!std::path::Path::new("/db").try_exists()?);
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct MemFilesystem {
+    entries: Mutex<BTreeMap<PathBuf, MemEntry>>,
+}
+
+#[derive(Debug)]
+enum MemEntry {
+    Dir,
+    File(Arc<MemNode>),
+}
+
+/// Content of an in-memory file, shared by all of its handles and hard links
+#[derive(Debug, Default)]
+struct MemNode {
+    data: RwLock<Vec<u8>>,
+    locked: Mutex<bool>,
+}
+
+/// Open file of a [`MemFilesystem`]
+#[derive(Debug)]
+struct MemFile {
+    node: Arc<MemNode>,
+    pos: u64,
+    writable: bool,
+    holds_lock: AtomicBool,
+}
+
+impl MemFile {
+    fn new(node: Arc<MemNode>, writable: bool) -> Self {
+        Self {
+            node,
+            pos: 0,
+            writable,
+            holds_lock: AtomicBool::new(false),
+        }
+    }
+
+    fn check_writable(&self) -> std::io::Result<()> {
+        if self.writable {
+            Ok(())
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "file is not opened for writing",
+            ))
+        }
+    }
+}
+
+impl Drop for MemFile {
+    fn drop(&mut self) {
+        if *self.holds_lock.get_mut() {
+            *self.node.locked.lock().expect("lock is poisoned") = false;
+        }
+    }
+}
+
+impl Read for MemFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let bytes_read = self.read_at(buf, self.pos)?;
+        self.pos += bytes_read as u64;
+        Ok(bytes_read)
+    }
+}
+
+impl Write for MemFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.check_writable()?;
+
+        let mut data = self.node.data.write().expect("lock is poisoned");
+
+        let start = usize::try_from(self.pos).map_err(std::io::Error::other)?;
+        let end = start + buf.len();
+
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+
+        if let Some(dest) = data.get_mut(start..end) {
+            dest.copy_from_slice(buf);
+        }
+
+        drop(data);
+
+        self.pos = end as u64;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for MemFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len()?.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        self.pos = new_pos.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.pos)
+    }
+}
+
+impl FileHandle for MemFile {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+        let data = self.node.data.read().expect("lock is poisoned");
+
+        let start = usize::try_from(offset).unwrap_or(usize::MAX);
+        let src = data.get(start..).unwrap_or_default();
+
+        let len = src.len().min(buf.len());
+
+        if let (Some(dest), Some(src)) = (buf.get_mut(..len), src.get(..len)) {
+            dest.copy_from_slice(src);
+        }
+
+        drop(data);
+
+        Ok(len)
+    }
+
+    fn len(&self) -> std::io::Result<u64> {
+        Ok(self.node.data.read().expect("lock is poisoned").len() as u64)
+    }
+
+    fn try_clone(&self) -> std::io::Result<Box<dyn FileHandle>> {
+        Ok(Box::new(Self::new(self.node.clone(), self.writable)))
+    }
+
+    fn set_len(&self, len: u64) -> std::io::Result<()> {
+        self.check_writable()?;
+
+        let len = usize::try_from(len).map_err(std::io::Error::other)?;
+        self.node
+            .data
+            .write()
+            .expect("lock is poisoned")
+            .resize(len, 0);
+
+        Ok(())
+    }
+
+    fn sync_all(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn try_lock(&self) -> Result<(), TryLockError> {
+        if self.holds_lock.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        let mut locked = self.node.locked.lock().expect("lock is poisoned");
+
+        if *locked {
+            return Err(TryLockError::WouldBlock);
+        }
+
+        *locked = true;
+        drop(locked);
+
+        self.holds_lock.store(true, Ordering::Release);
+
+        Ok(())
+    }
+}
+
+fn not_found(path: &Path) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("{} does not exist", path.display()),
+    )
+}
+
+/// Removes `.` components, so the same file is always found under the same key.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect()
+}
+
+impl MemFilesystem {
+    fn open_file(&self, path: &Path, writable: bool) -> std::io::Result<Box<dyn FileHandle>> {
+        match self
+            .entries
+            .lock()
+            .expect("lock is poisoned")
+            .get(&normalize(path))
+        {
+            Some(MemEntry::File(node)) => Ok(Box::new(MemFile::new(node.clone(), writable))),
+            Some(MemEntry::Dir) => Err(std::io::Error::new(
+                std::io::ErrorKind::IsADirectory,
+                format!("{} is a directory", path.display()),
+            )),
+            None => Err(not_found(path)),
+        }
+    }
+
+    /// Fails if the parent directory of a new entry does not exist.
+    fn check_parent(entries: &BTreeMap<PathBuf, MemEntry>, path: &Path) -> std::io::Result<()> {
+        match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => match entries.get(parent) {
+                Some(MemEntry::Dir) => Ok(()),
+                _ => Err(not_found(parent)),
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Filesystem for MemFilesystem {
+    fn open(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        self.open_file(path, false)
+    }
+
+    fn open_writable(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        self.open_file(path, true)
+    }
+
+    fn create_new(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        let path = normalize(path);
+        let mut entries = self.entries.lock().expect("lock is poisoned");
+
+        if entries.contains_key(&path) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        Self::check_parent(&entries, &path)?;
+
+        let node = Arc::new(MemNode::default());
+        entries.insert(path, MemEntry::File(node.clone()));
+        drop(entries);
+
+        Ok(Box::new(MemFile::new(node, true)))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.entries.lock().expect("lock is poisoned");
+
+        for ancestor in path.ancestors() {
+            if ancestor.as_os_str().is_empty() {
+                continue;
+            }
+
+            match entries.get(ancestor) {
+                Some(MemEntry::Dir) => {}
+                Some(MemEntry::File(_)) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotADirectory,
+                        format!("{} is a file", ancestor.display()),
+                    ));
+                }
+                None => {
+                    entries.insert(ancestor.to_path_buf(), MemEntry::Dir);
+                }
+            }
+        }
+
+        drop(entries);
+
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let path = normalize(path);
+        let entries = self.entries.lock().expect("lock is poisoned");
+
+        if !matches!(entries.get(&path), Some(MemEntry::Dir)) {
+            return Err(not_found(&path));
+        }
+
+        Ok(entries
+            .keys()
+            .filter(|child| child.parent() == Some(&path))
+            .cloned()
+            .collect())
+    }
+
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.entries.lock().expect("lock is poisoned");
+
+        match entries.get(&path) {
+            Some(MemEntry::File(_)) => {
+                entries.remove(&path);
+                drop(entries);
+                Ok(())
+            }
+            Some(MemEntry::Dir) => Err(std::io::Error::new(
+                std::io::ErrorKind::IsADirectory,
+                format!("{} is a directory", path.display()),
+            )),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.entries.lock().expect("lock is poisoned");
+
+        if !matches!(entries.get(&path), Some(MemEntry::Dir)) {
+            return Err(not_found(&path));
+        }
+
+        if entries.keys().any(|child| child.parent() == Some(&path)) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::DirectoryNotEmpty,
+                format!("{} is not empty", path.display()),
+            ));
+        }
+
+        entries.remove(&path);
+        drop(entries);
+
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        let from = normalize(from);
+        let to = normalize(to);
+        let mut entries = self.entries.lock().expect("lock is poisoned");
+
+        if !entries.contains_key(&from) {
+            return Err(not_found(&from));
+        }
+        Self::check_parent(&entries, &to)?;
+
+        if let Some(MemEntry::File(_)) = entries.get(&to) {
+            entries.remove(&to);
+        }
+
+        // NOTE: Renaming a directory moves all of its children
+        let moved = entries
+            .keys()
+            .filter(|path| path.starts_with(&from))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for path in moved {
+            if let (Some(entry), Ok(suffix)) = (entries.remove(&path), path.strip_prefix(&from)) {
+                let dest = if suffix.as_os_str().is_empty() {
+                    to.clone()
+                } else {
+                    to.join(suffix)
+                };
+                entries.insert(dest, entry);
+            }
+        }
+
+        drop(entries);
+
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        Ok(self
+            .entries
+            .lock()
+            .expect("lock is poisoned")
+            .contains_key(&normalize(path)))
+    }
+
+    fn is_dir(&self, path: &Path) -> std::io::Result<bool> {
+        let path = normalize(path);
+
+        match self.entries.lock().expect("lock is poisoned").get(&path) {
+            Some(entry) => Ok(matches!(entry, MemEntry::Dir)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn sync_directory(&self, path: &Path) -> std::io::Result<()> {
+        if self.is_dir(path)? {
+            Ok(())
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::NotADirectory,
+                format!("{} is a file", path.display()),
+            ))
+        }
+    }
+
+    fn hard_link(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        let from = normalize(from);
+        let to = normalize(to);
+        let mut entries = self.entries.lock().expect("lock is poisoned");
+
+        let Some(MemEntry::File(node)) = entries.get(&from) else {
+            return Err(not_found(&from));
+        };
+        let node = node.clone();
+
+        if entries.contains_key(&to) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", to.display()),
+            ));
+        }
+        Self::check_parent(&entries, &to)?;
+
+        entries.insert(to, MemEntry::File(node));
+        drop(entries);
+
+        Ok(())
+    }
+}
+
+/// Atomically rewrites a file, using a temporary file in the same folder.
+pub fn rewrite_atomic(fs: &dyn Filesystem, path: &Path, content: &[u8]) -> std::io::Result<()> {
+    #[expect(
+        clippy::expect_used,
+        reason = "every file should have a parent directory"
+    )]
+    let folder = path.parent().expect("should have a parent");
+
+    let temp_path = {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".tmp");
+        folder.join(name)
+    };
+
+    // NOTE: A stale temporary file may be left over from a crash
+    if fs.exists(&temp_path)? {
+        fs.remove_file(&temp_path)?;
+    }
+
+    {
+        let mut temp_file = fs.create_new(&temp_path)?;
+        temp_file.write_all(content)?;
+        temp_file.flush()?;
+        temp_file.sync_all()?;
+    }
+
+    fs.rename(&temp_path, path)?;
+
+    // TODO: not sure why it fails on Windows...
+    #[cfg(not(target_os = "windows"))]
+    {
+        let file = fs.open(path)?;
+        file.sync_all()?;
+    }
+
+    fs.sync_directory(folder)?;
+
+    Ok(())
+}
+
+/// Takes an exclusive lock on a tree folder, so it cannot be opened twice.
+///
+/// The lock is released when the returned file is closed.
+pub fn lock_directory(fs: &dyn Filesystem, folder: &Path) -> crate::Result<Box<dyn FileHandle>> {
+    let path = folder.join(crate::file::LOCK_FILE);
+
+    let file = match fs.create_new(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => fs.open_writable(&path)?,
+        Err(e) => return Err(e.into()),
+    };
+
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            log::error!("Tree at {} is already opened", folder.display());
+            return Err(crate::Error::AlreadyLocked);
+        }
+        Err(TryLockError::Error(e)) if e.kind() == std::io::ErrorKind::Unsupported => {
+            // NOTE: Some platforms (e.g. WASI) do not support file locks
+            log::warn!(
+                "File locking is not supported, cannot lock {}",
+                folder.display()
+            );
+        }
+        Err(TryLockError::Error(e)) => return Err(e.into()),
+    }
+
+    Ok(file)
+}
+
+/// Reads the table of contents of an archive file.
+pub fn read_archive(fs: &dyn Filesystem, path: &Path) -> crate::Result<sfa::Reader> {
+    let mut file = fs.open(path)?;
+    Ok(sfa::Reader::from_reader(&mut file)?)
+}
+
+/// Opens a buffered reader over a section of an archive file.
+pub fn section_reader(
+    fs: &dyn Filesystem,
+    path: &Path,
+    section: &sfa::TocEntry,
+) -> std::io::Result<impl BufRead> {
+    let mut reader = BufReader::new(fs.open(path)?);
+    reader.seek(SeekFrom::Start(section.pos()))?;
+    Ok(reader.take(section.len()))
+}
+
+/// Moves a folder, copying it if it cannot be renamed because the destination
+/// is on a different filesystem.
+///
+/// The destination's parent folder needs to exist.
+pub fn move_dir(fs: &dyn Filesystem, from: &Path, to: &Path) -> std::io::Result<()> {
+    match fs.rename(from, to) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            log::debug!(
+                "Cannot rename {} to {}, copying instead",
+                from.display(),
+                to.display(),
+            );
+            copy_dir(fs, from, to)?;
+            remove_dir_all(fs, from)?;
+        }
+        Err(e) => return Err(e),
+    }
+
+    if let Some(parent) = to.parent() {
+        fs.sync_directory(parent)?;
+    }
+    if let Some(parent) = from.parent() {
+        fs.sync_directory(parent)?;
+    }
+
+    Ok(())
+}
+
+/// Recursively copies a folder, persisting all copied files.
+fn copy_dir(fs: &dyn Filesystem, from: &Path, to: &Path) -> std::io::Result<()> {
+    fs.create_dir_all(to)?;
+
+    for path in fs.read_dir(from)? {
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+        let dest = to.join(file_name);
+
+        if fs.is_dir(&path)? {
+            copy_dir(fs, &path, &dest)?;
+        } else {
+            copy_file(fs, &path, &dest)?;
+        }
+    }
+
+    fs.sync_directory(to)
+}
+
+/// Copies a file, persisting the copy.
+fn copy_file<F: Filesystem + ?Sized>(fs: &F, from: &Path, to: &Path) -> std::io::Result<()> {
+    let mut reader = fs.open(from)?;
+    let mut writer = fs.create_new(to)?;
+    std::io::copy(&mut reader, &mut writer)?;
+    writer.sync_all()
+}
+
+/// Hard links a file, copying it if it cannot be linked because the destination
+/// is on a different filesystem.
+pub fn link_or_copy(fs: &dyn Filesystem, from: &Path, to: &Path) -> std::io::Result<()> {
+    match fs.hard_link(from, to) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            log::debug!(
+                "Cannot link {} to {}, copying instead",
+                from.display(),
+                to.display(),
+            );
+            copy_file(fs, from, to)
+        }
+        result => result,
+    }
+}
+
+/// Recursively removes a folder.
+pub fn remove_dir_all(fs: &dyn Filesystem, path: &Path) -> std::io::Result<()> {
+    for child in fs.read_dir(path)? {
+        if fs.is_dir(&child)? {
+            remove_dir_all(fs, &child)?;
+        } else {
+            fs.remove_file(&child)?;
+        }
+    }
+
+    fs.remove_dir(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use test_log::test;
+
+    #[test]
+    fn atomic_rewrite() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let path = dir.path().join("test.txt");
+        {
+            let mut file = File::create(&path)?;
+            write!(file, "asdasdasdasdasd")?;
+        }
+
+        rewrite_atomic(&StdFilesystem, &path, b"newcontent")?;
+
+        let content = std::fs::read_to_string(&path)?;
+        assert_eq!("newcontent", content);
+
+        Ok(())
+    }
+
+    #[test]
+    fn atomic_rewrite_stale_temp_file() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let path = dir.path().join("test.txt");
+        std::fs::write(dir.path().join("test.txt.tmp"), "stale")?;
+
+        rewrite_atomic(&StdFilesystem, &path, b"newcontent")?;
+
+        let content = std::fs::read_to_string(&path)?;
+        assert_eq!("newcontent", content);
+        assert!(!dir.path().join("test.txt.tmp").try_exists()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn copy_and_remove_dir() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let from = dir.path().join("from");
+        std::fs::create_dir_all(from.join("a").join("b"))?;
+        std::fs::write(from.join("1"), "one")?;
+        std::fs::write(from.join("a").join("b").join("2"), "two")?;
+
+        let to = dir.path().join("to");
+        copy_dir(&StdFilesystem, &from, &to)?;
+        remove_dir_all(&StdFilesystem, &from)?;
+
+        assert!(!from.try_exists()?);
+        assert_eq!("one", std::fs::read_to_string(to.join("1"))?);
+        assert_eq!(
+            "two",
+            std::fs::read_to_string(to.join("a").join("b").join("2"))?,
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn link_or_copy_file() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let from = dir.path().join("from");
+        std::fs::write(&from, "one")?;
+
+        let to = dir.path().join("to");
+        link_or_copy(&StdFilesystem, &from, &to)?;
+
+        // NOTE: The link stays valid when the original file is deleted
+        std::fs::remove_file(&from)?;
+        assert_eq!("one", std::fs::read_to_string(&to)?);
+
+        assert!(link_or_copy(&StdFilesystem, &to, &to).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn move_dir_rename() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let from = dir.path().join("from");
+        std::fs::create_dir_all(&from)?;
+        std::fs::write(from.join("1"), "one")?;
+
+        let to = dir.path().join("to");
+        move_dir(&StdFilesystem, &from, &to)?;
+
+        assert!(!from.try_exists()?);
+        assert_eq!("one", std::fs::read_to_string(to.join("1"))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mem_filesystem_file() -> crate::Result<()> {
+        let fs = MemFilesystem::default();
+        fs.create_dir_all(Path::new("/a/b"))?;
+
+        let path = Path::new("/a/b/file");
+        {
+            let mut file = fs.create_new(path)?;
+            file.write_all(b"hello world")?;
+        }
+        assert!(fs.create_new(path).is_err());
+
+        let mut file = fs.open(path)?;
+        assert_eq!(11, file.len()?);
+        assert!(file.write_all(b"x").is_err());
+
+        let mut buf = [0; 5];
+        assert_eq!(5, file.read_at(&mut buf, 6)?);
+        assert_eq!(b"world", &buf);
+
+        file.seek(SeekFrom::Start(6))?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        assert_eq!("world", content);
+
+        fs.open_writable(path)?.set_len(5)?;
+        assert_eq!(5, file.len()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mem_filesystem_dirs() -> crate::Result<()> {
+        let fs = MemFilesystem::default();
+
+        let from = Path::new("/from");
+        fs.create_dir_all(&from.join("a"))?;
+        fs.create_new(&from.join("1"))?.write_all(b"one")?;
+        fs.create_new(&from.join("a").join("2"))?
+            .write_all(b"two")?;
+
+        assert!(fs.remove_dir(from).is_err());
+
+        let to = Path::new("/to");
+        move_dir(&fs, from, to)?;
+        assert!(!fs.exists(from)?);
+
+        let mut children = fs.read_dir(to)?;
+        children.sort();
+        assert_eq!(vec![to.join("1"), to.join("a")], children);
+        assert!(fs.is_dir(&to.join("a"))?);
+
+        // NOTE: Hard links share their content
+        link_or_copy(&fs, &to.join("1"), &to.join("3"))?;
+        fs.remove_file(&to.join("1"))?;
+        assert_eq!(3, fs.open(&to.join("3"))?.len()?);
+
+        remove_dir_all(&fs, to)?;
+        assert!(!fs.exists(to)?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{range_tombstone::RangeTombstone, Encryptor, InternalValue, SeqNo, UserKey, ValueType};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use std::io::{Read, Write};
+use varint_rs::{VarintReader, VarintWriter};
+
+/// Type of journal entries that hold a range tombstone
+///
+/// Does not collide with any [`ValueType`].
+const RANGE_TOMBSTONE_TYPE: u8 = 0b1000_0000;
+
+/// A journaled write
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Entry {
+    /// A write of a single key
+    Value(InternalValue),
+
+    /// A range tombstone, see [`crate::AbstractTree::remove_range`]
+    RangeTombstone(RangeTombstone),
+}
+
+impl Entry {
+    /// Returns the sequence number of the write.
+    pub fn seqno(&self) -> SeqNo {
+        match self {
+            Self::Value(value) => value.key.seqno,
+            Self::RangeTombstone(rt) => rt.seqno,
+        }
+    }
+}
+
+/// Encodes a journal entry
+///
+/// [type; 1 byte] [seqno; varint] [key len; varint] [key] [value len; varint] [value] [checksum; 8 bytes]
+///
+/// Range tombstones store their start key as key, and their end key as value
+/// (empty if the range is unbounded, because the end key of a range tombstone is never empty).
+///
+/// The checksum is the XXH3 hash of all preceding bytes of the entry.
+pub fn encode(value: &InternalValue) -> Vec<u8> {
+    encode_raw(
+        u8::from(value.key.value_type),
+        value.key.seqno,
+        &value.key.user_key,
+        &value.value,
+    )
+}
+
+/// Encodes a range tombstone journal entry, see [`encode`].
+pub fn encode_range_tombstone(rt: &RangeTombstone) -> Vec<u8> {
+    encode_raw(
+        RANGE_TOMBSTONE_TYPE,
+        rt.seqno,
+        &rt.start,
+        rt.end.as_deref().unwrap_or_default(),
+    )
+}
+
+fn encode_raw(tag: u8, seqno: SeqNo, key: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(key.len() + value.len() + 24);
+
+    // NOTE: Writing into a Vec cannot fail
+    let _ = encode_into(&mut buf, tag, seqno, key, value);
+
+    let checksum = xxhash_rust::xxh3::xxh3_64(&buf);
+    buf.extend_from_slice(&checksum.to_le_bytes());
+
+    buf
+}
+
+fn encode_into<W: Write>(
+    writer: &mut W,
+    tag: u8,
+    seqno: SeqNo,
+    key: &[u8],
+    value: &[u8],
+) -> std::io::Result<()> {
+    writer.write_u8(tag)?;
+    writer.write_u64_varint(seqno)?;
+
+    #[expect(clippy::cast_possible_truncation, reason = "keys are u16 length max")]
+    writer.write_u16_varint(key.len() as u16)?;
+    writer.write_all(key)?;
+
+    #[expect(clippy::cast_possible_truncation, reason = "values are u32 length max")]
+    writer.write_u32_varint(value.len() as u32)?;
+    writer.write_all(value)?;
+
+    Ok(())
+}
+
+/// Reader adapter that remembers all bytes read through it
+struct Recorder<'a, R: Read> {
+    inner: &'a mut R,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> Read for Recorder<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.buf.extend_from_slice(buf.get(..n).unwrap_or_default());
+        Ok(n)
+    }
+}
+
+/// Result of decoding a single journal entry
+pub enum Decoded {
+    /// A valid entry, and its encoded size
+    Entry(Entry, u64),
+
+    /// End of valid data (end of file, torn write or corruption)
+    End,
+}
+
+/// Decodes a journal entry.
+///
+/// Truncated or corrupt entries are not an error, but signal the end of valid data.
+pub fn decode_from<R: Read>(reader: &mut R) -> crate::Result<Decoded> {
+    let mut recorder = Recorder {
+        inner: reader,
+        buf: Vec::new(),
+    };
+
+    match decode_inner(&mut recorder) {
+        Ok(Some(entry)) => {
+            let expected = xxhash_rust::xxh3::xxh3_64(&recorder.buf);
+
+            let got = match recorder.inner.read_u64::<LE>() {
+                Ok(checksum) => checksum,
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return Ok(Decoded::End);
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            if got != expected {
+                log::warn!("Journal entry checksum mismatch, got={got}, expected={expected}");
+                return Ok(Decoded::End);
+            }
+
+            let size = recorder.buf.len() as u64 + std::mem::size_of::<u64>() as u64;
+
+            Ok(Decoded::Entry(entry, size))
+        }
+        Ok(None) => Ok(Decoded::End),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(Decoded::End),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Encodes an encrypted journal entry
+///
+/// [ciphertext len; varint] [ciphertext] [checksum; 8 bytes]
+///
+/// The ciphertext is the encrypted entry, as encoded by [`encode`] or [`encode_range_tombstone`].
+/// The checksum is the XXH3 hash of all preceding bytes of the entry.
+pub fn encode_encrypted(entry: &[u8], encryptor: &dyn Encryptor) -> crate::Result<Vec<u8>> {
+    let ciphertext = encryptor.encrypt(entry)?;
+
+    let mut buf = Vec::with_capacity(ciphertext.len() + 16);
+
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "entries are u32 length max"
+    )]
+    buf.write_u32_varint(ciphertext.len() as u32)?;
+    buf.write_all(&ciphertext)?;
+
+    let checksum = xxhash_rust::xxh3::xxh3_64(&buf);
+    buf.extend_from_slice(&checksum.to_le_bytes());
+
+    Ok(buf)
+}
+
+/// Decodes an encrypted journal entry.
+///
+/// Truncated or corrupt entries are not an error, but signal the end of valid data.
+///
+/// Entries that cannot be decrypted are an error.
+pub fn decode_encrypted_from<R: Read>(
+    reader: &mut R,
+    encryptor: &dyn Encryptor,
+) -> crate::Result<Decoded> {
+    let mut recorder = Recorder {
+        inner: reader,
+        buf: Vec::new(),
+    };
+
+    let ciphertext = match recorder
+        .read_u32_varint()
+        .and_then(|len| read_bytes(&mut recorder, len.into()))
+    {
+        Ok(ciphertext) => ciphertext,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(Decoded::End),
+        Err(e) => return Err(e.into()),
+    };
+
+    let expected = xxhash_rust::xxh3::xxh3_64(&recorder.buf);
+
+    let got = match recorder.inner.read_u64::<LE>() {
+        Ok(checksum) => checksum,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            return Ok(Decoded::End);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if got != expected {
+        log::warn!("Journal entry checksum mismatch, got={got}, expected={expected}");
+        return Ok(Decoded::End);
+    }
+
+    let plaintext = encryptor.decrypt(&ciphertext)?;
+
+    let size = recorder.buf.len() as u64 + std::mem::size_of::<u64>() as u64;
+
+    Ok(match decode_from(&mut &plaintext[..])? {
+        Decoded::Entry(item, _) => Decoded::Entry(item, size),
+        Decoded::End => Decoded::End,
+    })
+}
+
+fn decode_inner<R: Read>(reader: &mut R) -> std::io::Result<Option<Entry>> {
+    let tag = reader.read_u8()?;
+
+    let value_type = if tag == RANGE_TOMBSTONE_TYPE {
+        None
+    } else if let Ok(value_type) = ValueType::try_from(tag) {
+        Some(value_type)
+    } else {
+        log::warn!("Invalid journal entry value type");
+        return Ok(None);
+    };
+
+    let seqno = reader.read_u64_varint()?;
+
+    let key_len = reader.read_u16_varint()?;
+    let key = read_bytes(reader, key_len.into())?;
+
+    let value_len = reader.read_u32_varint()?;
+    let value = read_bytes(reader, value_len.into())?;
+
+    Ok(Some(match value_type {
+        Some(value_type) => Entry::Value(InternalValue::from_components(
+            key, value, seqno, value_type,
+        )),
+        None => Entry::RangeTombstone(RangeTombstone {
+            start: UserKey::from(key),
+            end: (!value.is_empty()).then(|| value.into()),
+            seqno,
+        }),
+    }))
+}
+
+fn read_bytes<R: Read>(reader: &mut R, len: u64) -> std::io::Result<Vec<u8>> {
+    // NOTE: Do not trust the length prefix for allocation, it may be garbage
+    let mut buf = Vec::new();
+    reader.take(len).read_to_end(&mut buf)?;
+
+    if buf.len() as u64 == len {
+        Ok(buf)
+    } else {
+        Err(std::io::ErrorKind::UnexpectedEof.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn journal_entry_roundtrip() -> crate::Result<()> {
+        let value = InternalValue::from_components("abc", "def", 5, ValueType::Value);
+        let bytes = encode(&value);
+
+        let Decoded::Entry(decoded, size) = decode_from(&mut &bytes[..])? else {
+            panic!("should decode");
+        };
+        assert_eq!(Entry::Value(value), decoded);
+        assert_eq!(bytes.len() as u64, size);
+
+        Ok(())
+    }
+
+    #[test]
+    fn journal_entry_roundtrip_range_tombstone() -> crate::Result<()> {
+        for end in [Some(UserKey::from("b")), None] {
+            let rt = RangeTombstone {
+                start: UserKey::empty(),
+                end,
+                seqno: 5,
+            };
+            let bytes = encode_range_tombstone(&rt);
+
+            let Decoded::Entry(decoded, size) = decode_from(&mut &bytes[..])? else {
+                panic!("should decode");
+            };
+            assert_eq!(Entry::RangeTombstone(rt), decoded);
+            assert_eq!(bytes.len() as u64, size);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[expect(clippy::indexing_slicing)]
+    fn journal_entry_roundtrip_encrypted() -> crate::Result<()> {
+        let encryptor = crate::encryption::XorEncryptor(0xAB);
+
+        let value = InternalValue::from_components("abc", "def", 5, ValueType::Value);
+        let bytes = encode_encrypted(&encode(&value), &encryptor)?;
+        assert!(!bytes.windows(3).any(|window| window == b"def"));
+
+        let Decoded::Entry(decoded, size) = decode_encrypted_from(&mut &bytes[..], &encryptor)?
+        else {
+            panic!("should decode");
+        };
+        assert_eq!(Entry::Value(value), decoded);
+        assert_eq!(bytes.len() as u64, size);
+
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                decode_encrypted_from(&mut &bytes[..len], &encryptor)?,
+                Decoded::End,
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[expect(clippy::indexing_slicing)]
+    fn journal_entry_torn() -> crate::Result<()> {
+        let value = InternalValue::new_tombstone("abc", 5);
+        let bytes = encode(&value);
+
+        for len in 0..bytes.len() {
+            assert!(matches!(decode_from(&mut &bytes[..len])?, Decoded::End));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn journal_entry_corrupt() -> crate::Result<()> {
+        let value = InternalValue::from_components("abc", "def", 5, ValueType::Value);
+        let mut bytes = encode(&value);
+
+        if let Some(byte) = bytes.get_mut(4) {
+            *byte ^= 0xFF;
+        }
+
+        assert!(matches!(decode_from(&mut &bytes[..])?, Decoded::End));
+
+        Ok(())
+    }
+}
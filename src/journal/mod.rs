@@ -0,0 +1,763 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+pub mod entry;
+pub mod reader;
+
+use crate::{
+    config::Durability, range_tombstone::RangeTombstone, tree::inner::MemtableId, Encryptor,
+    FileHandle, Filesystem, InternalValue, SeqNo,
+};
+use entry::Entry;
+use reader::Reader;
+use std::{
+    collections::BTreeMap,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::Duration,
+};
+
+/// Unique journal file ID
+///
+/// Journal file IDs are monotonically increasing integers.
+pub type JournalFileId = u64;
+
+/// Metadata of a sealed (immutable) journal file
+struct SealedFile {
+    /// Highest sequence number in the journal file
+    max_seqno: Option<SeqNo>,
+
+    /// The sealed memtable holding the journal file's data
+    ///
+    /// Recovered files are replayed into the active memtable,
+    /// so they belong to the memtable that is sealed next.
+    memtable_id: Option<MemtableId>,
+
+    /// `true` if the data of the journal file has been persisted in tables
+    flushed: bool,
+}
+
+struct ActiveFile {
+    id: JournalFileId,
+    file: BufWriter<Box<dyn FileHandle>>,
+    max_seqno: Option<SeqNo>,
+
+    /// Number of bytes written to the journal (across all journal files) since it was opened
+    position: u64,
+}
+
+impl ActiveFile {
+    /// Writes the buffered data to the journal file, and fsyncs it.
+    fn sync(&mut self) -> crate::Result<()> {
+        self.file.flush()?;
+        self.file.get_ref().sync_all()?;
+        Ok(())
+    }
+}
+
+/// Progress of fsyncing the journal, see [`Journal::commit`]
+#[derive(Default)]
+struct SyncState {
+    /// Journal position up to which all writes are fsynced
+    synced: u64,
+
+    /// `true` while some writer (the leader) fsyncs the journal on behalf of all waiting writers
+    syncing: bool,
+}
+
+/// Append-only log of all writes of a tree
+///
+/// The journal is split into files; on every memtable rotation a new journal file is
+/// started. Sealed journal files are deleted once their data is flushed, and no
+/// change data capture reader needs them anymore.
+pub struct Journal {
+    fs: Arc<dyn Filesystem>,
+
+    encryptor: Option<Arc<dyn Encryptor>>,
+
+    durability: Durability,
+
+    folder: PathBuf,
+
+    active: Mutex<ActiveFile>,
+
+    sealed: Mutex<BTreeMap<JournalFileId, SealedFile>>,
+
+    sync_state: Mutex<SyncState>,
+
+    /// Signals writers waiting in [`Journal::commit`] that a sync is done
+    sync_signal: Condvar,
+
+    /// Acknowledged sequence numbers of CDC readers
+    cursors: Mutex<crate::HashMap<u64, Option<SeqNo>>>,
+
+    cursor_id_counter: AtomicU64,
+
+    /// Set once a write or fsync failed, after which all writes are refused,
+    /// because it is unknown which writes made it into the journal
+    poisoned: AtomicBool,
+}
+
+fn create_file(
+    fs: &dyn Filesystem,
+    folder: &Path,
+    id: JournalFileId,
+) -> crate::Result<BufWriter<Box<dyn FileHandle>>> {
+    let file = fs.create_new(&folder.join(id.to_string()))?;
+    fs.sync_directory(folder)?;
+    Ok(BufWriter::new(file))
+}
+
+impl Journal {
+    /// Creates a new, empty journal in the given folder.
+    pub fn create_new<P: AsRef<Path>>(
+        fs: Arc<dyn Filesystem>,
+        encryptor: Option<Arc<dyn Encryptor>>,
+        durability: Durability,
+        folder: P,
+    ) -> crate::Result<Self> {
+        let folder = folder.as_ref();
+
+        log::debug!("Creating journal at {}", folder.display());
+
+        fs.create_dir_all(folder)?;
+
+        Ok(Self {
+            active: Mutex::new(ActiveFile {
+                id: 0,
+                file: create_file(&*fs, folder, 0)?,
+                max_seqno: None,
+                position: 0,
+            }),
+            fs,
+            encryptor,
+            durability,
+            folder: folder.into(),
+            sealed: Mutex::default(),
+            sync_state: Mutex::default(),
+            sync_signal: Condvar::new(),
+            cursors: Mutex::default(),
+            cursor_id_counter: AtomicU64::default(),
+            poisoned: AtomicBool::default(),
+        })
+    }
+
+    /// Recovers a journal from the given folder.
+    ///
+    /// The given files are flushed (their data is persisted in tables), and are deleted;
+    /// all other files need to be replayed in full.
+    ///
+    /// Torn writes at the end of journal files are truncated.
+    /// If an unflushed file is corrupted before its end, all later files are renamed to
+    /// `<id>.corrupt` and not replayed, so the recovered writes are a prefix of all writes.
+    pub fn recover<P: AsRef<Path>>(
+        fs: Arc<dyn Filesystem>,
+        encryptor: Option<Arc<dyn Encryptor>>,
+        durability: Durability,
+        folder: P,
+        flushed_files: &[JournalFileId],
+    ) -> crate::Result<Self> {
+        let folder = folder.as_ref();
+
+        if !fs.exists(folder)? {
+            return Self::create_new(fs, encryptor, durability, folder);
+        }
+
+        log::debug!("Recovering journal at {}", folder.display());
+
+        let mut sealed = BTreeMap::new();
+        let mut corrupted_id = None;
+
+        let ids = list_files(&*fs, folder)?;
+
+        for &id in &ids {
+            let path = folder.join(id.to_string());
+
+            if let Some(corrupted_id) = corrupted_id {
+                log::error!(
+                    "Discarding journal file {} that was written after corrupted journal file {corrupted_id}",
+                    path.display(),
+                );
+
+                fs.rename(&path, &folder.join(format!("{id}.corrupt")))?;
+                continue;
+            }
+
+            let mut reader = Reader::new(fs.open(&path)?, 0, encryptor.clone())?;
+            let mut max_seqno = None;
+
+            while let Some(entry) = reader.next_entry()? {
+                max_seqno = max_seqno.max(Some(entry.seqno()));
+            }
+
+            let valid_len = reader.offset();
+
+            let file = fs.open_writable(&path)?;
+
+            if file.len()? > valid_len {
+                log::warn!(
+                    "Truncating journal file {} to {valid_len} bytes",
+                    path.display()
+                );
+
+                file.set_len(valid_len)?;
+                file.sync_all()?;
+
+                // NOTE: A flushed file is not replayed anyway
+                if !flushed_files.contains(&id) {
+                    corrupted_id = Some(id);
+                }
+            }
+
+            sealed.insert(
+                id,
+                SealedFile {
+                    max_seqno,
+                    memtable_id: None,
+                    flushed: max_seqno.is_none() || flushed_files.contains(&id),
+                },
+            );
+        }
+
+        // NOTE: Never append to recovered files, always start a new one,
+        // and never reuse the ID of a flushed or discarded file
+        let active_id = ids.iter().chain(flushed_files).max().map_or(0, |id| id + 1);
+
+        let journal = Self {
+            active: Mutex::new(ActiveFile {
+                id: active_id,
+                file: create_file(&*fs, folder, active_id)?,
+                max_seqno: None,
+                position: 0,
+            }),
+            fs,
+            encryptor,
+            durability,
+            folder: folder.into(),
+            sealed: Mutex::new(sealed),
+            sync_state: Mutex::default(),
+            sync_signal: Condvar::new(),
+            cursors: Mutex::default(),
+            cursor_id_counter: AtomicU64::default(),
+            poisoned: AtomicBool::default(),
+        };
+
+        journal.maintenance()?;
+
+        Ok(journal)
+    }
+
+    /// Returns the path of a journal file.
+    pub fn file_path(&self, id: JournalFileId) -> PathBuf {
+        self.folder.join(id.to_string())
+    }
+
+    /// Opens a reader over a journal file.
+    pub fn open_reader(&self, id: JournalFileId) -> crate::Result<Reader> {
+        Reader::new(
+            self.fs.open(&self.file_path(id))?,
+            0,
+            self.encryptor.clone(),
+        )
+    }
+
+    /// Returns the IDs of all journal files that currently exist, in ascending order.
+    pub fn file_ids(&self) -> Vec<JournalFileId> {
+        // NOTE: Lock active file first, so no file can be sealed in between
+        let active = self.active.lock().expect("lock is poisoned");
+        let sealed = self.sealed.lock().expect("lock is poisoned");
+
+        sealed
+            .keys()
+            .copied()
+            .chain(std::iter::once(active.id))
+            .collect()
+    }
+
+    /// Returns the size of all journal files.
+    pub fn disk_space(&self) -> crate::Result<u64> {
+        let mut size = 0;
+
+        for id in self.file_ids() {
+            size += self.fs.open(&self.file_path(id))?.len()?;
+        }
+
+        Ok(size)
+    }
+
+    /// Returns the ID of the active journal file.
+    pub fn active_file_id(&self) -> JournalFileId {
+        self.active.lock().expect("lock is poisoned").id
+    }
+
+    /// Appends an entry to the active journal file, and waits until it is as durable as configured.
+    pub fn append(&self, value: &InternalValue) -> crate::Result<()> {
+        let position = self.write(std::slice::from_ref(value))?;
+        self.commit(position)
+    }
+
+    /// Appends multiple entries to the active journal file, using a single write,
+    /// and waits until they are as durable as configured.
+    pub fn append_batch(&self, values: &[InternalValue]) -> crate::Result<()> {
+        let position = self.write(values)?;
+        self.commit(position)
+    }
+
+    /// Appends a range tombstone to the active journal file, and waits until it is as durable as configured.
+    pub fn append_range_tombstone(&self, rt: &RangeTombstone) -> crate::Result<()> {
+        let bytes = self.seal_entry(entry::encode_range_tombstone(rt))?;
+        let position = self.write_bytes(&bytes, Some(rt.seqno))?;
+        self.commit(position)
+    }
+
+    /// Appends entries to the active journal file, without waiting for them to be fsynced.
+    ///
+    /// Returns the journal position after the entries, which needs to be passed
+    /// to [`Journal::commit`] before the write is acknowledged.
+    pub fn write(&self, values: &[InternalValue]) -> crate::Result<u64> {
+        let mut bytes = Vec::new();
+
+        for value in values {
+            bytes.extend(self.seal_entry(entry::encode(value))?);
+        }
+
+        let max_seqno = values.iter().map(|value| value.key.seqno).max();
+
+        self.write_bytes(&bytes, max_seqno)
+    }
+
+    /// Encrypts an encoded entry, if the journal is encrypted.
+    fn seal_entry(&self, entry: Vec<u8>) -> crate::Result<Vec<u8>> {
+        match &self.encryptor {
+            Some(encryptor) => entry::encode_encrypted(&entry, &**encryptor),
+            None => Ok(entry),
+        }
+    }
+
+    fn write_bytes(&self, bytes: &[u8], max_seqno: Option<SeqNo>) -> crate::Result<u64> {
+        if self.is_poisoned() {
+            return Err(crate::Error::Poisoned);
+        }
+
+        self.poison_on_error(self.write_bytes_inner(bytes, max_seqno))
+    }
+
+    fn write_bytes_inner(&self, bytes: &[u8], max_seqno: Option<SeqNo>) -> crate::Result<u64> {
+        let mut active = self.active.lock().expect("lock is poisoned");
+        active.file.write_all(bytes)?;
+        active.max_seqno = active.max_seqno.max(max_seqno);
+        active.position += bytes.len() as u64;
+
+        // NOTE: With `SyncEveryWrite`, the buffer is written out by the writer that fsyncs
+        if matches!(
+            self.durability,
+            Durability::Buffer | Durability::SyncInterval(_)
+        ) {
+            active.file.flush()?;
+        }
+
+        let position = active.position;
+        drop(active);
+
+        Ok(position)
+    }
+
+    /// Waits until all writes up to the given journal position are as durable as configured.
+    ///
+    /// With [`Durability::SyncEveryWrite`], writers use group commit: one writer (the leader)
+    /// fsyncs the journal, which makes the writes of all other writers (the followers) that
+    /// were written until then durable as well, so they do not need their own fsync.
+    /// Writers that are not covered by the leader's fsync wait for it to finish,
+    /// and then elect a new leader among themselves.
+    pub fn commit(&self, position: u64) -> crate::Result<()> {
+        if self.durability != Durability::SyncEveryWrite {
+            return Ok(());
+        }
+
+        let mut state = self.sync_state.lock().expect("lock is poisoned");
+
+        loop {
+            if state.synced >= position {
+                return Ok(());
+            }
+
+            if !state.syncing {
+                break;
+            }
+
+            state = self.sync_signal.wait(state).expect("lock is poisoned");
+        }
+
+        state.syncing = true;
+        drop(state);
+
+        let result = self.sync_data();
+
+        let mut state = self.sync_state.lock().expect("lock is poisoned");
+        state.syncing = false;
+
+        if let Ok(synced) = result {
+            state.synced = state.synced.max(synced);
+        }
+
+        drop(state);
+        self.sync_signal.notify_all();
+
+        self.poison_on_error(result.map(|_| ()))
+    }
+
+    /// Returns `true` if a write or fsync failed, see [`crate::Error::Poisoned`].
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Acquire)
+    }
+
+    fn poison_on_error<T>(&self, result: crate::Result<T>) -> crate::Result<T> {
+        if let Err(e) = &result {
+            log::error!("Journal write failed, refusing further writes: {e:?}");
+            self.poisoned.store(true, Ordering::Release);
+        }
+        result
+    }
+
+    /// Writes out and fsyncs the active journal file, returning the journal position that is now durable.
+    fn sync_data(&self) -> crate::Result<u64> {
+        let (file, position) = {
+            let mut active = self.active.lock().expect("lock is poisoned");
+            active.file.flush()?;
+            (active.file.get_ref().try_clone()?, active.position)
+        };
+
+        // NOTE: Fsync without holding the lock, so other writers can keep appending
+        file.sync_data()?;
+
+        Ok(position)
+    }
+
+    /// Marks all writes up to the given journal position as fsynced, waking up waiting writers.
+    fn mark_synced(&self, position: u64) {
+        let mut state = self.sync_state.lock().expect("lock is poisoned");
+        state.synced = state.synced.max(position);
+        drop(state);
+
+        self.sync_signal.notify_all();
+    }
+
+    /// Seals the active journal file, which now belongs to the given sealed memtable,
+    /// and starts a new journal file.
+    pub fn rotate(&self, memtable_id: MemtableId) -> crate::Result<()> {
+        self.seal_active(Some(memtable_id))
+    }
+
+    /// Seals the active journal file and starts a new journal file.
+    ///
+    /// Used when journal files are not tied to a single memtable,
+    /// see [`Journal::mark_persisted`].
+    pub fn seal(&self) -> crate::Result<()> {
+        self.seal_active(None)
+    }
+
+    fn seal_active(&self, memtable_id: Option<MemtableId>) -> crate::Result<()> {
+        self.poison_on_error(self.seal_active_inner(memtable_id))
+    }
+
+    #[expect(clippy::significant_drop_tightening)]
+    fn seal_active_inner(&self, memtable_id: Option<MemtableId>) -> crate::Result<()> {
+        let mut active = self.active.lock().expect("lock is poisoned");
+
+        let next_id = active.id + 1;
+        let next_file = create_file(&*self.fs, &self.folder, next_id)?;
+
+        active.sync()?;
+
+        log::trace!(
+            "Sealing journal file {} for memtable {memtable_id:?}",
+            active.id
+        );
+
+        let mut sealed = self.sealed.lock().expect("lock is poisoned");
+
+        if memtable_id.is_some() {
+            for file in sealed
+                .values_mut()
+                .filter(|file| !file.flushed && file.memtable_id.is_none())
+            {
+                file.memtable_id = memtable_id;
+            }
+        }
+
+        sealed.insert(
+            active.id,
+            SealedFile {
+                max_seqno: active.max_seqno,
+                memtable_id,
+                flushed: false,
+            },
+        );
+        drop(sealed);
+
+        *active = ActiveFile {
+            id: next_id,
+            file: next_file,
+            max_seqno: None,
+            position: active.position,
+        };
+
+        // NOTE: The sealed journal file is fsynced, so its writes are durable
+        self.mark_synced(active.position);
+
+        Ok(())
+    }
+
+    /// Marks all sealed journal files that only contain data below `seqno` as flushed,
+    /// deleting them if possible.
+    pub fn mark_persisted(&self, seqno: SeqNo) -> crate::Result<()> {
+        for file in self.sealed.lock().expect("lock is poisoned").values_mut() {
+            if file.max_seqno.is_none_or(|max| max < seqno) {
+                file.flushed = true;
+            }
+        }
+
+        self.maintenance()
+    }
+
+    /// Returns the highest sequence number in the journal.
+    pub fn highest_seqno(&self) -> Option<SeqNo> {
+        let active = self.active.lock().expect("lock is poisoned");
+        let sealed = self.sealed.lock().expect("lock is poisoned");
+
+        sealed
+            .values()
+            .map(|file| file.max_seqno)
+            .chain(std::iter::once(active.max_seqno))
+            .max()
+            .flatten()
+    }
+
+    /// Flushes and fsyncs the active journal file.
+    pub fn sync(&self) -> crate::Result<()> {
+        let mut active = self.active.lock().expect("lock is poisoned");
+        self.poison_on_error(active.sync())?;
+
+        let position = active.position;
+        drop(active);
+
+        self.mark_synced(position);
+
+        Ok(())
+    }
+
+    /// Returns the IDs of all sealed journal files that are flushed once the given memtables are flushed.
+    ///
+    /// The IDs need to be persisted (see [`crate::version::Version::flushed_journal_files`])
+    /// before calling [`Journal::mark_flushed`], which may delete the files.
+    pub fn flushed_file_ids(&self, memtable_ids: &[MemtableId]) -> Vec<JournalFileId> {
+        self.sealed
+            .lock()
+            .expect("lock is poisoned")
+            .iter()
+            .filter(|(_, file)| {
+                file.flushed
+                    || file
+                        .memtable_id
+                        .is_some_and(|id| memtable_ids.contains(&id))
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Marks the journal files of the given (now flushed) memtables as flushed,
+    /// deleting them if possible.
+    pub fn mark_flushed(&self, memtable_ids: &[MemtableId]) -> crate::Result<()> {
+        {
+            let mut sealed = self.sealed.lock().expect("lock is poisoned");
+
+            for file in sealed.values_mut() {
+                if file
+                    .memtable_id
+                    .is_some_and(|id| memtable_ids.contains(&id))
+                {
+                    file.flushed = true;
+                }
+            }
+        }
+
+        self.maintenance()
+    }
+
+    /// Registers a CDC reader, returning its cursor ID.
+    pub fn register_cursor(&self, acked: Option<SeqNo>) -> u64 {
+        let id = self.cursor_id_counter.fetch_add(1, Ordering::Relaxed);
+
+        self.cursors
+            .lock()
+            .expect("lock is poisoned")
+            .insert(id, acked);
+
+        id
+    }
+
+    /// Acknowledges that a CDC reader has consumed all entries up to (and including) `seqno`.
+    pub fn ack(&self, cursor_id: u64, seqno: SeqNo) -> crate::Result<()> {
+        {
+            let mut cursors = self.cursors.lock().expect("lock is poisoned");
+
+            if let Some(acked) = cursors.get_mut(&cursor_id) {
+                *acked = (*acked).max(Some(seqno));
+            }
+        }
+
+        self.maintenance()
+    }
+
+    /// Unregisters a CDC reader.
+    pub fn unregister_cursor(&self, cursor_id: u64) {
+        self.cursors
+            .lock()
+            .expect("lock is poisoned")
+            .remove(&cursor_id);
+
+        if let Err(e) = self.maintenance() {
+            log::warn!("Journal maintenance failed: {e:?}");
+        }
+    }
+
+    /// Deletes sealed journal files that are not needed anymore.
+    #[expect(clippy::significant_drop_tightening)]
+    pub fn maintenance(&self) -> crate::Result<()> {
+        // NOTE: None = no cursor; Some(None) = some cursor has not acked anything yet
+        let min_acked = self
+            .cursors
+            .lock()
+            .expect("lock is poisoned")
+            .values()
+            .copied()
+            .min();
+
+        let mut sealed = self.sealed.lock().expect("lock is poisoned");
+
+        let deletable = sealed
+            .iter()
+            .filter(|(_, file)| {
+                file.flushed
+                    && match (min_acked, file.max_seqno) {
+                        (None, _) | (_, None) => true,
+                        (Some(acked), Some(max_seqno)) => acked.is_some_and(|a| a >= max_seqno),
+                    }
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        for id in deletable {
+            let path = self.file_path(id);
+            log::trace!("Deleting journal file {}", path.display());
+
+            self.fs.remove_file(&path)?;
+            sealed.remove(&id);
+        }
+
+        Ok(())
+    }
+}
+
+/// Spawns a thread that fsyncs a journal periodically, see [`Durability::SyncInterval`].
+///
+/// The thread only holds a weak reference to the journal (not its tree or keyspace,
+/// so it never keeps their folder locked), and stops once the journal is dropped.
+pub fn spawn_sync_thread(journal: &Arc<Journal>, interval: Duration) -> crate::Result<()> {
+    let journal = Arc::downgrade(journal);
+
+    std::thread::Builder::new()
+        .name("lsm-tree-journal-sync".into())
+        .spawn(move || loop {
+            std::thread::sleep(interval);
+
+            let Some(journal) = journal.upgrade() else {
+                return;
+            };
+
+            if let Err(e) = journal.sync() {
+                log::error!("Failed to sync journal: {e:?}");
+            }
+        })?;
+
+    Ok(())
+}
+
+/// Reads the entries of all journal files in the given folder that are not flushed,
+/// without modifying any file.
+///
+/// Used by secondary instances, which read the journal of a tree that is owned by another instance.
+/// Files that are deleted while reading (because their data was flushed) are skipped.
+pub fn read_unflushed(
+    fs: &dyn Filesystem,
+    encryptor: Option<&Arc<dyn Encryptor>>,
+    folder: &Path,
+    flushed_files: &[JournalFileId],
+    mut f: impl FnMut(Entry),
+) -> crate::Result<()> {
+    if !fs.exists(folder)? {
+        return Ok(());
+    }
+
+    for id in list_files(fs, folder)? {
+        if flushed_files.contains(&id) {
+            continue;
+        }
+
+        let file = match fs.open(&folder.join(id.to_string())) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut reader = Reader::new(file, 0, encryptor.cloned())?;
+
+        while let Some(entry) = reader.next_entry()? {
+            f(entry);
+        }
+    }
+
+    Ok(())
+}
+
+fn list_files(fs: &dyn Filesystem, folder: &Path) -> crate::Result<Vec<JournalFileId>> {
+    let mut ids = Vec::new();
+
+    for path in fs.read_dir(folder)? {
+        let file_name = path.file_name().unwrap_or_default();
+
+        // https://en.wikipedia.org/wiki/.DS_Store
+        if file_name == ".DS_Store" {
+            continue;
+        }
+
+        // https://en.wikipedia.org/wiki/AppleSingle_and_AppleDouble_formats
+        if file_name.to_string_lossy().starts_with("._") {
+            continue;
+        }
+
+        // NOTE: Discarded by an earlier recovery, see `Journal::recover`
+        if file_name.to_string_lossy().ends_with(".corrupt") {
+            continue;
+        }
+
+        let Some(id) = file_name
+            .to_str()
+            .and_then(|name| name.parse::<JournalFileId>().ok())
+        else {
+            log::warn!("Unknown file in journal folder: {}", path.display());
+            continue;
+        };
+
+        ids.push(id);
+    }
+
+    ids.sort_unstable();
+
+    Ok(ids)
+}
@@ -0,0 +1,66 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::entry::{decode_encrypted_from, decode_from, Decoded, Entry};
+use crate::{Encryptor, FileHandle};
+use std::{
+    io::{BufReader, Seek, SeekFrom},
+    sync::Arc,
+};
+
+/// Reads entries of a journal file
+pub struct Reader {
+    inner: BufReader<Box<dyn FileHandle>>,
+
+    /// Offset after the last valid entry
+    offset: u64,
+
+    encryptor: Option<Arc<dyn Encryptor>>,
+}
+
+impl Reader {
+    /// Reads a journal file, starting at the given offset.
+    pub fn new(
+        file: Box<dyn FileHandle>,
+        offset: u64,
+        encryptor: Option<Arc<dyn Encryptor>>,
+    ) -> crate::Result<Self> {
+        let mut inner = BufReader::new(file);
+        inner.seek(SeekFrom::Start(offset))?;
+
+        Ok(Self {
+            inner,
+            offset,
+            encryptor,
+        })
+    }
+
+    /// Returns the offset after the last valid entry.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Reads the next entry.
+    ///
+    /// Returns `None` at the end of valid data. Because the journal file may
+    /// still be appended to, reading can be retried later.
+    pub fn next_entry(&mut self) -> crate::Result<Option<Entry>> {
+        let decoded = match &self.encryptor {
+            Some(encryptor) => decode_encrypted_from(&mut self.inner, &**encryptor)?,
+            None => decode_from(&mut self.inner)?,
+        };
+
+        match decoded {
+            Decoded::Entry(item, size) => {
+                self.offset += size;
+                Ok(Some(item))
+            }
+            Decoded::End => {
+                // NOTE: Rewind, so a partially read entry can be read again later
+                self.inner.seek(SeekFrom::Start(self.offset))?;
+                Ok(None)
+            }
+        }
+    }
+}
@@ -43,6 +43,8 @@ impl std::fmt::Debug for InternalKey {
                 ValueType::Tombstone => "T",
                 ValueType::WeakTombstone => "W",
                 ValueType::Indirection => "Vb",
+                ValueType::MergeOperand => "M",
+                ValueType::ExpiringValue => "X",
             },
         )
     }
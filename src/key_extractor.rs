@@ -0,0 +1,75 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Derives a (usually shorter) key from user keys, like a prefix
+///
+/// If set, every table builds an additional filter over the derived keys of its items,
+/// which is always kept in memory, and every memtable keeps a set of derived keys.
+/// This makes "does any key with this derived key exist" queries
+/// (see [`AbstractTree::derived_key_may_exist`](crate::AbstractTree::derived_key_may_exist))
+/// pure memory probes that never touch the disk.
+///
+/// The filter is probabilistic, so it may report false positives, but never false negatives.
+///
+/// # Naming
+///
+/// The name is stored in every table, and the table's filter is only used if the
+/// name matches the currently configured extractor. So the name needs to change whenever
+/// the extraction logic changes, otherwise queries may return false negatives.
+pub trait KeyExtractor: std::fmt::Debug + Send + Sync + 'static {
+    /// Returns the name of the extractor.
+    fn name(&self) -> &str;
+
+    /// Returns the derived key of a user key.
+    ///
+    /// Returns `None` if the key is not in the extractor's domain,
+    /// in which case it is not added to any filter.
+    fn extract<'a>(&self, key: &'a [u8]) -> Option<&'a [u8]>;
+}
+
+/// [`KeyExtractor`] which uses a fixed-length prefix of the key
+///
+/// Keys that are shorter than the prefix length are not in the extractor's domain.
+#[derive(Debug)]
+pub struct FixedPrefixExtractor {
+    len: usize,
+    name: String,
+}
+
+impl FixedPrefixExtractor {
+    /// Creates an extractor that uses the first `len` bytes of every key.
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            name: format!("fixed_prefix#{len}"),
+        }
+    }
+}
+
+impl KeyExtractor for FixedPrefixExtractor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn extract<'a>(&self, key: &'a [u8]) -> Option<&'a [u8]> {
+        key.get(..self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn fixed_prefix_extractor() {
+        let extractor = FixedPrefixExtractor::new(3);
+
+        assert_eq!("fixed_prefix#3", extractor.name());
+        assert_eq!(Some(&b"abc"[..]), extractor.extract(b"abcdef"));
+        assert_eq!(Some(&b"abc"[..]), extractor.extract(b"abc"));
+        assert_eq!(None, extractor.extract(b"ab"));
+    }
+}
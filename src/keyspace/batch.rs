@@ -0,0 +1,332 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{partition::PartitionInner, Keyspace, Partition};
+use crate::{AbstractTree, InternalValue, SeqNo, Slice, UserKey, UserValue, ValueType};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use std::{
+    io::{Cursor, Read, Write},
+    sync::Arc,
+};
+use varint_rs::{VarintReader, VarintWriter};
+
+/// Key of journal entries that hold a batch
+///
+/// The journal does not allow empty keys; the operations are stored in the value.
+const RECORD_KEY: &[u8] = b"batch";
+
+/// A single operation of a batch
+pub struct BatchOp<P> {
+    pub partition: P,
+    pub value_type: ValueType,
+    pub key: UserKey,
+    pub value: UserValue,
+}
+
+/// An atomic write batch across multiple partitions of a keyspace
+///
+/// All operations are written to the journal as a single record, and share a single sequence number,
+/// so they either become visible (and durable) all together or not at all.
+///
+/// Savepoints can be used to undo the operations that were added after them,
+/// without having to rebuild the batch.
+pub struct WriteBatch {
+    keyspace: Keyspace,
+    ops: Vec<BatchOp<Arc<PartitionInner>>>,
+
+    /// Operation counts at the time each savepoint was set
+    savepoints: Vec<usize>,
+}
+
+impl WriteBatch {
+    pub(crate) fn new(keyspace: Keyspace) -> Self {
+        Self {
+            keyspace,
+            ops: Vec::new(),
+            savepoints: Vec::new(),
+        }
+    }
+
+    fn push(
+        &mut self,
+        partition: &Partition,
+        value_type: ValueType,
+        key: UserKey,
+        value: UserValue,
+    ) {
+        assert!(
+            Arc::ptr_eq(&partition.keyspace.0, &self.keyspace.0),
+            "partition belongs to another keyspace",
+        );
+
+        self.ops.push(BatchOp {
+            partition: partition.inner.clone(),
+            value_type,
+            key,
+            value,
+        });
+    }
+
+    /// Inserts a key-value pair into a partition.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the partition belongs to another keyspace.
+    pub fn insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &mut self,
+        partition: &Partition,
+        key: K,
+        value: V,
+    ) {
+        self.push(partition, ValueType::Value, key.into(), value.into());
+    }
+
+    /// Removes a key from a partition.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the partition belongs to another keyspace.
+    pub fn remove<K: Into<UserKey>>(&mut self, partition: &Partition, key: K) {
+        self.push(
+            partition,
+            ValueType::Tombstone,
+            key.into(),
+            UserValue::empty(),
+        );
+    }
+
+    /// Adds a merge operand to a partition, see [`crate::AbstractTree::add_merge`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the partition belongs to another keyspace.
+    pub fn add_merge<K: Into<UserKey>, V: Into<UserValue>>(
+        &mut self,
+        partition: &Partition,
+        key: K,
+        operand: V,
+    ) {
+        self.push(
+            partition,
+            ValueType::MergeOperand,
+            key.into(),
+            operand.into(),
+        );
+    }
+
+    /// Returns the number of operations in the batch.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if the batch contains no operations.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Returns the approximate size of the batch's keys and values in bytes.
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.ops
+            .iter()
+            .map(|op| (op.key.len() + op.value.len()) as u64)
+            .sum()
+    }
+
+    /// Sets a savepoint, see [`WriteBatch::rollback_to_savepoint`].
+    ///
+    /// Savepoints can be nested.
+    pub fn set_savepoint(&mut self) {
+        self.savepoints.push(self.ops.len());
+    }
+
+    /// Removes all operations that were added after the most recent savepoint,
+    /// and removes the savepoint.
+    ///
+    /// Returns `false` if there is no savepoint, in which case the batch is unchanged.
+    pub fn rollback_to_savepoint(&mut self) -> bool {
+        let Some(len) = self.savepoints.pop() else {
+            return false;
+        };
+
+        self.ops.truncate(len);
+
+        true
+    }
+
+    /// Removes the most recent savepoint, keeping all operations.
+    ///
+    /// Returns `false` if there is no savepoint.
+    pub fn pop_savepoint(&mut self) -> bool {
+        self.savepoints.pop().is_some()
+    }
+
+    /// Returns the number of savepoints.
+    #[must_use]
+    pub fn savepoint_count(&self) -> usize {
+        self.savepoints.len()
+    }
+
+    /// Atomically applies the batch.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs while writing the journal,
+    /// in which case none of the operations are applied, or while syncing the journal,
+    /// in which case the operations are applied, but may not be durable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn commit(self) -> crate::Result<()> {
+        if self.ops.is_empty() {
+            return Ok(());
+        }
+
+        let shared = &self.keyspace.0.shared;
+
+        let (seqno, position) = {
+            let _lock = shared.write_lock.lock().expect("lock is poisoned");
+
+            let seqno = shared.config.seqno.next();
+
+            let position = shared.journal.write(&[InternalValue::from_components(
+                RECORD_KEY,
+                encode(&self.ops),
+                seqno,
+                ValueType::Value,
+            )])?;
+
+            for op in &self.ops {
+                apply(&op.partition.tree, op, seqno);
+            }
+
+            (seqno, position)
+        };
+
+        // NOTE: Wait for the journal outside of the write lock, so concurrent batches
+        // can be written in the meantime, and share a single fsync (group commit)
+        shared.journal.commit(position)?;
+
+        // NOTE: Publishing a later batch first is fine, because the journal is synced
+        // in order, so all earlier batches are applied and durable as well
+        shared.publish(seqno + 1);
+
+        for op in &self.ops {
+            shared.maybe_request_flush(&op.partition);
+        }
+
+        Ok(())
+    }
+}
+
+/// Applies a batch operation to a tree.
+pub fn apply<P>(tree: &crate::AnyTree, op: &BatchOp<P>, seqno: SeqNo) {
+    match op.value_type {
+        ValueType::Value => {
+            tree.insert(op.key.clone(), op.value.clone(), seqno);
+        }
+        ValueType::Tombstone => {
+            tree.remove(op.key.clone(), seqno);
+        }
+        ValueType::WeakTombstone => {
+            tree.remove_weak(op.key.clone(), seqno);
+        }
+        ValueType::MergeOperand => {
+            tree.add_merge(op.key.clone(), op.value.clone(), seqno);
+        }
+        ValueType::Indirection => unreachable!("batches never contain indirections"),
+        ValueType::ExpiringValue => unreachable!("batches never contain expiring values"),
+    }
+}
+
+/// Encodes the operations of a batch into a journal record payload
+///
+/// [op count; varint] [[partition name len; 1 byte] [partition name] [type; 1 byte] [key len; varint] [key] [value len; varint] [value]]...
+fn encode(ops: &[BatchOp<Arc<PartitionInner>>]) -> Vec<u8> {
+    let mut buf = Vec::new();
+
+    // NOTE: Writing into a Vec cannot fail
+    let _ = encode_into(&mut buf, ops);
+
+    buf
+}
+
+fn encode_into<W: Write>(
+    writer: &mut W,
+    ops: &[BatchOp<Arc<PartitionInner>>],
+) -> std::io::Result<()> {
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "batches are u32 length max"
+    )]
+    writer.write_u32_varint(ops.len() as u32)?;
+
+    for op in ops {
+        let name = op.partition.name.as_bytes();
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "partition names are u8 length max"
+        )]
+        writer.write_u8(name.len() as u8)?;
+        writer.write_all(name)?;
+
+        writer.write_u8(u8::from(op.value_type))?;
+
+        #[expect(clippy::cast_possible_truncation, reason = "keys are u16 length max")]
+        writer.write_u16_varint(op.key.len() as u16)?;
+        writer.write_all(&op.key)?;
+
+        #[expect(clippy::cast_possible_truncation, reason = "values are u32 length max")]
+        writer.write_u32_varint(op.value.len() as u32)?;
+        writer.write_all(&op.value)?;
+    }
+
+    Ok(())
+}
+
+/// Decodes the operations of a journal record payload.
+pub fn decode(bytes: &[u8]) -> crate::Result<Vec<BatchOp<Slice>>> {
+    let mut reader = Cursor::new(bytes);
+
+    let count = reader.read_u32_varint()?;
+    let mut ops = Vec::new();
+
+    for _ in 0..count {
+        let name_len = reader.read_u8()?;
+        let partition = read_bytes(&mut reader, name_len.into())?;
+
+        let tag = reader.read_u8()?;
+        let value_type = match ValueType::try_from(tag) {
+            Ok(ValueType::Indirection) | Err(()) => {
+                return Err(crate::Error::InvalidTag(("ValueType", tag)));
+            }
+            Ok(value_type) => value_type,
+        };
+
+        let key_len = reader.read_u16_varint()?;
+        let key = read_bytes(&mut reader, key_len.into())?;
+
+        let value_len = reader.read_u32_varint()?;
+        let value = read_bytes(&mut reader, value_len as usize)?;
+
+        ops.push(BatchOp {
+            partition,
+            value_type,
+            key,
+            value,
+        });
+    }
+
+    Ok(ops)
+}
+
+fn read_bytes<R: Read>(reader: &mut R, len: usize) -> std::io::Result<Slice> {
+    let mut buf = vec![0; len];
+    reader.read_exact(&mut buf)?;
+    Ok(buf.into())
+}
@@ -0,0 +1,477 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Multiple named trees that share one journal, cache and background workers.
+//!
+//! A [`Keyspace`] hosts [`Partition`]s, which are trees stored in `<path>/partitions/<name>`.
+//! All writes go through a single journal in `<path>/journal`, so a [`WriteBatch`]
+//! can atomically write to multiple partitions.
+//!
+//! Memtables that grow past [`Config::max_memtable_size`] are flushed by background
+//! flush workers, which then hand the partition to the compaction workers
+//! (see [`Config::flush_workers`] and [`Config::compaction_workers`]).
+//! Journal files are deleted once all partitions have flushed their data.
+//!
+//! ```
+//! # use lsm_tree::{Config, SequenceNumberCounter};
+//! # use lsm_tree::keyspace::Keyspace;
+//! #
+//! # let folder = tempfile::tempdir()?;
+//! let keyspace = Keyspace::open(Config::new(folder, SequenceNumberCounter::default()))?;
+//!
+//! let items = keyspace.open_partition("items")?;
+//! let counts = keyspace.open_partition("counts")?;
+//!
+//! let mut batch = keyspace.batch();
+//! batch.insert(&items, "a", "hello");
+//! batch.insert(&counts, "items", 1u64.to_be_bytes());
+//! batch.commit()?;
+//!
+//! assert_eq!(Some("hello".as_bytes().into()), items.get("a")?);
+//! assert!(counts.contains_key("items")?);
+//! #
+//! # Ok::<(), lsm_tree::Error>(())
+//! ```
+
+mod batch;
+mod partition;
+mod worker;
+
+pub use batch::WriteBatch;
+pub use partition::Partition;
+
+use crate::{
+    file::{JOURNAL_FOLDER, PARTITIONS_FOLDER},
+    journal::{entry::Entry as JournalEntry, Journal},
+    AbstractTree, Config, Durability, FileHandle, SeqNo,
+};
+use partition::PartitionInner;
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+use worker::WorkQueue;
+
+/// State shared between the keyspace handles and the background workers
+pub(crate) struct Shared {
+    /// Template config for partitions
+    config: Config,
+
+    journal: Arc<Journal>,
+
+    /// Serializes writes, so journal order matches sequence number order
+    write_lock: Mutex<()>,
+
+    /// Sequence number that readers use, so they never observe partially applied batches
+    visible_seqno: AtomicU64,
+
+    /// All partitions; `None` for partitions that exist on disk, but have not been opened yet
+    partitions: RwLock<BTreeMap<String, Option<Arc<PartitionInner>>>>,
+
+    flush_queue: WorkQueue,
+    compaction_queue: WorkQueue,
+
+    stop: AtomicBool,
+
+    #[expect(
+        dead_code,
+        reason = "the keyspace folder is locked until the file is closed"
+    )]
+    lock_file: Box<dyn FileHandle>,
+}
+
+impl Shared {
+    fn instant(&self) -> SeqNo {
+        self.visible_seqno.load(Ordering::Acquire)
+    }
+
+    fn publish(&self, seqno: SeqNo) {
+        self.visible_seqno.fetch_max(seqno, Ordering::AcqRel);
+    }
+
+    fn maybe_request_flush(&self, partition: &Arc<PartitionInner>) {
+        let max_size = partition.tree.tree_config().max_memtable_size;
+
+        if partition.tree.active_memtable_size() >= max_size
+            && !partition.flush_requested.swap(true, Ordering::AcqRel)
+        {
+            self.flush_queue.push(partition.clone());
+        }
+    }
+
+    fn request_compaction(&self, partition: &Arc<PartitionInner>) {
+        if !partition.compaction_requested.swap(true, Ordering::AcqRel) {
+            self.compaction_queue.push(partition.clone());
+        }
+    }
+
+    /// Deletes journal files whose data has been flushed by all partitions.
+    fn journal_maintenance(&self) -> crate::Result<()> {
+        let _lock = self.write_lock.lock().expect("lock is poisoned");
+
+        let partitions = self.partitions.read().expect("lock is poisoned");
+
+        // NOTE: Every write below this sequence number is in a table
+        let mut bound = self.config.seqno.get();
+
+        for partition in partitions.values() {
+            // NOTE: A partition that is not opened may still need its journal data
+            let Some(partition) = partition else {
+                return Ok(());
+            };
+
+            let tree = &partition.tree;
+
+            // NOTE: Sealed memtables are flushed right now, try again after their flush
+            if tree.sealed_memtable_count() > 0 {
+                return Ok(());
+            }
+
+            if tree.active_memtable_size() > 0 {
+                let persisted_below = tree.get_highest_persisted_seqno().map_or(0, |s| s + 1);
+                bound = bound.min(persisted_below);
+            }
+        }
+
+        drop(partitions);
+
+        self.journal.mark_persisted(bound)
+    }
+}
+
+pub(crate) struct KeyspaceInner {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Drop for KeyspaceInner {
+    fn drop(&mut self) {
+        log::trace!("Stopping keyspace workers");
+
+        self.shared.stop.store(true, Ordering::Release);
+        self.shared.flush_queue.wake_all();
+        self.shared.compaction_queue.wake_all();
+
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                log::error!("Keyspace worker panicked");
+            }
+        }
+    }
+}
+
+/// A collection of named trees (partitions) that share one journal, cache and background workers
+///
+/// Keyspaces are cheap to clone handles. The background workers stop once the last
+/// keyspace handle (or [`Partition`]) is dropped.
+#[derive(Clone)]
+pub struct Keyspace(pub(crate) Arc<KeyspaceInner>);
+
+impl Keyspace {
+    /// Opens a keyspace in the folder of the given config.
+    ///
+    /// The config is used as template for all partitions, and its cache, descriptor table
+    /// and sequence number generator are shared by all partitions.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, [`crate::Error::AlreadyLocked`]
+    /// if the keyspace is already opened, or [`crate::Error::InvalidConfig`]
+    /// if the config is invalid or ephemeral, or when compiled for wasm.
+    pub fn open(mut config: Config) -> crate::Result<Self> {
+        config.validate()?;
+
+        // NOTE: Flushes and compactions of a keyspace always run in worker threads
+        if cfg!(target_family = "wasm") {
+            return Err(crate::Error::InvalidConfig(
+                "keyspaces are not supported on wasm",
+            ));
+        }
+
+        if config.ephemeral {
+            return Err(crate::Error::InvalidConfig(
+                "keyspaces can not be ephemeral",
+            ));
+        }
+
+        if config.temporary && config.temporary_folder.is_none() {
+            let folder = tempfile::Builder::new().prefix("lsm-tree-").tempdir()?;
+            config.path = crate::path::absolute_path(folder.path());
+            config.temporary_folder = Some(Arc::new(folder));
+        }
+
+        log::debug!("Opening keyspace at {}", config.path.display());
+
+        config.fs.create_dir_all(&config.path)?;
+
+        // IMPORTANT: Lock the folder before touching any other file
+        let lock_file = crate::fs::lock_directory(&*config.fs, &config.path)?;
+
+        let partitions_folder = config.path.join(PARTITIONS_FOLDER);
+        config.fs.create_dir_all(&partitions_folder)?;
+
+        let journal = Journal::recover(
+            config.fs.clone(),
+            config.encryptor.clone(),
+            config.durability,
+            config.path.join(JOURNAL_FOLDER),
+            &[],
+        )?;
+
+        if let Some(seqno) = journal.highest_seqno() {
+            config.seqno.fetch_max(seqno + 1);
+        }
+
+        let mut partitions = BTreeMap::new();
+
+        for path in config.fs.read_dir(&partitions_folder)? {
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| is_valid_partition_name(name))
+            else {
+                log::warn!("Unknown file in partitions folder: {}", path.display());
+                continue;
+            };
+
+            partitions.insert(name.to_owned(), None);
+        }
+
+        let shared = Arc::new(Shared {
+            visible_seqno: AtomicU64::new(config.seqno.get()),
+            config,
+            journal: Arc::new(journal),
+            write_lock: Mutex::default(),
+            partitions: RwLock::new(partitions),
+            flush_queue: WorkQueue::default(),
+            compaction_queue: WorkQueue::default(),
+            stop: AtomicBool::default(),
+            lock_file,
+        });
+
+        // NOTE: If spawning fails, dropping the keyspace stops the workers that were spawned
+        let mut inner = KeyspaceInner {
+            shared,
+            workers: Vec::new(),
+        };
+
+        inner.workers = worker::spawn(
+            &inner.shared,
+            "flush",
+            inner.shared.config.flush_workers,
+            |shared| &shared.flush_queue,
+            worker::flush,
+        )?;
+
+        let compaction_workers = worker::spawn(
+            &inner.shared,
+            "compaction",
+            inner.shared.config.compaction_workers,
+            |shared| &shared.compaction_queue,
+            worker::compact,
+        )?;
+        inner.workers.extend(compaction_workers);
+
+        if let Durability::SyncInterval(ms) = inner.shared.config.durability {
+            crate::journal::spawn_sync_thread(&inner.shared.journal, Duration::from_millis(ms))?;
+        }
+
+        Ok(Self(Arc::new(inner)))
+    }
+
+    /// Returns the keyspace folder.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.0.shared.config.path
+    }
+
+    /// Returns the sequence number that all committed writes are visible at.
+    #[must_use]
+    pub fn instant(&self) -> SeqNo {
+        self.0.shared.instant()
+    }
+
+    /// Returns the names of all partitions, including the ones that have not been opened yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn partition_names(&self) -> Vec<String> {
+        self.0
+            .shared
+            .partitions
+            .read()
+            .expect("lock is poisoned")
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Creates a new, empty write batch.
+    #[must_use]
+    pub fn batch(&self) -> WriteBatch {
+        WriteBatch::new(self.clone())
+    }
+
+    /// Flushes and fsyncs the journal, making all committed writes durable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn persist(&self) -> crate::Result<()> {
+        self.0.shared.journal.sync()
+    }
+
+    /// Opens (or creates) a partition, using the keyspace config.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or [`crate::Error::InvalidConfig`]
+    /// if the name is invalid.
+    pub fn open_partition(&self, name: &str) -> crate::Result<Partition> {
+        self.open_partition_with(name, |config| config)
+    }
+
+    /// Opens (or creates) a partition, using the keyspace config changed by `f`.
+    ///
+    /// Partition names consist of 1 to 255 ASCII alphanumeric characters, `_`, `-` or `#`.
+    ///
+    /// The partition always uses the folder, journal, cache, descriptor table and
+    /// sequence number generator of the keyspace.
+    /// If the partition is already opened, `f` is ignored and the opened partition is returned.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or [`crate::Error::InvalidConfig`]
+    /// if the name or config is invalid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn open_partition_with<F: FnOnce(Config) -> Config>(
+        &self,
+        name: &str,
+        f: F,
+    ) -> crate::Result<Partition> {
+        let shared = &self.0.shared;
+
+        if !is_valid_partition_name(name) {
+            return Err(crate::Error::InvalidConfig("invalid partition name"));
+        }
+
+        let inner = {
+            let _lock = shared.write_lock.lock().expect("lock is poisoned");
+
+            if let Some(Some(inner)) = shared
+                .partitions
+                .read()
+                .expect("lock is poisoned")
+                .get(name)
+            {
+                return Ok(Partition {
+                    inner: inner.clone(),
+                    keyspace: self.clone(),
+                });
+            }
+
+            let template = &shared.config;
+
+            let mut config = f(template.clone());
+            config.path = template.path.join(PARTITIONS_FOLDER).join(name);
+            config.cache = template.cache.clone();
+            config.descriptor_table = template.descriptor_table.clone();
+            config.fs = template.fs.clone();
+            config.seqno = template.seqno.clone();
+            config.journal = false;
+            config.ephemeral = false;
+            config.temporary = false;
+            config.background_threads = 0;
+
+            log::debug!("Opening partition {name:?}");
+
+            let tree = config.open()?;
+
+            self.replay(name, &tree)?;
+
+            if let Some(seqno) = tree.get_highest_seqno() {
+                template.seqno.fetch_max(seqno + 1);
+            }
+            shared.publish(template.seqno.get());
+
+            let inner = Arc::new(PartitionInner {
+                name: name.to_owned(),
+                tree,
+                flush_requested: AtomicBool::default(),
+                compaction_requested: AtomicBool::default(),
+            });
+
+            shared
+                .partitions
+                .write()
+                .expect("lock is poisoned")
+                .insert(name.to_owned(), Some(inner.clone()));
+
+            inner
+        };
+
+        shared.maybe_request_flush(&inner);
+        shared.journal_maintenance()?;
+
+        Ok(Partition {
+            inner,
+            keyspace: self.clone(),
+        })
+    }
+
+    /// Applies all journaled writes of the partition that have not been flushed yet.
+    fn replay(&self, name: &str, tree: &crate::AnyTree) -> crate::Result<()> {
+        let journal = &self.0.shared.journal;
+        let persisted_seqno = tree.get_highest_persisted_seqno();
+
+        let mut count = 0;
+
+        for id in journal.file_ids() {
+            let mut reader = journal.open_reader(id)?;
+
+            while let Some(entry) = reader.next_entry()? {
+                // NOTE: The keyspace only journals batches
+                let JournalEntry::Value(entry) = entry else {
+                    continue;
+                };
+
+                let seqno = entry.key.seqno;
+
+                if persisted_seqno.is_some_and(|persisted| seqno <= persisted) {
+                    continue;
+                }
+
+                for op in batch::decode(&entry.value)? {
+                    if &*op.partition == name.as_bytes() {
+                        batch::apply(tree, &op, seqno);
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        log::debug!("Replayed {count} journaled writes into partition {name:?}");
+
+        Ok(())
+    }
+}
+
+fn is_valid_partition_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 255
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || b == b'#')
+}
@@ -0,0 +1,114 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{Keyspace, WriteBatch};
+use crate::{AbstractTree, AnyTree, UserKey, UserValue};
+use std::sync::{atomic::AtomicBool, Arc};
+
+pub struct PartitionInner {
+    /// Partition name, which is also its folder name
+    pub name: String,
+
+    pub tree: AnyTree,
+
+    /// If `true`, a flush is already queued
+    pub flush_requested: AtomicBool,
+
+    /// If `true`, a compaction is already queued
+    pub compaction_requested: AtomicBool,
+}
+
+/// A named tree inside a [`Keyspace`]
+///
+/// Partitions share the journal, cache and background workers of their keyspace.
+/// Writes are journaled by the keyspace and become visible at [`Keyspace::instant`].
+///
+/// Partitions are cheap to clone handles, and keep the keyspace alive.
+#[derive(Clone)]
+pub struct Partition {
+    pub(crate) inner: Arc<PartitionInner>,
+    pub(crate) keyspace: Keyspace,
+}
+
+impl std::fmt::Debug for Partition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Partition({:?})", self.inner.name)
+    }
+}
+
+impl Partition {
+    /// Returns the partition name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Returns the underlying tree.
+    ///
+    /// Writes should go through the partition (or a [`WriteBatch`]),
+    /// otherwise they are not journaled.
+    #[must_use]
+    pub fn tree(&self) -> &AnyTree {
+        &self.inner.tree
+    }
+
+    /// Inserts a key-value pair.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+    ) -> crate::Result<()> {
+        let mut batch = WriteBatch::new(self.keyspace.clone());
+        batch.insert(self, key, value);
+        batch.commit()
+    }
+
+    /// Removes a key.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn remove<K: Into<UserKey>>(&self, key: K) -> crate::Result<()> {
+        let mut batch = WriteBatch::new(self.keyspace.clone());
+        batch.remove(self, key);
+        batch.commit()
+    }
+
+    /// Adds a merge operand, see [`AbstractTree::add_merge`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn add_merge<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        operand: V,
+    ) -> crate::Result<()> {
+        let mut batch = WriteBatch::new(self.keyspace.clone());
+        batch.add_merge(self, key, operand);
+        batch.commit()
+    }
+
+    /// Retrieves the latest visible value of a key.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> crate::Result<Option<UserValue>> {
+        self.inner.tree.get(key, self.keyspace.instant())
+    }
+
+    /// Returns `true` if the key is visible in the partition.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn contains_key<K: AsRef<[u8]>>(&self, key: K) -> crate::Result<bool> {
+        self.inner.tree.contains_key(key, self.keyspace.instant())
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{partition::PartitionInner, Shared};
+use crate::AbstractTree;
+use std::{
+    collections::VecDeque,
+    sync::{atomic::Ordering, Arc, Condvar, Mutex},
+    thread::JoinHandle,
+};
+
+/// Queue of partitions that need background work
+#[derive(Default)]
+pub struct WorkQueue {
+    jobs: Mutex<VecDeque<Arc<PartitionInner>>>,
+    signal: Condvar,
+}
+
+impl WorkQueue {
+    pub fn push(&self, partition: Arc<PartitionInner>) {
+        self.jobs
+            .lock()
+            .expect("lock is poisoned")
+            .push_back(partition);
+
+        self.signal.notify_one();
+    }
+
+    /// Wakes up all workers, so they can observe the stop flag.
+    pub fn wake_all(&self) {
+        let _jobs = self.jobs.lock().expect("lock is poisoned");
+        self.signal.notify_all();
+    }
+
+    /// Blocks until there is a job, or returns `None` if the keyspace is stopping.
+    fn pop(&self, shared: &Shared) -> Option<Arc<PartitionInner>> {
+        let mut jobs = self.jobs.lock().expect("lock is poisoned");
+
+        loop {
+            if shared.stop.load(Ordering::Acquire) {
+                return None;
+            }
+
+            if let Some(partition) = jobs.pop_front() {
+                return Some(partition);
+            }
+
+            jobs = self.signal.wait(jobs).expect("lock is poisoned");
+        }
+    }
+}
+
+/// Spawns `count` threads that run `job` for every partition pushed to the queue.
+pub fn spawn(
+    shared: &Arc<Shared>,
+    name: &'static str,
+    count: usize,
+    queue: fn(&Shared) -> &WorkQueue,
+    job: fn(&Shared, &Arc<PartitionInner>) -> crate::Result<()>,
+) -> crate::Result<Vec<JoinHandle<()>>> {
+    (0..count)
+        .map(|idx| {
+            let shared = shared.clone();
+
+            std::thread::Builder::new()
+                .name(format!("lsm-tree-{name}-{idx}"))
+                .spawn(move || {
+                    while let Some(partition) = queue(&shared).pop(&shared) {
+                        if let Err(e) = job(&shared, &partition) {
+                            log::error!(
+                                "Background {name} of partition {:?} failed: {e:?}",
+                                partition.name,
+                            );
+                        }
+                    }
+                })
+                .map_err(Into::into)
+        })
+        .collect()
+}
+
+/// Flushes the active memtable of a partition, and releases journal files that are not needed anymore.
+pub fn flush(shared: &Shared, partition: &Arc<PartitionInner>) -> crate::Result<()> {
+    partition.flush_requested.store(false, Ordering::Release);
+
+    let tree = &partition.tree;
+
+    // IMPORTANT: Rotate under the write lock, so no batch is split across memtables
+    let rotated = {
+        let _lock = shared.write_lock.lock().expect("lock is poisoned");
+
+        let rotated = tree.rotate_memtable();
+
+        // NOTE: Seal the journal file, so it can be deleted once all partitions have flushed its data
+        if rotated.is_some() {
+            shared.journal.seal()?;
+        }
+
+        rotated
+    };
+
+    if let Some((table_id, memtable)) = rotated {
+        if let Some((table, blob_file)) = tree.flush_memtable(table_id, &memtable, 0)? {
+            tree.register_tables(
+                std::slice::from_ref(&table),
+                blob_file.as_ref().map(std::slice::from_ref),
+                None,
+            )?;
+        }
+
+        shared.request_compaction(partition);
+    }
+
+    shared.journal_maintenance()
+}
+
+/// Runs the compaction strategy of a partition.
+pub fn compact(shared: &Shared, partition: &Arc<PartitionInner>) -> crate::Result<()> {
+    partition
+        .compaction_requested
+        .store(false, Ordering::Release);
+
+    partition.tree.run_compaction(shared.instant())
+}
@@ -0,0 +1,66 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{version::Level, KeyRange, SeqNo};
+
+/// Shape of a level of a tree, see [`AbstractTree::levels`](crate::AbstractTree::levels)
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LevelInfo {
+    /// Number of tables in the level
+    pub table_count: usize,
+
+    /// Number of sorted runs in the level
+    ///
+    /// Only L0 can contain multiple runs.
+    pub run_count: usize,
+
+    /// On-disk size of the tables of the level
+    pub bytes: u64,
+
+    /// Number of items (including tombstones) in the level
+    pub item_count: u64,
+
+    /// Number of tombstones (including weak tombstones) in the level
+    pub tombstone_count: u64,
+
+    /// Smallest and largest key of the level, or `None` if the level is empty
+    pub key_range: Option<KeyRange>,
+
+    /// Lowest and highest sequence number of the level, or `None` if the level is empty
+    pub seqnos: Option<(SeqNo, SeqNo)>,
+}
+
+impl LevelInfo {
+    pub(crate) fn from_level(level: &Level) -> Self {
+        let mut info = Self {
+            table_count: level.table_count(),
+            run_count: level.run_count(),
+            bytes: level.size(),
+            ..Default::default()
+        };
+
+        for table in level.iter().flat_map(|run| run.iter()) {
+            info.item_count += table.metadata.item_count;
+            info.tombstone_count += table.tombstone_count();
+
+            let (lo, hi) = table.metadata.seqnos;
+            info.seqnos = Some(match info.seqnos {
+                Some((min, max)) => (min.min(lo), max.max(hi)),
+                None => (lo, hi),
+            });
+        }
+
+        if !level.is_empty() {
+            info.key_range = Some(level.aggregate_key_range());
+        }
+
+        info
+    }
+
+    /// Returns `true` if the level contains no tables.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.table_count == 0
+    }
+}
@@ -84,6 +84,23 @@
 //! #
 //! # Ok::<(), lsm_tree::Error>(())
 //! ```
+//!
+//! # Partitions
+//!
+//! Instead of opening many trees, a [`keyspace::Keyspace`] hosts named partitions (column families)
+//! in a single folder. Partitions have their own memtables and levels, but share the journal,
+//! block cache and background workers, and a [`keyspace::WriteBatch`] writes to multiple
+//! partitions atomically.
+//!
+//! # WASM
+//!
+//! The crate compiles for `wasm32-wasip1`. WASM runtimes are single-threaded, so options
+//! that spawn threads ([`Config::background_threads`], [`Config::max_subcompactions`],
+//! [`Durability::SyncInterval`]) are rejected by [`Config::validate`], and keyspaces can not
+//! be opened. Flushes and compactions then only run when explicitly called.
+//! File access goes through the [`Filesystem`] of the [`Config`], or can be avoided
+//! entirely using [`Config::ephemeral`]. Because the positional reads of
+//! `std::os::wasi::fs::FileExt` are not stable yet, blocks are read by seeking the file instead.
 
 #![doc(html_logo_url = "https://raw.githubusercontent.com/fjall-rs/lsm-tree/main/logo.png")]
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/fjall-rs/lsm-tree/main/logo.png")]
@@ -124,14 +141,22 @@ pub(crate) use unwrap;
 
 mod any_tree;
 
+mod archive;
+
+pub mod backup;
+
 mod r#abstract;
 
+mod batch;
+
 #[doc(hidden)]
 pub mod blob_tree;
 
 #[doc(hidden)]
 mod cache;
 
+mod cdc;
+
 mod checksum;
 
 #[doc(hidden)]
@@ -143,19 +168,32 @@ mod compression;
 /// Configuration
 pub mod config;
 
+mod cursor;
+
 mod double_ended_peekable;
+mod encryption;
+mod env;
 
 mod error;
+mod export;
 
 #[doc(hidden)]
 pub mod file;
 
+mod fs;
+
 mod hash;
 
 mod iter_guard;
 
+mod journal;
+
 mod key;
+mod key_extractor;
+
 mod key_range;
+pub mod keyspace;
+mod level_info;
 
 mod run_reader;
 mod run_scanner;
@@ -163,12 +201,17 @@ mod run_scanner;
 mod manifest;
 mod memtable;
 
+/// Migration from other storage engines
+pub mod migrate;
+
 #[doc(hidden)]
 pub mod descriptor_table;
 
 #[doc(hidden)]
 pub mod merge;
 
+mod merge_operator;
+
 #[cfg(feature = "metrics")]
 pub(crate) mod metrics;
 
@@ -182,9 +225,15 @@ mod path;
 #[doc(hidden)]
 pub mod range;
 
+mod range_tombstone;
+mod rate_limiter;
+mod read_options;
+
 #[doc(hidden)]
 pub mod table;
 
+pub mod tiering;
+
 mod seqno;
 mod slice;
 mod slice_windows;
@@ -194,7 +243,10 @@ pub mod stop_signal;
 
 mod format_version;
 mod time;
+mod transaction;
 mod tree;
+mod ttl;
+mod usage;
 
 /// Utility functions
 pub mod util;
@@ -203,6 +255,7 @@ mod value;
 mod value_type;
 mod version;
 mod vlog;
+mod write_stall;
 
 /// User defined key (byte array)
 pub type UserKey = Slice;
@@ -228,22 +281,42 @@ pub use {
 
 pub use {
     any_tree::AnyTree,
-    blob_tree::BlobTree,
+    batch::Batch,
+    blob_tree::{
+        BlobTree, Snapshot as BlobTreeSnapshot, Stats as BlobTreeStats,
+        VerifyReport as BlobTreeVerifyReport,
+    },
     cache::Cache,
-    compression::CompressionType,
-    config::{Config, KvSeparationOptions, TreeType},
+    cdc::{CdcReader, ChangeEvent, ChangeOp},
+    compression::{register_compressor, CompressionType, Compressor},
+    config::{Config, Durability, KvSeparationOptions, TreeType},
+    cursor::Cursor,
     descriptor_table::DescriptorTable,
+    encryption::Encryptor,
+    env::Env,
     error::{Error, Result},
+    export::ExportFormat,
     format_version::FormatVersion,
+    fs::{FileHandle, Filesystem, MemFilesystem, StdFilesystem},
     iter_guard::IterGuard as Guard,
+    key_extractor::{FixedPrefixExtractor, KeyExtractor},
+    level_info::LevelInfo,
     memtable::Memtable,
-    r#abstract::AbstractTree,
+    merge_operator::MergeOperator,
+    r#abstract::{AbstractTree, RawItem},
+    rate_limiter::RateLimiter,
+    read_options::ReadOptions,
     seqno::SequenceNumberCounter,
     slice::Slice,
-    tree::Tree,
+    table::{SstBuilder, TableInfo},
+    time::{Clock, SystemClock},
+    transaction::{Conflict, Transaction, TransactionalTree},
+    tree::{FreezeGuard, Tree},
+    usage::{DiskUsage, MemoryUsage},
     value::SeqNo,
     value_type::ValueType,
-    vlog::BlobFile,
+    vlog::{BlobFile, BlobReader},
+    write_stall::{WriteStallCondition, WriteStallListener, WriteStallOptions},
 };
 
 #[cfg(feature = "metrics")]
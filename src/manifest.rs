@@ -2,18 +2,19 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{FormatVersion, TreeType};
-use byteorder::{ReadBytesExt, WriteBytesExt};
+use crate::{fs::section_reader, Filesystem, FormatVersion, TreeType};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 use std::{io::Write, path::Path};
 
 pub struct Manifest {
     pub version: FormatVersion,
     pub tree_type: TreeType,
     pub level_count: u8,
+    pub directory_shards: u16,
 }
 
 impl Manifest {
-    pub fn encode_into(&self, writer: &mut sfa::Writer) -> Result<(), crate::Error> {
+    pub fn encode_into(&self, writer: &mut crate::archive::Writer) -> Result<(), crate::Error> {
         writer.start("format_version")?;
         writer.write_u8(self.version.into())?;
 
@@ -26,12 +27,19 @@ impl Manifest {
         writer.start("level_count")?;
         writer.write_u8(self.level_count)?;
 
+        writer.start("directory_shards")?;
+        writer.write_u16::<LE>(self.directory_shards)?;
+
         Ok(())
     }
 }
 
 impl Manifest {
-    pub fn decode_from(path: &Path, reader: &sfa::Reader) -> Result<Self, crate::Error> {
+    pub fn decode_from(
+        fs: &dyn Filesystem,
+        path: &Path,
+        reader: &sfa::Reader,
+    ) -> Result<Self, crate::Error> {
         let toc = reader.toc();
 
         let version = {
@@ -39,7 +47,7 @@ impl Manifest {
                 .section(b"format_version")
                 .expect("format_version section must exist in manifest");
 
-            let mut reader = section.buf_reader(path)?;
+            let mut reader = section_reader(fs, path, section)?;
             let version = reader.read_u8()?;
             FormatVersion::try_from(version).map_err(|()| crate::Error::InvalidVersion(version))?
         };
@@ -49,7 +57,7 @@ impl Manifest {
                 .section(b"tree_type")
                 .expect("tree_type section must exist in manifest");
 
-            let mut reader = section.buf_reader(path)?;
+            let mut reader = section_reader(fs, path, section)?;
             let tree_type = reader.read_u8()?;
             tree_type
                 .try_into()
@@ -61,17 +69,21 @@ impl Manifest {
                 .section(b"level_count")
                 .expect("level_count section must exist in manifest");
 
-            let mut reader = section.buf_reader(path)?;
+            let mut reader = section_reader(fs, path, section)?;
             reader.read_u8()?
         };
 
-        // Currently level count is hard coded to 7
-        assert_eq!(7, level_count, "level count should be 7");
+        // NOTE: Trees that were created before directory sharding are not sharded
+        let directory_shards = match toc.section(b"directory_shards") {
+            Some(section) => section_reader(fs, path, section)?.read_u16::<LE>()?,
+            None => 1,
+        };
 
         Ok(Self {
             version,
             tree_type,
             level_count,
+            directory_shards,
         })
     }
 }
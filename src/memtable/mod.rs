@@ -4,12 +4,13 @@
 
 use crate::key::InternalKey;
 use crate::{
+    range_tombstone::RangeTombstone,
     value::{InternalValue, SeqNo, UserValue},
-    ValueType,
+    KeyExtractor, ValueType,
 };
-use crossbeam_skiplist::SkipMap;
+use crossbeam_skiplist::{SkipMap, SkipSet};
 use std::ops::RangeBounds;
-use std::sync::atomic::AtomicU64;
+use std::sync::{atomic::AtomicU64, RwLock};
 
 /// The memtable serves as an intermediary, ephemeral, sorted storage for new items
 ///
@@ -29,12 +30,29 @@ pub struct Memtable {
     ///
     /// This is used so that `get_highest_seqno` has O(1) complexity.
     pub(crate) highest_seqno: AtomicU64,
+
+    /// Hashes of the derived keys of all values, see [`crate::KeyExtractor`].
+    pub(crate) derived_key_hashes: SkipSet<u64>,
+
+    /// Range tombstones, see [`crate::AbstractTree::remove_range`].
+    ///
+    /// There are typically only a handful of them, so they are not indexed.
+    pub(crate) range_tombstones: RwLock<Vec<RangeTombstone>>,
 }
 
 impl Memtable {
     /// Clears the memtable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
     pub fn clear(&mut self) {
         self.items.clear();
+        self.derived_key_hashes.clear();
+        self.range_tombstones
+            .get_mut()
+            .expect("lock is poisoned")
+            .clear();
         self.highest_seqno = AtomicU64::new(0);
         self.approximate_size
             .store(0, std::sync::atomic::Ordering::Release);
@@ -108,10 +126,73 @@ impl Memtable {
         self.items.len()
     }
 
+    /// Adds the derived key of a value to the derived key set.
+    pub(crate) fn register_derived_key(&self, extractor: &dyn KeyExtractor, item: &InternalValue) {
+        if item.is_tombstone() {
+            return;
+        }
+
+        if let Some(derived_key) = extractor.extract(&item.key.user_key) {
+            self.derived_key_hashes
+                .insert(crate::hash::hash64(derived_key));
+        }
+    }
+
+    /// Returns `true` if the memtable may contain a value with the given derived key hash.
+    pub(crate) fn may_contain_derived_key_hash(&self, hash: u64) -> bool {
+        self.derived_key_hashes.contains(&hash)
+    }
+
     /// Returns `true` if the memtable is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.items.is_empty()
+        self.items.is_empty() && !self.has_range_tombstones()
+    }
+
+    /// Returns `true` if the memtable contains range tombstones.
+    pub(crate) fn has_range_tombstones(&self) -> bool {
+        !self
+            .range_tombstones
+            .read()
+            .expect("lock is poisoned")
+            .is_empty()
+    }
+
+    /// Returns a copy of all range tombstones.
+    pub(crate) fn range_tombstones(&self) -> Vec<RangeTombstone> {
+        self.range_tombstones
+            .read()
+            .expect("lock is poisoned")
+            .clone()
+    }
+
+    /// Returns `true` if a range tombstone that is visible at `read_seqno` deletes the given version of a key.
+    pub(crate) fn is_range_deleted(&self, key: &[u8], seqno: SeqNo, read_seqno: SeqNo) -> bool {
+        self.range_tombstones
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .any(|rt| rt.seqno < read_seqno && rt.covers(key, seqno))
+    }
+
+    /// Inserts a range tombstone into the memtable.
+    pub(crate) fn insert_range_tombstone(&self, tombstone: RangeTombstone) -> (u64, u64) {
+        #[expect(clippy::expect_used, reason = "keys are limited to 16-bit length")]
+        let item_size = tombstone.size().try_into().expect("should fit into u64");
+
+        let size_before = self
+            .approximate_size
+            .fetch_add(item_size, std::sync::atomic::Ordering::AcqRel);
+
+        self.highest_seqno
+            .fetch_max(tombstone.seqno, std::sync::atomic::Ordering::AcqRel);
+
+        self.range_tombstones
+            .write()
+            .expect("lock is poisoned")
+            .push(tombstone);
+
+        (item_size, size_before + item_size)
     }
 
     /// Inserts an item into the memtable
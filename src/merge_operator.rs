@@ -0,0 +1,58 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::UserValue;
+
+/// Combines merge operands with older versions of a key
+///
+/// Merge operands are written using [`AbstractTree::add_merge`](crate::AbstractTree::add_merge),
+/// and are not resolved at write time. Instead, reads fold the chain of operands of a key,
+/// starting from its newest value (or the oldest operand, if the key has no value),
+/// and compactions collapse operands that are not visible to any snapshot into a single write.
+///
+/// This turns read-modify-write cycles (counters, sets, append-only lists, ...)
+/// into single blind writes.
+///
+/// # Associativity
+///
+/// Because compactions can merge any adjacent range of operands, the operator needs to be
+/// associative: `merge(merge(a, b), c)` needs to equal `merge(a, merge(b, c))`.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{AbstractTree, Config, MergeOperator, UserValue};
+/// use std::sync::Arc;
+///
+/// #[derive(Debug)]
+/// struct Counter;
+///
+/// impl MergeOperator for Counter {
+///     fn merge(&self, _key: &[u8], older: &[u8], newer: &[u8]) -> UserValue {
+///         let parse = |bytes: &[u8]| u64::from_be_bytes(bytes.try_into().unwrap_or_default());
+///         (parse(older) + parse(newer)).to_be_bytes().into()
+///     }
+/// }
+///
+/// let tree = Config::new(folder, Default::default())
+///     .with_merge_operator(Some(Arc::new(Counter)))
+///     .open()?;
+///
+/// tree.insert("hits", 1u64.to_be_bytes(), 0);
+/// tree.add_merge("hits", 2u64.to_be_bytes(), 1);
+/// tree.add_merge("hits", 3u64.to_be_bytes(), 2);
+///
+/// let hits = tree.get("hits", 3)?.expect("should exist");
+/// assert_eq!(6u64.to_be_bytes(), &*hits);
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub trait MergeOperator: std::fmt::Debug + Send + Sync + 'static {
+    /// Merges two adjacent versions of a key.
+    ///
+    /// `older` is either the value of the key or an older (possibly already merged) operand,
+    /// `newer` is the newer operand.
+    fn merge(&self, key: &[u8], older: &[u8], newer: &[u8]) -> UserValue;
+}
@@ -0,0 +1,90 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Import from ordered embedded key-value stores, like `sled` or `redb`.
+//!
+//! Both `sled` and `redb` iterate in ascending key order, so their contents
+//! can be streamed into the bulk ingestion path directly, without buffering
+//! or a memtable round trip.
+//!
+//! To not depend on any of these crates, the importer accepts any fallible
+//! iterator of key-value pairs, so the store's own iterator can be passed in:
+//!
+//! ```ignore
+//! // sled
+//! let db = sled::open("my_sled_db")?;
+//! lsm_tree::migrate::kv::import(&tree, db.iter(), &seqno, &visible_seqno)?;
+//!
+//! // redb
+//! let db = redb::Database::open("my_redb_db")?;
+//! let read_txn = db.begin_read()?;
+//! let table = read_txn.open_table(TABLE)?;
+//!
+//! let iter = table.iter()?.map(|item| {
+//!     item.map(|(k, v)| (k.value().to_vec(), v.value().to_vec()))
+//! });
+//! lsm_tree::migrate::kv::import(&tree, iter, &seqno, &visible_seqno)?;
+//! ```
+
+use crate::{tree::ingest::Ingestion, SequenceNumberCounter, Tree, UserKey};
+
+/// Imports key-value pairs, sorted by key in ascending order, into a tree.
+///
+/// All pairs are written into new tables using bulk ingestion, with a
+/// single new sequence number. The visible sequence number is raised above
+/// it, so the imported data is visible afterwards.
+///
+/// Should only be called on a new, empty tree.
+///
+/// Returns the amount of imported pairs.
+///
+/// # Errors
+///
+/// Will return `Err` if an IO error occurs, the source iterator returns an error,
+/// or the keys are not in strictly ascending order.
+pub fn import<K, V, E, I>(
+    tree: &Tree,
+    iter: I,
+    seqno_generator: &SequenceNumberCounter,
+    visible_seqno: &SequenceNumberCounter,
+) -> crate::Result<u64>
+where
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    I: IntoIterator<Item = Result<(K, V), E>>,
+{
+    let start = std::time::Instant::now();
+
+    let seqno = seqno_generator.next();
+    let mut ingestion = Ingestion::new(tree)?.with_seqno(seqno);
+
+    let mut count = 0;
+    let mut last_key: Option<UserKey> = None;
+
+    for item in iter {
+        let (key, value) = item.map_err(std::io::Error::other)?;
+        let key = UserKey::from(key.as_ref());
+
+        if last_key.as_ref().is_some_and(|last_key| key <= *last_key) {
+            return Err(crate::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "keys are not in ascending order",
+            )));
+        }
+
+        ingestion.write(key.clone(), value.as_ref().into())?;
+        last_key = Some(key);
+
+        count += 1;
+    }
+
+    ingestion.finish()?;
+
+    visible_seqno.fetch_max(seqno + 1);
+
+    log::info!("Imported {count} key-value pairs in {:?}", start.elapsed());
+
+    Ok(count)
+}
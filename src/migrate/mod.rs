@@ -0,0 +1,8 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Helpers to migrate data from other storage engines.
+
+pub mod kv;
+pub mod rocksdb;
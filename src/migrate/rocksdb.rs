@@ -0,0 +1,522 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Import of `RocksDB` `BlockBasedTable` SST files.
+//!
+//! The importer reads data blocks of an SST file directly and writes
+//! the internal entries (user key, sequence number, value type, value)
+//! into native tables using the bulk ingestion path, so no memtable
+//! round trip is required.
+//!
+//! Supported are table format versions 0 to 5 with a binary search
+//! or hash index (not the partitioned index), and uncompressed blocks
+//! (or LZ4 compressed blocks when the `lz4` feature is enabled).
+//! Block checksums are not verified.
+//!
+//! Range tombstones, merge operands, blob indexes and wide column entities
+//! have no equivalent in this crate, so files containing them are rejected.
+
+use crate::{
+    tree::ingest::Ingestion, AbstractTree, InternalValue, SeqNo, SequenceNumberCounter, Tree,
+    UserKey, UserValue, ValueType,
+};
+use byteorder::{ReadBytesExt, LE};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+use varint_rs::VarintReader;
+
+/// Magic number of tables written with table format version 0
+const LEGACY_MAGIC: u64 = 0xdb47_7524_8b80_fb57;
+
+/// Magic number of tables written with table format version 1+
+const MAGIC: u64 = 0x88e2_41b7_85f4_cff7;
+
+const LEGACY_FOOTER_SIZE: u64 = 48;
+const FOOTER_SIZE: u64 = 53;
+
+/// Every block is followed by a compression type byte and a 32-bit checksum
+const BLOCK_TRAILER_SIZE: u64 = 5;
+
+const MAX_FORMAT_VERSION: u32 = 5;
+
+const PROPERTIES_BLOCK: &[u8] = b"rocksdb.properties";
+const RANGE_DEL_BLOCK: &[u8] = b"rocksdb.range_del";
+const PROP_INDEX_TYPE: &[u8] = b"rocksdb.block.based.table.index.type";
+const PROP_INDEX_DELTA_ENCODED: &[u8] = b"rocksdb.index.value.is.delta.encoded";
+
+const INDEX_TYPE_TWO_LEVEL: u32 = 2;
+const INDEX_TYPE_BINARY_SEARCH_WITH_FIRST_KEY: u32 = 3;
+
+const COMPRESSION_NONE: u8 = 0;
+#[cfg(feature = "lz4")]
+const COMPRESSION_LZ4: u8 = 4;
+#[cfg(feature = "lz4")]
+const COMPRESSION_LZ4HC: u8 = 5;
+
+const TYPE_DELETION: u8 = 0x0;
+const TYPE_VALUE: u8 = 0x1;
+const TYPE_SINGLE_DELETION: u8 = 0x7;
+const TYPE_RANGE_DELETION: u8 = 0xF;
+
+fn corrupt_block() -> crate::Error {
+    crate::Error::InvalidHeader("RocksDbBlock")
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct BlockHandle {
+    offset: u64,
+    size: u64,
+}
+
+impl BlockHandle {
+    fn decode_from<R: Read>(reader: &mut R) -> crate::Result<Self> {
+        let offset = reader.read_u64_varint()?;
+        let size = reader.read_u64_varint()?;
+        Ok(Self { offset, size })
+    }
+}
+
+/// Splits a block into its entry section and its restart points.
+fn split_block(data: &[u8]) -> crate::Result<(&[u8], Vec<u32>)> {
+    let footer_start = data.len().checked_sub(4).ok_or_else(corrupt_block)?;
+
+    let mut footer = data.get(footer_start..).ok_or_else(corrupt_block)?;
+    let mut restart_count = footer.read_u32::<LE>()?;
+
+    // NOTE: The MSB signals that the block carries a data block hash index
+    // after the restart array, which we skip
+    let restarts_end = if restart_count & (1 << 31) == 0 {
+        footer_start
+    } else {
+        restart_count &= 0x7FFF_FFFF;
+
+        let bucket_count_start = footer_start.checked_sub(2).ok_or_else(corrupt_block)?;
+        let mut bucket_count = data
+            .get(bucket_count_start..footer_start)
+            .ok_or_else(corrupt_block)?;
+        let bucket_count = bucket_count.read_u16::<LE>()?;
+
+        bucket_count_start
+            .checked_sub(bucket_count.into())
+            .ok_or_else(corrupt_block)?
+    };
+
+    let restarts_start = restarts_end
+        .checked_sub(restart_count as usize * 4)
+        .ok_or_else(corrupt_block)?;
+
+    let mut reader = data
+        .get(restarts_start..restarts_end)
+        .ok_or_else(corrupt_block)?;
+
+    let restarts = (0..restart_count)
+        .map(|_| reader.read_u32::<LE>())
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    let entries = data.get(..restarts_start).ok_or_else(corrupt_block)?;
+
+    Ok((entries, restarts))
+}
+
+fn read_slice<'a>(cursor: &mut Cursor<&'a [u8]>, len: usize) -> crate::Result<&'a [u8]> {
+    let data: &'a [u8] = cursor.get_ref();
+
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "cursor is backed by a slice"
+    )]
+    let start = cursor.position() as usize;
+
+    let end = start.checked_add(len).ok_or_else(corrupt_block)?;
+    let slice = data.get(start..end).ok_or_else(corrupt_block)?;
+
+    cursor.set_position(end as u64);
+
+    Ok(slice)
+}
+
+/// Reads the next entry's key, returning the value length (if encoded).
+fn read_key(
+    cursor: &mut Cursor<&[u8]>,
+    last_key: &mut Vec<u8>,
+    has_value_len: bool,
+) -> crate::Result<Option<u32>> {
+    let shared = cursor.read_u32_varint()? as usize;
+    let non_shared = cursor.read_u32_varint()? as usize;
+
+    let value_len = if has_value_len {
+        Some(cursor.read_u32_varint()?)
+    } else {
+        None
+    };
+
+    if shared > last_key.len() {
+        return Err(corrupt_block());
+    }
+
+    last_key.truncate(shared);
+    last_key.extend_from_slice(read_slice(cursor, non_shared)?);
+
+    Ok(value_len)
+}
+
+/// Decodes all key-value entries of a regular (non delta encoded) block.
+fn decode_entries(data: &[u8]) -> crate::Result<Vec<(Vec<u8>, &[u8])>> {
+    let (entries, _) = split_block(data)?;
+
+    let mut cursor = Cursor::new(entries);
+    let mut last_key = Vec::new();
+    let mut items = Vec::new();
+
+    while cursor.position() < entries.len() as u64 {
+        let value_len = read_key(&mut cursor, &mut last_key, true)?.unwrap_or_default();
+        let value = read_slice(&mut cursor, value_len as usize)?;
+        items.push((last_key.clone(), value));
+    }
+
+    Ok(items)
+}
+
+/// Decodes the block handles of an index block.
+fn decode_index(
+    data: &[u8],
+    delta_encoded: bool,
+    index_type: u32,
+) -> crate::Result<Vec<BlockHandle>> {
+    if !delta_encoded {
+        return decode_entries(data)?
+            .into_iter()
+            .map(|(_, mut value)| BlockHandle::decode_from(&mut value))
+            .collect();
+    }
+
+    let (entries, restarts) = split_block(data)?;
+
+    let mut cursor = Cursor::new(entries);
+    let mut last_key = Vec::new();
+    let mut handles: Vec<BlockHandle> = Vec::new();
+
+    while cursor.position() < entries.len() as u64 {
+        let is_restart = u32::try_from(cursor.position()).is_ok_and(|pos| restarts.contains(&pos));
+
+        read_key(&mut cursor, &mut last_key, false)?;
+
+        let handle = match handles.last() {
+            Some(prev) if !is_restart => {
+                // NOTE: Only the (zig-zag encoded) size delta is stored,
+                // the offset directly follows the previous block
+                let raw = cursor.read_u64_varint()?;
+
+                #[expect(clippy::cast_possible_wrap, reason = "zig-zag decoding")]
+                let delta = (raw >> 1) as i64 ^ -((raw & 1) as i64);
+
+                BlockHandle {
+                    offset: prev.offset + prev.size + BLOCK_TRAILER_SIZE,
+                    size: prev
+                        .size
+                        .checked_add_signed(delta)
+                        .ok_or_else(corrupt_block)?,
+                }
+            }
+            _ => BlockHandle::decode_from(&mut cursor)?,
+        };
+
+        if index_type == INDEX_TYPE_BINARY_SEARCH_WITH_FIRST_KEY {
+            let first_key_len = cursor.read_u32_varint()?;
+            read_slice(&mut cursor, first_key_len as usize)?;
+        }
+
+        handles.push(handle);
+    }
+
+    Ok(handles)
+}
+
+/// Reader over the internal entries of a `RocksDB` `BlockBasedTable` SST file
+///
+/// Entries are returned in the file's order, which is ascending by user key
+/// and descending by sequence number (assuming the default bytewise comparator).
+pub struct SstReader {
+    file: File,
+    format_version: u32,
+    data_blocks: VecDeque<BlockHandle>,
+    buffer: VecDeque<InternalValue>,
+}
+
+impl SstReader {
+    /// Opens a `RocksDB` SST file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or the file is not
+    /// a supported `BlockBasedTable` file.
+    pub fn new<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let mut file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+
+        if file_size < LEGACY_FOOTER_SIZE {
+            return Err(crate::Error::InvalidTrailer);
+        }
+
+        file.seek(SeekFrom::End(-8))?;
+        let magic = file.read_u64::<LE>()?;
+
+        let (footer_size, has_checksum_type) = match magic {
+            LEGACY_MAGIC => (LEGACY_FOOTER_SIZE, false),
+            MAGIC if file_size >= FOOTER_SIZE => (FOOTER_SIZE, true),
+            _ => return Err(crate::Error::InvalidTrailer),
+        };
+
+        file.seek(SeekFrom::Start(file_size - footer_size))?;
+
+        #[expect(clippy::cast_possible_truncation, reason = "footer is small")]
+        let mut footer = vec![0; footer_size as usize];
+        file.read_exact(&mut footer)?;
+
+        let mut reader = Cursor::new(&footer[..]);
+
+        if has_checksum_type {
+            // NOTE: Checksums are not verified
+            reader.read_u8()?;
+        }
+
+        let metaindex_handle = BlockHandle::decode_from(&mut reader)?;
+        let index_handle = BlockHandle::decode_from(&mut reader)?;
+
+        let format_version = if has_checksum_type {
+            reader.seek(SeekFrom::End(-12))?;
+            reader.read_u32::<LE>()?
+        } else {
+            0
+        };
+
+        if format_version > MAX_FORMAT_VERSION {
+            log::error!("Unsupported RocksDB table format version: {format_version}");
+            return Err(crate::Error::InvalidVersion(
+                u8::try_from(format_version).unwrap_or(u8::MAX),
+            ));
+        }
+
+        let mut reader = Self {
+            file,
+            format_version,
+            data_blocks: VecDeque::new(),
+            buffer: VecDeque::new(),
+        };
+
+        let mut index_type = 0;
+        let mut delta_encoded = false;
+
+        let metaindex_block = reader.read_block(metaindex_handle)?;
+
+        for (name, mut value) in decode_entries(&metaindex_block)? {
+            let handle = BlockHandle::decode_from(&mut value)?;
+
+            if name == PROPERTIES_BLOCK {
+                let properties_block = reader.read_block(handle)?;
+
+                for (name, mut value) in decode_entries(&properties_block)? {
+                    if name == PROP_INDEX_TYPE {
+                        index_type = value.read_u32::<LE>()?;
+                    } else if name == PROP_INDEX_DELTA_ENCODED {
+                        delta_encoded = value.read_u64_varint()? != 0;
+                    }
+                }
+            } else if name == RANGE_DEL_BLOCK {
+                let range_del_block = reader.read_block(handle)?;
+
+                if !decode_entries(&range_del_block)?.is_empty() {
+                    log::error!("RocksDB range tombstones cannot be imported");
+                    return Err(crate::Error::InvalidTag((
+                        "RocksDbValueType",
+                        TYPE_RANGE_DELETION,
+                    )));
+                }
+            }
+        }
+
+        if index_type == INDEX_TYPE_TWO_LEVEL {
+            log::error!("RocksDB partitioned index is not supported");
+            return Err(crate::Error::InvalidTag((
+                "RocksDbIndexType",
+                u8::try_from(index_type).unwrap_or(u8::MAX),
+            )));
+        }
+
+        let index_block = reader.read_block(index_handle)?;
+        reader.data_blocks = decode_index(&index_block, delta_encoded, index_type)?.into();
+
+        Ok(reader)
+    }
+
+    fn read_block(&mut self, handle: BlockHandle) -> crate::Result<Vec<u8>> {
+        self.file.seek(SeekFrom::Start(handle.offset))?;
+
+        let len = usize::try_from(handle.size + BLOCK_TRAILER_SIZE).map_err(|_| corrupt_block())?;
+
+        let mut buf = vec![0; len];
+        self.file.read_exact(&mut buf)?;
+
+        #[expect(clippy::cast_possible_truncation, reason = "trailer is 5 bytes")]
+        let trailer_start = len - BLOCK_TRAILER_SIZE as usize;
+
+        let compression_type = buf.get(trailer_start).copied();
+        buf.truncate(trailer_start);
+
+        match compression_type {
+            Some(COMPRESSION_NONE) => Ok(buf),
+
+            #[cfg(feature = "lz4")]
+            Some(COMPRESSION_LZ4 | COMPRESSION_LZ4HC) if self.format_version >= 2 => {
+                let mut reader = &buf[..];
+                let uncompressed_len = reader.read_u32_varint()?;
+
+                lz4_flex::decompress(reader, uncompressed_len as usize)
+                    .map_err(|_| crate::Error::Decompress(crate::CompressionType::Lz4))
+            }
+
+            Some(tag) => {
+                log::error!(
+                    "Unsupported RocksDB block compression type: {tag} (format version: {})",
+                    self.format_version
+                );
+                Err(crate::Error::InvalidTag(("RocksDbCompressionType", tag)))
+            }
+
+            None => Err(corrupt_block()),
+        }
+    }
+
+    fn load_next_block(&mut self) -> crate::Result<bool> {
+        let Some(handle) = self.data_blocks.pop_front() else {
+            return Ok(false);
+        };
+
+        let block = self.read_block(handle)?;
+
+        for (internal_key, value) in decode_entries(&block)? {
+            let trailer_start = internal_key
+                .len()
+                .checked_sub(8)
+                .ok_or_else(corrupt_block)?;
+
+            let (user_key, mut trailer) = internal_key.split_at(trailer_start);
+            let trailer = trailer.read_u64::<LE>()?;
+
+            let seqno: SeqNo = trailer >> 8;
+
+            let value_type = match (trailer & 0xFF) as u8 {
+                TYPE_VALUE => ValueType::Value,
+                TYPE_DELETION => ValueType::Tombstone,
+                TYPE_SINGLE_DELETION => ValueType::WeakTombstone,
+                tag => {
+                    log::error!("Unsupported RocksDB value type: {tag}");
+                    return Err(crate::Error::InvalidTag(("RocksDbValueType", tag)));
+                }
+            };
+
+            let value = if value_type == ValueType::Value {
+                UserValue::from(value)
+            } else {
+                UserValue::empty()
+            };
+
+            self.buffer.push_back(InternalValue::from_components(
+                UserKey::from(user_key),
+                value,
+                seqno,
+                value_type,
+            ));
+        }
+
+        Ok(true)
+    }
+}
+
+impl Iterator for SstReader {
+    type Item = crate::Result<InternalValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                return Some(Ok(item));
+            }
+
+            if !fail_iter!(self.load_next_block()) {
+                return None;
+            }
+        }
+    }
+}
+
+/// Imports `RocksDB` SST files into a tree.
+///
+/// Every file is written into new tables using bulk ingestion, keeping
+/// the sequence numbers and value types of the imported entries.
+///
+/// The sequence number generator and visible sequence number are raised
+/// above the highest imported sequence number, so the imported data is
+/// visible afterwards.
+///
+/// Because the imported sequence numbers may be lower than the ones of
+/// existing data, only an empty tree can be imported into.
+///
+/// Returns the amount of imported entries.
+///
+/// # Errors
+///
+/// Will return `Err` if an IO error occurs, a file is not
+/// a supported `BlockBasedTable` file, or [`crate::Error::InvalidIngestion`]
+/// if the tree is not empty.
+pub fn import<P: AsRef<Path>>(
+    tree: &Tree,
+    paths: impl IntoIterator<Item = P>,
+    seqno_generator: &SequenceNumberCounter,
+    visible_seqno: &SequenceNumberCounter,
+) -> crate::Result<u64> {
+    // NOTE: Existing (especially unflushed) writes could be shadowed by imported
+    // entries with higher sequence numbers, or be skipped during journal replay
+    if tree.table_count() > 0 || tree.active_memtable_size() > 0 || tree.sealed_memtable_count() > 0
+    {
+        return Err(crate::Error::InvalidIngestion(
+            "RocksDB files can only be imported into an empty tree",
+        ));
+    }
+
+    let start = std::time::Instant::now();
+
+    let mut count = 0;
+    let mut max_seqno = None;
+
+    for path in paths {
+        let path = path.as_ref();
+        log::debug!("Importing RocksDB SST file {}", path.display());
+
+        let mut ingestion = Ingestion::new(tree)?;
+
+        for item in SstReader::new(path)? {
+            let item = item?;
+
+            max_seqno = max_seqno.max(Some(item.key.seqno));
+            ingestion.write_internal(item)?;
+
+            count += 1;
+        }
+
+        ingestion.finish()?;
+    }
+
+    if let Some(seqno) = max_seqno {
+        seqno_generator.fetch_max(seqno + 1);
+        visible_seqno.fetch_max(seqno + 1);
+    }
+
+    log::info!("Imported {count} RocksDB entries in {:?}", start.elapsed());
+
+    Ok(count)
+}
@@ -3,13 +3,17 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::double_ended_peekable::{DoubleEndedPeekable, DoubleEndedPeekableExt};
-use crate::{InternalValue, UserKey};
+use crate::{InternalValue, MergeOperator, UserKey, ValueType};
+use std::sync::Arc;
 
 /// Consumes a stream of KVs and emits a new stream according to MVCC and tombstone rules
 ///
 /// This iterator is used for read operations.
 pub struct MvccStream<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> {
     inner: DoubleEndedPeekable<crate::Result<InternalValue>, I>,
+
+    /// Folds merge operands into older versions of their key
+    merge_operator: Option<Arc<dyn MergeOperator>>,
 }
 
 impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> MvccStream<I> {
@@ -18,9 +22,17 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> MvccStream<I>
     pub fn new(iter: I) -> Self {
         Self {
             inner: iter.double_ended_peekable(),
+            merge_operator: None,
         }
     }
 
+    /// Installs a merge operator that resolves merge operands.
+    #[must_use]
+    pub fn with_merge_operator(mut self, merge_operator: Option<Arc<dyn MergeOperator>>) -> Self {
+        self.merge_operator = merge_operator;
+        self
+    }
+
     fn drain_key_min(&mut self, key: &UserKey) -> crate::Result<()> {
         loop {
             let Some(next) = self.inner.next_if(|kv| {
@@ -36,6 +48,62 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> MvccStream<I>
             next?;
         }
     }
+
+    /// Folds the older versions of the key into the merge operand `head`,
+    /// until a value or tombstone is reached.
+    fn resolve_forwards(
+        &mut self,
+        merge_operator: &dyn MergeOperator,
+        mut head: InternalValue,
+    ) -> crate::Result<InternalValue> {
+        loop {
+            let Some(next) = self.inner.next_if(|kv| {
+                if let Ok(kv) = kv {
+                    kv.key.user_key == head.key.user_key
+                } else {
+                    true
+                }
+            }) else {
+                break;
+            };
+
+            let older = next?;
+
+            if older.key.is_tombstone() {
+                break;
+            }
+
+            head.value = merge_operator.merge(&head.key.user_key, &older.value, &head.value);
+
+            if older.key.value_type == ValueType::Value {
+                break;
+            }
+        }
+
+        self.drain_key_min(&head.key.user_key)?;
+
+        head.key.value_type = ValueType::Value;
+        Ok(head)
+    }
+}
+
+/// Folds a newer version of a key into the (already folded) older versions, when iterating backwards.
+fn fold_backwards(
+    merge_operator: Option<&dyn MergeOperator>,
+    older: Option<InternalValue>,
+    mut newer: InternalValue,
+) -> InternalValue {
+    let (Some(merge_operator), Some(older)) = (merge_operator, older) else {
+        return newer;
+    };
+
+    if !newer.key.value_type.is_merge_operand() || older.key.is_tombstone() {
+        return newer;
+    }
+
+    newer.value = merge_operator.merge(&newer.key.user_key, &older.value, &newer.value);
+    newer.key.value_type = older.key.value_type;
+    newer
 }
 
 impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> Iterator for MvccStream<I> {
@@ -44,10 +112,16 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> Iterator for M
     fn next(&mut self) -> Option<Self::Item> {
         let head = fail_iter!(self.inner.next()?);
 
+        if head.key.value_type.is_merge_operand() {
+            if let Some(merge_operator) = self.merge_operator.clone() {
+                return Some(self.resolve_forwards(&*merge_operator, head));
+            }
+        }
+
         // As long as items are the same key, ignore them
         fail_iter!(self.drain_key_min(&head.key.user_key));
 
-        Some(Ok(head))
+        Some(Ok(resolved(head)))
     }
 }
 
@@ -55,8 +129,11 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> DoubleEndedIte
     for MvccStream<I>
 {
     fn next_back(&mut self) -> Option<Self::Item> {
+        let mut folded = None;
+
         loop {
             let tail = fail_iter!(self.inner.next_back()?);
+            let tail = fold_backwards(self.merge_operator.as_deref(), folded.take(), tail);
 
             let prev = match self.inner.peek_back() {
                 Some(Ok(prev)) => prev,
@@ -72,17 +149,27 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> DoubleEndedIte
                         .expect_err("should be error")));
                 }
                 None => {
-                    return Some(Ok(tail));
+                    return Some(Ok(resolved(tail)));
                 }
             };
 
             if prev.key.user_key < tail.key.user_key {
-                return Some(Ok(tail));
+                return Some(Ok(resolved(tail)));
             }
+
+            folded = Some(tail);
         }
     }
 }
 
+/// Turns a fully folded merge operand into a value.
+fn resolved(mut item: InternalValue) -> InternalValue {
+    if item.key.value_type.is_merge_operand() {
+        item.key.value_type = ValueType::Value;
+    }
+    item
+}
+
 #[cfg(test)]
 #[expect(clippy::string_lit_as_bytes)]
 mod tests {
@@ -102,6 +189,7 @@ mod tests {
                   "V" => ValueType::Value,
                   "T" => ValueType::Tombstone,
                   "W" => ValueType::WeakTombstone,
+                  "M" => ValueType::MergeOperand,
                   _ => panic!("Unknown value type"),
               };
 
@@ -633,4 +721,71 @@ mod tests {
 
         Ok(())
     }
+
+    #[derive(Debug)]
+    struct Append;
+
+    impl MergeOperator for Append {
+        fn merge(&self, _key: &[u8], older: &[u8], newer: &[u8]) -> crate::UserValue {
+            [older, newer].concat().into()
+        }
+    }
+
+    #[test]
+    #[expect(clippy::unwrap_used)]
+    fn mvcc_stream_merge_operands() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "c", "M",
+          "a", "b", "M",
+          "a", "a", "V",
+          "a", "old", "V",
+          "b", "c", "M",
+          "b", "b", "M",
+          "c", "c", "M",
+          "c", "", "T",
+          "c", "old", "V",
+          "d", "", "T",
+          "d", "a", "M",
+        ];
+
+        let merge_operator: Option<Arc<dyn MergeOperator>> = Some(Arc::new(Append));
+
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+        let mut iter = MvccStream::new(iter).with_merge_operator(merge_operator.clone());
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"abc", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"b", *b"bc", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"c", *b"c", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"d", *b"", 999, ValueType::Tombstone),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+        let forwards = MvccStream::new(iter)
+            .with_merge_operator(merge_operator.clone())
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+        let mut backwards = MvccStream::new(iter)
+            .with_merge_operator(merge_operator)
+            .rev()
+            .collect::<crate::Result<Vec<_>>>()?;
+        backwards.reverse();
+
+        assert_eq!(forwards, backwards);
+
+        Ok(())
+    }
 }
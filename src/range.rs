@@ -7,15 +7,18 @@ use crate::{
     memtable::Memtable,
     merge::Merger,
     mvcc_stream::MvccStream,
+    range_tombstone::{RangeTombstone, RangeTombstoneIndex},
     run_reader::RunReader,
+    ttl,
     value::{SeqNo, UserKey},
     version::SuperVersion,
-    BoxedIterator, InternalValue,
+    BoxedIterator, InternalValue, MergeOperator,
 };
 use self_cell::self_cell;
 use std::{
     ops::{Bound, RangeBounds},
     sync::Arc,
+    time::Duration,
 };
 
 #[must_use]
@@ -23,6 +26,34 @@ pub fn seqno_filter(item_seqno: SeqNo, seqno: SeqNo) -> bool {
     item_seqno < seqno
 }
 
+/// Wraps each iterator so it skips versions that are deleted by one of the range tombstones.
+fn filter_range_tombstones(
+    iters: Vec<BoxedIterator<'_>>,
+    range_tombstones: Vec<RangeTombstone>,
+) -> Vec<BoxedIterator<'_>> {
+    if range_tombstones.is_empty() {
+        return iters;
+    }
+
+    let range_tombstones = Arc::new(RangeTombstoneIndex::from(range_tombstones));
+
+    iters
+        .into_iter()
+        .map(|iter| {
+            let range_tombstones = range_tombstones.clone();
+
+            Box::new(iter.filter(move |item| match item {
+                Ok(item) => !range_tombstones.is_range_deleted(
+                    &item.key.user_key,
+                    item.key.seqno,
+                    SeqNo::MAX,
+                ),
+                Err(_) => true,
+            })) as BoxedIterator<'_>
+        })
+        .collect()
+}
+
 pub(crate) fn prefix_upper_range(prefix: &[u8]) -> Bound<UserKey> {
     use std::ops::Bound::{Excluded, Unbounded};
 
@@ -65,6 +96,9 @@ pub fn prefix_to_range(prefix: &[u8]) -> (Bound<UserKey>, Bound<UserKey>) {
 pub struct IterState {
     pub(crate) version: SuperVersion,
     pub(crate) ephemeral: Option<Arc<Memtable>>,
+
+    /// Whether data blocks that are loaded from disk are inserted into the block cache
+    pub(crate) fill_cache: bool,
 }
 
 type BoxedMerge<'a> = Box<dyn DoubleEndedIterator<Item = crate::Result<InternalValue>> + Send + 'a>;
@@ -93,131 +127,159 @@ impl DoubleEndedIterator for TreeIter {
     }
 }
 
+/// Creates the iterators over all tables and memtables of a super version,
+/// restricted to the range and sequence number.
+///
+/// Returns the range as internal key bounds, as well.
+fn create_source_iters<'a, K: AsRef<[u8]>, R: RangeBounds<K>>(
+    lock: &'a IterState,
+    range: &R,
+    seqno: SeqNo,
+) -> (
+    (Bound<InternalKey>, Bound<InternalKey>),
+    Vec<BoxedIterator<'a>>,
+) {
+    let lo = match range.start_bound() {
+        // NOTE: See memtable.rs for range explanation
+        Bound::Included(key) => Bound::Included(InternalKey::new(
+            key.as_ref(),
+            SeqNo::MAX,
+            crate::ValueType::Tombstone,
+        )),
+        Bound::Excluded(key) => Bound::Excluded(InternalKey::new(
+            key.as_ref(),
+            0,
+            crate::ValueType::Tombstone,
+        )),
+        Bound::Unbounded => Bound::Unbounded,
+    };
+
+    let hi = match range.end_bound() {
+        // NOTE: See memtable.rs for range explanation, this is the reverse case
+        // where we need to go all the way to the last seqno of an item
+        //
+        // Example: We search for (Unbounded..Excluded(abdef))
+        //
+        // key -> seqno
+        //
+        // a   -> 7 <<< This is the lowest key that matches the range
+        // abc -> 5
+        // abc -> 4
+        // abc -> 3 <<< This is the highest key that matches the range
+        // abcdef -> 6
+        // abcdef -> 5
+        //
+        Bound::Included(key) => {
+            Bound::Included(InternalKey::new(key.as_ref(), 0, crate::ValueType::Value))
+        }
+        Bound::Excluded(key) => Bound::Excluded(InternalKey::new(
+            key.as_ref(),
+            SeqNo::MAX,
+            crate::ValueType::Value,
+        )),
+        Bound::Unbounded => Bound::Unbounded,
+    };
+
+    let range = (lo, hi);
+
+    let mut iters: Vec<BoxedIterator<'_>> = Vec::with_capacity(5);
+
+    for run in lock
+        .version
+        .version
+        .iter_levels()
+        .flat_map(|lvl| lvl.iter())
+    {
+        match run.len() {
+            0 => {
+                // Do nothing
+            }
+            1 => {
+                #[expect(clippy::expect_used, reason = "we checked for length")]
+                let table = run.first().expect("should exist");
+
+                if table.check_key_range_overlap(&(
+                    range.start_bound().map(|x| &*x.user_key),
+                    range.end_bound().map(|x| &*x.user_key),
+                )) {
+                    let reader = table.range_with_fill_cache(
+                        (
+                            range.start_bound().map(|x| &x.user_key).cloned(),
+                            range.end_bound().map(|x| &x.user_key).cloned(),
+                        ),
+                        lock.fill_cache,
+                    );
+
+                    iters.push(Box::new(reader.filter(move |item| match item {
+                        Ok(item) => seqno_filter(item.key.seqno, seqno),
+                        Err(_) => true,
+                    })));
+                }
+            }
+            _ => {
+                if let Some(reader) = RunReader::new(
+                    run.clone(),
+                    (
+                        range.start_bound().map(|x| &x.user_key).cloned(),
+                        range.end_bound().map(|x| &x.user_key).cloned(),
+                    ),
+                    lock.fill_cache,
+                ) {
+                    iters.push(Box::new(reader.filter(move |item| match item {
+                        Ok(item) => seqno_filter(item.key.seqno, seqno),
+                        Err(_) => true,
+                    })));
+                }
+            }
+        }
+    }
+
+    // Sealed memtables
+    for (_, memtable) in lock.version.sealed_memtables.iter() {
+        let iter = memtable.range(range.clone());
+
+        iters.push(Box::new(
+            iter.filter(move |item| seqno_filter(item.key.seqno, seqno))
+                .map(Ok),
+        ));
+    }
+
+    // Active memtable
+    {
+        let iter = lock.version.active_memtable.range(range.clone());
+
+        iters.push(Box::new(
+            iter.filter(move |item| seqno_filter(item.key.seqno, seqno))
+                .map(Ok),
+        ));
+    }
+
+    (range, iters)
+}
+
 impl TreeIter {
     pub fn create_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
         guard: IterState,
         range: R,
         seqno: SeqNo,
+        merge_operator: Option<Arc<dyn MergeOperator>>,
+        now: Duration,
     ) -> Self {
         Self::new(guard, |lock| {
-            let lo = match range.start_bound() {
-                // NOTE: See memtable.rs for range explanation
-                Bound::Included(key) => Bound::Included(InternalKey::new(
-                    key.as_ref(),
-                    SeqNo::MAX,
-                    crate::ValueType::Tombstone,
-                )),
-                Bound::Excluded(key) => Bound::Excluded(InternalKey::new(
-                    key.as_ref(),
-                    0,
-                    crate::ValueType::Tombstone,
-                )),
-                Bound::Unbounded => Bound::Unbounded,
-            };
-
-            let hi = match range.end_bound() {
-                // NOTE: See memtable.rs for range explanation, this is the reverse case
-                // where we need to go all the way to the last seqno of an item
-                //
-                // Example: We search for (Unbounded..Excluded(abdef))
-                //
-                // key -> seqno
-                //
-                // a   -> 7 <<< This is the lowest key that matches the range
-                // abc -> 5
-                // abc -> 4
-                // abc -> 3 <<< This is the highest key that matches the range
-                // abcdef -> 6
-                // abcdef -> 5
-                //
-                Bound::Included(key) => {
-                    Bound::Included(InternalKey::new(key.as_ref(), 0, crate::ValueType::Value))
-                }
-                Bound::Excluded(key) => Bound::Excluded(InternalKey::new(
-                    key.as_ref(),
-                    SeqNo::MAX,
-                    crate::ValueType::Value,
-                )),
-                Bound::Unbounded => Bound::Unbounded,
-            };
-
-            let range = (lo, hi);
-
-            let mut iters: Vec<BoxedIterator<'_>> = Vec::with_capacity(5);
-
-            for run in lock
-                .version
-                .version
-                .iter_levels()
-                .flat_map(|lvl| lvl.iter())
-            {
-                match run.len() {
-                    0 => {
-                        // Do nothing
-                    }
-                    1 => {
-                        #[expect(clippy::expect_used, reason = "we checked for length")]
-                        let table = run.first().expect("should exist");
-
-                        if table.check_key_range_overlap(&(
-                            range.start_bound().map(|x| &*x.user_key),
-                            range.end_bound().map(|x| &*x.user_key),
-                        )) {
-                            let reader = table.range((
-                                range.start_bound().map(|x| &x.user_key).cloned(),
-                                range.end_bound().map(|x| &x.user_key).cloned(),
-                            ));
-
-                            iters.push(Box::new(reader.filter(move |item| match item {
-                                Ok(item) => seqno_filter(item.key.seqno, seqno),
-                                Err(_) => true,
-                            })));
-                        }
-                    }
-                    _ => {
-                        if let Some(reader) = RunReader::new(
-                            run.clone(),
-                            (
-                                range.start_bound().map(|x| &x.user_key).cloned(),
-                                range.end_bound().map(|x| &x.user_key).cloned(),
-                            ),
-                        ) {
-                            iters.push(Box::new(reader.filter(move |item| match item {
-                                Ok(item) => seqno_filter(item.key.seqno, seqno),
-                                Err(_) => true,
-                            })));
-                        }
-                    }
-                }
-            }
-
-            // Sealed memtables
-            for (_, memtable) in lock.version.sealed_memtables.iter() {
-                let iter = memtable.range(range.clone());
+            let (range, iters) = create_source_iters(lock, &range, seqno);
 
-                iters.push(Box::new(
-                    iter.filter(move |item| seqno_filter(item.key.seqno, seqno))
-                        .map(Ok),
-                ));
-            }
-
-            // Active memtable
-            {
-                let iter = lock.version.active_memtable.range(range.clone());
-
-                iters.push(Box::new(
-                    iter.filter(move |item| seqno_filter(item.key.seqno, seqno))
-                        .map(Ok),
-                ));
-            }
+            // NOTE: Drop versions that are deleted by range tombstones,
+            // before the MVCC stream picks the newest version of each key
+            let mut iters = filter_range_tombstones(iters, lock.version.range_tombstones(seqno));
 
             if let Some(index) = &lock.ephemeral {
                 let iter = Box::new(index.range(range).map(Ok));
                 iters.push(iter);
             }
 
-            let merged = Merger::new(iters);
-            let iter = MvccStream::new(merged);
+            // NOTE: Expired values need to shadow their older versions like tombstones
+            let merged = Merger::new(iters).map(move |item| item.map(|kv| ttl::resolve(kv, now)));
+            let iter = MvccStream::new(merged).with_merge_operator(merge_operator);
 
             Box::new(iter.filter(|x| match x {
                 Ok(value) => !value.key.is_tombstone(),
@@ -225,6 +287,19 @@ impl TreeIter {
             }))
         })
     }
+
+    /// Creates an iterator over all versions of the items in the range,
+    /// including tombstones, in internal key order.
+    pub fn create_raw_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        guard: IterState,
+        range: R,
+        seqno: SeqNo,
+    ) -> Self {
+        Self::new(guard, |lock| {
+            let (_, iters) = create_source_iters(lock, &range, seqno);
+            Box::new(Merger::new(iters))
+        })
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,354 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    coding::{Decode, Encode},
+    SeqNo, UserKey,
+};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use std::{
+    io::{Read, Write},
+    ops::{Bound, RangeBounds},
+};
+
+/// A range tombstone deletes all versions of the keys in `[start, end)`
+/// that are older than the tombstone itself
+///
+/// Range tombstones are written using [`AbstractTree::remove_range`](crate::AbstractTree::remove_range).
+/// They are kept in the memtable, and move into the tree's version once the memtable is flushed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeTombstone {
+    /// Inclusive start key
+    pub start: UserKey,
+
+    /// Exclusive end key, `None` if the range is unbounded
+    pub end: Option<UserKey>,
+
+    /// Sequence number of the tombstone
+    pub seqno: SeqNo,
+}
+
+/// Returns the smallest key that is greater than `key`.
+fn successor(key: &[u8]) -> UserKey {
+    let mut next = Vec::with_capacity(key.len() + 1);
+    next.extend_from_slice(key);
+    next.push(0);
+    next.into()
+}
+
+impl RangeTombstone {
+    /// Normalizes a range into a half-open range tombstone.
+    ///
+    /// Returns `None` if the range is empty.
+    pub fn from_bounds<K: AsRef<[u8]>, R: RangeBounds<K>>(range: &R, seqno: SeqNo) -> Option<Self> {
+        let start = match range.start_bound() {
+            Bound::Included(key) => key.as_ref().into(),
+            Bound::Excluded(key) => successor(key.as_ref()),
+            Bound::Unbounded => UserKey::empty(),
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(key) => Some(successor(key.as_ref())),
+            Bound::Excluded(key) => Some(key.as_ref().into()),
+            Bound::Unbounded => None,
+        };
+
+        if end.as_ref().is_some_and(|end| *start >= **end) {
+            return None;
+        }
+
+        Some(Self { start, end, seqno })
+    }
+
+    /// Returns `true` if the key is inside the tombstone's range.
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        *self.start <= *key && self.end.as_ref().is_none_or(|end| key < &**end)
+    }
+
+    /// Returns `true` if the tombstone deletes the given version of a key.
+    pub fn covers(&self, key: &[u8], seqno: SeqNo) -> bool {
+        seqno < self.seqno && self.contains_key(key)
+    }
+
+    /// Returns the tombstone's range as bounds.
+    pub fn bounds(&self) -> (Bound<&[u8]>, Bound<&[u8]>) {
+        (
+            Bound::Included(&self.start),
+            self.end
+                .as_deref()
+                .map_or(Bound::Unbounded, Bound::Excluded),
+        )
+    }
+
+    /// Returns the approximate in-memory size of the tombstone.
+    pub fn size(&self) -> usize {
+        self.start.len()
+            + self.end.as_ref().map_or(0, |end| end.len())
+            + std::mem::size_of::<Self>()
+    }
+}
+
+/// A key range in which the same set of range tombstones applies
+#[derive(Debug)]
+struct Fragment {
+    /// Inclusive start key
+    start: UserKey,
+
+    /// Exclusive end key, `None` if the range is unbounded
+    end: Option<UserKey>,
+
+    /// Sequence numbers of the tombstones that cover the range, in ascending order
+    seqnos: Vec<SeqNo>,
+}
+
+/// Range tombstones, indexed for lookups in logarithmic time
+///
+/// The (possibly overlapping) tombstones are split into sorted, non-overlapping fragments,
+/// so the fragment of a key can be binary searched, instead of checking every tombstone.
+#[derive(Debug, Default)]
+pub struct RangeTombstoneIndex {
+    tombstones: Vec<RangeTombstone>,
+    fragments: Vec<Fragment>,
+}
+
+impl From<Vec<RangeTombstone>> for RangeTombstoneIndex {
+    fn from(tombstones: Vec<RangeTombstone>) -> Self {
+        let mut points = tombstones
+            .iter()
+            .flat_map(|rt| std::iter::once(&rt.start).chain(rt.end.as_ref()))
+            .cloned()
+            .collect::<Vec<_>>();
+        points.sort();
+        points.dedup();
+
+        let mut fragments = points
+            .iter()
+            .enumerate()
+            .map(|(idx, start)| Fragment {
+                start: start.clone(),
+                end: points.get(idx + 1).cloned(),
+                seqnos: vec![],
+            })
+            .collect::<Vec<_>>();
+
+        for rt in &tombstones {
+            let first = fragments.partition_point(|fragment| fragment.start < rt.start);
+
+            for fragment in fragments.iter_mut().skip(first) {
+                if rt.end.as_ref().is_some_and(|end| fragment.start >= *end) {
+                    break;
+                }
+                fragment.seqnos.push(rt.seqno);
+            }
+        }
+
+        fragments.retain(|fragment| !fragment.seqnos.is_empty());
+
+        for fragment in &mut fragments {
+            fragment.seqnos.sort_unstable();
+        }
+
+        Self {
+            tombstones,
+            fragments,
+        }
+    }
+}
+
+impl RangeTombstoneIndex {
+    /// Returns all range tombstones.
+    pub fn tombstones(&self) -> &[RangeTombstone] {
+        &self.tombstones
+    }
+
+    /// Returns `true` if a range tombstone that is visible at `read_seqno` deletes the given version of a key.
+    pub fn is_range_deleted(&self, key: &[u8], seqno: SeqNo, read_seqno: SeqNo) -> bool {
+        let idx = self
+            .fragments
+            .partition_point(|fragment| *fragment.start <= *key);
+
+        let Some(fragment) = idx.checked_sub(1).and_then(|idx| self.fragments.get(idx)) else {
+            return false;
+        };
+
+        if fragment.end.as_ref().is_some_and(|end| key >= &**end) {
+            return false;
+        }
+
+        // NOTE: The oldest tombstone that is newer than the version
+        let idx = fragment
+            .seqnos
+            .partition_point(|&rt_seqno| rt_seqno <= seqno);
+
+        fragment
+            .seqnos
+            .get(idx)
+            .is_some_and(|&rt_seqno| rt_seqno < read_seqno)
+    }
+}
+
+impl Encode for RangeTombstone {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), crate::Error> {
+        writer.write_u64::<LE>(self.seqno)?;
+
+        #[expect(clippy::cast_possible_truncation, reason = "keys are u16 length max")]
+        writer.write_u16::<LE>(self.start.len() as u16)?;
+        writer.write_all(&self.start)?;
+
+        if let Some(end) = &self.end {
+            writer.write_u8(1)?;
+
+            #[expect(clippy::cast_possible_truncation, reason = "keys are u16 length max")]
+            writer.write_u16::<LE>(end.len() as u16)?;
+            writer.write_all(end)?;
+        } else {
+            writer.write_u8(0)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Decode for RangeTombstone {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, crate::Error> {
+        let seqno = reader.read_u64::<LE>()?;
+
+        let start_len = reader.read_u16::<LE>()?;
+        let start = UserKey::from_reader(reader, start_len.into())?;
+
+        let end = match reader.read_u8()? {
+            0 => None,
+            1 => {
+                let end_len = reader.read_u16::<LE>()?;
+                Some(UserKey::from_reader(reader, end_len.into())?)
+            }
+            tag => return Err(crate::Error::InvalidTag(("RangeTombstoneEnd", tag))),
+        };
+
+        Ok(Self { start, end, seqno })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn range_tombstone_bounds() {
+        let rt = RangeTombstone::from_bounds(&("b".."d"), 5).expect("should not be empty");
+        assert!(!rt.contains_key(b"a"));
+        assert!(rt.contains_key(b"b"));
+        assert!(rt.contains_key(b"czzz"));
+        assert!(!rt.contains_key(b"d"));
+
+        let rt = RangeTombstone::from_bounds(&("b"..="d"), 5).expect("should not be empty");
+        assert!(rt.contains_key(b"d"));
+        assert!(!rt.contains_key(b"d\0"));
+
+        let rt =
+            RangeTombstone::from_bounds::<&str, _>(&(Bound::Excluded("b"), Bound::Unbounded), 5)
+                .expect("should not be empty");
+        assert!(!rt.contains_key(b"b"));
+        assert!(rt.contains_key(b"b\0"));
+        assert!(rt.contains_key(b"zzz"));
+
+        let rt = RangeTombstone::from_bounds::<&str, _>(&.., 5).expect("should not be empty");
+        assert!(rt.contains_key(b""));
+        assert!(rt.contains_key(b"a"));
+    }
+
+    #[test]
+    fn range_tombstone_empty() {
+        assert!(RangeTombstone::from_bounds(&("b".."b"), 5).is_none());
+        assert!(RangeTombstone::from_bounds(&("c".."b"), 5).is_none());
+        assert!(RangeTombstone::from_bounds(&("b"..="b"), 5).is_some());
+    }
+
+    #[test]
+    fn range_tombstone_covers() {
+        let rt = RangeTombstone::from_bounds(&("a".."z"), 5).expect("should not be empty");
+        assert!(rt.covers(b"b", 4));
+        assert!(!rt.covers(b"b", 5));
+        assert!(!rt.covers(b"b", 6));
+    }
+
+    #[test]
+    fn range_tombstone_index() {
+        let index = RangeTombstoneIndex::from(
+            [
+                RangeTombstone::from_bounds(&("b".."f"), 5),
+                RangeTombstone::from_bounds(&("d".."h"), 10),
+                RangeTombstone::from_bounds::<&str, _>(&("x"..), 3),
+            ]
+            .into_iter()
+            .map(|rt| rt.expect("should not be empty"))
+            .collect::<Vec<_>>(),
+        );
+        assert_eq!(3, index.tombstones().len());
+
+        for (key, seqno, read_seqno, expected) in [
+            ("a", 0, SeqNo::MAX, false),
+            ("b", 4, SeqNo::MAX, true),
+            ("b", 5, SeqNo::MAX, false),
+            ("b", 4, 5, false),
+            ("c", 4, 6, true),
+            ("d", 4, 6, true),
+            ("d", 7, SeqNo::MAX, true),
+            ("d", 7, 10, false),
+            ("e", 9, SeqNo::MAX, true),
+            ("f", 4, SeqNo::MAX, true),
+            ("f", 4, 6, false),
+            ("h", 0, SeqNo::MAX, false),
+            ("w", 0, SeqNo::MAX, false),
+            ("x", 2, SeqNo::MAX, true),
+            ("zzz", 2, SeqNo::MAX, true),
+            ("zzz", 3, SeqNo::MAX, false),
+        ] {
+            assert_eq!(
+                expected,
+                index.is_range_deleted(key.as_bytes(), seqno, read_seqno),
+                "{key}@{seqno} read at {read_seqno}",
+            );
+        }
+    }
+
+    #[test]
+    fn range_tombstone_index_matches_scan() {
+        let tombstones = (0..50u8)
+            .filter_map(|i| {
+                let start = [i % 7 * 3];
+                let end = [i % 7 * 3 + i % 5 + 1];
+                RangeTombstone::from_bounds(&(&start[..]..&end[..]), u64::from(i))
+            })
+            .collect::<Vec<_>>();
+        let index = RangeTombstoneIndex::from(tombstones.clone());
+
+        for key in 0..30u8 {
+            for seqno in 0..55 {
+                for read_seqno in [10, 30, SeqNo::MAX] {
+                    let expected = tombstones
+                        .iter()
+                        .any(|rt| rt.seqno < read_seqno && rt.covers(&[key], seqno));
+
+                    assert_eq!(expected, index.is_range_deleted(&[key], seqno, read_seqno));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn range_tombstone_roundtrip() -> crate::Result<()> {
+        for rt in [
+            RangeTombstone::from_bounds(&("a".."z"), 5),
+            RangeTombstone::from_bounds::<&str, _>(&("a"..), 7),
+        ] {
+            let rt = rt.expect("should not be empty");
+            let bytes = rt.encode_into_vec();
+            assert_eq!(rt, RangeTombstone::decode_from(&mut &bytes[..])?);
+        }
+
+        Ok(())
+    }
+}
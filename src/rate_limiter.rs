@@ -0,0 +1,181 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+struct State {
+    bytes_per_second: u64,
+
+    /// Available bytes, negative if writers are waiting for bytes
+    available: i128,
+
+    last_refill: Instant,
+}
+
+impl State {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.last_refill).as_nanos();
+        self.last_refill = now;
+
+        // NOTE: Allow bursts of up to one second worth of bytes
+        let capacity = i128::from(self.bytes_per_second);
+
+        #[expect(
+            clippy::cast_possible_wrap,
+            reason = "elapsed nanoseconds do not exceed i128::MAX"
+        )]
+        let refilled = (elapsed * u128::from(self.bytes_per_second) / 1_000_000_000) as i128;
+
+        self.available = (self.available + refilled).min(capacity);
+    }
+}
+
+/// Token bucket that limits the write throughput of flushes and compactions
+///
+/// Flushes and compactions request the bytes of every block they write,
+/// and are put to sleep while the rate is exceeded, so background work cannot
+/// saturate the disk and starve foreground reads.
+/// Writes can burst up to one second worth of bytes.
+///
+/// A rate limiter can be shared between multiple trees (see [`Config::with_rate_limiter`](crate::Config::with_rate_limiter)
+/// and [`Env::with_rate_limiter`](crate::Env::with_rate_limiter)), to limit their combined throughput.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{Config, RateLimiter};
+/// use std::sync::Arc;
+///
+/// // Limit flushes and compactions to 50 MiB/s
+/// let rate_limiter = Arc::new(RateLimiter::new(50 * 1_024 * 1_024));
+///
+/// let tree = Config::new(folder, Default::default())
+///     .with_rate_limiter(Some(rate_limiter.clone()))
+///     .open()?;
+///
+/// // Give background work more headroom at night
+/// rate_limiter.set_bytes_per_second(200 * 1_024 * 1_024);
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub struct RateLimiter {
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter that allows `bytes_per_second` bytes to be written per second.
+    ///
+    /// A rate of 0 disables the limit.
+    #[must_use]
+    pub fn new(bytes_per_second: u64) -> Self {
+        Self {
+            state: Mutex::new(State {
+                bytes_per_second,
+                available: i128::from(bytes_per_second),
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Returns the allowed bytes per second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    #[must_use]
+    pub fn bytes_per_second(&self) -> u64 {
+        self.state
+            .lock()
+            .expect("lock is poisoned")
+            .bytes_per_second
+    }
+
+    /// Changes the allowed bytes per second.
+    ///
+    /// A rate of 0 disables the limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn set_bytes_per_second(&self, bytes_per_second: u64) {
+        let mut state = self.state.lock().expect("lock is poisoned");
+        state.refill(Instant::now());
+        state.bytes_per_second = bytes_per_second;
+        state.available = state.available.min(i128::from(bytes_per_second));
+        drop(state);
+    }
+
+    /// Requests to write `bytes` bytes, blocking the caller until the rate allows it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn request(&self, bytes: u64) {
+        let mut state = self.state.lock().expect("lock is poisoned");
+
+        if state.bytes_per_second == 0 {
+            return;
+        }
+
+        state.refill(Instant::now());
+        state.available -= i128::from(bytes);
+
+        if state.available >= 0 {
+            return;
+        }
+
+        // NOTE: The caller waits until its share of the debt is paid off,
+        // later callers queue up behind it
+        let debt = state.available.unsigned_abs();
+        let nanos = debt * 1_000_000_000 / u128::from(state.bytes_per_second);
+        drop(state);
+
+        std::thread::sleep(Duration::from_nanos(
+            u64::try_from(nanos).unwrap_or(u64::MAX),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn rate_limiter_burst() {
+        let limiter = RateLimiter::new(1_000_000);
+
+        let start = Instant::now();
+        limiter.request(1_000_000);
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn rate_limiter_throttles() {
+        let limiter = RateLimiter::new(100_000);
+
+        let start = Instant::now();
+        limiter.request(100_000);
+        limiter.request(20_000);
+        limiter.request(20_000);
+        assert!(start.elapsed() >= Duration::from_millis(350));
+    }
+
+    #[test]
+    fn rate_limiter_unlimited() {
+        let limiter = RateLimiter::new(0);
+
+        let start = Instant::now();
+        limiter.request(u64::MAX);
+        limiter.request(u64::MAX);
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        limiter.set_bytes_per_second(1_000);
+        assert_eq!(1_000, limiter.bytes_per_second());
+    }
+}
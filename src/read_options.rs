@@ -0,0 +1,251 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{iter_guard::IterGuardImpl, UserKey};
+use std::ops::{Bound, RangeBounds};
+
+type BoxedIter = Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static>;
+
+/// Options for range reads, see [`AbstractTree::range_with_options`](crate::AbstractTree::range_with_options)
+///
+/// # Examples
+///
+/// ```
+/// use lsm_tree::ReadOptions;
+///
+/// // Scan at most 1000 items below "user2/", without populating the block cache
+/// let options = ReadOptions::default()
+///     .upper_bound("user2/")
+///     .limit(1_000)
+///     .fill_cache(false);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ReadOptions {
+    lower_bound: Bound<UserKey>,
+    upper_bound: Bound<UserKey>,
+    limit: Option<usize>,
+    key_only: bool,
+    fill_cache: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            lower_bound: Bound::Unbounded,
+            upper_bound: Bound::Unbounded,
+            limit: None,
+            key_only: false,
+            fill_cache: true,
+        }
+    }
+}
+
+impl ReadOptions {
+    /// Sets the inclusive lower bound of the read.
+    ///
+    /// The bound is intersected with the range that is read.
+    #[must_use]
+    pub fn lower_bound<K: Into<UserKey>>(mut self, key: K) -> Self {
+        self.lower_bound = Bound::Included(key.into());
+        self
+    }
+
+    /// Sets the exclusive upper bound of the read.
+    ///
+    /// The bound is intersected with the range that is read.
+    #[must_use]
+    pub fn upper_bound<K: Into<UserKey>>(mut self, key: K) -> Self {
+        self.upper_bound = Bound::Excluded(key.into());
+        self
+    }
+
+    /// Sets the maximum amount of items that are returned.
+    ///
+    /// Items returned by either end of the iterator count towards the limit,
+    /// and no more data is read once the limit is reached.
+    #[must_use]
+    pub fn limit(mut self, n: usize) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// If `true`, only keys are returned, and values are empty.
+    ///
+    /// For a key-value separated tree, this avoids reading blob files.
+    ///
+    /// Default = false
+    #[must_use]
+    pub fn key_only(mut self, key_only: bool) -> Self {
+        self.key_only = key_only;
+        self
+    }
+
+    /// If `false`, data blocks that are read from disk are not inserted into the block cache,
+    /// so long scans do not evict the working set of point reads.
+    ///
+    /// Blocks that are already cached are still used.
+    ///
+    /// Default = true
+    #[must_use]
+    pub fn fill_cache(mut self, fill_cache: bool) -> Self {
+        self.fill_cache = fill_cache;
+        self
+    }
+
+    pub(crate) fn is_key_only(&self) -> bool {
+        self.key_only
+    }
+
+    pub(crate) fn is_fill_cache(&self) -> bool {
+        self.fill_cache
+    }
+
+    /// Intersects the given range with the bounds of the read options.
+    ///
+    /// Returns `None` if the intersection is empty.
+    pub(crate) fn bounds<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: &R,
+    ) -> Option<(Bound<UserKey>, Bound<UserKey>)> {
+        let lo = tighter(to_owned_bound(range.start_bound()), &self.lower_bound, true);
+        let hi = tighter(to_owned_bound(range.end_bound()), &self.upper_bound, false);
+
+        let is_empty = match (&lo, &hi) {
+            (Bound::Included(lo), Bound::Included(hi)) => lo > hi,
+            (Bound::Included(lo) | Bound::Excluded(lo), Bound::Excluded(hi))
+            | (Bound::Excluded(lo), Bound::Included(hi)) => lo >= hi,
+            _ => false,
+        };
+
+        (!is_empty).then_some((lo, hi))
+    }
+
+    /// Applies the limit of the read options to an iterator.
+    pub(crate) fn limit_iter(&self, iter: BoxedIter) -> BoxedIter {
+        match self.limit {
+            Some(remaining) => Box::new(Limit { iter, remaining }),
+            None => iter,
+        }
+    }
+}
+
+fn to_owned_bound<K: AsRef<[u8]>>(bound: Bound<&K>) -> Bound<UserKey> {
+    match bound {
+        Bound::Included(key) => Bound::Included(key.as_ref().into()),
+        Bound::Excluded(key) => Bound::Excluded(key.as_ref().into()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// Returns the more restrictive of two lower (or upper) bounds.
+fn tighter(a: Bound<UserKey>, b: &Bound<UserKey>, is_lower: bool) -> Bound<UserKey> {
+    let (a_key, b_key) = match (&a, b) {
+        (_, Bound::Unbounded) => return a,
+        (Bound::Unbounded, b) => return b.clone(),
+        (Bound::Included(a) | Bound::Excluded(a), Bound::Included(b) | Bound::Excluded(b)) => {
+            (a, b)
+        }
+    };
+
+    match a_key.cmp(b_key) {
+        std::cmp::Ordering::Equal => {
+            if matches!(b, Bound::Excluded(_)) {
+                b.clone()
+            } else {
+                a
+            }
+        }
+        std::cmp::Ordering::Greater if is_lower => a,
+        std::cmp::Ordering::Less if !is_lower => a,
+        _ => b.clone(),
+    }
+}
+
+/// Stops an iterator after a given amount of items, counting both ends
+struct Limit {
+    iter: BoxedIter,
+    remaining: usize,
+}
+
+impl Iterator for Limit {
+    type Item = IterGuardImpl;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let item = self.iter.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl DoubleEndedIterator for Limit {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let item = self.iter.next_back()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+    use test_log::test;
+
+    fn key(s: &str) -> UserKey {
+        UserKey::from(s)
+    }
+
+    #[test]
+    fn read_options_bounds_unbounded() {
+        let options = ReadOptions::default();
+        assert_eq!(
+            Some((Unbounded, Unbounded)),
+            options.bounds::<&[u8], _>(&..)
+        );
+        assert_eq!(
+            Some((Included(key("a")), Excluded(key("c")))),
+            options.bounds(&("a".."c")),
+        );
+    }
+
+    #[test]
+    fn read_options_bounds_intersect() {
+        let options = ReadOptions::default().lower_bound("b").upper_bound("d");
+
+        assert_eq!(
+            Some((Included(key("b")), Excluded(key("d")))),
+            options.bounds::<&[u8], _>(&..),
+        );
+        assert_eq!(
+            Some((Included(key("c")), Excluded(key("d")))),
+            options.bounds(&("c"..="e")),
+        );
+        assert_eq!(
+            Some((Included(key("b")), Included(key("c")))),
+            options.bounds(&("a"..="c")),
+        );
+        assert_eq!(
+            Some((Excluded(key("b")), Excluded(key("d")))),
+            options.bounds::<&str, _>(&(Excluded("b"), Included("d"))),
+        );
+    }
+
+    #[test]
+    fn read_options_bounds_empty() {
+        let options = ReadOptions::default().lower_bound("c").upper_bound("d");
+
+        assert_eq!(None, options.bounds(&("a".."b")));
+        assert_eq!(None, options.bounds(&("a".."c")));
+        assert_eq!(None, options.bounds(&("d"..)));
+        assert!(options.bounds(&("a"..="c")).is_some());
+    }
+}
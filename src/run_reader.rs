@@ -15,19 +15,24 @@ pub struct RunReader {
     hi: usize,
     lo_reader: Option<BoxedIterator<'static>>,
     hi_reader: Option<BoxedIterator<'static>>,
+    fill_cache: bool,
 }
 
 impl RunReader {
+    /// Creates a reader over the tables of the run that overlap with the range.
+    ///
+    /// Data blocks that are loaded from disk are only inserted into the block cache if `fill_cache` is `true`.
     #[must_use]
     pub fn new<R: RangeBounds<UserKey> + Clone + Send + 'static>(
         run: Arc<Run<Table>>,
         range: R,
+        fill_cache: bool,
     ) -> Option<Self> {
         assert!(!run.is_empty(), "level reader cannot read empty level");
 
         let (lo, hi) = run.range_overlap_indexes(&range)?;
 
-        Some(Self::culled(run, range, (Some(lo), Some(hi))))
+        Some(Self::culled(run, range, (Some(lo), Some(hi)), fill_cache))
     }
 
     #[must_use]
@@ -35,18 +40,19 @@ impl RunReader {
         run: Arc<Run<Table>>,
         range: R,
         (lo, hi): (Option<usize>, Option<usize>),
+        fill_cache: bool,
     ) -> Self {
         let lo = lo.unwrap_or_default();
         let hi = hi.unwrap_or(run.len() - 1);
 
         // TODO: lazily init readers?
         let lo_table = run.deref().get(lo).expect("should exist");
-        let lo_reader = lo_table.range(range.clone());
+        let lo_reader = lo_table.range_with_fill_cache(range.clone(), fill_cache);
 
         // TODO: lazily init readers?
         let hi_reader = if hi > lo {
             let hi_table = run.deref().get(hi).expect("should exist");
-            Some(hi_table.range(range))
+            Some(hi_table.range_with_fill_cache(range, fill_cache))
         } else {
             None
         };
@@ -57,6 +63,7 @@ impl RunReader {
             hi,
             lo_reader: Some(Box::new(lo_reader)),
             hi_reader: hi_reader.map(|x| Box::new(x) as BoxedIterator),
+            fill_cache,
         }
     }
 }
@@ -77,7 +84,10 @@ impl Iterator for RunReader {
 
                 if self.lo < self.hi {
                     self.lo_reader = Some(Box::new(
-                        self.run.get(self.lo).expect("should exist").iter(),
+                        self.run
+                            .get(self.lo)
+                            .expect("should exist")
+                            .range_with_fill_cache(.., self.fill_cache),
                     ));
                 }
             } else if let Some(hi_reader) = &mut self.hi_reader {
@@ -106,7 +116,10 @@ impl DoubleEndedIterator for RunReader {
 
                 if self.lo < self.hi {
                     self.hi_reader = Some(Box::new(
-                        self.run.get(self.hi).expect("should exist").iter(),
+                        self.run
+                            .get(self.hi)
+                            .expect("should exist")
+                            .range_with_fill_cache(.., self.fill_cache),
                     ));
                 }
             } else if let Some(lo_reader) = &mut self.lo_reader {
@@ -155,9 +168,11 @@ mod tests {
 
         let level = Arc::new(Run::new(tables));
 
-        assert!(RunReader::new(level.clone(), UserKey::from("y")..=UserKey::from("z"),).is_none());
+        assert!(
+            RunReader::new(level.clone(), UserKey::from("y")..=UserKey::from("z"), true).is_none()
+        );
 
-        assert!(RunReader::new(level, UserKey::from("y")..).is_none());
+        assert!(RunReader::new(level, UserKey::from("y").., true).is_none());
 
         Ok(())
     }
@@ -191,7 +206,7 @@ mod tests {
         let level = Arc::new(Run::new(tables));
 
         {
-            let multi_reader = RunReader::new(level.clone(), ..).unwrap();
+            let multi_reader = RunReader::new(level.clone(), .., true).unwrap();
 
             let mut iter = multi_reader.flatten();
 
@@ -210,7 +225,7 @@ mod tests {
         }
 
         {
-            let multi_reader = RunReader::new(level.clone(), ..).unwrap();
+            let multi_reader = RunReader::new(level.clone(), .., true).unwrap();
 
             let mut iter = multi_reader.rev().flatten();
 
@@ -229,7 +244,7 @@ mod tests {
         }
 
         {
-            let multi_reader = RunReader::new(level.clone(), ..).unwrap();
+            let multi_reader = RunReader::new(level.clone(), .., true).unwrap();
 
             let mut iter = multi_reader.flatten();
 
@@ -248,7 +263,7 @@ mod tests {
         }
 
         {
-            let multi_reader = RunReader::new(level.clone(), UserKey::from("g")..).unwrap();
+            let multi_reader = RunReader::new(level.clone(), UserKey::from("g").., true).unwrap();
 
             let mut iter = multi_reader.flatten();
 
@@ -261,7 +276,7 @@ mod tests {
         }
 
         {
-            let multi_reader = RunReader::new(level, UserKey::from("g")..).unwrap();
+            let multi_reader = RunReader::new(level, UserKey::from("g").., true).unwrap();
 
             let mut iter = multi_reader.flatten().rev();
 
@@ -69,6 +69,18 @@ impl SequenceNumberCounter {
         seqno
     }
 
+    /// Reserves a contiguous range of sequence numbers, returning the first one.
+    pub(crate) fn reserve(&self, count: u64) -> SeqNo {
+        let seqno = self.0.fetch_add(count, Release);
+
+        assert!(
+            seqno.saturating_add(count) < 0x8000_0000_0000_0000,
+            "Ran out of sequence numbers",
+        );
+
+        seqno
+    }
+
     /// Sets the sequence number.
     pub fn set(&self, seqno: SeqNo) {
         self.0.store(seqno, Release);
@@ -218,6 +218,32 @@ mod tests {
         );
     }
 
+    #[test]
+    #[expect(clippy::indexing_slicing)]
+    fn slice_subslice_zero_copy() {
+        let slice = Slice::from(vec![7; 1_000]);
+
+        let subslice = slice.slice(100..900);
+        assert_eq!(&*subslice, &[7; 800]);
+        assert_eq!(subslice.as_ptr(), slice[100..].as_ptr());
+
+        assert_eq!(&*slice.slice(..), &*slice);
+        assert!(slice.slice(1_000..).is_empty());
+    }
+
+    #[test]
+    #[expect(clippy::indexing_slicing)]
+    #[cfg(feature = "bytes_1")]
+    fn slice_bytes_zero_copy() {
+        let bytes = bytes::Bytes::from(vec![7; 1_000]);
+
+        let slice = Slice::from(bytes.clone());
+        assert_eq!(slice.as_ptr(), bytes.as_ptr());
+
+        let bytes_again = bytes::Bytes::from(slice.slice(100..));
+        assert_eq!(bytes_again.as_ptr(), bytes[100..].as_ptr());
+    }
+
     /// This test verifies that we can create a `Slice` from various types and compare a `Slice` with them.
     #[test]
     fn test_slice_instantiation() {
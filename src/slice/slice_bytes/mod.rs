@@ -40,7 +40,14 @@ impl Slice {
         builder
     }
 
-    #[doc(hidden)]
+    /// Returns a subslice of the given range, sharing the underlying buffer.
+    ///
+    /// Block and blob buffers are subsliced this way when reading, so
+    /// keys and values do not need to be copied out of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
     #[must_use]
     pub fn slice(&self, range: impl std::ops::RangeBounds<usize>) -> Self {
         Self(self.0.slice(range))
@@ -87,6 +94,13 @@ impl From<Bytes> for Slice {
     }
 }
 
+// BytesMut::freeze is zero-copy
+impl From<Builder> for Slice {
+    fn from(value: Builder) -> Self {
+        Self(value.freeze())
+    }
+}
+
 impl From<Slice> for Bytes {
     fn from(value: Slice) -> Self {
         value.0
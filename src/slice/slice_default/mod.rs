@@ -28,7 +28,16 @@ impl Slice {
         ByteView::builder_unzeroed(len)
     }
 
-    pub(crate) fn slice(&self, range: impl std::ops::RangeBounds<usize>) -> Self {
+    /// Returns a subslice of the given range, sharing the underlying buffer.
+    ///
+    /// Block and blob buffers are subsliced this way when reading, so
+    /// keys and values do not need to be copied out of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    #[must_use]
+    pub fn slice(&self, range: impl std::ops::RangeBounds<usize>) -> Self {
         Self(self.0.slice(range))
     }
 
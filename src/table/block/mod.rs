@@ -21,9 +21,8 @@ pub(crate) use trailer::{Trailer, TRAILER_START_MARKER};
 use crate::{
     coding::{Decode, Encode},
     table::BlockHandle,
-    Checksum, CompressionType, Slice,
+    Checksum, CompressionType, Encryptor, FileHandle, Slice,
 };
-use std::fs::File;
 
 /// A block on disk
 ///
@@ -42,11 +41,14 @@ impl Block {
     }
 
     /// Encodes a block into a writer.
+    ///
+    /// If an encryptor is given, the (possibly compressed) data is encrypted.
     pub fn write_into<W: std::io::Write>(
         mut writer: &mut W,
         data: &[u8],
         block_type: BlockType,
         compression: CompressionType,
+        encryptor: Option<&dyn Encryptor>,
     ) -> crate::Result<Header> {
         let mut header = Header {
             block_type,
@@ -58,20 +60,29 @@ impl Block {
         };
 
         let data = match compression {
-            CompressionType::None => data,
+            CompressionType::None => std::borrow::Cow::Borrowed(data),
 
             #[cfg(feature = "lz4")]
-            CompressionType::Lz4 => &lz4_flex::compress(data),
+            CompressionType::Lz4 => std::borrow::Cow::Owned(lz4_flex::compress(data)),
+
+            CompressionType::Custom(id) => {
+                std::borrow::Cow::Owned(crate::compression::compress_custom(id, data)?)
+            }
+        };
+
+        let data = match encryptor {
+            Some(encryptor) => std::borrow::Cow::Owned(encryptor.encrypt(&data)?),
+            None => data,
         };
 
         #[expect(clippy::cast_possible_truncation, reason = "blocks are limited to u32")]
         {
             header.data_length = data.len() as u32;
-            header.checksum = Checksum::from_raw(crate::hash::hash128(data));
+            header.checksum = Checksum::from_raw(crate::hash::hash128(&data));
         }
 
         header.encode_into(&mut writer)?;
-        writer.write_all(data)?;
+        writer.write_all(&data)?;
 
         log::trace!(
             "Writing block with size {}B (compressed: {}B) (excluding header of {}B)",
@@ -87,6 +98,7 @@ impl Block {
     pub fn from_reader<R: std::io::Read>(
         reader: &mut R,
         compression: CompressionType,
+        encryptor: Option<&dyn Encryptor>,
     ) -> crate::Result<Self> {
         let header = Header::decode_from(reader)?;
         let raw_data = Slice::from_reader(reader, header.data_length as usize)?;
@@ -101,6 +113,11 @@ impl Block {
             );
         })?;
 
+        let raw_data = match encryptor {
+            Some(encryptor) => Slice::from(encryptor.decrypt(&raw_data)?),
+            None => raw_data,
+        };
+
         let data = match compression {
             CompressionType::None => raw_data,
 
@@ -115,6 +132,12 @@ impl Block {
 
                 builder.freeze().into()
             }
+
+            CompressionType::Custom(id) => Slice::from(crate::compression::decompress_custom(
+                id,
+                &raw_data,
+                header.uncompressed_length as usize,
+            )?),
         };
 
         debug_assert_eq!(header.uncompressed_length, {
@@ -129,9 +152,10 @@ impl Block {
 
     /// Reads a block from a file.
     pub fn from_file(
-        file: &File,
+        file: &dyn FileHandle,
         handle: BlockHandle,
         compression: CompressionType,
+        encryptor: Option<&dyn Encryptor>,
     ) -> crate::Result<Self> {
         let buf = crate::file::read_exact(file, *handle.offset(), handle.size() as usize)?;
 
@@ -148,34 +172,45 @@ impl Block {
             );
         })?;
 
+        let raw_data = match encryptor {
+            Some(encryptor) => {
+                // NOTE: We know that a header always exists and data is never empty
+                // So the slice is fine
+                #[expect(clippy::indexing_slicing)]
+                let ciphertext = &buf[Header::serialized_len()..];
+
+                Slice::from(encryptor.decrypt(ciphertext)?)
+            }
+            None => buf.slice(Header::serialized_len()..),
+        };
+
         let buf = match compression {
             CompressionType::None => {
-                let value = buf.slice(Header::serialized_len()..);
-
                 #[expect(clippy::cast_possible_truncation, reason = "values are u32 length max")]
                 {
-                    debug_assert_eq!(header.uncompressed_length, value.len() as u32);
+                    debug_assert_eq!(header.uncompressed_length, raw_data.len() as u32);
                 }
 
-                value
+                raw_data
             }
 
             #[cfg(feature = "lz4")]
             CompressionType::Lz4 => {
-                // NOTE: We know that a header always exists and data is never empty
-                // So the slice is fine
-                #[expect(clippy::indexing_slicing)]
-                let raw_data = &buf[Header::serialized_len()..];
-
                 #[warn(unsafe_code)]
                 let mut builder =
                     unsafe { Slice::builder_unzeroed(header.uncompressed_length as usize) };
 
-                lz4_flex::decompress_into(raw_data, &mut builder)
+                lz4_flex::decompress_into(&raw_data, &mut builder)
                     .map_err(|_| crate::Error::Decompress(compression))?;
 
                 builder.freeze().into()
             }
+
+            CompressionType::Custom(id) => Slice::from(crate::compression::decompress_custom(
+                id,
+                &raw_data,
+                header.uncompressed_length as usize,
+            )?),
         };
 
         Ok(Self { header, data: buf })
@@ -198,16 +233,41 @@ mod tests {
             b"abcdefabcdefabcdef",
             BlockType::Data,
             CompressionType::None,
+            None,
+        )?;
+
+        {
+            let mut reader = &writer[..];
+            let block = Block::from_reader(&mut reader, CompressionType::None, None)?;
+            assert_eq!(b"abcdefabcdefabcdef", &*block.data);
+        }
+
+        Ok(())
+    }
+    #[test]
+    fn block_roundtrip_encrypted() -> crate::Result<()> {
+        let encryptor = crate::encryption::XorEncryptor(0xAB);
+        let mut writer = vec![];
+
+        Block::write_into(
+            &mut writer,
+            b"abcdefabcdefabcdef",
+            BlockType::Data,
+            CompressionType::None,
+            Some(&encryptor),
         )?;
 
+        assert!(!writer.windows(6).any(|window| window == b"abcdef"));
+
         {
             let mut reader = &writer[..];
-            let block = Block::from_reader(&mut reader, CompressionType::None)?;
+            let block = Block::from_reader(&mut reader, CompressionType::None, Some(&encryptor))?;
             assert_eq!(b"abcdefabcdefabcdef", &*block.data);
         }
 
         Ok(())
     }
+
     #[test]
     #[cfg(feature = "lz4")]
     fn block_roundtrip_lz4() -> crate::Result<()> {
@@ -218,11 +278,12 @@ mod tests {
             b"abcdefabcdefabcdef",
             BlockType::Data,
             CompressionType::Lz4,
+            None,
         )?;
 
         {
             let mut reader = &writer[..];
-            let block = Block::from_reader(&mut reader, CompressionType::Lz4)?;
+            let block = Block::from_reader(&mut reader, CompressionType::Lz4, None)?;
             assert_eq!(b"abcdefabcdefabcdef", &*block.data);
         }
 
@@ -27,6 +27,9 @@ pub enum BlockIndexIterImpl {
     Full(self::full::Iter),
     Volatile(self::volatile::Iter),
     TwoLevel(self::two_level::Iter),
+
+    /// Yields the error of loading the block index, see [`crate::Table::preload`]
+    Error(Option<crate::Error>),
 }
 
 impl BlockIndexIter for BlockIndexIterImpl {
@@ -35,6 +38,7 @@ impl BlockIndexIter for BlockIndexIterImpl {
             Self::Full(i) => i.seek_lower(key),
             Self::Volatile(i) => i.seek_lower(key),
             Self::TwoLevel(i) => i.seek_lower(key),
+            Self::Error(_) => true,
         }
     }
 
@@ -43,6 +47,7 @@ impl BlockIndexIter for BlockIndexIterImpl {
             Self::Full(i) => i.seek_upper(key),
             Self::Volatile(i) => i.seek_upper(key),
             Self::TwoLevel(i) => i.seek_upper(key),
+            Self::Error(_) => true,
         }
     }
 }
@@ -55,6 +60,7 @@ impl Iterator for BlockIndexIterImpl {
             Self::Full(i) => i.next(),
             Self::Volatile(i) => i.next(),
             Self::TwoLevel(i) => i.next(),
+            Self::Error(e) => e.take().map(Err),
         }
     }
 }
@@ -65,6 +71,7 @@ impl DoubleEndedIterator for BlockIndexIterImpl {
             Self::Full(i) => i.next_back(),
             Self::Volatile(i) => i.next_back(),
             Self::TwoLevel(i) => i.next_back(),
+            Self::Error(e) => e.take().map(Err),
         }
     }
 }
@@ -98,9 +105,7 @@ impl BlockIndex for BlockIndexImpl {
         match self {
             Self::Full(index) => index.forward_reader(needle).map(BlockIndexIterImpl::Full),
             Self::VolatileFull(index) => {
-                let mut it = index.iter();
-                it.seek_lower(needle);
-                Some(BlockIndexIterImpl::Volatile(it))
+                Some(BlockIndexIterImpl::Volatile(index.forward_reader(needle)))
             }
             Self::TwoLevel(index) => {
                 let mut it = index.iter();
@@ -9,7 +9,7 @@ use crate::{
         block_index::{iter::OwnedIndexBlockIter, BlockIndexIter},
         util::load_block,
     },
-    Cache, CompressionType, DescriptorTable, GlobalTableId, UserKey,
+    Cache, CompressionType, DescriptorTable, Encryptor, Filesystem, GlobalTableId, UserKey,
 };
 use std::{path::PathBuf, sync::Arc};
 
@@ -23,9 +23,11 @@ pub struct TwoLevelBlockIndex {
     pub(crate) top_level_index: IndexBlock,
     pub(crate) table_id: GlobalTableId,
     pub(crate) path: PathBuf,
+    pub(crate) fs: Arc<dyn Filesystem>,
     pub(crate) descriptor_table: Arc<DescriptorTable>,
     pub(crate) cache: Arc<Cache>,
     pub(crate) compression: CompressionType,
+    pub(crate) encryptor: Option<Arc<dyn Encryptor>>,
 
     #[cfg(feature = "metrics")]
     pub(crate) metrics: Arc<Metrics>,
@@ -42,9 +44,11 @@ impl TwoLevelBlockIndex {
             hi: None,
             table_id: self.table_id,
             path: self.path.clone(),
+            fs: self.fs.clone(),
             descriptor_table: self.descriptor_table.clone(),
             cache: self.cache.clone(),
             compression: self.compression,
+            encryptor: self.encryptor.clone(),
 
             #[cfg(feature = "metrics")]
             metrics: self.metrics.clone(),
@@ -64,9 +68,11 @@ pub struct Iter {
 
     table_id: GlobalTableId,
     path: PathBuf,
+    fs: Arc<dyn Filesystem>,
     descriptor_table: Arc<DescriptorTable>,
     cache: Arc<Cache>,
     compression: CompressionType,
+    encryptor: Option<Arc<dyn Encryptor>>,
 
     #[cfg(feature = "metrics")]
     metrics: Arc<Metrics>,
@@ -126,11 +132,14 @@ impl Iterator for Iter {
                 let block = fail_iter!(load_block(
                     self.table_id,
                     &self.path,
+                    &*self.fs,
                     &self.descriptor_table,
                     &self.cache,
                     &handle.into_inner(),
                     BlockType::Index,
                     self.compression,
+                    self.encryptor.as_deref(),
+                    true,
                     #[cfg(feature = "metrics")]
                     &self.metrics,
                 ));
@@ -189,11 +198,14 @@ impl DoubleEndedIterator for Iter {
                 let block = fail_iter!(load_block(
                     self.table_id,
                     &self.path,
+                    &*self.fs,
                     &self.descriptor_table,
                     &self.cache,
                     &handle.into_inner(),
                     BlockType::Index,
                     self.compression,
+                    self.encryptor.as_deref(),
+                    true,
                     #[cfg(feature = "metrics")]
                     &self.metrics,
                 ));
@@ -10,7 +10,7 @@ use crate::{
         util::load_block,
         BlockHandle, IndexBlock,
     },
-    Cache, CompressionType, DescriptorTable, GlobalTableId, UserKey,
+    Cache, CompressionType, DescriptorTable, Encryptor, Filesystem, GlobalTableId, UserKey,
 };
 use std::{path::PathBuf, sync::Arc};
 
@@ -23,10 +23,12 @@ use crate::Metrics;
 pub struct VolatileBlockIndex {
     pub(crate) table_id: GlobalTableId,
     pub(crate) path: PathBuf,
+    pub(crate) fs: Arc<dyn Filesystem>,
     pub(crate) descriptor_table: Arc<DescriptorTable>,
     pub(crate) cache: Arc<Cache>,
     pub(crate) handle: BlockHandle,
     pub(crate) compression: CompressionType,
+    pub(crate) encryptor: Option<Arc<dyn Encryptor>>,
 
     #[cfg(feature = "metrics")]
     pub(crate) metrics: Arc<Metrics>,
@@ -48,10 +50,12 @@ pub struct Iter {
     inner: Option<OwnedIndexBlockIter>,
     table_id: GlobalTableId,
     path: PathBuf,
+    fs: Arc<dyn Filesystem>,
     descriptor_table: Arc<DescriptorTable>,
     cache: Arc<Cache>,
     handle: BlockHandle,
     compression: CompressionType,
+    encryptor: Option<Arc<dyn Encryptor>>,
 
     lo: Option<UserKey>,
     hi: Option<UserKey>,
@@ -66,10 +70,12 @@ impl Iter {
             inner: None,
             table_id: index.table_id,
             path: index.path.clone(),
+            fs: index.fs.clone(),
             descriptor_table: index.descriptor_table.clone(),
             cache: index.cache.clone(),
             handle: index.handle,
             compression: index.compression,
+            encryptor: index.encryptor.clone(),
 
             lo: None,
             hi: None,
@@ -102,11 +108,14 @@ impl Iterator for Iter {
             let block = fail_iter!(load_block(
                 self.table_id,
                 &self.path,
+                &*self.fs,
                 &self.descriptor_table,
                 &self.cache,
                 &self.handle,
                 BlockType::Index,
                 self.compression,
+                self.encryptor.as_deref(),
+                true,
                 #[cfg(feature = "metrics")]
                 &self.metrics,
             ));
@@ -142,11 +151,14 @@ impl DoubleEndedIterator for Iter {
             let block = fail_iter!(load_block(
                 self.table_id,
                 &self.path,
+                &*self.fs,
                 &self.descriptor_table,
                 &self.cache,
                 &self.handle,
                 BlockType::Index,
                 self.compression,
+                self.encryptor.as_deref(),
+                true,
                 #[cfg(feature = "metrics")]
                 &self.metrics,
             ));
@@ -0,0 +1,239 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{Block, DataBlock, Writer};
+use crate::{
+    config::FilterPolicyEntry, table::block::BlockType, Checksum, CompressionType, Config,
+    Encryptor, Filesystem, InternalValue, TableId, UserKey, UserValue, ValueType,
+};
+use std::{
+    io::{BufWriter, Read, Seek},
+    path::Path,
+};
+
+/// Writes a standalone table file from sorted input
+///
+/// The table file can be bulk loaded into a tree using [`AbstractTree::ingest_tables`](crate::AbstractTree::ingest_tables),
+/// which is much cheaper than writing the same items through the memtable.
+///
+/// The table is written using the block settings of the last level of the given config,
+/// so the config should match the config of the tree the table is ingested into.
+///
+/// Items NEED to be added in ascending key order.
+///
+/// # Examples
+///
+/// ```
+/// # use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter, SstBuilder};
+/// #
+/// # let folder = tempfile::tempdir()?;
+/// # let sst_folder = tempfile::tempdir()?;
+/// let seqno = SequenceNumberCounter::default();
+/// let visible_seqno = SequenceNumberCounter::default();
+///
+/// let config = Config::new(&folder, seqno.clone());
+///
+/// let path = sst_folder.path().join("0.sst");
+/// let mut builder = SstBuilder::new(&path, &config)?;
+/// builder.insert("a", "abc")?;
+/// builder.insert("b", "def")?;
+/// builder.finish()?;
+///
+/// let tree = config.open()?;
+/// tree.ingest_tables(&[path], &seqno, &visible_seqno)?;
+/// assert_eq!(2, tree.len(SeqNo::MAX, None)?);
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub struct SstBuilder {
+    writer: Writer,
+    last_key: Option<UserKey>,
+}
+
+impl SstBuilder {
+    /// Creates a new table file at the given path.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or the file already exists.
+    pub fn new<P: AsRef<Path>>(path: P, config: &Config) -> crate::Result<Self> {
+        let level = usize::from(config.level_count - 1);
+
+        #[expect(clippy::cast_possible_truncation, reason = "level count is u8")]
+        let mut writer = Writer::new(
+            config.fs.clone(),
+            path.as_ref().to_path_buf(),
+            0,
+            level as u8,
+        )?
+        .use_clock(config.clock.clone())
+        .use_encryption(config.encryptor.clone())
+        .use_key_extractor(config.key_extractor.clone())
+        .use_data_block_restart_interval(config.data_block_restart_interval_policy.get(level))
+        .use_index_block_restart_interval(config.index_block_restart_interval_policy.get(level))
+        .use_data_block_compression(config.data_block_compression_policy.get(level))
+        .use_index_block_compression(config.index_block_compression_policy.get(level))
+        .use_data_block_size(config.data_block_size_policy.get(level))
+        .use_data_block_hash_ratio(config.data_block_hash_ratio_policy.get(level))
+        .use_bloom_policy({
+            if let FilterPolicyEntry::Bloom(p) = config.filter_policy.get(level) {
+                p
+            } else {
+                crate::config::BloomConstructionPolicy::BitsPerKey(0.0)
+            }
+        });
+
+        if config.index_block_partitioning_policy.get(level) {
+            writer = writer.use_partitioned_index();
+        }
+        if config.filter_block_partitioning_policy.get(level) {
+            writer = writer.use_partitioned_filter();
+        }
+
+        Ok(Self {
+            writer,
+            last_key: None,
+        })
+    }
+
+    fn write(
+        &mut self,
+        key: UserKey,
+        value: UserValue,
+        value_type: ValueType,
+    ) -> crate::Result<()> {
+        if let Some(last_key) = &self.last_key {
+            assert!(
+                key > last_key,
+                "next key in table builder was not greater than last key, last: {last_key:?}, next: {key:?}",
+            );
+        }
+        self.last_key = Some(key.clone());
+
+        // NOTE: The sequence number is assigned when the table is ingested
+        self.writer
+            .write(InternalValue::from_components(key, value, 0, value_type))
+    }
+
+    /// Writes a key-value pair.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not greater than the previously written key.
+    pub fn insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> crate::Result<()> {
+        self.write(key.into(), value.into(), ValueType::Value)
+    }
+
+    /// Writes a tombstone, deleting the key from the tree the table is ingested into.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not greater than the previously written key.
+    pub fn remove<K: Into<UserKey>>(&mut self, key: K) -> crate::Result<()> {
+        self.write(key.into(), UserValue::empty(), ValueType::Tombstone)
+    }
+
+    /// Finishes the table file, making sure it is written durably.
+    ///
+    /// If no item was written, no file is created.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn finish(self) -> crate::Result<()> {
+        self.writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Copies a table file, changing its table ID.
+///
+/// The table ID is only stored in the meta block, so all other sections are copied as-is,
+/// without decoding their blocks.
+///
+/// Returns the checksum of the new table file.
+pub fn copy_with_table_id(
+    fs: &dyn Filesystem,
+    encryptor: Option<&dyn Encryptor>,
+    from: &Path,
+    to: &Path,
+    table_id: TableId,
+) -> crate::Result<Checksum> {
+    let mut file = fs.open(from)?;
+    let trailer = sfa::Reader::from_reader(&mut file)?;
+
+    let mut writer = crate::archive::Writer::from_writer(BufWriter::new(fs.create_new(to)?));
+
+    for section in trailer.toc().iter() {
+        writer.start(section.name())?;
+
+        if section.name() == b"meta" {
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "the meta block is never 4 GiB in size"
+            )]
+            let handle =
+                super::BlockHandle::new(super::BlockOffset(section.pos()), section.len() as u32);
+
+            let block = Block::from_file(&*file, handle, CompressionType::None, encryptor)?;
+
+            if block.header.block_type != BlockType::Meta {
+                return Err(crate::Error::InvalidTag((
+                    "BlockType",
+                    block.header.block_type.into(),
+                )));
+            }
+
+            let block = DataBlock::new(block);
+
+            let items = block
+                .iter()
+                .map(|item| {
+                    use super::block::ParsedItem;
+
+                    let mut item = item.materialize(block.as_slice());
+
+                    if &*item.key.user_key == b"id" {
+                        item.value = table_id.to_le_bytes().into();
+                    }
+
+                    item
+                })
+                .collect::<Vec<_>>();
+
+            let buf = DataBlock::encode_into_vec(&items, 1, 0.0)?;
+
+            Block::write_into(
+                &mut writer,
+                &buf,
+                BlockType::Meta,
+                CompressionType::None,
+                encryptor,
+            )?;
+        } else {
+            file.seek(std::io::SeekFrom::Start(section.pos()))?;
+            std::io::copy(&mut (&mut file).take(section.len()), &mut writer)?;
+        }
+    }
+
+    let checksum = writer.finish()?;
+
+    if let Some(folder) = to.parent() {
+        fs.sync_directory(folder)?;
+    }
+
+    Ok(checksum)
+}
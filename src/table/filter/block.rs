@@ -23,3 +23,48 @@ impl FilterBlock {
         self.0.size()
     }
 }
+
+/// Filter over the derived keys of a [`KeyExtractor`](crate::KeyExtractor)
+///
+/// Consists of the extractor name, followed by a Bloom filter,
+/// which is empty if the table contains no derived keys.
+#[derive(Clone)]
+pub struct DerivedKeyFilterBlock(Block);
+
+impl DerivedKeyFilterBlock {
+    #[must_use]
+    pub fn new(block: Block) -> Self {
+        Self(block)
+    }
+
+    fn name_len(&self) -> usize {
+        self.0
+            .data
+            .first()
+            .copied()
+            .map(usize::from)
+            .unwrap_or_default()
+    }
+
+    /// Returns the name of the key extractor the filter was built with.
+    #[must_use]
+    pub fn extractor_name(&self) -> &[u8] {
+        self.0.data.get(1..=self.name_len()).unwrap_or_default()
+    }
+
+    pub fn maybe_contains_hash(&self, hash: u64) -> crate::Result<bool> {
+        let filter = self.0.data.get(1 + self.name_len()..).unwrap_or_default();
+
+        if filter.is_empty() {
+            return Ok(false);
+        }
+
+        Ok(StandardBloomFilterReader::new(filter)?.contains_hash(hash))
+    }
+
+    /// Returns the block size in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.0.size()
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{Table, TableId};
+use crate::{KeyRange, SeqNo};
+use std::path::PathBuf;
+
+/// Metadata of a table of a tree, see [`AbstractTree::tables`](crate::AbstractTree::tables)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TableInfo {
+    /// ID of the table
+    pub id: TableId,
+
+    /// Level the table is in
+    pub level: usize,
+
+    /// Path of the table file
+    pub path: PathBuf,
+
+    /// Size of the table, as used by [`AbstractTree::disk_space`](crate::AbstractTree::disk_space)
+    pub file_size: u64,
+
+    /// Smallest and largest key of the table
+    pub key_range: KeyRange,
+
+    /// Number of items (including tombstones) in the table
+    pub item_count: u64,
+
+    /// Number of tombstones (including weak tombstones) in the table
+    pub tombstone_count: u64,
+
+    /// Lowest and highest sequence number of the table
+    pub seqnos: (SeqNo, SeqNo),
+
+    /// Creation time of the table, in nanoseconds since the Unix epoch
+    pub created_at: u128,
+}
+
+impl TableInfo {
+    pub(crate) fn new(table: &Table, level: usize) -> Self {
+        Self {
+            id: table.id(),
+            level,
+            path: (*table.path).clone(),
+            file_size: table.file_size(),
+            key_range: table.metadata.key_range.clone(),
+            item_count: table.metadata.item_count,
+            tombstone_count: table.tombstone_count(),
+            seqnos: table.metadata.seqnos,
+            created_at: table.metadata.created_at.into(),
+        }
+    }
+}
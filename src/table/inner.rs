@@ -9,9 +9,12 @@ use super::{block_index::BlockIndexImpl, meta::ParsedMeta, regions::ParsedRegion
 use crate::{
     cache::Cache,
     descriptor_table::DescriptorTable,
-    table::{filter::block::FilterBlock, IndexBlock},
+    table::{
+        filter::block::{DerivedKeyFilterBlock, FilterBlock},
+        IndexBlock,
+    },
     tree::inner::TreeId,
-    Checksum, GlobalTableId,
+    Checksum, Encryptor, Filesystem, GlobalTableId,
 };
 use std::{
     path::PathBuf,
@@ -23,6 +26,10 @@ pub struct Inner {
 
     pub(crate) tree_id: TreeId,
 
+    pub(crate) fs: Arc<dyn Filesystem>,
+
+    pub(crate) encryptor: Option<Arc<dyn Encryptor>>,
+
     #[doc(hidden)]
     pub descriptor_table: Arc<DescriptorTable>,
 
@@ -34,23 +41,26 @@ pub struct Inner {
     #[doc(hidden)]
     pub regions: ParsedRegions,
 
-    /// Translates key (first item of a block) to block offset (address inside file) and (compressed) size
-    #[doc(hidden)]
-    pub block_index: Arc<BlockIndexImpl>,
-
     /// Block cache
     ///
     /// Stores index and data blocks
     #[doc(hidden)]
     pub cache: Arc<Cache>,
 
-    pub(super) pinned_filter_index: Option<IndexBlock>,
+    /// Blocks that are kept in memory, loaded on first access, see [`crate::Table::preload`]
+    pub(super) pinned: OnceLock<PinnedBlocks>,
 
-    /// Pinned AMQ filter
-    pub pinned_filter_block: Option<FilterBlock>,
+    /// Whether to pin the filter block once it is loaded
+    pub(super) pin_filter: bool,
+
+    /// Whether to pin the block index once it is loaded
+    pub(super) pin_index: bool,
 
     pub is_deleted: AtomicBool,
 
+    /// Whether the file descriptor is pinned in the descriptor table, see [`crate::Table::pin_file`]
+    pub(super) is_file_pinned: AtomicBool,
+
     pub(super) checksum: Checksum,
 
     #[cfg(feature = "metrics")]
@@ -61,14 +71,35 @@ pub struct Inner {
     pub(crate) cached_blob_bytes: OnceLock<u64>,
 }
 
+/// Index and filter blocks of a table that are kept in memory
+pub struct PinnedBlocks {
+    /// Translates key (first item of a block) to block offset (address inside file) and (compressed) size
+    pub(super) block_index: BlockIndexImpl,
+
+    pub(super) filter_index: Option<IndexBlock>,
+
+    /// Pinned AMQ filter
+    pub(super) filter_block: Option<FilterBlock>,
+
+    /// Pinned AMQ filter over derived keys, see [`crate::KeyExtractor`]
+    pub(super) derived_key_filter: Option<DerivedKeyFilterBlock>,
+}
+
 impl Drop for Inner {
     fn drop(&mut self) {
         let global_id: GlobalTableId = (self.tree_id, self.metadata.id).into();
 
+        if self
+            .is_file_pinned
+            .load(std::sync::atomic::Ordering::Acquire)
+        {
+            self.descriptor_table.unpin_table(&global_id);
+        }
+
         if self.is_deleted.load(std::sync::atomic::Ordering::Acquire) {
             log::trace!("Cleanup deleted table {global_id:?} at {:?}", self.path);
 
-            if let Err(e) = std::fs::remove_file(&*self.path) {
+            if let Err(e) = self.fs.remove_file(&self.path) {
                 log::warn!(
                     "Failed to cleanup deleted table {global_id:?} at {:?}: {e:?}",
                     self.path,
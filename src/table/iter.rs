@@ -10,7 +10,7 @@ use crate::{
         util::load_block,
         BlockHandle,
     },
-    Cache, CompressionType, DescriptorTable, InternalValue, SeqNo, UserKey,
+    Cache, CompressionType, DescriptorTable, Encryptor, Filesystem, InternalValue, SeqNo, UserKey,
 };
 use self_cell::self_cell;
 use std::{path::PathBuf, sync::Arc};
@@ -93,6 +93,7 @@ fn create_data_block_reader(block: DataBlock) -> OwnedDataBlockIter {
 pub struct Iter {
     table_id: GlobalTableId,
     path: Arc<PathBuf>,
+    fs: Arc<dyn Filesystem>,
 
     #[expect(clippy::struct_field_names)]
     index_iter: BlockIndexIterImpl,
@@ -100,6 +101,7 @@ pub struct Iter {
     descriptor_table: Arc<DescriptorTable>,
     cache: Arc<Cache>,
     compression: CompressionType,
+    encryptor: Option<Arc<dyn Encryptor>>,
 
     index_initialized: bool,
 
@@ -111,28 +113,36 @@ pub struct Iter {
 
     range: Bounds,
 
+    /// Whether data blocks that are loaded from disk are inserted into the block cache
+    fill_cache: bool,
+
     #[cfg(feature = "metrics")]
     metrics: Arc<Metrics>,
 }
 
 impl Iter {
+    #[expect(clippy::too_many_arguments)]
     pub fn new(
         table_id: GlobalTableId,
         path: Arc<PathBuf>,
+        fs: Arc<dyn Filesystem>,
         index_iter: BlockIndexIterImpl,
         descriptor_table: Arc<DescriptorTable>,
         cache: Arc<Cache>,
         compression: CompressionType,
+        encryptor: Option<Arc<dyn Encryptor>>,
         #[cfg(feature = "metrics")] metrics: Arc<Metrics>,
     ) -> Self {
         Self {
             table_id,
             path,
+            fs,
 
             index_iter,
             descriptor_table,
             cache,
             compression,
+            encryptor,
 
             index_initialized: false,
 
@@ -144,6 +154,8 @@ impl Iter {
 
             range: (None, None),
 
+            fill_cache: true,
+
             #[cfg(feature = "metrics")]
             metrics,
         }
@@ -156,6 +168,10 @@ impl Iter {
     pub fn set_upper_bound(&mut self, bound: Bound) {
         self.range.1 = Some(bound);
     }
+
+    pub fn set_fill_cache(&mut self, fill_cache: bool) {
+        self.fill_cache = fill_cache;
+    }
 }
 
 impl Iterator for Iter {
@@ -233,11 +249,14 @@ impl Iterator for Iter {
                     fail_iter!(load_block(
                         self.table_id,
                         &self.path,
+                        &*self.fs,
                         &self.descriptor_table,
                         &self.cache,
                         &BlockHandle::new(handle.offset(), handle.size()),
                         crate::table::block::BlockType::Data,
                         self.compression,
+                        self.encryptor.as_deref(),
+                        self.fill_cache,
                         #[cfg(feature = "metrics")]
                         &self.metrics,
                     ))
@@ -338,11 +357,14 @@ impl DoubleEndedIterator for Iter {
                     fail_iter!(load_block(
                         self.table_id,
                         &self.path,
+                        &*self.fs,
                         &self.descriptor_table,
                         &self.cache,
                         &BlockHandle::new(handle.offset(), handle.size()),
                         crate::table::block::BlockType::Data,
                         self.compression,
+                        self.encryptor.as_deref(),
+                        self.fill_cache,
                         #[cfg(feature = "metrics")]
                         &self.metrics,
                     ))
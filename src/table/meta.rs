@@ -3,9 +3,12 @@
 // (found in the LICENSE-* files in the repository)
 
 use super::{Block, BlockHandle, DataBlock};
-use crate::{coding::Decode, table::block::BlockType, CompressionType, KeyRange, SeqNo, TableId};
+use crate::{
+    coding::Decode, table::block::BlockType, CompressionType, Encryptor, FileHandle, KeyRange,
+    SeqNo, TableId,
+};
 use byteorder::{LittleEndian, ReadBytesExt};
-use std::{fs::File, ops::Deref};
+use std::ops::Deref;
 
 /// Nanosecond timestamp.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
@@ -73,8 +76,12 @@ macro_rules! read_u64 {
 
 impl ParsedMeta {
     #[expect(clippy::expect_used, clippy::too_many_lines)]
-    pub fn load_with_handle(file: &File, handle: &BlockHandle) -> crate::Result<Self> {
-        let block = Block::from_file(file, *handle, CompressionType::None)?;
+    pub fn load_with_handle(
+        file: &dyn FileHandle,
+        handle: &BlockHandle,
+        encryptor: Option<&dyn Encryptor>,
+    ) -> crate::Result<Self> {
+        let block = Block::from_file(file, *handle, CompressionType::None, encryptor)?;
 
         if block.header.block_type != BlockType::Meta {
             return Err(crate::Error::InvalidTag((
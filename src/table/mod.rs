@@ -4,10 +4,12 @@
 
 pub mod block;
 pub(crate) mod block_index;
+pub(crate) mod builder;
 pub mod data_block;
 pub mod filter;
 mod id;
 mod index_block;
+mod info;
 mod inner;
 mod iter;
 mod meta;
@@ -21,9 +23,11 @@ pub mod writer;
 mod tests;
 
 pub use block::{Block, BlockOffset};
+pub use builder::SstBuilder;
 pub use data_block::DataBlock;
 pub use id::{GlobalTableId, TableId};
 pub use index_block::{BlockHandle, IndexBlock, KeyedBlockHandle};
+pub use info::TableInfo;
 pub use scanner::Scanner;
 pub use writer::Writer;
 
@@ -32,23 +36,26 @@ use crate::{
     descriptor_table::DescriptorTable,
     table::{
         block::{BlockType, ParsedItem},
-        block_index::{BlockIndex, FullBlockIndex, TwoLevelBlockIndex, VolatileBlockIndex},
-        filter::block::FilterBlock,
+        block_index::{
+            BlockIndex, BlockIndexIter, BlockIndexIterImpl, FullBlockIndex, TwoLevelBlockIndex,
+            VolatileBlockIndex,
+        },
+        filter::block::{DerivedKeyFilterBlock, FilterBlock},
         regions::ParsedRegions,
         writer::LinkedFile,
     },
-    Checksum, CompressionType, InternalValue, SeqNo, TreeId, UserKey,
+    Checksum, CompressionType, Encryptor, FileHandle, Filesystem, InternalValue, SeqNo, TreeId,
+    UserKey,
 };
 use block_index::BlockIndexImpl;
-use inner::Inner;
+use inner::{Inner, PinnedBlocks};
 use iter::Iter;
 use std::{
     borrow::Cow,
-    fs::File,
-    io::{BufReader, Read, Seek},
+    io::Read,
     ops::{Bound, RangeBounds},
     path::PathBuf,
-    sync::Arc,
+    sync::{Arc, OnceLock},
 };
 use util::load_block;
 
@@ -115,10 +122,9 @@ impl Table {
         use byteorder::{ReadBytesExt, LE};
 
         Ok(if let Some(handle) = &self.regions.linked_blob_files {
-            let reader = File::open(&*self.path)?;
-            let mut reader = BufReader::new(reader);
-            reader.seek(std::io::SeekFrom::Start(*handle.offset()))?;
-            let mut reader = reader.take(u64::from(handle.size()));
+            let file = self.open_file()?;
+            let bytes = crate::file::read_exact(&*file, *handle.offset(), handle.size() as usize)?;
+            let mut reader = &bytes[..];
 
             let mut blob_files = vec![];
 
@@ -150,22 +156,61 @@ impl Table {
         (self.tree_id, self.id()).into()
     }
 
+    /// Returns the on-disk size of the filter blocks in bytes.
     #[must_use]
     pub fn filter_size(&self) -> usize {
-        unimplemented!()
+        [self.regions.filter, self.regions.filter_tli]
+            .into_iter()
+            .flatten()
+            .map(|handle| handle.size() as usize)
+            .sum()
     }
 
     #[must_use]
     pub fn pinned_filter_size(&self) -> usize {
-        self.pinned_filter_block
-            .as_ref()
+        self.pinned
+            .get()
+            .and_then(|pinned| pinned.filter_block.as_ref())
             .map(FilterBlock::size)
             .unwrap_or_default()
     }
 
+    /// Returns the size of the pinned derived key filter in bytes.
+    #[must_use]
+    pub fn derived_key_filter_size(&self) -> usize {
+        self.pinned
+            .get()
+            .and_then(|pinned| pinned.derived_key_filter.as_ref())
+            .map(DerivedKeyFilterBlock::size)
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if the table may contain a key with the given derived key hash.
+    ///
+    /// Returns `None` if the table has no derived key filter built by the given key extractor.
+    pub(crate) fn derived_key_may_exist(
+        &self,
+        extractor_name: &str,
+        hash: u64,
+    ) -> crate::Result<Option<bool>> {
+        let Some(filter) = &self.pinned()?.derived_key_filter else {
+            return Ok(None);
+        };
+
+        if filter.extractor_name() != extractor_name.as_bytes() {
+            return Ok(None);
+        }
+
+        filter.maybe_contains_hash(hash).map(Some)
+    }
+
     #[must_use]
     pub fn pinned_block_index_size(&self) -> usize {
-        match &*self.block_index {
+        let Some(pinned) = self.pinned.get() else {
+            return 0;
+        };
+
+        match &pinned.block_index {
             BlockIndexImpl::Full(full_block_index) => full_block_index.inner().inner.size(),
             BlockIndexImpl::VolatileFull(_) => 0,
             BlockIndexImpl::TwoLevel(two_level_block_index) => {
@@ -192,11 +237,14 @@ impl Table {
         load_block(
             self.global_id(),
             &self.path,
+            &*self.fs,
             &self.descriptor_table,
             &self.cache,
             handle,
             block_type,
             compression,
+            self.encryptor.as_deref(),
+            true,
             #[cfg(feature = "metrics")]
             &self.metrics,
         )
@@ -216,22 +264,13 @@ impl Table {
         self.metadata.file_size
     }
 
-    pub fn get(
-        &self,
-        key: &[u8],
-        seqno: SeqNo,
-        key_hash: u64,
-    ) -> crate::Result<Option<InternalValue>> {
-        #[cfg(feature = "metrics")]
-        use std::sync::atomic::Ordering::Relaxed;
-
-        if self.metadata.seqnos.0 >= seqno {
-            return Ok(None);
-        }
+    /// Returns the filter block that covers the given key, if the table has a filter.
+    fn load_filter_block(&self, key: &[u8]) -> crate::Result<Option<Cow<'_, FilterBlock>>> {
+        let pinned = self.pinned()?;
 
-        let filter_block = if let Some(block) = &self.pinned_filter_block {
+        let filter_block = if let Some(block) = &pinned.filter_block {
             Some(Cow::Borrowed(block))
-        } else if let Some(filter_idx) = &self.pinned_filter_index {
+        } else if let Some(filter_idx) = &pinned.filter_index {
             let mut iter = filter_idx.iter();
             iter.seek(key);
 
@@ -264,7 +303,41 @@ impl Table {
             None
         };
 
-        if let Some(filter_block) = filter_block {
+        Ok(filter_block)
+    }
+
+    /// Returns `false` if the table definitely does not contain the key.
+    ///
+    /// Only checks the key range and filter, so no data blocks are read.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn key_may_exist(&self, key: &[u8], key_hash: u64) -> crate::Result<bool> {
+        if !self.is_key_in_key_range(key) {
+            return Ok(false);
+        }
+
+        match self.load_filter_block(key)? {
+            Some(filter_block) => filter_block.maybe_contains_hash(key_hash),
+            None => Ok(true),
+        }
+    }
+
+    pub fn get(
+        &self,
+        key: &[u8],
+        seqno: SeqNo,
+        key_hash: u64,
+    ) -> crate::Result<Option<InternalValue>> {
+        #[cfg(feature = "metrics")]
+        use std::sync::atomic::Ordering::Relaxed;
+
+        if self.metadata.seqnos.0 >= seqno {
+            return Ok(None);
+        }
+
+        if let Some(filter_block) = self.load_filter_block(key)? {
             #[cfg(feature = "metrics")]
             self.metrics.filter_queries.fetch_add(1, Relaxed);
 
@@ -284,7 +357,7 @@ impl Table {
     // TODO: we would need to return something like ValueType + Value
     // TODO: so the caller can decide whether to return the value or not
     fn point_read(&self, key: &[u8], seqno: SeqNo) -> crate::Result<Option<InternalValue>> {
-        let Some(iter) = self.block_index.forward_reader(key) else {
+        let Some(iter) = self.pinned()?.block_index.forward_reader(key) else {
             return Ok(None);
         };
 
@@ -327,9 +400,11 @@ impl Table {
             .expect("data block count should fit");
 
         Scanner::new(
+            &*self.fs,
             &self.path,
             block_count,
             self.metadata.data_block_compression,
+            self.encryptor.clone(),
         )
     }
 
@@ -355,15 +430,30 @@ impl Table {
         &self,
         range: R,
     ) -> impl DoubleEndedIterator<Item = crate::Result<InternalValue>> + Send {
-        let index_iter = self.block_index.iter();
+        self.range_with_fill_cache(range, true)
+    }
+
+    /// Like [`Table::range`], but data blocks that are loaded from disk
+    /// are only inserted into the block cache if `fill_cache` is `true`.
+    pub(crate) fn range_with_fill_cache<R: RangeBounds<UserKey> + Send>(
+        &self,
+        range: R,
+        fill_cache: bool,
+    ) -> impl DoubleEndedIterator<Item = crate::Result<InternalValue>> + Send {
+        let index_iter = match self.pinned() {
+            Ok(pinned) => pinned.block_index.iter(),
+            Err(e) => BlockIndexIterImpl::Error(Some(e)),
+        };
 
         let mut iter = Iter::new(
             self.global_id(),
             self.path.clone(),
+            self.fs.clone(),
             index_iter,
             self.descriptor_table.clone(),
             self.cache.clone(),
             self.metadata.data_block_compression,
+            self.encryptor.clone(),
             #[cfg(feature = "metrics")]
             self.metrics.clone(),
         );
@@ -380,17 +470,20 @@ impl Table {
             Bound::Unbounded => {}
         }
 
+        iter.set_fill_cache(fill_cache);
+
         iter
     }
 
     fn read_tli(
         regions: &ParsedRegions,
-        file: &File,
+        file: &dyn FileHandle,
         compression: CompressionType,
+        encryptor: Option<&dyn Encryptor>,
     ) -> crate::Result<IndexBlock> {
         log::trace!("Reading TLI block, with tli_ptr={:?}", regions.tli);
 
-        let block = Block::from_file(file, regions.tli, compression)?;
+        let block = Block::from_file(file, regions.tli, compression, encryptor)?;
 
         if block.header.block_type != BlockType::Index {
             return Err(crate::Error::InvalidTag((
@@ -403,10 +496,46 @@ impl Table {
     }
 
     /// Tries to recover a table from a file.
+    #[expect(clippy::too_many_arguments)]
     pub fn recover(
         file_path: PathBuf,
         checksum: Checksum,
         tree_id: TreeId,
+        fs: Arc<dyn Filesystem>,
+        encryptor: Option<Arc<dyn Encryptor>>,
+        cache: Arc<Cache>,
+        descriptor_table: Arc<DescriptorTable>,
+        pin_filter: bool,
+        pin_index: bool,
+        #[cfg(feature = "metrics")] metrics: Arc<Metrics>,
+    ) -> crate::Result<Self> {
+        let table = Self::recover_lazily(
+            file_path,
+            checksum,
+            tree_id,
+            fs,
+            encryptor,
+            cache,
+            descriptor_table,
+            pin_filter,
+            pin_index,
+            #[cfg(feature = "metrics")]
+            metrics,
+        )?;
+        table.preload()?;
+        Ok(table)
+    }
+
+    /// Tries to recover a table from a file, only reading its metadata.
+    ///
+    /// The block index and filters are loaded on first access, see [`Table::preload`].
+    #[expect(clippy::too_many_arguments)]
+    pub(crate) fn recover_lazily(
+        file_path: PathBuf,
+        checksum: Checksum,
+        tree_id: TreeId,
+        fs: Arc<dyn Filesystem>,
+        encryptor: Option<Arc<dyn Encryptor>>,
         cache: Arc<Cache>,
         descriptor_table: Arc<DescriptorTable>,
         pin_filter: bool,
@@ -418,13 +547,101 @@ impl Table {
         use std::sync::atomic::AtomicBool;
 
         log::debug!("Recovering table from file {}", file_path.display());
-        let mut file = std::fs::File::open(&file_path)?;
+        let mut file = fs.open(&file_path)?;
 
         let trailer = sfa::Reader::from_reader(&mut file)?;
         let regions = ParsedRegions::parse_from_toc(trailer.toc())?;
 
         log::trace!("Reading meta block, with meta_ptr={:?}", regions.metadata);
-        let metadata = ParsedMeta::load_with_handle(&file, &regions.metadata)?;
+        let metadata =
+            ParsedMeta::load_with_handle(&*file, &regions.metadata, encryptor.as_deref())?;
+
+        log::trace!("Table #{} recovered", metadata.id);
+
+        Ok(Self(Arc::new(Inner {
+            path: Arc::new(file_path),
+            tree_id,
+            fs,
+            encryptor,
+
+            metadata,
+            regions,
+
+            cache,
+
+            descriptor_table,
+
+            pinned: OnceLock::new(),
+            pin_filter,
+            pin_index,
+
+            is_deleted: AtomicBool::default(),
+            is_file_pinned: AtomicBool::default(),
+
+            checksum,
+
+            #[cfg(feature = "metrics")]
+            metrics,
+            cached_blob_bytes: OnceLock::new(),
+        })))
+    }
+
+    /// Loads the block index and filters, if they are not loaded yet.
+    ///
+    /// Tables of a recovered tree are loaded on first access, so opening a tree
+    /// only needs to read the metadata of each table.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn preload(&self) -> crate::Result<()> {
+        self.pinned().map(|_| ())
+    }
+
+    /// Keeps the table file open for as long as the table is alive, so it stays
+    /// readable after another process (e.g. the primary of a secondary instance) deletes it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub(crate) fn pin_file(&self) -> crate::Result<()> {
+        let file = self.fs.open(&self.path)?;
+        self.descriptor_table
+            .pin_table(self.global_id(), Arc::from(file));
+        self.is_file_pinned
+            .store(true, std::sync::atomic::Ordering::Release);
+        Ok(())
+    }
+
+    /// Opens the table file, reusing a cached file descriptor if there is one.
+    fn open_file(&self) -> crate::Result<Arc<dyn FileHandle>> {
+        if let Some(fd) = self.descriptor_table.access_for_table(&self.global_id()) {
+            return Ok(fd);
+        }
+
+        Ok(Arc::from(self.fs.open(&self.path)?))
+    }
+
+    /// Returns the pinned blocks, loading them on first access.
+    fn pinned(&self) -> crate::Result<&PinnedBlocks> {
+        if let Some(pinned) = self.0.pinned.get() {
+            return Ok(pinned);
+        }
+
+        let pinned = self.load_pinned_blocks()?;
+
+        // NOTE: Another thread may have loaded the blocks concurrently, which is fine
+        Ok(self.0.pinned.get_or_init(|| pinned))
+    }
+
+    #[expect(clippy::too_many_lines)]
+    fn load_pinned_blocks(&self) -> crate::Result<PinnedBlocks> {
+        let file = self.open_file()?;
+        let regions = &self.regions;
+        let metadata = &self.metadata;
+        let encryptor = self.encryptor.as_deref();
+
+        log::trace!("Loading pinned blocks of table #{}", metadata.id);
 
         let block_index = if regions.index.is_some() {
             log::trace!(
@@ -432,52 +649,62 @@ impl Table {
                 regions.tli,
             );
 
-            let block = Self::read_tli(&regions, &file, metadata.index_block_compression)?;
+            let block =
+                Self::read_tli(regions, &*file, metadata.index_block_compression, encryptor)?;
             BlockIndexImpl::TwoLevel(TwoLevelBlockIndex {
                 top_level_index: block,
-                cache: cache.clone(),
+                cache: self.cache.clone(),
                 compression: metadata.index_block_compression,
-                descriptor_table: descriptor_table.clone(),
-                path: file_path.clone(),
-                table_id: (tree_id, metadata.id).into(),
+                descriptor_table: self.descriptor_table.clone(),
+                path: self.path.to_path_buf(),
+                fs: self.fs.clone(),
+                encryptor: self.encryptor.clone(),
+                table_id: self.global_id(),
 
                 #[cfg(feature = "metrics")]
-                metrics: metrics.clone(),
+                metrics: self.metrics.clone(),
             })
-        } else if pin_index {
+        } else if self.pin_index {
             log::trace!(
                 "Creating pinned, full block index, with tli_ptr={:?}",
                 regions.tli,
             );
 
-            let block = Self::read_tli(&regions, &file, metadata.index_block_compression)?;
+            let block =
+                Self::read_tli(regions, &*file, metadata.index_block_compression, encryptor)?;
             BlockIndexImpl::Full(FullBlockIndex::new(block))
         } else {
             log::trace!("Creating volatile, full block index");
 
             BlockIndexImpl::VolatileFull(VolatileBlockIndex {
-                cache: cache.clone(),
+                cache: self.cache.clone(),
                 compression: metadata.index_block_compression,
-                descriptor_table: descriptor_table.clone(),
+                descriptor_table: self.descriptor_table.clone(),
                 handle: regions.tli,
-                path: file_path.clone(),
-                table_id: (tree_id, metadata.id).into(),
+                path: self.path.to_path_buf(),
+                fs: self.fs.clone(),
+                encryptor: self.encryptor.clone(),
+                table_id: self.global_id(),
 
                 #[cfg(feature = "metrics")]
-                metrics: metrics.clone(),
+                metrics: self.metrics.clone(),
             })
         };
 
-        let pinned_filter_index = if let Some(filter_tli_handle) = regions.filter_tli {
-            let block =
-                Block::from_file(&file, filter_tli_handle, metadata.index_block_compression)?;
+        let filter_index = if let Some(filter_tli_handle) = regions.filter_tli {
+            let block = Block::from_file(
+                &*file,
+                filter_tli_handle,
+                metadata.index_block_compression,
+                encryptor,
+            )?;
             Some(IndexBlock::new(block))
         } else {
             None
         };
 
         // TODO: FilterBlock newtype
-        let pinned_filter_block = if pinned_filter_index.is_none() && pin_filter {
+        let filter_block = if filter_index.is_none() && self.pin_filter {
             regions
                 .filter
                 .map(|filter_handle| {
@@ -486,9 +713,10 @@ impl Table {
                     );
 
                     let block = Block::from_file(
-                        &file,
+                        &*file,
                         filter_handle,
                         crate::CompressionType::None, // NOTE: We never write a filter block with compression
+                        encryptor,
                     )
                     .and_then(|block| {
                         if block.header.block_type == BlockType::Filter {
@@ -508,33 +736,35 @@ impl Table {
             None
         };
 
-        log::trace!("Table #{} recovered", metadata.id);
-
-        Ok(Self(Arc::new(Inner {
-            path: Arc::new(file_path),
-            tree_id,
-
-            metadata,
-            regions,
-
-            cache,
-
-            descriptor_table,
-
-            block_index: Arc::new(block_index),
-
-            pinned_filter_index,
+        let derived_key_filter = regions
+            .derived_key_filter
+            .map(|handle| {
+                log::trace!("Loading and pinning derived key filter block, with ptr={handle:?}");
 
-            pinned_filter_block,
+                let block = Block::from_file(
+                    &*file,
+                    handle,
+                    crate::CompressionType::None, // NOTE: We never write a filter block with compression
+                    encryptor,
+                )?;
 
-            is_deleted: AtomicBool::default(),
+                if block.header.block_type != BlockType::Filter {
+                    return Err(crate::Error::InvalidTag((
+                        "BlockType",
+                        block.header.block_type.into(),
+                    )));
+                }
 
-            checksum,
+                Ok(DerivedKeyFilterBlock::new(block))
+            })
+            .transpose()?;
 
-            #[cfg(feature = "metrics")]
-            metrics,
-            cached_blob_bytes: std::sync::OnceLock::new(),
-        })))
+        Ok(PinnedBlocks {
+            block_index,
+            filter_index,
+            filter_block,
+            derived_key_filter,
+        })
     }
 
     #[must_use]
@@ -553,6 +783,62 @@ impl Table {
         self.metadata.key_range.contains_key(key)
     }
 
+    /// Approximates the on-disk size of the data blocks that overlap with a key range.
+    ///
+    /// If the table's key range is fully contained in the range, the file size is returned
+    /// without consulting the block index.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn approximate_size(&self, bounds: &(Bound<&[u8]>, Bound<&[u8]>)) -> crate::Result<u64> {
+        if !self.check_key_range_overlap(bounds) {
+            return Ok(0);
+        }
+
+        let (min, max) = (self.metadata.key_range.min(), self.metadata.key_range.max());
+
+        let covers_min = match bounds.0 {
+            Bound::Included(key) => key <= &**min,
+            Bound::Excluded(key) => key < &**min,
+            Bound::Unbounded => true,
+        };
+
+        let covers_max = match bounds.1 {
+            Bound::Included(key) => key >= &**max,
+            Bound::Excluded(key) => key > &**max,
+            Bound::Unbounded => true,
+        };
+
+        if covers_min && covers_max {
+            return Ok(self.file_size());
+        }
+
+        let mut index_iter = self.pinned()?.block_index.iter();
+
+        // NOTE: Block boundaries are only known by their end keys,
+        // so the first and last block are always counted fully
+        if let Bound::Included(key) | Bound::Excluded(key) = bounds.0 {
+            if !index_iter.seek_lower(key) {
+                return Ok(0);
+            }
+        }
+
+        if let Bound::Included(key) | Bound::Excluded(key) = bounds.1 {
+            if !index_iter.seek_upper(key) {
+                return Ok(0);
+            }
+        }
+
+        let mut size = 0;
+
+        for handle in index_iter {
+            size += u64::from(handle?.size());
+        }
+
+        Ok(size)
+    }
+
     /// Checks if a key range is (partially or fully) contained in this table.
     pub(crate) fn check_key_range_overlap(&self, bounds: &(Bound<&[u8]>, Bound<&[u8]>)) -> bool {
         self.metadata.key_range.overlaps_with_bounds(bounds)
@@ -589,8 +875,16 @@ impl Table {
     #[must_use]
     #[doc(hidden)]
     pub fn tombstone_ratio(&self) -> f32 {
-        todo!()
+        if self.metadata.item_count == 0 {
+            return 0.0;
+        }
+
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "the ratio does not need to be exact"
+        )]
+        let ratio = self.metadata.tombstone_count as f32 / self.metadata.item_count as f32;
 
-        //  self.metadata.tombstone_count as f32 / self.metadata.key_count as f32
+        ratio
     }
 }
@@ -5,9 +5,10 @@
 use super::{filter::BloomConstructionPolicy, writer::Writer};
 use crate::{
     blob_tree::handle::BlobIndirection, table::writer::LinkedFile, value::InternalValue,
-    vlog::BlobFileId, Checksum, CompressionType, HashMap, SequenceNumberCounter, TableId, UserKey,
+    vlog::BlobFileId, Checksum, Clock, CompressionType, Encryptor, Filesystem, HashMap,
+    KeyExtractor, RateLimiter, SequenceNumberCounter, SystemClock, TableId, UserKey,
 };
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
 /// Like `Writer` but will rotate to a new table, once a table grows larger than `target_size`
 ///
@@ -15,6 +16,19 @@ use std::path::PathBuf;
 pub struct MultiWriter {
     pub(crate) base_path: PathBuf,
 
+    /// Number of subfolders the tables are spread across, see [`crate::file::sharded_path`]
+    shard_count: u16,
+
+    fs: Arc<dyn Filesystem>,
+
+    clock: Arc<dyn Clock>,
+
+    encryptor: Option<Arc<dyn Encryptor>>,
+
+    key_extractor: Option<Arc<dyn KeyExtractor>>,
+
+    rate_limiter: Option<Arc<RateLimiter>>,
+
     data_block_hash_ratio: f32,
 
     data_block_size: u32,
@@ -54,20 +68,30 @@ pub struct MultiWriter {
 impl MultiWriter {
     /// Sets up a new `MultiWriter` at the given tables folder
     pub fn new(
+        fs: Arc<dyn Filesystem>,
         base_path: PathBuf,
+        shard_count: u16,
         table_id_generator: SequenceNumberCounter,
         target_size: u64,
         initial_level: u8,
     ) -> crate::Result<Self> {
         let current_table_id = table_id_generator.next();
 
-        let path = base_path.join(current_table_id.to_string());
-        let writer = Writer::new(path, current_table_id, initial_level)?;
+        let path = crate::file::sharded_path(&base_path, current_table_id, shard_count);
+        let writer = Writer::new(fs.clone(), path, current_table_id, initial_level)?;
 
         Ok(Self {
             initial_level,
 
             base_path,
+            shard_count,
+            fs,
+            clock: Arc::new(SystemClock),
+            encryptor: None,
+
+            key_extractor: None,
+
+            rate_limiter: None,
 
             data_block_hash_ratio: 0.0,
 
@@ -111,6 +135,34 @@ impl MultiWriter {
             });
     }
 
+    #[must_use]
+    pub fn use_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.writer = self.writer.use_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    #[must_use]
+    pub fn use_encryption(mut self, encryptor: Option<Arc<dyn Encryptor>>) -> Self {
+        self.writer = self.writer.use_encryption(encryptor.clone());
+        self.encryptor = encryptor;
+        self
+    }
+
+    #[must_use]
+    pub fn use_key_extractor(mut self, extractor: Option<Arc<dyn KeyExtractor>>) -> Self {
+        self.writer = self.writer.use_key_extractor(extractor.clone());
+        self.key_extractor = extractor;
+        self
+    }
+
+    #[must_use]
+    pub fn use_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.writer = self.writer.use_rate_limiter(rate_limiter.clone());
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
     #[must_use]
     pub fn use_partitioned_index(mut self) -> Self {
         self.use_partitioned_index = true;
@@ -178,21 +230,30 @@ impl MultiWriter {
         self
     }
 
+    /// Returns the path of a table that is written by this writer.
+    pub(crate) fn table_path(&self, table_id: TableId) -> PathBuf {
+        crate::file::sharded_path(&self.base_path, table_id, self.shard_count)
+    }
+
     /// Flushes the current writer, stores its metadata, and sets up a new writer for the next table
     fn rotate(&mut self) -> crate::Result<()> {
         log::debug!("Rotating table writer");
 
         let new_table_id = self.table_id_generator.next();
-        let path = self.base_path.join(new_table_id.to_string());
+        let path = self.table_path(new_table_id);
 
-        let mut new_writer = Writer::new(path, new_table_id, self.initial_level)?
+        let mut new_writer = Writer::new(self.fs.clone(), path, new_table_id, self.initial_level)?
             .use_data_block_compression(self.data_block_compression)
             .use_index_block_compression(self.index_block_compression)
             .use_data_block_size(self.data_block_size)
             .use_data_block_restart_interval(self.data_block_restart_interval)
             .use_index_block_restart_interval(self.index_block_restart_interval)
             .use_bloom_policy(self.bloom_policy)
-            .use_data_block_hash_ratio(self.data_block_hash_ratio);
+            .use_data_block_hash_ratio(self.data_block_hash_ratio)
+            .use_clock(self.clock.clone())
+            .use_encryption(self.encryptor.clone())
+            .use_key_extractor(self.key_extractor.clone())
+            .use_rate_limiter(self.rate_limiter.clone());
 
         if self.use_partitioned_index {
             new_writer = new_writer.use_partitioned_index();
@@ -237,6 +298,27 @@ impl MultiWriter {
         Ok(())
     }
 
+    /// Deletes all tables written so far, because they are not needed anymore
+    /// (e.g. the compaction that wrote them failed).
+    pub fn abort(self) {
+        let table_paths = self
+            .results
+            .iter()
+            .map(|&(table_id, _)| self.table_path(table_id))
+            .chain(std::iter::once(self.writer.path.clone()));
+
+        for path in table_paths {
+            if let Err(e) = self.fs.remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!(
+                        "Failed to delete aborted table at {}: {e:?}",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+
     /// Finishes the last table, making sure all data is written durably
     ///
     /// Returns the metadata of created tables
@@ -20,6 +20,9 @@ fn toc_entry_to_handle(entry: &TocEntry) -> BlockHandle {
 /// |--------------|
 /// |    filter    | <- may not exist
 /// |--------------|
+/// | derived key  | <- may not exist (if no key extractor is used)
+/// |    filter    |
+/// |--------------|
 /// |      ...     |
 /// |--------------|
 /// | linked blobs | <- may not exist
@@ -36,6 +39,7 @@ pub struct ParsedRegions {
     pub index: Option<BlockHandle>,
     pub filter_tli: Option<BlockHandle>,
     pub filter: Option<BlockHandle>,
+    pub derived_key_filter: Option<BlockHandle>,
     pub linked_blob_files: Option<BlockHandle>,
     pub metadata: BlockHandle,
 }
@@ -53,6 +57,7 @@ impl ParsedRegions {
                 })?,
             index: toc.section(b"index").map(toc_entry_to_handle),
             filter: toc.section(b"filter").map(toc_entry_to_handle),
+            derived_key_filter: toc.section(b"derived_key_filter").map(toc_entry_to_handle),
             linked_blob_files: toc.section(b"linked_blob_files").map(toc_entry_to_handle),
             metadata: toc
                 .section(b"meta")
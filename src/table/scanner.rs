@@ -5,30 +5,33 @@
 use super::{Block, DataBlock};
 use crate::{
     table::{block::BlockType, iter::OwnedDataBlockIter},
-    CompressionType, InternalValue,
+    CompressionType, Encryptor, FileHandle, Filesystem, InternalValue,
 };
-use std::{fs::File, io::BufReader, path::Path};
+use std::{io::BufReader, path::Path, sync::Arc};
 
 /// Table reader that is optimized for consuming an entire table
 pub struct Scanner {
-    reader: BufReader<File>,
+    reader: BufReader<Box<dyn FileHandle>>,
     iter: OwnedDataBlockIter,
 
     compression: CompressionType,
+    encryptor: Option<Arc<dyn Encryptor>>,
     block_count: usize,
     read_count: usize,
 }
 
 impl Scanner {
     pub fn new(
+        fs: &dyn Filesystem,
         path: &Path,
         block_count: usize,
         compression: CompressionType,
+        encryptor: Option<Arc<dyn Encryptor>>,
     ) -> crate::Result<Self> {
         // TODO: a larger buffer size may be better for HDD, maybe make this configurable
-        let mut reader = BufReader::with_capacity(8 * 4_096, File::open(path)?);
+        let mut reader = BufReader::with_capacity(8 * 4_096, fs.open(path)?);
 
-        let block = Self::fetch_next_block(&mut reader, compression)?;
+        let block = Self::fetch_next_block(&mut reader, compression, encryptor.as_deref())?;
         let iter = OwnedDataBlockIter::new(block, DataBlock::iter);
 
         Ok(Self {
@@ -36,16 +39,18 @@ impl Scanner {
             iter,
 
             compression,
+            encryptor,
             block_count,
             read_count: 1,
         })
     }
 
     fn fetch_next_block(
-        reader: &mut BufReader<File>,
+        reader: &mut BufReader<Box<dyn FileHandle>>,
         compression: CompressionType,
+        encryptor: Option<&dyn Encryptor>,
     ) -> crate::Result<DataBlock> {
-        let block = Block::from_reader(reader, compression);
+        let block = Block::from_reader(reader, compression, encryptor);
 
         match block {
             Ok(block) => {
@@ -77,7 +82,11 @@ impl Iterator for Scanner {
             }
 
             // Init new block
-            let block = fail_iter!(Self::fetch_next_block(&mut self.reader, self.compression));
+            let block = fail_iter!(Self::fetch_next_block(
+                &mut self.reader,
+                self.compression,
+                self.encryptor.as_deref(),
+            ));
             self.iter = OwnedDataBlockIter::new(block, DataBlock::iter);
 
             self.read_count += 1;
@@ -5,6 +5,7 @@
 use super::*;
 use crate::{
     config::BloomConstructionPolicy, table::filter::standard_bloom::Builder as BloomBuilder,
+    StdFilesystem,
 };
 use tempfile::tempdir;
 use test_log::test;
@@ -25,7 +26,7 @@ fn test_with_table(
     let file = dir.path().join("table");
 
     {
-        let mut writer = Writer::new(file.clone(), 0, 0)?;
+        let mut writer = Writer::new(Arc::new(StdFilesystem), file.clone(), 0, 0)?;
 
         if let Some(f) = &config_writer {
             writer = f(writer);
@@ -49,6 +50,8 @@ fn test_with_table(
                 file.clone(),
                 checksum,
                 0,
+                Arc::new(StdFilesystem),
+                None,
                 Arc::new(Cache::with_capacity_bytes(1_000_000)),
                 Arc::new(DescriptorTable::new(10)),
                 false,
@@ -74,6 +77,8 @@ fn test_with_table(
                 file.clone(),
                 checksum,
                 0,
+                Arc::new(StdFilesystem),
+                None,
                 Arc::new(Cache::with_capacity_bytes(1_000_000)),
                 Arc::new(DescriptorTable::new(10)),
                 true,
@@ -99,6 +104,8 @@ fn test_with_table(
                 file.clone(),
                 checksum,
                 0,
+                Arc::new(StdFilesystem),
+                None,
                 Arc::new(Cache::with_capacity_bytes(1_000_000)),
                 Arc::new(DescriptorTable::new(10)),
                 false,
@@ -124,6 +131,8 @@ fn test_with_table(
                 file.clone(),
                 checksum,
                 0,
+                Arc::new(StdFilesystem),
+                None,
                 Arc::new(Cache::with_capacity_bytes(1_000_000)),
                 Arc::new(DescriptorTable::new(10)),
                 true,
@@ -145,7 +154,8 @@ fn test_with_table(
     std::fs::remove_file(&file)?;
 
     {
-        let mut writer = Writer::new(file.clone(), 0, 0)?.use_partitioned_index();
+        let mut writer =
+            Writer::new(Arc::new(StdFilesystem), file.clone(), 0, 0)?.use_partitioned_index();
 
         if let Some(f) = config_writer {
             writer = f(writer);
@@ -169,6 +179,8 @@ fn test_with_table(
                 file.clone(),
                 checksum,
                 0,
+                Arc::new(StdFilesystem),
+                None,
                 Arc::new(Cache::with_capacity_bytes(1_000_000)),
                 Arc::new(DescriptorTable::new(10)),
                 false,
@@ -193,6 +205,8 @@ fn test_with_table(
                 file.clone(),
                 checksum,
                 0,
+                Arc::new(StdFilesystem),
+                None,
                 Arc::new(Cache::with_capacity_bytes(1_000_000)),
                 Arc::new(DescriptorTable::new(10)),
                 true,
@@ -217,6 +231,8 @@ fn test_with_table(
                 file.clone(),
                 checksum,
                 0,
+                Arc::new(StdFilesystem),
+                None,
                 Arc::new(Cache::with_capacity_bytes(1_000_000)),
                 Arc::new(DescriptorTable::new(10)),
                 false,
@@ -242,6 +258,8 @@ fn test_with_table(
                 file,
                 checksum,
                 0,
+                Arc::new(StdFilesystem),
+                None,
                 Arc::new(Cache::with_capacity_bytes(1_000_000)),
                 Arc::new(DescriptorTable::new(10)),
                 true,
@@ -5,7 +5,7 @@
 use super::{Block, BlockHandle, GlobalTableId};
 use crate::{
     table::block::BlockType, version::run::Ranged, Cache, CompressionType, DescriptorTable,
-    KeyRange, Table,
+    Encryptor, Filesystem, KeyRange, Table,
 };
 use std::{path::Path, sync::Arc};
 
@@ -26,15 +26,20 @@ pub struct SliceIndexes(pub usize, pub usize);
 /// Loads a block from disk or block cache, if cached.
 ///
 /// Also handles file descriptor opening and caching.
-#[warn(clippy::too_many_arguments)]
+///
+/// If `fill_cache` is `false`, a block that is loaded from disk is not inserted into the block cache.
+#[expect(clippy::too_many_arguments)]
 pub fn load_block(
     table_id: GlobalTableId,
     path: &Path,
+    fs: &dyn Filesystem,
     descriptor_table: &DescriptorTable,
     cache: &Cache,
     handle: &BlockHandle,
     block_type: BlockType,
     compression: CompressionType,
+    encryptor: Option<&dyn Encryptor>,
+    fill_cache: bool,
     #[cfg(feature = "metrics")] metrics: &Metrics,
 ) -> crate::Result<Block> {
     #[cfg(feature = "metrics")]
@@ -69,15 +74,15 @@ pub fn load_block(
 
         fd
     } else {
-        let fd = std::fs::File::open(path)?;
+        let fd = fs.open(path)?;
 
         #[cfg(feature = "metrics")]
         metrics.table_file_opened.fetch_add(1, Relaxed);
 
-        Arc::new(fd)
+        Arc::from(fd)
     };
 
-    let block = Block::from_file(&fd, *handle, compression)?;
+    let block = Block::from_file(&*fd, *handle, compression, encryptor)?;
 
     if block.header.block_type != block_type {
         return Err(crate::Error::InvalidTag((
@@ -105,7 +110,9 @@ pub fn load_block(
         descriptor_table.insert_for_table(table_id, fd);
     }
 
-    cache.insert_block(table_id, handle.offset(), block.clone());
+    if fill_cache {
+        cache.insert_block(table_id, handle.offset(), block.clone());
+    }
 
     Ok(block)
 }
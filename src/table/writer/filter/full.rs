@@ -6,14 +6,17 @@ use super::FilterWriter;
 use crate::{
     config::BloomConstructionPolicy,
     table::{filter::standard_bloom::Builder, Block},
-    CompressionType, UserKey,
+    CompressionType, Encryptor, UserKey,
 };
+use std::sync::Arc;
 
 pub struct FullFilterWriter {
     /// Key hashes for AMQ filter
     pub bloom_hash_buffer: Vec<u64>,
 
     bloom_policy: BloomConstructionPolicy,
+
+    encryptor: Option<Arc<dyn Encryptor>>,
 }
 
 impl FullFilterWriter {
@@ -21,6 +24,7 @@ impl FullFilterWriter {
         Self {
             bloom_hash_buffer: Vec::new(),
             bloom_policy,
+            encryptor: None,
         }
     }
 }
@@ -30,6 +34,14 @@ impl<W: std::io::Write + std::io::Seek> FilterWriter<W> for FullFilterWriter {
         self
     }
 
+    fn use_encryption(
+        mut self: Box<Self>,
+        encryptor: Option<Arc<dyn Encryptor>>,
+    ) -> Box<dyn FilterWriter<W>> {
+        self.encryptor = encryptor;
+        self
+    }
+
     fn set_filter_policy(
         mut self: Box<Self>,
         policy: BloomConstructionPolicy,
@@ -43,7 +55,7 @@ impl<W: std::io::Write + std::io::Seek> FilterWriter<W> for FullFilterWriter {
         Ok(())
     }
 
-    fn finish(self: Box<Self>, file_writer: &mut sfa::Writer) -> crate::Result<()> {
+    fn finish(self: Box<Self>, file_writer: &mut crate::archive::Writer) -> crate::Result<()> {
         if self.bloom_hash_buffer.is_empty() {
             log::trace!("Filter write has no buffered hashes - not building filter");
         } else {
@@ -79,6 +91,7 @@ impl<W: std::io::Write + std::io::Seek> FilterWriter<W> for FullFilterWriter {
                 &filter_bytes,
                 crate::table::block::BlockType::Filter,
                 CompressionType::None,
+                self.encryptor.as_deref(),
             )?;
         }
 
@@ -8,16 +8,17 @@ mod partitioned;
 pub use full::FullFilterWriter;
 pub use partitioned::PartitionedFilterWriter;
 
-use crate::{config::BloomConstructionPolicy, CompressionType, UserKey};
+use crate::{config::BloomConstructionPolicy, CompressionType, Encryptor, UserKey};
+use std::sync::Arc;
 
-pub trait FilterWriter<W: std::io::Write> {
+pub trait FilterWriter<W: std::io::Write>: Send {
     // NOTE: We purposefully use a UserKey instead of &[u8]
     // so we can clone it without heap allocation, if needed
     /// Registers a key in the block index.
     fn register_key(&mut self, key: &UserKey) -> crate::Result<()>;
 
     /// Writes the filter to a file.
-    fn finish(self: Box<Self>, file_writer: &mut sfa::Writer) -> crate::Result<()>;
+    fn finish(self: Box<Self>, file_writer: &mut crate::archive::Writer) -> crate::Result<()>;
 
     fn set_filter_policy(
         self: Box<Self>,
@@ -28,4 +29,9 @@ pub trait FilterWriter<W: std::io::Write> {
         self: Box<Self>,
         compression: CompressionType,
     ) -> Box<dyn FilterWriter<W>>;
+
+    fn use_encryption(
+        self: Box<Self>,
+        encryptor: Option<Arc<dyn Encryptor>>,
+    ) -> Box<dyn FilterWriter<W>>;
 }
@@ -2,7 +2,10 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use std::io::{Seek, Write};
+use std::{
+    io::{Seek, Write},
+    sync::Arc,
+};
 
 use super::FilterWriter;
 use crate::{
@@ -11,7 +14,7 @@ use crate::{
         block::Header as BlockHeader, filter::standard_bloom::Builder, Block, BlockOffset,
         IndexBlock, KeyedBlockHandle,
     },
-    CompressionType, UserKey,
+    CompressionType, Encryptor, UserKey,
 };
 
 pub struct PartitionedFilterWriter {
@@ -32,6 +35,8 @@ pub struct PartitionedFilterWriter {
     last_key: Option<UserKey>,
 
     compression: CompressionType,
+
+    encryptor: Option<Arc<dyn Encryptor>>,
 }
 
 impl PartitionedFilterWriter {
@@ -51,6 +56,8 @@ impl PartitionedFilterWriter {
             last_key: None,
 
             compression: CompressionType::None,
+
+            encryptor: None,
         }
     }
 
@@ -70,6 +77,7 @@ impl PartitionedFilterWriter {
             &filter_bytes,
             crate::table::block::BlockType::Filter,
             CompressionType::None,
+            self.encryptor.as_deref(),
         )?;
 
         let bytes_written = (header.data_length as usize + BlockHeader::serialized_len()) as u32;
@@ -94,7 +102,7 @@ impl PartitionedFilterWriter {
 
     fn write_top_level_index(
         &mut self,
-        file_writer: &mut sfa::Writer,
+        file_writer: &mut crate::archive::Writer,
         index_base_offset: BlockOffset,
     ) -> crate::Result<()> {
         file_writer.start("filter_tli")?;
@@ -111,6 +119,7 @@ impl PartitionedFilterWriter {
             &bytes,
             crate::table::block::BlockType::Index,
             self.compression,
+            self.encryptor.as_deref(),
         )?;
 
         #[expect(
@@ -139,6 +148,14 @@ impl<W: std::io::Write + std::io::Seek> FilterWriter<W> for PartitionedFilterWri
         self
     }
 
+    fn use_encryption(
+        mut self: Box<Self>,
+        encryptor: Option<Arc<dyn Encryptor>>,
+    ) -> Box<dyn FilterWriter<W>> {
+        self.encryptor = encryptor;
+        self
+    }
+
     fn set_filter_policy(
         mut self: Box<Self>,
         policy: BloomConstructionPolicy,
@@ -163,12 +180,18 @@ impl<W: std::io::Write + std::io::Seek> FilterWriter<W> for PartitionedFilterWri
         Ok(())
     }
 
-    fn finish(mut self: Box<Self>, file_writer: &mut sfa::Writer) -> crate::Result<()> {
+    fn finish(mut self: Box<Self>, file_writer: &mut crate::archive::Writer) -> crate::Result<()> {
         if !self.bloom_hash_buffer.is_empty() {
             let last_key = self.last_key.take().expect("last key should exist");
             self.spill_filter_partition(&last_key)?;
         }
 
+        // NOTE: No keys were registered (e.g. filters are disabled for the last level)
+        if self.tli_handles.is_empty() {
+            log::trace!("Filter writer has no partitions - not building filter");
+            return Ok(());
+        }
+
         let index_base_offset = BlockOffset(file_writer.get_mut().stream_position()?);
 
         file_writer.start("filter")?;
@@ -7,11 +7,13 @@ use crate::{
         block::Header as BlockHeader, index_block::KeyedBlockHandle,
         writer::index::BlockIndexWriter, Block, IndexBlock,
     },
-    CompressionType,
+    CompressionType, Encryptor,
 };
+use std::sync::Arc;
 
 pub struct FullIndexWriter {
     compression: CompressionType,
+    encryptor: Option<Arc<dyn Encryptor>>,
     block_handles: Vec<KeyedBlockHandle>,
 }
 
@@ -19,6 +21,7 @@ impl FullIndexWriter {
     pub fn new() -> Self {
         Self {
             compression: CompressionType::None,
+            encryptor: None,
             block_handles: Vec::new(),
         }
     }
@@ -33,6 +36,14 @@ impl<W: std::io::Write + std::io::Seek> BlockIndexWriter<W> for FullIndexWriter
         self
     }
 
+    fn use_encryption(
+        mut self: Box<Self>,
+        encryptor: Option<Arc<dyn Encryptor>>,
+    ) -> Box<dyn BlockIndexWriter<W>> {
+        self.encryptor = encryptor;
+        self
+    }
+
     fn register_data_block(&mut self, block_handle: KeyedBlockHandle) -> crate::Result<()> {
         log::trace!(
             "Registering block at {:?} with size {} [end_key={:?}]",
@@ -46,7 +57,7 @@ impl<W: std::io::Write + std::io::Seek> BlockIndexWriter<W> for FullIndexWriter
         Ok(())
     }
 
-    fn finish(self: Box<Self>, file_writer: &mut sfa::Writer) -> crate::Result<usize> {
+    fn finish(self: Box<Self>, file_writer: &mut crate::archive::Writer) -> crate::Result<usize> {
         file_writer.start("tli")?;
 
         let mut bytes = vec![];
@@ -57,6 +68,7 @@ impl<W: std::io::Write + std::io::Seek> BlockIndexWriter<W> for FullIndexWriter
             &bytes,
             crate::table::block::BlockType::Index,
             self.compression,
+            self.encryptor.as_deref(),
         )?;
 
         #[expect(
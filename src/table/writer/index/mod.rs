@@ -8,19 +8,25 @@ mod partitioned;
 pub use full::FullIndexWriter;
 pub use partitioned::PartitionedIndexWriter;
 
-use crate::{table::index_block::KeyedBlockHandle, CompressionType};
+use crate::{table::index_block::KeyedBlockHandle, CompressionType, Encryptor};
+use std::sync::Arc;
 
-pub trait BlockIndexWriter<W: std::io::Write> {
+pub trait BlockIndexWriter<W: std::io::Write>: Send {
     /// Registers a data block in the block index.
     fn register_data_block(&mut self, block_handle: KeyedBlockHandle) -> crate::Result<()>;
 
     /// Writes the block index to a file.
     ///
     /// Returns the number of index blocks written.
-    fn finish(self: Box<Self>, file_writer: &mut sfa::Writer) -> crate::Result<usize>;
+    fn finish(self: Box<Self>, file_writer: &mut crate::archive::Writer) -> crate::Result<usize>;
 
     fn use_compression(
         self: Box<Self>,
         compression: CompressionType,
     ) -> Box<dyn BlockIndexWriter<W>>;
+
+    fn use_encryption(
+        self: Box<Self>,
+        encryptor: Option<Arc<dyn Encryptor>>,
+    ) -> Box<dyn BlockIndexWriter<W>>;
 }
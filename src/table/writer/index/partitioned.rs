@@ -7,15 +7,20 @@ use crate::{
         block::Header as BlockHeader, index_block::KeyedBlockHandle,
         writer::index::BlockIndexWriter, Block, BlockOffset, IndexBlock,
     },
-    CompressionType,
+    CompressionType, Encryptor,
+};
+use std::{
+    io::{Seek, Write},
+    sync::Arc,
 };
-use std::io::{Seek, Write};
 
 pub struct PartitionedIndexWriter {
     relative_file_pos: u64,
 
     compression: CompressionType,
 
+    encryptor: Option<Arc<dyn Encryptor>>,
+
     tli_handles: Vec<KeyedBlockHandle>,
     data_block_handles: Vec<KeyedBlockHandle>,
 
@@ -38,6 +43,7 @@ impl PartitionedIndexWriter {
 
             block_size: 4_096, // TODO: allow to set this
             compression: CompressionType::None,
+            encryptor: None,
 
             tli_handles: Vec::new(),
             data_block_handles: Vec::new(),
@@ -56,6 +62,7 @@ impl PartitionedIndexWriter {
             &bytes,
             crate::table::block::BlockType::Index,
             self.compression,
+            self.encryptor.as_deref(),
         )?;
 
         #[expect(
@@ -95,7 +102,7 @@ impl PartitionedIndexWriter {
 
     fn write_top_level_index(
         &mut self,
-        file_writer: &mut sfa::Writer,
+        file_writer: &mut crate::archive::Writer,
         index_base_offset: BlockOffset,
     ) -> crate::Result<()> {
         file_writer.start("tli")?;
@@ -112,6 +119,7 @@ impl PartitionedIndexWriter {
             &bytes,
             crate::table::block::BlockType::Index,
             self.compression,
+            self.encryptor.as_deref(),
         )?;
 
         #[expect(
@@ -140,6 +148,14 @@ impl<W: std::io::Write + std::io::Seek> BlockIndexWriter<W> for PartitionedIndex
         self
     }
 
+    fn use_encryption(
+        mut self: Box<Self>,
+        encryptor: Option<Arc<dyn Encryptor>>,
+    ) -> Box<dyn BlockIndexWriter<W>> {
+        self.encryptor = encryptor;
+        self
+    }
+
     fn register_data_block(&mut self, block_handle: KeyedBlockHandle) -> crate::Result<()> {
         log::trace!(
             "Registering block at {:?} with size {} [end_key={:?}]",
@@ -166,7 +182,10 @@ impl<W: std::io::Write + std::io::Seek> BlockIndexWriter<W> for PartitionedIndex
         Ok(())
     }
 
-    fn finish(mut self: Box<Self>, file_writer: &mut sfa::Writer) -> crate::Result<usize> {
+    fn finish(
+        mut self: Box<Self>,
+        file_writer: &mut crate::archive::Writer,
+    ) -> crate::Result<usize> {
         if self.buffer_size > 0 {
             self.cut_index_block()?;
         }
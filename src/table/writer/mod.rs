@@ -12,17 +12,16 @@ use super::{
 };
 use crate::{
     coding::Encode,
-    file::fsync_directory,
     table::writer::{
         filter::{FilterWriter, FullFilterWriter},
         index::FullIndexWriter,
     },
-    time::unix_timestamp,
     vlog::BlobFileId,
-    Checksum, CompressionType, InternalValue, TableId, UserKey, ValueType,
+    Checksum, Clock, CompressionType, Encryptor, FileHandle, Filesystem, InternalValue,
+    KeyExtractor, RateLimiter, SystemClock, TableId, UserKey, ValueType,
 };
 use index::BlockIndexWriter;
-use std::{fs::File, io::BufWriter, path::PathBuf};
+use std::{io::BufWriter, path::PathBuf, sync::Arc};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, std::hash::Hash)]
 pub struct LinkedFile {
@@ -37,6 +36,10 @@ pub struct Writer {
     /// Table file path
     pub(crate) path: PathBuf,
 
+    fs: Arc<dyn Filesystem>,
+
+    clock: Arc<dyn Clock>,
+
     table_id: TableId,
 
     data_block_restart_interval: u8,
@@ -52,20 +55,32 @@ pub struct Writer {
     /// Compression to use for data blocks
     index_block_compression: CompressionType,
 
+    /// Encryption to use for all blocks
+    encryptor: Option<Arc<dyn Encryptor>>,
+
+    /// Limits the write throughput of data blocks
+    rate_limiter: Option<Arc<RateLimiter>>,
+
     /// Buffer to serialize blocks into
     block_buffer: Vec<u8>,
 
     /// File writer
     #[expect(clippy::struct_field_names)]
-    file_writer: sfa::Writer,
+    file_writer: crate::archive::Writer,
 
     /// Writer of index blocks
     #[expect(clippy::struct_field_names)]
-    index_writer: Box<dyn BlockIndexWriter<BufWriter<File>>>,
+    index_writer: Box<dyn BlockIndexWriter<BufWriter<Box<dyn FileHandle>>>>,
 
     /// Writer of filter
     #[expect(clippy::struct_field_names)]
-    filter_writer: Box<dyn FilterWriter<BufWriter<File>>>,
+    filter_writer: Box<dyn FilterWriter<BufWriter<Box<dyn FileHandle>>>>,
+
+    /// Key extractor to build the derived key filter with
+    key_extractor: Option<Arc<dyn KeyExtractor>>,
+
+    /// Derived key hashes for the derived key filter
+    derived_key_hashes: Vec<u64>,
 
     /// Buffer of KVs
     chunk: Vec<InternalValue>,
@@ -89,10 +104,15 @@ pub struct Writer {
 }
 
 impl Writer {
-    pub fn new(path: PathBuf, table_id: TableId, initial_level: u8) -> crate::Result<Self> {
-        let block_writer = File::create_new(&path)?;
+    pub fn new(
+        fs: Arc<dyn Filesystem>,
+        path: PathBuf,
+        table_id: TableId,
+        initial_level: u8,
+    ) -> crate::Result<Self> {
+        let block_writer = fs.create_new(&path)?;
         let block_writer = BufWriter::with_capacity(u16::MAX.into(), block_writer);
-        let mut block_writer = sfa::Writer::from_writer(block_writer);
+        let mut block_writer = crate::archive::Writer::from_writer(block_writer);
         block_writer.start("data")?;
 
         Ok(Self {
@@ -112,11 +132,19 @@ impl Writer {
             data_block_compression: CompressionType::None,
             index_block_compression: CompressionType::None,
 
+            encryptor: None,
+            rate_limiter: None,
+
             path: std::path::absolute(path)?,
+            fs,
+            clock: Arc::new(SystemClock),
 
             index_writer: Box::new(FullIndexWriter::new()),
             filter_writer: Box::new(FullFilterWriter::new(BloomConstructionPolicy::default())),
 
+            key_extractor: None,
+            derived_key_hashes: Vec::new(),
+
             block_buffer: Vec::new(),
             file_writer: block_writer,
             chunk: Vec::new(),
@@ -150,17 +178,26 @@ impl Writer {
         });
     }
 
+    /// Sets the clock that is used for the creation timestamp of the table.
+    #[must_use]
+    pub fn use_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     #[must_use]
     pub fn use_partitioned_filter(mut self) -> Self {
         self.filter_writer = Box::new(filter::PartitionedFilterWriter::new(self.bloom_policy))
-            .use_tli_compression(self.index_block_compression);
+            .use_tli_compression(self.index_block_compression)
+            .use_encryption(self.encryptor.clone());
         self
     }
 
     #[must_use]
     pub fn use_partitioned_index(mut self) -> Self {
         self.index_writer = Box::new(index::PartitionedIndexWriter::new())
-            .use_compression(self.index_block_compression);
+            .use_compression(self.index_block_compression)
+            .use_encryption(self.encryptor.clone());
         self
     }
 
@@ -206,6 +243,22 @@ impl Writer {
         self
     }
 
+    /// Sets the encryptor that is used for all blocks of the table.
+    #[must_use]
+    pub fn use_encryption(mut self, encryptor: Option<Arc<dyn Encryptor>>) -> Self {
+        self.index_writer = self.index_writer.use_encryption(encryptor.clone());
+        self.filter_writer = self.filter_writer.use_encryption(encryptor.clone());
+        self.encryptor = encryptor;
+        self
+    }
+
+    /// Sets the rate limiter that the bytes of every data block are requested from.
+    #[must_use]
+    pub fn use_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
     #[must_use]
     pub fn use_bloom_policy(mut self, bloom_policy: BloomConstructionPolicy) -> Self {
         self.bloom_policy = bloom_policy;
@@ -213,6 +266,51 @@ impl Writer {
         self
     }
 
+    /// Sets the key extractor that is used to build the derived key filter.
+    #[must_use]
+    pub fn use_key_extractor(mut self, extractor: Option<Arc<dyn KeyExtractor>>) -> Self {
+        self.key_extractor = extractor;
+        self
+    }
+
+    /// Writes the derived key filter block, consisting of the extractor name and
+    /// a Bloom filter over the derived keys (which is empty if there are no derived keys).
+    fn write_derived_key_filter(
+        file_writer: &mut crate::archive::Writer,
+        extractor_name: &str,
+        hashes: &[u64],
+        encryptor: Option<&dyn Encryptor>,
+    ) -> crate::Result<()> {
+        let name_len = u8::try_from(extractor_name.len()).map_err(|_| {
+            crate::Error::InvalidConfig("key extractor name must be at most 255 bytes")
+        })?;
+
+        let mut bytes = vec![name_len];
+        bytes.extend_from_slice(extractor_name.as_bytes());
+
+        if !hashes.is_empty() {
+            let mut builder = BloomConstructionPolicy::default().init(hashes.len());
+
+            for &hash in hashes {
+                builder.set_with_hash(hash);
+            }
+
+            bytes.extend(builder.build());
+        }
+
+        file_writer.start("derived_key_filter")?;
+
+        Block::write_into(
+            file_writer,
+            &bytes,
+            crate::table::block::BlockType::Filter,
+            CompressionType::None,
+            encryptor,
+        )?;
+
+        Ok(())
+    }
+
     /// Writes an item.
     ///
     /// # Note
@@ -255,6 +353,19 @@ impl Writer {
             if self.bloom_policy.is_active() {
                 self.filter_writer.register_key(&user_key)?;
             }
+
+            if let Some(derived_key) = self
+                .key_extractor
+                .as_ref()
+                .and_then(|extractor| extractor.extract(&user_key))
+            {
+                let hash = crate::table::filter::standard_bloom::Builder::get_hash(derived_key);
+
+                // NOTE: Consecutive keys often share the same derived key (e.g. a prefix)
+                if self.derived_key_hashes.last() != Some(&hash) {
+                    self.derived_key_hashes.push(hash);
+                }
+            }
         }
 
         if self.meta.first_key.is_none() {
@@ -299,6 +410,7 @@ impl Writer {
             &self.block_buffer,
             super::block::BlockType::Data,
             self.data_block_compression,
+            self.encryptor.as_deref(),
         )?;
 
         self.meta.uncompressed_size += u64::from(header.uncompressed_length);
@@ -309,6 +421,10 @@ impl Writer {
         )]
         let bytes_written = BlockHeader::serialized_len() as u32 + header.data_length;
 
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.request(u64::from(bytes_written));
+        }
+
         self.index_writer
             .register_data_block(KeyedBlockHandle::new(
                 last.key.user_key.clone(),
@@ -353,7 +469,7 @@ impl Writer {
 
         // No items written! Just delete table file and return nothing
         if self.meta.item_count == 0 {
-            std::fs::remove_file(&self.path)?;
+            self.fs.remove_file(&self.path)?;
             return Ok(None);
         }
 
@@ -363,6 +479,16 @@ impl Writer {
         // Write filter
         self.filter_writer.finish(&mut self.file_writer)?;
 
+        // Write derived key filter
+        if let Some(extractor) = &self.key_extractor {
+            Self::write_derived_key_filter(
+                &mut self.file_writer,
+                extractor.name(),
+                &self.derived_key_hashes,
+                self.encryptor.as_deref(),
+            )?;
+        }
+
         if !self.linked_blob_files.is_empty() {
             use byteorder::{WriteBytesExt, LE};
 
@@ -402,7 +528,7 @@ impl Writer {
                     &self.index_block_compression.encode_into_vec(),
                 ),
                 meta("crate_version", env!("CARGO_PKG_VERSION").as_bytes()),
-                meta("created_at", &unix_timestamp().as_nanos().to_le_bytes()),
+                meta("created_at", &self.clock.now().as_nanos().to_le_bytes()),
                 meta(
                     "data_block_count",
                     &(self.meta.data_block_count as u64).to_le_bytes(),
@@ -478,6 +604,7 @@ impl Writer {
                 &self.block_buffer,
                 crate::table::block::BlockType::Meta,
                 CompressionType::None,
+                self.encryptor.as_deref(),
             )?;
         };
 
@@ -491,7 +618,8 @@ impl Writer {
             clippy::expect_used,
             reason = "if there's no parent folder, something has gone horribly wrong"
         )]
-        fsync_directory(self.path.parent().expect("should have folder"))?;
+        self.fs
+            .sync_directory(self.path.parent().expect("should have folder"))?;
 
         log::debug!(
             "Written {} items in {} blocks into new table file #{}, written {} MiB",
@@ -501,6 +629,6 @@ impl Writer {
             *self.meta.file_pos / 1_024 / 1_024,
         );
 
-        Ok(Some((self.table_id, checksum.into())))
+        Ok(Some((self.table_id, checksum)))
     }
 }
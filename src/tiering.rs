@@ -0,0 +1,549 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Tiering of cold files to an object store.
+//!
+//! Tables in the last level (and blob files that are only referenced by them) are rarely
+//! rewritten, so they can be moved to cheaper remote storage (e.g. S3),
+//! while hot data stays on local disk.
+//!
+//! The [`TieredFilesystem`] wraps the local [`Filesystem`], and is passed to the tree
+//! using [`crate::Config::with_filesystem`]. [`offload_cold_files`] uploads cold files
+//! using a [`RemoteStorage`] and replaces them with small local stubs, that point to their object.
+//!
+//! Opening a stub does not download its object. Reads are served in fixed-size ranges
+//! (see [`TieredFilesystem::with_fetch_size`]), which are fetched on demand and kept in a
+//! bounded in-memory cache (see [`TieredFilesystem::with_cache_capacity`]).
+//! Concurrent reads of the same range wait for a single fetch, reads of other ranges
+//! and other objects are not blocked. Recovering a tree only fetches the ranges that hold
+//! the metadata of each file.
+//!
+//! ```
+//! # use lsm_tree::{AbstractTree, Config, SequenceNumberCounter, StdFilesystem};
+//! # use lsm_tree::tiering::{offload_cold_files, RemoteStorage, TieredFilesystem};
+//! # use std::sync::Arc;
+//! #
+//! # #[derive(Debug)]
+//! # struct S3;
+//! #
+//! # impl RemoteStorage for S3 {
+//! #     fn upload(&self, _: &str, _: &mut dyn std::io::Read) -> std::io::Result<()> { Ok(()) }
+//! #     fn download_range(&self, _: &str, _: u64, _: &mut [u8]) -> std::io::Result<()> { Ok(()) }
+//! #     fn delete(&self, _: &str) -> std::io::Result<()> { Ok(()) }
+//! # }
+//! #
+//! # let folder = tempfile::tempdir()?;
+//! let fs = Arc::new(TieredFilesystem::new(Arc::new(StdFilesystem), Arc::new(S3)));
+//!
+//! let tree = Config::new(folder, SequenceNumberCounter::default())
+//!     .with_filesystem(fs.clone())
+//!     .open()?;
+//!
+//! // Periodically, e.g. after compactions
+//! offload_cold_files(&tree, &fs)?;
+//! #
+//! # Ok::<(), lsm_tree::Error>(())
+//! ```
+
+use crate::{fs::rewrite_atomic, AbstractTree, FileHandle, Filesystem, Slice};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use quick_cache::{sync::Cache as QuickCache, Weighter};
+use std::{
+    collections::HashSet,
+    io::{BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Magic bytes of a local stub of an offloaded file
+const STUB_MAGIC: &[u8; 4] = b"LSMR";
+
+/// Default size of the ranges that are fetched from the remote storage
+const DEFAULT_FETCH_SIZE: u64 = 256 * 1_024;
+
+/// Default capacity of the cache of fetched ranges
+const DEFAULT_CACHE_CAPACITY: u64 = 64 * 1_024 * 1_024;
+
+/// Object store to offload cold files to
+///
+/// Objects are immutable, and are written once, then read any number of times.
+pub trait RemoteStorage: std::fmt::Debug + Send + Sync + 'static {
+    /// Uploads an object, replacing it if it exists.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn upload(&self, key: &str, reader: &mut dyn Read) -> std::io::Result<()>;
+
+    /// Downloads a range of an object, starting at the given offset, filling the buffer.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, the object does not exist,
+    /// or the range is out of bounds.
+    fn download_range(&self, key: &str, offset: u64, buf: &mut [u8]) -> std::io::Result<()>;
+
+    /// Deletes an object.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn delete(&self, key: &str) -> std::io::Result<()>;
+}
+
+/// Local stub of an offloaded file
+struct Stub {
+    /// Object key in the remote storage
+    key: String,
+
+    /// Size of the original file in bytes
+    file_size: u64,
+}
+
+impl Stub {
+    fn encode_into_vec(&self) -> std::io::Result<Vec<u8>> {
+        let mut bytes = vec![];
+        bytes.write_all(STUB_MAGIC)?;
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "keys are derived from paths"
+        )]
+        bytes.write_u16::<LE>(self.key.len() as u16)?;
+        bytes.write_all(self.key.as_bytes())?;
+
+        bytes.write_u64::<LE>(self.file_size)?;
+
+        Ok(bytes)
+    }
+
+    /// Reads a stub, returning `None` if the file is not a stub.
+    fn read(fs: &dyn Filesystem, path: &Path) -> std::io::Result<Option<Self>> {
+        let mut file = fs.open(path)?;
+
+        let mut magic = [0; STUB_MAGIC.len()];
+
+        if let Err(e) = file.read_exact(&mut magic) {
+            return match e.kind() {
+                std::io::ErrorKind::UnexpectedEof => Ok(None),
+                _ => Err(e),
+            };
+        }
+
+        if &magic != STUB_MAGIC {
+            return Ok(None);
+        }
+
+        let mut reader = BufReader::new(file);
+
+        let key_len = reader.read_u16::<LE>()?;
+        let mut key = vec![0; key_len.into()];
+        reader.read_exact(&mut key)?;
+
+        let key = String::from_utf8(key).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid stub object key")
+        })?;
+
+        let file_size = reader.read_u64::<LE>()?;
+
+        Ok(Some(Self { key, file_size }))
+    }
+}
+
+/// Cache key of a fetched range, the object key and the index of the range
+type RangeKey = (Arc<str>, u64);
+
+#[derive(Clone)]
+struct RangeWeighter;
+
+impl Weighter<RangeKey, Slice> for RangeWeighter {
+    fn weight(&self, _: &RangeKey, range: &Slice) -> u64 {
+        range.len() as u64
+    }
+}
+
+type RangeCache = QuickCache<RangeKey, Slice, RangeWeighter>;
+
+/// [`Filesystem`] that transparently fetches offloaded files from a [`RemoteStorage`]
+///
+/// All other operations are passed to the inner filesystem.
+#[derive(Debug)]
+pub struct TieredFilesystem {
+    inner: Arc<dyn Filesystem>,
+    remote: Arc<dyn RemoteStorage>,
+
+    /// Fetched ranges of offloaded files
+    cache: Arc<RangeCache>,
+
+    fetch_size: u64,
+}
+
+impl TieredFilesystem {
+    /// Creates a new tiered filesystem.
+    ///
+    /// Offloaded files are fetched in ranges of 256 KiB,
+    /// and up to 64 MiB of fetched ranges are cached.
+    #[must_use]
+    pub fn new(inner: Arc<dyn Filesystem>, remote: Arc<dyn RemoteStorage>) -> Self {
+        Self {
+            inner,
+            remote,
+            cache: Arc::new(Self::create_cache(DEFAULT_CACHE_CAPACITY)),
+            fetch_size: DEFAULT_FETCH_SIZE,
+        }
+    }
+
+    fn create_cache(capacity: u64) -> RangeCache {
+        use quick_cache::sync::DefaultLifecycle;
+
+        #[expect(clippy::expect_used, reason = "nothing we can do if it fails")]
+        let opts = quick_cache::OptionsBuilder::new()
+            .weight_capacity(capacity)
+            .estimated_items_capacity(1_000)
+            .build()
+            .expect("cache options should be valid");
+
+        QuickCache::with_options(
+            opts,
+            RangeWeighter,
+            std::hash::RandomState::default(),
+            DefaultLifecycle::default(),
+        )
+    }
+
+    /// Sets the capacity of the cache of fetched ranges in bytes.
+    ///
+    /// Default = 64 MiB
+    #[must_use]
+    pub fn with_cache_capacity(mut self, bytes: u64) -> Self {
+        self.cache = Arc::new(Self::create_cache(bytes));
+        self
+    }
+
+    /// Sets the size of the ranges that are fetched from the remote storage in bytes.
+    ///
+    /// Smaller ranges fetch less unneeded data for point reads,
+    /// larger ranges need fewer requests for scans.
+    ///
+    /// Default = 256 KiB
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size is 0.
+    #[must_use]
+    pub fn with_fetch_size(mut self, bytes: u64) -> Self {
+        assert!(bytes > 0, "fetch size should not be 0");
+        self.fetch_size = bytes;
+        self
+    }
+
+    /// Returns the amount of cached bytes of offloaded files.
+    #[must_use]
+    pub fn cached_bytes(&self) -> u64 {
+        self.cache.weight()
+    }
+
+    /// Derives the object key of a file from its path.
+    fn object_key(path: &Path) -> String {
+        path.components()
+            .filter_map(|component| match component {
+                std::path::Component::Normal(name) => Some(name.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Returns `true` if the file has been offloaded.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn is_offloaded(&self, path: &Path) -> std::io::Result<bool> {
+        Stub::read(&*self.inner, path).map(|stub| stub.is_some())
+    }
+
+    /// Uploads a file to the remote storage, and replaces it with a local stub.
+    ///
+    /// Files that are already offloaded are skipped.
+    /// Only immutable files (tables and blob files) should be offloaded.
+    ///
+    /// Returns `true` if the file was uploaded.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn offload(&self, path: &Path) -> std::io::Result<bool> {
+        if Stub::read(&*self.inner, path)?.is_some() {
+            return Ok(false);
+        }
+
+        let key = Self::object_key(path);
+
+        let mut file = self.inner.open(path)?;
+        let file_size = file.len()?;
+        self.remote.upload(&key, &mut file)?;
+
+        // NOTE: Already opened files stay readable, until they are closed
+        let stub = Stub { key, file_size };
+        rewrite_atomic(&*self.inner, path, &stub.encode_into_vec()?)?;
+
+        log::debug!("Offloaded {} ({file_size}B)", path.display());
+
+        Ok(true)
+    }
+
+    /// Evicts the cached ranges of an offloaded file.
+    fn evict(&self, stub: &Stub) {
+        let key: Arc<str> = stub.key.as_str().into();
+
+        for idx in 0..stub.file_size.div_ceil(self.fetch_size) {
+            self.cache.remove(&(key.clone(), idx));
+        }
+    }
+}
+
+/// Read-only handle of an offloaded file, see [`TieredFilesystem`]
+#[derive(Debug)]
+struct RemoteFile {
+    key: Arc<str>,
+    file_size: u64,
+    pos: u64,
+    remote: Arc<dyn RemoteStorage>,
+    cache: Arc<RangeCache>,
+    fetch_size: u64,
+}
+
+impl RemoteFile {
+    /// Returns a range of the file, fetching it if it is not cached.
+    fn range(&self, idx: u64) -> std::io::Result<Slice> {
+        self.cache.get_or_insert_with(&(self.key.clone(), idx), || {
+            let offset = idx * self.fetch_size;
+            let len = self.fetch_size.min(self.file_size.saturating_sub(offset));
+
+            let mut buf = vec![0; usize::try_from(len).map_err(std::io::Error::other)?];
+            self.remote.download_range(&self.key, offset, &mut buf)?;
+
+            log::trace!("Fetched {len}B at {offset} of object {:?}", self.key);
+
+            Ok(Slice::from(buf))
+        })
+    }
+
+    fn read_only() -> std::io::Error {
+        std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "offloaded files are read-only",
+        )
+    }
+}
+
+impl Read for RemoteFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let bytes_read = self.read_at(buf, self.pos)?;
+        self.pos += bytes_read as u64;
+        Ok(bytes_read)
+    }
+}
+
+impl Write for RemoteFile {
+    fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+        Err(Self::read_only())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for RemoteFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.file_size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        self.pos = new_pos.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.pos)
+    }
+}
+
+impl FileHandle for RemoteFile {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+        let mut bytes_read = 0;
+
+        while bytes_read < buf.len() {
+            let pos = offset + bytes_read as u64;
+
+            if pos >= self.file_size {
+                break;
+            }
+
+            let range = self.range(pos / self.fetch_size)?;
+
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "the offset is smaller than the fetch size, which fits into the range"
+            )]
+            let src = range
+                .get((pos % self.fetch_size) as usize..)
+                .unwrap_or_default();
+
+            let dest = buf.get_mut(bytes_read..).unwrap_or_default();
+            let len = src.len().min(dest.len());
+
+            if len == 0 {
+                break;
+            }
+
+            if let (Some(dest), Some(src)) = (dest.get_mut(..len), src.get(..len)) {
+                dest.copy_from_slice(src);
+            }
+
+            bytes_read += len;
+        }
+
+        Ok(bytes_read)
+    }
+
+    fn len(&self) -> std::io::Result<u64> {
+        Ok(self.file_size)
+    }
+
+    fn try_clone(&self) -> std::io::Result<Box<dyn FileHandle>> {
+        Ok(Box::new(Self {
+            key: self.key.clone(),
+            file_size: self.file_size,
+            pos: 0,
+            remote: self.remote.clone(),
+            cache: self.cache.clone(),
+            fetch_size: self.fetch_size,
+        }))
+    }
+
+    fn set_len(&self, _: u64) -> std::io::Result<()> {
+        Err(Self::read_only())
+    }
+
+    fn sync_all(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Filesystem for TieredFilesystem {
+    fn open(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        match Stub::read(&*self.inner, path)? {
+            Some(stub) => Ok(Box::new(RemoteFile {
+                key: stub.key.into(),
+                file_size: stub.file_size,
+                pos: 0,
+                remote: self.remote.clone(),
+                cache: self.cache.clone(),
+                fetch_size: self.fetch_size,
+            })),
+            None => self.inner.open(path),
+        }
+    }
+
+    fn open_writable(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        self.inner.open_writable(path)
+    }
+
+    fn create_new(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        self.inner.create_new(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        self.inner.create_dir_all(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        self.inner.read_dir(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        if let Ok(Some(stub)) = Stub::read(&*self.inner, path) {
+            self.remote.delete(&stub.key)?;
+            self.evict(&stub);
+        }
+
+        self.inner.remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        self.inner.remove_dir(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        self.inner.exists(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> std::io::Result<bool> {
+        self.inner.is_dir(path)
+    }
+
+    fn sync_directory(&self, path: &Path) -> std::io::Result<()> {
+        self.inner.sync_directory(path)
+    }
+}
+
+/// Offloads all tables in the last level of a tree, and the blob files that are
+/// only referenced by them, to remote storage.
+///
+/// The tree needs to use the given filesystem.
+///
+/// Returns the amount of files that were uploaded.
+///
+/// # Errors
+///
+/// Will return `Err` if an IO error occurs.
+pub fn offload_cold_files(tree: &impl AbstractTree, fs: &TieredFilesystem) -> crate::Result<usize> {
+    let version = tree.current_version();
+
+    let last_level_idx = version.level_count() - 1;
+
+    let mut cold_tables = vec![];
+    let mut hot_blob_file_ids = HashSet::new();
+
+    for (idx, level) in version.iter_levels().enumerate() {
+        for run in level.iter() {
+            for table in run.iter() {
+                if idx == last_level_idx {
+                    cold_tables.push(table.clone());
+                } else if let Some(blob_files) = table.list_blob_file_references()? {
+                    hot_blob_file_ids.extend(blob_files.iter().map(|f| f.blob_file_id));
+                }
+            }
+        }
+    }
+
+    let mut count = 0;
+
+    for table in cold_tables {
+        if fs.offload(&table.path)? {
+            count += 1;
+        }
+    }
+
+    for blob_file in version.blob_files.iter() {
+        if !hot_blob_file_ids.contains(&blob_file.id()) && fs.offload(blob_file.path())? {
+            count += 1;
+        }
+    }
+
+    log::info!("Offloaded {count} cold files");
+
+    Ok(count)
+}
@@ -2,8 +2,35 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+use std::time::Duration;
+
+/// Time source of a tree
+///
+/// The clock is used for creation timestamps of tables and blob files,
+/// and for time-based compaction decisions, like TTL-based dropping of tables
+/// in [`crate::compaction::Fifo`].
+///
+/// It can be replaced, e.g. by a manually advanced clock in tests, or a custom time
+/// source on embedded systems.
+///
+/// The default implementation is [`SystemClock`].
+pub trait Clock: std::fmt::Debug + Send + Sync + 'static {
+    /// Returns the current time as duration since the unix epoch.
+    fn now(&self) -> Duration;
+}
+
+/// [`Clock`] implementation using [`std::time::SystemTime`]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        unix_timestamp()
+    }
+}
+
 /// Gets the unix timestamp as a duration
-pub fn unix_timestamp() -> std::time::Duration {
+pub fn unix_timestamp() -> Duration {
     #[cfg(test)]
     {
         if let Some(cell) = NOW_OVERRIDE.get() {
@@ -24,10 +51,10 @@ pub fn unix_timestamp() -> std::time::Duration {
 use std::sync::{Mutex, OnceLock};
 
 #[cfg(test)]
-static NOW_OVERRIDE: OnceLock<Mutex<Option<std::time::Duration>>> = OnceLock::new();
+static NOW_OVERRIDE: OnceLock<Mutex<Option<Duration>>> = OnceLock::new();
 
 #[cfg(test)]
-pub(crate) fn set_unix_timestamp_for_test(value: Option<std::time::Duration>) {
+pub(crate) fn set_unix_timestamp_for_test(value: Option<Duration>) {
     let cell = NOW_OVERRIDE.get_or_init(|| Mutex::new(None));
     *cell.lock().expect("lock is poisoned") = value;
 }
@@ -0,0 +1,238 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{AbstractTree, AnyTree, SeqNo, UserKey, UserValue};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// The transaction conflicted with another write, and was not applied
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conflict;
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "transaction conflict")
+    }
+}
+
+impl std::error::Error for Conflict {}
+
+struct TransactionalTreeInner {
+    tree: AnyTree,
+
+    /// Serializes conflict checks and commits
+    commit_lock: Mutex<()>,
+
+    /// All commits below this sequence number are fully applied
+    visible_seqno: AtomicU64,
+}
+
+/// A tree with optimistic transactions
+///
+/// Transactions read from a snapshot, and buffer their writes until they are committed.
+/// A commit fails with a [`Conflict`] if any key the transaction has read or written
+/// was written by another commit after the transaction's snapshot was taken.
+///
+/// All writes of a transaction share a single sequence number (taken from the tree's
+/// sequence number generator), so they become visible all together.
+///
+/// All writes to the tree should go through transactions, otherwise
+/// they may not be visible to new transactions right away.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{Config, TransactionalTree};
+///
+/// let tree = TransactionalTree::new(Config::new(folder, Default::default()).open()?);
+///
+/// let mut tx1 = tree.begin();
+/// let mut tx2 = tree.begin();
+///
+/// let balance = tx1.get("balance")?.map_or(0, |v| v[0]);
+/// tx1.insert("balance", [balance + 10]);
+///
+/// let balance = tx2.get("balance")?.map_or(0, |v| v[0]);
+/// tx2.insert("balance", [balance + 20]);
+///
+/// assert!(tx1.commit()?.is_ok());
+///
+/// // NOTE: tx2 read a stale balance
+/// assert!(tx2.commit()?.is_err());
+///
+/// assert_eq!(Some([10].as_slice().into()), tree.get("balance")?);
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct TransactionalTree(Arc<TransactionalTreeInner>);
+
+impl TransactionalTree {
+    /// Wraps a tree.
+    #[must_use]
+    pub fn new(tree: AnyTree) -> Self {
+        let visible_seqno = tree.tree_config().seqno.get();
+
+        Self(Arc::new(TransactionalTreeInner {
+            tree,
+            commit_lock: Mutex::default(),
+            visible_seqno: AtomicU64::new(visible_seqno),
+        }))
+    }
+
+    /// Returns the underlying tree.
+    #[must_use]
+    pub fn tree(&self) -> &AnyTree {
+        &self.0.tree
+    }
+
+    /// Returns the sequence number that all committed transactions are visible at.
+    #[must_use]
+    pub fn instant(&self) -> SeqNo {
+        self.0.visible_seqno.load(Ordering::Acquire)
+    }
+
+    /// Starts a transaction that reads from the current snapshot.
+    #[must_use]
+    pub fn begin(&self) -> Transaction {
+        Transaction {
+            tree: self.clone(),
+            read_seqno: self.instant(),
+            read_set: BTreeSet::new(),
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// Retrieves the latest committed value of a key.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> crate::Result<Option<UserValue>> {
+        self.0.tree.get(key, self.instant())
+    }
+}
+
+/// An optimistic transaction, see [`TransactionalTree`]
+///
+/// Dropping a transaction without committing it discards its writes.
+pub struct Transaction {
+    tree: TransactionalTree,
+    read_seqno: SeqNo,
+
+    /// Keys that were read from the snapshot
+    read_set: BTreeSet<UserKey>,
+
+    /// Buffered writes, `None` being a removal
+    writes: BTreeMap<UserKey, Option<UserValue>>,
+}
+
+impl Transaction {
+    /// Returns the sequence number of the transaction's snapshot.
+    #[must_use]
+    pub fn read_seqno(&self) -> SeqNo {
+        self.read_seqno
+    }
+
+    /// Retrieves a key, including the transaction's own writes.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn get<K: AsRef<[u8]>>(&mut self, key: K) -> crate::Result<Option<UserValue>> {
+        let key = key.as_ref();
+
+        if let Some(value) = self.writes.get(key) {
+            return Ok(value.clone());
+        }
+
+        self.read_set.insert(key.into());
+
+        self.tree.0.tree.get(key, self.read_seqno)
+    }
+
+    /// Returns `true` if the key exists, including the transaction's own writes.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn contains_key<K: AsRef<[u8]>>(&mut self, key: K) -> crate::Result<bool> {
+        self.get(key).map(|value| value.is_some())
+    }
+
+    /// Inserts a key-value pair when the transaction is committed.
+    pub fn insert<K: Into<UserKey>, V: Into<UserValue>>(&mut self, key: K, value: V) {
+        self.writes.insert(key.into(), Some(value.into()));
+    }
+
+    /// Removes a key when the transaction is committed.
+    pub fn remove<K: Into<UserKey>>(&mut self, key: K) {
+        self.writes.insert(key.into(), None);
+    }
+
+    /// Returns the number of buffered writes.
+    #[must_use]
+    pub fn write_count(&self) -> usize {
+        self.writes.len()
+    }
+
+    /// Commits the transaction.
+    ///
+    /// Returns `Ok(Err(Conflict))` if another commit wrote one of the keys that were
+    /// read or written by this transaction after its snapshot was taken,
+    /// in which case none of the writes are applied.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, in which case none of the writes are applied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn commit(self) -> crate::Result<Result<(), Conflict>> {
+        let inner = &self.tree.0;
+
+        // NOTE: The lock is held until the writes are visible
+        let _lock = inner.commit_lock.lock().expect("lock is poisoned");
+
+        for key in self.read_set.iter().chain(self.writes.keys()) {
+            if inner
+                .tree
+                .get_key_seqno(key)?
+                .is_some_and(|seqno| seqno >= self.read_seqno)
+            {
+                log::trace!("Transaction conflict on key {key:?}");
+                return Ok(Err(Conflict));
+            }
+        }
+
+        if self.writes.is_empty() {
+            return Ok(Ok(()));
+        }
+
+        // NOTE: Apply all writes as one batch, so they are a single journal record,
+        // and cannot be split across memtables
+        let mut batch = inner.tree.batch();
+
+        for (key, value) in self.writes {
+            match value {
+                Some(value) => batch.insert(key, value),
+                None => batch.remove(key),
+            }
+        }
+
+        let seqno = inner.tree.tree_config().seqno.next();
+        batch.commit(seqno)?;
+
+        inner.visible_seqno.fetch_max(seqno + 1, Ordering::AcqRel);
+
+        Ok(Ok(()))
+    }
+}
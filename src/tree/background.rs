@@ -0,0 +1,187 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::inner::TreeInner;
+use crate::{AbstractTree, AnyTree, BlobTree, SequenceNumberCounter, Tree};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Condvar, Mutex, Weak},
+};
+
+#[derive(Default)]
+struct State {
+    work_requested: bool,
+    paused: bool,
+    stopped: bool,
+
+    /// Number of workers that are currently flushing or compacting
+    running: usize,
+}
+
+/// State shared between a tree and its background workers, see [`Config::background_threads`](crate::Config::background_threads)
+#[derive(Default)]
+pub struct Background {
+    state: Mutex<State>,
+    signal: Condvar,
+}
+
+impl Background {
+    /// Wakes up a worker to check if the tree needs to be flushed or compacted.
+    pub fn request_work(&self) {
+        let mut state = self.state.lock().expect("lock is poisoned");
+        state.work_requested = true;
+        drop(state);
+
+        self.signal.notify_one();
+    }
+
+    /// Stops scheduling new work, and blocks until all running work is done.
+    pub fn pause(&self) {
+        let mut state = self.state.lock().expect("lock is poisoned");
+        state.paused = true;
+
+        while state.running > 0 {
+            state = self.signal.wait(state).expect("lock is poisoned");
+        }
+
+        drop(state);
+    }
+
+    /// Resumes scheduling work, catching up on work that was requested while paused.
+    pub fn resume(&self) {
+        let mut state = self.state.lock().expect("lock is poisoned");
+        state.paused = false;
+        state.work_requested = true;
+        drop(state);
+
+        self.signal.notify_all();
+    }
+
+    /// Stops all workers.
+    pub fn stop(&self) {
+        let mut state = self.state.lock().expect("lock is poisoned");
+        state.stopped = true;
+        drop(state);
+
+        self.signal.notify_all();
+    }
+
+    /// Blocks until there is work to do, or returns `false` if the tree is dropped.
+    fn wait_for_work(&self) -> bool {
+        let mut state = self.state.lock().expect("lock is poisoned");
+
+        loop {
+            if state.stopped {
+                return false;
+            }
+
+            if state.work_requested && !state.paused {
+                state.work_requested = false;
+                state.running += 1;
+                return true;
+            }
+
+            state = self.signal.wait(state).expect("lock is poisoned");
+        }
+    }
+
+    fn finish_work(&self) {
+        let mut state = self.state.lock().expect("lock is poisoned");
+        state.running -= 1;
+        drop(state);
+
+        // NOTE: Wake up callers of `pause` that wait for running work
+        self.signal.notify_all();
+    }
+}
+
+/// Handle to the tree that does not keep it alive
+enum WeakTree {
+    Standard(Weak<TreeInner>),
+    Blob(Weak<TreeInner>, Arc<PathBuf>),
+}
+
+impl WeakTree {
+    fn new(tree: &AnyTree) -> Self {
+        match tree {
+            AnyTree::Standard(tree) => Self::Standard(Arc::downgrade(&tree.0)),
+            AnyTree::Blob(tree) => {
+                Self::Blob(Arc::downgrade(&tree.index.0), tree.blobs_folder.clone())
+            }
+        }
+    }
+
+    fn upgrade(&self) -> Option<AnyTree> {
+        match self {
+            Self::Standard(inner) => inner.upgrade().map(|inner| AnyTree::Standard(Tree(inner))),
+            Self::Blob(inner, blobs_folder) => inner.upgrade().map(|inner| {
+                AnyTree::Blob(BlobTree {
+                    index: Tree(inner),
+                    blobs_folder: blobs_folder.clone(),
+                })
+            }),
+        }
+    }
+}
+
+/// Spawns the background workers of a tree.
+///
+/// The workers only hold a weak reference to the tree, and stop once the tree is dropped.
+pub fn spawn(tree: &AnyTree, count: usize) -> crate::Result<()> {
+    let background = match tree {
+        AnyTree::Standard(tree) => tree.background.clone(),
+        AnyTree::Blob(tree) => tree.index.background.clone(),
+    };
+
+    for idx in 0..count {
+        let background = background.clone();
+        let weak = WeakTree::new(tree);
+
+        std::thread::Builder::new()
+            .name(format!("lsm-tree-background-{idx}"))
+            .spawn(move || {
+                while background.wait_for_work() {
+                    // NOTE: Only keep the tree alive while working on it
+                    if let Some(tree) = weak.upgrade() {
+                        match run(&tree) {
+                            Ok(true) => background.request_work(),
+                            Ok(false) => {}
+                            Err(e) => log::error!("Background work failed: {e:?}"),
+                        }
+                    }
+
+                    background.finish_work();
+                }
+            })?;
+    }
+
+    // NOTE: The tree may need to be compacted right after opening
+    background.request_work();
+
+    Ok(())
+}
+
+/// Flushes the active memtable if it is full, and runs the compaction strategy.
+///
+/// Returns `true` if the tree was changed, so there may be more work to do.
+fn run(tree: &AnyTree) -> crate::Result<bool> {
+    let config = tree.tree_config();
+
+    // NOTE: Snapshots are not tracked by the tree, so only drop old versions
+    // below the watermark the caller supplied
+    let seqno_threshold = config
+        .background_gc_watermark
+        .as_ref()
+        .map_or(0, SequenceNumberCounter::get);
+
+    let version_id = tree.current_version().id();
+
+    if tree.active_memtable_size() >= config.max_memtable_size {
+        tree.flush_active_memtable(seqno_threshold)?;
+    }
+
+    tree.run_compaction(seqno_threshold)?;
+
+    Ok(tree.current_version().id() != version_id)
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::Tree;
+use crate::{
+    file::{sharded_path, BLOBS_FOLDER, MANIFEST_FILE, TABLES_FOLDER},
+    fs::link_or_copy,
+    version::persist_version,
+    AbstractTree,
+};
+use std::path::Path;
+
+impl Tree {
+    /// Writes all files of the current version into a new folder, see [`AbstractTree::checkpoint`].
+    ///
+    /// Memtables need to be flushed by the caller.
+    pub(crate) fn write_checkpoint(&self, path: &Path) -> crate::Result<()> {
+        if self.config.ephemeral {
+            return Err(crate::Error::InvalidConfig(
+                "ephemeral trees can not be checkpointed",
+            ));
+        }
+
+        let fs = &*self.config.fs;
+        let shards = self.config.directory_shards;
+
+        if fs.exists(path)? {
+            return Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists).into());
+        }
+
+        log::info!("Creating checkpoint at {}", path.display());
+
+        // IMPORTANT: Block flushes and compactions, so no file of the version
+        // is deleted while it is linked
+        let _guard = self.freeze_files();
+        let version = self.current_version();
+
+        let table_folder = path.join(TABLES_FOLDER);
+        fs.create_dir_all(&table_folder)?;
+        crate::file::create_shard_folders(fs, &table_folder, shards)?;
+
+        for table in version.iter_tables() {
+            let dest = sharded_path(&table_folder, table.id(), shards);
+            link_or_copy(fs, &table.path, &dest)?;
+        }
+
+        if fs.exists(&self.config.path.join(BLOBS_FOLDER))? {
+            let blob_folder = path.join(BLOBS_FOLDER);
+            fs.create_dir_all(&blob_folder)?;
+            crate::file::create_shard_folders(fs, &blob_folder, shards)?;
+
+            for blob_file in version.blob_files.iter() {
+                let dest = sharded_path(&blob_folder, blob_file.id(), shards);
+                link_or_copy(fs, blob_file.path(), &dest)?;
+            }
+
+            sync_sharded_dir(fs, &blob_folder, shards)?;
+        }
+
+        sync_sharded_dir(fs, &table_folder, shards)?;
+
+        // NOTE: The manifest is never changed after creating the tree
+        link_or_copy(
+            fs,
+            &self.config.path.join(MANIFEST_FILE),
+            &path.join(MANIFEST_FILE),
+        )?;
+
+        persist_version(fs, path, &version)?;
+
+        if let Some(parent) = path.parent() {
+            fs.sync_directory(parent)?;
+        }
+
+        log::info!(
+            "Created checkpoint of version {} with {} tables and {} blob files",
+            version.id(),
+            version.table_count(),
+            version.blob_files.len(),
+        );
+
+        Ok(())
+    }
+}
+
+/// Persists the entries of a table or blob folder, and all of its shard folders.
+fn sync_sharded_dir(fs: &dyn crate::Filesystem, folder: &Path, shards: u16) -> crate::Result<()> {
+    for shard in fs.read_dir(folder)? {
+        if shards > 1 && fs.is_dir(&shard)? {
+            fs.sync_directory(&shard)?;
+        }
+    }
+
+    fs.sync_directory(folder)?;
+
+    Ok(())
+}
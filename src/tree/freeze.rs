@@ -0,0 +1,14 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::compaction::state::CompactionState;
+use std::sync::MutexGuard;
+
+/// Keeps the files of a tree unchanged while it is held, see [`crate::AbstractTree::freeze_files`]
+///
+/// Flushes and compactions wait until the guard is dropped.
+pub struct FreezeGuard<'a> {
+    #[expect(unused, reason = "only held to block manifest changes")]
+    pub(crate) compaction_state: MutexGuard<'a, CompactionState>,
+}
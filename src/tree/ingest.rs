@@ -4,10 +4,15 @@
 
 use super::Tree;
 use crate::{
-    compaction::MoveDown, config::FilterPolicyEntry, table::multi_writer::MultiWriter,
-    AbstractTree, BlobIndirection, SeqNo, UserKey, UserValue,
+    compaction::MoveDown, config::FilterPolicyEntry, key::InternalKey, memtable::Memtable,
+    table::multi_writer::MultiWriter, version::SuperVersion, AbstractTree, BlobIndirection,
+    Checksum, KeyRange, SeqNo, SequenceNumberCounter, Table, TableId, UserKey, UserValue,
+    ValueType,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
 };
-use std::{path::PathBuf, sync::Arc};
 
 pub const INITIAL_CANONICAL_LEVEL: usize = 1;
 
@@ -28,6 +33,8 @@ impl<'a> Ingestion<'a> {
     ///
     /// Will return `Err` if an IO error occurs.
     pub fn new(tree: &'a Tree) -> crate::Result<Self> {
+        tree.config.ensure_writable()?;
+
         let folder = tree.config.path.join(crate::file::TABLES_FOLDER);
         log::debug!("Ingesting into tables in {}", folder.display());
 
@@ -43,11 +50,16 @@ impl<'a> Ingestion<'a> {
 
         // TODO: maybe create a PrepareMultiWriter that can be used by flush, ingest and compaction worker
         let mut writer = MultiWriter::new(
+            tree.config.fs.clone(),
             folder.clone(),
+            tree.config.directory_shards,
             tree.table_id_counter.clone(),
             64 * 1_024 * 1_024,
             6,
         )?
+        .use_clock(tree.config.clock.clone())
+        .use_encryption(tree.config.encryptor.clone())
+        .use_key_extractor(tree.config.key_extractor.clone())
         .use_bloom_policy({
             if let FilterPolicyEntry::Bloom(p) =
                 tree.config.filter_policy.get(INITIAL_CANONICAL_LEVEL)
@@ -134,6 +146,15 @@ impl<'a> Ingestion<'a> {
         Ok(())
     }
 
+    /// Writes an internal value, keeping its sequence number and value type.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub(crate) fn write_internal(&mut self, value: crate::InternalValue) -> crate::Result<()> {
+        self.writer.write(value)
+    }
+
     /// Writes a key-value pair.
     ///
     /// # Errors
@@ -169,8 +190,20 @@ impl<'a> Ingestion<'a> {
     ///
     /// Will return `Err` if an IO error occurs.
     pub fn finish(self) -> crate::Result<()> {
-        use crate::Table;
+        let tree = self.tree;
+        let created_tables = self.finish_tables()?;
+
+        tree.register_tables(&created_tables, None, None)?;
 
+        let last_level_idx = tree.config.level_count - 1;
+
+        tree.compact(Arc::new(MoveDown(0, last_level_idx)), 0)?;
+
+        Ok(())
+    }
+
+    /// Finishes the table writer, returning the written tables without registering them.
+    pub(crate) fn finish_tables(self) -> crate::Result<Vec<Table>> {
         let results = self.writer.finish()?;
 
         log::info!("Finished ingestion writer");
@@ -197,9 +230,15 @@ impl<'a> Ingestion<'a> {
                 //  .run(path, tree_id, cache, descriptor_table);
 
                 Table::recover(
-                    self.folder.join(table_id.to_string()),
+                    crate::file::sharded_path(
+                        &self.folder,
+                        table_id,
+                        self.tree.config.directory_shards,
+                    ),
                     checksum,
                     self.tree.id,
+                    self.tree.config.fs.clone(),
+                    self.tree.config.encryptor.clone(),
                     self.tree.config.cache.clone(),
                     self.tree.config.descriptor_table.clone(),
                     pin_filter,
@@ -210,13 +249,280 @@ impl<'a> Ingestion<'a> {
             })
             .collect::<crate::Result<Vec<_>>>()?;
 
-        self.tree.register_tables(&created_tables, None, None)?;
+        Ok(created_tables)
+    }
+}
+
+/// Returns `true` if the key range contains any item or range tombstone of the tree.
+fn overlaps_tree(super_version: &SuperVersion, key_range: &KeyRange) -> bool {
+    let overlaps_memtable = |memtable: &Memtable| {
+        memtable
+            .range(InternalKey::new(key_range.min().clone(), SeqNo::MAX, ValueType::Tombstone)..)
+            .next()
+            .is_some_and(|item| item.key.user_key <= *key_range.max())
+    };
+
+    overlaps_memtable(&super_version.active_memtable)
+        || super_version
+            .sealed_memtables
+            .iter()
+            .any(|(_, memtable)| overlaps_memtable(memtable))
+        || super_version
+            .version
+            .iter_tables()
+            .any(|table| table.metadata.key_range.overlaps_with_key_range(key_range))
+        || super_version
+            .range_tombstones(SeqNo::MAX)
+            .iter()
+            .any(|rt| key_range.overlaps_with_bounds(&rt.bounds()))
+}
+
+impl Tree {
+    /// Ingests table files that were written by a [`crate::SstBuilder`].
+    ///
+    /// The caller needs to flush the active memtable first, so writes made before the ingestion
+    /// are more likely to be found in tables than in memtables.
+    ///
+    /// Tables that do not overlap any data of the tree are copied into the last level as-is.
+    /// Tables that overlap are rewritten into L0 using a new sequence number, so they shadow older data.
+    pub(crate) fn ingest_external(
+        &self,
+        paths: &[&Path],
+        seqno_generator: &SequenceNumberCounter,
+        visible_seqno: &SequenceNumberCounter,
+    ) -> crate::Result<()> {
+        self.config.ensure_writable()?;
+
+        let external_tables = self.recover_external_tables(paths)?;
+
+        if external_tables.is_empty() {
+            return Ok(());
+        }
+
+        let seqno = seqno_generator.next();
+
+        if self.config.ephemeral {
+            self.insert_external_tables(&external_tables, seqno)?;
+            visible_seqno.fetch_max(seqno + 1);
+            return Ok(());
+        }
+
+        let super_version = self
+            .version_history
+            .read()
+            .map_err(|_| crate::Error::Poisoned)?
+            .latest_version();
+
+        let (overlapping, disjoint): (Vec<_>, Vec<_>) = external_tables
+            .into_iter()
+            .partition(|table| overlaps_tree(&super_version, &table.metadata.key_range));
+
+        drop(super_version);
+
+        let moved_tables = self.copy_external_tables(disjoint)?;
+        let rewritten_tables = self.rewrite_external_tables(&overlapping, seqno)?;
+
+        let moved_ids = moved_tables.iter().map(Table::id).collect::<Vec<_>>();
+
+        let tables = moved_tables
+            .into_iter()
+            .chain(rewritten_tables)
+            .collect::<Vec<_>>();
+
+        self.register_ingested_tables(&tables, &moved_ids)?;
+
+        visible_seqno.fetch_max(seqno + 1);
+
+        log::info!(
+            "Ingested {} tables, {} of them were rewritten",
+            tables.len(),
+            overlapping.len(),
+        );
+
+        // NOTE: The new L0 run may need to be compacted
+        self.request_background_work();
+
+        Ok(())
+    }
+
+    /// Opens the tables to ingest, sorted by key range.
+    ///
+    /// Returns [`crate::Error::InvalidIngestion`] if a table was not written by a
+    /// table builder, or the tables overlap each other.
+    fn recover_external_tables(&self, paths: &[&Path]) -> crate::Result<Vec<Table>> {
+        let config = &self.config;
+
+        let mut external_tables = paths
+            .iter()
+            .map(|path| {
+                // NOTE: Only reads the metadata, the table is never read through the block cache
+                Table::recover_lazily(
+                    path.to_path_buf(),
+                    Checksum::from_raw(0),
+                    self.id,
+                    config.fs.clone(),
+                    config.encryptor.clone(),
+                    config.cache.clone(),
+                    config.descriptor_table.clone(),
+                    false,
+                    false,
+                    #[cfg(feature = "metrics")]
+                    self.metrics.clone(),
+                )
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        if external_tables
+            .iter()
+            .any(|table| table.get_highest_seqno() != 0)
+        {
+            return Err(crate::Error::InvalidIngestion(
+                "table was not written by a table builder",
+            ));
+        }
+
+        external_tables.sort_by(|a, b| a.metadata.key_range.min().cmp(b.metadata.key_range.min()));
 
-        let last_level_idx = self.tree.config.level_count - 1;
+        if !KeyRange::is_disjoint(
+            &external_tables
+                .iter()
+                .map(|table| &table.metadata.key_range)
+                .collect::<Vec<_>>(),
+        ) {
+            return Err(crate::Error::InvalidIngestion(
+                "tables to ingest overlap each other",
+            ));
+        }
+
+        Ok(external_tables)
+    }
 
-        self.tree
-            .compact(Arc::new(MoveDown(0, last_level_idx)), 0)?;
+    /// Writes the items of the tables into the memtable, for ephemeral trees.
+    fn insert_external_tables(&self, tables: &[Table], seqno: SeqNo) -> crate::Result<()> {
+        for table in tables {
+            for item in table.scan()? {
+                let item = item?;
+
+                if item.is_tombstone() {
+                    self.remove(item.key.user_key, seqno);
+                } else {
+                    self.insert(item.key.user_key, item.value, seqno);
+                }
+            }
+        }
 
         Ok(())
     }
+
+    /// Copies tables that do not overlap the tree into the tables folder, keeping their sequence numbers.
+    fn copy_external_tables(&self, tables: Vec<Table>) -> crate::Result<Vec<Table>> {
+        use crate::{file::TABLES_FOLDER, table::builder::copy_with_table_id};
+
+        let config = &self.config;
+
+        let pin_filter = config.filter_block_pinning_policy.get(0);
+        let pin_index = config.index_block_pinning_policy.get(0);
+
+        let folder = config.path.join(TABLES_FOLDER);
+
+        let mut moved_tables = Vec::with_capacity(tables.len());
+
+        for table in tables {
+            let table_id = self.get_next_table_id();
+            let path = crate::file::sharded_path(&folder, table_id, config.directory_shards);
+
+            log::debug!(
+                "Copying table {} to {} for ingestion",
+                table.path.display(),
+                path.display(),
+            );
+
+            let checksum = copy_with_table_id(
+                &*config.fs,
+                config.encryptor.as_deref(),
+                &table.path,
+                &path,
+                table_id,
+            )?;
+
+            moved_tables.push(Table::recover(
+                path,
+                checksum,
+                self.id,
+                config.fs.clone(),
+                config.encryptor.clone(),
+                config.cache.clone(),
+                config.descriptor_table.clone(),
+                pin_filter,
+                pin_index,
+                #[cfg(feature = "metrics")]
+                self.metrics.clone(),
+            )?);
+        }
+
+        Ok(moved_tables)
+    }
+
+    /// Rewrites tables that overlap the tree using the given sequence number.
+    fn rewrite_external_tables(&self, tables: &[Table], seqno: SeqNo) -> crate::Result<Vec<Table>> {
+        if tables.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut ingestion = Ingestion::new(self)?.with_seqno(seqno);
+
+        for table in tables {
+            log::debug!(
+                "Rewriting overlapping table {} for ingestion",
+                table.path.display(),
+            );
+
+            for item in table.scan()? {
+                let mut item = item?;
+                item.key.seqno = seqno;
+                ingestion.write_internal(item)?;
+            }
+        }
+
+        ingestion.finish_tables()
+    }
+
+    /// Adds the ingested tables to a new L0 run, and moves the copied tables into the last level.
+    fn register_ingested_tables(
+        &self,
+        tables: &[Table],
+        moved_ids: &[TableId],
+    ) -> crate::Result<()> {
+        let config = &self.config;
+        let last_level_idx = usize::from(config.level_count - 1);
+
+        let _compaction_state = self
+            .compaction_state
+            .lock()
+            .map_err(|_| crate::Error::Poisoned)?;
+
+        let mut version_lock = self
+            .version_history
+            .write()
+            .map_err(|_| crate::Error::Poisoned)?;
+
+        version_lock.upgrade_version(
+            &*config.fs,
+            &config.path,
+            |current| {
+                let mut copy = current.clone();
+
+                // NOTE: Disjoint tables have sequence number 0, so they can be moved
+                // below all other data of the tree
+                copy.version = copy.version.with_new_l0_run(tables, None, None);
+
+                if !moved_ids.is_empty() {
+                    copy.version = copy.version.with_moved(moved_ids, last_level_idx);
+                }
+
+                Ok(copy)
+            },
+            &config.seqno,
+        )
+    }
 }
@@ -2,14 +2,19 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+use super::background::Background;
 use crate::{
     compaction::state::CompactionState,
     config::Config,
+    journal::Journal,
     stop_signal::StopSignal,
     version::{persist_version, SuperVersions, Version},
-    SequenceNumberCounter, TableId,
+    FileHandle, SequenceNumberCounter, TableId,
+};
+use std::sync::{
+    atomic::{AtomicU64, AtomicU8},
+    Arc, Mutex, RwLock,
 };
-use std::sync::{atomic::AtomicU64, Arc, Mutex, RwLock};
 
 #[cfg(feature = "metrics")]
 use crate::metrics::Metrics;
@@ -49,35 +54,77 @@ pub struct TreeInner {
     /// Tree configuration
     pub config: Config,
 
+    /// Journal of all writes, if enabled
+    pub(crate) journal: Option<Arc<Journal>>,
+
     /// Compaction may take a while; setting the signal to `true`
     /// will interrupt the compaction and kill the worker.
     pub(crate) stop_signal: StopSignal,
 
+    /// Schedules work for the background workers, if any
+    pub(crate) background: Arc<Background>,
+
+    /// Current write stall condition, see [`crate::WriteStallCondition`]
+    pub(crate) write_stall_condition: AtomicU8,
+
     /// Used by major compaction to be the exclusive compaction going on.
     ///
     /// Minor compactions use `major_compaction_lock.read()` instead, so they
     /// can be concurrent next to each other.
     pub(crate) major_compaction_lock: RwLock<()>,
 
+    /// Held shared by every write, and exclusively by conditional writes,
+    /// so no other write can land between their read and write
+    pub(crate) write_lock: RwLock<()>,
+
+    /// Exclusive lock on the tree folder, released when the tree is dropped
+    ///
+    /// Ephemeral trees do not have a lock file.
+    #[expect(unused, reason = "only held to keep the folder locked")]
+    pub(crate) lock_file: Option<Box<dyn FileHandle>>,
+
     #[doc(hidden)]
     #[cfg(feature = "metrics")]
     pub metrics: Arc<Metrics>,
 }
 
 impl TreeInner {
-    pub(crate) fn create_new(config: Config) -> crate::Result<Self> {
-        let version = Version::new(0);
-        persist_version(&config.path, &version)?;
+    pub(crate) fn create_new(
+        config: Config,
+        lock_file: Option<Box<dyn FileHandle>>,
+    ) -> crate::Result<Self> {
+        let version = Version::new(0, config.level_count);
+
+        if !config.ephemeral {
+            persist_version(&*config.fs, &config.path, &version)?;
+        }
+
+        let journal = if config.journal && !config.ephemeral {
+            let folder = config.path.join(crate::file::JOURNAL_FOLDER);
+            Some(Arc::new(Journal::create_new(
+                config.fs.clone(),
+                config.encryptor.clone(),
+                config.durability,
+                folder,
+            )?))
+        } else {
+            None
+        };
 
         Ok(Self {
             id: get_next_tree_id(),
             table_id_counter: SequenceNumberCounter::default(),
             blob_file_id_generator: SequenceNumberCounter::default(),
             config,
+            journal,
             version_history: Arc::new(RwLock::new(SuperVersions::new(version))),
             stop_signal: StopSignal::default(),
+            background: Arc::default(),
+            write_stall_condition: AtomicU8::default(),
             major_compaction_lock: RwLock::default(),
+            write_lock: RwLock::default(),
             compaction_state: Arc::new(Mutex::new(CompactionState::default())),
+            lock_file,
 
             #[cfg(feature = "metrics")]
             metrics: Metrics::default().into(),
@@ -95,5 +142,8 @@ impl Drop for TreeInner {
 
         log::trace!("Sending stop signal to compactors");
         self.stop_signal.send();
+
+        log::trace!("Stopping background workers");
+        self.background.stop();
     }
 }
@@ -2,38 +2,50 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+pub mod background;
+mod checkpoint;
+mod freeze;
 pub mod ingest;
 pub mod inner;
 pub mod sealed;
+mod secondary;
+
+pub use freeze::FreezeGuard;
 
 use crate::{
     blob_tree::FragmentationMap,
+    cdc::CdcReader,
     compaction::{drop_range::OwnedBounds, state::CompactionState, CompactionStrategy},
-    config::Config,
+    config::{Config, Durability, OpenMode},
     file::BLOBS_FOLDER,
     format_version::FormatVersion,
     iter_guard::{IterGuard, IterGuardImpl},
+    journal::{entry::Entry as JournalEntry, Journal},
     manifest::Manifest,
     memtable::Memtable,
+    r#abstract::RawItem,
+    range_tombstone::RangeTombstone,
     slice::Slice,
     table::Table,
     value::InternalValue,
     version::{recovery::recover, SuperVersion, SuperVersions, Version, VersionId},
     vlog::BlobFile,
-    AbstractTree, Cache, Checksum, DescriptorTable, KvPair, SeqNo, SequenceNumberCounter, TableId,
-    TreeType, UserKey, UserValue, ValueType,
+    AbstractTree, Cache, Checksum, DescriptorTable, Encryptor, FileHandle, Filesystem, KvPair,
+    ReadOptions, SeqNo, SequenceNumberCounter, TableId, TreeType, UserKey, UserValue, ValueType,
+    WriteStallCondition,
 };
 use inner::{MemtableId, TreeId, TreeInner};
 use std::{
     ops::{Bound, RangeBounds},
     path::Path,
-    sync::{Arc, Mutex, RwLock},
+    sync::{atomic::AtomicU8, Arc, Mutex, RwLock, RwLockReadGuard},
+    time::Duration,
 };
 
 #[cfg(feature = "metrics")]
 use crate::metrics::Metrics;
 
-pub struct Guard(crate::Result<(UserKey, UserValue)>);
+pub struct Guard(pub(crate) crate::Result<(UserKey, UserValue)>);
 
 impl IterGuard for Guard {
     fn key(self) -> crate::Result<UserKey> {
@@ -79,24 +91,38 @@ impl AbstractTree for Tree {
         self.id
     }
 
-    #[expect(clippy::significant_drop_tightening)]
-    fn get_internal_entry(&self, key: &[u8], seqno: SeqNo) -> crate::Result<Option<InternalValue>> {
-        let version_history_lock = self.version_history.read().expect("lock is poisoned");
-        let super_version = version_history_lock.get_version_for_snapshot(seqno);
+    fn get_key_seqno(&self, key: &[u8]) -> crate::Result<Option<SeqNo>> {
+        let super_version = self.get_version_for_snapshot(SeqNo::MAX);
 
-        if let Some(entry) = super_version.active_memtable.get(key, seqno) {
-            return Ok(ignore_tombstone_value(entry));
-        }
+        let entry_seqno = self
+            .get_newest_version(&super_version, key, SeqNo::MAX)?
+            .map(|entry| entry.key.seqno);
 
-        // Now look in sealed memtables
-        if let Some(entry) =
-            Self::get_internal_entry_from_sealed_memtables(&super_version, key, seqno)
+        let range_tombstone_seqno = super_version
+            .range_tombstones(SeqNo::MAX)
+            .iter()
+            .filter(|rt| rt.contains_key(key))
+            .map(|rt| rt.seqno)
+            .max();
+
+        Ok(entry_seqno.max(range_tombstone_seqno))
+    }
+
+    fn get_internal_entry(&self, key: &[u8], seqno: SeqNo) -> crate::Result<Option<InternalValue>> {
+        let entry = self.get_newest_internal_entry(key, seqno)?;
+
+        // NOTE: Merge operands need to be folded into the older versions of the key
+        if entry
+            .as_ref()
+            .is_some_and(|entry| entry.key.value_type.is_merge_operand())
         {
-            return Ok(ignore_tombstone_value(entry));
+            return self
+                .create_internal_range(&(key..=key), seqno, None)
+                .next()
+                .transpose();
         }
 
-        // Now look in tables... this may involve disk I/O
-        self.get_internal_entry_from_tables(&super_version.version, key, seqno)
+        Ok(entry)
     }
 
     fn current_version(&self) -> Version {
@@ -110,6 +136,15 @@ impl AbstractTree for Tree {
     fn flush_active_memtable(&self, seqno_threshold: SeqNo) -> crate::Result<Option<Table>> {
         log::debug!("Flushing active memtable");
 
+        self.config.ensure_writable()?;
+
+        if self.config.ephemeral {
+            if self.rotate_memtable().is_some() {
+                self.merge_sealed_memtables(seqno_threshold)?;
+            }
+            return Ok(None);
+        }
+
         let Some((table_id, yanked_memtable)) = self.rotate_memtable() else {
             return Ok(None);
         };
@@ -159,6 +194,56 @@ impl AbstractTree for Tree {
         )
     }
 
+    fn range_with_options<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+        options: &ReadOptions,
+    ) -> Box<dyn DoubleEndedIterator<Item = IterGuardImpl> + Send + 'static> {
+        let Some(bounds) = options.bounds(&range) else {
+            return Box::new(std::iter::empty());
+        };
+
+        let version = self.get_version_for_snapshot(seqno);
+        let key_only = options.is_key_only();
+
+        let iter = self
+            .create_internal_range_with(version, &bounds, seqno, None, options.is_fill_cache())
+            .map(move |item| {
+                let kv = item.map(|kv| {
+                    let value = if key_only {
+                        UserValue::empty()
+                    } else {
+                        kv.value
+                    };
+                    (kv.key.user_key, value)
+                });
+                IterGuardImpl::Standard(Guard(kv))
+            });
+
+        options.limit_iter(Box::new(iter))
+    }
+
+    fn raw_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<RawItem>> + Send + 'static> {
+        use crate::range::{IterState, TreeIter};
+
+        let iter_state = IterState {
+            version: self.get_version_for_snapshot(seqno),
+            ephemeral: None,
+            fill_cache: true,
+        };
+
+        Box::new(
+            TreeIter::create_raw_range(iter_state, range, seqno).map(|item| {
+                item.map(|kv| (kv.key.user_key, kv.value, kv.key.seqno, kv.key.value_type))
+            }),
+        )
+    }
+
     /// Returns the number of tombstones in the tree.
     fn tombstone_count(&self) -> u64 {
         self.current_version()
@@ -194,6 +279,14 @@ impl AbstractTree for Tree {
 
         let seqno = seqno_generator.next();
 
+        if self.config.ephemeral {
+            for (key, value) in iter {
+                self.insert(key, value, seqno);
+            }
+            visible_seqno.fetch_max(seqno + 1);
+            return Ok(());
+        }
+
         // TODO: allow ingestion always, by flushing memtable
 
         let mut writer = Ingestion::new(self)?.with_seqno(seqno);
@@ -225,6 +318,19 @@ impl AbstractTree for Tree {
         Ok(())
     }
 
+    fn ingest_tables<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+        seqno_generator: &SequenceNumberCounter,
+        visible_seqno: &SequenceNumberCounter,
+    ) -> crate::Result<()> {
+        // NOTE: Flush first, so writes made before the ingestion are considered when checking for overlaps
+        self.flush_active_memtable(0)?;
+
+        let paths = paths.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+        self.ingest_external(&paths, seqno_generator, visible_seqno)
+    }
+
     fn drop_range<K: AsRef<[u8]>, R: RangeBounds<K>>(&self, range: R) -> crate::Result<()> {
         let (bounds, is_empty) = Self::range_bounds_to_owned_bounds(&range);
 
@@ -245,6 +351,42 @@ impl AbstractTree for Tree {
         self.inner_compact(strategy, 0)
     }
 
+    fn rewrite_all_tables(&self, seqno_threshold: SeqNo) -> crate::Result<()> {
+        let table_id_watermark = self.table_id_counter.get();
+        let strategy = Arc::new(crate::compaction::rewrite::Strategy::new(
+            table_id_watermark,
+        ));
+
+        log::info!("Rewriting all tables below table ID {table_id_watermark}");
+
+        // NOTE: Tables that are hidden by other compactions are rewritten by them
+        while self
+            .current_version()
+            .iter_tables()
+            .any(|table| table.id() < table_id_watermark)
+        {
+            self.compact(strategy.clone(), seqno_threshold)?;
+        }
+
+        Ok(())
+    }
+
+    fn checkpoint<P: AsRef<Path>>(&self, path: P) -> crate::Result<()> {
+        // NOTE: Flush first, so the checkpoint contains all writes made before it
+        self.flush_active_memtable(0)?;
+        self.write_checkpoint(path.as_ref())
+    }
+
+    fn try_catch_up(&self) -> crate::Result<()> {
+        self.catch_up()
+    }
+
+    fn freeze_files(&self) -> FreezeGuard<'_> {
+        FreezeGuard {
+            compaction_state: self.compaction_state.lock().expect("lock is poisoned"),
+        }
+    }
+
     #[doc(hidden)]
     fn major_compact(&self, target_size: u64, seqno_threshold: SeqNo) -> crate::Result<()> {
         let strategy = Arc::new(crate::compaction::major::Strategy::new(target_size));
@@ -260,6 +402,48 @@ impl AbstractTree for Tree {
         self.inner_compact(strategy, seqno_threshold)
     }
 
+    fn compact_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        target_size: u64,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<()> {
+        let (bounds, is_empty) = Self::range_bounds_to_owned_bounds(&range);
+
+        if is_empty {
+            return Ok(());
+        }
+
+        let strategy = Arc::new(crate::compaction::compact_range::Strategy::new(
+            bounds,
+            target_size,
+        ));
+
+        // IMPORTANT: Write lock so we can be the only compaction going on
+        let _lock = self
+            .0
+            .major_compaction_lock
+            .write()
+            .expect("lock is poisoned");
+
+        log::info!("Starting compact_range compaction");
+        self.inner_compact(strategy, seqno_threshold)
+    }
+
+    fn pause_background_work(&self) {
+        self.background.pause();
+    }
+
+    fn resume_background_work(&self) {
+        self.background.resume();
+    }
+
+    fn write_stall_condition(&self) -> WriteStallCondition {
+        self.write_stall_condition
+            .load(std::sync::atomic::Ordering::Acquire)
+            .into()
+    }
+
     fn l0_run_count(&self) -> usize {
         self.current_version()
             .level(0)
@@ -311,10 +495,18 @@ impl AbstractTree for Tree {
         use crate::{compaction::stream::CompactionStream, file::TABLES_FOLDER, table::Writer};
         use std::time::Instant;
 
+        // NOTE: Ephemeral trees keep sealed memtables around instead of writing tables
+        if self.config.ephemeral {
+            return Ok(None);
+        }
+
+        self.config.ensure_writable()?;
+
         let start = Instant::now();
 
         let folder = self.config.path.join(TABLES_FOLDER);
-        let table_file_path = folder.join(table_id.to_string());
+        let table_file_path =
+            crate::file::sharded_path(&folder, table_id, self.config.directory_shards);
 
         let data_block_size = self.config.data_block_size_policy.get(0);
 
@@ -334,7 +526,11 @@ impl AbstractTree for Tree {
             table_file_path.display(),
         );
 
-        let mut table_writer = Writer::new(table_file_path, table_id, 0)?
+        let mut table_writer = Writer::new(self.config.fs.clone(), table_file_path, table_id, 0)?
+            .use_clock(self.config.clock.clone())
+            .use_encryption(self.config.encryptor.clone())
+            .use_rate_limiter(self.config.rate_limiter.clone())
+            .use_key_extractor(self.config.key_extractor.clone())
             .use_data_block_restart_interval(data_block_restart_interval)
             .use_index_block_restart_interval(index_block_restart_interval)
             .use_data_block_compression(data_block_compression)
@@ -359,7 +555,10 @@ impl AbstractTree for Tree {
         }
 
         let iter = memtable.iter().map(Ok);
-        let compaction_filter = CompactionStream::new(iter, seqno_threshold);
+        let compaction_filter = CompactionStream::new(iter, seqno_threshold)
+            .with_merge_operator(self.config.merge_operator.clone())
+            .with_range_tombstones(memtable.range_tombstones())
+            .expire_values(self.config.clock.now());
 
         for item in compaction_filter {
             table_writer.write(item?)?;
@@ -367,6 +566,10 @@ impl AbstractTree for Tree {
 
         let result = self.consume_writer(table_writer)?;
 
+        if result.is_none() {
+            self.release_empty_memtable(table_id)?;
+        }
+
         log::debug!("Flushed memtable {table_id:?} in {:?}", start.elapsed());
 
         Ok(result.map(|table| (table, None)))
@@ -385,10 +588,13 @@ impl AbstractTree for Tree {
             blob_files.map(<[BlobFile]>::len).unwrap_or_default(),
         );
 
+        let memtable_ids = tables.iter().map(Table::id).collect::<Vec<_>>();
+
         let mut _compaction_state = self.compaction_state.lock().expect("lock is poisoned");
         let mut version_lock = self.version_history.write().expect("lock is poisoned");
 
         version_lock.upgrade_version(
+            &*self.config.fs,
             &self.config.path,
             |current| {
                 let mut copy = current.clone();
@@ -401,14 +607,23 @@ impl AbstractTree for Tree {
 
                 for table in tables {
                     log::trace!("releasing sealed memtable {}", table.id());
-                    copy.sealed_memtables = Arc::new(copy.sealed_memtables.remove(table.id()));
+                    copy.release_sealed_memtable(table.id());
                 }
 
+                copy.version = self.with_flushed_journal_files(&copy.version, &memtable_ids);
+
                 Ok(copy)
             },
             &self.config.seqno,
         )?;
 
+        if let Some(journal) = &self.journal {
+            journal.mark_flushed(&memtable_ids)?;
+        }
+
+        // NOTE: The new L0 run may need to be compacted
+        self.request_background_work();
+
         Ok(())
     }
 
@@ -421,6 +636,8 @@ impl AbstractTree for Tree {
     }
 
     fn set_active_memtable(&self, memtable: Memtable) {
+        self.register_derived_keys(&memtable);
+
         self.version_history
             .write()
             .expect("lock is poisoned")
@@ -429,6 +646,8 @@ impl AbstractTree for Tree {
     }
 
     fn add_sealed_memtable(&self, id: MemtableId, memtable: Arc<Memtable>) {
+        self.register_derived_keys(&memtable);
+
         let mut version_lock = self.version_history.write().expect("lock is poisoned");
         version_lock.append_sealed_memtable(id, memtable);
     }
@@ -486,6 +705,12 @@ impl AbstractTree for Tree {
         let yanked_memtable = super_version.active_memtable;
         let tmp_memtable_id = self.get_next_table_id();
 
+        if let Some(journal) = &self.journal {
+            if let Err(e) = journal.rotate(tmp_memtable_id) {
+                log::error!("Failed to rotate journal: {e:?}");
+            }
+        }
+
         let mut copy = version_history_lock.latest_version();
         copy.seqno = self.config.seqno.next();
         copy.active_memtable = Arc::new(Memtable::default());
@@ -506,6 +731,75 @@ impl AbstractTree for Tree {
         self.current_version().table_count()
     }
 
+    fn derived_key_may_exist<K: AsRef<[u8]>>(&self, derived_key: K) -> crate::Result<bool> {
+        let Some(extractor) = &self.config.key_extractor else {
+            return Ok(true);
+        };
+
+        let hash = crate::hash::hash64(derived_key.as_ref());
+
+        let super_version = self
+            .version_history
+            .read()
+            .expect("lock is poisoned")
+            .latest_version();
+
+        if super_version
+            .active_memtable
+            .may_contain_derived_key_hash(hash)
+            || super_version
+                .sealed_memtables
+                .iter()
+                .any(|(_, mt)| mt.may_contain_derived_key_hash(hash))
+        {
+            return Ok(true);
+        }
+
+        for table in super_version.version.iter_tables() {
+            // NOTE: Tables without a matching filter may contain anything
+            if table
+                .derived_key_may_exist(extractor.name(), hash)?
+                .unwrap_or(true)
+            {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn key_may_exist<K: AsRef<[u8]>>(&self, key: K) -> crate::Result<bool> {
+        let key = key.as_ref();
+
+        let super_version = self
+            .version_history
+            .read()
+            .expect("lock is poisoned")
+            .latest_version();
+
+        // NOTE: Memtables are exact, so the newest version decides
+        if let Some(entry) = super_version
+            .active_memtable
+            .get(key, SeqNo::MAX)
+            .or_else(|| {
+                Self::get_internal_entry_from_sealed_memtables(&super_version, key, SeqNo::MAX)
+            })
+        {
+            return Ok(!entry.is_tombstone()
+                && !super_version.is_range_deleted(key, entry.key.seqno, SeqNo::MAX));
+        }
+
+        let key_hash = crate::table::filter::standard_bloom::Builder::get_hash(key);
+
+        for table in super_version.version.iter_tables() {
+            if table.key_may_exist(key, key_hash)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
     fn level_table_count(&self, idx: usize) -> Option<usize> {
         self.current_version().level(idx).map(|x| x.table_count())
     }
@@ -543,6 +837,66 @@ impl AbstractTree for Tree {
             .sum()
     }
 
+    fn sync(&self) -> crate::Result<()> {
+        match &self.journal {
+            Some(journal) => journal.sync(),
+            None => Ok(()),
+        }
+    }
+
+    fn disk_usage(&self) -> crate::Result<crate::DiskUsage> {
+        let journal_bytes = match &self.journal {
+            Some(journal) => journal.disk_space()?,
+            None => 0,
+        };
+
+        Ok(crate::DiskUsage {
+            levels: self
+                .current_version()
+                .iter_levels()
+                .map(super::version::Level::size)
+                .collect(),
+            blob_bytes: 0,
+            journal_bytes,
+        })
+    }
+
+    fn memory_usage(&self) -> crate::MemoryUsage {
+        let super_version = self
+            .version_history
+            .read()
+            .expect("lock is poisoned")
+            .latest_version();
+
+        crate::MemoryUsage {
+            active_memtable_bytes: super_version.active_memtable.size(),
+            sealed_memtable_bytes: super_version
+                .sealed_memtables
+                .iter()
+                .map(|(_, mt)| mt.size())
+                .sum(),
+            block_cache_bytes: self.config.cache.size(),
+            blob_cache_bytes: 0,
+            pinned_filter_bytes: self.pinned_filter_size() as u64,
+            pinned_block_index_bytes: self.pinned_block_index_size() as u64,
+        }
+    }
+
+    fn approximate_size<K: AsRef<[u8]>, R: RangeBounds<K>>(&self, range: R) -> crate::Result<u64> {
+        let bounds = (
+            range.start_bound().map(AsRef::as_ref),
+            range.end_bound().map(AsRef::as_ref),
+        );
+
+        let mut size = 0;
+
+        for table in self.current_version().iter_tables() {
+            size += table.approximate_size(&bounds)?;
+        }
+
+        Ok(size)
+    }
+
     #[expect(clippy::significant_drop_tightening)]
     fn get_highest_memtable_seqno(&self) -> Option<SeqNo> {
         let version = self
@@ -564,10 +918,7 @@ impl AbstractTree for Tree {
     }
 
     fn get_highest_persisted_seqno(&self) -> Option<SeqNo> {
-        self.current_version()
-            .iter_tables()
-            .map(Table::get_highest_seqno)
-            .max()
+        self.current_version().get_highest_seqno()
     }
 
     fn get<K: AsRef<[u8]>>(&self, key: K, seqno: SeqNo) -> crate::Result<Option<UserValue>> {
@@ -576,73 +927,380 @@ impl AbstractTree for Tree {
             .map(|x| x.value))
     }
 
-    fn insert<K: Into<UserKey>, V: Into<UserValue>>(
+    // NOTE: Override the default implementation to not fold merge operands,
+    // because merging always results in a value, so the newest version decides
+    fn contains_key<K: AsRef<[u8]>>(&self, key: K, seqno: SeqNo) -> crate::Result<bool> {
+        Ok(self
+            .get_newest_internal_entry(key.as_ref(), seqno)?
+            .is_some())
+    }
+
+    fn multi_get<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(
+        &self,
+        keys: I,
+        seqno: SeqNo,
+    ) -> crate::Result<Vec<Option<UserValue>>> {
+        let super_version = self.get_version_for_snapshot(seqno);
+
+        keys.into_iter()
+            .map(|key| {
+                Ok(self
+                    .get_internal_entry_in(&super_version, key.as_ref(), seqno)?
+                    .map(|x| x.value))
+            })
+            .collect()
+    }
+
+    fn try_insert<K: Into<UserKey>, V: Into<UserValue>>(
         &self,
         key: K,
         value: V,
         seqno: SeqNo,
-    ) -> (u64, u64) {
+    ) -> crate::Result<(u64, u64)> {
         let value = InternalValue::from_components(key, value, seqno, ValueType::Value);
         self.append_entry(value)
     }
 
-    fn remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u64, u64) {
-        let value = InternalValue::new_tombstone(key, seqno);
+    fn try_insert_with_ttl<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+        ttl: Duration,
+    ) -> crate::Result<(u64, u64)> {
+        let expires_at = self.config.clock.now().saturating_add(ttl);
+        let value = crate::ttl::encode_value(&value.into(), expires_at);
+
+        let value = InternalValue::from_components(key, value, seqno, ValueType::ExpiringValue);
         self.append_entry(value)
     }
 
-    fn remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u64, u64) {
-        let value = InternalValue::new_weak_tombstone(key, seqno);
+    fn try_add_merge<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        operand: V,
+        seqno: SeqNo,
+    ) -> crate::Result<(u64, u64)> {
+        let value = InternalValue::from_components(key, operand, seqno, ValueType::MergeOperand);
         self.append_entry(value)
     }
-}
 
-impl Tree {
-    pub(crate) fn get_version_for_snapshot(&self, seqno: SeqNo) -> SuperVersion {
-        self.version_history
-            .read()
-            .expect("lock is poisoned")
-            .get_version_for_snapshot(seqno)
+    fn try_remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u64, u64)> {
+        let value = InternalValue::new_tombstone(key, seqno);
+        self.append_entry(value)
     }
 
-    /// Normalizes a user-provided range into owned `Bound<Slice>` values.
-    ///
-    /// Returns a tuple containing:
-    /// - the `OwnedBounds` that mirror the original bounds semantics (including
-    ///   inclusive/exclusive markers and unbounded endpoints), and
-    /// - a `bool` flag indicating whether the normalized range is logically
-    ///   empty (e.g., when the lower bound is greater than the upper bound).
-    ///
-    /// Callers can use the flag to detect empty ranges and skip further work
-    /// while still having access to the normalized bounds for non-empty cases.
-    fn range_bounds_to_owned_bounds<K: AsRef<[u8]>, R: RangeBounds<K>>(
-        range: &R,
-    ) -> (OwnedBounds, bool) {
-        use Bound::{Excluded, Included, Unbounded};
+    fn remove_if<K: Into<UserKey>, V: AsRef<[u8]>>(
+        &self,
+        key: K,
+        expected: V,
+        seqno: SeqNo,
+    ) -> crate::Result<bool> {
+        let key = key.into();
 
-        let start = match range.start_bound() {
-            Included(key) => Included(Slice::from(key.as_ref())),
-            Excluded(key) => Excluded(Slice::from(key.as_ref())),
-            Unbounded => Unbounded,
-        };
+        // NOTE: Hold the write lock exclusively, so no other write can land between the read and the write
+        let _write_lock = self.write_lock.write().expect("lock is poisoned");
 
-        let end = match range.end_bound() {
-            Included(key) => Included(Slice::from(key.as_ref())),
-            Excluded(key) => Excluded(Slice::from(key.as_ref())),
-            Unbounded => Unbounded,
+        if self
+            .get(&key, SeqNo::MAX)?
+            .is_none_or(|value| *value != *expected.as_ref())
+        {
+            return Ok(false);
+        }
+
+        self.write_entry(InternalValue::new_tombstone(key, seqno))?;
+
+        Ok(true)
+    }
+
+    fn compare_and_swap<K: Into<UserKey>, E: AsRef<[u8]>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        expected: Option<E>,
+        new: Option<V>,
+    ) -> crate::Result<bool> {
+        let key = key.into();
+
+        let _write_lock = self.write_lock.write().expect("lock is poisoned");
+
+        let current = self.get(&key, SeqNo::MAX)?;
+
+        let is_match = match (&current, &expected) {
+            (Some(current), Some(expected)) => **current == *expected.as_ref(),
+            (None, None) => true,
+            _ => false,
         };
 
-        let is_empty =
-            if let (Included(lo) | Excluded(lo), Included(hi) | Excluded(hi)) = (&start, &end) {
-                lo.as_ref() > hi.as_ref()
-            } else {
-                false
-            };
+        if !is_match {
+            return Ok(false);
+        }
 
-        (OwnedBounds { start, end }, is_empty)
+        match new {
+            Some(value) => {
+                let seqno = self.config.seqno.next();
+                self.write_entry(InternalValue::from_components(
+                    key,
+                    value,
+                    seqno,
+                    ValueType::Value,
+                ))?;
+            }
+            None if current.is_some() => {
+                let seqno = self.config.seqno.next();
+                self.write_entry(InternalValue::new_tombstone(key, seqno))?;
+            }
+            None => {}
+        }
+
+        Ok(true)
     }
 
-    /// Opens an LSM-tree in the given directory.
+    fn fetch_update<K: Into<UserKey>, F: FnOnce(Option<&UserValue>) -> Option<UserValue>>(
+        &self,
+        key: K,
+        f: F,
+    ) -> crate::Result<Option<UserValue>> {
+        let key = key.into();
+
+        let _write_lock = self.write_lock.write().expect("lock is poisoned");
+
+        let current = self.get(&key, SeqNo::MAX)?;
+
+        match f(current.as_ref()) {
+            Some(value) => {
+                let seqno = self.config.seqno.next();
+                self.write_entry(InternalValue::from_components(
+                    key,
+                    value,
+                    seqno,
+                    ValueType::Value,
+                ))?;
+            }
+            None if current.is_some() => {
+                let seqno = self.config.seqno.next();
+                self.write_entry(InternalValue::new_tombstone(key, seqno))?;
+            }
+            None => {}
+        }
+
+        Ok(current)
+    }
+
+    #[expect(
+        clippy::significant_drop_tightening,
+        reason = "the version lock is held until the write is in the memtable"
+    )]
+    fn try_remove_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: SeqNo,
+    ) -> crate::Result<(u64, u64)> {
+        let _lock = self.write_lock.read().expect("lock is poisoned");
+        let version_history_lock = self.lock_version_history_for_write();
+        let active_memtable = version_history_lock.latest_version().active_memtable;
+
+        let Some(tombstone) = RangeTombstone::from_bounds(&range, seqno) else {
+            return Ok((0, active_memtable.size()));
+        };
+
+        // NOTE: Journal while holding the version lock, so the write cannot
+        // end up in a different memtable than its journal file belongs to
+        if let Some(journal) = &self.journal {
+            journal.append_range_tombstone(&tombstone)?;
+        }
+
+        Ok(active_memtable.insert_range_tombstone(tombstone))
+    }
+
+    fn try_remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u64, u64)> {
+        let value = InternalValue::new_weak_tombstone(key, seqno);
+        self.append_entry(value)
+    }
+
+    fn batch(&self) -> crate::Batch {
+        crate::Batch::new(self.clone())
+    }
+
+    fn get_versions<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        limit: usize,
+    ) -> crate::Result<Vec<(SeqNo, ValueType, UserValue)>> {
+        Ok(self
+            .get_internal_versions(key.as_ref(), limit)?
+            .into_iter()
+            .map(|item| (item.key.seqno, item.key.value_type, item.value))
+            .collect())
+    }
+}
+
+impl Tree {
+    /// Returns the newest version of a key, without resolving merge operands.
+    #[expect(clippy::significant_drop_tightening)]
+    fn get_newest_internal_entry(
+        &self,
+        key: &[u8],
+        seqno: SeqNo,
+    ) -> crate::Result<Option<InternalValue>> {
+        let version_history_lock = self.version_history.read().expect("lock is poisoned");
+        let super_version = version_history_lock.get_version_for_snapshot(seqno);
+
+        let entry = self.get_newest_version(&super_version, key, seqno)?;
+
+        // NOTE: If the newest version is deleted by a range tombstone, so are all older versions
+        Ok(entry
+            .filter(|entry| !super_version.is_range_deleted(key, entry.key.seqno, seqno))
+            .and_then(ignore_tombstone_value))
+    }
+
+    /// Like [`AbstractTree::get_internal_entry`], but reads from the given super version,
+    /// instead of the super version that was current at `seqno`.
+    pub(crate) fn get_internal_entry_in(
+        &self,
+        super_version: &SuperVersion,
+        key: &[u8],
+        seqno: SeqNo,
+    ) -> crate::Result<Option<InternalValue>> {
+        let entry = self
+            .get_newest_version(super_version, key, seqno)?
+            .filter(|entry| !super_version.is_range_deleted(key, entry.key.seqno, seqno))
+            .and_then(ignore_tombstone_value);
+
+        if entry
+            .as_ref()
+            .is_some_and(|entry| entry.key.value_type.is_merge_operand())
+        {
+            return self
+                .create_internal_range_in(super_version.clone(), &(key..=key), seqno, None)
+                .next()
+                .transpose();
+        }
+
+        Ok(entry)
+    }
+
+    /// Returns up to `limit` stored versions of a key, newest first.
+    pub(crate) fn get_internal_versions(
+        &self,
+        key: &[u8],
+        limit: usize,
+    ) -> crate::Result<Vec<InternalValue>> {
+        use crate::key::InternalKey;
+
+        let super_version = self.get_version_for_snapshot(SeqNo::MAX);
+
+        // NOTE: See memtable.rs for range explanation
+        let range = InternalKey::new(key, SeqNo::MAX, ValueType::Tombstone)
+            ..=InternalKey::new(key, 0, ValueType::Value);
+
+        let mut versions = super_version
+            .active_memtable
+            .range(range.clone())
+            .collect::<Vec<_>>();
+
+        for (_, memtable) in super_version.sealed_memtables.iter() {
+            versions.extend(memtable.range(range.clone()));
+        }
+
+        let user_key = UserKey::from(key);
+
+        for table in super_version
+            .version
+            .iter_tables()
+            .filter(|table| table.is_key_in_key_range(key))
+        {
+            for item in table.range(user_key.clone()..=user_key.clone()) {
+                versions.push(item?);
+            }
+        }
+
+        versions.sort_by_key(|item| std::cmp::Reverse(item.key.seqno));
+        versions.dedup_by_key(|item| item.key.seqno);
+        versions.retain(|item| !super_version.is_range_deleted(key, item.key.seqno, SeqNo::MAX));
+
+        versions.truncate(limit);
+
+        // NOTE: Like in all other reads, expired values are tombstones,
+        // and expiring values are returned without their expiry timestamp
+        let now = self.config.clock.now();
+
+        Ok(versions
+            .into_iter()
+            .map(|item| crate::ttl::resolve(item, now))
+            .collect())
+    }
+
+    /// Returns the newest version of a key, including tombstones.
+    fn get_newest_version(
+        &self,
+        super_version: &SuperVersion,
+        key: &[u8],
+        seqno: SeqNo,
+    ) -> crate::Result<Option<InternalValue>> {
+        let now = self.config.clock.now();
+
+        if let Some(entry) = super_version.active_memtable.get(key, seqno) {
+            return Ok(Some(crate::ttl::resolve(entry, now)));
+        }
+
+        // Now look in sealed memtables
+        if let Some(entry) =
+            Self::get_internal_entry_from_sealed_memtables(super_version, key, seqno)
+        {
+            return Ok(Some(crate::ttl::resolve(entry, now)));
+        }
+
+        // Now look in tables... this may involve disk I/O
+        Ok(self
+            .get_internal_entry_from_tables(&super_version.version, key, seqno)?
+            .map(|entry| crate::ttl::resolve(entry, now)))
+    }
+
+    pub(crate) fn get_version_for_snapshot(&self, seqno: SeqNo) -> SuperVersion {
+        self.version_history
+            .read()
+            .expect("lock is poisoned")
+            .get_version_for_snapshot(seqno)
+    }
+
+    /// Normalizes a user-provided range into owned `Bound<Slice>` values.
+    ///
+    /// Returns a tuple containing:
+    /// - the `OwnedBounds` that mirror the original bounds semantics (including
+    ///   inclusive/exclusive markers and unbounded endpoints), and
+    /// - a `bool` flag indicating whether the normalized range is logically
+    ///   empty (e.g., when the lower bound is greater than the upper bound).
+    ///
+    /// Callers can use the flag to detect empty ranges and skip further work
+    /// while still having access to the normalized bounds for non-empty cases.
+    fn range_bounds_to_owned_bounds<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        range: &R,
+    ) -> (OwnedBounds, bool) {
+        use Bound::{Excluded, Included, Unbounded};
+
+        let start = match range.start_bound() {
+            Included(key) => Included(Slice::from(key.as_ref())),
+            Excluded(key) => Excluded(Slice::from(key.as_ref())),
+            Unbounded => Unbounded,
+        };
+
+        let end = match range.end_bound() {
+            Included(key) => Included(Slice::from(key.as_ref())),
+            Excluded(key) => Excluded(Slice::from(key.as_ref())),
+            Unbounded => Unbounded,
+        };
+
+        let is_empty =
+            if let (Included(lo) | Excluded(lo), Included(hi) | Excluded(hi)) = (&start, &end) {
+                lo.as_ref() > hi.as_ref()
+            } else {
+                false
+            };
+
+        (OwnedBounds { start, end }, is_empty)
+    }
+
+    /// Opens an LSM-tree in the given directory.
     ///
     /// Will recover previous state if the folder was previously
     /// occupied by an LSM-tree, including the previous configuration.
@@ -654,26 +1312,223 @@ impl Tree {
     /// # Errors
     ///
     /// Returns error, if an IO error occurred.
-    pub(crate) fn open(config: Config) -> crate::Result<Self> {
+    pub(crate) fn open(mut config: Config) -> crate::Result<Self> {
         use crate::file::MANIFEST_FILE;
 
+        if config.ephemeral {
+            log::debug!("Opening ephemeral LSM-tree");
+            return Ok(Self(Arc::new(TreeInner::create_new(config, None)?)));
+        }
+
+        if config.temporary && config.temporary_folder.is_none() {
+            let folder = tempfile::Builder::new().prefix("lsm-tree-").tempdir()?;
+            config.path = crate::path::absolute_path(folder.path());
+            config.temporary_folder = Some(Arc::new(folder));
+        }
+
         log::debug!("Opening LSM-tree at {}", config.path.display());
 
         // Check for old version
-        if config.path.join("version").try_exists()? {
+        if config.fs.exists(&config.path.join("version"))? {
             log::error!("It looks like you are trying to open a V1 database - the database needs a manual migration, however a migration tool is not provided, as V1 is extremely outdated.");
             return Err(crate::Error::InvalidVersion(FormatVersion::V1.into()));
         }
 
-        let tree = if config.path.join(MANIFEST_FILE).try_exists()? {
-            Self::recover(config)
+        let manifest_path = config.path.join(MANIFEST_FILE);
+
+        // NOTE: A secondary instance never creates or locks the folder of the primary tree
+        if config.is_secondary() {
+            if !config.fs.exists(&manifest_path)? {
+                return Err(crate::Error::TreeNotFound);
+            }
+
+            return Self::recover(config, None);
+        }
+
+        if !config.fs.exists(&manifest_path)? {
+            // NOTE: Do not create the folder of a tree that is expected to exist
+            if config.open_mode == OpenMode::OpenExisting {
+                return Err(crate::Error::TreeNotFound);
+            }
+
+            // NOTE: Checked before locking, so no lock file is left in a foreign folder
+            if config.fs.exists(&config.path)? {
+                Self::ensure_no_foreign_files(&*config.fs, &config.path)?;
+            }
+        }
+
+        config.fs.create_dir_all(&config.path)?;
+
+        // IMPORTANT: Lock the folder before touching any other file
+        let lock_file = crate::fs::lock_directory(&*config.fs, &config.path)?;
+
+        let tree = if config.fs.exists(&manifest_path)? {
+            if config.open_mode == OpenMode::CreateNew {
+                return Err(crate::Error::TreeAlreadyExists);
+            }
+
+            Self::recover(config, Some(lock_file))
         } else {
-            Self::create_new(config)
+            if config.open_mode == OpenMode::OpenExisting {
+                return Err(crate::Error::TreeNotFound);
+            }
+
+            Self::create_new(config, lock_file)
         }?;
 
+        if let (Some(journal), Durability::SyncInterval(ms)) =
+            (&tree.journal, tree.config.durability)
+        {
+            crate::journal::spawn_sync_thread(journal, Duration::from_millis(ms))?;
+        }
+
         Ok(tree)
     }
 
+    /// Checks that a folder without a manifest only contains files a tree
+    /// creates before its manifest, so a tree is never created over unrelated data.
+    fn ensure_no_foreign_files(fs: &dyn Filesystem, path: &Path) -> crate::Result<()> {
+        use crate::file::{BLOBS_FOLDER, JOURNAL_FOLDER, LOCK_FILE, TABLES_FOLDER};
+
+        for file_path in fs.read_dir(path)? {
+            let name = file_path.file_name().unwrap_or_default();
+
+            // https://en.wikipedia.org/wiki/.DS_Store
+            if name == ".DS_Store" {
+                continue;
+            }
+
+            if [LOCK_FILE, TABLES_FOLDER, BLOBS_FOLDER, JOURNAL_FOLDER]
+                .iter()
+                .any(|known| name == *known)
+            {
+                continue;
+            }
+
+            log::error!(
+                "Refusing to create LSM-tree in {}, because it contains {}",
+                path.display(),
+                name.display(),
+            );
+            return Err(crate::Error::ForeignFolder);
+        }
+
+        Ok(())
+    }
+
+    /// Merges all sealed memtables into a single one, dropping versions below the MVCC watermark.
+    ///
+    /// Ephemeral trees do not have tables, so the merged memtable takes their place.
+    #[expect(clippy::significant_drop_tightening)]
+    pub(crate) fn merge_sealed_memtables(&self, seqno_threshold: SeqNo) -> crate::Result<()> {
+        use crate::{compaction::stream::CompactionStream, merge::Merger};
+
+        let mut version_history_lock = self.version_history.write().expect("lock is poisoned");
+        let super_version = version_history_lock.latest_version();
+
+        let Some((last_id, _)) = super_version.sealed_memtables.iter().next_back() else {
+            return Ok(());
+        };
+
+        log::trace!(
+            "Merging {} sealed memtables into memtable id={last_id}",
+            super_version.sealed_memtables.len(),
+        );
+
+        let merged = Memtable::default();
+
+        let iters = super_version
+            .sealed_memtables
+            .iter()
+            .map(|(_, memtable)| memtable.iter().map(Ok))
+            .collect::<Vec<_>>();
+
+        let range_tombstones = super_version
+            .sealed_memtables
+            .iter()
+            .flat_map(|(_, memtable)| memtable.range_tombstones())
+            .collect::<Vec<_>>();
+
+        for item in CompactionStream::new(Merger::new(iters), seqno_threshold)
+            .with_merge_operator(self.config.merge_operator.clone())
+            .with_range_tombstones(range_tombstones.clone())
+        {
+            let item = item?;
+
+            if let Some(extractor) = &self.config.key_extractor {
+                merged.register_derived_key(&**extractor, &item);
+            }
+
+            merged.insert(item);
+        }
+
+        for tombstone in range_tombstones {
+            merged.insert_range_tombstone(tombstone);
+        }
+
+        let mut copy = super_version.clone();
+        copy.seqno = self.config.seqno.next();
+        copy.sealed_memtables = Arc::new(
+            crate::tree::sealed::SealedMemtables::default().add(*last_id, Arc::new(merged)),
+        );
+
+        version_history_lock.append_version(copy);
+        version_history_lock.free(seqno_threshold);
+
+        Ok(())
+    }
+
+    /// Releases a sealed memtable whose flush did not produce a table,
+    /// so its range tombstones are not lost, and its journal files are not replayed again.
+    #[expect(clippy::significant_drop_tightening)]
+    pub(crate) fn release_empty_memtable(&self, id: MemtableId) -> crate::Result<()> {
+        let has_range_tombstones = self
+            .get_version_for_snapshot(SeqNo::MAX)
+            .sealed_memtables
+            .get(id)
+            .is_some_and(|memtable| memtable.has_range_tombstones());
+
+        if !has_range_tombstones && self.journal.is_none() {
+            return Ok(());
+        }
+
+        let _compaction_state = self.compaction_state.lock().expect("lock is poisoned");
+        let mut version_lock = self.version_history.write().expect("lock is poisoned");
+
+        version_lock.upgrade_version(
+            &*self.config.fs,
+            &self.config.path,
+            |current| {
+                let mut copy = current.clone();
+                copy.release_sealed_memtable(id);
+                copy.version = self.with_flushed_journal_files(&copy.version, &[id]);
+                Ok(copy)
+            },
+            &self.config.seqno,
+        )?;
+
+        if let Some(journal) = &self.journal {
+            journal.mark_flushed(&[id])?;
+        }
+
+        Ok(())
+    }
+
+    /// Records the journal files that are flushed once the given memtables are flushed in a version,
+    /// so they are not replayed on recovery.
+    fn with_flushed_journal_files(
+        &self,
+        version: &Version,
+        memtable_ids: &[MemtableId],
+    ) -> Version {
+        match &self.journal {
+            Some(journal) => {
+                version.with_flushed_journal_files(journal.flushed_file_ids(memtable_ids))
+            }
+            None => version.clone(),
+        }
+    }
+
     pub(crate) fn consume_writer(
         &self,
         writer: crate::table::Writer,
@@ -693,6 +1548,8 @@ impl Tree {
             table_file_path,
             checksum,
             self.id,
+            self.config.fs.clone(),
+            self.config.encryptor.clone(),
             self.config.cache.clone(),
             self.config.descriptor_table.clone(),
             pin_filter,
@@ -776,6 +1633,11 @@ impl Tree {
     ) -> crate::Result<()> {
         use crate::compaction::worker::{do_compaction, Options};
 
+        // NOTE: Ephemeral trees have no tables, compacting only means merging the sealed memtables
+        if self.config.ephemeral {
+            return self.merge_sealed_memtables(mvcc_gc_watermark);
+        }
+
         let mut opts = Options::from_tree(self, strategy);
         opts.mvcc_gc_watermark = mvcc_gc_watermark;
 
@@ -786,6 +1648,94 @@ impl Tree {
         Ok(())
     }
 
+    /// Runs a compaction that rewrites the blob files whose ratio of stale bytes
+    /// is at least `staleness_threshold`, regardless of the configured [`crate::KvSeparationOptions`].
+    pub(crate) fn compact_blob_files(
+        &self,
+        strategy: Arc<dyn CompactionStrategy>,
+        staleness_threshold: f32,
+        mvcc_gc_watermark: SeqNo,
+    ) -> crate::Result<()> {
+        use crate::compaction::worker::{do_compaction, Options};
+
+        let _lock = self
+            .0
+            .major_compaction_lock
+            .read()
+            .expect("lock is poisoned");
+
+        let mut opts = Options::from_tree(self, strategy);
+        opts.mvcc_gc_watermark = mvcc_gc_watermark;
+
+        if let Some(blob_opts) = &mut opts.config.kv_separation_opts {
+            blob_opts.staleness_threshold = staleness_threshold;
+            blob_opts.age_cutoff = 1.0;
+        }
+
+        do_compaction(&opts)
+    }
+
+    /// Asks the compaction strategy for a compaction, and returns it as a job
+    /// that can be run by an external worker, see [`crate::compaction::remote`].
+    ///
+    /// Compactions that do not rewrite data (moving or dropping tables) are
+    /// applied directly, and `None` is returned.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or the tree is encrypted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn prepare_remote_compaction(
+        &self,
+        strategy: Arc<dyn CompactionStrategy>,
+        mvcc_gc_watermark: SeqNo,
+    ) -> crate::Result<Option<crate::compaction::remote::CompactionJob>> {
+        use crate::compaction::worker::Options;
+
+        let _lock = self
+            .0
+            .major_compaction_lock
+            .read()
+            .expect("lock is poisoned");
+
+        let mut opts = Options::from_tree(self, strategy);
+        opts.mvcc_gc_watermark = mvcc_gc_watermark;
+
+        crate::compaction::remote::prepare(&opts)
+    }
+
+    /// Installs the result of a remote compaction job, atomically replacing its input tables.
+    ///
+    /// The output tables are moved from `output_folder` into the tree, so it should be on
+    /// the same file system as the tree.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or the input tables do not exist anymore.
+    pub fn install_remote_compaction<P: AsRef<Path>>(
+        &self,
+        job: &crate::compaction::remote::CompactionJob,
+        output: &crate::compaction::remote::CompactionOutput,
+        output_folder: P,
+        mvcc_gc_watermark: SeqNo,
+    ) -> crate::Result<()> {
+        crate::compaction::remote::install(
+            self,
+            job,
+            output,
+            output_folder.as_ref(),
+            mvcc_gc_watermark,
+        )
+    }
+
+    /// Aborts a remote compaction job, so its input tables can be compacted again.
+    pub fn abort_remote_compaction(&self, job: &crate::compaction::remote::CompactionJob) {
+        crate::compaction::remote::abort(self, job);
+    }
+
     #[doc(hidden)]
     #[must_use]
     pub fn create_iter(
@@ -802,6 +1752,32 @@ impl Tree {
         range: &'a R,
         seqno: SeqNo,
         ephemeral: Option<Arc<Memtable>>,
+    ) -> impl DoubleEndedIterator<Item = crate::Result<InternalValue>> + 'static {
+        let version = self.get_version_for_snapshot(seqno);
+        self.create_internal_range_in(version, range, seqno, ephemeral)
+    }
+
+    /// Like [`Tree::create_internal_range`], but reads from the given super version,
+    /// instead of the super version that was current at `seqno`.
+    pub(crate) fn create_internal_range_in<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        version: SuperVersion,
+        range: &R,
+        seqno: SeqNo,
+        ephemeral: Option<Arc<Memtable>>,
+    ) -> impl DoubleEndedIterator<Item = crate::Result<InternalValue>> + 'static {
+        self.create_internal_range_with(version, range, seqno, ephemeral, true)
+    }
+
+    /// Like [`Tree::create_internal_range_in`], but data blocks that are loaded from disk
+    /// are only inserted into the block cache if `fill_cache` is `true`.
+    pub(crate) fn create_internal_range_with<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        version: SuperVersion,
+        range: &R,
+        seqno: SeqNo,
+        ephemeral: Option<Arc<Memtable>>,
+        fill_cache: bool,
     ) -> impl DoubleEndedIterator<Item = crate::Result<InternalValue>> + 'static {
         use crate::range::{IterState, TreeIter};
         use std::ops::Bound::{self, Excluded, Included, Unbounded};
@@ -820,11 +1796,21 @@ impl Tree {
 
         let bounds: (Bound<UserKey>, Bound<UserKey>) = (lo, hi);
 
-        let version = self.get_version_for_snapshot(seqno);
-
-        let iter_state = { IterState { version, ephemeral } };
+        let iter_state = {
+            IterState {
+                version,
+                ephemeral,
+                fill_cache,
+            }
+        };
 
-        TreeIter::create_range(iter_state, bounds, seqno)
+        TreeIter::create_range(
+            iter_state,
+            bounds,
+            seqno,
+            self.config.merge_operator.clone(),
+            self.config.clock.now(),
+        )
     }
 
     #[doc(hidden)]
@@ -854,26 +1840,311 @@ impl Tree {
         self.create_range(&range, seqno, ephemeral)
     }
 
+    /// Adds the derived keys of an externally built memtable to its derived key set.
+    fn register_derived_keys(&self, memtable: &Memtable) {
+        if let Some(extractor) = &self.config.key_extractor {
+            for item in memtable.iter() {
+                memtable.register_derived_key(&**extractor, &item);
+            }
+        }
+    }
+
     /// Adds an item to the active memtable.
     ///
     /// Returns the added item's size and new size of the memtable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the write could not be journaled, in which case it is not applied.
     #[doc(hidden)]
-    #[must_use]
-    pub fn append_entry(&self, value: InternalValue) -> (u64, u64) {
-        self.version_history
-            .read()
-            .expect("lock is poisoned")
-            .latest_version()
-            .active_memtable
-            .insert(value)
+    pub fn append_entry(&self, value: InternalValue) -> crate::Result<(u64, u64)> {
+        let _lock = self.write_lock.read().expect("lock is poisoned");
+        self.write_entry(value)
+    }
+
+    /// Like [`Tree::append_entry`], for callers that already hold the write lock.
+    #[expect(
+        clippy::significant_drop_tightening,
+        reason = "the version lock is held until the write is in the memtable"
+    )]
+    pub(crate) fn write_entry(&self, value: InternalValue) -> crate::Result<(u64, u64)> {
+        let version_history_lock = self.lock_version_history_for_write();
+
+        // NOTE: Journal while holding the version lock, so the write cannot
+        // end up in a different memtable than its journal file belongs to
+        if let Some(journal) = &self.journal {
+            journal.append(&value)?;
+        }
+
+        let active_memtable = version_history_lock.latest_version().active_memtable;
+
+        if let Some(extractor) = &self.config.key_extractor {
+            active_memtable.register_derived_key(&**extractor, &value);
+        }
+
+        let (item_size, memtable_size) = active_memtable.insert(value);
+
+        if memtable_size >= self.config.max_memtable_size {
+            self.request_background_work();
+        }
+
+        Ok((item_size, memtable_size))
+    }
+
+    /// Acquires the version lock for a write, applying backpressure first, see [`crate::WriteStallOptions`].
+    fn lock_version_history_for_write(&self) -> RwLockReadGuard<'_, SuperVersions> {
+        let Some(opts) = &self.config.write_stall else {
+            return self.version_history.read().expect("lock is poisoned");
+        };
+
+        loop {
+            let version_history_lock = self.version_history.read().expect("lock is poisoned");
+
+            let super_version = version_history_lock.latest_version();
+            let condition = opts.condition(
+                super_version.version.l0().table_count(),
+                super_version.sealed_memtables.len(),
+            );
+
+            self.set_write_stall_condition(condition);
+
+            if condition == WriteStallCondition::Normal {
+                return version_history_lock;
+            }
+
+            // NOTE: Release the lock while waiting, so flushes can install new versions
+            drop(version_history_lock);
+            self.request_background_work();
+            std::thread::sleep(opts.delay);
+
+            if condition == WriteStallCondition::Delayed {
+                return self.version_history.read().expect("lock is poisoned");
+            }
+        }
+    }
+
+    fn set_write_stall_condition(&self, condition: WriteStallCondition) {
+        let prev = self
+            .write_stall_condition
+            .swap(condition.into(), std::sync::atomic::Ordering::AcqRel);
+
+        if prev != u8::from(condition) {
+            log::debug!("Write stall condition changed to {condition:?}");
+
+            if let Some(listener) = &self.config.write_stall_listener {
+                listener.on_write_stall(condition);
+            }
+        }
+    }
+
+    /// Wakes up a background worker, if the tree has any, see [`Config::background_threads`].
+    pub(crate) fn request_background_work(&self) {
+        if self.config.background_threads > 0 {
+            self.background.request_work();
+        }
+    }
+
+    /// Adds multiple items to the active memtable, acquiring the version lock only once,
+    /// so all items end up in the same memtable (and journal file).
+    ///
+    /// Returns the added size and new size of the memtable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the writes could not be journaled, in which case none of them are applied.
+    pub(crate) fn append_entries(&self, values: Vec<InternalValue>) -> crate::Result<(u64, u64)> {
+        let _lock = self.write_lock.read().expect("lock is poisoned");
+        self.write_entries(values)
+    }
+
+    /// Like [`Tree::append_entries`], for callers that already hold the write lock.
+    #[expect(
+        clippy::significant_drop_tightening,
+        reason = "the version lock is held until the writes are in the memtable"
+    )]
+    fn write_entries(&self, values: Vec<InternalValue>) -> crate::Result<(u64, u64)> {
+        let version_history_lock = self.lock_version_history_for_write();
+
+        if let Some(journal) = &self.journal {
+            journal.append_batch(&values)?;
+        }
+
+        let active_memtable = version_history_lock.latest_version().active_memtable;
+
+        let mut added_size = 0;
+        let mut memtable_size = active_memtable.size();
+
+        for value in values {
+            if let Some(extractor) = &self.config.key_extractor {
+                active_memtable.register_derived_key(&**extractor, &value);
+            }
+
+            let (item_size, new_size) = active_memtable.insert(value);
+            added_size += item_size;
+            memtable_size = new_size;
+        }
+
+        if memtable_size >= self.config.max_memtable_size {
+            self.request_background_work();
+        }
+
+        Ok((added_size, memtable_size))
+    }
+
+    /// Applies a batch of pre-sequenced entries, as received from a replication leader.
+    ///
+    /// The entries keep their original sequence numbers and value types, so no local
+    /// sequence number is assigned; the tree's sequence number generator is advanced
+    /// past the highest applied sequence number instead.
+    ///
+    /// Sequence numbers must be monotonically increasing: within a batch, entries may
+    /// share a sequence number (e.g. an atomic write batch on the leader), but every entry
+    /// needs to be newer than all data already in the tree.
+    /// The batch is validated before anything is applied.
+    ///
+    /// Replicated batches should be applied from a single thread, in the leader's order.
+    ///
+    /// Returns the added size and new size of the memtable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, InternalValue};
+    ///
+    /// let tree = Config::new(folder, Default::default()).open()?;
+    /// # let lsm_tree::AnyTree::Standard(tree) = tree else { unreachable!() };
+    ///
+    /// tree.apply_replicated_batch(vec![
+    ///     InternalValue::from_components("a", "abc", 5, lsm_tree::ValueType::Value),
+    ///     InternalValue::new_tombstone("b", 5),
+    /// ])?;
+    /// assert_eq!(Some(5), tree.get_highest_seqno());
+    ///
+    /// // Older entries are rejected
+    /// assert!(tree
+    ///     .apply_replicated_batch(vec![InternalValue::new_tombstone("a", 4)])
+    ///     .is_err());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if sequence numbers are not monotonically increasing, or
+    /// an entry is a blob indirection (which only exists in the leader's blob files).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn apply_replicated_batch<I: IntoIterator<Item = InternalValue>>(
+        &self,
+        entries: I,
+    ) -> crate::Result<(u64, u64)> {
+        let entries = entries.into_iter().collect::<Vec<_>>();
+
+        // NOTE: Hold the write lock exclusively, so no other write can land
+        // between the sequence number check and the apply
+        let _write_lock = self.write_lock.write().expect("lock is poisoned");
+
+        let tree_highest = self.get_highest_seqno();
+        let mut highest = None;
+
+        for entry in &entries {
+            if entry.key.value_type == ValueType::Indirection {
+                return Err(crate::Error::InvalidTag((
+                    "ValueType",
+                    ValueType::Indirection.into(),
+                )));
+            }
+
+            let seqno = entry.key.seqno;
+
+            // NOTE: Entries of the same batch may share a sequence number,
+            // but must not go backwards
+            let is_ordered = match (highest, tree_highest) {
+                (Some(prev), _) => seqno >= prev,
+                (None, Some(prev)) => seqno > prev,
+                (None, None) => true,
+            };
+
+            if !is_ordered {
+                return Err(crate::Error::NonMonotonicSeqNo {
+                    got: seqno,
+                    highest: highest.or(tree_highest).unwrap_or_default(),
+                });
+            }
+
+            highest = Some(seqno);
+        }
+
+        // NOTE: Apply all entries as one batch, so they are a single journal record,
+        // and cannot be split across memtables
+        let sizes = if entries.is_empty() {
+            (0, self.active_memtable_size())
+        } else {
+            self.write_entries(entries)?
+        };
+
+        if let Some(highest) = highest {
+            self.config.seqno.fetch_max(highest + 1);
+        }
+
+        Ok(sizes)
+    }
+
+    /// Returns a change data capture reader that reads all writes,
+    /// starting at the given sequence number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, ChangeOp, Config};
+    ///
+    /// let tree = Config::new(folder, Default::default())
+    ///     .use_journal(true)
+    ///     .open()?;
+    /// # let lsm_tree::AnyTree::Standard(tree) = tree else { unreachable!() };
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.remove("a", 1);
+    ///
+    /// let mut reader = tree.cdc_reader(0)?;
+    ///
+    /// let event = reader.next().expect("should exist")?;
+    /// assert_eq!((0, ChangeOp::Insert), (event.seqno, event.op));
+    ///
+    /// let event = reader.next().expect("should exist")?;
+    /// assert_eq!((1, ChangeOp::Remove), (event.seqno, event.op));
+    /// reader.ack(1)?;
+    ///
+    /// assert!(reader.next().is_none());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return [`crate::Error::InvalidConfig`] if the journal is not enabled.
+    pub fn cdc_reader(&self, from_seqno: SeqNo) -> crate::Result<CdcReader> {
+        let Some(journal) = self.journal.clone() else {
+            return Err(crate::Error::InvalidConfig(
+                "change data capture requires the journal",
+            ));
+        };
+
+        Ok(CdcReader::new(journal, from_seqno))
     }
 
     /// Recovers previous state, by loading the level manifest, tables and blob files.
     ///
+    /// Secondary instances are not locked, and load the state without touching any file.
+    ///
     /// # Errors
     ///
     /// Returns error, if an IO error occurred.
-    fn recover(mut config: Config) -> crate::Result<Self> {
+    fn recover(mut config: Config, lock_file: Option<Box<dyn FileHandle>>) -> crate::Result<Self> {
         use crate::{file::MANIFEST_FILE, stop_signal::StopSignal};
         use inner::get_next_tree_id;
 
@@ -881,8 +2152,8 @@ impl Tree {
 
         let manifest = {
             let manifest_path = config.path.join(MANIFEST_FILE);
-            let reader = sfa::Reader::new(&manifest_path)?;
-            Manifest::decode_from(&manifest_path, &reader)?
+            let reader = crate::fs::read_archive(&*config.fs, &manifest_path)?;
+            Manifest::decode_from(&*config.fs, &manifest_path, &reader)?
         };
 
         if manifest.version != FormatVersion::V3 {
@@ -897,20 +2168,29 @@ impl Tree {
 
         // IMPORTANT: Restore persisted config
         config.level_count = manifest.level_count;
+        config.directory_shards = manifest.directory_shards;
 
         let tree_id = get_next_tree_id();
 
         #[cfg(feature = "metrics")]
         let metrics = Arc::new(Metrics::default());
 
-        let version = Self::recover_levels(
-            &config.path,
-            tree_id,
-            &config.cache,
-            &config.descriptor_table,
-            #[cfg(feature = "metrics")]
-            &metrics,
-        )?;
+        // NOTE: Recovering the levels cleans up orphaned files, which a secondary instance
+        // must never do, so it loads the version of the primary tree once the tree is built
+        let version = if config.is_secondary() {
+            Version::new(0, config.level_count)
+        } else {
+            Self::recover_levels(
+                &config.fs,
+                config.encryptor.as_ref(),
+                &config.path,
+                tree_id,
+                &config.cache,
+                &config.descriptor_table,
+                #[cfg(feature = "metrics")]
+                &metrics,
+            )?
+        };
 
         let highest_table_id = version
             .iter_tables()
@@ -918,46 +2198,125 @@ impl Tree {
             .max()
             .unwrap_or_default();
 
+        let journal = if config.journal && !config.is_secondary() {
+            let folder = config.path.join(crate::file::JOURNAL_FOLDER);
+            Some(Arc::new(Journal::recover(
+                config.fs.clone(),
+                config.encryptor.clone(),
+                config.durability,
+                folder,
+                version.flushed_journal_files(),
+            )?))
+        } else {
+            None
+        };
+
+        // NOTE: Continue the sequence number generator after the highest persisted write,
+        // so automatically assigned sequence numbers stay monotonic across restarts
+        let highest_seqno = version
+            .get_highest_seqno()
+            .max(journal.as_ref().and_then(|journal| journal.highest_seqno()));
+
+        if let Some(seqno) = highest_seqno {
+            config.seqno.fetch_max(seqno + 1);
+        }
+
         let inner = TreeInner {
             id: tree_id,
             table_id_counter: SequenceNumberCounter::new(highest_table_id + 1),
             blob_file_id_generator: SequenceNumberCounter::default(),
             version_history: Arc::new(RwLock::new(SuperVersions::new(version))),
             stop_signal: StopSignal::default(),
+            background: Arc::default(),
+            write_stall_condition: AtomicU8::default(),
             config,
+            journal,
             major_compaction_lock: RwLock::default(),
+            write_lock: RwLock::default(),
             compaction_state: Arc::new(Mutex::new(CompactionState::default())),
+            lock_file,
 
             #[cfg(feature = "metrics")]
             metrics,
         };
 
-        Ok(Self(Arc::new(inner)))
+        let tree = Self(Arc::new(inner));
+
+        if tree.config.is_secondary() {
+            tree.load_primary_version()?;
+        } else {
+            tree.replay_journal()?;
+        }
+
+        Ok(tree)
+    }
+
+    /// Applies all journaled writes that have not been flushed yet to the active memtable.
+    ///
+    /// Flushed journal files are deleted when the journal is recovered,
+    /// so all remaining files are replayed in full.
+    fn replay_journal(&self) -> crate::Result<()> {
+        let Some(journal) = &self.journal else {
+            return Ok(());
+        };
+
+        let active_memtable = self.get_version_for_snapshot(SeqNo::MAX).active_memtable;
+        let mut count = 0;
+
+        for id in journal.file_ids() {
+            let mut reader = journal.open_reader(id)?;
+
+            while let Some(entry) = reader.next_entry()? {
+                match entry {
+                    JournalEntry::Value(item) => {
+                        if let Some(extractor) = &self.config.key_extractor {
+                            active_memtable.register_derived_key(&**extractor, &item);
+                        }
+
+                        active_memtable.insert(item);
+                    }
+                    JournalEntry::RangeTombstone(rt) => {
+                        active_memtable.insert_range_tombstone(rt);
+                    }
+                }
+
+                count += 1;
+            }
+        }
+
+        log::debug!("Replayed {count} journaled writes");
+
+        Ok(())
     }
 
     /// Creates a new LSM-tree in a directory.
-    fn create_new(config: Config) -> crate::Result<Self> {
-        use crate::file::{fsync_directory, MANIFEST_FILE, TABLES_FOLDER};
-        use std::fs::create_dir_all;
+    fn create_new(config: Config, lock_file: Box<dyn FileHandle>) -> crate::Result<Self> {
+        use crate::file::{MANIFEST_FILE, TABLES_FOLDER};
+        use std::io::BufWriter;
+
+        let fs = config.fs.clone();
 
         let path = config.path.clone();
         log::trace!("Creating LSM-tree at {}", path.display());
 
-        create_dir_all(&path)?;
+        fs.create_dir_all(&path)?;
 
         let manifest_path = path.join(MANIFEST_FILE);
-        assert!(!manifest_path.try_exists()?);
+        assert!(!fs.exists(&manifest_path)?);
 
         let table_folder_path = path.join(TABLES_FOLDER);
-        create_dir_all(&table_folder_path)?;
+        fs.create_dir_all(&table_folder_path)?;
+        crate::file::create_shard_folders(&*fs, &table_folder_path, config.directory_shards)?;
 
         // Create manifest
         {
-            let mut writer = sfa::Writer::new_at_path(manifest_path)?;
+            let file = fs.create_new(&manifest_path)?;
+            let mut writer = crate::archive::Writer::from_writer(BufWriter::new(file));
 
             Manifest {
                 version: FormatVersion::V3,
                 level_count: config.level_count,
+                directory_shards: config.directory_shards,
                 tree_type: if config.kv_separation_opts.is_some() {
                     TreeType::Blob
                 } else {
@@ -970,26 +2329,29 @@ impl Tree {
         }
 
         // IMPORTANT: fsync folders on Unix
-        fsync_directory(&table_folder_path)?;
-        fsync_directory(&path)?;
+        fs.sync_directory(&table_folder_path)?;
+        fs.sync_directory(&path)?;
 
-        let inner = TreeInner::create_new(config)?;
+        let inner = TreeInner::create_new(config, Some(lock_file))?;
         Ok(Self(Arc::new(inner)))
     }
 
     /// Recovers the level manifest, loading all tables from disk.
+    #[expect(clippy::too_many_lines)]
     fn recover_levels<P: AsRef<Path>>(
+        fs: &Arc<dyn Filesystem>,
+        encryptor: Option<&Arc<dyn Encryptor>>,
         tree_path: P,
         tree_id: TreeId,
         cache: &Arc<Cache>,
         descriptor_table: &Arc<DescriptorTable>,
         #[cfg(feature = "metrics")] metrics: &Arc<Metrics>,
     ) -> crate::Result<Version> {
-        use crate::{file::fsync_directory, file::TABLES_FOLDER, TableId};
+        use crate::{file::TABLES_FOLDER, TableId};
 
         let tree_path = tree_path.as_ref();
 
-        let recovery = recover(tree_path)?;
+        let recovery = recover(&**fs, tree_path)?;
 
         let table_map = {
             let mut result: crate::HashMap<TableId, (u8 /* Level index */, Checksum)> =
@@ -1032,16 +2394,18 @@ impl Tree {
 
         let table_base_folder = tree_path.join(TABLES_FOLDER);
 
-        if !table_base_folder.try_exists()? {
-            std::fs::create_dir_all(&table_base_folder)?;
-            fsync_directory(&table_base_folder)?;
+        if !fs.exists(&table_base_folder)? {
+            fs.create_dir_all(&table_base_folder)?;
+            fs.sync_directory(&table_base_folder)?;
         }
 
         let mut orphaned_tables = vec![];
 
-        for (idx, dirent) in std::fs::read_dir(&table_base_folder)?.enumerate() {
-            let dirent = dirent?;
-            let file_name = dirent.file_name();
+        for (idx, table_file_path) in crate::file::read_sharded_dir(&**fs, &table_base_folder)?
+            .into_iter()
+            .enumerate()
+        {
+            let file_name = table_file_path.file_name().unwrap_or_default();
 
             // https://en.wikipedia.org/wiki/.DS_Store
             if file_name == ".DS_Store" {
@@ -1058,8 +2422,7 @@ impl Tree {
                 crate::Error::Unrecoverable
             })?;
 
-            let table_file_path = dirent.path();
-            assert!(!table_file_path.is_dir());
+            assert!(!fs.is_dir(&table_file_path)?);
 
             log::debug!("Recovering table from {}", table_file_path.display());
 
@@ -1069,10 +2432,13 @@ impl Tree {
             })?;
 
             if let Some(&(level_idx, checksum)) = table_map.get(&table_id) {
-                let table = Table::recover(
+                // NOTE: Only the metadata is read, so opening a tree with many tables is fast
+                let table = Table::recover_lazily(
                     table_file_path,
                     checksum,
                     tree_id,
+                    fs.clone(),
+                    encryptor.cloned(),
                     cache.clone(),
                     descriptor_table.clone(),
                     level_idx <= 1, // TODO: look at configuration
@@ -1104,6 +2470,8 @@ impl Tree {
         log::debug!("Successfully recovered {} tables", tables.len());
 
         let (blob_files, orphaned_blob_files) = crate::vlog::recover_blob_files(
+            fs,
+            encryptor,
             &tree_path.join(BLOBS_FOLDER),
             &recovery.blob_file_ids,
         )?;
@@ -1112,36 +2480,38 @@ impl Tree {
 
         // NOTE: Cleanup old versions
         // But only after we definitely recovered the latest version
-        Self::cleanup_orphaned_version(tree_path, version.id())?;
+        Self::cleanup_orphaned_version(&**fs, tree_path, version.id())?;
 
         for table_path in orphaned_tables {
             log::debug!("Deleting orphaned table {}", table_path.display());
-            std::fs::remove_file(&table_path)?;
+            fs.remove_file(&table_path)?;
         }
 
         for blob_file_path in orphaned_blob_files {
             log::debug!("Deleting orphaned blob file {}", blob_file_path.display());
-            std::fs::remove_file(&blob_file_path)?;
+            fs.remove_file(&blob_file_path)?;
         }
 
         Ok(version)
     }
 
-    fn cleanup_orphaned_version(path: &Path, latest_version_id: VersionId) -> crate::Result<()> {
+    fn cleanup_orphaned_version(
+        fs: &dyn Filesystem,
+        path: &Path,
+        latest_version_id: VersionId,
+    ) -> crate::Result<()> {
         let version_str = format!("v{latest_version_id}");
 
-        for file in std::fs::read_dir(path)? {
-            let dirent = file?;
-
-            if dirent.file_type()?.is_dir() {
+        for file_path in fs.read_dir(path)? {
+            if fs.is_dir(&file_path)? {
                 continue;
             }
 
-            let name = dirent.file_name();
+            let name = file_path.file_name().unwrap_or_default();
 
             if name.to_string_lossy().starts_with('v') && *name != *version_str {
                 log::trace!("Cleanup orphaned version {}", name.display());
-                std::fs::remove_file(dirent.path())?;
+                fs.remove_file(&file_path)?;
             }
         }
 
@@ -27,6 +27,13 @@ impl SealedMemtables {
         copy
     }
 
+    pub fn get(&self, id: MemtableId) -> Option<&Arc<Memtable>> {
+        self.0
+            .iter()
+            .find(|(memtable_id, _)| *memtable_id == id)
+            .map(|(_, memtable)| memtable)
+    }
+
     pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(MemtableId, Arc<Memtable>)> {
         self.0.iter()
     }
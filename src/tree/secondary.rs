@@ -0,0 +1,193 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::Tree;
+use crate::{
+    file::{sharded_path, BLOBS_FOLDER, JOURNAL_FOLDER, TABLES_FOLDER},
+    journal::{entry::Entry as JournalEntry, JournalFileId},
+    memtable::Memtable,
+    version::{
+        recovery::{get_current_version, recover, Recovery},
+        SuperVersion, Version,
+    },
+    AbstractTree, SeqNo, Table,
+};
+use std::sync::Arc;
+
+impl Tree {
+    /// Catches up with the primary tree, see [`crate::AbstractTree::try_catch_up`].
+    pub(crate) fn catch_up(&self) -> crate::Result<()> {
+        if !self.config.is_secondary() {
+            return Err(crate::Error::InvalidConfig(
+                "only secondary instances can catch up",
+            ));
+        }
+
+        let current = self.current_version();
+        let fs = &*self.config.fs;
+
+        let version = if get_current_version(fs, &self.config.path)? == current.id() {
+            current
+        } else {
+            self.recover_primary_version(&current)?
+        };
+
+        self.install_caught_up_version(version)
+    }
+
+    /// Loads the version of the primary tree when a secondary instance is opened.
+    pub(crate) fn load_primary_version(&self) -> crate::Result<()> {
+        let version = self.recover_primary_version(&self.current_version())?;
+        self.install_caught_up_version(version)
+    }
+
+    /// Recovers the current version of the primary tree.
+    ///
+    /// The primary tree may install a newer version (and delete the files of the
+    /// recovered one) while it is loaded, in which case the newer version is loaded instead.
+    fn recover_primary_version(&self, current: &Version) -> crate::Result<Version> {
+        let fs = &*self.config.fs;
+
+        loop {
+            let version_id = get_current_version(fs, &self.config.path)?;
+
+            match recover(fs, &self.config.path).and_then(|r| self.load_version(r, current)) {
+                Err(crate::Error::Io(e))
+                    if e.kind() == std::io::ErrorKind::NotFound
+                        && get_current_version(fs, &self.config.path)? != version_id =>
+                {
+                    log::debug!("Version {version_id} was replaced while loading it, retrying");
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Builds a recovered version, reusing the tables and blob files of the current version.
+    ///
+    /// Orphaned files are not deleted, because they may be written by the primary tree right now.
+    fn load_version(&self, recovery: Recovery, current: &Version) -> crate::Result<Version> {
+        let table_folder = self.config.path.join(TABLES_FOLDER);
+        let mut tables = vec![];
+
+        for (level_idx, level) in recovery.table_ids.iter().enumerate() {
+            for &(table_id, checksum) in level.iter().flatten() {
+                if let Some(table) = current.get_table(table_id) {
+                    tables.push(table.clone());
+                    continue;
+                }
+
+                // NOTE: Only the metadata is read, so catching up with many new tables is fast
+                let table = Table::recover_lazily(
+                    sharded_path(&table_folder, table_id, self.config.directory_shards),
+                    checksum,
+                    self.id,
+                    self.config.fs.clone(),
+                    self.config.encryptor.clone(),
+                    self.config.cache.clone(),
+                    self.config.descriptor_table.clone(),
+                    level_idx <= 1, // TODO: look at configuration
+                    level_idx <= 2, // TODO: look at configuration
+                    #[cfg(feature = "metrics")]
+                    self.metrics.clone(),
+                )?;
+
+                // NOTE: The primary tree deletes tables once they are compacted away,
+                // so keep the file open for as long as any version references the table
+                table.pin_file()?;
+
+                tables.push(table);
+            }
+        }
+
+        let (new_blob_file_ids, known_blob_file_ids): (Vec<_>, Vec<_>) = recovery
+            .blob_file_ids
+            .iter()
+            .partition(|(id, _)| !current.blob_files.contains_key(*id));
+
+        let mut blob_files = if new_blob_file_ids.is_empty() {
+            vec![]
+        } else {
+            crate::vlog::recover_blob_files(
+                &self.config.fs,
+                self.config.encryptor.as_ref(),
+                &self.config.path.join(BLOBS_FOLDER),
+                &new_blob_file_ids,
+            )?
+            .0
+        };
+
+        blob_files.extend(
+            known_blob_file_ids
+                .iter()
+                .filter_map(|(id, _)| current.blob_files.get(*id))
+                .cloned(),
+        );
+
+        log::debug!(
+            "Secondary instance caught up to version {} with {} tables",
+            recovery.curr_version_id,
+            tables.len(),
+        );
+
+        Version::from_recovery(recovery, &tables, &blob_files)
+    }
+
+    /// Installs a version as the only super version, together with the writes
+    /// of the primary tree that are not part of it yet.
+    fn install_caught_up_version(&self, version: Version) -> crate::Result<()> {
+        let memtable = self.read_primary_journal(version.flushed_journal_files())?;
+
+        if let Some(seqno) = version
+            .get_highest_seqno()
+            .max(memtable.get_highest_seqno())
+        {
+            self.config.seqno.fetch_max(seqno + 1);
+        }
+
+        let mut version_history = self.version_history.write().expect("lock is poisoned");
+
+        // NOTE: A secondary instance has no version history of its own,
+        // so the caught up version is visible to reads at any sequence number
+        version_history.append_version(SuperVersion {
+            active_memtable: Arc::new(memtable),
+            sealed_memtables: Arc::default(),
+            version,
+            seqno: 0,
+        });
+
+        // NOTE: Readers hold their own copy of a super version, and older versions own no files
+        // (the primary tree deletes them), so all older super versions can be freed
+        version_history.free(SeqNo::MAX);
+        drop(version_history);
+
+        Ok(())
+    }
+
+    /// Reads the writes of the primary tree that are not flushed yet from its journal.
+    fn read_primary_journal(&self, flushed_files: &[JournalFileId]) -> crate::Result<Memtable> {
+        let memtable = Memtable::default();
+
+        if self.config.journal {
+            crate::journal::read_unflushed(
+                &*self.config.fs,
+                self.config.encryptor.as_ref(),
+                &self.config.path.join(JOURNAL_FOLDER),
+                flushed_files,
+                |entry| match entry {
+                    JournalEntry::Value(item) => {
+                        memtable.insert(item);
+                    }
+                    JournalEntry::RangeTombstone(rt) => {
+                        memtable.insert_range_tombstone(rt);
+                    }
+                },
+            )?;
+        }
+
+        self.register_derived_keys(&memtable);
+
+        Ok(memtable)
+    }
+}
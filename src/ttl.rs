@@ -0,0 +1,100 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Expiring values (per-entry TTL)
+//!
+//! The value of an expiring entry is prefixed with its expiry timestamp:
+//!
+//! [expires at; 8 bytes, ms since unix epoch, LE] [value]
+
+use crate::{InternalValue, UserValue, ValueType};
+use std::time::Duration;
+
+const TIMESTAMP_LEN: usize = std::mem::size_of::<u64>();
+
+fn as_millis(timestamp: Duration) -> u64 {
+    u64::try_from(timestamp.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Prefixes the value with its expiry timestamp.
+pub fn encode_value(value: &[u8], expires_at: Duration) -> UserValue {
+    let mut buf = Vec::with_capacity(TIMESTAMP_LEN + value.len());
+    buf.extend_from_slice(&as_millis(expires_at).to_le_bytes());
+    buf.extend_from_slice(value);
+    buf.into()
+}
+
+/// Returns the expiry timestamp of an expiring value.
+fn expires_at(value: &[u8]) -> Option<Duration> {
+    let (timestamp, _) = value.split_first_chunk::<TIMESTAMP_LEN>()?;
+    Some(Duration::from_millis(u64::from_le_bytes(*timestamp)))
+}
+
+/// Splits an expiring value into its expiry timestamp and the actual value.
+pub fn decode_value(value: &UserValue) -> Option<(Duration, UserValue)> {
+    let expires_at = expires_at(value)?;
+    Some((expires_at, value.slice(TIMESTAMP_LEN..)))
+}
+
+/// Returns `true` if the item is an expiring value that is expired at `now`.
+pub fn is_expired(item: &InternalValue, now: Duration) -> bool {
+    // NOTE: Values without a valid timestamp are treated as expired
+    item.key.value_type.is_expiring_value()
+        && expires_at(&item.value).is_none_or(|expires_at| expires_at <= now)
+}
+
+/// Turns an expired value into a tombstone, so it shadows the older versions of its key.
+pub fn expire(mut item: InternalValue, now: Duration) -> InternalValue {
+    if is_expired(&item, now) {
+        item.key.value_type = ValueType::Tombstone;
+        item.value = UserValue::empty();
+    }
+    item
+}
+
+/// Resolves an expiring value for reading.
+///
+/// Expired values become tombstones, all other expiring values become normal values.
+pub fn resolve(item: InternalValue, now: Duration) -> InternalValue {
+    let mut item = expire(item, now);
+
+    if item.key.value_type.is_expiring_value() {
+        item.key.value_type = ValueType::Value;
+        item.value = item.value.slice(TIMESTAMP_LEN..);
+    }
+
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn ttl_resolve() {
+        let value = encode_value(b"abc", Duration::from_secs(10));
+        let item = InternalValue::from_components("a", value, 0, ValueType::ExpiringValue);
+
+        let resolved = resolve(item.clone(), Duration::from_secs(9));
+        assert_eq!(ValueType::Value, resolved.key.value_type);
+        assert_eq!(b"abc", &*resolved.value);
+
+        let resolved = resolve(item, Duration::from_secs(10));
+        assert!(resolved.is_tombstone());
+    }
+
+    #[test]
+    fn ttl_normal_value() {
+        let item = InternalValue::from_components("a", "abc", 0, ValueType::Value);
+        assert!(!is_expired(&item, Duration::MAX));
+        assert_eq!(item, resolve(item.clone(), Duration::MAX));
+    }
+
+    #[test]
+    fn ttl_invalid_timestamp() {
+        let item = InternalValue::from_components("a", "abc", 0, ValueType::ExpiringValue);
+        assert!(is_expired(&item, Duration::ZERO));
+    }
+}
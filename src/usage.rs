@@ -0,0 +1,104 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Disk space usage of a tree, see [`AbstractTree::disk_usage`](crate::AbstractTree::disk_usage)
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DiskUsage {
+    /// Size of the tables of each level, starting at L0
+    pub levels: Vec<u64>,
+
+    /// Size of all blob files
+    pub blob_bytes: u64,
+
+    /// Size of the journal files of the tree
+    ///
+    /// Trees of a [`Keyspace`](crate::keyspace::Keyspace) share the keyspace's journal,
+    /// which is not included.
+    pub journal_bytes: u64,
+}
+
+impl DiskUsage {
+    /// Returns the size of all tables.
+    #[must_use]
+    pub fn table_bytes(&self) -> u64 {
+        self.levels.iter().sum()
+    }
+
+    /// Returns the size of all tables, blob files and journal files.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.table_bytes() + self.blob_bytes + self.journal_bytes
+    }
+}
+
+/// Memory usage of a tree, see [`AbstractTree::memory_usage`](crate::AbstractTree::memory_usage)
+///
+/// Caches may be shared between trees (see [`Config::use_cache`](crate::Config::use_cache)),
+/// in which case their size is reported by every tree that uses them.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryUsage {
+    /// Approximate size of the active memtable
+    pub active_memtable_bytes: u64,
+
+    /// Approximate size of all sealed memtables that are not flushed yet
+    pub sealed_memtable_bytes: u64,
+
+    /// Size of the block cache
+    pub block_cache_bytes: u64,
+
+    /// Size of the blob cache, if it is not the block cache
+    pub blob_cache_bytes: u64,
+
+    /// Size of filters that are pinned in memory
+    pub pinned_filter_bytes: u64,
+
+    /// Size of block indexes that are pinned in memory
+    pub pinned_block_index_bytes: u64,
+}
+
+impl MemoryUsage {
+    /// Returns the size of all memtables.
+    #[must_use]
+    pub fn memtable_bytes(&self) -> u64 {
+        self.active_memtable_bytes + self.sealed_memtable_bytes
+    }
+
+    /// Returns the total memory usage.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.memtable_bytes()
+            + self.block_cache_bytes
+            + self.blob_cache_bytes
+            + self.pinned_filter_bytes
+            + self.pinned_block_index_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn usage_totals() {
+        let disk = DiskUsage {
+            levels: vec![10, 0, 100],
+            blob_bytes: 1_000,
+            journal_bytes: 5,
+        };
+        assert_eq!(110, disk.table_bytes());
+        assert_eq!(1_115, disk.total());
+
+        let memory = MemoryUsage {
+            active_memtable_bytes: 1,
+            sealed_memtable_bytes: 2,
+            block_cache_bytes: 30,
+            blob_cache_bytes: 0,
+            pinned_filter_bytes: 400,
+            pinned_block_index_bytes: 5_000,
+        };
+        assert_eq!(3, memory.memtable_bytes());
+        assert_eq!(5_433, memory.total());
+    }
+}
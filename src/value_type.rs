@@ -19,6 +19,17 @@ pub enum ValueType {
     ///
     /// Points to a blob in a blob file.
     Indirection,
+
+    /// Merge operand
+    ///
+    /// Combined with older versions of the key using the tree's merge operator,
+    /// see [`crate::MergeOperator`].
+    MergeOperand,
+
+    /// Value with an expiry timestamp
+    ///
+    /// Treated as deleted once it is expired, see [`crate::AbstractTree::insert_with_ttl`].
+    ExpiringValue,
 }
 
 impl ValueType {
@@ -31,6 +42,14 @@ impl ValueType {
     pub(crate) fn is_indirection(self) -> bool {
         self == Self::Indirection
     }
+
+    pub(crate) fn is_merge_operand(self) -> bool {
+        self == Self::MergeOperand
+    }
+
+    pub(crate) fn is_expiring_value(self) -> bool {
+        self == Self::ExpiringValue
+    }
 }
 
 impl TryFrom<u8> for ValueType {
@@ -42,6 +61,8 @@ impl TryFrom<u8> for ValueType {
             0x0000_0001 => Ok(Self::Tombstone),
             0x0000_0011 => Ok(Self::WeakTombstone),
             0b0000_0100 => Ok(Self::Indirection),
+            0b0000_1000 => Ok(Self::MergeOperand),
+            0b0001_0000 => Ok(Self::ExpiringValue),
             _ => Err(()),
         }
     }
@@ -54,6 +75,8 @@ impl From<ValueType> for u8 {
             ValueType::Tombstone => 0x0000_0001,
             ValueType::WeakTombstone => 0x0000_0011,
             ValueType::Indirection => 0b0000_0100,
+            ValueType::MergeOperand => 0b0000_1000,
+            ValueType::ExpiringValue => 0b0001_0000,
         }
     }
 }
@@ -19,8 +19,10 @@ use crate::coding::Encode;
 use crate::compaction::state::hidden_set::HiddenSet;
 use crate::version::recovery::Recovery;
 use crate::{
+    journal::JournalFileId,
+    range_tombstone::{RangeTombstone, RangeTombstoneIndex},
     vlog::{BlobFile, BlobFileId},
-    HashSet, KeyRange, Table, TableId,
+    HashSet, KeyRange, SeqNo, Table, TableId,
 };
 use optimize::optimize_runs;
 use run::Ranged;
@@ -166,6 +168,12 @@ pub struct VersionInner {
 
     /// Blob file fragmentation
     gc_stats: Arc<FragmentationMap>,
+
+    /// Range tombstones of flushed memtables
+    range_tombstones: Arc<RangeTombstoneIndex>,
+
+    /// Journal files whose data is persisted in tables, so they are not replayed on recovery
+    flushed_journal_files: Arc<Vec<JournalFileId>>,
 }
 
 /// A version is an immutable, point-in-time view of a tree's structure
@@ -195,6 +203,30 @@ impl Version {
         &self.gc_stats
     }
 
+    /// Returns the range tombstones of flushed memtables.
+    pub fn range_tombstones(&self) -> &[RangeTombstone] {
+        self.range_tombstones.tombstones()
+    }
+
+    /// Returns `true` if a range tombstone that is visible at `read_seqno` deletes the given version of a key.
+    pub fn is_range_deleted(&self, key: &[u8], seqno: SeqNo, read_seqno: SeqNo) -> bool {
+        self.range_tombstones
+            .is_range_deleted(key, seqno, read_seqno)
+    }
+
+    /// Returns the journal files whose data is persisted in tables.
+    pub fn flushed_journal_files(&self) -> &[JournalFileId] {
+        &self.flushed_journal_files
+    }
+
+    /// Returns the highest sequence number of all tables and range tombstones.
+    pub fn get_highest_seqno(&self) -> Option<SeqNo> {
+        self.iter_tables()
+            .map(Table::get_highest_seqno)
+            .chain(self.range_tombstones().iter().map(|rt| rt.seqno))
+            .max()
+    }
+
     pub fn l0(&self) -> &Level {
         #[expect(clippy::expect_used)]
         self.levels.first().expect("L0 should exist")
@@ -210,9 +242,9 @@ impl Version {
         })
     }
 
-    /// Creates a new empty version.
-    pub fn new(id: VersionId) -> Self {
-        let levels = (0..DEFAULT_LEVEL_COUNT).map(|_| Level::empty()).collect();
+    /// Creates a new empty version with `level_count` levels.
+    pub fn new(id: VersionId, level_count: u8) -> Self {
+        let levels = (0..level_count).map(|_| Level::empty()).collect();
 
         Self {
             inner: Arc::new(VersionInner {
@@ -220,6 +252,8 @@ impl Version {
                 levels,
                 blob_files: Arc::default(),
                 gc_stats: Arc::default(),
+                range_tombstones: Arc::default(),
+                flushed_journal_files: Arc::default(),
             }),
         }
     }
@@ -260,6 +294,8 @@ impl Version {
             version_levels,
             BlobFileList::new(blob_files.iter().cloned().map(|bf| (bf.id(), bf)).collect()),
             recovery.gc_stats,
+            recovery.range_tombstones,
+            recovery.flushed_journal_files,
         ))
     }
 
@@ -269,6 +305,8 @@ impl Version {
         levels: Vec<Level>,
         blob_files: BlobFileList,
         gc_stats: FragmentationMap,
+        range_tombstones: Vec<RangeTombstone>,
+        flushed_journal_files: Vec<JournalFileId>,
     ) -> Self {
         Self {
             inner: Arc::new(VersionInner {
@@ -276,6 +314,8 @@ impl Version {
                 levels,
                 blob_files: Arc::new(blob_files),
                 gc_stats: Arc::new(gc_stats),
+                range_tombstones: Arc::new(range_tombstones.into()),
+                flushed_journal_files: Arc::new(flushed_journal_files),
             }),
         }
     }
@@ -379,6 +419,8 @@ impl Version {
                 levels,
                 blob_files: value_log,
                 gc_stats,
+                range_tombstones: self.range_tombstones.clone(),
+                flushed_journal_files: self.flushed_journal_files.clone(),
             }),
         }
     }
@@ -434,6 +476,7 @@ impl Version {
                         .and_modify(|counter| {
                             counter.bytes += blob_file.bytes;
                             counter.len += blob_file.len;
+                            counter.on_disk_bytes += blob_file.on_disk_bytes;
                         })
                         .or_insert_with(|| {
                             FragmentationEntry::new(
@@ -462,6 +505,8 @@ impl Version {
                 levels,
                 blob_files: value_log,
                 gc_stats,
+                range_tombstones: self.range_tombstones.clone(),
+                flushed_journal_files: self.flushed_journal_files.clone(),
             }),
         })
     }
@@ -544,6 +589,8 @@ impl Version {
                 levels,
                 blob_files: value_log,
                 gc_stats,
+                range_tombstones: self.range_tombstones.clone(),
+                flushed_journal_files: self.flushed_journal_files.clone(),
             }),
         }
     }
@@ -589,13 +636,101 @@ impl Version {
                 levels,
                 blob_files: self.blob_files.clone(),
                 gc_stats: self.gc_stats.clone(),
+                range_tombstones: self.range_tombstones.clone(),
+                flushed_journal_files: self.flushed_journal_files.clone(),
             }),
         }
     }
 }
 
 impl Version {
-    pub(crate) fn encode_into(&self, writer: &mut sfa::Writer) -> Result<(), crate::Error> {
+    /// Returns a new version with the given range tombstones.
+    pub fn with_range_tombstones(&self, range_tombstones: Vec<RangeTombstone>) -> Self {
+        Self {
+            inner: Arc::new(VersionInner {
+                id: self.id + 1,
+                levels: self.levels.clone(),
+                blob_files: self.blob_files.clone(),
+                gc_stats: self.gc_stats.clone(),
+                range_tombstones: Arc::new(range_tombstones.into()),
+                flushed_journal_files: self.flushed_journal_files.clone(),
+            }),
+        }
+    }
+
+    /// Returns a new version with the given flushed journal files.
+    pub fn with_flushed_journal_files(&self, flushed_journal_files: Vec<JournalFileId>) -> Self {
+        Self {
+            inner: Arc::new(VersionInner {
+                id: self.id + 1,
+                levels: self.levels.clone(),
+                blob_files: self.blob_files.clone(),
+                gc_stats: self.gc_stats.clone(),
+                range_tombstones: self.range_tombstones.clone(),
+                flushed_journal_files: Arc::new(flushed_journal_files),
+            }),
+        }
+    }
+
+    /// Returns a new version without the range tombstones that cannot delete anything anymore.
+    ///
+    /// A range tombstone can be dropped once it is visible to all snapshots (below the MVCC watermark),
+    /// and no table contains versions inside its range that are older than the tombstone.
+    ///
+    /// Compactions drop all versions that are deleted by such tombstones,
+    /// so the tables are usually checked using a single seek.
+    pub fn with_obsolete_range_tombstones_dropped(
+        &self,
+        gc_watermark: SeqNo,
+    ) -> crate::Result<Self> {
+        let mut range_tombstones = Vec::with_capacity(self.range_tombstones().len());
+
+        for rt in self.range_tombstones() {
+            if rt.seqno >= gc_watermark || self.has_versions_beneath(rt)? {
+                range_tombstones.push(rt.clone());
+            }
+        }
+
+        if range_tombstones.len() == self.range_tombstones().len() {
+            return Ok(self.clone());
+        }
+
+        log::debug!(
+            "Dropping {} obsolete range tombstones",
+            self.range_tombstones().len() - range_tombstones.len(),
+        );
+
+        Ok(self.with_range_tombstones(range_tombstones))
+    }
+
+    /// Returns `true` if some table contains versions that are deleted by the range tombstone.
+    fn has_versions_beneath(&self, rt: &RangeTombstone) -> crate::Result<bool> {
+        for table in self.iter_tables().filter(|table| {
+            table.metadata.seqnos.0 < rt.seqno && table.check_key_range_overlap(&rt.bounds())
+        }) {
+            let range = (
+                std::ops::Bound::Included(rt.start.clone()),
+                rt.end
+                    .clone()
+                    .map_or(std::ops::Bound::Unbounded, std::ops::Bound::Excluded),
+            );
+
+            for item in table.range(range) {
+                if item?.key.seqno < rt.seqno {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl Version {
+    pub(crate) fn encode_into(
+        &self,
+        writer: &mut crate::archive::Writer,
+    ) -> Result<(), crate::Error> {
         use byteorder::{LittleEndian, WriteBytesExt};
 
         writer.start("tables")?;
@@ -651,6 +786,30 @@ impl Version {
 
         self.gc_stats.encode_into(writer)?;
 
+        writer.start("range_tombstones")?;
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "there are always less than 4 billion range tombstones"
+        )]
+        writer.write_u32::<LittleEndian>(self.range_tombstones().len() as u32)?;
+
+        for rt in self.range_tombstones() {
+            rt.encode_into(writer)?;
+        }
+
+        writer.start("journal")?;
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "there are always less than 4 billion journal files"
+        )]
+        writer.write_u32::<LittleEndian>(self.flushed_journal_files.len() as u32)?;
+
+        for &id in self.flushed_journal_files.iter() {
+            writer.write_u64::<LittleEndian>(id)?;
+        }
+
         Ok(())
     }
 }
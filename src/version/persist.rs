@@ -1,10 +1,13 @@
-use crate::{
-    file::{fsync_directory, rewrite_atomic},
-    version::Version,
-};
+use crate::{fs::rewrite_atomic, version::Version, Filesystem};
 use std::{io::BufWriter, path::Path};
 
-pub fn persist_version(folder: &Path, version: &Version) -> crate::Result<()> {
+/// Writes a version file, and atomically makes it the current version.
+///
+/// A version file records all tables (per level and run), blob files and range tombstones,
+/// so a flush or compaction becomes visible as a whole once `current` is rewritten.
+/// If the process crashes before that, the previous version is recovered,
+/// and the new version file and the files it references are deleted as orphans.
+pub fn persist_version(fs: &dyn Filesystem, folder: &Path, version: &Version) -> crate::Result<()> {
     log::trace!(
         "Persisting version {} in {}",
         version.id(),
@@ -12,21 +15,18 @@ pub fn persist_version(folder: &Path, version: &Version) -> crate::Result<()> {
     );
 
     let path = folder.join(format!("v{}", version.id()));
-    let file = std::fs::File::create_new(path)?;
+    let file = fs.create_new(&path)?;
     let writer = BufWriter::new(file);
-    let mut writer = sfa::Writer::from_writer(writer);
+    let mut writer = crate::archive::Writer::from_writer(writer);
 
     version.encode_into(&mut writer)?;
 
-    writer.finish().map_err(|e| match e {
-        sfa::Error::Io(e) => crate::Error::from(e),
-        _ => unreachable!(),
-    })?;
+    writer.finish()?;
 
     // IMPORTANT: fsync folder on Unix
-    fsync_directory(folder)?;
+    fs.sync_directory(folder)?;
 
-    rewrite_atomic(&folder.join("current"), &version.id().to_le_bytes())?;
+    rewrite_atomic(fs, &folder.join("current"), &version.id().to_le_bytes())?;
 
     Ok(())
 }
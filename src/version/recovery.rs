@@ -2,14 +2,20 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{coding::Decode, version::VersionId, vlog::BlobFileId, Checksum, TableId};
+use crate::{
+    coding::Decode,
+    fs::{read_archive, section_reader},
+    journal::JournalFileId,
+    range_tombstone::RangeTombstone,
+    version::VersionId,
+    vlog::BlobFileId,
+    Checksum, Filesystem, TableId,
+};
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::path::Path;
 
-pub fn get_current_version(folder: &std::path::Path) -> crate::Result<VersionId> {
-    use byteorder::{LittleEndian, ReadBytesExt};
-
-    std::fs::File::open(folder.join("current"))
+pub fn get_current_version(fs: &dyn Filesystem, folder: &Path) -> crate::Result<VersionId> {
+    fs.open(&folder.join("current"))
         .and_then(|mut f| f.read_u64::<LittleEndian>())
         .map_err(Into::into)
 }
@@ -19,10 +25,12 @@ pub struct Recovery {
     pub table_ids: Vec<Vec<Vec<(TableId, Checksum)>>>,
     pub blob_file_ids: Vec<(BlobFileId, Checksum)>,
     pub gc_stats: crate::blob_tree::FragmentationMap,
+    pub range_tombstones: Vec<RangeTombstone>,
+    pub flushed_journal_files: Vec<JournalFileId>,
 }
 
-pub fn recover(folder: &Path) -> crate::Result<Recovery> {
-    let curr_version_id = get_current_version(folder)?;
+pub fn recover(fs: &dyn Filesystem, folder: &Path) -> crate::Result<Recovery> {
+    let curr_version_id = get_current_version(fs, folder)?;
     let version_file_path = folder.join(format!("v{curr_version_id}"));
 
     log::info!(
@@ -30,20 +38,20 @@ pub fn recover(folder: &Path) -> crate::Result<Recovery> {
         version_file_path.display(),
     );
 
-    let reader = sfa::Reader::new(&version_file_path)?;
+    let reader = read_archive(fs, &version_file_path)?;
     let toc = reader.toc();
 
     // // TODO: vvv move into Version::decode vvv
     let mut levels = vec![];
 
     {
-        let mut reader = toc
+        let section = toc
             .section(b"tables")
             .ok_or(crate::Error::Unrecoverable)
             .inspect_err(|_| {
                     log::error!("tables section not found in version #{curr_version_id} - maybe the file is corrupted?");
-            })?
-            .buf_reader(&version_file_path)?;
+            })?;
+        let mut reader = section_reader(fs, &version_file_path, section)?;
 
         let level_count = reader.read_u8()?;
 
@@ -77,13 +85,13 @@ pub fn recover(folder: &Path) -> crate::Result<Recovery> {
     }
 
     let blob_file_ids = {
-        let mut reader = toc
+        let section = toc
             .section(b"blob_files")
             .ok_or(crate::Error::Unrecoverable)
             .inspect_err(|_| {
                     log::error!("blob_files section not found in version #{curr_version_id} - maybe the file is corrupted?");
-            })?
-            .buf_reader(&version_file_path)?;
+            })?;
+        let mut reader = section_reader(fs, &version_file_path, section)?;
 
         let blob_file_count = reader.read_u32::<LittleEndian>()?;
         let mut blob_file_ids = Vec::with_capacity(blob_file_count as usize);
@@ -107,21 +115,51 @@ pub fn recover(folder: &Path) -> crate::Result<Recovery> {
     };
 
     let gc_stats = {
-        let mut reader = toc
+        let section = toc
             .section(b"blob_gc_stats")
             .ok_or(crate::Error::Unrecoverable)
             .inspect_err(|_| {
                     log::error!("blob_gc_stats section not found in version #{curr_version_id} - maybe the file is corrupted?");
-            })?
-            .buf_reader(&version_file_path)?;
+            })?;
+        let mut reader = section_reader(fs, &version_file_path, section)?;
 
         crate::blob_tree::FragmentationMap::decode_from(&mut reader)?
     };
 
+    // NOTE: Versions written before range tombstones existed do not have the section
+    let range_tombstones = match toc.section(b"range_tombstones") {
+        Some(section) => {
+            let mut reader = section_reader(fs, &version_file_path, section)?;
+
+            let count = reader.read_u32::<LittleEndian>()?;
+
+            (0..count)
+                .map(|_| RangeTombstone::decode_from(&mut reader))
+                .collect::<crate::Result<Vec<_>>>()?
+        }
+        None => vec![],
+    };
+
+    // NOTE: Versions written before flushed journal files were tracked do not have the section
+    let flushed_journal_files = match toc.section(b"journal") {
+        Some(section) => {
+            let mut reader = section_reader(fs, &version_file_path, section)?;
+
+            let count = reader.read_u32::<LittleEndian>()?;
+
+            (0..count)
+                .map(|_| reader.read_u64::<LittleEndian>())
+                .collect::<std::io::Result<Vec<_>>>()?
+        }
+        None => vec![],
+    };
+
     Ok(Recovery {
         curr_version_id,
         table_ids: levels,
         blob_file_ids,
         gc_stats,
+        range_tombstones,
+        flushed_journal_files,
     })
 }
@@ -4,9 +4,10 @@
 
 use crate::{
     memtable::Memtable,
+    range_tombstone::RangeTombstone,
     tree::{inner::MemtableId, sealed::SealedMemtables},
     version::{persist_version, Version},
-    SeqNo, SequenceNumberCounter,
+    Filesystem, SeqNo, SequenceNumberCounter,
 };
 use std::{collections::VecDeque, path::Path, sync::Arc};
 
@@ -25,6 +26,47 @@ pub struct SuperVersion {
     pub(crate) seqno: SeqNo,
 }
 
+impl SuperVersion {
+    /// Returns all range tombstones that are visible at `seqno`.
+    pub(crate) fn range_tombstones(&self, seqno: SeqNo) -> Vec<RangeTombstone> {
+        let mut tombstones = self.active_memtable.range_tombstones();
+
+        for (_, memtable) in self.sealed_memtables.iter() {
+            tombstones.extend(memtable.range_tombstones());
+        }
+
+        tombstones.extend(self.version.range_tombstones().iter().cloned());
+        tombstones.retain(|rt| rt.seqno < seqno);
+        tombstones
+    }
+
+    /// Returns `true` if a range tombstone that is visible at `read_seqno` deletes the given version of a key.
+    pub(crate) fn is_range_deleted(&self, key: &[u8], seqno: SeqNo, read_seqno: SeqNo) -> bool {
+        self.active_memtable
+            .is_range_deleted(key, seqno, read_seqno)
+            || self
+                .sealed_memtables
+                .iter()
+                .any(|(_, memtable)| memtable.is_range_deleted(key, seqno, read_seqno))
+            || self.version.is_range_deleted(key, seqno, read_seqno)
+    }
+
+    /// Releases a flushed sealed memtable, moving its range tombstones into the version.
+    pub(crate) fn release_sealed_memtable(&mut self, id: MemtableId) {
+        let Some(memtable) = self.sealed_memtables.get(id) else {
+            return;
+        };
+
+        if memtable.has_range_tombstones() {
+            let mut tombstones = self.version.range_tombstones().to_vec();
+            tombstones.extend(memtable.range_tombstones());
+            self.version = self.version.with_range_tombstones(tombstones);
+        }
+
+        self.sealed_memtables = Arc::new(self.sealed_memtables.remove(id));
+    }
+}
+
 pub struct SuperVersions(VecDeque<SuperVersion>);
 
 impl SuperVersions {
@@ -44,9 +86,39 @@ impl SuperVersions {
         self.0.len().saturating_sub(1)
     }
 
-    pub(crate) fn maintenance(&mut self, folder: &Path, gc_watermark: SeqNo) -> crate::Result<()> {
+    pub(crate) fn maintenance(
+        &mut self,
+        fs: &dyn Filesystem,
+        folder: &Path,
+        gc_watermark: SeqNo,
+    ) -> crate::Result<()> {
         log::trace!("Running manifest GC with watermark={gc_watermark}");
 
+        self.gc(gc_watermark, |version| {
+            let path = folder.join(format!("v{}", version.id()));
+            if fs.exists(&path)? {
+                fs.remove_file(&path)?;
+            }
+            Ok(())
+        })?;
+
+        log::trace!("Manifest GC done, version length now {}", self.0.len());
+
+        Ok(())
+    }
+
+    /// Frees old versions that are below the watermark, without touching any files.
+    ///
+    /// Used by ephemeral trees, which do not persist versions.
+    pub(crate) fn free(&mut self, gc_watermark: SeqNo) {
+        let _ = self.gc(gc_watermark, |_| Ok(()));
+    }
+
+    fn gc<F: FnMut(&Version) -> crate::Result<()>>(
+        &mut self,
+        gc_watermark: SeqNo,
+        mut on_free: F,
+    ) -> crate::Result<()> {
         loop {
             if self.free_list_len() == 0 {
                 break;
@@ -57,18 +129,13 @@ impl SuperVersions {
             };
 
             if head.seqno < gc_watermark {
-                let path = folder.join(format!("v{}", head.version.id()));
-                if path.try_exists()? {
-                    std::fs::remove_file(path)?;
-                }
+                on_free(&head.version)?;
                 self.0.pop_front();
             } else {
                 break;
             }
         }
 
-        log::trace!("Manifest GC done, version length now {}", self.0.len());
-
         Ok(())
     }
 
@@ -80,6 +147,7 @@ impl SuperVersions {
     /// The function takes care of persisting the version changes on disk.
     pub(crate) fn upgrade_version<F: FnOnce(&SuperVersion) -> crate::Result<SuperVersion>>(
         &mut self,
+        fs: &dyn Filesystem,
         tree_path: &Path,
         f: F,
         seqno: &SequenceNumberCounter,
@@ -94,7 +162,7 @@ impl SuperVersions {
         next_version.seqno = seqno.next();
         log::trace!("Next version seqno={}", next_version.seqno);
 
-        persist_version(tree_path, &next_version.version)?;
+        persist_version(fs, tree_path, &next_version.version)?;
         self.append_version(next_version);
 
         Ok(())
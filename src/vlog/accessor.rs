@@ -4,10 +4,20 @@
 
 use crate::{
     version::BlobFileList,
-    vlog::{blob_file::reader::Reader, ValueHandle},
-    Cache, DescriptorTable, GlobalTableId, TreeId, UserValue,
+    vlog::{
+        blob_file::{reader::Reader, stream::BlobReader, writer::BLOB_HEADER_LEN},
+        ValueHandle,
+    },
+    BlobFile, Cache, CompressionType, DescriptorTable, FileHandle, Filesystem, GlobalTableId,
+    TreeId, UserValue,
 };
-use std::{fs::File, path::Path, sync::Arc};
+use std::sync::Arc;
+
+/// Blobs that are at most this many bytes apart are read using a single read
+const COALESCE_MAX_GAP: u64 = 16 * 1_024;
+
+/// Coalesced reads are not extended beyond this size
+const COALESCE_MAX_LEN: u64 = 4 * 1_024 * 1_024;
 
 pub struct Accessor<'a>(&'a BlobFileList);
 
@@ -19,9 +29,9 @@ impl<'a> Accessor<'a> {
     pub fn get(
         &self,
         tree_id: TreeId,
-        base_path: &Path,
         key: &[u8],
         vhandle: &ValueHandle,
+        fs: &dyn Filesystem,
         cache: &Cache,
         descriptor_table: &DescriptorTable,
     ) -> crate::Result<Option<UserValue>> {
@@ -41,12 +51,10 @@ impl<'a> Accessor<'a> {
         let file = if let Some(fd) = cached_fd {
             fd
         } else {
-            Arc::new(File::open(
-                base_path.join(vhandle.blob_file_id.to_string()),
-            )?)
+            Arc::from(fs.open(blob_file.path())?)
         };
 
-        let value = Reader::new(blob_file, &file).get(key, vhandle)?;
+        let value = Reader::new(blob_file, &*file).get(key, vhandle)?;
         cache.insert_blob(tree_id, vhandle, value.clone());
 
         if fd_cache_miss {
@@ -55,4 +63,204 @@ impl<'a> Accessor<'a> {
 
         Ok(Some(value))
     }
+
+    /// Returns a reader that streams the blob.
+    pub fn get_reader(
+        &self,
+        tree_id: TreeId,
+        key: &[u8],
+        vhandle: &ValueHandle,
+        fs: &dyn Filesystem,
+        cache: &Cache,
+        descriptor_table: &DescriptorTable,
+    ) -> crate::Result<Option<BlobReader>> {
+        if let Some(value) = cache.get_blob(tree_id, vhandle) {
+            return Ok(Some(BlobReader::from_value(value)));
+        }
+
+        let Some(blob_file) = self.0.get(vhandle.blob_file_id) else {
+            return Ok(None);
+        };
+
+        let file = Self::open_file(tree_id, blob_file, fs, descriptor_table)?;
+
+        BlobReader::open(blob_file, file, key, vhandle).map(Some)
+    }
+
+    /// Reads up to `len` bytes of the blob, starting at `offset`.
+    ///
+    /// If the blob is stored uncompressed and unencrypted, only the requested bytes are read,
+    /// so the checksum of the blob is not verified.
+    #[expect(clippy::too_many_arguments)]
+    pub fn get_range(
+        &self,
+        tree_id: TreeId,
+        key: &[u8],
+        vhandle: &ValueHandle,
+        offset: u64,
+        len: usize,
+        fs: &dyn Filesystem,
+        cache: &Cache,
+        descriptor_table: &DescriptorTable,
+    ) -> crate::Result<Option<UserValue>> {
+        let Some(blob_file) = self.0.get(vhandle.blob_file_id) else {
+            return Ok(None);
+        };
+
+        if blob_file.0.encryptor.is_some()
+            || blob_file.0.meta.compression != CompressionType::None
+            || cache.get_blob(tree_id, vhandle).is_some()
+        {
+            let Some(value) = self.get(tree_id, key, vhandle, fs, cache, descriptor_table)? else {
+                return Ok(None);
+            };
+
+            #[expect(clippy::cast_possible_truncation, reason = "values are u32 max length")]
+            let lo = offset.min(value.len() as u64) as usize;
+            let hi = lo.saturating_add(len).min(value.len());
+
+            return Ok(Some(value.slice(lo..hi)));
+        }
+
+        let size = u64::from(vhandle.on_disk_size);
+        let offset = offset.min(size);
+
+        #[expect(clippy::cast_possible_truncation, reason = "values are u32 max length")]
+        let len = (len as u64).min(size - offset) as usize;
+
+        if len == 0 {
+            return Ok(Some(UserValue::empty()));
+        }
+
+        let file = Self::open_file(tree_id, blob_file, fs, descriptor_table)?;
+
+        let data_offset = vhandle.offset + (BLOB_HEADER_LEN + key.len()) as u64;
+        let value = crate::file::read_exact(&*file, data_offset + offset, len)?;
+
+        Ok(Some(value))
+    }
+
+    fn open_file(
+        tree_id: TreeId,
+        blob_file: &BlobFile,
+        fs: &dyn Filesystem,
+        descriptor_table: &DescriptorTable,
+    ) -> crate::Result<Arc<dyn FileHandle>> {
+        let bf_id = GlobalTableId::from((tree_id, blob_file.id()));
+
+        if let Some(fd) = descriptor_table.access_for_blob_file(&bf_id) {
+            return Ok(fd);
+        }
+
+        let file: Arc<dyn FileHandle> = Arc::from(fs.open(blob_file.path())?);
+        descriptor_table.insert_for_blob_file(bf_id, file.clone());
+
+        Ok(file)
+    }
+
+    /// Resolves multiple value handles.
+    ///
+    /// Cache misses are read sorted by blob file and offset, and blobs that are
+    /// close to each other are read using a single read, turning random IO into
+    /// (mostly) sequential IO.
+    ///
+    /// Returns the values in the order of the given handles.
+    pub fn multi_get(
+        &self,
+        tree_id: TreeId,
+        handles: &[(&[u8], &ValueHandle)],
+        fs: &dyn Filesystem,
+        cache: &Cache,
+        descriptor_table: &DescriptorTable,
+    ) -> crate::Result<Vec<Option<UserValue>>> {
+        let mut results = Vec::with_capacity(handles.len());
+        let mut misses = vec![];
+
+        for (idx, &(key, vhandle)) in handles.iter().enumerate() {
+            let value = cache.get_blob(tree_id, vhandle);
+
+            if value.is_none() {
+                misses.push((idx, key, vhandle));
+            }
+
+            results.push(value);
+        }
+
+        misses.sort_by_key(|(_, _, vhandle)| (vhandle.blob_file_id, vhandle.offset));
+
+        for group in misses.chunk_by(|(_, _, a), (_, _, b)| a.blob_file_id == b.blob_file_id) {
+            let Some(blob_file) = group
+                .first()
+                .and_then(|(_, _, vhandle)| self.0.get(vhandle.blob_file_id))
+            else {
+                continue;
+            };
+
+            let bf_id = GlobalTableId::from((tree_id, blob_file.id()));
+
+            let cached_fd = descriptor_table.access_for_blob_file(&bf_id);
+            let fd_cache_miss = cached_fd.is_none();
+
+            let file = if let Some(fd) = cached_fd {
+                fd
+            } else {
+                Arc::from(fs.open(blob_file.path())?)
+            };
+
+            let reader = Reader::new(blob_file, &*file);
+
+            let mut run = vec![];
+            let mut run_start = 0;
+            let mut run_end = 0;
+
+            for &(idx, key, vhandle) in group {
+                let is_close = vhandle.offset <= run_end + COALESCE_MAX_GAP
+                    && vhandle.offset - run_start <= COALESCE_MAX_LEN;
+
+                if !run.is_empty() && !is_close {
+                    Self::read_run(&reader, tree_id, cache, &run, &mut results)?;
+                    run.clear();
+                }
+
+                if run.is_empty() {
+                    run_start = vhandle.offset;
+                    run_end = vhandle.offset;
+                }
+
+                run_end = run_end.max(vhandle.offset + u64::from(vhandle.on_disk_size));
+                run.push((idx, key, vhandle));
+            }
+
+            Self::read_run(&reader, tree_id, cache, &run, &mut results)?;
+
+            if fd_cache_miss {
+                descriptor_table.insert_for_blob_file(bf_id, file);
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn read_run(
+        reader: &Reader<'_>,
+        tree_id: TreeId,
+        cache: &Cache,
+        run: &[(usize, &[u8], &ValueHandle)],
+        results: &mut [Option<UserValue>],
+    ) -> crate::Result<()> {
+        let items = run
+            .iter()
+            .map(|&(_, key, vhandle)| (key, vhandle))
+            .collect::<Vec<_>>();
+
+        for (&(idx, _, vhandle), value) in run.iter().zip(reader.get_many(&items)?) {
+            cache.insert_blob(tree_id, vhandle, value.clone());
+
+            if let Some(slot) = results.get_mut(idx) {
+                *slot = Some(value);
+            }
+        }
+
+        Ok(())
+    }
 }
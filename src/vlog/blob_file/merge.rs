@@ -98,7 +98,7 @@ impl Iterator for MergeScanner {
 mod tests {
     use super::super::scanner::Scanner;
     use super::*;
-    use crate::{vlog::blob_file::writer::Writer as BlobFileWriter, Slice};
+    use crate::{vlog::blob_file::writer::Writer as BlobFileWriter, Slice, StdFilesystem};
     use tempfile::tempdir;
     use test_log::test;
 
@@ -109,7 +109,7 @@ mod tests {
         let blob_file_path = dir.path().join("0");
         {
             {
-                let mut writer = BlobFileWriter::new(&blob_file_path, 0)?;
+                let mut writer = BlobFileWriter::new(&StdFilesystem, &blob_file_path, 0)?;
 
                 writer.write(b"a", 1, &b"1".repeat(100))?;
                 writer.write(b"a", 0, &b"0".repeat(100))?;
@@ -119,7 +119,8 @@ mod tests {
         }
 
         {
-            let mut merger = MergeScanner::new(vec![Scanner::new(&blob_file_path, 0)?]);
+            let mut merger =
+                MergeScanner::new(vec![Scanner::new(&StdFilesystem, &blob_file_path, 0)?]);
 
             assert_eq!(
                 (Slice::from(b"a"), Slice::from(b"1".repeat(100))),
@@ -153,7 +154,7 @@ mod tests {
             let keys = [b"a", b"c", b"e"];
 
             {
-                let mut writer = BlobFileWriter::new(&blob_file_0_path, 0)?;
+                let mut writer = BlobFileWriter::new(&StdFilesystem, &blob_file_0_path, 0)?;
 
                 for key in keys {
                     writer.write(key, 0, &key.repeat(100))?;
@@ -167,7 +168,7 @@ mod tests {
             let keys = [b"b", b"d"];
 
             {
-                let mut writer = BlobFileWriter::new(&blob_file_1_path, 1)?;
+                let mut writer = BlobFileWriter::new(&StdFilesystem, &blob_file_1_path, 1)?;
 
                 for key in keys {
                     writer.write(key, 1, &key.repeat(100))?;
@@ -179,8 +180,8 @@ mod tests {
 
         {
             let mut merger = MergeScanner::new(vec![
-                Scanner::new(&blob_file_0_path, 0)?,
-                Scanner::new(&blob_file_1_path, 1)?,
+                Scanner::new(&StdFilesystem, &blob_file_0_path, 0)?,
+                Scanner::new(&StdFilesystem, &blob_file_1_path, 1)?,
             ]);
 
             let merged_keys = [b"a", b"b", b"c", b"d", b"e"];
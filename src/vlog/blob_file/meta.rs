@@ -5,7 +5,7 @@
 use crate::{
     coding::{Decode, Encode},
     table::{Block, DataBlock},
-    CompressionType, InternalValue, KeyRange, SeqNo, Slice,
+    CompressionType, Encryptor, InternalValue, KeyRange, SeqNo, Slice,
 };
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::{Read, Write};
@@ -14,7 +14,7 @@ macro_rules! read_u64 {
     ($block:expr, $name:expr) => {{
         let bytes = $block
             .point_read($name, SeqNo::MAX)
-            .unwrap_or_else(|| panic!("meta property {:?} should exist", $name));
+            .ok_or(crate::Error::Corrupted("BlobFileMeta"))?;
 
         let mut bytes = &bytes.value[..];
         bytes.read_u64::<LittleEndian>()?
@@ -25,7 +25,7 @@ macro_rules! read_u128 {
     ($block:expr, $name:expr) => {{
         let bytes = $block
             .point_read($name, SeqNo::MAX)
-            .unwrap_or_else(|| panic!("meta property {:?} should exist", $name));
+            .ok_or(crate::Error::Corrupted("BlobFileMeta"))?;
 
         let mut bytes = &bytes.value[..];
         bytes.read_u128::<LittleEndian>()?
@@ -55,7 +55,11 @@ pub struct Metadata {
 }
 
 impl Metadata {
-    pub fn encode_into<W: Write>(&self, writer: &mut W) -> crate::Result<()> {
+    pub fn encode_into<W: Write>(
+        &self,
+        writer: &mut W,
+        encryptor: Option<&dyn Encryptor>,
+    ) -> crate::Result<()> {
         fn meta(key: &str, value: &[u8]) -> InternalValue {
             InternalValue::from_components(key, value, 0, crate::ValueType::Value)
         }
@@ -90,12 +94,13 @@ impl Metadata {
             &buf,
             crate::table::block::BlockType::Meta,
             CompressionType::None,
+            encryptor,
         )?;
 
         Ok(())
     }
 
-    pub fn from_slice(slice: &Slice) -> crate::Result<Self> {
+    pub fn from_slice(slice: &Slice, encryptor: Option<&dyn Encryptor>) -> crate::Result<Self> {
         let reader = &mut &slice[..];
 
         // Check header
@@ -107,7 +112,7 @@ impl Metadata {
         }
 
         // TODO: Block::from_slice
-        let block = Block::from_reader(reader, CompressionType::None)?;
+        let block = Block::from_reader(reader, CompressionType::None, encryptor)?;
         let block = DataBlock::new(block);
 
         let created_at = read_u128!(block, b"created_at");
@@ -118,7 +123,7 @@ impl Metadata {
         let compression = {
             let bytes = block
                 .point_read(b"compression", SeqNo::MAX)
-                .expect("size should exist");
+                .ok_or(crate::Error::Corrupted("BlobFileMeta"))?;
 
             let mut bytes = &bytes.value[..];
             CompressionType::decode_from(&mut bytes)?
@@ -127,11 +132,11 @@ impl Metadata {
         let key_range = KeyRange::new((
             block
                 .point_read(b"key#min", SeqNo::MAX)
-                .expect("key min should exist")
+                .ok_or(crate::Error::Corrupted("BlobFileMeta"))?
                 .value,
             block
                 .point_read(b"key#max", SeqNo::MAX)
-                .expect("key max should exist")
+                .ok_or(crate::Error::Corrupted("BlobFileMeta"))?
                 .value,
         ));
 
@@ -164,10 +169,10 @@ mod tests {
         };
 
         let mut buf = Vec::new();
-        meta.encode_into(&mut buf).unwrap();
+        meta.encode_into(&mut buf, None).unwrap();
         let buf = Slice::from(buf);
 
-        let meta2 = Metadata::from_slice(&buf).unwrap();
+        let meta2 = Metadata::from_slice(&buf, None).unwrap();
         assert_eq!(meta, meta2);
     }
 }
@@ -7,11 +7,18 @@ pub mod meta;
 pub mod multi_writer;
 pub mod reader;
 pub mod scanner;
+pub mod stream;
 pub mod writer;
 
-use crate::{blob_tree::FragmentationMap, vlog::BlobFileId, Checksum};
+use crate::{
+    blob_tree::FragmentationMap, vlog::BlobFileId, Checksum, CompressionType, Encryptor,
+    Filesystem, UserKey, UserValue,
+};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 pub use meta::Metadata;
 use std::{
+    borrow::Cow,
+    io::{Read, Write},
     path::{Path, PathBuf},
     sync::{atomic::AtomicBool, Arc},
 };
@@ -25,6 +32,10 @@ pub struct Inner {
     /// File path
     pub path: PathBuf,
 
+    pub(crate) fs: Arc<dyn Filesystem>,
+
+    pub(crate) encryptor: Option<Arc<dyn Encryptor>>,
+
     /// Statistics
     pub meta: Metadata,
 
@@ -43,7 +54,7 @@ impl Drop for Inner {
                 self.path.display(),
             );
 
-            if let Err(e) = std::fs::remove_file(&*self.path) {
+            if let Err(e) = self.fs.remove_file(&self.path) {
                 log::warn!(
                     "Failed to cleanup deleted blob file {:?} at {}: {e:?}",
                     self.id,
@@ -54,6 +65,78 @@ impl Drop for Inner {
     }
 }
 
+/// Encrypts a blob.
+///
+/// The key is encrypted together with the (possibly compressed) value,
+/// so it does not appear in plain text in the blob file.
+pub fn encrypt_blob(encryptor: &dyn Encryptor, key: &[u8], value: &[u8]) -> crate::Result<Vec<u8>> {
+    let mut plaintext = Vec::with_capacity(std::mem::size_of::<u16>() + key.len() + value.len());
+
+    #[expect(clippy::cast_possible_truncation, reason = "keys are u16 length max")]
+    plaintext.write_u16::<LE>(key.len() as u16)?;
+    plaintext.write_all(key)?;
+    plaintext.write_all(value)?;
+
+    Ok(encryptor.encrypt(&plaintext)?)
+}
+
+/// Decrypts a blob that was encrypted using [`encrypt_blob`], returning its key and value.
+pub fn decrypt_blob(
+    encryptor: &dyn Encryptor,
+    ciphertext: &[u8],
+) -> crate::Result<(UserKey, UserValue)> {
+    let plaintext = encryptor.decrypt(ciphertext)?;
+    let mut reader = &plaintext[..];
+
+    let key_len = reader.read_u16::<LE>()?;
+    let key = UserKey::from_reader(&mut reader, key_len.into())?;
+
+    let mut value = vec![];
+    reader.read_to_end(&mut value)?;
+
+    Ok((key, value.into()))
+}
+
+/// Compresses a blob.
+pub fn compress_blob(compression: CompressionType, value: &[u8]) -> crate::Result<Cow<'_, [u8]>> {
+    Ok(match compression {
+        CompressionType::None => Cow::Borrowed(value),
+
+        #[cfg(feature = "lz4")]
+        CompressionType::Lz4 => Cow::Owned(lz4_flex::compress(value)),
+
+        CompressionType::Custom(id) => Cow::Owned(crate::compression::compress_custom(id, value)?),
+    })
+}
+
+/// Decompresses a blob that was compressed using [`compress_blob`].
+pub fn decompress_blob(
+    compression: CompressionType,
+    raw_data: UserValue,
+    uncompressed_len: usize,
+) -> crate::Result<UserValue> {
+    Ok(match compression {
+        CompressionType::None => raw_data,
+
+        #[cfg(feature = "lz4")]
+        CompressionType::Lz4 => {
+            #[warn(unsafe_code)]
+            let mut builder = unsafe { UserValue::builder_unzeroed(uncompressed_len) };
+
+            lz4_flex::decompress_into(&raw_data, &mut builder)
+                .map_err(|_| crate::Error::Decompress(compression))?;
+
+            builder.freeze().into()
+        }
+
+        CompressionType::Custom(id) => UserValue::from(crate::compression::decompress_custom(
+            id,
+            &raw_data,
+            uncompressed_len,
+        )?),
+    })
+}
+
 /// A blob file stores large values and is part of the value log
 #[derive(Clone)]
 pub struct BlobFile(pub(crate) Arc<Inner>);
@@ -104,16 +187,21 @@ impl BlobFile {
         self.0.meta.item_count
     }
 
-    /// Returns `true` if the blob file is stale (based on the given staleness threshold).
-    pub(crate) fn is_stale(&self, frag_map: &FragmentationMap, threshold: f32) -> bool {
-        frag_map.get(&self.id()).is_some_and(|x| {
+    /// Returns the ratio of stale bytes in the blob file, or `None` if it has no stale blobs.
+    pub(crate) fn stale_ratio(&self, frag_map: &FragmentationMap) -> Option<f32> {
+        frag_map.get(&self.id()).map(|x| {
             let stale_bytes = x.bytes as f32;
             let all_bytes = self.0.meta.total_uncompressed_bytes as f32;
-            let ratio = stale_bytes / all_bytes;
-            ratio >= threshold
+            stale_bytes / all_bytes
         })
     }
 
+    /// Returns `true` if the blob file is stale (based on the given staleness threshold).
+    pub(crate) fn is_stale(&self, frag_map: &FragmentationMap, threshold: f32) -> bool {
+        self.stale_ratio(frag_map)
+            .is_some_and(|ratio| ratio >= threshold)
+    }
+
     /// Returns `true` if the blob file has no more incoming references, and can be safely removed from a Version.
     pub(crate) fn is_dead(&self, frag_map: &FragmentationMap) -> bool {
         frag_map.get(&self.id()).is_some_and(|x| {
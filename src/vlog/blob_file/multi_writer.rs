@@ -8,7 +8,8 @@ use crate::{
         blob_file::{Inner as BlobFileInner, Metadata},
         BlobFileId,
     },
-    BlobFile, CompressionType, SeqNo, SequenceNumberCounter,
+    BlobFile, Clock, CompressionType, Encryptor, Filesystem, RateLimiter, SeqNo,
+    SequenceNumberCounter, SystemClock,
 };
 use std::{
     path::{Path, PathBuf},
@@ -17,7 +18,9 @@ use std::{
 
 /// Blob file writer, may write multiple blob files
 pub struct MultiWriter {
+    fs: Arc<dyn Filesystem>,
     folder: PathBuf,
+    shard_count: u16,
     target_size: u64,
 
     active_writer: Writer,
@@ -28,6 +31,12 @@ pub struct MultiWriter {
 
     compression: CompressionType,
     passthrough_compression: CompressionType,
+
+    clock: Arc<dyn Clock>,
+
+    encryptor: Option<Arc<dyn Encryptor>>,
+
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl MultiWriter {
@@ -38,29 +47,62 @@ impl MultiWriter {
     /// Will return `Err` if an IO error occurs.
     #[doc(hidden)]
     pub fn new<P: AsRef<Path>>(
+        fs: Arc<dyn Filesystem>,
         id_generator: SequenceNumberCounter,
         target_size: u64,
         folder: P,
+        shard_count: u16,
     ) -> crate::Result<Self> {
         let folder = folder.as_ref();
 
         let blob_file_id = id_generator.next();
-        let blob_file_path = folder.join(blob_file_id.to_string());
+        let blob_file_path = crate::file::sharded_path(folder, blob_file_id, shard_count);
 
         Ok(Self {
+            active_writer: Writer::new(&*fs, blob_file_path, blob_file_id)?,
+
+            fs,
             id_generator,
             folder: folder.into(),
+            shard_count,
             target_size,
 
-            active_writer: Writer::new(blob_file_path, blob_file_id)?,
-
             results: Vec::new(),
 
             compression: CompressionType::None,
             passthrough_compression: CompressionType::None,
+
+            clock: Arc::new(SystemClock),
+
+            encryptor: None,
+
+            rate_limiter: None,
         })
     }
 
+    /// Sets the clock that is used for the creation timestamps of blob files.
+    #[must_use]
+    pub(crate) fn use_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.active_writer = self.active_writer.use_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the encryptor that is used for all blobs.
+    #[must_use]
+    pub(crate) fn use_encryption(mut self, encryptor: Option<Arc<dyn Encryptor>>) -> Self {
+        self.active_writer = self.active_writer.use_encryption(encryptor.clone());
+        self.encryptor = encryptor;
+        self
+    }
+
+    /// Sets the rate limiter that the bytes of every blob are requested from.
+    #[must_use]
+    pub(crate) fn use_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
     /// Sets the blob file target size.
     #[must_use]
     pub fn use_target_size(mut self, bytes: u64) -> Self {
@@ -78,6 +120,12 @@ impl MultiWriter {
         self
     }
 
+    /// Returns the compression method that is set in the metadata of pass-through blob files,
+    /// see [`MultiWriter::use_passthrough_compression`].
+    pub(crate) fn passthrough_compression(&self) -> CompressionType {
+        self.passthrough_compression
+    }
+
     /// Sets the compression method.
     #[must_use]
     #[doc(hidden)]
@@ -102,25 +150,30 @@ impl MultiWriter {
         log::debug!("Rotating blob file writer");
 
         let new_blob_file_id = self.id_generator.next();
-        let blob_file_path = self.folder.join(new_blob_file_id.to_string());
+        let blob_file_path =
+            crate::file::sharded_path(&self.folder, new_blob_file_id, self.shard_count);
 
-        let new_writer =
-            Writer::new(blob_file_path, new_blob_file_id)?.use_compression(self.compression);
+        let new_writer = Writer::new(&*self.fs, blob_file_path, new_blob_file_id)?
+            .use_compression(self.compression)
+            .use_clock(self.clock.clone())
+            .use_encryption(self.encryptor.clone());
 
         let old_writer = std::mem::replace(&mut self.active_writer, new_writer);
-        let blob_file = Self::consume_writer(old_writer, self.passthrough_compression)?;
+        let blob_file = Self::consume_writer(&self.fs, old_writer, self.passthrough_compression)?;
         self.results.extend(blob_file);
 
         Ok(())
     }
 
     fn consume_writer(
+        fs: &Arc<dyn Filesystem>,
         writer: Writer,
         passthrough_compression: CompressionType,
     ) -> crate::Result<Option<BlobFile>> {
         if writer.item_count > 0 {
             let blob_file_id = writer.blob_file_id;
             let path = writer.path.clone();
+            let encryptor = writer.encryptor.clone();
 
             log::debug!(
                 "Created blob file #{blob_file_id:?} ({} items, {} userdata bytes)",
@@ -130,13 +183,25 @@ impl MultiWriter {
 
             let (metadata, checksum) = writer.finish()?;
 
+            // IMPORTANT: fsync folder on Unix
+            //
+            // The blob file may be referenced by a version right after this,
+            // so it needs to survive a crash
+            #[expect(
+                clippy::expect_used,
+                reason = "if there's no parent folder, something has gone horribly wrong"
+            )]
+            fs.sync_directory(path.parent().expect("should have folder"))?;
+
             let blob_file = BlobFile(Arc::new(BlobFileInner {
                 checksum,
                 path,
+                fs: fs.clone(),
+                encryptor,
                 is_deleted: AtomicBool::new(false),
                 id: blob_file_id,
                 meta: Metadata {
-                    created_at: crate::time::unix_timestamp().as_nanos(),
+                    created_at: metadata.created_at,
                     item_count: metadata.item_count,
                     total_compressed_bytes: metadata.total_compressed_bytes,
                     total_uncompressed_bytes: metadata.total_uncompressed_bytes,
@@ -157,7 +222,7 @@ impl MultiWriter {
                 writer.path.display(),
             );
 
-            if let Err(e) = std::fs::remove_file(&writer.path) {
+            if let Err(e) = fs.remove_file(&writer.path) {
                 log::warn!(
                     "Could not delete empty blob file at {}: {e:?}",
                     writer.path.display(),
@@ -180,6 +245,10 @@ impl MultiWriter {
         let writer = &mut self.active_writer;
         let bytes_written = writer.write(key, seqno, value)?;
 
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.request(u64::from(bytes_written));
+        }
+
         // Check for blob file size target, maybe rotate to next writer
         if writer.offset() >= target_size {
             self.rotate()?;
@@ -201,6 +270,10 @@ impl MultiWriter {
         let writer = &mut self.active_writer;
         let bytes_written = writer.write_raw(key, seqno, value, uncompressed_len)?;
 
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.request(u64::from(bytes_written));
+        }
+
         // Check for blob file size target, maybe rotate to next writer
         if writer.offset() >= target_size {
             self.rotate()?;
@@ -210,7 +283,8 @@ impl MultiWriter {
     }
 
     pub(crate) fn finish(mut self) -> crate::Result<Vec<BlobFile>> {
-        let blob_file = Self::consume_writer(self.active_writer, self.passthrough_compression)?;
+        let blob_file =
+            Self::consume_writer(&self.fs, self.active_writer, self.passthrough_compression)?;
         self.results.extend(blob_file);
         Ok(self.results)
     }
@@ -7,35 +7,91 @@ use crate::{
         blob_file::writer::{BLOB_HEADER_LEN, BLOB_HEADER_MAGIC},
         ValueHandle,
     },
-    BlobFile, Checksum, CompressionType, UserValue,
+    BlobFile, Checksum, FileHandle, UserValue,
 };
 use byteorder::{LittleEndian, ReadBytesExt};
-use std::{
-    fs::File,
-    io::{Cursor, Read, Seek},
-};
+use std::io::{Cursor, Read, Seek};
 
 /// Reads a single blob from a blob file
 pub struct Reader<'a> {
     blob_file: &'a BlobFile,
-    file: &'a File,
+    file: &'a dyn FileHandle,
 }
 
 impl<'a> Reader<'a> {
-    pub fn new(blob_file: &'a BlobFile, file: &'a File) -> Self {
+    pub fn new(blob_file: &'a BlobFile, file: &'a dyn FileHandle) -> Self {
         Self { blob_file, file }
     }
 
+    /// Returns the number of bytes the blob takes up in the blob file, including its header.
+    fn on_disk_len(&self, key: &[u8], vhandle: &ValueHandle) -> usize {
+        // NOTE: If encrypted, the key is stored inside the encrypted value
+        let key_len = if self.blob_file.0.encryptor.is_some() {
+            0
+        } else {
+            key.len()
+        };
+
+        BLOB_HEADER_LEN + key_len + vhandle.on_disk_size as usize
+    }
+
     pub fn get(&self, key: &'a [u8], vhandle: &'a ValueHandle) -> crate::Result<UserValue> {
         debug_assert_eq!(vhandle.blob_file_id, self.blob_file.id());
 
-        let add_size = (BLOB_HEADER_LEN as u64) + (key.len() as u64);
+        let value =
+            crate::file::read_exact(self.file, vhandle.offset, self.on_disk_len(key, vhandle))?;
+
+        self.parse(key, vhandle, &value)
+    }
+
+    /// Reads multiple blobs using a single read.
+    ///
+    /// The blobs need to be sorted by offset, and should be close to each other,
+    /// because everything between the first and the last blob is read as well.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "the read is only as large as the blobs in it, which are loaded into memory anyway"
+    )]
+    pub fn get_many(&self, items: &[(&[u8], &ValueHandle)]) -> crate::Result<Vec<UserValue>> {
+        let Some((_, first)) = items.first() else {
+            return Ok(vec![]);
+        };
+
+        let start = first.offset;
+        let end = items
+            .iter()
+            .map(|(key, vhandle)| vhandle.offset + self.on_disk_len(key, vhandle) as u64)
+            .max()
+            .unwrap_or(start);
+
+        let buf = crate::file::read_exact(self.file, start, (end - start) as usize)?;
+
+        items
+            .iter()
+            .map(|(key, vhandle)| {
+                debug_assert_eq!(vhandle.blob_file_id, self.blob_file.id());
+                debug_assert!(vhandle.offset >= start);
+
+                let lo = (vhandle.offset - start) as usize;
+                let hi = lo + self.on_disk_len(key, vhandle);
+
+                self.parse(key, vhandle, &buf.slice(lo..hi))
+            })
+            .collect()
+    }
+
+    fn parse(
+        &self,
+        key: &[u8],
+        vhandle: &ValueHandle,
+        value: &UserValue,
+    ) -> crate::Result<UserValue> {
+        let encryptor = self.blob_file.0.encryptor.as_deref();
+
+        // NOTE: If encrypted, the key is stored inside the encrypted value
+        let key_on_disk = if encryptor.is_some() { &[][..] } else { key };
 
-        let value = crate::file::read_exact(
-            self.file,
-            vhandle.offset,
-            (u64::from(vhandle.on_disk_size) + add_size) as usize,
-        )?;
+        let add_size = (BLOB_HEADER_LEN as u64) + (key_on_disk.len() as u64);
 
         let mut reader = Cursor::new(&value[..]);
 
@@ -51,7 +107,6 @@ impl<'a> Reader<'a> {
         let _seqno = reader.read_u64::<LittleEndian>()?;
         let key_len = reader.read_u16::<LittleEndian>()?;
 
-        #[expect(unused, reason = "only used in feature flagged branch")]
         let real_val_len = reader.read_u32::<LittleEndian>()? as usize;
 
         let _on_disk_val_len = reader.read_u32::<LittleEndian>()? as usize;
@@ -63,7 +118,7 @@ impl<'a> Reader<'a> {
         {
             let checksum = {
                 let mut hasher = xxhash_rust::xxh3::Xxh3::default();
-                hasher.update(key);
+                hasher.update(key_on_disk);
                 hasher.update(&raw_data);
                 hasher.digest128()
             };
@@ -80,23 +135,21 @@ impl<'a> Reader<'a> {
             }
         }
 
-        #[warn(clippy::match_single_binding)]
-        let value = match &self.blob_file.0.meta.compression {
-            CompressionType::None => raw_data,
+        let raw_data = match encryptor {
+            Some(encryptor) => {
+                let (blob_key, value) = super::decrypt_blob(encryptor, &raw_data)?;
 
-            #[cfg(feature = "lz4")]
-            CompressionType::Lz4 => {
-                #[warn(unsafe_code)]
-                let mut builder = unsafe { UserValue::builder_unzeroed(real_val_len as usize) };
+                if blob_key != key {
+                    log::error!("Blob key mismatch for blob {vhandle:?}");
+                    return Err(crate::Error::InvalidHeader("Blob"));
+                }
 
-                lz4_flex::decompress_into(&raw_data, &mut builder)
-                    .map_err(|_| crate::Error::Decompress(self.blob_file.0.meta.compression))?;
-
-                builder.freeze().into()
+                value
             }
+            None => raw_data,
         };
 
-        Ok(value)
+        super::decompress_blob(self.blob_file.0.meta.compression, raw_data, real_val_len)
     }
 }
 
@@ -104,7 +157,8 @@ impl<'a> Reader<'a> {
 #[expect(clippy::unwrap_used)]
 mod tests {
     use super::*;
-    use crate::SequenceNumberCounter;
+    use crate::{CompressionType, SequenceNumberCounter};
+    use std::sync::Arc;
     use test_log::test;
 
     #[test]
@@ -112,8 +166,14 @@ mod tests {
         let id_generator = SequenceNumberCounter::default();
 
         let folder = tempfile::tempdir()?;
-        let mut writer =
-            crate::vlog::BlobFileWriter::new(id_generator, u64::MAX, folder.path()).unwrap();
+        let mut writer = crate::vlog::BlobFileWriter::new(
+            Arc::new(crate::StdFilesystem),
+            id_generator,
+            u64::MAX,
+            folder.path(),
+            1,
+        )
+        .unwrap();
 
         let offset = writer.offset();
         let on_disk_size = writer.write(b"a", 0, b"abcdef")?;
@@ -126,7 +186,7 @@ mod tests {
         let blob_file = writer.finish()?;
         let blob_file = blob_file.first().unwrap();
 
-        let file = File::open(&blob_file.0.path)?;
+        let file = std::fs::File::open(&blob_file.0.path)?;
         let reader = Reader::new(blob_file, &file);
 
         assert_eq!(reader.get(b"a", &handle)?, b"abcdef");
@@ -140,9 +200,15 @@ mod tests {
         let id_generator = SequenceNumberCounter::default();
 
         let folder = tempfile::tempdir()?;
-        let mut writer = crate::vlog::BlobFileWriter::new(id_generator, u64::MAX, folder.path())
-            .unwrap()
-            .use_compression(CompressionType::Lz4);
+        let mut writer = crate::vlog::BlobFileWriter::new(
+            Arc::new(crate::StdFilesystem),
+            id_generator,
+            u64::MAX,
+            folder.path(),
+            1,
+        )
+        .unwrap()
+        .use_compression(CompressionType::Lz4);
 
         let offset = writer.offset();
         let on_disk_size = writer.write(b"a", 0, b"abcdef")?;
@@ -163,7 +229,7 @@ mod tests {
         let blob_file = writer.finish()?;
         let blob_file = blob_file.first().unwrap();
 
-        let file = File::open(&blob_file.0.path)?;
+        let file = std::fs::File::open(&blob_file.0.path)?;
         let reader = Reader::new(blob_file, &file);
 
         assert_eq!(reader.get(b"a", &handle0)?, b"abcdef");
@@ -171,4 +237,61 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[expect(clippy::indexing_slicing)]
+    fn blob_reader_get_many() -> crate::Result<()> {
+        let id_generator = SequenceNumberCounter::default();
+
+        let folder = tempfile::tempdir()?;
+        let mut writer = crate::vlog::BlobFileWriter::new(
+            Arc::new(crate::StdFilesystem),
+            id_generator,
+            u64::MAX,
+            folder.path(),
+            1,
+        )
+        .unwrap();
+
+        let mut handles = vec![];
+
+        for (key, value) in [
+            (b"a", b"abcdef".as_slice()),
+            (b"b", b"ghi"),
+            (b"c", b"jklmno"),
+        ] {
+            let offset = writer.offset();
+            let on_disk_size = writer.write(key, 0, value)?;
+            handles.push(ValueHandle {
+                blob_file_id: 0,
+                offset,
+                on_disk_size,
+            });
+        }
+
+        let blob_file = writer.finish()?;
+        let blob_file = blob_file.first().unwrap();
+
+        let file = std::fs::File::open(&blob_file.0.path)?;
+        let reader = Reader::new(blob_file, &file);
+
+        assert_eq!(
+            reader.get_many(&[
+                (b"a", &handles[0]),
+                (b"b", &handles[1]),
+                (b"c", &handles[2])
+            ])?,
+            [b"abcdef".as_slice(), b"ghi", b"jklmno"],
+        );
+
+        // NOTE: Blobs in between are read, but not returned
+        assert_eq!(
+            reader.get_many(&[(b"a", &handles[0]), (b"c", &handles[2])])?,
+            [b"abcdef".as_slice(), b"jklmno"],
+        );
+
+        assert!(reader.get_many(&[])?.is_empty());
+
+        Ok(())
+    }
 }
@@ -5,20 +5,21 @@
 use super::writer::BLOB_HEADER_MAGIC;
 use crate::{
     vlog::{blob_file::meta::METADATA_HEADER_MAGIC, BlobFileId},
-    Checksum, SeqNo, UserKey, UserValue,
+    Checksum, Encryptor, FileHandle, Filesystem, SeqNo, UserKey, UserValue,
 };
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::{
-    fs::File,
     io::{BufReader, Read, Seek},
     path::Path,
+    sync::Arc,
 };
 
 /// Reads through a blob file in order
 pub struct Scanner {
     pub(crate) blob_file_id: BlobFileId, // TODO: remove unused?
-    inner: BufReader<File>,
+    inner: BufReader<Box<dyn FileHandle>>,
     is_terminated: bool,
+    encryptor: Option<Arc<dyn Encryptor>>,
 }
 
 impl Scanner {
@@ -27,20 +28,35 @@ impl Scanner {
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
-    pub fn new<P: AsRef<Path>>(path: P, blob_file_id: BlobFileId) -> crate::Result<Self> {
-        let file_reader = BufReader::with_capacity(32_000, File::open(path)?);
+    pub fn new<P: AsRef<Path>>(
+        fs: &dyn Filesystem,
+        path: P,
+        blob_file_id: BlobFileId,
+    ) -> crate::Result<Self> {
+        let file_reader = BufReader::with_capacity(32_000, fs.open(path.as_ref())?);
         Ok(Self::with_reader(blob_file_id, file_reader))
     }
 
     /// Initializes a new blob file reader.
     #[must_use]
-    pub fn with_reader(blob_file_id: BlobFileId, file_reader: BufReader<File>) -> Self {
+    pub fn with_reader(
+        blob_file_id: BlobFileId,
+        file_reader: BufReader<Box<dyn FileHandle>>,
+    ) -> Self {
         Self {
             blob_file_id,
             inner: file_reader,
             is_terminated: false,
+            encryptor: None,
         }
     }
+
+    /// Sets the encryptor that is used to decrypt blobs.
+    #[must_use]
+    pub(crate) fn use_encryption(mut self, encryptor: Option<Arc<dyn Encryptor>>) -> Self {
+        self.encryptor = encryptor;
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -113,6 +129,11 @@ impl Iterator for Scanner {
             }
         }
 
+        let (key, value) = match &self.encryptor {
+            Some(encryptor) => fail_iter!(super::decrypt_blob(&**encryptor, &value)),
+            None => (key, value),
+        };
+
         Some(Ok(ScanEntry {
             key,
             seqno,
@@ -127,7 +148,7 @@ impl Iterator for Scanner {
 #[expect(clippy::unwrap_used)]
 mod tests {
     use super::*;
-    use crate::{vlog::blob_file::writer::Writer as BlobFileWriter, Slice};
+    use crate::{vlog::blob_file::writer::Writer as BlobFileWriter, Slice, StdFilesystem};
     use tempfile::tempdir;
     use test_log::test;
 
@@ -139,7 +160,7 @@ mod tests {
         let keys = [b"a", b"b", b"c", b"d", b"e"];
 
         {
-            let mut writer = BlobFileWriter::new(&blob_file_path, 0)?;
+            let mut writer = BlobFileWriter::new(&StdFilesystem, &blob_file_path, 0)?;
 
             for key in keys {
                 writer.write(key, 0, &key.repeat(100))?;
@@ -149,7 +170,7 @@ mod tests {
         }
 
         {
-            let mut scanner = Scanner::new(&blob_file_path, 0)?;
+            let mut scanner = Scanner::new(&StdFilesystem, &blob_file_path, 0)?;
 
             for key in keys {
                 assert_eq!(
@@ -0,0 +1,146 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{
+    reader::Reader,
+    writer::{BLOB_HEADER_LEN, BLOB_HEADER_MAGIC},
+};
+use crate::{vlog::ValueHandle, BlobFile, CompressionType, FileHandle, UserValue};
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::{
+    io::{Cursor, Read},
+    sync::Arc,
+};
+
+/// Streams a value without loading it into memory as a whole
+///
+/// Blobs that are stored uncompressed and unencrypted are read from the blob file
+/// in chunks, as the reader is consumed. The checksum of the blob is verified
+/// once the end of the blob is reached, so the last read fails if the blob is corrupted.
+///
+/// Compressed or encrypted blobs, and values that are stored inline
+/// in the index tree, are loaded into memory as a whole.
+pub struct BlobReader(Inner);
+
+enum Inner {
+    Memory(Cursor<UserValue>),
+    File(Box<FileStream>),
+}
+
+struct FileStream {
+    /// Keeps the blob file from being deleted while it is being read
+    blob_file: BlobFile,
+
+    file: Arc<dyn FileHandle>,
+    pos: u64,
+    end: u64,
+
+    hasher: xxhash_rust::xxh3::Xxh3,
+    expected_checksum: u128,
+}
+
+impl FileStream {
+    fn verify(&self) -> std::io::Result<()> {
+        let checksum = self.hasher.digest128();
+
+        if checksum == self.expected_checksum {
+            Ok(())
+        } else {
+            log::error!(
+                "Checksum mismatch for streamed blob in blob file #{}, got={checksum}, expected={}",
+                self.blob_file.id(),
+                self.expected_checksum,
+            );
+
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "blob checksum mismatch",
+            ))
+        }
+    }
+}
+
+impl BlobReader {
+    pub(crate) fn from_value(value: UserValue) -> Self {
+        Self(Inner::Memory(Cursor::new(value)))
+    }
+
+    pub(crate) fn open(
+        blob_file: &BlobFile,
+        file: Arc<dyn FileHandle>,
+        key: &[u8],
+        vhandle: &ValueHandle,
+    ) -> crate::Result<Self> {
+        debug_assert_eq!(vhandle.blob_file_id, blob_file.id());
+
+        if blob_file.0.encryptor.is_some() || blob_file.0.meta.compression != CompressionType::None
+        {
+            let value = Reader::new(blob_file, &*file).get(key, vhandle)?;
+            return Ok(Self::from_value(value));
+        }
+
+        let header_len = BLOB_HEADER_LEN + key.len();
+        let header = crate::file::read_exact(&*file, vhandle.offset, header_len)?;
+
+        let mut reader = Cursor::new(&header[..]);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+
+        if magic != BLOB_HEADER_MAGIC {
+            return Err(crate::Error::InvalidHeader("Blob"));
+        }
+
+        let expected_checksum = reader.read_u128::<LittleEndian>()?;
+
+        let mut hasher = xxhash_rust::xxh3::Xxh3::default();
+        hasher.update(key);
+
+        let pos = vhandle.offset + header_len as u64;
+
+        Ok(Self(Inner::File(Box::new(FileStream {
+            blob_file: blob_file.clone(),
+            file,
+            pos,
+            end: pos + u64::from(vhandle.on_disk_size),
+            hasher,
+            expected_checksum,
+        }))))
+    }
+}
+
+impl Read for BlobReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let stream = match &mut self.0 {
+            Inner::Memory(cursor) => return cursor.read(buf),
+            Inner::File(stream) => stream,
+        };
+
+        let remaining = stream.end - stream.pos;
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "the chunk is at most as large as the buffer"
+        )]
+        let n = remaining.min(buf.len() as u64) as usize;
+
+        if n == 0 {
+            return Ok(0);
+        }
+
+        let chunk = crate::file::read_exact(&*stream.file, stream.pos, n)?;
+        stream.hasher.update(&chunk);
+        stream.pos += n as u64;
+
+        if stream.pos == stream.end {
+            stream.verify()?;
+        }
+
+        if let Some(buf) = buf.get_mut(..n) {
+            buf.copy_from_slice(&chunk);
+        }
+
+        Ok(n)
+    }
+}
@@ -4,12 +4,14 @@
 
 use super::meta::Metadata;
 use crate::{
-    time::unix_timestamp, vlog::BlobFileId, Checksum, CompressionType, KeyRange, SeqNo, UserKey,
+    vlog::BlobFileId, Checksum, Clock, CompressionType, Encryptor, Filesystem, KeyRange, SeqNo,
+    SystemClock, UserKey,
 };
 use byteorder::{LittleEndian, WriteBytesExt};
 use std::{
-    io::Write,
+    io::{BufWriter, Write},
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 pub const BLOB_HEADER_MAGIC: &[u8] = b"BLOB";
@@ -27,7 +29,7 @@ pub struct Writer {
     pub(crate) blob_file_id: BlobFileId,
 
     #[expect(clippy::struct_field_names)]
-    writer: sfa::Writer,
+    writer: crate::archive::Writer,
 
     offset: u64,
 
@@ -39,6 +41,10 @@ pub struct Writer {
     pub(crate) last_key: Option<UserKey>,
 
     pub(crate) compression: CompressionType,
+
+    clock: Arc<dyn Clock>,
+
+    pub(crate) encryptor: Option<Arc<dyn Encryptor>>,
 }
 
 impl Writer {
@@ -48,9 +54,14 @@ impl Writer {
     ///
     /// Will return `Err` if an IO error occurs.
     #[doc(hidden)]
-    pub fn new<P: AsRef<Path>>(path: P, blob_file_id: BlobFileId) -> crate::Result<Self> {
+    pub fn new<P: AsRef<Path>>(
+        fs: &dyn Filesystem,
+        path: P,
+        blob_file_id: BlobFileId,
+    ) -> crate::Result<Self> {
         let path = path.as_ref();
-        let mut writer = sfa::Writer::new_at_path(path)?;
+        let file = fs.create_new(path)?;
+        let mut writer = crate::archive::Writer::from_writer(BufWriter::new(file));
         writer.start("data")?;
 
         Ok(Self {
@@ -68,9 +79,27 @@ impl Writer {
             last_key: None,
 
             compression: CompressionType::None,
+
+            clock: Arc::new(SystemClock),
+
+            encryptor: None,
         })
     }
 
+    /// Sets the clock that is used for the creation timestamp of the blob file.
+    #[must_use]
+    pub(crate) fn use_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the encryptor that is used for all blobs and the metadata of the blob file.
+    #[must_use]
+    pub(crate) fn use_encryption(mut self, encryptor: Option<Arc<dyn Encryptor>>) -> Self {
+        self.encryptor = encryptor;
+        self
+    }
+
     pub fn use_compression(mut self, compressor: CompressionType) -> Self {
         self.compression = compressor;
         self
@@ -119,17 +148,25 @@ impl Writer {
         // [on-disk val len; 4B]
         // [...key; ?]
         // [...val; ?]
+        //
+        // If encrypted, the key is stored inside the encrypted value,
+        // and the key length is 0.
 
         // Write header
         self.writer.write_all(BLOB_HEADER_MAGIC)?;
 
-        let value = match &self.compression {
-            CompressionType::None => std::borrow::Cow::Borrowed(value),
+        let value = super::compress_blob(self.compression, value)?;
 
-            #[cfg(feature = "lz4")]
-            CompressionType::Lz4 => std::borrow::Cow::Owned(lz4_flex::compress(value)),
+        let (key, value) = match &self.encryptor {
+            Some(encryptor) => (
+                &[][..],
+                std::borrow::Cow::Owned(super::encrypt_blob(&**encryptor, key, &value)?),
+            ),
+            None => (key, value),
         };
 
+        assert!(u32::try_from(value.len()).is_ok());
+
         let checksum = {
             let mut hasher = xxhash_rust::xxh3::Xxh3::default();
             hasher.update(key);
@@ -199,7 +236,7 @@ impl Writer {
 
         // Write metadata
         let metadata = Metadata {
-            created_at: unix_timestamp().as_nanos(),
+            created_at: self.clock.now().as_nanos(),
             item_count: self.item_count,
             total_compressed_bytes: self.written_blob_bytes,
             total_uncompressed_bytes: self.uncompressed_bytes,
@@ -213,10 +250,10 @@ impl Writer {
             )),
             compression: self.compression,
         };
-        metadata.encode_into(&mut self.writer)?;
+        metadata.encode_into(&mut self.writer, self.encryptor.as_deref())?;
 
         let checksum = self.writer.finish()?;
 
-        Ok((metadata, checksum.into()))
+        Ok((metadata, checksum))
     }
 }
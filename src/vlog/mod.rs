@@ -9,12 +9,13 @@ mod handle;
 pub use {
     accessor::Accessor, blob_file::merge::MergeScanner as BlobFileMergeScanner,
     blob_file::multi_writer::MultiWriter as BlobFileWriter,
-    blob_file::scanner::Scanner as BlobFileScanner, blob_file::BlobFile, handle::ValueHandle,
+    blob_file::scanner::Scanner as BlobFileScanner, blob_file::stream::BlobReader,
+    blob_file::BlobFile, handle::ValueHandle,
 };
 
 use crate::{
     vlog::blob_file::{Inner as BlobFileInner, Metadata},
-    Checksum,
+    Checksum, Encryptor, Filesystem,
 };
 use std::{
     path::{Path, PathBuf},
@@ -22,10 +23,12 @@ use std::{
 };
 
 pub fn recover_blob_files(
+    fs: &Arc<dyn Filesystem>,
+    encryptor: Option<&Arc<dyn Encryptor>>,
     folder: &Path,
     ids: &[(BlobFileId, Checksum)],
 ) -> crate::Result<(Vec<BlobFile>, Vec<PathBuf>)> {
-    if !folder.try_exists()? {
+    if !fs.exists(folder)? {
         return Ok((vec![], vec![]));
     }
 
@@ -42,9 +45,11 @@ pub fn recover_blob_files(
     let mut blob_files = Vec::with_capacity(ids.len());
     let mut orphaned_blob_files = vec![];
 
-    for (idx, dirent) in std::fs::read_dir(folder)?.enumerate() {
-        let dirent = dirent?;
-        let file_name = dirent.file_name();
+    for (idx, blob_file_path) in crate::file::read_sharded_dir(&**fs, folder)?
+        .into_iter()
+        .enumerate()
+    {
+        let file_name = blob_file_path.file_name().unwrap_or_default();
 
         // https://en.wikipedia.org/wiki/.DS_Store
         if file_name == ".DS_Store" {
@@ -66,14 +71,13 @@ pub fn recover_blob_files(
             crate::Error::Unrecoverable
         })?;
 
-        let blob_file_path = dirent.path();
-        assert!(!blob_file_path.is_dir());
+        assert!(!fs.is_dir(&blob_file_path)?);
 
         if let Some(&(_, checksum)) = ids.iter().find(|(id, _)| id == &blob_file_id) {
             log::trace!("Recovering blob file #{blob_file_id:?}");
 
             let meta = {
-                let reader = sfa::Reader::new(&blob_file_path)?;
+                let reader = crate::fs::read_archive(&**fs, &blob_file_path)?;
                 let toc = reader.toc();
 
                 let metadata_section = toc.section(b"meta")
@@ -82,19 +86,21 @@ pub fn recover_blob_files(
                     log::error!("meta section in blob file #{blob_file_id} is missing - maybe the file is corrupted?");
                 })?;
 
-                let file = std::fs::File::open(&blob_file_path)?;
+                let file = fs.open(&blob_file_path)?;
                 let metadata_slice = crate::file::read_exact(
-                    &file,
+                    &*file,
                     metadata_section.pos(),
                     metadata_section.len() as usize,
                 )?;
 
-                Metadata::from_slice(&metadata_slice)?
+                Metadata::from_slice(&metadata_slice, encryptor.map(|encryptor| &**encryptor))?
             };
 
             blob_files.push(BlobFile(Arc::new(BlobFileInner {
                 id: blob_file_id,
                 path: blob_file_path,
+                fs: fs.clone(),
+                encryptor: encryptor.cloned(),
                 meta,
                 is_deleted: AtomicBool::new(false),
                 checksum,
@@ -0,0 +1,205 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::time::Duration;
+
+/// Whether writes to a tree are currently delayed, see [`WriteStallOptions`]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum WriteStallCondition {
+    /// Writes are not delayed
+    #[default]
+    Normal,
+
+    /// Every write is delayed, so flushes and compactions can catch up
+    Delayed,
+
+    /// Writes are blocked until flushes and compactions have caught up
+    Stopped,
+}
+
+impl From<u8> for WriteStallCondition {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Delayed,
+            2 => Self::Stopped,
+            _ => Self::Normal,
+        }
+    }
+}
+
+impl From<WriteStallCondition> for u8 {
+    fn from(value: WriteStallCondition) -> Self {
+        match value {
+            WriteStallCondition::Normal => 0,
+            WriteStallCondition::Delayed => 1,
+            WriteStallCondition::Stopped => 2,
+        }
+    }
+}
+
+/// Gets notified when the write stall condition of a tree changes,
+/// see [`Config::with_write_stall_listener`](crate::Config::with_write_stall_listener)
+///
+/// The listener is called by the writing thread, so it should return quickly.
+pub trait WriteStallListener: Send + Sync + 'static {
+    /// Called when the write stall condition of a tree changes.
+    fn on_write_stall(&self, condition: WriteStallCondition);
+}
+
+/// Options for write stalls (backpressure)
+///
+/// If writes are faster than flushes and compactions, L0 tables and sealed memtables pile up,
+/// which increases read amplification (and memory usage) without bounds.
+/// To prevent that, writes are delayed once a slowdown threshold is reached,
+/// and blocked once a stop threshold is reached, until flushes and compactions catch up.
+///
+/// Flushes and compactions need to run on other threads (see [`Config::background_threads`](crate::Config::background_threads)
+/// or [`Keyspace`](crate::keyspace::Keyspace)), otherwise a stopped write blocks forever.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{Config, SequenceNumberCounter, WriteStallOptions};
+///
+/// let seqno = SequenceNumberCounter::default();
+///
+/// let tree = Config::new(folder, seqno.clone())
+///     .background_threads(4)
+///     .background_gc_watermark(seqno)
+///     .with_write_stall(Some(WriteStallOptions::default().l0_slowdown(8).l0_stop(16)))
+///     .open()?;
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WriteStallOptions {
+    /// Number of L0 tables after which writes are delayed
+    pub(crate) l0_slowdown: usize,
+
+    /// Number of L0 tables after which writes are blocked
+    pub(crate) l0_stop: usize,
+
+    /// Number of sealed memtables after which writes are delayed
+    pub(crate) sealed_memtable_slowdown: usize,
+
+    /// Number of sealed memtables after which writes are blocked
+    pub(crate) sealed_memtable_stop: usize,
+
+    /// Delay of every write while writes are delayed
+    pub(crate) delay: Duration,
+}
+
+impl Default for WriteStallOptions {
+    fn default() -> Self {
+        Self {
+            l0_slowdown: 20,
+            l0_stop: 36,
+            sealed_memtable_slowdown: 4,
+            sealed_memtable_stop: 8,
+            delay: Duration::from_millis(1),
+        }
+    }
+}
+
+impl WriteStallOptions {
+    /// Sets the number of L0 tables after which writes are delayed.
+    ///
+    /// Defaults to 20.
+    #[must_use]
+    pub fn l0_slowdown(mut self, n: usize) -> Self {
+        self.l0_slowdown = n;
+        self
+    }
+
+    /// Sets the number of L0 tables after which writes are blocked.
+    ///
+    /// Defaults to 36.
+    #[must_use]
+    pub fn l0_stop(mut self, n: usize) -> Self {
+        self.l0_stop = n;
+        self
+    }
+
+    /// Sets the number of sealed memtables (that are not flushed yet)
+    /// after which writes are delayed.
+    ///
+    /// Defaults to 4.
+    #[must_use]
+    pub fn sealed_memtable_slowdown(mut self, n: usize) -> Self {
+        self.sealed_memtable_slowdown = n;
+        self
+    }
+
+    /// Sets the number of sealed memtables (that are not flushed yet)
+    /// after which writes are blocked.
+    ///
+    /// Defaults to 8.
+    #[must_use]
+    pub fn sealed_memtable_stop(mut self, n: usize) -> Self {
+        self.sealed_memtable_stop = n;
+        self
+    }
+
+    /// Sets the delay of every write while writes are delayed.
+    ///
+    /// Blocked writes check the stall condition again after the same delay.
+    ///
+    /// Defaults to 1 ms.
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Returns the stall condition of a tree with the given number of
+    /// L0 tables and sealed memtables.
+    pub(crate) fn condition(
+        &self,
+        l0_table_count: usize,
+        sealed_memtable_count: usize,
+    ) -> WriteStallCondition {
+        if l0_table_count >= self.l0_stop || sealed_memtable_count >= self.sealed_memtable_stop {
+            WriteStallCondition::Stopped
+        } else if l0_table_count >= self.l0_slowdown
+            || sealed_memtable_count >= self.sealed_memtable_slowdown
+        {
+            WriteStallCondition::Delayed
+        } else {
+            WriteStallCondition::Normal
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn write_stall_condition() {
+        let opts = WriteStallOptions::default()
+            .l0_slowdown(2)
+            .l0_stop(4)
+            .sealed_memtable_slowdown(1)
+            .sealed_memtable_stop(3);
+
+        assert_eq!(WriteStallCondition::Normal, opts.condition(1, 0));
+        assert_eq!(WriteStallCondition::Delayed, opts.condition(2, 0));
+        assert_eq!(WriteStallCondition::Delayed, opts.condition(0, 1));
+        assert_eq!(WriteStallCondition::Stopped, opts.condition(4, 0));
+        assert_eq!(WriteStallCondition::Stopped, opts.condition(0, 3));
+    }
+
+    #[test]
+    fn write_stall_condition_roundtrip() {
+        for condition in [
+            WriteStallCondition::Normal,
+            WriteStallCondition::Delayed,
+            WriteStallCondition::Stopped,
+        ] {
+            assert_eq!(condition, WriteStallCondition::from(u8::from(condition)));
+        }
+    }
+}
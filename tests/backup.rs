@@ -0,0 +1,216 @@
+use lsm_tree::{
+    backup::BackupEngine, AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter,
+};
+use std::path::Path;
+use test_log::test;
+
+fn file_count(path: &Path) -> lsm_tree::Result<usize> {
+    Ok(std::fs::read_dir(path)?.count())
+}
+
+#[test]
+fn backup_incremental() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    let backups = BackupEngine::open(&backup_folder)?;
+
+    for i in 0..10u32 {
+        tree.insert(format!("{i:0>3}"), "v", seqno.next());
+        tree.flush_active_memtable(0)?;
+    }
+
+    let info = backups.create_backup(&tree)?;
+    assert_eq!(0, info.id);
+    assert_eq!(10, info.table_count);
+    assert_eq!(10, file_count(&backup_folder.path().join("tables"))?);
+
+    // NOTE: Only the new table is copied
+    tree.insert("a", "v", seqno.next());
+    let info = backups.create_backup(&tree)?;
+    assert_eq!(1, info.id);
+    assert_eq!(11, info.table_count);
+    assert_eq!(11, file_count(&backup_folder.path().join("tables"))?);
+
+    assert_eq!(
+        vec![0, 1],
+        backups
+            .list_backups()?
+            .into_iter()
+            .map(|info| info.id)
+            .collect::<Vec<_>>(),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn backup_restore() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+    let restore_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone())
+        .directory_shards(4)
+        .open()?;
+    let backups = BackupEngine::open(&backup_folder)?;
+
+    for i in 0..100u32 {
+        tree.insert(format!("{i:0>3}"), "v", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.remove_range("000".."010", seqno.next());
+
+    // NOTE: Not flushed yet
+    tree.insert("a", "a", seqno.next());
+
+    backups.create_backup(&tree)?;
+
+    // NOTE: Not part of the backup
+    tree.insert("b", "b", seqno.next());
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    drop(tree);
+    drop(folder);
+
+    let path = restore_folder.path().join("tree");
+    backups.restore(&path)?;
+
+    let restored = Config::new(&path, SequenceNumberCounter::default()).open()?;
+    assert_eq!(91, restored.len(SeqNo::MAX, None)?);
+    assert!(restored.contains_key("a", SeqNo::MAX)?);
+    assert!(!restored.contains_key("b", SeqNo::MAX)?);
+    assert!(!restored.contains_key("005", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn backup_restore_blob_tree() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+    let restore_folder = tempfile::tempdir()?;
+
+    let big = "a".repeat(1_000);
+
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+    let backups = BackupEngine::open(&backup_folder)?;
+
+    for i in 0..10u32 {
+        tree.insert(format!("{i:0>3}"), &big, seqno.next());
+    }
+
+    let info = backups.create_backup(&tree)?;
+    assert_eq!(1, info.table_count);
+    assert_eq!(1, info.blob_file_count);
+
+    let path = restore_folder.path().join("tree");
+    backups.restore(&path)?;
+
+    let restored = Config::new(&path, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+    assert_eq!(1, restored.blob_file_count());
+    assert_eq!(10, restored.len(SeqNo::MAX, None)?);
+    assert_eq!(
+        Some(big.as_bytes().into()),
+        restored.get("005", SeqNo::MAX)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn backup_restore_older_backup() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+    let restore_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    let backups = BackupEngine::open(&backup_folder)?;
+
+    tree.insert("a", "v", seqno.next());
+    let first = backups.create_backup(&tree)?;
+
+    tree.insert("b", "v", seqno.next());
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    backups.create_backup(&tree)?;
+
+    let path = restore_folder.path().join("tree");
+    backups.restore_backup(first.id, &path)?;
+
+    let restored = Config::new(&path, SequenceNumberCounter::default()).open()?;
+    assert_eq!(1, restored.len(SeqNo::MAX, None)?);
+    assert!(!restored.contains_key("b", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn backup_delete() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+    let restore_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    let backups = BackupEngine::open(&backup_folder)?;
+
+    tree.insert("a", "v", seqno.next());
+    backups.create_backup(&tree)?;
+
+    tree.insert("b", "v", seqno.next());
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    backups.create_backup(&tree)?;
+    assert_eq!(2, file_count(&backup_folder.path().join("tables"))?);
+
+    // NOTE: The table of the first backup is not needed anymore
+    backups.delete_backup(0)?;
+    assert_eq!(1, backups.list_backups()?.len());
+    assert_eq!(1, file_count(&backup_folder.path().join("tables"))?);
+
+    let path = restore_folder.path().join("tree");
+    backups.restore(&path)?;
+
+    let restored = Config::new(&path, SequenceNumberCounter::default()).open()?;
+    assert_eq!(2, restored.len(SeqNo::MAX, None)?);
+
+    // NOTE: The new backup does not reuse the ID of the deleted one
+    assert_eq!(2, backups.create_backup(&tree)?.id);
+
+    backups.purge_old_backups(0)?;
+    assert!(backups.list_backups()?.is_empty());
+    assert_eq!(0, file_count(&backup_folder.path().join("tables"))?);
+
+    Ok(())
+}
+
+#[test]
+fn backup_restore_exists() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+    let restore_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    let backups = BackupEngine::open(&backup_folder)?;
+
+    assert!(backups.restore(restore_folder.path().join("tree")).is_err());
+
+    tree.insert("a", "v", seqno.next());
+    backups.create_backup(&tree)?;
+
+    assert!(backups.restore(restore_folder.path()).is_err());
+    assert!(backups
+        .restore_backup(1, restore_folder.path().join("tree"))
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use lsm_tree::{AbstractTree, Cache, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn blob_cache_dedicated() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let block_cache = Arc::new(Cache::with_capacity_bytes(1_024 * 1_024));
+    let blob_cache = Arc::new(Cache::with_capacity_bytes(1_024 * 1_024));
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_cache(block_cache.clone())
+        .use_blob_cache(blob_cache.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    let value = "a".repeat(10_000);
+    tree.insert("a", &value, 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+
+    assert!(blob_cache.is_empty());
+    let block_cache_size = block_cache.size();
+
+    assert_eq!(Some(value.as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+    assert_eq!(1, blob_cache.len());
+    assert!(blob_cache.size() >= 10_000);
+
+    // NOTE: Only index blocks and data blocks go into the block cache
+    assert!(block_cache.size() - block_cache_size < 10_000);
+
+    // NOTE: Clones share the cache
+    let clone = tree.clone();
+    assert_eq!(Some(value.as_bytes().into()), clone.get("a", SeqNo::MAX)?);
+    assert_eq!(1, blob_cache.len());
+
+    Ok(())
+}
+
+#[test]
+fn blob_cache_default() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let block_cache = Arc::new(Cache::with_capacity_bytes(1_024 * 1_024));
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_cache(block_cache.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    tree.insert("a", "a".repeat(10_000), 0);
+    tree.flush_active_memtable(0)?;
+
+    let block_cache_size = block_cache.size();
+    assert!(tree.get("a", SeqNo::MAX)?.is_some());
+
+    // NOTE: Without a dedicated blob cache, blobs are cached in the block cache
+    assert!(block_cache.size() - block_cache_size >= 10_000);
+
+    Ok(())
+}
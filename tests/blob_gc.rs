@@ -0,0 +1,116 @@
+use lsm_tree::{
+    config::BlockSizePolicy, AbstractTree, AnyTree, Config, KvSeparationOptions, SeqNo,
+    SequenceNumberCounter,
+};
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+/// Writes all keys, then overwrites half of them, so the first blob file is 50% stale.
+fn fragmented_tree(folder: &std::path::Path) -> lsm_tree::Result<lsm_tree::BlobTree> {
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(folder, seqno.clone())
+        .data_block_size_policy(BlockSizePolicy::all(1_024))
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().compression(lsm_tree::CompressionType::None),
+        ))
+        .open()?;
+
+    let AnyTree::Blob(tree) = tree else {
+        panic!("should be a blob tree");
+    };
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(format!("{x:0>3}"), "a".repeat(2_000), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in 0..(ITEM_COUNT / 2) {
+        tree.insert(format!("{x:0>3}"), "b".repeat(2_000), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Only updates the GC stats, the age cutoff keeps the blob file from being relocated
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(2, tree.blob_file_count());
+    assert!(tree.stale_blob_bytes() > 0);
+
+    Ok(tree)
+}
+
+fn assert_values(tree: &lsm_tree::BlobTree) -> lsm_tree::Result<()> {
+    for x in 0..ITEM_COUNT {
+        let expected = if x < ITEM_COUNT / 2 { "b" } else { "a" };
+
+        assert_eq!(
+            Some(expected.repeat(2_000).as_bytes().into()),
+            tree.get(format!("{x:0>3}"), SeqNo::MAX)?,
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_gc_staleness_threshold() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = fragmented_tree(folder.path())?;
+
+    let stale_bytes = tree.stale_blob_bytes();
+
+    tree.gc_with_staleness_threshold(0.6, SeqNo::MAX)?;
+    assert_eq!(stale_bytes, tree.stale_blob_bytes());
+    assert_eq!(2, tree.blob_file_count());
+
+    tree.gc_with_staleness_threshold(0.4, SeqNo::MAX)?;
+    assert_eq!(0, tree.stale_blob_bytes());
+    assert_eq!(2, tree.blob_file_count());
+    assert_values(&tree)?;
+
+    assert!(matches!(
+        tree.gc_with_staleness_threshold(2.0, SeqNo::MAX),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn blob_gc_space_amp() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = fragmented_tree(folder.path())?;
+
+    let stale_bytes = tree.stale_blob_bytes();
+
+    // NOTE: 150 blobs on disk, 100 of them live
+    tree.gc(1.6, SeqNo::MAX)?;
+    assert_eq!(stale_bytes, tree.stale_blob_bytes());
+
+    tree.gc(1.2, SeqNo::MAX)?;
+    assert_eq!(0, tree.stale_blob_bytes());
+    assert_values(&tree)?;
+
+    assert!(matches!(
+        tree.gc(0.5, SeqNo::MAX),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn blob_gc_multiple_tables() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = fragmented_tree(folder.path())?;
+
+    // NOTE: Split the index into multiple tables, which all reference the stale blob file
+    tree.major_compact(1, SeqNo::MAX)?;
+    assert!(tree.table_count() > 1);
+
+    tree.gc_with_staleness_threshold(0.4, SeqNo::MAX)?;
+    assert_eq!(0, tree.stale_blob_bytes());
+    assert_values(&tree)?;
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn blob_tree_batch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = b"neptune!".repeat(16_000);
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(Default::default()))
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    tree.insert("old", "a", seqno.next());
+
+    let mut batch = tree.batch();
+    for x in 0..10u8 {
+        batch.insert([b'b', x], big_value.clone());
+    }
+    batch.insert("meta", "small");
+    batch.remove("old");
+    assert_eq!(12, batch.len());
+
+    let batch_seqno = seqno.next();
+    batch.commit(batch_seqno)?;
+
+    for _ in 0..2 {
+        // NOTE: None of the batch is visible before its sequence number...
+        assert!(tree.contains_key("old", batch_seqno)?);
+        assert!(!tree.contains_key("meta", batch_seqno)?);
+        assert_eq!(1, tree.len(batch_seqno, None)?);
+
+        // ...and all of it after
+        assert!(!tree.contains_key("old", batch_seqno + 1)?);
+        assert_eq!(11, tree.len(batch_seqno + 1, None)?);
+        assert_eq!(
+            big_value,
+            &*tree.get([b'b', 9], SeqNo::MAX)?.expect("should exist"),
+        );
+
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.blob_file_count());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_batch_journal() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(Default::default()))
+        .use_journal(true)
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    let mut batch = tree.batch();
+    batch.insert("a", "a".repeat(10_000));
+    batch.insert("b", "b");
+    batch.commit(seqno.next())?;
+
+    let events = tree.cdc_reader(0)?.collect::<lsm_tree::Result<Vec<_>>>()?;
+
+    assert_eq!(2, events.len());
+    assert!(events.iter().all(|event| event.seqno == 0));
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use lsm_tree::{
+    AbstractTree, AnyTree, BlobTree, Config, KvSeparationOptions, SequenceNumberCounter,
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use test_log::test;
+
+const WRITERS: u64 = 4;
+const ITEMS_PER_WRITER: u64 = 2_000;
+
+fn assert_handle<T: Clone + Send + Sync + 'static>() {}
+
+fn value_of(key: u64) -> Vec<u8> {
+    key.to_be_bytes().repeat(20)
+}
+
+#[test]
+fn blob_tree_concurrent_read_write_flush() -> lsm_tree::Result<()> {
+    assert_handle::<BlobTree>();
+
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let AnyTree::Blob(tree) = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().separation_threshold(100),
+        ))
+        .open()?
+    else {
+        unreachable!();
+    };
+
+    let done = Arc::new(AtomicBool::new(false));
+
+    let writers = (0..WRITERS)
+        .map(|writer| {
+            let tree = tree.clone();
+            let seqno = seqno.clone();
+
+            std::thread::spawn(move || {
+                for x in 0..ITEMS_PER_WRITER {
+                    let key = writer * ITEMS_PER_WRITER + x;
+                    tree.insert(key.to_be_bytes(), value_of(key), seqno.next());
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let readers = (0..2)
+        .map(|_| {
+            let tree = tree.clone();
+            let seqno = seqno.clone();
+            let done = done.clone();
+
+            std::thread::spawn(move || -> lsm_tree::Result<()> {
+                while !done.load(Ordering::Acquire) {
+                    for key in (0..WRITERS * ITEMS_PER_WRITER).step_by(97) {
+                        if let Some(value) = tree.get(key.to_be_bytes(), seqno.get())? {
+                            assert_eq!(&*value, value_of(key));
+                        }
+                    }
+                }
+                Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let flusher = {
+        let tree = tree.clone();
+        let done = done.clone();
+
+        std::thread::spawn(move || -> lsm_tree::Result<()> {
+            while !done.load(Ordering::Acquire) {
+                tree.flush_active_memtable(0)?;
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+            Ok(())
+        })
+    };
+
+    for writer in writers {
+        writer.join().expect("writer should not panic");
+    }
+
+    done.store(true, Ordering::Release);
+
+    for reader in readers {
+        reader.join().expect("reader should not panic")?;
+    }
+    flusher.join().expect("flusher should not panic")?;
+
+    tree.flush_active_memtable(0)?;
+    assert!(tree.blob_file_count() > 0);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    assert_eq!(
+        WRITERS * ITEMS_PER_WRITER,
+        tree.len(seqno.get(), None)? as u64,
+    );
+
+    for key in 0..WRITERS * ITEMS_PER_WRITER {
+        assert_eq!(
+            Some(value_of(key).into()),
+            tree.get(key.to_be_bytes(), seqno.get())?,
+        );
+    }
+
+    Ok(())
+}
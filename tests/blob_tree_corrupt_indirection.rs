@@ -0,0 +1,43 @@
+use lsm_tree::{AbstractTree, Config, InternalValue, SeqNo, SequenceNumberCounter, ValueType};
+use test_log::test;
+
+#[test]
+fn blob_tree_dangling_indirection() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(Default::default()))
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    tree.insert("a", "a", 0);
+
+    // NOTE: [offset=0] [blob file=99] [on-disk size=10] [size=10], but there is no blob file #99
+    tree.index.append_entry(InternalValue::from_components(
+        "b",
+        [0, 99, 10, 10],
+        1,
+        ValueType::Indirection,
+    ))?;
+
+    // NOTE: Truncated indirection
+    tree.index.append_entry(InternalValue::from_components(
+        "c",
+        [0],
+        2,
+        ValueType::Indirection,
+    ))?;
+
+    assert!(matches!(
+        tree.get("b", SeqNo::MAX),
+        Err(lsm_tree::Error::Corrupted(_)),
+    ));
+    assert!(tree.get("c", SeqNo::MAX).is_err());
+
+    // NOTE: Other keys are still readable
+    assert_eq!(b"a", &*tree.get("a", SeqNo::MAX)?.expect("should exist"));
+
+    Ok(())
+}
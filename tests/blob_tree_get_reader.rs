@@ -0,0 +1,155 @@
+use lsm_tree::{
+    AbstractTree, CompressionType, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter,
+};
+use std::io::Read;
+use test_log::test;
+
+fn value() -> Vec<u8> {
+    (0..500_000u32).map(|x| (x % 251) as u8).collect()
+}
+
+#[test]
+fn blob_tree_get_reader() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let value = value();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().compression(CompressionType::None),
+        ))
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    tree.insert("big", &value, seqno.next());
+    tree.insert("small", "abc", seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+
+    let mut reader = tree.get_reader("big", SeqNo::MAX)?.expect("should exist");
+    let mut buf = vec![0; 1_000];
+    reader.read_exact(&mut buf)?;
+    assert_eq!(&value[..1_000], &buf);
+
+    let mut rest = vec![];
+    reader.read_to_end(&mut rest)?;
+    assert_eq!(&value[1_000..], &rest);
+
+    let mut small = vec![];
+    tree.get_reader("small", SeqNo::MAX)?
+        .expect("should exist")
+        .read_to_end(&mut small)?;
+    assert_eq!(b"abc", &*small);
+
+    assert!(tree.get_reader("missing", SeqNo::MAX)?.is_none());
+
+    assert_eq!(
+        &value[100..200],
+        &*tree
+            .get_range("big", SeqNo::MAX, 100, 100)?
+            .expect("should exist"),
+    );
+    assert_eq!(
+        &value[499_990..],
+        &*tree
+            .get_range("big", SeqNo::MAX, 499_990, 100)?
+            .expect("should exist"),
+    );
+    assert!(tree
+        .get_range("big", SeqNo::MAX, 1_000_000, 100)?
+        .expect("should exist")
+        .is_empty());
+    assert_eq!(
+        b"bc",
+        &*tree
+            .get_range("small", SeqNo::MAX, 1, 100)?
+            .expect("should exist"),
+    );
+    assert!(tree.get_range("missing", SeqNo::MAX, 0, 100)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_get_reader_checksum() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let value = value();
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .with_kv_separation(Some(
+                KvSeparationOptions::default().compression(CompressionType::None),
+            ))
+            .open()?;
+
+        tree.insert("big", &value, seqno.next());
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Corrupt a byte in the middle of the blob
+    let blob_file_path = folder.path().join("blobs").join("0");
+    let mut bytes = std::fs::read(&blob_file_path)?;
+    bytes[250_000] ^= 0xFF;
+    std::fs::write(&blob_file_path, bytes)?;
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().compression(CompressionType::None),
+        ))
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    let mut reader = tree.get_reader("big", SeqNo::MAX)?.expect("should exist");
+    let mut buf = vec![0; 1_000];
+    reader.read_exact(&mut buf)?;
+
+    let mut rest = vec![];
+    let err = reader
+        .read_to_end(&mut rest)
+        .expect_err("should be corrupted");
+    assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+
+    assert!(tree.get("big", SeqNo::MAX).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "lz4")]
+fn blob_tree_get_reader_lz4() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let value = value();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().compression(CompressionType::Lz4),
+        ))
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    tree.insert("big", &value, seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let mut buf = vec![];
+    tree.get_reader("big", SeqNo::MAX)?
+        .expect("should exist")
+        .read_to_end(&mut buf)?;
+    assert_eq!(value, buf);
+
+    assert_eq!(
+        &value[100..200],
+        &*tree
+            .get_range("big", SeqNo::MAX, 100, 100)?
+            .expect("should exist"),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use std::path::Path;
+use test_log::test;
+
+fn remove_files(folder: &Path) -> lsm_tree::Result<()> {
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() {
+            remove_files(&entry.path())?;
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_keys_skip_blob_files() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .with_kv_separation(Some(KvSeparationOptions::default()))
+            .open()?;
+
+        for x in 0..10u64 {
+            tree.insert(x.to_be_bytes(), "a".repeat(10_000), seqno.next());
+        }
+        tree.remove(5u64.to_be_bytes(), seqno.next());
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.blob_file_count());
+    }
+
+    // NOTE: Reopen to make sure no blob file is cached
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default()))
+        .open()?;
+
+    remove_files(&folder.path().join("blobs"))?;
+
+    let keys = tree
+        .keys(SeqNo::MAX, None)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(9, keys.len());
+    assert_eq!(&9u64.to_be_bytes(), &*keys[8]);
+
+    let keys = tree
+        .range_keys(2u64.to_be_bytes()..7u64.to_be_bytes(), SeqNo::MAX, None)
+        .rev()
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(4, keys.len());
+    assert_eq!(&6u64.to_be_bytes(), &*keys[0]);
+
+    // NOTE: Existence checks do not read from blob files either
+    assert!(tree.contains_key(0u64.to_be_bytes(), SeqNo::MAX)?);
+    assert!(!tree.contains_key(5u64.to_be_bytes(), SeqNo::MAX)?);
+
+    assert!(tree.get(0u64.to_be_bytes(), SeqNo::MAX).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use lsm_tree::{
+    AbstractTree, CompressionType, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter,
+};
+use test_log::test;
+
+#[test]
+fn blob_tree_remove_stale_bytes() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let value = "a".repeat(10_000);
+    let new_value = "b".repeat(10_000);
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            // NOTE: Stale bytes are counted on disk, so they depend on compression
+            KvSeparationOptions::default().compression(CompressionType::None),
+        ))
+        .open()?;
+
+    for x in 0..10u64 {
+        tree.insert(x.to_be_bytes(), &value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(0, tree.stale_blob_bytes());
+
+    for x in 0..3u64 {
+        tree.remove(x.to_be_bytes(), seqno.next());
+    }
+    for x in 3..6u64 {
+        tree.insert(x.to_be_bytes(), &new_value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.blob_file_count());
+
+    // NOTE: Tombstones and overwrites are only known to shadow a blob
+    // once compaction merges them with the older indirection
+    assert_eq!(0, tree.stale_blob_bytes());
+
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(6 * value.len() as u64, tree.stale_blob_bytes());
+    assert_eq!(2, tree.blob_file_count());
+
+    assert_eq!(7, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_remove_all_drops_blob_file() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let value = "a".repeat(10_000);
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().compression(CompressionType::None),
+        ))
+        .open()?;
+
+    for x in 0..10u64 {
+        tree.insert(x.to_be_bytes(), &value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in 0..5u64 {
+        tree.remove(x.to_be_bytes(), seqno.next());
+    }
+    for x in 5..10u64 {
+        tree.insert(x.to_be_bytes(), "small", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+
+    // NOTE: The first compaction marks the blob file as stale, the second one drops it
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(10 * value.len() as u64, tree.stale_blob_bytes());
+
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(0, tree.blob_file_count());
+    assert_eq!(0, tree.stale_blob_bytes());
+
+    assert_eq!(5, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
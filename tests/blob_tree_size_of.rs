@@ -0,0 +1,60 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn blob_tree_size_of() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .with_kv_separation(Some(
+                KvSeparationOptions::default().separation_threshold(1_000),
+            ))
+            .open()?;
+
+        tree.insert("big", "a".repeat(10_000), seqno.next());
+        tree.insert("small", "a".repeat(100), seqno.next());
+        tree.insert("gone", "a".repeat(10_000), seqno.next());
+        tree.remove("gone", seqno.next());
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.blob_file_count());
+    }
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().separation_threshold(1_000),
+        ))
+        .open()?;
+
+    // NOTE: Sizes are read from the index tree, so blob files are not needed
+    for entry in walkdir(&folder.path().join("blobs"))? {
+        std::fs::remove_file(entry)?;
+    }
+
+    assert_eq!(Some(10_000), tree.size_of("big", SeqNo::MAX)?);
+    assert_eq!(Some(100), tree.size_of("small", SeqNo::MAX)?);
+    assert_eq!(None, tree.size_of("gone", SeqNo::MAX)?);
+    assert_eq!(Some(10_000), tree.size_of("gone", 3)?);
+    assert_eq!(None, tree.size_of("missing", SeqNo::MAX)?);
+
+    assert!(tree.get("big", SeqNo::MAX).is_err());
+
+    Ok(())
+}
+
+fn walkdir(folder: &std::path::Path) -> lsm_tree::Result<Vec<std::path::PathBuf>> {
+    let mut files = vec![];
+
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() {
+            files.extend(walkdir(&entry.path())?);
+        } else {
+            files.push(entry.path());
+        }
+    }
+
+    Ok(files)
+}
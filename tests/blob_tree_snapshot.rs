@@ -0,0 +1,78 @@
+use lsm_tree::{AbstractTree, Config, Guard, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use std::path::Path;
+use test_log::test;
+
+fn count_files(folder: &Path) -> lsm_tree::Result<usize> {
+    let mut count = 0;
+
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() {
+            count += count_files(&entry.path())?;
+        } else {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+#[test]
+fn blob_tree_snapshot_pins_blob_files() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let blobs_folder = folder.path().join("blobs");
+    let seqno = SequenceNumberCounter::default();
+
+    let old_value = b"neptune!".repeat(16_000);
+    let new_value = b"winter!".repeat(16_000);
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default()))
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    tree.insert("a", &old_value, seqno.next());
+    tree.insert("b", &old_value, seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.snapshot(seqno.get());
+
+    tree.insert("a", &new_value, seqno.next());
+    tree.remove("b", seqno.next());
+    tree.insert("c", &new_value, seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.blob_file_count());
+
+    // NOTE: The first compaction marks the old blob file as stale, the second one drops it
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(1, tree.blob_file_count());
+    assert_eq!(2, count_files(&blobs_folder)?);
+
+    assert_eq!(old_value, &*snapshot.get("a")?.expect("should exist"));
+    assert_eq!(old_value, &*snapshot.get("b")?.expect("should exist"));
+    assert!(!snapshot.contains_key("c")?);
+
+    let items = snapshot
+        .iter()
+        .map(Guard::into_inner)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(2, items.len());
+    assert!(items.iter().all(|(_, v)| *v == old_value));
+    assert_eq!(1, snapshot.prefix("b").count());
+    assert_eq!(1, snapshot.range("a".."b").rev().count());
+
+    assert_eq!(
+        new_value,
+        &*tree.get("a", SeqNo::MAX)?.expect("should exist")
+    );
+    assert!(!tree.contains_key("b", SeqNo::MAX)?);
+
+    drop(snapshot);
+    assert_eq!(1, count_files(&blobs_folder)?);
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use lsm_tree::{
+    AbstractTree, CompressionType, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter,
+};
+use test_log::test;
+
+#[test]
+fn blob_tree_stats() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let value = "a".repeat(10_000);
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().compression(CompressionType::None),
+        ))
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    assert_eq!(lsm_tree::BlobTreeStats::default(), tree.stats());
+
+    for x in 0..10u64 {
+        tree.insert(x.to_be_bytes(), &value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    let stats = tree.stats();
+    assert_eq!(1, stats.blob_file_count);
+    assert_eq!(0, stats.stale_blob_bytes);
+    assert!(stats.stale_ratio() < f32::EPSILON);
+    assert!(stats.index_bytes > 0);
+    assert_eq!(tree.disk_space(), stats.disk_space());
+
+    for x in 0..5u64 {
+        tree.insert(x.to_be_bytes(), &value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+
+    let stats = tree.stats();
+    assert_eq!(2, stats.blob_file_count);
+    assert_eq!(tree.blob_file_count(), stats.blob_file_count);
+    assert_eq!(tree.stale_blob_bytes(), stats.stale_blob_bytes);
+    assert!(stats.stale_blob_bytes >= 5 * value.len() as u64);
+    assert!(stats.live_blob_bytes() >= 10 * value.len() as u64);
+    assert!(stats.stale_ratio() > 0.3 && stats.stale_ratio() < 0.4);
+    assert!(stats.space_amp() > 1.4 && stats.space_amp() < 1.6);
+    assert_eq!(tree.disk_space(), stats.disk_space());
+
+    tree.gc(1.0, SeqNo::MAX)?;
+
+    let stats = tree.stats();
+    assert_eq!(0, stats.stale_blob_bytes);
+    assert!(stats.space_amp() < 1.01);
+
+    Ok(())
+}
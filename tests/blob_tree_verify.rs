@@ -0,0 +1,90 @@
+use lsm_tree::{
+    AbstractTree, CompressionType, Config, InternalValue, KvSeparationOptions,
+    SequenceNumberCounter, ValueType,
+};
+use test_log::test;
+
+#[test]
+fn blob_tree_verify() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().compression(CompressionType::None),
+        ))
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    for x in 0..10u64 {
+        tree.insert(x.to_be_bytes(), x.to_string().repeat(10_000), seqno.next());
+    }
+    tree.insert("small", "abc", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let report = tree.verify()?;
+    assert!(report.is_ok());
+    assert_eq!(10, report.checked_blob_count);
+
+    let dangling_seqno = seqno.next();
+
+    // NOTE: [offset=0] [blob file=99] [on-disk size=10] [size=10], but there is no blob file #99
+    tree.index.append_entry(InternalValue::from_components(
+        "dangling",
+        [0, 99, 10, 10],
+        dangling_seqno,
+        ValueType::Indirection,
+    ))?;
+
+    let orphan_path = folder.path().join("blobs").join("999");
+    std::fs::File::create(&orphan_path)?;
+
+    let report = tree.verify()?;
+    assert!(!report.is_ok());
+    assert_eq!(10, report.checked_blob_count);
+    assert_eq!(
+        vec![(b"dangling".as_slice().into(), dangling_seqno)],
+        report.dangling_handles
+    );
+    assert!(report.corrupted_blobs.is_empty());
+    assert_eq!(vec![orphan_path], report.orphaned_blob_files);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_verify_corrupted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let value = "a".repeat(10_000);
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().compression(CompressionType::None),
+        ))
+        .open()?;
+    let lsm_tree::AnyTree::Blob(tree) = tree else {
+        panic!("should be blob tree");
+    };
+
+    tree.insert("a", &value, seqno.next());
+    tree.insert("b", &value, seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Corrupt the second blob
+    let blob_file_path = folder.path().join("blobs").join("0");
+    let mut bytes = std::fs::read(&blob_file_path)?;
+    bytes[15_000] ^= 0xFF;
+    std::fs::write(&blob_file_path, bytes)?;
+
+    let report = tree.verify()?;
+    assert_eq!(2, report.checked_blob_count);
+    assert_eq!(vec![(b"b".as_slice().into(), 1)], report.corrupted_blobs);
+    assert!(report.dangling_handles.is_empty());
+    assert!(report.orphaned_blob_files.is_empty());
+
+    Ok(())
+}
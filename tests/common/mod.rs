@@ -0,0 +1,25 @@
+use std::path::Path;
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let dest = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &dest)?;
+        } else {
+            std::fs::copy(entry.path(), dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies a fixture into a temporary folder, so opening it does not modify the fixture
+pub fn copy_fixture(name: &str) -> lsm_tree::Result<tempfile::TempDir> {
+    let folder = tempfile::tempdir()?;
+    copy_dir(&Path::new("test_fixture").join(name), folder.path())?;
+    Ok(folder)
+}
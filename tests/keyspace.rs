@@ -0,0 +1,321 @@
+use lsm_tree::{keyspace::Keyspace, AbstractTree, Config, Durability, SequenceNumberCounter};
+use std::{sync::Arc, time::Duration};
+use test_log::test;
+
+fn config(path: &std::path::Path) -> Config {
+    Config::new(path, SequenceNumberCounter::default())
+}
+
+#[test]
+fn keyspace_batch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let keyspace = Keyspace::open(config(folder.path()))?;
+
+    let a = keyspace.open_partition("a")?;
+    let b = keyspace.open_partition("b")?;
+
+    let mut batch = keyspace.batch();
+    batch.insert(&a, "x", "1");
+    batch.insert(&b, "y", "2");
+    batch.remove(&a, "z");
+    assert_eq!(3, batch.len());
+
+    // NOTE: Nothing is visible before commit
+    assert!(!a.contains_key("x")?);
+    assert!(!b.contains_key("y")?);
+
+    let instant = keyspace.instant();
+    batch.commit()?;
+    assert_eq!(instant + 1, keyspace.instant());
+
+    assert_eq!(Some("1".as_bytes().into()), a.get("x")?);
+    assert_eq!(Some("2".as_bytes().into()), b.get("y")?);
+
+    // NOTE: All operations of a batch share a sequence number
+    assert_eq!(a.tree().get_highest_seqno(), b.tree().get_highest_seqno());
+
+    // NOTE: Partitions stay separate
+    assert!(!a.contains_key("y")?);
+    assert!(!b.contains_key("x")?);
+
+    a.remove("x")?;
+    assert!(!a.contains_key("x")?);
+
+    Ok(())
+}
+
+#[test]
+fn keyspace_batch_savepoints() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let keyspace = Keyspace::open(config(folder.path()))?;
+
+    let a = keyspace.open_partition("a")?;
+
+    let mut batch = keyspace.batch();
+    assert!(!batch.rollback_to_savepoint());
+
+    batch.insert(&a, "x", "1");
+    let size = batch.size();
+    assert_eq!(2, size);
+
+    batch.set_savepoint();
+    batch.insert(&a, "y", "2");
+
+    batch.set_savepoint();
+    batch.insert(&a, "z", "3");
+    batch.remove(&a, "x");
+    assert_eq!(4, batch.len());
+    assert_eq!(2, batch.savepoint_count());
+
+    // NOTE: Only undoes the operations after the latest savepoint
+    assert!(batch.rollback_to_savepoint());
+    assert_eq!(2, batch.len());
+    assert_eq!(1, batch.savepoint_count());
+
+    assert!(batch.pop_savepoint());
+    assert!(!batch.rollback_to_savepoint());
+    assert_eq!(2, batch.len());
+
+    batch.set_savepoint();
+    batch.insert(&a, "w", "4");
+    assert!(batch.rollback_to_savepoint());
+    assert_eq!(2, batch.len());
+    assert_eq!(4, batch.size());
+
+    batch.commit()?;
+
+    assert!(a.contains_key("x")?);
+    assert!(a.contains_key("y")?);
+    assert!(!a.contains_key("z")?);
+    assert!(!a.contains_key("w")?);
+
+    Ok(())
+}
+
+#[test]
+fn keyspace_recover() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let keyspace = Keyspace::open(config(folder.path()))?;
+        let a = keyspace.open_partition("a")?;
+        let b = keyspace.open_partition("b")?;
+
+        for x in 0..100u64 {
+            let mut batch = keyspace.batch();
+            batch.insert(&a, x.to_be_bytes(), "a");
+            batch.insert(&b, x.to_be_bytes(), "b");
+            batch.commit()?;
+        }
+
+        // NOTE: Some data is flushed, some is only in the journal
+        a.tree().flush_active_memtable(0)?;
+        a.insert("after_flush", "a")?;
+        b.remove(0u64.to_be_bytes())?;
+
+        keyspace.persist()?;
+    }
+
+    {
+        let keyspace = Keyspace::open(config(folder.path()))?;
+        assert_eq!(vec!["a", "b"], keyspace.partition_names());
+
+        let a = keyspace.open_partition("a")?;
+        assert_eq!(101, a.tree().len(keyspace.instant(), None)?);
+        assert!(a.contains_key("after_flush")?);
+
+        let b = keyspace.open_partition("b")?;
+        assert_eq!(99, b.tree().len(keyspace.instant(), None)?);
+        assert!(!b.contains_key(0u64.to_be_bytes())?);
+
+        // NOTE: New writes are ordered after all recovered writes
+        a.insert("after_flush", "new")?;
+        assert_eq!(Some("new".as_bytes().into()), a.get("after_flush")?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn keyspace_recover_sync_interval() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let keyspace =
+            Keyspace::open(config(folder.path()).durability(Durability::SyncInterval(10)))?;
+        let a = keyspace.open_partition("a")?;
+        a.insert("a", "a")?;
+
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let keyspace = Keyspace::open(config(folder.path()))?;
+    let a = keyspace.open_partition("a")?;
+    assert_eq!(Some("a".as_bytes().into()), a.get("a")?);
+
+    Ok(())
+}
+
+#[test]
+fn keyspace_batch_torn_write() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let keyspace = Keyspace::open(config(folder.path()))?;
+        let a = keyspace.open_partition("a")?;
+        let b = keyspace.open_partition("b")?;
+
+        for x in 0..2u64 {
+            let mut batch = keyspace.batch();
+            batch.insert(&a, x.to_be_bytes(), "a");
+            batch.insert(&b, x.to_be_bytes(), "b");
+            batch.commit()?;
+        }
+
+        keyspace.persist()?;
+    }
+
+    // NOTE: Simulate a crash in the middle of writing the last batch
+    let journal_file = std::fs::read_dir(folder.path().join("journal"))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?
+        .into_iter()
+        .max()
+        .expect("should have journal file");
+
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(&journal_file)?;
+    file.set_len(file.metadata()?.len() - 5)?;
+    drop(file);
+
+    {
+        let keyspace = Keyspace::open(config(folder.path()))?;
+        let a = keyspace.open_partition("a")?;
+        let b = keyspace.open_partition("b")?;
+
+        // NOTE: The torn batch is dropped from both partitions
+        assert!(a.contains_key(0u64.to_be_bytes())?);
+        assert!(b.contains_key(0u64.to_be_bytes())?);
+        assert!(!a.contains_key(1u64.to_be_bytes())?);
+        assert!(!b.contains_key(1u64.to_be_bytes())?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn keyspace_background_flush() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let keyspace = Keyspace::open(config(folder.path()).max_memtable_size(4_096))?;
+        let a = keyspace.open_partition("a")?;
+
+        for x in 0..1_000u64 {
+            a.insert(x.to_be_bytes(), "a".repeat(100))?;
+        }
+
+        let mut waited = Duration::ZERO;
+        while a.tree().active_memtable_size() >= 4_096 || a.tree().sealed_memtable_count() > 0 {
+            assert!(
+                waited < Duration::from_secs(10),
+                "background flush timed out"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+            waited += Duration::from_millis(10);
+        }
+
+        assert!(a.tree().table_count() > 0);
+        assert_eq!(1_000, a.tree().len(keyspace.instant(), None)?);
+
+        // NOTE: Flushed journal files are deleted
+        let journal_files = std::fs::read_dir(folder.path().join("journal"))?.count();
+        assert!(journal_files < 10, "{journal_files} journal files left");
+    }
+
+    {
+        let keyspace = Keyspace::open(config(folder.path()))?;
+        let a = keyspace.open_partition("a")?;
+        assert_eq!(1_000, a.tree().len(keyspace.instant(), None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn keyspace_shared_resources() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let keyspace = Keyspace::open(config(folder.path()).cache_size(1_024 * 1_024))?;
+
+    let a = keyspace.open_partition("a")?;
+    let b = keyspace.open_partition_with("b", |config| config.cache_size(1))?;
+
+    assert!(Arc::ptr_eq(
+        &a.tree().tree_config().cache,
+        &b.tree().tree_config().cache,
+    ));
+    assert!(b.tree().tree_config().path.starts_with(folder.path()));
+
+    // NOTE: Opening a partition again returns the same partition
+    let a2 = keyspace.open_partition("a")?;
+    a.insert("x", "y")?;
+    assert!(a2.contains_key("x")?);
+
+    assert!(matches!(
+        keyspace.open_partition("../escape"),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+    assert!(matches!(
+        keyspace.open_partition(""),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+
+    assert!(matches!(
+        Keyspace::open(config(folder.path())),
+        Err(lsm_tree::Error::AlreadyLocked),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn keyspace_group_commit() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let keyspace =
+            Keyspace::open(config(folder.path()).durability(Durability::SyncEveryWrite))?;
+        let a = keyspace.open_partition("a")?;
+
+        std::thread::scope(|scope| {
+            let handles = (0..4u32)
+                .map(|thread| {
+                    let keyspace = &keyspace;
+                    let a = &a;
+
+                    scope.spawn(move || -> lsm_tree::Result<()> {
+                        for i in 0..100u32 {
+                            let mut batch = keyspace.batch();
+                            batch.insert(a, format!("{thread}-{i:0>3}"), "a");
+                            batch.commit()?;
+                        }
+                        Ok(())
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .try_for_each(|handle| handle.join().expect("thread should not panic"))
+        })?;
+
+        assert_eq!(400, a.tree().len(keyspace.instant(), None)?);
+    }
+
+    let keyspace = Keyspace::open(config(folder.path()))?;
+    let a = keyspace.open_partition("a")?;
+    assert_eq!(400, a.tree().len(keyspace.instant(), None)?);
+
+    Ok(())
+}
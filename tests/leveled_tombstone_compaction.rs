@@ -0,0 +1,85 @@
+use lsm_tree::{compaction::Leveled, AbstractTree, AnyTree, Config, SeqNo, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+/// Writes a last level table that consists of ~47% tombstones
+fn fill(tree: &AnyTree, seqno: &SequenceNumberCounter) -> lsm_tree::Result<()> {
+    for i in 0..100u32 {
+        tree.insert(format!("{i:0>3}"), "v", seqno.next());
+    }
+    for i in 10..100u32 {
+        tree.remove(format!("{i:0>3}"), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Tombstones and the values they delete are kept because of the seqno threshold
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.level_table_count(6).unwrap_or_default());
+    assert_eq!(190, tree.approximate_len());
+    assert_eq!(90, tree.tombstone_count());
+
+    Ok(())
+}
+
+#[test]
+fn leveled_tombstone_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    fill(&tree, &seqno)?;
+
+    let compaction = Arc::new(Leveled::default().with_tombstone_compaction_threshold(Some(0.4)));
+    tree.compact(compaction.clone(), SeqNo::MAX)?;
+
+    assert_eq!(1, tree.level_table_count(6).unwrap_or_default());
+    assert_eq!(0, tree.tombstone_count());
+    assert_eq!(10, tree.approximate_len());
+    assert_eq!(10, tree.len(SeqNo::MAX, None)?);
+
+    // NOTE: There are no tombstones left, so the table is not compacted again
+    let tables = tree.tables();
+    tree.compact(compaction, SeqNo::MAX)?;
+    assert_eq!(tables, tree.tables());
+
+    Ok(())
+}
+
+#[test]
+fn leveled_tombstone_compaction_below_threshold() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    fill(&tree, &seqno)?;
+
+    tree.compact(Arc::new(Leveled::default()), SeqNo::MAX)?;
+    assert_eq!(90, tree.tombstone_count());
+
+    tree.compact(
+        Arc::new(Leveled::default().with_tombstone_compaction_threshold(Some(0.5))),
+        SeqNo::MAX,
+    )?;
+    assert_eq!(90, tree.tombstone_count());
+
+    Ok(())
+}
+
+#[test]
+fn leveled_tombstone_compaction_run_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .compaction_strategy(Arc::new(
+            Leveled::default().with_tombstone_compaction_threshold(Some(0.4)),
+        ))
+        .open()?;
+    fill(&tree, &seqno)?;
+
+    tree.run_compaction(SeqNo::MAX)?;
+    assert_eq!(0, tree.tombstone_count());
+    assert_eq!(10, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
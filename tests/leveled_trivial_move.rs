@@ -53,3 +53,43 @@ fn leveled_trivial_move_into_l1() -> lsm_tree::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn leveled_trivial_move_partial_l0() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(folder, SequenceNumberCounter::default()).open()?;
+
+    let compaction = Arc::new(lsm_tree::compaction::Leveled::default());
+
+    tree.insert("m", "m", 0);
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.level_table_count(6).unwrap_or_default());
+
+    // NOTE: L0 spans the L6 table, but none of these tables overlap it
+    for (lo, hi) in [("a", "b"), ("c", "d"), ("n", "o"), ("x", "z")] {
+        tree.insert(lo, lo, 0);
+        tree.insert(hi, hi, 0);
+        tree.flush_active_memtable(0)?;
+    }
+    let movable_table_ids = tree.tables().iter().map(|t| t.id).collect::<Vec<_>>();
+
+    // NOTE: Overlaps the L6 table, so it has to be merged
+    tree.insert("l", "l", 0);
+    tree.insert("mm", "mm", 0);
+    tree.flush_active_memtable(0)?;
+
+    tree.compact(compaction.clone(), 0)?;
+    assert_eq!(1, tree.level_table_count(0).unwrap_or_default());
+    assert_eq!(5, tree.level_table_count(6).unwrap_or_default());
+
+    // NOTE: The tables were moved, not rewritten
+    assert!(tree
+        .tables()
+        .iter()
+        .filter(|t| t.level == 6)
+        .all(|t| movable_table_ids.contains(&t.id)));
+
+    Ok(())
+}
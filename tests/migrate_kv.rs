@@ -0,0 +1,87 @@
+use lsm_tree::{migrate::kv::import, AbstractTree, AnyTree, Config, SequenceNumberCounter};
+use std::collections::BTreeMap;
+use test_log::test;
+
+fn open_tree(path: &std::path::Path) -> lsm_tree::Result<lsm_tree::Tree> {
+    let AnyTree::Standard(tree) = Config::new(path, SequenceNumberCounter::default()).open()?
+    else {
+        unreachable!();
+    };
+    Ok(tree)
+}
+
+#[test]
+fn migrate_kv_import() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open_tree(folder.path())?;
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+
+    // NOTE: Stands in for a sled or redb database, which iterate in key order
+    let source = (0..10_000u32)
+        .map(|x| (x.to_be_bytes().to_vec(), x.to_string()))
+        .collect::<BTreeMap<_, _>>();
+
+    let count = import(
+        &tree,
+        source.iter().map(Ok::<_, std::io::Error>),
+        &seqno,
+        &visible_seqno,
+    )?;
+    assert_eq!(10_000, count);
+
+    assert_eq!(1, seqno.get());
+    assert_eq!(1, visible_seqno.get());
+
+    assert_eq!(10_000, tree.len(visible_seqno.get(), None)?);
+    assert_eq!(
+        Some("1234".as_bytes().into()),
+        tree.get(1_234u32.to_be_bytes(), visible_seqno.get())?,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn migrate_kv_import_source_error() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open_tree(folder.path())?;
+
+    let source = vec![
+        Ok((b"a".to_vec(), b"abc".to_vec())),
+        Err(std::io::Error::other("source is corrupted")),
+    ];
+
+    let result = import(
+        &tree,
+        source,
+        &SequenceNumberCounter::default(),
+        &SequenceNumberCounter::default(),
+    );
+    assert!(matches!(result, Err(lsm_tree::Error::Io(_))));
+    assert_eq!(0, tree.table_count());
+
+    Ok(())
+}
+
+#[test]
+fn migrate_kv_import_unsorted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open_tree(folder.path())?;
+
+    let source = [("b", "abc"), ("a", "abc")].map(Ok::<_, std::io::Error>);
+
+    let result = import(
+        &tree,
+        source,
+        &SequenceNumberCounter::default(),
+        &SequenceNumberCounter::default(),
+    );
+    assert!(matches!(
+        result,
+        Err(lsm_tree::Error::Io(e)) if e.kind() == std::io::ErrorKind::InvalidInput,
+    ));
+
+    Ok(())
+}
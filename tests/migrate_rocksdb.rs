@@ -0,0 +1,407 @@
+use lsm_tree::{
+    migrate::rocksdb::SstReader, AbstractTree, Config, SeqNo, SequenceNumberCounter, ValueType,
+};
+use std::path::Path;
+use test_log::test;
+
+const TYPE_DELETION: u8 = 0x0;
+const TYPE_VALUE: u8 = 0x1;
+const TYPE_MERGE: u8 = 0x2;
+const TYPE_SINGLE_DELETION: u8 = 0x7;
+
+/// Minimal encoder for uncompressed RocksDB `BlockBasedTable` files
+struct SstEncoder {
+    format_version: u32,
+    items_per_block: usize,
+    delta_index: bool,
+    hash_index: bool,
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_handle(buf: &mut Vec<u8>, handle: (u64, u64)) {
+    put_varint(buf, handle.0);
+    put_varint(buf, handle.1);
+}
+
+fn shared_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn finish_block(mut buf: Vec<u8>, restarts: &[u32], hash_index: bool) -> Vec<u8> {
+    for restart in restarts {
+        buf.extend_from_slice(&restart.to_le_bytes());
+    }
+
+    let mut restart_count = restarts.len() as u32;
+
+    if hash_index {
+        // NOTE: Buckets are never consulted by the importer
+        buf.extend_from_slice(&[0xFF; 3]);
+        buf.extend_from_slice(&3u16.to_le_bytes());
+        restart_count |= 1 << 31;
+    }
+
+    buf.extend_from_slice(&restart_count.to_le_bytes());
+    buf
+}
+
+fn build_block(items: &[(Vec<u8>, Vec<u8>)], restart_interval: usize, hash_index: bool) -> Vec<u8> {
+    let mut buf = vec![];
+    let mut restarts = vec![];
+    let mut last_key: &[u8] = &[];
+
+    for (idx, (key, value)) in items.iter().enumerate() {
+        let shared = if idx % restart_interval == 0 {
+            restarts.push(buf.len() as u32);
+            0
+        } else {
+            shared_prefix(last_key, key)
+        };
+
+        put_varint(&mut buf, shared as u64);
+        put_varint(&mut buf, (key.len() - shared) as u64);
+        put_varint(&mut buf, value.len() as u64);
+        buf.extend_from_slice(&key[shared..]);
+        buf.extend_from_slice(value);
+
+        last_key = key;
+    }
+
+    finish_block(buf, &restarts, hash_index)
+}
+
+fn build_delta_index_block(items: &[(Vec<u8>, (u64, u64))], restart_interval: usize) -> Vec<u8> {
+    let mut buf = vec![];
+    let mut restarts = vec![];
+    let mut last: Option<&(Vec<u8>, (u64, u64))> = None;
+
+    for (idx, item) in items.iter().enumerate() {
+        let (key, handle) = item;
+        let is_restart = idx % restart_interval == 0;
+
+        let shared = match last {
+            Some((last_key, _)) if !is_restart => shared_prefix(last_key, key),
+            _ => {
+                restarts.push(buf.len() as u32);
+                0
+            }
+        };
+
+        put_varint(&mut buf, shared as u64);
+        put_varint(&mut buf, (key.len() - shared) as u64);
+        buf.extend_from_slice(&key[shared..]);
+
+        match last {
+            Some((_, prev)) if !is_restart => {
+                let delta = handle.1 as i64 - prev.1 as i64;
+                put_varint(&mut buf, ((delta << 1) ^ (delta >> 63)) as u64);
+            }
+            _ => put_handle(&mut buf, *handle),
+        }
+
+        last = Some(item);
+    }
+
+    finish_block(buf, &restarts, false)
+}
+
+impl SstEncoder {
+    fn new() -> Self {
+        Self {
+            format_version: 5,
+            items_per_block: 10,
+            delta_index: true,
+            hash_index: false,
+        }
+    }
+
+    fn write<P: AsRef<Path>>(&self, path: P, items: &[(&[u8], SeqNo, u8, &[u8])]) {
+        let mut file = vec![];
+
+        let write_block = |file: &mut Vec<u8>, block: Vec<u8>| {
+            let handle = (file.len() as u64, block.len() as u64);
+            file.extend_from_slice(&block);
+            file.extend_from_slice(&[0; 5]);
+            handle
+        };
+
+        let mut index = vec![];
+
+        for chunk in items.chunks(self.items_per_block) {
+            let entries = chunk
+                .iter()
+                .map(|(key, seqno, ty, value)| {
+                    let mut internal_key = key.to_vec();
+                    internal_key.extend_from_slice(&((seqno << 8) | u64::from(*ty)).to_le_bytes());
+                    (internal_key, value.to_vec())
+                })
+                .collect::<Vec<_>>();
+
+            let separator = entries.last().unwrap().0.clone();
+            let handle = write_block(&mut file, build_block(&entries, 4, self.hash_index));
+            index.push((separator, handle));
+        }
+
+        let mut delta_flag = vec![];
+        put_varint(&mut delta_flag, u64::from(self.delta_index));
+
+        let properties = vec![
+            (
+                b"rocksdb.block.based.table.index.type".to_vec(),
+                0u32.to_le_bytes().to_vec(),
+            ),
+            (b"rocksdb.index.value.is.delta.encoded".to_vec(), delta_flag),
+            (b"rocksdb.num.entries".to_vec(), {
+                let mut buf = vec![];
+                put_varint(&mut buf, items.len() as u64);
+                buf
+            }),
+        ];
+        let properties_handle = write_block(&mut file, build_block(&properties, 1, false));
+
+        let mut handle_value = vec![];
+        put_handle(&mut handle_value, properties_handle);
+        let metaindex = vec![(b"rocksdb.properties".to_vec(), handle_value)];
+        let metaindex_handle = write_block(&mut file, build_block(&metaindex, 1, false));
+
+        let index_block = if self.delta_index {
+            build_delta_index_block(&index, 16)
+        } else {
+            let entries = index
+                .iter()
+                .map(|(key, handle)| {
+                    let mut value = vec![];
+                    put_handle(&mut value, *handle);
+                    (key.clone(), value)
+                })
+                .collect::<Vec<_>>();
+            build_block(&entries, 1, false)
+        };
+        let index_handle = write_block(&mut file, index_block);
+
+        let mut handles = vec![];
+        put_handle(&mut handles, metaindex_handle);
+        put_handle(&mut handles, index_handle);
+        handles.resize(40, 0);
+
+        if self.format_version == 0 {
+            file.extend_from_slice(&handles);
+            file.extend_from_slice(&0xdb47_7524_8b80_fb57_u64.to_le_bytes());
+        } else {
+            file.push(1);
+            file.extend_from_slice(&handles);
+            file.extend_from_slice(&self.format_version.to_le_bytes());
+            file.extend_from_slice(&0x88e2_41b7_85f4_cff7_u64.to_le_bytes());
+        }
+
+        std::fs::write(path, file).unwrap();
+    }
+}
+
+fn make_items(count: u64) -> Vec<(Vec<u8>, SeqNo, u8, Vec<u8>)> {
+    let mut items = vec![];
+
+    for idx in 0..count {
+        let key = format!("key{idx:05}").into_bytes();
+
+        match idx % 10 {
+            3 => {
+                items.push((key.clone(), 1_000 + idx, TYPE_DELETION, vec![]));
+                items.push((key, idx, TYPE_VALUE, b"old".to_vec()));
+            }
+            7 => {
+                items.push((key.clone(), 1_000 + idx, TYPE_SINGLE_DELETION, vec![]));
+                items.push((key, idx, TYPE_VALUE, b"old".to_vec()));
+            }
+            _ => {
+                items.push((key, idx, TYPE_VALUE, format!("value{idx}").into_bytes()));
+            }
+        }
+    }
+
+    items
+}
+
+fn write_items<P: AsRef<Path>>(
+    encoder: &SstEncoder,
+    path: P,
+    items: &[(Vec<u8>, SeqNo, u8, Vec<u8>)],
+) {
+    let items = items
+        .iter()
+        .map(|(k, s, t, v)| (k.as_slice(), *s, *t, v.as_slice()))
+        .collect::<Vec<_>>();
+
+    encoder.write(path, &items);
+}
+
+#[test]
+fn migrate_rocksdb_reader() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path().join("000001.sst");
+
+    let items = make_items(100);
+    write_items(&SstEncoder::new(), &path, &items);
+
+    let read = SstReader::new(&path)?.collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(items.len(), read.len());
+
+    for ((key, seqno, ty, value), item) in items.iter().zip(&read) {
+        assert_eq!(&**key, &*item.key.user_key);
+        assert_eq!(*seqno, item.key.seqno);
+
+        let expected_type = match *ty {
+            TYPE_VALUE => ValueType::Value,
+            TYPE_DELETION => ValueType::Tombstone,
+            TYPE_SINGLE_DELETION => ValueType::WeakTombstone,
+            _ => unreachable!(),
+        };
+        assert_eq!(expected_type, item.key.value_type);
+
+        if expected_type == ValueType::Value {
+            assert_eq!(&**value, &*item.value);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn migrate_rocksdb_import() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_path = folder.path().join("000001.sst");
+
+    write_items(&SstEncoder::new(), &sst_path, &make_items(1_000));
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(folder.path().join("tree"), seqno.clone()).open()?;
+    let lsm_tree::AnyTree::Standard(tree) = tree else {
+        unreachable!();
+    };
+
+    let count = lsm_tree::migrate::rocksdb::import(&tree, [&sst_path], &seqno, &visible_seqno)?;
+
+    assert_eq!(1_200, count);
+    assert_eq!(1_998, seqno.get());
+    assert_eq!(1_998, visible_seqno.get());
+    assert!(tree.table_count() > 0);
+
+    assert_eq!(800, tree.len(SeqNo::MAX, None)?);
+    assert_eq!(
+        Some("value1".as_bytes().into()),
+        tree.get("key00001", SeqNo::MAX)?
+    );
+    assert_eq!(None, tree.get("key00003", SeqNo::MAX)?);
+    assert_eq!(None, tree.get("key00007", SeqNo::MAX)?);
+
+    // Snapshot reads see the sequence numbers from RocksDB
+    assert_eq!(Some("old".as_bytes().into()), tree.get("key00003", 1_000)?);
+    assert_eq!(None, tree.get("key00001", 1)?);
+
+    tree.insert("key00001", "new", seqno.next());
+    assert_eq!(
+        Some("new".as_bytes().into()),
+        tree.get("key00001", SeqNo::MAX)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn migrate_rocksdb_import_legacy_footer() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_path = folder.path().join("000001.sst");
+
+    let encoder = SstEncoder {
+        format_version: 0,
+        items_per_block: 3,
+        delta_index: false,
+        hash_index: true,
+    };
+    write_items(&encoder, &sst_path, &make_items(50));
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(folder.path().join("tree"), seqno.clone()).open()?;
+    let lsm_tree::AnyTree::Standard(tree) = tree else {
+        unreachable!();
+    };
+
+    lsm_tree::migrate::rocksdb::import(&tree, [&sst_path], &seqno, &visible_seqno)?;
+    assert_eq!(40, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn migrate_rocksdb_reject_merge_operand() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_path = folder.path().join("000001.sst");
+
+    SstEncoder::new().write(&sst_path, &[(b"a", 0, TYPE_MERGE, b"+1")]);
+
+    let result = SstReader::new(&sst_path)?.collect::<lsm_tree::Result<Vec<_>>>();
+
+    assert!(matches!(
+        result,
+        Err(lsm_tree::Error::InvalidTag((
+            "RocksDbValueType",
+            TYPE_MERGE
+        )))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn migrate_rocksdb_reject_invalid_file() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path().join("000001.sst");
+
+    std::fs::write(&path, [0; 100])?;
+
+    assert!(matches!(
+        SstReader::new(&path),
+        Err(lsm_tree::Error::InvalidTrailer)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn migrate_rocksdb_reject_non_empty_tree() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_path = folder.path().join("000001.sst");
+
+    write_items(&SstEncoder::new(), &sst_path, &make_items(100));
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(folder.path().join("tree"), seqno.clone())
+        .use_journal(true)
+        .open()?;
+    let lsm_tree::AnyTree::Standard(tree) = tree else {
+        unreachable!();
+    };
+
+    tree.insert("z", "abc", seqno.next());
+
+    assert!(matches!(
+        lsm_tree::migrate::rocksdb::import(&tree, [&sst_path], &seqno, &visible_seqno),
+        Err(lsm_tree::Error::InvalidIngestion(_)),
+    ));
+    assert_eq!(1, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use lsm_tree::{
+    AbstractTree, AnyTree, ChangeOp, Config, InternalValue, SequenceNumberCounter, ValueType,
+};
+use test_log::test;
+
+#[test]
+fn tree_apply_replicated_batch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    let AnyTree::Standard(tree) = tree else {
+        unreachable!();
+    };
+
+    tree.apply_replicated_batch(vec![
+        InternalValue::from_components("a", "abc", 10, ValueType::Value),
+        InternalValue::from_components("b", "abc", 10, ValueType::Value),
+    ])?;
+    tree.apply_replicated_batch(vec![InternalValue::new_tombstone("a", 11)])?;
+
+    assert_eq!(Some(11), tree.get_highest_seqno());
+    assert_eq!(12, seqno.get());
+
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("a", 11)?);
+    assert_eq!(None, tree.get("a", 12)?);
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("b", 12)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_apply_replicated_batch_non_monotonic() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    let AnyTree::Standard(tree) = tree else {
+        unreachable!();
+    };
+
+    tree.apply_replicated_batch(vec![InternalValue::new_tombstone("a", 5)])?;
+
+    // NOTE: Same seqno as already applied batch
+    assert!(matches!(
+        tree.apply_replicated_batch(vec![InternalValue::new_tombstone("b", 5)]),
+        Err(lsm_tree::Error::NonMonotonicSeqNo { got: 5, highest: 5 }),
+    ));
+
+    // NOTE: Going backwards inside a batch rejects the entire batch
+    assert!(matches!(
+        tree.apply_replicated_batch(vec![
+            InternalValue::new_tombstone("c", 7),
+            InternalValue::new_tombstone("d", 6),
+        ]),
+        Err(lsm_tree::Error::NonMonotonicSeqNo { got: 6, highest: 7 }),
+    ));
+    assert_eq!(None, tree.get_internal_entry(b"c", 8)?);
+
+    // NOTE: Also checks against flushed data
+    tree.flush_active_memtable(0)?;
+    assert!(matches!(
+        tree.apply_replicated_batch(vec![InternalValue::new_tombstone("e", 3)]),
+        Err(lsm_tree::Error::NonMonotonicSeqNo { got: 3, highest: 5 }),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn tree_apply_replicated_batch_concurrent() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    let AnyTree::Standard(tree) = tree else {
+        unreachable!();
+    };
+
+    let results = std::thread::scope(|s| {
+        let handles = (0..8u8)
+            .map(|x| {
+                let tree = &tree;
+                s.spawn(move || {
+                    tree.apply_replicated_batch(vec![
+                        InternalValue::from_components([b'a', x], "abc", 5, ValueType::Value),
+                        InternalValue::from_components([b'b', x], "abc", 5, ValueType::Value),
+                    ])
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("should not panic"))
+            .collect::<Vec<_>>()
+    });
+
+    // NOTE: Exactly one batch with the same sequence number is applied
+    assert_eq!(1, results.iter().filter(|result| result.is_ok()).count());
+    assert_eq!(2, tree.len(6, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_apply_replicated_batch_from_cdc() -> lsm_tree::Result<()> {
+    let leader_folder = tempfile::tempdir()?;
+    let follower_folder = tempfile::tempdir()?;
+
+    let AnyTree::Standard(leader) = Config::new(&leader_folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?
+    else {
+        unreachable!();
+    };
+
+    let follower_seqno = SequenceNumberCounter::default();
+    let follower = Config::new(&follower_folder, follower_seqno.clone())
+        .with_kv_separation(Some(Default::default()))
+        .open()?;
+    let AnyTree::Blob(follower) = follower else {
+        unreachable!();
+    };
+
+    leader.insert("a", "abc", 0);
+    leader.insert("b", "def", 1);
+    leader.remove("a", 2);
+    leader.remove_weak("c", 3);
+
+    let entries = leader
+        .cdc_reader(0)?
+        .map(|event| {
+            event.map(|event| {
+                let value_type = match event.op {
+                    ChangeOp::Insert => ValueType::Value,
+                    ChangeOp::Remove => ValueType::Tombstone,
+                    ChangeOp::RemoveWeak => ValueType::WeakTombstone,
+                    ChangeOp::Merge => ValueType::MergeOperand,
+                    ChangeOp::InsertWithTtl(_) | ChangeOp::RemoveRange => {
+                        unreachable!("no expiring values or range tombstones were written")
+                    }
+                };
+                InternalValue::from_components(event.key, event.value, event.seqno, value_type)
+            })
+        })
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+    follower.apply_replicated_batch(entries)?;
+    assert_eq!(4, follower_seqno.get());
+
+    follower.flush_active_memtable(0)?;
+    assert_eq!(None, follower.get("a", 4)?);
+    assert_eq!(Some("def".as_bytes().into()), follower.get("b", 4)?);
+    assert_eq!(Some(3), follower.get_highest_seqno());
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SequenceNumberCounter};
+use tempfile::tempdir;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 10_000;
+
+#[test]
+fn tree_approx_size() -> lsm_tree::Result<()> {
+    let folder = tempdir()?;
+
+    let tree = Config::new(folder, SequenceNumberCounter::default()).open()?;
+    assert_eq!(0, tree.approximate_size::<&[u8], _>(..)?);
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), x);
+    }
+
+    // NOTE: Memtables are not counted
+    assert_eq!(0, tree.approximate_size::<&[u8], _>(..)?);
+
+    tree.flush_active_memtable(0)?;
+
+    let full = tree.approximate_size::<&[u8], _>(..)?;
+    assert_eq!(tree.disk_space(), full);
+
+    let half = tree.approximate_size(..(ITEM_COUNT / 2).to_be_bytes())?;
+    let quarter = tree.approximate_size(..(ITEM_COUNT / 4).to_be_bytes())?;
+    assert!(quarter > 0);
+    assert!(quarter < half);
+    assert!(half < full);
+
+    // NOTE: Only the block that crosses the split point is counted twice
+    // (blocks can be slightly larger than the configured block size)
+    let second_half = tree.approximate_size((ITEM_COUNT / 2).to_be_bytes()..)?;
+    assert!(half + second_half <= full + 2 * 4_096);
+
+    // NOTE: Ranges outside of the table are empty
+    assert_eq!(
+        0,
+        tree.approximate_size(ITEM_COUNT.to_be_bytes()..(ITEM_COUNT * 2).to_be_bytes())?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_approx_size_blob() -> lsm_tree::Result<()> {
+    let folder = tempdir()?;
+
+    let tree = Config::new(folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let full = tree.approximate_size::<&[u8], _>(..)?;
+    assert!(full.abs_diff(tree.disk_space()) <= 1);
+
+    let half = tree.approximate_size(..(ITEM_COUNT / 2).to_be_bytes())?;
+    assert!(half > 0);
+    assert!(half < full);
+
+    Ok(())
+}
+
+#[test]
+fn tree_approx_size_split_point() -> lsm_tree::Result<()> {
+    let folder = tempdir()?;
+
+    let tree = Config::new(folder, SequenceNumberCounter::default()).open()?;
+
+    // NOTE: Spread the key space over multiple, overlapping tables
+    for batch in 0..4 {
+        for x in (batch..ITEM_COUNT).step_by(4) {
+            tree.insert(x.to_be_bytes(), "a".repeat(100), x);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(4, tree.table_count());
+
+    let full = tree.approximate_size::<&[u8], _>(..)?;
+    assert_eq!(tree.disk_space(), full);
+
+    // NOTE: Find the key that splits the tree into two (about) equally sized shards
+    let (mut lo, mut hi) = (0, ITEM_COUNT);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+
+        if tree.approximate_size(..mid.to_be_bytes())? * 2 < full {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let split = lo;
+    assert!(split.abs_diff(ITEM_COUNT / 2) < ITEM_COUNT / 20);
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use lsm_tree::{AbstractTree, AnyTree, Config, SeqNo, SequenceNumberCounter};
+use std::time::{Duration, Instant};
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+fn wait_until(tree: &AnyTree, f: impl Fn(&AnyTree) -> bool) {
+    let start = Instant::now();
+
+    while !f(tree) {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "background work did not finish",
+        );
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn open(folder: &tempfile::TempDir) -> lsm_tree::Result<AnyTree> {
+    let seqno = SequenceNumberCounter::default();
+
+    Config::new(folder, seqno.clone())
+        .max_memtable_size(4_096)
+        .background_threads(2)
+        .background_gc_watermark(seqno)
+        .open()
+}
+
+#[test]
+fn tree_background_work_flush_and_compact() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open(&folder)?;
+
+    for i in 0..ITEM_COUNT {
+        tree.insert_auto(format!("{i:0>6}"), "v".repeat(50));
+    }
+
+    wait_until(&tree, |tree| {
+        tree.active_memtable_size() < 4_096 && tree.sealed_memtable_count() == 0
+    });
+
+    // NOTE: The leveled strategy keeps L0 small
+    wait_until(&tree, |tree| tree.l0_run_count() < 4);
+
+    assert!(tree.table_count() > 0);
+    assert_eq!(ITEM_COUNT, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_background_work_pause_resume() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open(&folder)?;
+
+    tree.pause_background_work();
+
+    for i in 0..ITEM_COUNT {
+        tree.insert_auto(format!("{i:0>6}"), "v".repeat(50));
+    }
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(0, tree.table_count());
+    assert!(tree.active_memtable_size() >= 4_096);
+
+    tree.resume_background_work();
+    wait_until(&tree, |tree| tree.table_count() > 0);
+
+    assert_eq!(ITEM_COUNT, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_background_work_stops_on_drop() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open(&folder)?;
+
+        for i in 0..ITEM_COUNT {
+            tree.insert_auto(format!("{i:0>6}"), "v".repeat(50));
+        }
+
+        wait_until(&tree, |tree| tree.table_count() > 0);
+
+        // NOTE: Waits for running work, so the workers do not keep the tree alive
+        tree.pause_background_work();
+    }
+
+    // NOTE: The folder is not locked anymore
+    let tree = open(&folder)?;
+    assert!(tree.table_count() > 0);
+
+    Ok(())
+}
+
+#[test]
+fn tree_background_work_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .max_memtable_size(4_096)
+        .open()?;
+
+    for i in 0..ITEM_COUNT {
+        tree.insert_auto(format!("{i:0>6}"), "v".repeat(50));
+    }
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(0, tree.table_count());
+
+    // NOTE: Does nothing without background workers
+    tree.pause_background_work();
+    tree.resume_background_work();
+
+    Ok(())
+}
+
+#[test]
+fn tree_background_work_requires_gc_watermark() {
+    let folder = tempfile::tempdir().expect("should create folder");
+
+    let result = Config::new(&folder, SequenceNumberCounter::default())
+        .background_threads(2)
+        .open();
+
+    assert!(matches!(result, Err(lsm_tree::Error::InvalidConfig(_))));
+}
+
+#[test]
+fn tree_background_work_keeps_snapshot_versions() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    // NOTE: The watermark is never raised, because the snapshot is read until the end
+    let tree = Config::new(&folder, seqno.clone())
+        .max_memtable_size(4_096)
+        .background_threads(2)
+        .background_gc_watermark(SequenceNumberCounter::default())
+        .open()?;
+
+    tree.insert("a", "old", seqno.next());
+    let snapshot = seqno.get();
+
+    // NOTE: Every flushed table overwrites "a", and compactions merge them
+    for i in 0..ITEM_COUNT {
+        tree.insert(format!("{i:0>6}"), "v".repeat(50), seqno.next());
+        tree.insert("a", "new", seqno.next());
+    }
+
+    wait_until(&tree, |tree| {
+        tree.active_memtable_size() < 4_096 && tree.sealed_memtable_count() == 0
+    });
+    wait_until(&tree, |tree| {
+        tree.l0_run_count() < 4 && tree.table_count() > 0
+    });
+
+    assert_eq!(Some("old".as_bytes().into()), tree.get("a", snapshot)?);
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use test_log::test;
+
+#[test]
+fn tree_batch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    tree.insert("old", "a", seqno.next());
+
+    let mut batch = tree.batch();
+    for x in 0..10u8 {
+        batch.insert([b'b', x], "value");
+    }
+    batch.remove("old");
+    assert_eq!(11, batch.len());
+
+    let batch_seqno = seqno.next();
+    batch.commit(batch_seqno)?;
+
+    for _ in 0..2 {
+        assert!(tree.contains_key("old", batch_seqno)?);
+        assert_eq!(1, tree.len(batch_seqno, None)?);
+
+        assert!(!tree.contains_key("old", batch_seqno + 1)?);
+        assert_eq!(10, tree.len(batch_seqno + 1, None)?);
+
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Empty batches do not write anything
+    tree.batch().commit(seqno.next())?;
+    assert_eq!(0, tree.active_memtable_size());
+
+    Ok(())
+}
+
+#[test]
+fn tree_batch_concurrent_readers() -> lsm_tree::Result<()> {
+    const BATCH_COUNT: u64 = 500;
+
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    // NOTE: Readers only read at sequence numbers of committed batches
+    let visible = Arc::new(AtomicU64::new(0));
+
+    let writer = {
+        let tree = tree.clone();
+        let seqno = seqno.clone();
+        let visible = visible.clone();
+
+        std::thread::spawn(move || {
+            for x in 0..BATCH_COUNT {
+                let mut batch = tree.batch();
+                batch.insert("a", x.to_be_bytes());
+                batch.insert("b", x.to_be_bytes());
+
+                let batch_seqno = seqno.next();
+                batch.commit(batch_seqno)?;
+                visible.store(batch_seqno + 1, Ordering::Release);
+            }
+
+            Ok::<_, lsm_tree::Error>(())
+        })
+    };
+
+    loop {
+        let read_seqno = visible.load(Ordering::Acquire);
+
+        let a = tree.get("a", read_seqno)?;
+        let b = tree.get("b", read_seqno)?;
+        assert_eq!(a, b);
+
+        if a.is_some_and(|v| *v == (BATCH_COUNT - 1).to_be_bytes()) {
+            break;
+        }
+    }
+
+    writer.join().expect("should join")?;
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+#[test_log::test]
+#[cfg(feature = "bytes_1")]
+fn tree_bytes_zero_copy() -> lsm_tree::Result<()> {
+    use bytes::Bytes;
+    use lsm_tree::{AbstractTree, SequenceNumberCounter};
+
+    let folder = tempfile::tempdir()?;
+
+    let tree = lsm_tree::Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    let key = Bytes::from(vec![b'k'; 100]);
+    let value = Bytes::from(vec![b'v'; 1_000]);
+
+    tree.insert(key.clone(), value.clone(), 0);
+
+    // NOTE: The memtable holds the inserted buffers, without copying them
+    let item = tree.get(&key, 1)?.expect("should exist");
+    assert_eq!(item.as_ptr(), value.as_ptr());
+
+    tree.flush_active_memtable(0)?;
+
+    let item: Bytes = tree.get(&key, 1)?.expect("should exist").into();
+    assert_eq!(item, value);
+
+    Ok(())
+}
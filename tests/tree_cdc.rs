@@ -0,0 +1,239 @@
+use lsm_tree::{
+    AbstractTree, AnyTree, ChangeEvent, ChangeOp, Config, KvSeparationOptions,
+    SequenceNumberCounter,
+};
+use std::path::Path;
+use test_log::test;
+
+fn journal_file_count(path: &Path) -> lsm_tree::Result<usize> {
+    Ok(std::fs::read_dir(path.join("journal"))?.count())
+}
+
+fn open_tree(path: &Path) -> lsm_tree::Result<lsm_tree::Tree> {
+    let AnyTree::Standard(tree) = Config::new(path, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?
+    else {
+        unreachable!();
+    };
+    Ok(tree)
+}
+
+#[test]
+fn tree_cdc_simple() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open_tree(folder.path())?;
+
+    tree.insert("a", "abc", 0);
+    tree.remove("b", 1);
+    tree.remove_weak("c", 2);
+
+    let mut reader = tree.cdc_reader(0)?;
+
+    assert_eq!(
+        vec![
+            ChangeEvent {
+                seqno: 0,
+                key: "a".into(),
+                op: ChangeOp::Insert,
+                value: "abc".into(),
+            },
+            ChangeEvent {
+                seqno: 1,
+                key: "b".into(),
+                op: ChangeOp::Remove,
+                value: "".into(),
+            },
+            ChangeEvent {
+                seqno: 2,
+                key: "c".into(),
+                op: ChangeOp::RemoveWeak,
+                value: "".into(),
+            },
+        ],
+        reader.by_ref().collect::<lsm_tree::Result<Vec<_>>>()?,
+    );
+
+    // NOTE: Caught up, but new writes are picked up when polling again
+    assert!(reader.next().is_none());
+
+    tree.insert("d", "def", 3);
+    assert_eq!(3, reader.next().unwrap()?.seqno);
+    assert!(reader.next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn tree_cdc_from_seqno() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open_tree(folder.path())?;
+
+    for seqno in 0..10 {
+        tree.insert(seqno.to_string(), "abc", seqno);
+    }
+
+    let seqnos = tree
+        .cdc_reader(5)?
+        .map(|event| event.map(|event| event.seqno))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+    assert_eq!(vec![5, 6, 7, 8, 9], seqnos);
+
+    Ok(())
+}
+
+#[test]
+fn tree_cdc_retain_until_ack() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open_tree(folder.path())?;
+
+    let mut reader = tree.cdc_reader(0)?;
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("b", "abc", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("c", "abc", 2);
+
+    // NOTE: Flushed journal files are retained for the reader
+    assert_eq!(3, journal_file_count(folder.path())?);
+
+    let seqnos = reader
+        .by_ref()
+        .map(|event| event.map(|event| event.seqno))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(vec![0, 1, 2], seqnos);
+
+    reader.ack(0)?;
+    assert_eq!(2, journal_file_count(folder.path())?);
+
+    reader.ack(2)?;
+    assert_eq!(1, journal_file_count(folder.path())?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_cdc_delete_flushed_without_reader() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open_tree(folder.path())?;
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, journal_file_count(folder.path())?);
+
+    let reader = tree.cdc_reader(0)?;
+    tree.insert("b", "abc", 1);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, journal_file_count(folder.path())?);
+
+    drop(reader);
+    assert_eq!(1, journal_file_count(folder.path())?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_cdc_recover() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open_tree(folder.path())?;
+        tree.insert("a", "abc", 0);
+        tree.insert("b", "abc", 1);
+        tree.flush_active_memtable(0)?;
+        tree.insert("c", "abc", 2);
+    }
+
+    {
+        let tree = open_tree(folder.path())?;
+        tree.insert("d", "abc", 3);
+
+        // NOTE: The flushed journal file is gone
+        let keys = tree
+            .cdc_reader(0)?
+            .map(|event| event.map(|event| event.key))
+            .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+        assert_eq!(vec![lsm_tree::Slice::from("c"), "d".into()], keys);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_cdc() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+    let AnyTree::Blob(tree) = tree else {
+        unreachable!();
+    };
+
+    let mut reader = tree.cdc_reader(0)?;
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+
+    let event = reader.next().unwrap()?;
+    assert_eq!(ChangeOp::Insert, event.op);
+    assert_eq!(&*event.value, b"abc");
+
+    Ok(())
+}
+
+#[test]
+fn tree_cdc_remove_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open_tree(folder.path())?;
+
+    tree.remove_range("a".."c", 0);
+    tree.remove_range::<&str, _>("d".., 1);
+
+    let mut reader = tree.cdc_reader(0)?;
+
+    assert_eq!(
+        vec![
+            ChangeEvent {
+                seqno: 0,
+                key: "a".into(),
+                op: ChangeOp::RemoveRange,
+                value: "c".into(),
+            },
+            ChangeEvent {
+                seqno: 1,
+                key: "d".into(),
+                op: ChangeOp::RemoveRange,
+                value: "".into(),
+            },
+        ],
+        reader.by_ref().collect::<lsm_tree::Result<Vec<_>>>()?,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_cdc_journal_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let AnyTree::Standard(tree) = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(false)
+        .open()?
+    else {
+        unreachable!();
+    };
+
+    assert!(matches!(
+        tree.cdc_reader(0),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+
+    Ok(())
+}
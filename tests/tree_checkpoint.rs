@@ -0,0 +1,126 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_checkpoint() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+    let path = backup_folder.path().join("checkpoint");
+
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    for i in 0..100u32 {
+        tree.insert(format!("{i:0>3}"), "v", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+
+    tree.remove_range("000".."010", seqno.next());
+
+    // NOTE: Not flushed yet
+    tree.insert("a", "a", seqno.next());
+
+    tree.checkpoint(&path)?;
+
+    // NOTE: Not part of the checkpoint
+    tree.insert("b", "b", seqno.next());
+
+    // NOTE: The checkpoint does not depend on the files of the tree
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    drop(tree);
+    drop(folder);
+
+    let checkpoint = Config::new(&path, SequenceNumberCounter::default()).open()?;
+    assert_eq!(91, checkpoint.len(SeqNo::MAX, None)?);
+    assert!(checkpoint.contains_key("a", SeqNo::MAX)?);
+    assert!(!checkpoint.contains_key("b", SeqNo::MAX)?);
+    assert!(!checkpoint.contains_key("005", SeqNo::MAX)?);
+
+    // NOTE: The checkpoint is a tree of its own
+    checkpoint.insert(
+        "c",
+        "c",
+        checkpoint.get_highest_seqno().unwrap_or_default() + 1,
+    );
+    checkpoint.flush_active_memtable(0)?;
+    assert_eq!(92, checkpoint.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_checkpoint_sharded() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+    let path = backup_folder.path().join("checkpoint");
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .directory_shards(4)
+        .open()?;
+
+    for i in 0..10u32 {
+        tree.insert(format!("{i:0>3}"), "v", u64::from(i));
+        tree.flush_active_memtable(0)?;
+    }
+
+    tree.checkpoint(&path)?;
+
+    let checkpoint = Config::new(&path, SequenceNumberCounter::default()).open()?;
+    assert_eq!(10, checkpoint.table_count());
+    assert_eq!(10, checkpoint.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_checkpoint_exists() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    tree.insert("a", "a", 0);
+
+    assert!(matches!(
+        tree.checkpoint(backup_folder.path()),
+        Err(lsm_tree::Error::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists,
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_checkpoint() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+    let path = backup_folder.path().join("checkpoint");
+    let big = "a".repeat(1_000);
+
+    let config = |path: &std::path::Path| {
+        Config::new(path, SequenceNumberCounter::default())
+            .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+    };
+
+    let tree = config(folder.path()).open()?;
+
+    tree.insert("a", &big, 0);
+    tree.flush_active_memtable(0)?;
+    tree.insert("b", &big, 1);
+
+    tree.checkpoint(&path)?;
+    drop(tree);
+    drop(folder);
+
+    let checkpoint = config(&path).open()?;
+    assert_eq!(2, checkpoint.blob_file_count());
+    assert_eq!(
+        Some(big.as_bytes().into()),
+        checkpoint.get("a", SeqNo::MAX)?
+    );
+    assert_eq!(
+        Some(big.as_bytes().into()),
+        checkpoint.get("b", SeqNo::MAX)?
+    );
+
+    Ok(())
+}
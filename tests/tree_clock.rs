@@ -0,0 +1,88 @@
+use lsm_tree::{
+    compaction::Fifo, AbstractTree, Clock, Config, KvSeparationOptions, SequenceNumberCounter,
+};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Arc,
+    },
+    time::Duration,
+};
+use test_log::test;
+
+/// Clock that only moves when advanced manually
+#[derive(Debug)]
+struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    fn advance(&self, secs: u64) {
+        self.0.fetch_add(secs, Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_secs(self.0.load(Relaxed))
+    }
+}
+
+#[test]
+fn tree_clock_fifo_ttl() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_clock(clock.clone())
+        .open()?;
+
+    tree.insert("a", "1", 0);
+    tree.flush_active_memtable(0)?;
+
+    clock.advance(5);
+    tree.insert("b", "2", 1);
+    tree.flush_active_memtable(1)?;
+
+    let fifo = Arc::new(Fifo::new(u64::MAX, Some(10)));
+
+    clock.advance(4);
+    tree.compact(fifo.clone(), 2)?;
+    assert_eq!(2, tree.table_count());
+
+    clock.advance(1);
+    tree.compact(fifo.clone(), 2)?;
+    assert_eq!(1, tree.table_count());
+    assert!(!tree.contains_key("a", u64::MAX)?);
+
+    clock.advance(5);
+    tree.compact(fifo, 2)?;
+    assert_eq!(0, tree.table_count());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_clock_fifo_ttl() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_clock(clock.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+
+    let fifo = Arc::new(Fifo::new(u64::MAX, Some(10)));
+
+    tree.compact(fifo.clone(), 1)?;
+    assert_eq!(1, tree.table_count());
+
+    clock.advance(20);
+    tree.compact(fifo, 1)?;
+    assert_eq!(0, tree.table_count());
+    assert_eq!(0, tree.blob_file_count());
+
+    Ok(())
+}
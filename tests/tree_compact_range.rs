@@ -0,0 +1,71 @@
+use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_compact_range_reclaims_prefix() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    for tenant in ["tenant1", "tenant2", "tenant3"] {
+        for i in 0..100u32 {
+            tree.insert(format!("{tenant}/{i:0>4}"), "v", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.table_count());
+
+    // NOTE: Write some more data, so not all tables overlap with tenant2
+    for i in 0..100u32 {
+        tree.insert(format!("tenant4/{i:0>4}"), "v", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.table_count());
+
+    for i in 0..100u32 {
+        tree.remove(format!("tenant2/{i:0>4}"), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(100, tree.tombstone_count());
+
+    let untouched = tree
+        .tables()
+        .into_iter()
+        .find(|table| table.key_range.min().starts_with(b"tenant4"))
+        .expect("should exist");
+
+    tree.compact_range("tenant2/".."tenant20", u64::MAX, SeqNo::MAX)?;
+
+    // NOTE: The tombstones and the deleted data are gone, the other table was not rewritten
+    assert_eq!(0, tree.tombstone_count());
+    assert_eq!(2, tree.table_count());
+    assert!(tree.tables().iter().any(|table| table.id == untouched.id));
+    assert_eq!(300, tree.len(SeqNo::MAX, None)?);
+    assert_eq!(0, tree.prefix("tenant2/", SeqNo::MAX, None).count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_compact_range_empty_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    tree.insert("a", "v", 0);
+    tree.flush_active_memtable(0)?;
+
+    #[expect(clippy::reversed_empty_ranges)]
+    tree.compact_range("b".."a", u64::MAX, SeqNo::MAX)?;
+    assert_eq!(Some(1), tree.level_table_count(0));
+
+    tree.compact_range("b".., u64::MAX, SeqNo::MAX)?;
+    assert_eq!(Some(1), tree.level_table_count(0));
+
+    tree.compact_range::<&str, _>(.., u64::MAX, SeqNo::MAX)?;
+    assert_eq!(Some(0), tree.level_table_count(0));
+    assert_eq!(1, tree.table_count());
+
+    Ok(())
+}
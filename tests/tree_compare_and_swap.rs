@@ -0,0 +1,147 @@
+use lsm_tree::{
+    AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter, UserValue,
+};
+use test_log::test;
+
+fn parse(value: Option<&UserValue>) -> u64 {
+    value.map_or(0, |v| {
+        u64::from_be_bytes((**v).try_into().expect("should be u64"))
+    })
+}
+
+#[test]
+fn tree_compare_and_swap() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    // NOTE: Create only if the key does not exist
+    assert!(tree.compare_and_swap("a", None::<&str>, Some("abc"))?);
+    assert!(!tree.compare_and_swap("a", None::<&str>, Some("xyz"))?);
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    assert!(!tree.compare_and_swap("a", Some("ab"), Some("xyz"))?);
+    assert!(tree.compare_and_swap("a", Some("abc"), Some("def"))?);
+    assert_eq!(Some("def".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Compares against the newest version, even if it is on disk
+    assert!(!tree.compare_and_swap("a", Some("abc"), None::<&str>)?);
+    assert!(tree.compare_and_swap("a", Some("def"), None::<&str>)?);
+    assert!(!tree.contains_key("a", SeqNo::MAX)?);
+    assert!(tree.compare_and_swap("a", None::<&str>, Some("ghi"))?);
+    assert_eq!(Some("ghi".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    // NOTE: Removing a non-existing key is a no-op
+    assert!(tree.compare_and_swap("b", None::<&str>, None::<&str>)?);
+    assert_eq!(0, tree.tombstone_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_fetch_update() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    assert!(tree.fetch_update("a", |_| None)?.is_none());
+    assert!(tree.is_empty(SeqNo::MAX, None)?);
+
+    assert!(tree.fetch_update("a", |_| Some("abc".into()))?.is_none());
+
+    let prev = tree.fetch_update("a", |value| {
+        assert_eq!(Some(&"abc".as_bytes().into()), value);
+        None
+    })?;
+    assert_eq!(Some("abc".as_bytes().into()), prev);
+    assert!(!tree.contains_key("a", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_fetch_update_concurrent() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    std::thread::scope(|s| {
+        let handles = (0..8)
+            .map(|_| {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        tree.fetch_update("counter", |value| {
+                            Some((parse(value) + 1).to_be_bytes().into())
+                        })?;
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("should not panic"))
+            .collect::<lsm_tree::Result<Vec<()>>>()
+    })?;
+
+    // NOTE: No increment is lost
+    assert_eq!(800, parse(tree.get("counter", SeqNo::MAX)?.as_ref()));
+
+    Ok(())
+}
+
+#[test]
+fn tree_fetch_update_blocks_writes() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let (tree, seqno) = (&tree, &seqno);
+
+    std::thread::scope(|s| {
+        let writer = s.spawn(move || {
+            rx.recv().expect("should receive");
+            tree.insert("b", "b", seqno.next());
+
+            // NOTE: The insert had to wait for the update to be written
+            tree.contains_key("a", SeqNo::MAX)
+        });
+
+        tree.fetch_update("a", |_| {
+            tx.send(()).expect("should send");
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            Some("a".into())
+        })?;
+
+        assert!(writer.join().expect("should not panic")?);
+
+        Ok::<_, lsm_tree::Error>(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_compare_and_swap() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    let big = "a".repeat(1_000);
+    tree.insert_auto("a", big.clone());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+
+    assert!(!tree.compare_and_swap("a", Some("a"), Some("b"))?);
+    assert!(tree.compare_and_swap("a", Some(&big), Some("b"))?);
+    assert_eq!(Some("b".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    let prev = tree.fetch_update("a", |_| Some(big.clone().into()))?;
+    assert_eq!(Some("b".as_bytes().into()), prev);
+    assert_eq!(Some(big.as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    Ok(())
+}
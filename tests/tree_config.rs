@@ -0,0 +1,150 @@
+use lsm_tree::{
+    compaction::Fifo,
+    config::{BlockSizePolicy, PinningPolicy},
+    AbstractTree, Config, KvSeparationOptions, SequenceNumberCounter,
+};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn tree_config_invalid() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let config = Config::new(&folder, SequenceNumberCounter::default());
+
+    assert!(matches!(
+        config.clone().level_count(1).open(),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+    assert!(matches!(
+        config.clone().max_memtable_size(0).open(),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+    assert!(matches!(
+        config.clone().compaction_workers(0).open(),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+    assert!(matches!(
+        config
+            .clone()
+            .with_kv_separation(Some(
+                KvSeparationOptions::default().staleness_threshold(2.0)
+            ))
+            .open(),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+
+    // NOTE: Nothing was created
+    assert!(!folder.path().join("tables").try_exists()?);
+
+    config.open()?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_config_level_count() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .level_count(3)
+            .open()?;
+
+        assert!(tree.level_table_count(2).is_some());
+        assert!(tree.level_table_count(3).is_none());
+
+        for x in 0..10u64 {
+            tree.insert(x.to_be_bytes(), "a", x);
+        }
+        tree.flush_active_memtable(0)?;
+        tree.major_compact(u64::MAX, 1_000)?;
+
+        assert_eq!(Some(1), tree.level_table_count(2));
+    }
+
+    {
+        // NOTE: Level count is persisted
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .level_count(5)
+            .open()?;
+
+        assert_eq!(3, tree.tree_config().level_count);
+        assert_eq!(Some(1), tree.level_table_count(2));
+        assert_eq!(10, tree.len(u64::MAX, None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_config_compaction_strategy() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .compaction_strategy(Arc::new(Fifo::new(1, None)))
+        .cache_size(1_024 * 1_024)
+        .open()?;
+
+    assert_eq!(1_024 * 1_024, tree.tree_config().cache.capacity());
+
+    for x in 0..10u64 {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), x);
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(10, tree.table_count());
+
+    // NOTE: FIFO drops the oldest tables to stay under its limit
+    tree.run_compaction(u64::MAX)?;
+    assert!(tree.table_count() < 10);
+
+    Ok(())
+}
+
+#[test]
+fn tree_config_presets() -> lsm_tree::Result<()> {
+    let presets: [fn(Config) -> Config; 3] = [
+        Config::optimized_for_point_lookups,
+        Config::optimized_for_range_scans,
+        Config::optimized_for_bulk_load,
+    ];
+
+    for preset in presets {
+        let folder = tempfile::tempdir()?;
+        let tree = preset(Config::new(&folder, SequenceNumberCounter::default())).open()?;
+
+        for x in 0..1_000u64 {
+            tree.insert(x.to_be_bytes(), "a".repeat(100), x);
+        }
+        tree.flush_active_memtable(0)?;
+        tree.major_compact(u64::MAX, u64::MAX)?;
+
+        assert_eq!(1_000, tree.len(u64::MAX, None)?);
+        assert!(tree.contains_key(500u64.to_be_bytes(), u64::MAX)?);
+        assert!(!tree.contains_key(1_000u64.to_be_bytes(), u64::MAX)?);
+        assert_eq!(
+            Some("a".repeat(100).as_bytes().into()),
+            tree.get(999u64.to_be_bytes(), u64::MAX)?,
+        );
+    }
+
+    let config = Config::new("x", SequenceNumberCounter::default());
+
+    let point = config.clone().optimized_for_point_lookups();
+    assert_eq!(
+        BlockSizePolicy::all(4 * 1_024),
+        point.data_block_size_policy
+    );
+    assert_eq!(PinningPolicy::all(true), point.filter_block_pinning_policy);
+
+    let scan = config.clone().optimized_for_range_scans();
+    assert_eq!(
+        BlockSizePolicy::all(32 * 1_024),
+        scan.data_block_size_policy
+    );
+
+    let bulk = config.optimized_for_bulk_load();
+    assert!(bulk.max_memtable_size > point.max_memtable_size);
+    assert_eq!(PinningPolicy::all(false), bulk.index_block_pinning_policy);
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use lsm_tree::{AbstractTree, Config, Cursor, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+fn key(cursor_item: Option<lsm_tree::KvPair>) -> Option<Vec<u8>> {
+    cursor_item.map(|(k, _)| k.to_vec())
+}
+
+#[test]
+fn tree_cursor() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let standard = Config::new(folder.path().join("a"), SequenceNumberCounter::default()).open()?;
+    let blob = Config::new(folder.path().join("b"), SequenceNumberCounter::default())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().separation_threshold(1_000),
+        ))
+        .open()?;
+
+    for tree in [&standard, &blob] {
+        for (idx, k) in ["b", "d", "f", "h"].into_iter().enumerate() {
+            tree.insert(k, k.repeat(2_000), idx as SeqNo);
+        }
+        tree.flush_active_memtable(0)?;
+        tree.insert("e", "e".repeat(2_000), 4);
+        tree.remove("f", 5);
+
+        let mut cursor = Cursor::new(tree.clone(), SeqNo::MAX);
+        assert!(cursor.key().is_none());
+        assert!(cursor.prev()?.is_none());
+
+        assert_eq!(Some(b"b".to_vec()), key(cursor.next()?));
+        assert_eq!(Some(b"d".to_vec()), key(cursor.next()?));
+        assert_eq!(Some(b"b".to_vec()), key(cursor.prev()?));
+        assert_eq!(Some(b"d".to_vec()), key(cursor.next()?));
+        assert_eq!(Some(b"e".to_vec()), key(cursor.next()?));
+        assert_eq!(Some(b"h".to_vec()), key(cursor.next()?));
+        assert!(cursor.next()?.is_none());
+        assert!(cursor.key().is_none());
+        assert_eq!(Some(b"h".to_vec()), key(cursor.prev()?));
+
+        assert_eq!(Some(b"e".to_vec()), key(cursor.seek("e")?));
+        assert_eq!(Some(b"h".to_vec()), key(cursor.seek("f")?));
+        assert!(cursor.seek("i")?.is_none());
+        assert_eq!(Some(b"h".to_vec()), key(cursor.prev()?));
+
+        assert_eq!(Some(b"e".to_vec()), key(cursor.seek_for_prev("f")?));
+        assert_eq!(Some(&b"e".as_slice().into()), cursor.key());
+        assert_eq!(Some(b"d".to_vec()), key(cursor.prev()?));
+        assert!(cursor.seek_for_prev("a")?.is_none());
+        assert_eq!(Some(b"b".to_vec()), key(cursor.next()?));
+
+        let (_, value) = cursor.seek("d")?.expect("should exist");
+        assert_eq!("d".repeat(2_000).as_bytes(), &*value);
+
+        // NOTE: Snapshot reads do not see newer versions
+        let mut cursor = Cursor::new(tree.clone(), 5);
+        assert_eq!(Some(b"f".to_vec()), key(cursor.seek("f")?));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_cursor_merge_join() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let left = Config::new(folder.path().join("a"), SequenceNumberCounter::default()).open()?;
+    let right = Config::new(folder.path().join("b"), SequenceNumberCounter::default()).open()?;
+
+    for x in 0..100u64 {
+        if x % 2 == 0 {
+            left.insert(x.to_be_bytes(), "l", x);
+        }
+        if x % 3 == 0 {
+            right.insert(x.to_be_bytes(), "r", x);
+        }
+    }
+    left.flush_active_memtable(0)?;
+
+    let mut joined = vec![];
+
+    let mut l = Cursor::new(left, SeqNo::MAX);
+    let mut r = Cursor::new(right, SeqNo::MAX);
+
+    let mut l_item = l.next()?;
+    let mut r_item = r.next()?;
+
+    while let (Some((lk, _)), Some((rk, _))) = (&l_item, &r_item) {
+        match lk.cmp(rk) {
+            std::cmp::Ordering::Equal => {
+                joined.push(u64::from_be_bytes(
+                    (**lk).try_into().expect("should be u64"),
+                ));
+                l_item = l.next()?;
+                r_item = r.next()?;
+            }
+            std::cmp::Ordering::Less => l_item = l.seek(rk)?,
+            std::cmp::Ordering::Greater => r_item = r.seek(lk)?,
+        }
+    }
+
+    assert_eq!((0..100).step_by(6).collect::<Vec<_>>(), joined);
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use lsm_tree::{
+    config::CompressionPolicy, register_compressor, AbstractTree, CompressionType, Compressor,
+    Config, KvSeparationOptions, SequenceNumberCounter,
+};
+use std::sync::Arc;
+use test_log::test;
+
+/// Run-length encoding, stored as `[count, byte]` pairs
+#[derive(Debug)]
+struct RleCompressor;
+
+impl Compressor for RleCompressor {
+    fn compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut out = vec![];
+
+        for chunk in data.chunk_by(|a, b| a == b) {
+            for run in chunk.chunks(u8::MAX.into()) {
+                #[expect(clippy::cast_possible_truncation)]
+                out.push(run.len() as u8);
+                out.push(run[0]);
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn decompress(&self, data: &[u8], uncompressed_len: usize) -> std::io::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(uncompressed_len);
+
+        for pair in data.chunks(2) {
+            let [count, byte] = pair else {
+                return Err(std::io::Error::other("truncated run"));
+            };
+            out.extend(std::iter::repeat_n(*byte, (*count).into()));
+        }
+
+        Ok(out)
+    }
+}
+
+#[test]
+fn tree_custom_compression() -> lsm_tree::Result<()> {
+    const CODEC_ID: u8 = 42;
+
+    register_compressor(CODEC_ID, Arc::new(RleCompressor));
+
+    let folder = tempfile::tempdir()?;
+    let value = "a".repeat(10_000);
+
+    let config = Config::new(&folder, SequenceNumberCounter::default())
+        .data_block_compression_policy(CompressionPolicy::all(CompressionType::Custom(CODEC_ID)))
+        .index_block_compression_policy(CompressionPolicy::all(CompressionType::Custom(CODEC_ID)));
+
+    {
+        let tree = config.clone().open()?;
+
+        for x in 0..100u64 {
+            tree.insert(x.to_be_bytes(), &value, x);
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: 100x 10K values compress to a few bytes each
+        assert!(tree.disk_space() < 100_000);
+    }
+
+    {
+        let tree = config.open()?;
+
+        assert_eq!(100, tree.len(u64::MAX, None)?);
+        assert_eq!(
+            Some(value.as_bytes().into()),
+            tree.get(5u64.to_be_bytes(), u64::MAX)?,
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_custom_compression() -> lsm_tree::Result<()> {
+    const CODEC_ID: u8 = 43;
+
+    register_compressor(CODEC_ID, Arc::new(RleCompressor));
+
+    let folder = tempfile::tempdir()?;
+    let value = "a".repeat(10_000);
+
+    let config = Config::new(&folder, SequenceNumberCounter::default()).with_kv_separation(Some(
+        KvSeparationOptions::default()
+            .separation_threshold(1)
+            .compression(CompressionType::Custom(CODEC_ID)),
+    ));
+
+    {
+        let tree = config.clone().open()?;
+
+        tree.insert("a", &value, 0);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.blob_file_count());
+    }
+
+    {
+        let tree = config.open()?;
+        assert_eq!(Some(value.as_bytes().into()), tree.get("a", u64::MAX)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_compression_change_relocation() -> lsm_tree::Result<()> {
+    const CODEC_ID: u8 = 44;
+
+    register_compressor(CODEC_ID, Arc::new(RleCompressor));
+
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let config = |compression| {
+        Config::new(&folder, seqno.clone()).with_kv_separation(Some(
+            KvSeparationOptions::default()
+                .separation_threshold(1)
+                .compression(compression)
+                .staleness_threshold(0.000_001)
+                .age_cutoff(1.0),
+        ))
+    };
+
+    {
+        let tree = config(CompressionType::None).open()?;
+
+        for key in ["a", "b", "c"] {
+            tree.insert(key, key.repeat(10_000), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Blob files written before the change stay uncompressed
+    let tree = config(CompressionType::Custom(CODEC_ID)).open()?;
+
+    for key in ["d", "e", "f"] {
+        tree.insert(key, key.repeat(10_000), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    tree.remove("a", seqno.next());
+    tree.remove("d", seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.blob_file_count());
+
+    // NOTE: Both blob files are relocated into a single, compressed blob file
+    for _ in 0..2 {
+        tree.major_compact(u64::MAX, u64::MAX)?;
+    }
+    assert_eq!(1, tree.blob_file_count());
+
+    let blob_file_size = tree
+        .current_version()
+        .blob_files
+        .iter()
+        .map(|blob_file| Ok(std::fs::metadata(blob_file.path())?.len()))
+        .sum::<lsm_tree::Result<u64>>()?;
+    assert!(blob_file_size < 10_000);
+
+    for key in ["b", "c", "e", "f"] {
+        assert_eq!(
+            Some(key.repeat(10_000).as_bytes().into()),
+            tree.get(key, u64::MAX)?,
+        );
+    }
+    assert!(!tree.contains_key("a", u64::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_custom_compression_unregistered() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .data_block_compression_policy(CompressionPolicy::all(CompressionType::Custom(255)))
+        .open()?;
+
+    tree.insert("a", "abc", 0);
+    assert!(tree.flush_active_memtable(0).is_err());
+
+    Ok(())
+}
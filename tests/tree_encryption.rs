@@ -0,0 +1,274 @@
+use lsm_tree::{
+    AbstractTree, AnyTree, Config, Encryptor, KvSeparationOptions, SequenceNumberCounter,
+};
+use std::{
+    path::Path,
+    sync::{Arc, RwLock},
+};
+use test_log::test;
+
+const SECRET: &[u8] = b"top-secret";
+
+/// Insecure encryptor that XORs all bytes with a key byte
+///
+/// Like a real encryptor, the ID of the key that was used is prepended to the
+/// ciphertext, so data written with older keys can still be decrypted.
+#[derive(Debug)]
+struct XorEncryptor {
+    /// Key ID -> key, the last key is the current one
+    keys: RwLock<Vec<(u8, u8)>>,
+}
+
+impl XorEncryptor {
+    fn new(key_id: u8, key: u8) -> Self {
+        Self {
+            keys: RwLock::new(vec![(key_id, key)]),
+        }
+    }
+
+    fn add_key(&self, key_id: u8, key: u8) {
+        self.keys.write().unwrap().push((key_id, key));
+    }
+
+    fn retire_key(&self, key_id: u8) {
+        self.keys.write().unwrap().retain(|(id, _)| *id != key_id);
+    }
+}
+
+impl Encryptor for XorEncryptor {
+    fn encrypt(&self, plaintext: &[u8]) -> std::io::Result<Vec<u8>> {
+        let (key_id, key) = *self.keys.read().unwrap().last().unwrap();
+
+        Ok(std::iter::once(key_id)
+            .chain(plaintext.iter().map(|byte| byte ^ key))
+            .collect())
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> std::io::Result<Vec<u8>> {
+        let (key_id, ciphertext) = ciphertext
+            .split_first()
+            .ok_or_else(|| std::io::Error::other("ciphertext is empty"))?;
+
+        let key = self
+            .keys
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(id, _)| id == key_id)
+            .map(|(_, key)| *key)
+            .ok_or_else(|| std::io::Error::other("unknown key"))?;
+
+        Ok(ciphertext.iter().map(|byte| byte ^ key).collect())
+    }
+}
+
+fn contains_plaintext(path: &Path) -> lsm_tree::Result<bool> {
+    for dirent in std::fs::read_dir(path)? {
+        let path = dirent?.path();
+
+        let found = if path.is_dir() {
+            contains_plaintext(&path)?
+        } else {
+            std::fs::read(&path)?
+                .windows(SECRET.len())
+                .any(|window| window == SECRET)
+        };
+
+        if found {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+#[test]
+fn tree_encryption_roundtrip() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let encryptor = Arc::new(XorEncryptor::new(0, 0xAB));
+
+    let config = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .with_encryption(Some(encryptor.clone()));
+
+    {
+        let tree = config.clone().open()?;
+
+        for x in 0..100u64 {
+            tree.insert([SECRET, &x.to_be_bytes()].concat(), SECRET, x);
+        }
+        tree.flush_active_memtable(0)?;
+
+        tree.insert("a", SECRET, 100);
+
+        assert!(!contains_plaintext(folder.path())?);
+    }
+
+    {
+        let AnyTree::Standard(tree) = config.open()? else {
+            unreachable!();
+        };
+
+        // NOTE: The unflushed write is replayed from the journal
+        assert_eq!(101, tree.len(u64::MAX, None)?);
+        assert_eq!(
+            Some(SECRET.into()),
+            tree.get([SECRET, &5u64.to_be_bytes()].concat(), u64::MAX)?,
+        );
+        assert_eq!(Some(SECRET.into()), tree.get("a", u64::MAX)?);
+
+        // NOTE: The unflushed write is still in the journal
+        let events = tree
+            .cdc_reader(100)?
+            .collect::<lsm_tree::Result<Vec<_>>>()?;
+        assert_eq!(1, events.len());
+        assert_eq!(SECRET, &*events[0].value);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_encryption_wrong_key() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .with_encryption(Some(Arc::new(XorEncryptor::new(0, 0xAB))))
+            .open()?;
+
+        tree.insert("a", SECRET, 0);
+        tree.flush_active_memtable(0)?;
+    }
+
+    let result = Config::new(&folder, SequenceNumberCounter::default())
+        .with_encryption(Some(Arc::new(XorEncryptor::new(1, 0xCD))))
+        .open();
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn tree_encryption_key_rotation() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let encryptor = Arc::new(XorEncryptor::new(0, 0xAB));
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_encryption(Some(encryptor.clone()))
+        .open()?;
+
+    tree.insert("a", SECRET, 0);
+    tree.flush_active_memtable(0)?;
+
+    encryptor.add_key(1, 0xCD);
+
+    tree.insert("b", SECRET, 1);
+    tree.flush_active_memtable(1)?;
+    assert_eq!(2, tree.table_count());
+
+    // NOTE: Major compaction rewrites all tables with the new key
+    tree.major_compact(u64::MAX, 2)?;
+    assert_eq!(1, tree.table_count());
+
+    encryptor.retire_key(0);
+    drop(tree);
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_encryption(Some(encryptor))
+        .open()?;
+
+    assert_eq!(Some(SECRET.into()), tree.get("a", u64::MAX)?);
+    assert_eq!(Some(SECRET.into()), tree.get("b", u64::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_encryption() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let encryptor = Arc::new(XorEncryptor::new(0, 0xAB));
+    let big_value = SECRET.repeat(1_000);
+
+    let config = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .with_encryption(Some(encryptor.clone()));
+
+    {
+        let tree = config.clone().open()?;
+
+        tree.insert(SECRET, &big_value, 0);
+        tree.insert("b", &big_value, 1);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.blob_file_count());
+
+        assert!(!contains_plaintext(folder.path())?);
+
+        assert_eq!(Some(big_value.clone().into()), tree.get(SECRET, u64::MAX)?);
+    }
+
+    {
+        let tree = config.open()?;
+
+        assert_eq!(
+            vec![
+                Some(big_value.clone().into()),
+                Some(big_value.clone().into())
+            ],
+            tree.multi_get([b"b".as_slice(), SECRET], u64::MAX)?,
+        );
+
+        assert_eq!(Some(big_value.clone().into()), tree.get(SECRET, u64::MAX)?);
+        assert_eq!(Some(big_value.into()), tree.get("b", u64::MAX)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_encryption_key_rotation() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let encryptor = Arc::new(XorEncryptor::new(0, 0xAB));
+
+    let big_value = b"neptune!".repeat(128_000);
+    let new_big_value = b"winter!".repeat(128_000);
+
+    let config = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default()
+                .compression(lsm_tree::CompressionType::None)
+                .age_cutoff(1.0),
+        ))
+        .with_encryption(Some(encryptor.clone()));
+
+    {
+        let tree = config.clone().open()?;
+
+        tree.insert("big", &big_value, 0);
+        tree.insert("big2", &big_value, 0);
+        tree.flush_active_memtable(0)?;
+
+        encryptor.add_key(1, 0xCD);
+
+        tree.insert("big", &new_big_value, 1);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(2, tree.blob_file_count());
+
+        // NOTE: The second compaction relocates "big2" into a new blob file,
+        // re-encrypting it with the new key
+        tree.major_compact(64_000_000, 1_000)?;
+        tree.major_compact(64_000_000, 1_000)?;
+        assert_eq!(2, tree.blob_file_count());
+    }
+
+    encryptor.retire_key(0);
+
+    {
+        let tree = config.open()?;
+
+        assert_eq!(Some(new_big_value.into()), tree.get("big", u64::MAX)?);
+        assert_eq!(Some(big_value.into()), tree.get("big2", u64::MAX)?);
+    }
+
+    Ok(())
+}
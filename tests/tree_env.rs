@@ -0,0 +1,43 @@
+use lsm_tree::{AbstractTree, Config, Env, SeqNo, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+#[test]
+fn tree_env_shared_cache() -> lsm_tree::Result<()> {
+    let env = Env::new(/* 1 MiB */ 1_024 * 1_024, 64);
+    let seqno = SequenceNumberCounter::default();
+
+    let folder_a = tempfile::tempdir()?;
+    let folder_b = tempfile::tempdir()?;
+
+    let trees = [
+        Config::new(&folder_a, seqno.clone())
+            .with_env(&env)
+            .open()?,
+        Config::new(&folder_b, seqno.clone())
+            .with_env(&env.clone())
+            .open()?,
+    ];
+
+    for tree in &trees {
+        assert!(Arc::ptr_eq(env.cache(), &tree.tree_config().cache));
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(format!("{x:0>4}"), "a".repeat(100), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let size_before = env.cache().size();
+
+    assert!(trees[0].get("0042", SeqNo::MAX)?.is_some());
+    let size_after_first = env.cache().size();
+    assert!(size_after_first > size_before);
+
+    assert!(trees[1].get("0042", SeqNo::MAX)?.is_some());
+    assert!(env.cache().size() > size_after_first);
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use lsm_tree::{
+    AbstractTree, Config, FileHandle, Filesystem, KvSeparationOptions, SequenceNumberCounter,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use test_log::test;
+
+/// Fails the test on any file access
+#[derive(Debug)]
+struct NoFilesystem;
+
+impl Filesystem for NoFilesystem {
+    fn open(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        panic!("unexpected open of {}", path.display());
+    }
+
+    fn open_writable(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        panic!("unexpected open of {}", path.display());
+    }
+
+    fn create_new(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        panic!("unexpected creation of {}", path.display());
+    }
+
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        panic!("unexpected creation of {}", path.display());
+    }
+
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        panic!("unexpected listing of {}", path.display());
+    }
+
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        panic!("unexpected removal of {}", path.display());
+    }
+
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        panic!("unexpected removal of {}", path.display());
+    }
+
+    fn rename(&self, from: &Path, _: &Path) -> std::io::Result<()> {
+        panic!("unexpected rename of {}", from.display());
+    }
+
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        panic!("unexpected stat of {}", path.display());
+    }
+
+    fn is_dir(&self, path: &Path) -> std::io::Result<bool> {
+        panic!("unexpected stat of {}", path.display());
+    }
+
+    fn sync_directory(&self, path: &Path) -> std::io::Result<()> {
+        panic!("unexpected sync of {}", path.display());
+    }
+}
+
+fn ephemeral_config(seqno: SequenceNumberCounter) -> Config {
+    Config::ephemeral(seqno).with_filesystem(Arc::new(NoFilesystem))
+}
+
+#[test]
+fn tree_ephemeral_simple() -> lsm_tree::Result<()> {
+    let seqno = SequenceNumberCounter::default();
+    let tree = ephemeral_config(seqno.clone()).use_journal(true).open()?;
+
+    for key in 0..100u32 {
+        tree.insert(key.to_be_bytes(), "abc", seqno.next());
+    }
+    assert!(tree.flush_active_memtable(0)?.is_none());
+
+    for key in 0..50u32 {
+        tree.remove(key.to_be_bytes(), seqno.next());
+    }
+    assert!(tree.flush_active_memtable(0)?.is_none());
+
+    tree.insert(0u32.to_be_bytes(), "def", seqno.next());
+
+    let read_seqno = seqno.get();
+    assert_eq!(0, tree.table_count());
+    assert_eq!(51, tree.len(read_seqno, None)?);
+    assert_eq!(
+        Some("def".as_bytes().into()),
+        tree.get(0u32.to_be_bytes(), read_seqno)?
+    );
+    assert_eq!(None, tree.get(1u32.to_be_bytes(), read_seqno)?);
+    assert_eq!(
+        Some("abc".as_bytes().into()),
+        tree.get(99u32.to_be_bytes(), read_seqno)?,
+    );
+    assert_eq!(
+        10,
+        tree.range(50u32.to_be_bytes()..60u32.to_be_bytes(), read_seqno, None)
+            .count(),
+    );
+
+    // NOTE: Sealed memtables are merged into one
+    assert_eq!(1, tree.sealed_memtable_count());
+
+    tree.major_compact(u64::MAX, read_seqno)?;
+    tree.drop_range::<&[u8], _>(..)?;
+    assert_eq!(51, tree.len(seqno.get(), None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_ephemeral_mvcc() -> lsm_tree::Result<()> {
+    let seqno = SequenceNumberCounter::default();
+    let tree = ephemeral_config(seqno.clone()).open()?;
+
+    tree.insert("a", "v0", seqno.next());
+    let snapshot_seqno = seqno.get();
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", "v1", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Old versions are kept for snapshots above the watermark
+    assert_eq!(Some("v0".as_bytes().into()), tree.get("a", snapshot_seqno)?);
+    assert_eq!(Some("v1".as_bytes().into()), tree.get("a", seqno.get())?);
+
+    tree.insert("a", "v2", seqno.next());
+    tree.flush_active_memtable(seqno.get())?;
+
+    assert_eq!(Some("v2".as_bytes().into()), tree.get("a", seqno.get())?);
+
+    // NOTE: Versions below the watermark are dropped when merging memtables
+    assert_eq!(1, tree.approximate_len());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_ephemeral() -> lsm_tree::Result<()> {
+    let tree = ephemeral_config(SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    let big_value = "a".repeat(10_000);
+
+    tree.insert("a", &big_value, 0);
+    tree.insert("b", "abc", 1);
+    assert!(tree.flush_active_memtable(0)?.is_none());
+
+    assert_eq!(0, tree.blob_file_count());
+    assert_eq!(Some(big_value.as_bytes().into()), tree.get("a", 2)?);
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("b", 2)?);
+    assert_eq!(2, tree.len(2, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use lsm_tree::{
+    AbstractTree, Config, ExportFormat, KvSeparationOptions, SeqNo, SequenceNumberCounter,
+};
+use std::io::BufWriter;
+use test_log::test;
+
+#[test]
+fn tree_export_csv() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    tree.insert("a", "hello", 0);
+    tree.insert("b", "hello, \"world\"", 1);
+    tree.insert("c", "abc", 2);
+    tree.flush_active_memtable(0)?;
+    tree.insert("c", "def", 3);
+
+    let mut csv = vec![];
+    let count = tree.export::<&[u8], _, _>(.., ExportFormat::Csv, SeqNo::MAX, &mut csv)?;
+    assert_eq!(3, count);
+    assert_eq!(
+        "key,value\na,hello\nb,\"hello, \"\"world\"\"\"\nc,def\n",
+        std::str::from_utf8(&csv).unwrap(),
+    );
+
+    // Exports a snapshot
+    let mut csv = vec![];
+    tree.export("b".., ExportFormat::Csv, 3, &mut csv)?;
+    assert_eq!(
+        "key,value\nb,\"hello, \"\"world\"\"\"\nc,abc\n",
+        std::str::from_utf8(&csv).unwrap(),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_export_csv_hex() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    tree.insert([0xFF, 0x00], [0xAB], 0);
+
+    let mut csv = vec![];
+    assert!(matches!(
+        tree.export::<&[u8], _, _>(.., ExportFormat::Csv, SeqNo::MAX, &mut csv),
+        Err(lsm_tree::Error::Utf8(_))
+    ));
+
+    let mut csv = vec![];
+    tree.export::<&[u8], _, _>(.., ExportFormat::CsvHex, SeqNo::MAX, &mut csv)?;
+    assert_eq!("key,value\nff00,ab\n", std::str::from_utf8(&csv).unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn tree_export_sharded() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let export_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    for x in 0..1_000u64 {
+        tree.insert(format!("{x:04}"), format!("value{x}"), x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let count = tree.export_sharded(
+        &["0250".into(), "0500".into(), "0750".into()],
+        ExportFormat::Csv,
+        SeqNo::MAX,
+        |idx| {
+            let file = std::fs::File::create(export_folder.path().join(format!("{idx}.csv")))?;
+            Ok(BufWriter::new(file))
+        },
+    )?;
+    assert_eq!(1_000, count);
+
+    for idx in 0..4 {
+        let csv = std::fs::read_to_string(export_folder.path().join(format!("{idx}.csv")))?;
+        let mut lines = csv.lines();
+
+        assert_eq!(Some("key,value"), lines.next());
+        assert_eq!(250, lines.clone().count());
+        assert_eq!(
+            Some(format!("{:04},value{}", idx * 250, idx * 250).as_str()),
+            lines.next(),
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "parquet")]
+fn tree_export_parquet() -> lsm_tree::Result<()> {
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::RowAccessor,
+    };
+
+    let folder = tempfile::tempdir()?;
+    let export_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    for x in 0..100u64 {
+        tree.insert(format!("{x:04}"), x.to_be_bytes(), x);
+    }
+    tree.flush_active_memtable(0)?;
+    tree.insert([0xFF], [0xAB], 100);
+
+    let path = export_folder.path().join("export.parquet");
+
+    let count = tree.export::<&[u8], _, _>(
+        ..,
+        ExportFormat::Parquet,
+        SeqNo::MAX,
+        BufWriter::new(std::fs::File::create(&path)?),
+    )?;
+    assert_eq!(101, count);
+
+    let reader = SerializedFileReader::new(std::fs::File::open(&path)?).unwrap();
+    assert_eq!(101, reader.metadata().file_metadata().num_rows());
+
+    let rows = reader
+        .get_row_iter(None)
+        .unwrap()
+        .map(|row| {
+            let row = row.unwrap();
+            (
+                row.get_bytes(0).unwrap().data().to_vec(),
+                row.get_bytes(1).unwrap().data().to_vec(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(101, rows.len());
+    assert_eq!((b"0005".to_vec(), 5u64.to_be_bytes().to_vec()), rows[5]);
+    assert_eq!((vec![0xFF], vec![0xAB]), rows[100]);
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_export_tables() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let other_folder = tempfile::tempdir()?;
+    let export_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    for i in 0..1_000u32 {
+        tree.insert(format!("{i:0>4}"), "v".repeat(100), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.remove("0500", seqno.next());
+
+    // NOTE: Every table file contains ~10 KB of keys and values
+    let paths = tree.export_tables("0250".."0750", SeqNo::MAX, &export_folder, 10_000)?;
+    assert_eq!(6, paths.len());
+
+    let other_seqno = SequenceNumberCounter::default();
+    let other = Config::new(&other_folder, other_seqno.clone()).open()?;
+    other.ingest_tables(&paths, &other_seqno, &SequenceNumberCounter::default())?;
+
+    assert_eq!(6, other.table_count());
+    assert_eq!(499, other.len(SeqNo::MAX, None)?);
+    assert!(other.contains_key("0250", SeqNo::MAX)?);
+    assert!(!other.contains_key("0500", SeqNo::MAX)?);
+    assert!(!other.contains_key("0750", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_export_tables_empty() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let export_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    tree.insert("a", "a", 0);
+
+    let paths = tree.export_tables("b".., SeqNo::MAX, &export_folder, u64::MAX)?;
+    assert!(paths.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_export_tables() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let other_folder = tempfile::tempdir()?;
+    let export_folder = tempfile::tempdir()?;
+
+    let big = "a".repeat(1_000);
+
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    for i in 0..10u32 {
+        tree.insert(format!("{i:0>4}"), &big, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    let paths = tree.export_tables::<&str, _, _>(.., SeqNo::MAX, &export_folder, u64::MAX)?;
+    assert_eq!(1, paths.len());
+
+    // NOTE: The exported values do not depend on the blob files of the tree
+    drop(tree);
+    drop(folder);
+
+    let other_seqno = SequenceNumberCounter::default();
+    let other = Config::new(&other_folder, other_seqno.clone()).open()?;
+    other.ingest_tables(&paths, &other_seqno, &SequenceNumberCounter::default())?;
+
+    assert_eq!(10, other.len(SeqNo::MAX, None)?);
+    assert_eq!(Some(big.as_bytes().into()), other.get("0005", SeqNo::MAX)?);
+
+    Ok(())
+}
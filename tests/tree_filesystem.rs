@@ -0,0 +1,317 @@
+use lsm_tree::{
+    AbstractTree, Config, FileHandle, Filesystem, KvSeparationOptions, MemFilesystem, SeqNo,
+    SequenceNumberCounter, StdFilesystem,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed},
+        Arc, Mutex,
+    },
+};
+use test_log::test;
+
+/// Counts file accesses, and can fail file creation or renaming on demand
+#[derive(Debug, Default)]
+struct TestFilesystem {
+    opened: AtomicUsize,
+    created: AtomicUsize,
+    removed: AtomicUsize,
+    synced_directories: Mutex<Vec<PathBuf>>,
+    fail_create: AtomicBool,
+    fail_rename: AtomicBool,
+}
+
+impl Filesystem for TestFilesystem {
+    fn open(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        self.opened.fetch_add(1, Relaxed);
+        StdFilesystem.open(path)
+    }
+
+    fn open_writable(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        self.opened.fetch_add(1, Relaxed);
+        StdFilesystem.open_writable(path)
+    }
+
+    fn create_new(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        if self.fail_create.load(Relaxed) {
+            return Err(std::io::Error::other("injected fault"));
+        }
+        self.created.fetch_add(1, Relaxed);
+        StdFilesystem.create_new(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        StdFilesystem.create_dir_all(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        StdFilesystem.read_dir(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        self.removed.fetch_add(1, Relaxed);
+        StdFilesystem.remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        StdFilesystem.remove_dir(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        if self.fail_rename.load(Relaxed) {
+            return Err(std::io::Error::other("injected fault"));
+        }
+        StdFilesystem.rename(from, to)
+    }
+
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        StdFilesystem.exists(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> std::io::Result<bool> {
+        StdFilesystem.is_dir(path)
+    }
+
+    fn sync_directory(&self, path: &Path) -> std::io::Result<()> {
+        self.synced_directories
+            .lock()
+            .expect("lock is poisoned")
+            .push(path.into());
+        StdFilesystem.sync_directory(path)
+    }
+}
+
+#[test]
+fn tree_custom_filesystem() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let fs = Arc::new(TestFilesystem::default());
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .with_filesystem(fs.clone())
+            .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+            .use_journal(true)
+            .open()?;
+
+        for seqno in 0..10 {
+            tree.insert(seqno.to_string(), "abc", seqno);
+        }
+        tree.flush_active_memtable(0)?;
+
+        for seqno in 10..20 {
+            tree.insert((seqno % 10).to_string(), "def", seqno);
+        }
+        tree.flush_active_memtable(0)?;
+
+        tree.major_compact(u64::MAX, 20)?;
+        assert_eq!(1, tree.table_count());
+    }
+
+    // NOTE: Manifest, versions, tables, blob files, journal files...
+    assert!(fs.created.load(Relaxed) > 0);
+
+    // NOTE: Compacted tables are deleted
+    assert!(fs.removed.load(Relaxed) > 0);
+
+    let opened_before = fs.opened.load(Relaxed);
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_filesystem(fs.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    assert_eq!(10, tree.len(21, None)?);
+    assert_eq!(Some("def".as_bytes().into()), tree.get("5", 21)?);
+
+    // NOTE: Recovery and blob reads go through the filesystem as well
+    assert!(fs.opened.load(Relaxed) > opened_before);
+
+    Ok(())
+}
+
+#[test]
+fn tree_mem_filesystem() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path().join("db");
+    let fs = Arc::new(MemFilesystem::default());
+
+    {
+        let tree = Config::new(&path, SequenceNumberCounter::default())
+            .with_filesystem(fs.clone())
+            .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+            .use_journal(true)
+            .open()?;
+
+        for seqno in 0..10 {
+            tree.insert(seqno.to_string(), "abc", seqno);
+        }
+        tree.flush_active_memtable(0)?;
+
+        for seqno in 10..20 {
+            tree.insert((seqno % 10).to_string(), "def", seqno);
+        }
+        tree.flush_active_memtable(0)?;
+
+        tree.major_compact(u64::MAX, 20)?;
+        assert_eq!(1, tree.table_count());
+
+        // NOTE: Only in the journal
+        tree.insert("x", "ghi", 20);
+
+        // NOTE: The tree folder is locked in memory as well
+        assert!(matches!(
+            Config::new(&path, SequenceNumberCounter::default())
+                .with_filesystem(fs.clone())
+                .open(),
+            Err(lsm_tree::Error::AlreadyLocked),
+        ));
+    }
+
+    // NOTE: Nothing is written to disk
+    assert!(!path.try_exists()?);
+    assert!(fs.exists(&path.join("manifest"))?);
+
+    let tree = Config::new(&path, SequenceNumberCounter::default())
+        .with_filesystem(fs.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .use_journal(true)
+        .open()?;
+
+    assert_eq!(11, tree.len(21, None)?);
+    assert_eq!(Some("def".as_bytes().into()), tree.get("5", 21)?);
+    assert_eq!(Some("ghi".as_bytes().into()), tree.get("x", 21)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_filesystem_fault_injection() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let fs = Arc::new(TestFilesystem::default());
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_filesystem(fs.clone())
+        .open()?;
+
+    tree.insert("a", "abc", 0);
+
+    fs.fail_create.store(true, Relaxed);
+    assert!(tree.flush_active_memtable(0).is_err());
+    assert_eq!(0, tree.table_count());
+
+    fs.fail_create.store(false, Relaxed);
+    tree.insert("b", "abc", 1);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.table_count());
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("b", 2)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_filesystem_journal_poisoned() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let fs = Arc::new(TestFilesystem::default());
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .with_filesystem(fs.clone())
+            .use_journal(true)
+            .open()?;
+
+        tree.insert("a", "abc", 0);
+
+        // NOTE: Rotating the journal fails, so the tree refuses all further writes
+        fs.fail_create.store(true, Relaxed);
+        assert!(tree.flush_active_memtable(0).is_err());
+        fs.fail_create.store(false, Relaxed);
+
+        assert!(matches!(
+            tree.try_insert("b", "abc", 1),
+            Err(lsm_tree::Error::Poisoned),
+        ));
+        assert!(matches!(
+            tree.try_remove("a", 1),
+            Err(lsm_tree::Error::Poisoned),
+        ));
+        assert!(matches!(
+            tree.try_remove_range("a".."b", 1),
+            Err(lsm_tree::Error::Poisoned),
+        ));
+        assert!(!tree.contains_key("b", 2)?);
+
+        let mut batch = tree.batch();
+        batch.insert("c", "abc");
+        assert!(matches!(batch.commit(2), Err(lsm_tree::Error::Poisoned)));
+        assert!(!tree.contains_key("c", 3)?);
+
+        assert_eq!(Some("abc".as_bytes().into()), tree.get("a", 3)?);
+    }
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_filesystem(fs.clone())
+        .use_journal(true)
+        .open()?;
+
+    assert!(tree.contains_key("a", SeqNo::MAX)?);
+    tree.insert("b", "abc", 1);
+    assert!(tree.contains_key("b", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_flush_crash_before_publish() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let blobs_folder = folder.path().join("blobs");
+    let tables_folder = folder.path().join("tables");
+    let fs = Arc::new(TestFilesystem::default());
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .with_filesystem(fs.clone())
+            .with_kv_separation(Some(KvSeparationOptions::default()))
+            .open()?;
+
+        tree.insert("a", "a".repeat(10_000), 0);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.blob_file_count());
+
+        // NOTE: Blob files need to be durable before a version may reference them
+        assert!(fs
+            .synced_directories
+            .lock()
+            .expect("lock is poisoned")
+            .iter()
+            .any(|path| path.starts_with(&blobs_folder)));
+
+        // NOTE: Simulate a crash after writing the table and blob file,
+        // but before publishing the new version
+        tree.insert("b", "b".repeat(10_000), 1);
+        fs.fail_rename.store(true, Relaxed);
+        assert!(tree.flush_active_memtable(0).is_err());
+        assert_eq!(1, tree.blob_file_count());
+        assert_eq!(1, tree.table_count());
+    }
+
+    assert_eq!(2, std::fs::read_dir(&blobs_folder)?.count());
+    assert_eq!(2, std::fs::read_dir(&tables_folder)?.count());
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default()))
+        .open()?;
+
+    assert_eq!(1, tree.blob_file_count());
+    assert_eq!(1, tree.table_count());
+    assert_eq!(1, std::fs::read_dir(&blobs_folder)?.count());
+    assert_eq!(1, std::fs::read_dir(&tables_folder)?.count());
+
+    assert_eq!(
+        Some("a".repeat(10_000).as_bytes().into()),
+        tree.get("a", 2)?
+    );
+    assert!(!tree.contains_key("b", 2)?);
+
+    Ok(())
+}
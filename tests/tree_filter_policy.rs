@@ -0,0 +1,77 @@
+use lsm_tree::{config::FilterPolicy, AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn fill_and_flush(tree: &lsm_tree::AnyTree) -> lsm_tree::Result<()> {
+    for x in 0..ITEM_COUNT {
+        tree.insert((x * 2).to_be_bytes(), "a", x);
+    }
+    tree.flush_active_memtable(0)?;
+    Ok(())
+}
+
+#[test]
+fn tree_filter_policy_mixed() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let filtered = Config::new(folder.path().join("a"), SequenceNumberCounter::default()).open()?;
+    let unfiltered = Config::new(folder.path().join("b"), SequenceNumberCounter::default())
+        .filter_policy(FilterPolicy::disabled())
+        .open()?;
+
+    fill_and_flush(&filtered)?;
+    fill_and_flush(&unfiltered)?;
+
+    let may_exist_count = |tree: &lsm_tree::AnyTree| -> lsm_tree::Result<usize> {
+        let mut count = 0;
+
+        // NOTE: Odd keys were never written, but are inside the table's key range
+        for x in 0..(ITEM_COUNT - 1) {
+            if tree.key_may_exist((x * 2 + 1).to_be_bytes())? {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    };
+
+    assert!(may_exist_count(&filtered)? < 100);
+    assert_eq!(999, may_exist_count(&unfiltered)?);
+
+    for tree in [&filtered, &unfiltered] {
+        assert!(tree.contains_key(42u64.to_be_bytes(), SeqNo::MAX)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_filter_policy_per_level() -> lsm_tree::Result<()> {
+    use lsm_tree::config::{BloomConstructionPolicy, FilterPolicyEntry};
+
+    let folder = tempfile::tempdir()?;
+
+    // NOTE: Accurate filters in the small upper levels, cheaper filters in the last level
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .filter_policy(FilterPolicy::new(&[
+            FilterPolicyEntry::Bloom(BloomConstructionPolicy::FalsePositiveRate(0.0001)),
+            FilterPolicyEntry::Bloom(BloomConstructionPolicy::FalsePositiveRate(0.0001)),
+            FilterPolicyEntry::Bloom(BloomConstructionPolicy::BitsPerKey(2.0)),
+        ]))
+        .level_count(3)
+        .open()?;
+
+    fill_and_flush(&tree)?;
+    let accurate_filter_size = tree.filter_size();
+    assert!(accurate_filter_size > 0);
+
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(1, tree.table_count());
+
+    let cheap_filter_size = tree.filter_size();
+    assert!(cheap_filter_size > 0);
+    assert!(cheap_filter_size * 4 < accurate_filter_size);
+
+    Ok(())
+}
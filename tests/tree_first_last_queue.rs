@@ -0,0 +1,69 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_first_last_queue() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let standard = Config::new(folder.path().join("a"), SequenceNumberCounter::default()).open()?;
+    let blob = Config::new(folder.path().join("b"), SequenceNumberCounter::default())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().separation_threshold(1_000),
+        ))
+        .open()?;
+
+    for tree in [&standard, &blob] {
+        let mut seqno = 0;
+
+        // NOTE: Spread the queue over multiple tables and the memtable
+        for x in 0..300u64 {
+            tree.insert(x.to_be_bytes(), x.to_string().repeat(1_000), seqno);
+            seqno += 1;
+
+            if x % 100 == 99 {
+                tree.flush_active_memtable(0)?;
+            }
+        }
+        for x in 300..310u64 {
+            tree.insert(x.to_be_bytes(), x.to_string().repeat(1_000), seqno);
+            seqno += 1;
+        }
+
+        let (mut head, mut tail) = (0u64, 309u64);
+
+        while head <= tail {
+            let (key, value) = tree
+                .first_key_value(SeqNo::MAX, None)?
+                .expect("should exist");
+            assert_eq!(&head.to_be_bytes(), &*key);
+            assert_eq!(head.to_string().repeat(1_000).as_bytes(), &*value);
+            tree.remove(key, seqno);
+            seqno += 1;
+            head += 1;
+
+            if head > tail {
+                break;
+            }
+
+            let (key, _) = tree
+                .last_key_value(SeqNo::MAX, None)?
+                .expect("should exist");
+            assert_eq!(&tail.to_be_bytes(), &*key);
+            tree.remove(key, seqno);
+            seqno += 1;
+            tail -= 1;
+
+            if head % 50 == 0 {
+                tree.flush_active_memtable(0)?;
+            }
+            if head % 100 == 0 {
+                tree.major_compact(u64::MAX, seqno)?;
+            }
+        }
+
+        assert_eq!(None, tree.first_key_value(SeqNo::MAX, None)?);
+        assert_eq!(None, tree.last_key_value(SeqNo::MAX, None)?);
+    }
+
+    Ok(())
+}
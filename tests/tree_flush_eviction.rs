@@ -112,3 +112,73 @@ fn tree_flush_eviction_4() -> lsm_tree::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn tree_compaction_eviction_no_data_beneath() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path();
+
+    let tree = lsm_tree::Config::new(path, SequenceNumberCounter::default()).open()?;
+
+    tree.insert("a", "a", 0);
+    tree.remove("b", 1);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.tombstone_count());
+
+    // NOTE: Should evict tombstone because there is no data beneath L1
+    tree.compact(Arc::new(lsm_tree::compaction::PullDown(0, 1)), 0)?;
+    assert_eq!(1, tree.level_table_count(1).unwrap_or_default());
+    assert_eq!(0, tree.tombstone_count());
+    assert_eq!(1, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_eviction_data_beneath() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path();
+
+    let tree = lsm_tree::Config::new(path, SequenceNumberCounter::default()).open()?;
+
+    tree.insert("a", "a", 0);
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.level_table_count(6).unwrap_or_default());
+
+    tree.remove("a", 1);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Should not evict tombstone, because it deletes data in L6
+    tree.compact(Arc::new(lsm_tree::compaction::PullDown(0, 1)), 2)?;
+    assert_eq!(1, tree.level_table_count(1).unwrap_or_default());
+    assert_eq!(1, tree.tombstone_count());
+    assert_eq!(0, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_eviction_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path();
+
+    let tree = lsm_tree::Config::new(path, SequenceNumberCounter::default()).open()?;
+
+    tree.insert("a", "a", 0);
+    tree.remove("a", 1);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Should not evict tombstone, because a snapshot may still read the old value
+    tree.compact(Arc::new(lsm_tree::compaction::PullDown(0, 1)), 0)?;
+    assert_eq!(1, tree.tombstone_count());
+    assert_eq!(2, tree.approximate_len());
+    assert_eq!(None, tree.get("a", SeqNo::MAX)?);
+    assert_eq!(Some("a".as_bytes().into()), tree.get("a", 1)?);
+
+    // NOTE: Once the snapshot is gone, the tombstone and the value are evicted
+    tree.compact(Arc::new(lsm_tree::compaction::PullDown(1, 2)), 2)?;
+    assert_eq!(0, tree.table_count());
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use std::{path::Path, time::Duration};
+use test_log::test;
+
+fn list_files(folder: &Path) -> lsm_tree::Result<Vec<String>> {
+    let mut names = std::fs::read_dir(folder)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    names.sort();
+    Ok(names)
+}
+
+#[test]
+fn tree_freeze_files() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    for table in 0..4 {
+        for x in 0..10 {
+            tree.insert(format!("{table}:{x}"), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(4, tree.table_count());
+
+    let guard = tree.freeze_files();
+
+    let tables_before = list_files(&folder.path().join("tables"))?;
+    let root_before = list_files(folder.path())?;
+
+    let compaction = std::thread::spawn({
+        let tree = tree.clone();
+        move || tree.major_compact(u64::MAX, SeqNo::MAX)
+    });
+
+    // NOTE: Writes still work while frozen
+    tree.insert("5:0", "a", seqno.next());
+
+    std::thread::sleep(Duration::from_millis(250));
+
+    assert!(!compaction.is_finished());
+    assert_eq!(4, tree.table_count());
+    assert_eq!(tables_before, list_files(&folder.path().join("tables"))?);
+    assert_eq!(root_before, list_files(folder.path())?);
+
+    drop(guard);
+
+    compaction.join().expect("should join")?;
+    assert_eq!(1, tree.table_count());
+    assert_eq!(41, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_get_at_seqno() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let standard = Config::new(folder.path().join("a"), seqno.clone()).open()?;
+    let blob = Config::new(folder.path().join("b"), seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    for tree in [&standard, &blob] {
+        let before = seqno.get();
+
+        let v1 = seqno.next();
+        tree.insert("a", "v1", v1);
+        tree.flush_active_memtable(0)?;
+
+        let v2 = seqno.next();
+        tree.insert("a", "v2", v2);
+        tree.flush_active_memtable(0)?;
+
+        let deleted = seqno.next();
+        tree.remove("a", deleted);
+
+        let v4 = seqno.next();
+        tree.insert("a", "v4", v4);
+
+        assert_eq!(None, tree.get("a", before)?);
+        assert_eq!(Some("v1".as_bytes().into()), tree.get("a", v1 + 1)?);
+        assert_eq!(Some("v2".as_bytes().into()), tree.get("a", v2 + 1)?);
+        assert_eq!(None, tree.get("a", deleted + 1)?);
+        assert_eq!(Some("v4".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+        // NOTE: Versions visible at or above the threshold survive compaction
+        tree.flush_active_memtable(0)?;
+        tree.major_compact(u64::MAX, v1 + 1)?;
+
+        assert_eq!(Some("v2".as_bytes().into()), tree.get("a", v2 + 1)?);
+        assert_eq!(None, tree.get("a", deleted + 1)?);
+        assert_eq!(Some("v4".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+    }
+
+    Ok(())
+}
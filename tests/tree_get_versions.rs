@@ -0,0 +1,88 @@
+use lsm_tree::{
+    AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter, ValueType,
+};
+use test_log::test;
+
+#[test]
+fn tree_get_versions() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    assert!(tree.get_versions("a", usize::MAX)?.is_empty());
+
+    tree.insert("a", "v0", 0);
+    tree.insert("b", "b", 0);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", "v1", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", "v2", 2);
+    tree.rotate_memtable();
+
+    tree.remove("a", 3);
+    tree.insert("a", "v4", 4);
+
+    let versions = tree.get_versions("a", usize::MAX)?;
+    assert_eq!(
+        vec![
+            (4, ValueType::Value, "v4".as_bytes().into()),
+            (3, ValueType::Tombstone, "".as_bytes().into()),
+            (2, ValueType::Value, "v2".as_bytes().into()),
+            (1, ValueType::Value, "v1".as_bytes().into()),
+            (0, ValueType::Value, "v0".as_bytes().into()),
+        ],
+        versions,
+    );
+
+    assert_eq!(
+        vec![4, 3],
+        tree.get_versions("a", 2)?
+            .into_iter()
+            .map(|(seqno, _, _)| seqno)
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(1, tree.get_versions("b", usize::MAX)?.len());
+    assert!(tree.get_versions("c", usize::MAX)?.is_empty());
+
+    // NOTE: Compaction drops old versions (the sealed memtable is not flushed)
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(
+        vec![
+            (4, ValueType::Value, "v4".as_bytes().into()),
+            (2, ValueType::Value, "v2".as_bytes().into()),
+        ],
+        tree.get_versions("a", usize::MAX)?,
+    );
+
+    // NOTE: Range-deleted versions are hidden
+    tree.remove_range("a".."b", 5);
+    assert!(tree.get_versions("a", usize::MAX)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn tree_get_versions_blob() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    tree.insert("a", "v0", 0);
+    tree.flush_active_memtable(0)?;
+    tree.insert("a", "v1", 1);
+    tree.remove("a", 2);
+
+    assert_eq!(
+        vec![
+            (2, ValueType::Tombstone, "".as_bytes().into()),
+            (1, ValueType::Value, "v1".as_bytes().into()),
+            (0, ValueType::Value, "v0".as_bytes().into()),
+        ],
+        tree.get_versions("a", usize::MAX)?,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use lsm_tree::{
+    AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter, SstBuilder,
+};
+use std::path::{Path, PathBuf};
+use test_log::test;
+
+fn build(path: &Path, config: &Config, keys: std::ops::Range<u32>) -> lsm_tree::Result<PathBuf> {
+    let mut builder = SstBuilder::new(path, config)?;
+    for i in keys {
+        builder.insert(format!("{i:0>5}"), format!("v{i}"))?;
+    }
+    builder.finish()?;
+    Ok(path.to_path_buf())
+}
+
+#[test]
+fn tree_ingest_tables_disjoint() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+    let config = Config::new(&folder, seqno.clone());
+
+    let paths = [
+        build(&sst_folder.path().join("1.sst"), &config, 1_000..2_000)?,
+        build(&sst_folder.path().join("0.sst"), &config, 0..1_000)?,
+    ];
+
+    {
+        let tree = config.open()?;
+        tree.insert("x", "x", seqno.next());
+
+        tree.ingest_tables(&paths, &seqno, &visible_seqno)?;
+
+        // NOTE: The tables do not overlap the tree, so they are moved into the last level
+        assert_eq!(2, tree.level_table_count(6).unwrap_or_default());
+        assert_eq!(2_001, tree.len(SeqNo::MAX, None)?);
+        assert_eq!(
+            Some("v1500".as_bytes().into()),
+            tree.get("01500", SeqNo::MAX)?
+        );
+    }
+
+    // NOTE: The ingested tables do not depend on the input files
+    drop(sst_folder);
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    assert_eq!(3, tree.table_count());
+    assert_eq!(2_001, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_ingest_tables_overlapping() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+    let config = Config::new(&folder, seqno.clone());
+
+    let path = sst_folder.path().join("0.sst");
+    let mut builder = SstBuilder::new(&path, &config)?;
+    builder.insert("a", "new")?;
+    builder.remove("b")?;
+    builder.insert("c", "new")?;
+    builder.finish()?;
+
+    let tree = config.open()?;
+    tree.insert("a", "old", seqno.next());
+    tree.insert("b", "old", seqno.next());
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+
+    tree.ingest_tables(&[path], &seqno, &visible_seqno)?;
+
+    // NOTE: The table overlaps the tree, so it is rewritten into L0
+    assert_eq!(1, tree.level_table_count(0).unwrap_or_default());
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+    assert!(!tree.contains_key("b", SeqNo::MAX)?);
+    assert_eq!(Some("new".as_bytes().into()), tree.get("c", SeqNo::MAX)?);
+
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(2, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_ingest_tables_range_tombstone() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+    let config = Config::new(&folder, seqno.clone());
+
+    let path = build(&sst_folder.path().join("0.sst"), &config, 0..100)?;
+
+    let tree = config.open()?;
+    tree.insert("a", "a", seqno.next());
+    tree.remove_range("0".."1", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The range tombstone must not delete the ingested items
+    tree.ingest_tables(&[path], &seqno, &visible_seqno)?;
+    assert_eq!(101, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_ingest_tables_overlap_each_other() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+    let config = Config::new(&folder, seqno.clone());
+
+    let paths = [
+        build(&sst_folder.path().join("0.sst"), &config, 0..100)?,
+        build(&sst_folder.path().join("1.sst"), &config, 50..150)?,
+    ];
+
+    let tree = config.open()?;
+
+    assert!(matches!(
+        tree.ingest_tables(&paths, &seqno, &visible_seqno),
+        Err(lsm_tree::Error::InvalidIngestion(_)),
+    ));
+    assert_eq!(0, tree.table_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_ingest_tables_with_existing_ids() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    // NOTE: Every ingested table gets a new table ID, so ingesting the same file twice works
+    let path = build(&sst_folder.path().join("0.sst"), tree.tree_config(), 0..10)?;
+    tree.ingest_tables(&[&path], &seqno, &visible_seqno)?;
+
+    let path = build(&sst_folder.path().join("1.sst"), tree.tree_config(), 10..20)?;
+    tree.ingest_tables(&[&path], &seqno, &visible_seqno)?;
+    tree.ingest_tables(&[&path], &seqno, &visible_seqno)?;
+
+    assert_eq!(3, tree.table_count());
+    assert_eq!(20, tree.len(SeqNo::MAX, None)?);
+
+    let ids = tree
+        .tables()
+        .into_iter()
+        .map(|t| t.id)
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(3, ids.len());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_ingest_tables() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let sst_folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+    let visible_seqno = SequenceNumberCounter::default();
+    let config = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)));
+
+    let path = build(&sst_folder.path().join("0.sst"), &config, 0..100)?;
+
+    let tree = config.open()?;
+    tree.insert("a", "a".repeat(1_000), seqno.next());
+
+    tree.ingest_tables(&[path], &seqno, &visible_seqno)?;
+    assert_eq!(1, tree.blob_file_count());
+    assert_eq!(101, tree.len(SeqNo::MAX, None)?);
+    assert_eq!(Some("v5".as_bytes().into()), tree.get("00005", SeqNo::MAX)?);
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use lsm_tree::{
+    AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter, ValueType,
+};
+use test_log::test;
+
+#[test]
+fn tree_iter_internal() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let standard = Config::new(folder.path().join("a"), SequenceNumberCounter::default()).open()?;
+    let blob = Config::new(folder.path().join("b"), SequenceNumberCounter::default())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().separation_threshold(1_000),
+        ))
+        .open()?;
+
+    for tree in [&standard, &blob] {
+        tree.insert("a", "1", 0);
+        tree.insert("b", "2", 1);
+        tree.flush_active_memtable(0)?;
+        tree.insert("a", "3", 2);
+        tree.remove("b", 3);
+        tree.remove_weak("c", 4);
+
+        let items = tree
+            .iter_internal(SeqNo::MAX)
+            .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            vec![
+                (b"a".as_slice(), b"3".as_slice(), 2, ValueType::Value),
+                (b"a", b"1", 0, ValueType::Value),
+                (b"b", b"", 3, ValueType::Tombstone),
+                (b"b", b"2", 1, ValueType::Value),
+                (b"c", b"", 4, ValueType::WeakTombstone),
+            ],
+            items
+                .iter()
+                .map(|(k, v, seqno, vt)| (&**k, &**v, *seqno, *vt))
+                .collect::<Vec<_>>(),
+        );
+
+        // NOTE: Snapshot and range are respected, and iteration is double-ended
+        let items = tree
+            .raw_range("b"..="c", 4)
+            .rev()
+            .map(|item| item.map(|(_, _, seqno, _)| seqno))
+            .collect::<lsm_tree::Result<Vec<_>>>()?;
+        assert_eq!(vec![1, 3], items);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_iter_internal_indirection() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().separation_threshold(1_000),
+        ))
+        .open()?;
+
+    tree.insert("big", "a".repeat(2_000), 0);
+    tree.insert("small", "a", 1);
+    tree.flush_active_memtable(0)?;
+
+    let items = tree
+        .iter_internal(SeqNo::MAX)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(2, items.len());
+
+    let (_, value, _, value_type) = &items[0];
+    assert_eq!(ValueType::Indirection, *value_type);
+    assert!(value.len() < 2_000);
+
+    let (_, value, _, value_type) = &items[1];
+    assert_eq!(ValueType::Value, *value_type);
+    assert_eq!(b"a", &**value);
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use lsm_tree::{AbstractTree, Config, Durability, SeqNo, SequenceNumberCounter};
+use std::{path::Path, time::Duration};
+use test_log::test;
+
+fn journal_size(path: &Path) -> lsm_tree::Result<u64> {
+    let mut size = 0;
+
+    for entry in std::fs::read_dir(path.join("journal"))? {
+        size += entry?.metadata()?.len();
+    }
+
+    Ok(size)
+}
+
+fn open(path: &Path, durability: Durability) -> lsm_tree::Result<lsm_tree::AnyTree> {
+    Config::new(path, SequenceNumberCounter::default())
+        .use_journal(true)
+        .durability(durability)
+        .open()
+}
+
+#[test]
+fn tree_durability_none() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open(folder.path(), Durability::None)?;
+
+        tree.insert("a", "a", 0);
+
+        // NOTE: The write is still buffered in memory
+        assert_eq!(0, journal_size(folder.path())?);
+
+        tree.sync()?;
+        assert!(journal_size(folder.path())? > 0);
+
+        tree.insert("b", "b", 1);
+    }
+
+    // NOTE: The buffer is written out when the tree is dropped
+    let tree = open(folder.path(), Durability::None)?;
+    assert_eq!(2, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_durability_buffer() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open(folder.path(), Durability::Buffer)?;
+
+    tree.insert("a", "a", 0);
+    assert!(journal_size(folder.path())? > 0);
+
+    Ok(())
+}
+
+#[test]
+fn tree_durability_sync_every_write() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open(folder.path(), Durability::SyncEveryWrite)?;
+
+        tree.insert("a", "a", 0);
+        assert!(journal_size(folder.path())? > 0);
+    }
+
+    let tree = open(folder.path(), Durability::SyncEveryWrite)?;
+    assert!(tree.contains_key("a", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_durability_sync_interval() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = open(folder.path(), Durability::SyncInterval(10))?;
+
+        tree.insert("a", "a", 0);
+        assert!(journal_size(folder.path())? > 0);
+
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let tree = open(folder.path(), Durability::SyncInterval(10))?;
+    assert!(tree.contains_key("a", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_durability_sync_interval_zero() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    assert!(matches!(
+        open(folder.path(), Durability::SyncInterval(0)),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn tree_sync_without_journal() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    tree.insert("a", "a", 0);
+    tree.sync()?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_durability_group_commit() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .use_journal(true)
+            .durability(Durability::SyncEveryWrite)
+            .open()?;
+
+        std::thread::scope(|scope| {
+            for thread in 0..4u32 {
+                let tree = &tree;
+                let seqno = &seqno;
+
+                scope.spawn(move || {
+                    for i in 0..250u32 {
+                        tree.insert(format!("{thread}-{i:0>3}"), "v", seqno.next());
+                    }
+                });
+            }
+        });
+    }
+
+    let tree = open(folder.path(), Durability::SyncEveryWrite)?;
+    assert_eq!(1_000, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,261 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use std::path::Path;
+use test_log::test;
+
+fn journal_file_count(path: &Path) -> lsm_tree::Result<usize> {
+    Ok(std::fs::read_dir(path.join("journal"))?.count())
+}
+
+#[test]
+fn tree_journal_replay() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .use_journal(true)
+            .open()?;
+
+        tree.insert("a", "a", 0);
+        tree.insert("b", "b", 1);
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Not flushed, but journaled
+        tree.insert("a", "a2", 2);
+        tree.remove("b", 3);
+        tree.insert("c", "c", 4);
+    }
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?;
+
+    assert_eq!(1, tree.table_count());
+    assert!(tree.active_memtable_size() > 0);
+
+    assert_eq!(Some("a2".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+    assert!(!tree.contains_key("b", SeqNo::MAX)?);
+    assert_eq!(Some("c".as_bytes().into()), tree.get("c", SeqNo::MAX)?);
+    assert_eq!(Some("a".as_bytes().into()), tree.get("a", 2)?);
+    assert_eq!(2, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_journal_replay_twice() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .use_journal(true)
+            .open()?;
+
+        tree.insert("a", "a", 0);
+    }
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .use_journal(true)
+            .open()?;
+
+        assert!(tree.contains_key("a", SeqNo::MAX)?);
+        tree.insert("b", "b", 1);
+    }
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?;
+
+    assert_eq!(2, tree.len(SeqNo::MAX, None)?);
+
+    // NOTE: Once the replayed writes are flushed, their journal files are deleted
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, journal_file_count(folder.path())?);
+
+    drop(tree);
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?;
+
+    assert_eq!(0, tree.active_memtable_size());
+    assert_eq!(2, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_journal_replay_below_persisted_seqno() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let seqno = SequenceNumberCounter::default();
+
+        let tree = Config::new(&folder, seqno.clone())
+            .use_journal(true)
+            .open()?;
+
+        tree.insert("z", "z", seqno.next());
+
+        // NOTE: The ingested table holds a higher seqno than the journaled (unflushed) write
+        tree.ingest(
+            std::iter::once(("a".into(), "a".into())),
+            &seqno,
+            &SequenceNumberCounter::default(),
+        )?;
+        assert_eq!(1, tree.table_count());
+    }
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?;
+
+    assert_eq!(Some("z".as_bytes().into()), tree.get("z", SeqNo::MAX)?);
+    assert_eq!(Some("a".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_journal_replay_remove_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .use_journal(true)
+            .open()?;
+
+        tree.insert("a", "a", 0);
+        tree.insert("b", "b", 1);
+        tree.remove_range("a".."b", 2);
+        assert!(!tree.contains_key("a", SeqNo::MAX)?);
+    }
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?;
+
+    assert!(!tree.contains_key("a", SeqNo::MAX)?);
+    assert!(tree.contains_key("a", 2)?);
+    assert!(tree.contains_key("b", SeqNo::MAX)?);
+
+    // NOTE: The replayed range tombstone is flushed like any other
+    tree.flush_active_memtable(0)?;
+    assert!(!tree.contains_key("a", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_journal_replay_torn_write() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .use_journal(true)
+            .open()?;
+
+        tree.insert("a", "a", 0);
+        tree.insert("b", "b", 1);
+    }
+
+    // NOTE: Simulate a crash in the middle of the last write
+    let path = folder.path().join("journal").join("0");
+    let len = std::fs::metadata(&path)?.len();
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)?
+        .set_len(len - 3)?;
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?;
+
+    assert!(tree.contains_key("a", SeqNo::MAX)?);
+    assert!(!tree.contains_key("b", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_journal_replay() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let big = "a".repeat(1_000);
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+            .use_journal(true)
+            .open()?;
+
+        tree.insert("a", &big, 0);
+    }
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .use_journal(true)
+        .open()?;
+
+    assert_eq!(Some(big.as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+    assert_eq!(Some(big.as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_journal_replay_corrupted_file() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let journal_folder = folder.path().join("journal");
+
+    // NOTE: Every session writes into its own journal file
+    for (key, seqno) in [("a", 0), ("b", 1), ("c", 4)] {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .use_journal(true)
+            .open()?;
+
+        if key == "b" {
+            for seqno in 1..4 {
+                tree.insert(format!("b{seqno}"), "b".repeat(100), seqno);
+            }
+        } else {
+            tree.insert(key, key, seqno);
+        }
+    }
+
+    // NOTE: Corrupt the second write of the second file
+    let path = journal_folder.join("1");
+    let mut bytes = std::fs::read(&path)?;
+    let idx = bytes.len() / 2;
+    bytes[idx] ^= 0xFF;
+    std::fs::write(&path, bytes)?;
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .use_journal(true)
+            .open()?;
+
+        assert!(tree.contains_key("a", SeqNo::MAX)?);
+        assert!(tree.contains_key("b1", SeqNo::MAX)?);
+        assert!(!tree.contains_key("b2", SeqNo::MAX)?);
+        assert!(!tree.contains_key("b3", SeqNo::MAX)?);
+
+        // NOTE: The later write is discarded, even though it was not corrupted
+        assert!(!tree.contains_key("c", SeqNo::MAX)?);
+        assert!(journal_folder.join("2.corrupt").try_exists()?);
+
+        tree.insert("d", "d", 5);
+    }
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?;
+
+    assert!(tree.contains_key("b1", SeqNo::MAX)?);
+    assert!(!tree.contains_key("c", SeqNo::MAX)?);
+    assert!(tree.contains_key("d", SeqNo::MAX)?);
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use lsm_tree::{
+    AbstractTree, Config, FixedPrefixExtractor, KvSeparationOptions, SequenceNumberCounter,
+};
+use std::sync::Arc;
+use test_log::test;
+
+fn config(path: &std::path::Path) -> Config {
+    Config::new(path, SequenceNumberCounter::default())
+        .with_key_extractor(Some(Arc::new(FixedPrefixExtractor::new(4))))
+}
+
+#[test]
+fn tree_key_extractor() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = config(folder.path()).open()?;
+
+        for x in 0..100u32 {
+            tree.insert(format!("u{x:03}:name"), "a", x.into());
+            tree.insert(format!("u{x:03}:mail"), "b", x.into());
+        }
+        assert!(tree.derived_key_may_exist("u042")?);
+        assert!(!tree.derived_key_may_exist("u999")?);
+
+        tree.flush_active_memtable(0)?;
+        assert_eq!(0, tree.active_memtable_size());
+        assert!(tree.derived_key_may_exist("u042")?);
+        assert!(!tree.derived_key_may_exist("u999")?);
+
+        // NOTE: Deletes are not tracked, so derived keys may still exist
+        tree.remove("u042:name", 200);
+        tree.remove("u042:mail", 201);
+        tree.flush_active_memtable(0)?;
+        assert!(tree.derived_key_may_exist("u042")?);
+
+        tree.major_compact(u64::MAX, u64::MAX)?;
+        assert!(tree.derived_key_may_exist("u041")?);
+        assert!(!tree.derived_key_may_exist("u999")?);
+
+        // NOTE: Short keys are not in the extractor's domain
+        tree.insert("u99", "c", 300);
+        assert!(!tree.derived_key_may_exist("u99")?);
+    }
+
+    {
+        let tree = config(folder.path()).open()?;
+        assert!(tree.derived_key_may_exist("u001")?);
+        assert!(!tree.derived_key_may_exist("u999")?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_key_extractor_changed() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+        tree.insert("abcd:1", "a", 0);
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Without key extractor, we can not tell
+        assert!(tree.derived_key_may_exist("zzzz")?);
+    }
+
+    {
+        // NOTE: Table was written without key extractor, so it may contain anything
+        let tree = config(folder.path()).open()?;
+        assert!(tree.derived_key_may_exist("zzzz")?);
+
+        tree.major_compact(u64::MAX, u64::MAX)?;
+        assert!(tree.derived_key_may_exist("abcd")?);
+        assert!(!tree.derived_key_may_exist("zzzz")?);
+    }
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .with_key_extractor(Some(Arc::new(FixedPrefixExtractor::new(2))))
+            .open()?;
+
+        // NOTE: Table was written with a different key extractor
+        assert!(tree.derived_key_may_exist("zz")?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_key_extractor() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = config(folder.path())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    tree.insert("abcd:1", "a".repeat(1_000), 0);
+    tree.flush_active_memtable(0)?;
+
+    assert!(tree.derived_key_may_exist("abcd")?);
+    assert!(!tree.derived_key_may_exist("zzzz")?);
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SequenceNumberCounter};
+use tempfile::tempdir;
+use test_log::test;
+
+#[test]
+fn tree_key_may_exist() -> lsm_tree::Result<()> {
+    let folder = tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(folder, seqno.clone()).open()?;
+    assert!(!tree.key_may_exist("a")?);
+
+    tree.insert("a", "a", seqno.next());
+    tree.insert("b", "b", seqno.next());
+    assert!(tree.key_may_exist("a")?);
+    assert!(!tree.key_may_exist("c")?);
+
+    // NOTE: Tombstones in memtables are definite
+    tree.remove("b", seqno.next());
+    assert!(!tree.key_may_exist("b")?);
+
+    tree.flush_active_memtable(0)?;
+
+    for x in 0..1_000u64 {
+        tree.insert(format!("k:{x:0>4}"), "a", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    assert!(tree.key_may_exist("a")?);
+    assert!(tree.key_may_exist("k:0500")?);
+
+    // NOTE: Outside of any key range
+    assert!(!tree.key_may_exist("0")?);
+    assert!(!tree.key_may_exist("z")?);
+
+    // NOTE: Inside a key range, but rejected by the bloom filter (with rare false positives)
+    let false_positives = (0..1_000u64)
+        .filter(|x| {
+            tree.key_may_exist(format!("k:{x:0>4}:missing"))
+                .expect("should not fail")
+        })
+        .count();
+    assert!(false_positives < 50);
+
+    Ok(())
+}
+
+#[test]
+fn tree_key_may_exist_blob() -> lsm_tree::Result<()> {
+    let folder = tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(folder, seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    tree.insert("a", "a".repeat(1_000), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    assert!(tree.key_may_exist("a")?);
+    assert!(!tree.key_may_exist("b")?);
+
+    Ok(())
+}
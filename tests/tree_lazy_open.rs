@@ -0,0 +1,71 @@
+use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+const TABLE_COUNT: usize = 5;
+const ITEM_COUNT: usize = 100;
+
+#[test]
+fn tree_lazy_open() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone()).open()?;
+
+        for table in 0..TABLE_COUNT {
+            for x in 0..ITEM_COUNT {
+                tree.insert(format!("{table}:{x:0>3}"), "a", seqno.next());
+            }
+            tree.flush_active_memtable(0)?;
+        }
+
+        // NOTE: Tables that are written by the tree are loaded right away
+        assert!(tree.pinned_filter_size() > 0);
+        assert!(tree.pinned_block_index_size() > 0);
+    }
+
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    assert_eq!(TABLE_COUNT, tree.table_count());
+    assert_eq!(0, tree.pinned_filter_size());
+    assert_eq!(0, tree.pinned_block_index_size());
+
+    // NOTE: A point read only loads the table that contains the key
+    assert!(tree.contains_key("0:042", SeqNo::MAX)?);
+    let filter_size_after_read = tree.pinned_filter_size();
+    assert!(filter_size_after_read > 0);
+
+    tree.preload()?;
+    assert_eq!(
+        filter_size_after_read * TABLE_COUNT,
+        tree.pinned_filter_size()
+    );
+    assert!(tree.pinned_block_index_size() > 0);
+
+    assert_eq!(TABLE_COUNT * ITEM_COUNT, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_lazy_open_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone()).open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(format!("{x:0>3}"), "a", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    assert_eq!(0, tree.pinned_block_index_size());
+
+    assert_eq!(10, tree.range("010".."020", SeqNo::MAX, None).count());
+    assert_eq!(ITEM_COUNT, tree.iter(SeqNo::MAX, None).rev().count());
+    assert!(tree.pinned_block_index_size() > 0);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use lsm_tree::{
+    compaction::PullDown, AbstractTree, Config, KeyRange, KvSeparationOptions,
+    SequenceNumberCounter,
+};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn tree_levels() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    assert!(tree.levels().iter().all(|level| level.is_empty()));
+    assert_eq!(None, tree.levels()[0].key_range);
+    assert_eq!(None, tree.levels()[0].seqnos);
+
+    tree.insert("a", "abc", 0);
+    tree.insert("c", "abc", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("b", "abc", 2);
+    tree.remove("d", 3);
+    tree.flush_active_memtable(0)?;
+
+    let levels = tree.levels();
+    let l0 = &levels[0];
+    assert_eq!(2, l0.table_count);
+    assert_eq!(2, l0.run_count);
+    assert_eq!(4, l0.item_count);
+    assert_eq!(1, l0.tombstone_count);
+    assert_eq!(tree.disk_space(), l0.bytes);
+    assert_eq!(Some(KeyRange::new(("a".into(), "d".into()))), l0.key_range);
+    assert_eq!(Some((0, 3)), l0.seqnos);
+
+    tree.compact(Arc::new(PullDown(0, 6)), 0)?;
+
+    // NOTE: The tombstone is dropped in the last level
+    let levels = tree.levels();
+    assert!(levels[0].is_empty());
+    assert_eq!(1, levels[6].table_count);
+    assert_eq!(1, levels[6].run_count);
+    assert_eq!(tree.disk_space(), levels[6].bytes);
+    assert_eq!(0, levels[6].tombstone_count);
+    assert_eq!(
+        Some(KeyRange::new(("a".into(), "c".into()))),
+        levels[6].key_range,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_levels() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    tree.insert("a", "a".repeat(1_000), 0);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Only the index tree is reported, blob files are not part of any level
+    let l0 = &tree.levels()[0];
+    assert_eq!(1, l0.table_count);
+    assert!(l0.bytes < 1_000);
+
+    Ok(())
+}
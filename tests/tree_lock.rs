@@ -0,0 +1,50 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_lock_double_open() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let config = Config::new(&folder, SequenceNumberCounter::default());
+
+    let tree = config.clone().open()?;
+    tree.insert("a", "a", 0);
+    tree.flush_active_memtable(0)?;
+
+    assert!(matches!(
+        config.clone().open(),
+        Err(lsm_tree::Error::AlreadyLocked),
+    ));
+
+    // NOTE: Clones share the lock
+    let clone = tree.clone();
+    drop(tree);
+    assert!(matches!(
+        config.clone().open(),
+        Err(lsm_tree::Error::AlreadyLocked),
+    ));
+    drop(clone);
+
+    let tree = config.open()?;
+    assert_eq!(1, tree.len(u64::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_lock_double_open() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let config = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default()));
+
+    let tree = config.clone().open()?;
+
+    assert!(matches!(
+        config.clone().open(),
+        Err(lsm_tree::Error::AlreadyLocked),
+    ));
+
+    drop(tree);
+    config.open()?;
+
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use lsm_tree::{
+    AbstractTree, Config, Guard, KvSeparationOptions, MergeOperator, SequenceNumberCounter,
+    UserValue,
+};
+use std::sync::Arc;
+use test_log::test;
+
+#[derive(Debug)]
+struct Counter;
+
+impl MergeOperator for Counter {
+    fn merge(&self, _key: &[u8], older: &[u8], newer: &[u8]) -> UserValue {
+        (parse(older) + parse(newer)).to_be_bytes().into()
+    }
+}
+
+fn parse(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().expect("should be u64"))
+}
+
+fn config(path: &std::path::Path) -> Config {
+    Config::new(path, SequenceNumberCounter::default()).with_merge_operator(Some(Arc::new(Counter)))
+}
+
+#[test]
+fn tree_merge_operator_get() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = config(folder.path()).open()?;
+
+    // NOTE: Without a value, operands are folded starting at the oldest operand
+    tree.add_merge("a", 1u64.to_be_bytes(), 0);
+    tree.add_merge("a", 2u64.to_be_bytes(), 1);
+    assert_eq!(3, parse(&tree.get("a", 2)?.expect("should exist")));
+
+    tree.insert("b", 10u64.to_be_bytes(), 2);
+    tree.flush_active_memtable(0)?;
+    tree.add_merge("b", 5u64.to_be_bytes(), 3);
+    assert_eq!(15, parse(&tree.get("b", 4)?.expect("should exist")));
+
+    // NOTE: Snapshot reads only see older operands
+    assert_eq!(10, parse(&tree.get("b", 3)?.expect("should exist")));
+    assert_eq!(1, parse(&tree.get("a", 1)?.expect("should exist")));
+
+    // NOTE: Tombstones reset the chain
+    tree.remove("b", 4);
+    tree.add_merge("b", 7u64.to_be_bytes(), 5);
+    assert_eq!(7, parse(&tree.get("b", 6)?.expect("should exist")));
+    assert!(tree.contains_key("b", 6)?);
+    assert!(!tree.contains_key("b", 5)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_merge_operator_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = config(folder.path()).open()?;
+
+    let mut seqno = 0;
+    for round in 1..=3u64 {
+        for key in ["a", "b", "c"] {
+            tree.add_merge(key, round.to_be_bytes(), seqno);
+            seqno += 1;
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    tree.insert("b", 100u64.to_be_bytes(), seqno);
+    tree.add_merge("b", 1u64.to_be_bytes(), seqno + 1);
+    seqno += 2;
+
+    let expected = vec![("a", 6), ("b", 101), ("c", 6)];
+
+    let forwards = tree
+        .iter(seqno, None)
+        .map(|guard| {
+            let (k, v) = guard.into_inner()?;
+            Ok((String::from_utf8_lossy(&k).to_string(), parse(&v)))
+        })
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+    let mut backwards = tree
+        .iter(seqno, None)
+        .rev()
+        .map(|guard| {
+            let (k, v) = guard.into_inner()?;
+            Ok((String::from_utf8_lossy(&k).to_string(), parse(&v)))
+        })
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    backwards.reverse();
+
+    let expected = expected
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect::<Vec<_>>();
+
+    assert_eq!(expected, forwards);
+    assert_eq!(expected, backwards);
+
+    Ok(())
+}
+
+#[test]
+fn tree_merge_operator_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = config(folder.path()).open()?;
+
+        for seqno in 0..100 {
+            tree.add_merge("a", 1u64.to_be_bytes(), seqno);
+
+            if seqno % 10 == 9 {
+                tree.flush_active_memtable(0)?;
+            }
+        }
+        assert_eq!(10, tree.table_count());
+
+        // NOTE: Operands above the watermark stay visible to snapshots
+        tree.major_compact(u64::MAX, 50)?;
+        assert_eq!(100, parse(&tree.get("a", 100)?.expect("should exist")));
+        assert_eq!(60, parse(&tree.get("a", 60)?.expect("should exist")));
+
+        // NOTE: Chain is collapsed into a single value
+        tree.major_compact(u64::MAX, 100)?;
+        assert_eq!(1, tree.approximate_len());
+        assert_eq!(100, parse(&tree.get("a", 100)?.expect("should exist")));
+
+        tree.add_merge("a", 1u64.to_be_bytes(), 100);
+    }
+
+    {
+        let tree = config(folder.path()).open()?;
+        assert_eq!(100, parse(&tree.get("a", 101)?.expect("should exist")));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_merge_operator_missing() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    // NOTE: Without merge operator, the newest operand is read as value
+    tree.insert("a", 1u64.to_be_bytes(), 0);
+    tree.add_merge("a", 2u64.to_be_bytes(), 1);
+    assert_eq!(2, parse(&tree.get("a", 2)?.expect("should exist")));
+
+    assert!(matches!(
+        config(folder.path())
+            .with_kv_separation(Some(KvSeparationOptions::default()))
+            .open(),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Unreachable;
+
+impl MergeOperator for Unreachable {
+    fn merge(&self, _key: &[u8], _older: &[u8], _newer: &[u8]) -> UserValue {
+        panic!("should not merge");
+    }
+}
+
+#[test]
+fn tree_merge_operator_contains_key() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(folder.path(), SequenceNumberCounter::default())
+        .with_merge_operator(Some(Arc::new(Unreachable)))
+        .open()?;
+
+    tree.insert("a", 1u64.to_be_bytes(), 0);
+    tree.flush_active_memtable(0)?;
+    tree.add_merge("a", 2u64.to_be_bytes(), 1);
+    tree.add_merge("a", 3u64.to_be_bytes(), 2);
+
+    // NOTE: Existence checks do not need to fold operands
+    assert!(tree.contains_key("a", 3)?);
+    assert!(tree.contains_key("a", 1)?);
+    assert!(!tree.contains_key("a", 0)?);
+    assert!(!tree.contains_key("b", 3)?);
+
+    tree.remove("a", 3);
+    assert!(!tree.contains_key("a", 4)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_merge_operator_batch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = config(folder.path()).open()?;
+
+    tree.insert("a", 10u64.to_be_bytes(), 0);
+    tree.add_merge("c", 100u64.to_be_bytes(), 1);
+
+    // NOTE: All operations of a batch share a sequence number,
+    // so operands on the same key are folded when committing
+    let mut batch = tree.batch();
+    batch.add_merge("a", 1u64.to_be_bytes());
+    batch.add_merge("a", 2u64.to_be_bytes());
+    batch.add_merge("b", 3u64.to_be_bytes());
+    batch.add_merge("b", 4u64.to_be_bytes());
+    batch.insert("c", 5u64.to_be_bytes());
+    batch.add_merge("c", 6u64.to_be_bytes());
+    batch.remove("d");
+    batch.add_merge("d", 7u64.to_be_bytes());
+    batch.commit(2)?;
+
+    assert_eq!(13, parse(&tree.get("a", 3)?.expect("should exist")));
+    assert_eq!(7, parse(&tree.get("b", 3)?.expect("should exist")));
+    assert_eq!(11, parse(&tree.get("c", 3)?.expect("should exist")));
+    assert_eq!(7, parse(&tree.get("d", 3)?.expect("should exist")));
+
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 3)?;
+    assert_eq!(13, parse(&tree.get("a", 3)?.expect("should exist")));
+    assert_eq!(11, parse(&tree.get("c", 3)?.expect("should exist")));
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_multi_get() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let standard = Config::new(folder.path().join("a"), SequenceNumberCounter::default()).open()?;
+    let blob = Config::new(folder.path().join("b"), SequenceNumberCounter::default())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().separation_threshold(1_000),
+        ))
+        .open()?;
+
+    for tree in [&standard, &blob] {
+        for x in 0..100u64 {
+            tree.insert(x.to_be_bytes(), x.to_string().repeat(1_000), x);
+        }
+        tree.flush_active_memtable(0)?;
+
+        for x in (0..100u64).step_by(10) {
+            tree.insert(x.to_be_bytes(), "small", 100 + x);
+        }
+        tree.remove(5u64.to_be_bytes(), 200);
+        tree.flush_active_memtable(0)?;
+
+        tree.insert(7u64.to_be_bytes(), "new".repeat(1_000), 201);
+
+        let keys = [99u64, 5, 0, 7, 1_000, 42, 7, 3];
+
+        // NOTE: Read before the blobs are cached by point reads
+        let items = tree.multi_get(keys.iter().map(|key| key.to_be_bytes()), SeqNo::MAX)?;
+
+        let expected = keys
+            .iter()
+            .map(|key| tree.get(key.to_be_bytes(), SeqNo::MAX))
+            .collect::<lsm_tree::Result<Vec<_>>>()?;
+        assert_eq!(expected, items);
+
+        assert_eq!(keys.len(), items.len());
+        assert_eq!(None, items[1]);
+        assert_eq!(Some(b"small".as_slice().into()), items[2]);
+        assert_eq!(Some("new".repeat(1_000).as_bytes().into()), items[3]);
+        assert_eq!(None, items[4]);
+        assert_eq!(items[3], items[6]);
+
+        // NOTE: Read an older version
+        let items = tree.multi_get([5u64.to_be_bytes(), 0u64.to_be_bytes()], 100)?;
+        assert_eq!(
+            vec![
+                Some("5".repeat(1_000).as_bytes().into()),
+                Some("0".repeat(1_000).as_bytes().into()),
+            ],
+            items,
+        );
+
+        assert!(tree.multi_get(Vec::<&[u8]>::new(), SeqNo::MAX)?.is_empty());
+    }
+
+    assert!(blob.blob_file_count() > 0);
+
+    Ok(())
+}
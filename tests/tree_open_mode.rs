@@ -0,0 +1,76 @@
+use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_create_new() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default()).create_new()?;
+        tree.insert("a", "a", 0);
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert!(matches!(
+        Config::new(&folder, SequenceNumberCounter::default()).create_new(),
+        Err(lsm_tree::Error::TreeAlreadyExists),
+    ));
+
+    // NOTE: The existing tree is left untouched
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open_existing()?;
+    assert!(tree.contains_key("a", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_open_existing() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path().join("tree");
+
+    assert!(matches!(
+        Config::new(&path, SequenceNumberCounter::default()).open_existing(),
+        Err(lsm_tree::Error::TreeNotFound),
+    ));
+    assert!(!path.try_exists()?);
+
+    Config::new(&path, SequenceNumberCounter::default()).open_or_create()?;
+    Config::new(&path, SequenceNumberCounter::default()).open_existing()?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_open_foreign_folder() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    std::fs::write(folder.path().join("notes.txt"), "important")?;
+
+    assert!(matches!(
+        Config::new(&folder, SequenceNumberCounter::default()).open(),
+        Err(lsm_tree::Error::ForeignFolder),
+    ));
+    assert!(matches!(
+        Config::new(&folder, SequenceNumberCounter::default()).create_new(),
+        Err(lsm_tree::Error::ForeignFolder),
+    ));
+    assert!(!folder.path().join("tables").try_exists()?);
+    assert!(!folder.path().join("lock").try_exists()?);
+    assert_eq!(
+        "important",
+        std::fs::read_to_string(folder.path().join("notes.txt"))?,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_open_interrupted_creation() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    // NOTE: Leftovers of a tree creation that crashed before writing the manifest
+    std::fs::create_dir_all(folder.path().join("tables"))?;
+
+    Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use lsm_tree::{AbstractTree, Clock, Config, SeqNo, SequenceNumberCounter};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Arc,
+    },
+    time::Duration,
+};
+use test_log::test;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Clock that only moves when advanced manually
+#[derive(Debug)]
+struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    fn advance(&self, secs: u64) {
+        self.0.fetch_add(secs, Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_secs(self.0.load(Relaxed))
+    }
+}
+
+#[test]
+fn tree_periodic_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_clock(clock.clone())
+        .periodic_compaction_interval(Some(Duration::from_secs(DAY)))
+        .open()?;
+
+    for i in 0..100u32 {
+        tree.insert_with_ttl(
+            format!("{i:0>3}"),
+            "v",
+            seqno.next(),
+            Duration::from_secs(60),
+        );
+    }
+    tree.insert("forever", "v", seqno.next());
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+
+    clock.advance(60);
+    assert_eq!(1, tree.len(SeqNo::MAX, None)?);
+
+    // NOTE: The table is too young, so the expired values stay on disk
+    tree.run_compaction(SeqNo::MAX)?;
+    assert_eq!(101, tree.approximate_len());
+
+    clock.advance(DAY);
+    tree.run_compaction(SeqNo::MAX)?;
+    assert_eq!(1, tree.table_count());
+    assert_eq!(1, tree.level_table_count(6).unwrap_or_default());
+    assert_eq!(1, tree.approximate_len());
+    assert_eq!(1, tree.len(SeqNo::MAX, None)?);
+
+    // NOTE: The rewritten table is young again
+    let tables = tree.tables();
+    tree.run_compaction(SeqNo::MAX)?;
+    assert_eq!(tables, tree.tables());
+
+    Ok(())
+}
+
+#[test]
+fn tree_periodic_compaction_l0() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_clock(clock.clone())
+        .periodic_compaction_interval(Some(Duration::from_secs(DAY)))
+        .open()?;
+
+    tree.insert("a", "old", seqno.next());
+    tree.flush_active_memtable(0)?;
+    tree.insert("a", "new", seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.level_table_count(0).unwrap_or_default());
+
+    clock.advance(DAY);
+    tree.run_compaction(SeqNo::MAX)?;
+
+    // NOTE: L0 is rewritten as a whole
+    assert_eq!(1, tree.level_table_count(0).unwrap_or_default());
+    assert_eq!(1, tree.approximate_len());
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_periodic_compaction_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_clock(clock.clone())
+        .open()?;
+
+    tree.insert("a", "v", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let tables = tree.tables();
+    clock.advance(365 * DAY);
+    tree.run_compaction(SeqNo::MAX)?;
+    assert_eq!(tables, tree.tables());
+
+    Ok(())
+}
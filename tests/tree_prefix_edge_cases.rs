@@ -0,0 +1,74 @@
+use lsm_tree::{AbstractTree, Config, Guard, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+fn prefix_keys(tree: &lsm_tree::AnyTree, prefix: &[u8]) -> lsm_tree::Result<Vec<Vec<u8>>> {
+    tree.prefix(prefix, SeqNo::MAX, None)
+        .map(|guard| guard.key().map(|key| key.to_vec()))
+        .collect()
+}
+
+fn fill(tree: &lsm_tree::AnyTree) {
+    let keys: [&[u8]; 8] = [
+        &[0x01],
+        &[0x01, 0xFF],
+        &[0x01, 0xFF, 0x00],
+        &[0x01, 0xFF, 0xFF],
+        &[0x02],
+        &[0xFF],
+        &[0xFF, 0xFF],
+        &[0xFF, 0xFF, 0x01],
+    ];
+
+    for (idx, key) in keys.into_iter().enumerate() {
+        tree.insert(key, "a".repeat(2_000), idx as SeqNo);
+    }
+}
+
+#[test]
+fn tree_prefix_edge_cases() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let standard = Config::new(folder.path().join("a"), SequenceNumberCounter::default()).open()?;
+    let blob = Config::new(folder.path().join("b"), SequenceNumberCounter::default())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().separation_threshold(1_000),
+        ))
+        .open()?;
+
+    for tree in [&standard, &blob] {
+        fill(tree);
+
+        for _ in 0..2 {
+            assert_eq!(4, prefix_keys(tree, &[0x01])?.len());
+            assert_eq!(
+                vec![
+                    vec![0x01, 0xFF],
+                    vec![0x01, 0xFF, 0x00],
+                    vec![0x01, 0xFF, 0xFF]
+                ],
+                prefix_keys(tree, &[0x01, 0xFF])?,
+            );
+            assert_eq!(
+                vec![vec![0xFF, 0xFF], vec![0xFF, 0xFF, 0x01]],
+                prefix_keys(tree, &[0xFF, 0xFF])?,
+            );
+            assert_eq!(3, prefix_keys(tree, &[0xFF])?.len());
+            assert_eq!(8, prefix_keys(tree, &[])?.len());
+            assert!(prefix_keys(tree, &[0x03])?.is_empty());
+
+            assert_eq!(
+                Some(vec![0x01, 0xFF, 0xFF]),
+                tree.prefix([0x01, 0xFF], SeqNo::MAX, None)
+                    .next_back()
+                    .map(|guard| guard.key().map(|key| key.to_vec()))
+                    .transpose()?,
+            );
+
+            tree.flush_active_memtable(0)?;
+        }
+    }
+
+    assert!(blob.blob_file_count() > 0);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use lsm_tree::{AbstractTree, Config, Guard, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+fn event_key(stream: &str, ts: u64) -> Vec<u8> {
+    let mut key = format!("{stream}#").into_bytes();
+    key.extend_from_slice(&ts.to_be_bytes());
+    key
+}
+
+#[test]
+fn tree_prefix_rev_latest_events() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let standard = Config::new(folder.path().join("a"), SequenceNumberCounter::default()).open()?;
+    let blob = Config::new(folder.path().join("b"), SequenceNumberCounter::default())
+        .with_kv_separation(Some(
+            KvSeparationOptions::default().separation_threshold(1_000),
+        ))
+        .open()?;
+
+    for tree in [&standard, &blob] {
+        let mut seqno = 0;
+
+        // NOTE: Events are spread over tables and the memtable
+        for ts in 0..100u64 {
+            for stream in ["a", "b", "c"] {
+                tree.insert(event_key(stream, ts), ts.to_string().repeat(1_000), seqno);
+                seqno += 1;
+            }
+
+            if ts % 30 == 29 {
+                tree.flush_active_memtable(0)?;
+            }
+        }
+        tree.remove(event_key("b", 99), seqno);
+
+        // NOTE: Latest 5 events of stream "b", without reading it forward
+        let latest = tree
+            .prefix("b#", SeqNo::MAX, None)
+            .rev()
+            .take(5)
+            .map(Guard::into_inner)
+            .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            (94..99u64)
+                .rev()
+                .map(|ts| event_key("b", ts))
+                .collect::<Vec<_>>(),
+            latest.iter().map(|(k, _)| k.to_vec()).collect::<Vec<_>>(),
+        );
+        assert!(latest
+            .iter()
+            .zip((94..99u64).rev())
+            .all(|((_, v), ts)| &**v == ts.to_string().repeat(1_000).as_bytes()));
+
+        // NOTE: Both ends can be consumed from the same iterator
+        let mut iter = tree.range(event_key("c", 10)..event_key("c", 20), SeqNo::MAX, None);
+        assert_eq!(
+            event_key("c", 19),
+            &*iter.next_back().expect("should exist").key()?
+        );
+        assert_eq!(
+            event_key("c", 10),
+            &*iter.next().expect("should exist").key()?
+        );
+        assert_eq!(8, iter.count());
+    }
+
+    assert!(blob.blob_file_count() > 0);
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use lsm_tree::{AbstractTree, Config, Guard, KvSeparationOptions, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+#[test]
+fn tree_range_snapshot_concurrent_writes() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let standard = Config::new(folder.path().join("a"), seqno.clone()).open()?;
+    let blob = Config::new(folder.path().join("b"), seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    for tree in [&standard, &blob] {
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "old", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        let snapshot_seqno = seqno.get();
+
+        let mut iter = tree.iter(snapshot_seqno, None);
+        let mut range = tree.range(
+            10u64.to_be_bytes()..20u64.to_be_bytes(),
+            snapshot_seqno,
+            None,
+        );
+
+        let (_, first) = iter.next().expect("should exist").into_inner()?;
+        assert_eq!(b"old", &*first);
+
+        // NOTE: Overwrite and delete items while the iterators are open
+        for x in 0..ITEM_COUNT {
+            if x % 2 == 0 {
+                tree.remove(x.to_be_bytes(), seqno.next());
+            } else {
+                tree.insert(x.to_be_bytes(), "new", seqno.next());
+            }
+        }
+        tree.insert(ITEM_COUNT.to_be_bytes(), "new", seqno.next());
+        tree.flush_active_memtable(0)?;
+        tree.major_compact(u64::MAX, 0)?;
+
+        let rest = iter
+            .map(Guard::into_inner)
+            .collect::<lsm_tree::Result<Vec<_>>>()?;
+        assert_eq!(ITEM_COUNT as usize - 1, rest.len());
+        assert!(rest.iter().all(|(_, v)| &**v == b"old"));
+
+        let items = range
+            .by_ref()
+            .rev()
+            .map(Guard::into_inner)
+            .collect::<lsm_tree::Result<Vec<_>>>()?;
+        assert_eq!(10, items.len());
+        assert!(items.iter().all(|(_, v)| &**v == b"old"));
+
+        // NOTE: New iterators at the snapshot still see the old state,
+        // because the compaction did not drop versions above its threshold
+        assert_eq!(ITEM_COUNT as usize, tree.iter(snapshot_seqno, None).count(),);
+        assert_eq!(
+            ITEM_COUNT as usize / 2 + 1,
+            tree.iter(seqno.get(), None).count(),
+        );
+    }
+
+    Ok(())
+}
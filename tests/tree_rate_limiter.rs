@@ -0,0 +1,69 @@
+use lsm_tree::{AbstractTree, AnyTree, Config, Env, RateLimiter, SeqNo, SequenceNumberCounter};
+use rand::RngCore;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use test_log::test;
+
+const BYTES_PER_SECOND: u64 = 100_000;
+
+/// Writes ~100 KB of incompressible data
+fn fill(tree: &AnyTree) {
+    let mut rng = rand::rng();
+
+    for i in 0..100u32 {
+        let mut value = vec![0; 1_000];
+        rng.fill_bytes(&mut value);
+        tree.insert(i.to_be_bytes(), value, u64::from(i));
+    }
+}
+
+#[test]
+fn tree_rate_limiter_flush() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let rate_limiter = Arc::new(RateLimiter::new(BYTES_PER_SECOND));
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_rate_limiter(Some(rate_limiter))
+        .open()?;
+
+    // NOTE: The first second worth of bytes is a burst
+    fill(&tree);
+    tree.flush_active_memtable(0)?;
+
+    let start = Instant::now();
+    fill(&tree);
+    tree.flush_active_memtable(0)?;
+    assert!(start.elapsed() >= Duration::from_millis(500));
+
+    assert_eq!(100, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_rate_limiter_shared_env() -> lsm_tree::Result<()> {
+    let env = Env::default().with_rate_limiter(Some(Arc::new(RateLimiter::new(BYTES_PER_SECOND))));
+
+    let folder = tempfile::tempdir()?;
+    let tree1 = Config::new(&folder, SequenceNumberCounter::default())
+        .with_env(&env)
+        .open()?;
+
+    let folder = tempfile::tempdir()?;
+    let tree2 = Config::new(&folder, SequenceNumberCounter::default())
+        .with_env(&env)
+        .open()?;
+
+    fill(&tree1);
+    tree1.flush_active_memtable(0)?;
+
+    // NOTE: The burst was used up by the other tree
+    let start = Instant::now();
+    fill(&tree2);
+    tree2.flush_active_memtable(0)?;
+    assert!(start.elapsed() >= Duration::from_millis(500));
+
+    Ok(())
+}
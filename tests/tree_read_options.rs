@@ -0,0 +1,169 @@
+use lsm_tree::{
+    AbstractTree, Cache, Config, Guard, KvSeparationOptions, ReadOptions, SeqNo,
+    SequenceNumberCounter,
+};
+use std::sync::Arc;
+use test_log::test;
+
+fn collect_keys(iter: impl DoubleEndedIterator<Item = impl Guard>) -> lsm_tree::Result<Vec<u64>> {
+    iter.map(|guard| {
+        guard
+            .key()
+            .map(|k| u64::from_be_bytes((*k).try_into().expect("should be u64")))
+    })
+    .collect()
+}
+
+#[test]
+fn tree_read_options_bounds_and_limit() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    for x in 0..50u64 {
+        tree.insert(x.to_be_bytes(), "a", x);
+    }
+    tree.flush_active_memtable(0)?;
+    for x in 50..100u64 {
+        tree.insert(x.to_be_bytes(), "a", x);
+    }
+
+    let options = ReadOptions::default()
+        .lower_bound(10u64.to_be_bytes())
+        .upper_bound(60u64.to_be_bytes());
+
+    let keys = collect_keys(tree.iter_with_options(SeqNo::MAX, &options))?;
+    assert_eq!((10..60).collect::<Vec<_>>(), keys);
+
+    // NOTE: Bounds are intersected with the range
+    let keys = collect_keys(tree.range_with_options(..=20u64.to_be_bytes(), SeqNo::MAX, &options))?;
+    assert_eq!((10..=20).collect::<Vec<_>>(), keys);
+
+    let keys = collect_keys(tree.range_with_options(70u64.to_be_bytes().., SeqNo::MAX, &options))?;
+    assert!(keys.is_empty());
+
+    let options = options.limit(5);
+
+    let keys = collect_keys(tree.iter_with_options(SeqNo::MAX, &options))?;
+    assert_eq!((10..15).collect::<Vec<_>>(), keys);
+
+    let keys = collect_keys(tree.iter_with_options(SeqNo::MAX, &options).rev())?;
+    assert_eq!((55..60).rev().collect::<Vec<_>>(), keys);
+
+    // NOTE: Both ends count towards the limit
+    let mut iter = tree.iter_with_options(SeqNo::MAX, &options);
+    assert!(iter.next().is_some());
+    assert!(iter.next_back().is_some());
+    assert_eq!(3, iter.count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_read_options_prefix() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    tree.insert("user1/1", "a", 0);
+    tree.insert("user1/2", "b", 1);
+    tree.insert("user1/3", "c", 2);
+    tree.insert("user2/1", "d", 3);
+
+    let options = ReadOptions::default().limit(2).key_only(true);
+
+    let items = tree
+        .prefix_with_options("user1/", SeqNo::MAX, &options)
+        .map(Guard::into_inner)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+    assert_eq!(2, items.len());
+    assert_eq!(b"user1/2", &*items[1].0);
+    assert!(items.iter().all(|(_, v)| v.is_empty()));
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_read_options_key_only() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .with_kv_separation(Some(KvSeparationOptions::default()))
+            .open()?;
+
+        for x in 0..10u64 {
+            tree.insert(x.to_be_bytes(), "a".repeat(10_000), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.blob_file_count());
+    }
+
+    // NOTE: Reopen to make sure no blob is cached
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default()))
+        .open()?;
+
+    for entry in std::fs::read_dir(folder.path().join("blobs"))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            std::fs::remove_dir_all(path)?;
+        } else {
+            std::fs::remove_file(path)?;
+        }
+    }
+
+    let options = ReadOptions::default().key_only(true);
+
+    let items = tree
+        .iter_with_options(SeqNo::MAX, &options)
+        .map(Guard::into_inner)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(10, items.len());
+    assert!(items.iter().all(|(_, v)| v.is_empty()));
+
+    assert!(tree
+        .iter_with_options(SeqNo::MAX, &ReadOptions::default())
+        .map(Guard::into_inner)
+        .any(|item| item.is_err()));
+
+    Ok(())
+}
+
+#[test]
+fn tree_read_options_fill_cache() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone()).open()?;
+
+        for x in 0..10_000u64 {
+            tree.insert(x.to_be_bytes(), "a".repeat(100), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let cache = Arc::new(Cache::with_capacity_bytes(64 * 1_024 * 1_024));
+
+    let tree = Config::new(&folder, seqno.clone())
+        .use_cache(cache.clone())
+        .open()?;
+    tree.preload()?;
+
+    let cached_blocks = cache.len();
+
+    let options = ReadOptions::default().fill_cache(false);
+    assert_eq!(10_000, tree.iter_with_options(SeqNo::MAX, &options).count());
+    assert_eq!(cached_blocks, cache.len());
+
+    assert_eq!(10_000, tree.iter(SeqNo::MAX, None).count());
+    assert!(cache.len() > cached_blocks);
+
+    // NOTE: Cached blocks are still used
+    let cached_blocks = cache.len();
+    assert_eq!(10_000, tree.iter_with_options(SeqNo::MAX, &options).count());
+    assert_eq!(cached_blocks, cache.len());
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_recovery_interrupted_version_change() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path();
+
+    {
+        let tree = Config::new(path, SequenceNumberCounter::default()).open()?;
+        tree.insert("a", "a", 0);
+        tree.flush_active_memtable(0)?;
+        assert!(path.join("v1").try_exists()?);
+    }
+
+    // NOTE: Simulate a crash during a flush: the table and the version are written,
+    // but the version never became current
+    std::fs::write(path.join("tables").join("1"), "garbage")?;
+    std::fs::write(path.join("v2"), "garbage")?;
+
+    let tree = Config::new(path, SequenceNumberCounter::default()).open()?;
+    assert_eq!(1, tree.table_count());
+    assert_eq!(Some("a".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    assert!(!path.join("tables").join("1").try_exists()?);
+    assert!(!path.join("v2").try_exists()?);
+    assert!(path.join("v1").try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_recovery_interrupted_version_change() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path();
+    let big = "a".repeat(1_000);
+
+    let config = || {
+        Config::new(path, SequenceNumberCounter::default())
+            .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+    };
+
+    {
+        let tree = config().open()?;
+        tree.insert("a", &big, 0);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.blob_file_count());
+    }
+
+    // NOTE: Simulate a crash during a flush: the blob file, table and version are written,
+    // but the version never became current
+    std::fs::write(path.join("blobs").join("1"), "garbage")?;
+    std::fs::write(path.join("tables").join("1"), "garbage")?;
+    std::fs::write(path.join("v2"), "garbage")?;
+
+    let tree = config().open()?;
+    assert_eq!(1, tree.table_count());
+    assert_eq!(1, tree.blob_file_count());
+    assert_eq!(Some(big.as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    assert!(!path.join("blobs").join("1").try_exists()?);
+    assert!(!path.join("tables").join("1").try_exists()?);
+    assert!(!path.join("v2").try_exists()?);
+
+    Ok(())
+}
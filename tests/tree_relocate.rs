@@ -0,0 +1,97 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+#[test]
+fn tree_relocate() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let old_path = folder.path().join("old");
+    let new_path = folder.path().join("nested").join("new");
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&old_path, seqno.clone()).open()?;
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(format!("{x:0>3}"), "a", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Unflushed data is moved as well
+    tree.insert("new", "a", seqno.next());
+
+    let tree = tree.relocate(&new_path)?;
+    assert!(!old_path.try_exists()?);
+    assert_eq!(new_path, tree.tree_config().path);
+    assert_eq!(ITEM_COUNT + 1, tree.len(SeqNo::MAX, None)?);
+
+    tree.insert("newer", "a", seqno.next());
+    tree.flush_active_memtable(0)?;
+    drop(tree);
+
+    let tree = Config::new(&new_path, seqno.clone()).open_existing()?;
+    assert_eq!(ITEM_COUNT + 2, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_relocate_blob() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let new_path = folder.path().join("new");
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(folder.path().join("old"), seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(format!("{x:0>3}"), "a".repeat(100), seqno.next());
+    }
+
+    let tree = tree.relocate(&new_path)?;
+    assert!(matches!(tree, lsm_tree::AnyTree::Blob(_)));
+    assert_eq!(1, tree.blob_file_count());
+    assert_eq!(
+        Some("a".repeat(100).as_bytes().into()),
+        tree.get("042", SeqNo::MAX)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_relocate_shared_handle() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let old_path = folder.path().join("old");
+
+    let tree = Config::new(&old_path, SequenceNumberCounter::default()).open()?;
+    tree.insert("a", "a", 0);
+
+    let _other = tree.clone();
+
+    assert!(matches!(
+        tree.relocate(folder.path().join("new")),
+        Err(lsm_tree::Error::AlreadyLocked),
+    ));
+    assert!(old_path.try_exists()?);
+    assert!(!folder.path().join("new").try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_relocate_existing_destination() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let new_path = folder.path().join("new");
+    std::fs::create_dir_all(&new_path)?;
+
+    let tree = Config::new(folder.path().join("old"), SequenceNumberCounter::default()).open()?;
+
+    assert!(matches!(
+        tree.relocate(&new_path),
+        Err(lsm_tree::Error::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists,
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use lsm_tree::{
+    coding::{Decode, Encode},
+    compaction::{
+        remote::{CompactionJob, CompactionOutput},
+        Leveled,
+    },
+    AbstractTree, AnyTree, Config, SequenceNumberCounter,
+};
+use std::{path::Path, sync::Arc};
+use test_log::test;
+
+fn open_tree(path: &Path) -> lsm_tree::Result<lsm_tree::Tree> {
+    let AnyTree::Standard(tree) = Config::new(path, SequenceNumberCounter::default()).open()?
+    else {
+        unreachable!();
+    };
+    Ok(tree)
+}
+
+fn fill_l0(tree: &lsm_tree::Tree) -> lsm_tree::Result<()> {
+    for table in 0..4u64 {
+        for x in 0..100u64 {
+            tree.insert(x.to_be_bytes(), table.to_string(), table * 100 + x);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(4, tree.table_count());
+    Ok(())
+}
+
+/// Runs a job like an external worker would, on copies of the input files
+fn run_worker(job_bytes: &[u8], tables_folder: &Path, work_folder: &Path) -> Vec<u8> {
+    let job = CompactionJob::decode_from(&mut &job_bytes[..]).unwrap();
+
+    let input_folder = work_folder.join("input");
+    std::fs::create_dir_all(&input_folder).unwrap();
+
+    for table_id in job.table_ids() {
+        std::fs::copy(
+            tables_folder.join(table_id.to_string()),
+            input_folder.join(table_id.to_string()),
+        )
+        .unwrap();
+    }
+
+    job.execute(&input_folder, work_folder.join("output"))
+        .unwrap()
+        .encode_into_vec()
+}
+
+#[test]
+fn tree_remote_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let work_folder = tempfile::tempdir()?;
+
+    {
+        let tree = open_tree(folder.path())?;
+        fill_l0(&tree)?;
+
+        let job = tree
+            .prepare_remote_compaction(Arc::new(Leveled::default()), 1_000)?
+            .expect("should compact");
+        assert_eq!(4, job.table_ids().count());
+        assert!(tree.is_compacting());
+
+        // NOTE: Tables of running remote compactions are not compacted locally
+        tree.compact(Arc::new(Leveled::default()), 1_000)?;
+        assert_eq!(4, tree.table_count());
+
+        let output_bytes = run_worker(
+            &job.encode_into_vec(),
+            &folder.path().join("tables"),
+            work_folder.path(),
+        );
+        let output = CompactionOutput::decode_from(&mut &output_bytes[..])?;
+        assert_eq!(1, output.table_ids().count());
+
+        tree.install_remote_compaction(&job, &output, work_folder.path().join("output"), 1_000)?;
+        assert!(!tree.is_compacting());
+
+        assert_eq!(1, tree.table_count());
+        assert_eq!(0, tree.level_table_count(0).unwrap_or_default());
+        assert_eq!(100, tree.len(u64::MAX, None)?);
+        assert_eq!(
+            Some("3".as_bytes().into()),
+            tree.get(5u64.to_be_bytes(), u64::MAX)?
+        );
+    }
+
+    {
+        let tree = open_tree(folder.path())?;
+        assert_eq!(1, tree.table_count());
+        assert_eq!(100, tree.len(u64::MAX, None)?);
+        assert_eq!(
+            Some("3".as_bytes().into()),
+            tree.get(5u64.to_be_bytes(), u64::MAX)?
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_remote_compaction_abort() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let work_folder = tempfile::tempdir()?;
+
+    let tree = open_tree(folder.path())?;
+    fill_l0(&tree)?;
+
+    let job = tree
+        .prepare_remote_compaction(Arc::new(Leveled::default()), 1_000)?
+        .expect("should compact");
+
+    let output = job.execute(folder.path().join("tables"), work_folder.path())?;
+
+    tree.abort_remote_compaction(&job);
+    assert!(!tree.is_compacting());
+
+    tree.compact(Arc::new(Leveled::default()), 1_000)?;
+    assert_eq!(1, tree.table_count());
+
+    // NOTE: The input tables were compacted away in the meantime
+    assert!(tree
+        .install_remote_compaction(&job, &output, work_folder.path(), 1_000)
+        .is_err());
+    assert_eq!(1, tree.table_count());
+    assert_eq!(100, tree.len(u64::MAX, None)?);
+
+    Ok(())
+}
+
+/// Fills L0 of a second tree, whose tables have the same IDs as the ones of [`fill_l0`]
+fn fill_l0_foreign(
+    tree: &lsm_tree::Tree,
+    extra_key: u64,
+    seqno_offset: u64,
+) -> lsm_tree::Result<()> {
+    for table in 0..4u64 {
+        for x in 0..100u64 {
+            tree.insert(x.to_be_bytes(), "foreign", seqno_offset + table * 100 + x);
+        }
+        tree.insert(
+            extra_key.to_be_bytes(),
+            "foreign",
+            seqno_offset + table * 100,
+        );
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(4, tree.table_count());
+    Ok(())
+}
+
+/// Checks that a rejected job left the tree as it was
+fn assert_rolled_back(tree: &lsm_tree::Tree, tables_folder: &Path) -> lsm_tree::Result<()> {
+    assert!(!tree.is_compacting());
+    assert_eq!(4, tree.table_count());
+    assert_eq!(100, tree.len(u64::MAX, None)?);
+    assert_eq!(
+        Some("3".as_bytes().into()),
+        tree.get(5u64.to_be_bytes(), u64::MAX)?
+    );
+
+    // NOTE: Only the input tables are left
+    assert_eq!(4, std::fs::read_dir(tables_folder)?.count());
+
+    tree.compact(Arc::new(Leveled::default()), 1_000)?;
+    assert_eq!(1, tree.table_count());
+    assert_eq!(100, tree.len(u64::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_remote_compaction_reject_key_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let foreign_folder = tempfile::tempdir()?;
+    let work_folder = tempfile::tempdir()?;
+
+    let tree = open_tree(folder.path())?;
+    fill_l0(&tree)?;
+
+    let foreign_tree = open_tree(foreign_folder.path())?;
+    fill_l0_foreign(&foreign_tree, 1_000, 0)?;
+
+    let job = tree
+        .prepare_remote_compaction(Arc::new(Leveled::default()), 1_000)?
+        .expect("should compact");
+
+    // NOTE: The worker compacts the wrong files, which contain keys the tree never had
+    let output_bytes = run_worker(
+        &job.encode_into_vec(),
+        &foreign_folder.path().join("tables"),
+        work_folder.path(),
+    );
+    let output = CompactionOutput::decode_from(&mut &output_bytes[..])?;
+
+    assert!(tree
+        .install_remote_compaction(&job, &output, work_folder.path().join("output"), 1_000)
+        .is_err());
+
+    assert_rolled_back(&tree, &folder.path().join("tables"))
+}
+
+#[test]
+fn tree_remote_compaction_reject_seqno() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let foreign_folder = tempfile::tempdir()?;
+    let work_folder = tempfile::tempdir()?;
+
+    let tree = open_tree(folder.path())?;
+    fill_l0(&tree)?;
+
+    let foreign_tree = open_tree(foreign_folder.path())?;
+    fill_l0_foreign(&foreign_tree, 0, 10_000)?;
+
+    let job = tree
+        .prepare_remote_compaction(Arc::new(Leveled::default()), 1_000)?
+        .expect("should compact");
+
+    // NOTE: The worker compacts the wrong files, which contain newer seqnos than the tree has
+    let output_bytes = run_worker(
+        &job.encode_into_vec(),
+        &foreign_folder.path().join("tables"),
+        work_folder.path(),
+    );
+    let output = CompactionOutput::decode_from(&mut &output_bytes[..])?;
+
+    assert!(tree
+        .install_remote_compaction(&job, &output, work_folder.path().join("output"), 1_000)
+        .is_err());
+
+    assert_rolled_back(&tree, &folder.path().join("tables"))
+}
+
+#[test]
+fn tree_remote_compaction_reject_duplicate_table_ids() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let work_folder = tempfile::tempdir()?;
+
+    let tree = open_tree(folder.path())?;
+    fill_l0(&tree)?;
+
+    let job = tree
+        .prepare_remote_compaction(Arc::new(Leveled::default()), 1_000)?
+        .expect("should compact");
+
+    let mut output_bytes = run_worker(
+        &job.encode_into_vec(),
+        &folder.path().join("tables"),
+        work_folder.path(),
+    );
+
+    // NOTE: Lists the single output table twice
+    let entry = output_bytes[4..28].to_vec();
+    output_bytes[0..4].copy_from_slice(&2u32.to_le_bytes());
+    output_bytes.splice(28..28, entry);
+
+    let output = CompactionOutput::decode_from(&mut &output_bytes[..])?;
+    assert_eq!(2, output.table_ids().count());
+
+    assert!(tree
+        .install_remote_compaction(&job, &output, work_folder.path().join("output"), 1_000)
+        .is_err());
+
+    // NOTE: The output was rejected before anything was moved
+    assert_eq!(
+        1,
+        std::fs::read_dir(work_folder.path().join("output"))?.count()
+    );
+
+    assert_rolled_back(&tree, &folder.path().join("tables"))
+}
+
+#[test]
+fn tree_remote_compaction_rollback() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let work_folder = tempfile::tempdir()?;
+
+    let tree = open_tree(folder.path())?;
+    fill_l0(&tree)?;
+
+    let job = tree
+        .prepare_remote_compaction(Arc::new(Leveled::default()), 1_000)?
+        .expect("should compact");
+
+    let mut output_bytes = run_worker(
+        &job.encode_into_vec(),
+        &folder.path().join("tables"),
+        work_folder.path(),
+    );
+
+    // NOTE: Lists a second output table, whose file does not exist,
+    // so the first table is moved into the tree before the install fails
+    let table_id = u64::from_le_bytes(output_bytes[4..12].try_into().unwrap());
+    let mut entry = output_bytes[4..28].to_vec();
+    entry[0..8].copy_from_slice(&(table_id + 1).to_le_bytes());
+    output_bytes[0..4].copy_from_slice(&2u32.to_le_bytes());
+    output_bytes.splice(28..28, entry);
+
+    let output = CompactionOutput::decode_from(&mut &output_bytes[..])?;
+    assert_eq!(2, output.table_ids().count());
+
+    assert!(tree
+        .install_remote_compaction(&job, &output, work_folder.path().join("output"), 1_000)
+        .is_err());
+
+    assert_rolled_back(&tree, &folder.path().join("tables"))?;
+
+    // NOTE: The moved table does not come back after recovery either
+    drop(tree);
+
+    let tree = open_tree(folder.path())?;
+    assert_eq!(1, tree.table_count());
+    assert_eq!(100, tree.len(u64::MAX, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_remove_if() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    assert!(!tree.remove_if("a", "abc", 0)?);
+    assert_eq!(0, tree.active_memtable_size());
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+
+    assert!(!tree.remove_if("a", "ab", 1)?);
+    assert!(!tree.remove_if("a", "abcd", 1)?);
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("a", 2)?);
+
+    // NOTE: The newest version is compared
+    tree.insert("a", "def", 2);
+    assert!(!tree.remove_if("a", "abc", 3)?);
+    assert!(tree.remove_if("a", "def", 3)?);
+
+    assert!(!tree.contains_key("a", 4)?);
+    assert_eq!(Some("def".as_bytes().into()), tree.get("a", 3)?);
+
+    // NOTE: Deleted keys do not match anymore
+    assert!(!tree.remove_if("a", "def", 4)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_if_concurrent() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    tree.insert("a", "abc", seqno.next());
+
+    let removed = std::thread::scope(|s| {
+        let handles = (0..8)
+            .map(|_| s.spawn(|| tree.remove_if("a", "abc", seqno.next())))
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("should not panic"))
+            .collect::<lsm_tree::Result<Vec<_>>>()
+    })?;
+
+    // NOTE: Exactly one conditional delete wins
+    assert_eq!(1, removed.into_iter().filter(|removed| *removed).count());
+    assert!(!tree.contains_key("a", seqno.get())?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_remove_if() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    let big = "a".repeat(1_000);
+    tree.insert("a", big.clone(), 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+
+    assert!(!tree.remove_if("a", "a", 1)?);
+    assert!(tree.remove_if("a", &big, 1)?);
+    assert!(!tree.contains_key("a", 2)?);
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+fn fill(tree: &lsm_tree::AnyTree, prefix: &str, seqno: &SequenceNumberCounter) {
+    for x in 0..100u64 {
+        tree.insert(format!("{prefix}:{x:0>3}"), "a".repeat(100), seqno.next());
+    }
+}
+
+#[test]
+fn tree_remove_range_memtable() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    fill(&tree, "a", &seqno);
+    fill(&tree, "b", &seqno);
+    fill(&tree, "c", &seqno);
+
+    let snapshot = seqno.get();
+    tree.remove_range("b:".."c:", seqno.next());
+
+    assert_eq!(200, tree.len(SeqNo::MAX, None)?);
+    assert!(tree.contains_key("a:099", SeqNo::MAX)?);
+    assert!(!tree.contains_key("b:000", SeqNo::MAX)?);
+    assert!(!tree.contains_key("b:099", SeqNo::MAX)?);
+    assert!(tree.contains_key("c:000", SeqNo::MAX)?);
+    assert_eq!(
+        100,
+        tree.range("a:050"..="c:049", SeqNo::MAX, None)
+            .rev()
+            .count(),
+    );
+
+    // NOTE: Snapshots before the range tombstone still see the data
+    assert_eq!(300, tree.len(snapshot, None)?);
+
+    // NOTE: Newer writes are not affected
+    tree.insert("b:050", "new", seqno.next());
+    assert_eq!(
+        Some("new".as_bytes().into()),
+        tree.get("b:050", SeqNo::MAX)?
+    );
+    assert_eq!(201, tree.len(SeqNo::MAX, None)?);
+
+    // NOTE: Empty ranges are ignored
+    let size = tree.active_memtable_size();
+    #[expect(clippy::reversed_empty_ranges)]
+    tree.remove_range("c:".."b:", seqno.next());
+    assert_eq!(size, tree.active_memtable_size());
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_flush_and_recover() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tombstone_seqno = {
+        let tree = Config::new(&folder, seqno.clone()).open()?;
+
+        fill(&tree, "a", &seqno);
+        fill(&tree, "b", &seqno);
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: A memtable that only contains a range tombstone does not produce a table
+        let tombstone_seqno = seqno.next();
+        tree.remove_range("a:"..="a:~", tombstone_seqno);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.table_count());
+        assert_eq!(0, tree.sealed_memtable_count());
+
+        assert_eq!(100, tree.len(SeqNo::MAX, None)?);
+        assert!(!tree.contains_key("a:000", SeqNo::MAX)?);
+
+        tombstone_seqno
+    };
+
+    {
+        let tree = Config::new(&folder, seqno.clone()).open()?;
+        assert_eq!(Some(tombstone_seqno), tree.get_highest_persisted_seqno());
+        assert_eq!(100, tree.len(SeqNo::MAX, None)?);
+        assert!(!tree.contains_key("a:000", SeqNo::MAX)?);
+        assert!(tree.contains_key("b:000", SeqNo::MAX)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    fill(&tree, "a", &seqno);
+    fill(&tree, "b", &seqno);
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = seqno.get();
+    tree.remove_range("a:".., seqno.next());
+    tree.insert("b:050", "new", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: A snapshot still needs the range-deleted versions,
+    // but the shadowed version of b:050 can be dropped
+    tree.major_compact(u64::MAX, snapshot)?;
+    assert_eq!(200, tree.approximate_len());
+    assert_eq!(1, tree.len(SeqNo::MAX, None)?);
+
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(1, tree.approximate_len());
+    assert_eq!(1, tree.len(SeqNo::MAX, None)?);
+    assert_eq!(
+        Some("new".as_bytes().into()),
+        tree.get("b:050", SeqNo::MAX)?
+    );
+
+    // NOTE: The range tombstone is dropped once nothing is beneath it anymore,
+    // so (out-of-order) writes that are older than the tombstone are visible again
+    tree.insert("c", "old", 0);
+    assert!(tree.contains_key("c", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_blob() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    fill(&tree, "a", &seqno);
+    fill(&tree, "b", &seqno);
+    tree.flush_active_memtable(0)?;
+
+    tree.remove_range("a:".."b:", seqno.next());
+    assert_eq!(100, tree.len(SeqNo::MAX, None)?);
+    assert_eq!(None, tree.get("a:000", SeqNo::MAX)?);
+
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(100, tree.len(SeqNo::MAX, None)?);
+    assert_eq!(100, tree.approximate_len());
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_ephemeral() -> lsm_tree::Result<()> {
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::ephemeral(seqno.clone()).open()?;
+
+    fill(&tree, "a", &seqno);
+    tree.flush_active_memtable(0)?;
+
+    tree.remove_range("a:010".."a:020", seqno.next());
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+
+    assert_eq!(90, tree.len(SeqNo::MAX, None)?);
+    assert!(!tree.contains_key("a:015", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_read_paths() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+
+    fill(&tree, "a", &seqno);
+    fill(&tree, "b", &seqno);
+    tree.flush_active_memtable(0)?;
+    fill(&tree, "c", &seqno);
+
+    tree.remove_range("a:050".."c:050", seqno.next());
+
+    assert_eq!(
+        vec![true, false, false, true],
+        tree.multi_get(["a:049", "a:050", "c:049", "c:050"], SeqNo::MAX)?
+            .iter()
+            .map(Option::is_some)
+            .collect::<Vec<_>>(),
+    );
+
+    let options = lsm_tree::ReadOptions::default().limit(10).key_only(true);
+    assert_eq!(
+        10,
+        tree.range_with_options("a:045".., SeqNo::MAX, &options)
+            .count()
+    );
+    assert_eq!(
+        5,
+        tree.range_with_options("a:045".."c:055", SeqNo::MAX, &options)
+            .rev()
+            .take(5)
+            .count()
+    );
+
+    let mut cursor = lsm_tree::Cursor::new(tree.clone(), SeqNo::MAX);
+    let (key, _) = cursor.seek("a:050")?.expect("should exist");
+    assert_eq!(b"c:050", &*key);
+    let (key, _) = cursor.prev()?.expect("should exist");
+    assert_eq!(b"a:049", &*key);
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use lsm_tree::{config::BlockSizePolicy, AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn data_block_counts(tree: &lsm_tree::AnyTree) -> Vec<u64> {
+    tree.current_version()
+        .iter_tables()
+        .map(|table| table.metadata.data_block_count)
+        .collect()
+}
+
+#[test]
+fn tree_rewrite_all_tables() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .data_block_size_policy(BlockSizePolicy::all(1_024))
+            .open()?;
+
+        for batch in 0..2 {
+            for x in (batch * ITEM_COUNT)..((batch + 1) * ITEM_COUNT) {
+                tree.insert(x.to_be_bytes(), "a".repeat(50), seqno.next());
+            }
+            tree.flush_active_memtable(0)?;
+        }
+
+        // NOTE: Move one table out of L0
+        tree.compact(Arc::new(lsm_tree::compaction::MoveDown(0, 1)), SeqNo::MAX)?;
+
+        for x in (2 * ITEM_COUNT)..(3 * ITEM_COUNT) {
+            tree.insert(x.to_be_bytes(), "a".repeat(50), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Reopen with bigger blocks, existing tables keep their block size
+    let tree = Config::new(&folder, seqno.clone())
+        .data_block_size_policy(BlockSizePolicy::all(16 * 1_024))
+        .open()?;
+
+    assert_eq!(3, tree.table_count());
+    assert_eq!(Some(2), tree.level_table_count(1));
+    let levels_before = (0..7)
+        .map(|idx| tree.level_table_count(idx))
+        .collect::<Vec<_>>();
+    let blocks_before: u64 = data_block_counts(&tree).iter().sum();
+
+    tree.rewrite_all_tables(SeqNo::MAX)?;
+
+    assert_eq!(
+        levels_before,
+        (0..7)
+            .map(|idx| tree.level_table_count(idx))
+            .collect::<Vec<_>>(),
+    );
+    let blocks_after: u64 = data_block_counts(&tree).iter().sum();
+    assert!(blocks_after * 4 < blocks_before);
+
+    assert_eq!(3 * ITEM_COUNT as usize, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
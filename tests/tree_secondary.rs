@@ -0,0 +1,228 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_secondary_catch_up_flush() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let primary = Config::new(&folder, seqno.clone()).open()?;
+    primary.insert("a", "a", seqno.next());
+    primary.flush_active_memtable(0)?;
+
+    let secondary = Config::new(&folder, SequenceNumberCounter::default()).open_secondary()?;
+    assert!(secondary.contains_key("a", SeqNo::MAX)?);
+
+    primary.insert("b", "b", seqno.next());
+    primary.flush_active_memtable(0)?;
+    assert!(!secondary.contains_key("b", SeqNo::MAX)?);
+
+    secondary.try_catch_up()?;
+    assert!(secondary.contains_key("b", SeqNo::MAX)?);
+    assert_eq!(2, secondary.table_count());
+    assert_eq!(2, secondary.len(SeqNo::MAX, None)?);
+
+    // NOTE: Catching up without any change keeps the same version
+    secondary.try_catch_up()?;
+    assert_eq!(2, secondary.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_secondary_catch_up_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let primary = Config::new(&folder, seqno.clone()).open()?;
+
+    for i in 0..5u32 {
+        primary.insert(format!("{i}"), "old", seqno.next());
+        primary.flush_active_memtable(0)?;
+    }
+
+    let secondary = Config::new(&folder, SequenceNumberCounter::default()).open_secondary()?;
+    assert_eq!(5, secondary.table_count());
+
+    for i in 0..5u32 {
+        primary.insert(format!("{i}"), "new", seqno.next());
+    }
+    primary.flush_active_memtable(0)?;
+    primary.major_compact(u64::MAX, SeqNo::MAX)?;
+
+    secondary.try_catch_up()?;
+    assert_eq!(1, secondary.table_count());
+    assert_eq!(5, secondary.len(SeqNo::MAX, None)?);
+    assert_eq!(
+        Some("new".as_bytes().into()),
+        secondary.get("3", SeqNo::MAX)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_secondary_reads_compacted_tables() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let primary = Config::new(&folder, seqno.clone()).open()?;
+
+    for i in 0..5u32 {
+        primary.insert(format!("{i}"), "old", seqno.next());
+        primary.flush_active_memtable(0)?;
+    }
+
+    let secondary = Config::new(&folder, SequenceNumberCounter::default()).open_secondary()?;
+    assert_eq!(5, secondary.table_count());
+
+    for round in 0..3 {
+        for i in 0..5u32 {
+            primary.insert(format!("{i}"), format!("new{round}"), seqno.next());
+        }
+        primary.flush_active_memtable(0)?;
+
+        // NOTE: The primary deletes the tables the secondary has not read yet
+        primary.major_compact(u64::MAX, SeqNo::MAX)?;
+        assert_eq!(1, primary.table_count());
+
+        let expected = if round == 0 {
+            "old".to_owned()
+        } else {
+            format!("new{}", round - 1)
+        };
+
+        for i in 0..5u32 {
+            assert_eq!(
+                Some(expected.as_bytes().into()),
+                secondary.get(format!("{i}"), SeqNo::MAX)?,
+            );
+        }
+
+        secondary.try_catch_up()?;
+        assert_eq!(1, secondary.table_count());
+    }
+
+    assert_eq!(
+        Some("new2".as_bytes().into()),
+        secondary.get("3", SeqNo::MAX)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_secondary_reads_journal() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let primary = Config::new(&folder, seqno.clone())
+        .use_journal(true)
+        .open()?;
+    primary.insert("a", "a", seqno.next());
+
+    let secondary = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open_secondary()?;
+    assert!(secondary.contains_key("a", SeqNo::MAX)?);
+    assert_eq!(0, secondary.table_count());
+
+    primary.insert("b", "b", seqno.next());
+    secondary.try_catch_up()?;
+    assert!(secondary.contains_key("b", SeqNo::MAX)?);
+
+    // NOTE: Flushed writes are read from the table, not again from the journal
+    primary.flush_active_memtable(0)?;
+    primary.insert("c", "c", seqno.next());
+    secondary.try_catch_up()?;
+    assert_eq!(1, secondary.table_count());
+    assert_eq!(3, secondary.len(SeqNo::MAX, None)?);
+    assert!(secondary.active_memtable_size() > 0);
+
+    Ok(())
+}
+
+#[test]
+fn tree_secondary_read_only() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let primary = Config::new(&folder, seqno.clone()).open()?;
+    primary.insert("a", "a", seqno.next());
+    primary.flush_active_memtable(0)?;
+
+    let secondary = Config::new(&folder, SequenceNumberCounter::default()).open_secondary()?;
+    secondary.insert("b", "b", seqno.next());
+
+    assert!(matches!(
+        secondary.flush_active_memtable(0),
+        Err(lsm_tree::Error::ReadOnly),
+    ));
+    assert!(matches!(
+        secondary.major_compact(u64::MAX, SeqNo::MAX),
+        Err(lsm_tree::Error::ReadOnly),
+    ));
+    assert!(matches!(
+        secondary.ingest(
+            std::iter::once(("c".into(), "c".into())),
+            &seqno,
+            &SequenceNumberCounter::default(),
+        ),
+        Err(lsm_tree::Error::ReadOnly),
+    ));
+
+    // NOTE: Writes to a secondary instance are discarded when catching up
+    secondary.try_catch_up()?;
+    assert!(!secondary.contains_key("b", SeqNo::MAX)?);
+    assert_eq!(1, primary.table_count());
+
+    assert!(matches!(
+        primary.try_catch_up(),
+        Err(lsm_tree::Error::InvalidConfig(_)),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn tree_secondary_not_found() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path().join("tree");
+
+    assert!(matches!(
+        Config::new(&path, SequenceNumberCounter::default()).open_secondary(),
+        Err(lsm_tree::Error::TreeNotFound),
+    ));
+    assert!(!path.try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_secondary_catch_up() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let big = "a".repeat(1_000);
+
+    let config = |seqno| {
+        Config::new(&folder, seqno)
+            .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+    };
+
+    let primary = config(seqno.clone()).open()?;
+    primary.insert("a", &big, seqno.next());
+    primary.flush_active_memtable(0)?;
+
+    let secondary = config(SequenceNumberCounter::default()).open_secondary()?;
+    assert_eq!(1, secondary.blob_file_count());
+
+    primary.insert("b", &big, seqno.next());
+    primary.flush_active_memtable(0)?;
+
+    secondary.try_catch_up()?;
+    assert_eq!(2, secondary.blob_file_count());
+    assert_eq!(Some(big.as_bytes().into()), secondary.get("a", SeqNo::MAX)?);
+    assert_eq!(Some(big.as_bytes().into()), secondary.get("b", SeqNo::MAX)?);
+
+    Ok(())
+}
@@ -54,3 +54,37 @@ fn tree_highest_seqno() -> lsm_tree::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn tree_highest_seqno_recover() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    for kv_separation in [None, Some(lsm_tree::KvSeparationOptions::default())] {
+        let path = folder.path().join(format!("{}", kv_separation.is_some()));
+
+        {
+            let tree = Config::new(&path, SequenceNumberCounter::default())
+                .with_kv_separation(kv_separation.clone())
+                .open()?;
+
+            tree.insert("a", "a".repeat(10_000), 7);
+            tree.insert("b", "b", 3);
+            tree.flush_active_memtable(0)?;
+
+            // NOTE: Not flushed, so it is lost
+            tree.insert("c", "c", 10);
+        }
+
+        let seqno = SequenceNumberCounter::default();
+        let tree = Config::new(&path, seqno.clone())
+            .with_kv_separation(kv_separation)
+            .open()?;
+
+        assert_eq!(Some(7), tree.get_highest_seqno());
+        assert_eq!(Some(7), tree.get_highest_persisted_seqno());
+        assert_eq!(None, tree.get_highest_memtable_seqno());
+        assert_eq!(8, seqno.get());
+    }
+
+    Ok(())
+}
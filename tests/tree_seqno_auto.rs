@@ -0,0 +1,109 @@
+use lsm_tree::{AbstractTree, Config, SeqNo, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_seqno_auto() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let last_seqno = {
+        let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+        let a = tree.insert_auto("a", "a");
+        let b = tree.insert_auto("b", "b");
+        assert!(b > a);
+
+        let c = tree.remove_auto("a");
+        assert!(c > b);
+
+        assert!(tree.contains_key("a", c)?);
+        assert!(!tree.contains_key("a", c + 1)?);
+        assert!(tree.contains_key("b", SeqNo::MAX)?);
+
+        tree.flush_active_memtable(0)?;
+
+        c
+    };
+
+    {
+        // NOTE: The generator continues after the persisted data,
+        // so new writes shadow the old ones
+        let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+        let a = tree.insert_auto("a", "a2");
+        assert!(a > last_seqno);
+        assert_eq!(Some("a2".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+        let b = tree.remove_auto("b");
+        assert!(b > a);
+        assert!(!tree.contains_key("b", SeqNo::MAX)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_seqno_auto_journal() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let last_seqno = {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .use_journal(true)
+            .open()?;
+
+        tree.insert_auto("a", "a");
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Not flushed, but journaled
+        tree.insert_auto("b", "b")
+    };
+
+    {
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .use_journal(true)
+            .open()?;
+
+        assert!(tree.insert_auto("c", "c") > last_seqno);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_seqno_auto_other_writes() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let last_seqno = {
+        let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+        let mut batch = tree.batch();
+        batch.insert("a", "a");
+        batch.insert("b", "b");
+        batch.insert("c", "c");
+        let a = batch.commit_auto()?;
+        assert!(tree.contains_key("c", SeqNo::MAX)?);
+
+        let b = tree.remove_weak_auto("a");
+        assert!(b > a);
+        assert!(!tree.contains_key("a", SeqNo::MAX)?);
+
+        let c = tree.remove_range_auto("b"..="c");
+        assert!(c > b);
+        assert!(tree.is_empty(SeqNo::MAX, None)?);
+        assert!(tree.contains_key("b", c)?);
+
+        tree.flush_active_memtable(0)?;
+
+        c
+    };
+
+    {
+        // NOTE: The range tombstone is persisted, so new writes need to be newer than it
+        let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+        let a = tree.insert_auto("b", "b2");
+        assert!(a > last_seqno);
+        assert_eq!(Some("b2".as_bytes().into()), tree.get("b", SeqNo::MAX)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use std::path::Path;
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+/// Counts the files in the shard subfolders of a folder, failing if there are files at the top level.
+fn count_sharded_files(folder: &Path) -> lsm_tree::Result<usize> {
+    let mut count = 0;
+
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        assert!(path.is_dir(), "{} should be a shard folder", path.display());
+        count += std::fs::read_dir(path)?.count();
+    }
+
+    Ok(count)
+}
+
+#[test]
+fn tree_sharded_layout() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .directory_shards(4)
+            .open()?;
+
+        for batch in 0..4 {
+            for x in 0..ITEM_COUNT {
+                tree.insert(format!("{batch}:{x:0>3}"), "a", seqno.next());
+            }
+            tree.flush_active_memtable(0)?;
+        }
+
+        assert_eq!(4, tree.table_count());
+        assert_eq!(4, count_sharded_files(&folder.path().join("tables"))?);
+
+        tree.major_compact(u64::MAX, SeqNo::MAX)?;
+        assert_eq!(1, tree.table_count());
+        assert_eq!(1, count_sharded_files(&folder.path().join("tables"))?);
+    }
+
+    {
+        // NOTE: The shard count is persisted, so it is not needed to reopen the tree
+        let tree = Config::new(&folder, seqno.clone()).open()?;
+        assert_eq!(4, tree.tree_config().directory_shards);
+        assert_eq!(1, tree.table_count());
+        assert_eq!(ITEM_COUNT * 4, tree.len(SeqNo::MAX, None)?);
+
+        tree.insert("5:000", "a", seqno.next());
+        tree.flush_active_memtable(0)?;
+        assert_eq!(2, count_sharded_files(&folder.path().join("tables"))?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_sharded_layout_blob() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .directory_shards(4)
+            .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+            .open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(format!("{x:0>3}"), "a".repeat(100), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        assert_eq!(1, tree.blob_file_count());
+        assert_eq!(1, count_sharded_files(&folder.path().join("blobs"))?);
+    }
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+            .open()?;
+        assert_eq!(1, tree.blob_file_count());
+        assert_eq!(
+            Some("a".repeat(100).as_bytes().into()),
+            tree.get("042", SeqNo::MAX)?
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,245 @@
+use lsm_tree::{
+    config::CompressionPolicy, AbstractTree, AnyTree, Config, FileHandle, Filesystem, Guard,
+    KvSeparationOptions, SeqNo, SequenceNumberCounter, StdFilesystem,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        Arc,
+    },
+};
+use test_log::test;
+
+/// Fails creating tables once a number of tables was created
+#[derive(Debug)]
+struct FailingFilesystem {
+    tables_left: AtomicUsize,
+}
+
+impl Filesystem for FailingFilesystem {
+    fn open(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        StdFilesystem.open(path)
+    }
+
+    fn open_writable(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        StdFilesystem.open_writable(path)
+    }
+
+    fn create_new(&self, path: &Path) -> std::io::Result<Box<dyn FileHandle>> {
+        if path.components().any(|c| c.as_os_str() == "tables")
+            && self
+                .tables_left
+                .fetch_update(Relaxed, Relaxed, |left| left.checked_sub(1))
+                .is_err()
+        {
+            return Err(std::io::Error::other("injected fault"));
+        }
+        StdFilesystem.create_new(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        StdFilesystem.create_dir_all(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        StdFilesystem.read_dir(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        StdFilesystem.remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        StdFilesystem.remove_dir(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        StdFilesystem.rename(from, to)
+    }
+
+    fn exists(&self, path: &Path) -> std::io::Result<bool> {
+        StdFilesystem.exists(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> std::io::Result<bool> {
+        StdFilesystem.is_dir(path)
+    }
+
+    fn sync_directory(&self, path: &Path) -> std::io::Result<()> {
+        StdFilesystem.sync_directory(path)
+    }
+}
+
+fn file_count(path: &Path) -> lsm_tree::Result<usize> {
+    let mut count = 0;
+
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() {
+            count += file_count(&entry.path())?;
+        } else {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+const ITEM_COUNT: u32 = 1_000;
+
+fn fill(tree: &AnyTree, seqno: &SequenceNumberCounter, value: &[u8]) -> lsm_tree::Result<()> {
+    for batch in 0..4 {
+        for i in 0..ITEM_COUNT {
+            tree.insert(format!("{batch}/{i:0>5}"), value, seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Overwrite and delete keys across the entire key space
+    for batch in 0..4 {
+        for i in (0..ITEM_COUNT).step_by(10) {
+            tree.insert(format!("{batch}/{i:0>5}"), "new", seqno.next());
+        }
+        for i in (5..ITEM_COUNT).step_by(10) {
+            tree.remove(format!("{batch}/{i:0>5}"), seqno.next());
+        }
+    }
+    tree.flush_active_memtable(0)?;
+
+    Ok(())
+}
+
+fn assert_compacted(tree: &AnyTree) -> lsm_tree::Result<()> {
+    let expected_len = 4 * (ITEM_COUNT - ITEM_COUNT / 10);
+
+    assert_eq!(0, tree.tombstone_count());
+    assert_eq!(expected_len as usize, tree.len(SeqNo::MAX, None)?);
+
+    for batch in 0..4 {
+        assert_eq!(
+            Some("new".as_bytes().into()),
+            tree.get(format!("{batch}/00010"), SeqNo::MAX)?,
+        );
+        assert!(!tree.contains_key(format!("{batch}/00015"), SeqNo::MAX)?);
+        assert!(tree.contains_key(format!("{batch}/00016"), SeqNo::MAX)?);
+    }
+
+    // NOTE: The tables of the subcompactions form a single sorted run
+    let mut tables = tree.tables();
+    tables.sort_by(|a, b| a.key_range.min().cmp(b.key_range.min()));
+
+    for pair in tables.windows(2) {
+        let [a, b] = pair else { unreachable!() };
+        assert!(a.key_range.max() < b.key_range.min());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_subcompaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .max_subcompactions(4)
+        .open()?;
+
+    fill(&tree, &seqno, &[0; 100])?;
+    assert_eq!(5, tree.table_count());
+
+    tree.major_compact(16 * 1_024, SeqNo::MAX)?;
+
+    assert!(tree.table_count() >= 4);
+    assert_compacted(&tree)?;
+
+    // NOTE: The result is the same after recovery
+    drop(tree);
+    let tree = Config::new(&folder, seqno.clone()).open()?;
+    assert_compacted(&tree)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_subcompaction_failure_deletes_tables() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let fs = Arc::new(FailingFilesystem {
+        tables_left: AtomicUsize::new(usize::MAX),
+    });
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_filesystem(fs.clone())
+        .data_block_compression_policy(CompressionPolicy::disabled())
+        .max_subcompactions(4)
+        .open()?;
+
+    fill(&tree, &seqno, &[0; 100])?;
+    assert_eq!(5, tree.table_count());
+
+    // NOTE: Some subcompactions fail while writing their tables, after others already wrote theirs
+    fs.tables_left.store(6, Relaxed);
+    assert!(tree.major_compact(16 * 1_024, SeqNo::MAX).is_err());
+
+    assert_eq!(5, tree.table_count());
+    assert_eq!(5, file_count(&folder.path().join("tables"))?);
+
+    fs.tables_left.store(usize::MAX, Relaxed);
+    tree.major_compact(16 * 1_024, SeqNo::MAX)?;
+    assert_compacted(&tree)?;
+    assert_eq!(
+        tree.table_count(),
+        file_count(&folder.path().join("tables"))?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_subcompaction_same_as_single_compaction() -> lsm_tree::Result<()> {
+    let mut contents = vec![];
+
+    for max_subcompactions in [1, 4] {
+        let folder = tempfile::tempdir()?;
+        let seqno = SequenceNumberCounter::default();
+
+        let tree = Config::new(&folder, seqno.clone())
+            .max_subcompactions(max_subcompactions)
+            .open()?;
+
+        fill(&tree, &seqno, &[0; 100])?;
+        tree.major_compact(16 * 1_024, SeqNo::MAX)?;
+
+        contents.push(
+            tree.iter(SeqNo::MAX, None)
+                .map(Guard::into_inner)
+                .collect::<lsm_tree::Result<Vec<_>>>()?,
+        );
+    }
+
+    assert_eq!(contents[0], contents[1]);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_subcompaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .max_subcompactions(4)
+        .open()?;
+
+    fill(&tree, &seqno, &[0; 100])?;
+    tree.major_compact(16 * 1_024, SeqNo::MAX)?;
+
+    assert!(tree.table_count() >= 2);
+    assert_compacted(&tree)?;
+
+    Ok(())
+}
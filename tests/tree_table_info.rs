@@ -0,0 +1,59 @@
+use lsm_tree::{compaction::PullDown, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn tree_table_info() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+    assert!(tree.tables().is_empty());
+
+    tree.insert("a", "abc", 0);
+    tree.insert("b", "abc", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("x", "abc", 2);
+    tree.remove("y", 3);
+    tree.flush_active_memtable(0)?;
+
+    tree.compact(Arc::new(PullDown(0, 1)), 0)?;
+
+    tree.insert("m", "abc", 4);
+    tree.flush_active_memtable(0)?;
+
+    let tables = tree.tables();
+    assert_eq!(2, tables.len());
+    assert_eq!(
+        tree.disk_space(),
+        tables.iter().map(|t| t.file_size).sum::<u64>(),
+    );
+
+    let l0 = &tables[0];
+    assert_eq!(0, l0.level);
+    assert_eq!(1, l0.item_count);
+    assert_eq!((4, 4), l0.seqnos);
+    assert!(l0.created_at > 0);
+
+    // NOTE: There is no data beneath L1, so the tombstone was evicted
+    let l1 = &tables[1];
+    assert_eq!(1, l1.level);
+    assert_eq!(3, l1.item_count);
+    assert_eq!(0, l1.tombstone_count);
+    assert_eq!((0, 2), l1.seqnos);
+    assert_eq!(b"a", &**l1.key_range.min());
+    assert_eq!(b"x", &**l1.key_range.max());
+
+    // NOTE: Map a key to the files that may contain it
+    let files = tables
+        .iter()
+        .filter(|t| t.key_range.contains_key(b"m"))
+        .map(|t| t.id)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![l0.id, l1.id], files);
+
+    for table in &tables {
+        assert!(table.path.try_exists()?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use lsm_tree::{AbstractTree, Config, KvSeparationOptions, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_temporary() -> lsm_tree::Result<()> {
+    let config = Config::new(".unused", SequenceNumberCounter::default()).temporary(true);
+
+    let tree = config.clone().open()?;
+    let path = tree.tree_config().path.clone();
+    assert!(path.starts_with(std::env::temp_dir()));
+    assert!(!std::path::Path::new(".unused").try_exists()?);
+
+    for x in 0..100u64 {
+        tree.insert(x.to_be_bytes(), "a", x);
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.table_count());
+    assert!(path.join("tables").try_exists()?);
+
+    // NOTE: Every open gets its own folder
+    let other_tree = config.open()?;
+    assert_ne!(path, other_tree.tree_config().path);
+    assert_eq!(0, other_tree.len(u64::MAX, None)?);
+
+    drop(tree);
+    assert!(!path.try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_temporary() -> lsm_tree::Result<()> {
+    let tree = Config::new(".unused", SequenceNumberCounter::default())
+        .temporary(true)
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+    let path = tree.tree_config().path.clone();
+
+    tree.insert("a", "a".repeat(1_000), 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+    assert_eq!(Some("a".repeat(1_000).as_bytes().into()), tree.get("a", 1)?);
+
+    drop(tree);
+    assert!(!path.try_exists()?);
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use lsm_tree::{
+    compaction::{CompactionStrategy, LazyLeveled, SizeTiered},
+    AbstractTree, Config, SeqNo, SequenceNumberCounter,
+};
+use std::sync::Arc;
+use test_log::test;
+
+const KEY_COUNT: u64 = 100;
+
+fn overwrite_and_delete(
+    strategy: Arc<dyn CompactionStrategy + Send + Sync>,
+) -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone())
+        .compaction_strategy(strategy)
+        .open()?;
+
+    for round in 0..50u64 {
+        for key in (round % 3..KEY_COUNT).step_by(3) {
+            tree.insert(key.to_be_bytes(), round.to_be_bytes(), seqno.next());
+        }
+        if round % 7 == 0 {
+            tree.remove(round.to_be_bytes(), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+        tree.run_compaction(seqno.get())?;
+
+        // NOTE: No level ever holds more runs than the merge width
+        assert!(tree.levels().iter().all(|level| level.run_count <= 3));
+    }
+
+    for key in 0..KEY_COUNT {
+        let expected = (0..50u64).rev().find(|round| round % 3 == key % 3);
+        let is_deleted = key % 7 == 0 && key < 50 && expected.is_some_and(|round| round <= key);
+
+        let value = tree.get(key.to_be_bytes(), SeqNo::MAX)?;
+
+        if is_deleted {
+            assert_eq!(None, value, "key {key} should be deleted");
+        } else {
+            assert_eq!(
+                expected.map(|round| round.to_be_bytes().into()),
+                value,
+                "key {key} should have newest value",
+            );
+        }
+    }
+
+    // NOTE: Data spreads over multiple levels, instead of being merged into the last level right away
+    assert!(
+        tree.levels()
+            .iter()
+            .filter(|level| !level.is_empty())
+            .count()
+            > 1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_size_tiered() -> lsm_tree::Result<()> {
+    overwrite_and_delete(Arc::new(SizeTiered::default().with_level_ratio(3)))
+}
+
+#[test]
+fn tree_lazy_leveled() -> lsm_tree::Result<()> {
+    overwrite_and_delete(Arc::new(LazyLeveled::default().with_level_ratio(3)))
+}
@@ -0,0 +1,231 @@
+use lsm_tree::{
+    tiering::{offload_cold_files, RemoteStorage, TieredFilesystem},
+    AbstractTree, Config, KvSeparationOptions, SequenceNumberCounter, StdFilesystem,
+};
+use rand::RngCore;
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use test_log::test;
+
+#[derive(Debug, Default)]
+struct MemoryStorage {
+    objects: Mutex<HashMap<String, Vec<u8>>>,
+    downloaded_bytes: AtomicU64,
+}
+
+impl MemoryStorage {
+    fn len(&self) -> usize {
+        self.objects.lock().unwrap().len()
+    }
+}
+
+impl RemoteStorage for MemoryStorage {
+    fn upload(&self, key: &str, reader: &mut dyn std::io::Read) -> std::io::Result<()> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        self.objects.lock().unwrap().insert(key.into(), bytes);
+        Ok(())
+    }
+
+    fn download_range(&self, key: &str, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        let objects = self.objects.lock().unwrap();
+        let bytes = objects
+            .get(key)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))?;
+
+        let range = bytes
+            .get(offset as usize..offset as usize + buf.len())
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+        buf.copy_from_slice(range);
+
+        self.downloaded_bytes
+            .fetch_add(buf.len() as u64, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> std::io::Result<()> {
+        self.objects.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+fn local_size(path: &Path) -> u64 {
+    std::fs::read_dir(path)
+        .unwrap()
+        .map(|dirent| dirent.unwrap().metadata().unwrap().len())
+        .sum()
+}
+
+#[test]
+fn tree_tiering_offload() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let remote = Arc::new(MemoryStorage::default());
+    let fs = Arc::new(TieredFilesystem::new(
+        Arc::new(StdFilesystem),
+        remote.clone(),
+    ));
+
+    let config = Config::new(&folder, SequenceNumberCounter::default()).with_filesystem(fs.clone());
+    let value = "a".repeat(1_000);
+
+    {
+        let tree = config.clone().open()?;
+
+        for x in 0..1_000u64 {
+            tree.insert(x.to_be_bytes(), &value, x);
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: L0 tables are hot
+        assert_eq!(0, offload_cold_files(&tree, &fs)?);
+
+        tree.major_compact(u64::MAX, 1_000)?;
+        assert_eq!(1, tree.table_count());
+
+        let tables_folder = folder.path().join("tables");
+        let size_before = local_size(&tables_folder);
+
+        assert_eq!(1, offload_cold_files(&tree, &fs)?);
+        assert_eq!(1, remote.len());
+        assert!(local_size(&tables_folder) < size_before / 100);
+
+        // NOTE: Already offloaded
+        assert_eq!(0, offload_cold_files(&tree, &fs)?);
+
+        assert_eq!(
+            Some(value.as_bytes().into()),
+            tree.get(5u64.to_be_bytes(), u64::MAX)?,
+        );
+    }
+
+    {
+        let tree = config.open()?;
+
+        assert_eq!(1_000, tree.len(u64::MAX, None)?);
+        assert_eq!(
+            Some(value.as_bytes().into()),
+            tree.get(5u64.to_be_bytes(), u64::MAX)?,
+        );
+
+        tree.drop_range::<&[u8], _>(..)?;
+        assert_eq!(0, tree.table_count());
+    }
+
+    // NOTE: Deleting the table deletes its object
+    assert_eq!(0, remote.len());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_tiering_offload() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let remote = Arc::new(MemoryStorage::default());
+    let fs = Arc::new(TieredFilesystem::new(
+        Arc::new(StdFilesystem),
+        remote.clone(),
+    ));
+
+    let config = Config::new(&folder, SequenceNumberCounter::default())
+        .with_filesystem(fs.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)));
+    let big_value = "a".repeat(10_000);
+
+    {
+        let tree = config.clone().open()?;
+
+        tree.insert("a", &big_value, 0);
+        tree.flush_active_memtable(0)?;
+        tree.major_compact(u64::MAX, 1)?;
+        assert_eq!(1, tree.blob_file_count());
+
+        // NOTE: The table and its blob file
+        assert_eq!(2, offload_cold_files(&tree, &fs)?);
+        assert_eq!(2, remote.len());
+    }
+
+    {
+        let tree = config.open()?;
+        assert_eq!(Some(big_value.as_bytes().into()), tree.get("a", u64::MAX)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_tiering_ranged_fetch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let remote = Arc::new(MemoryStorage::default());
+
+    let new_fs = || {
+        Arc::new(
+            TieredFilesystem::new(Arc::new(StdFilesystem), remote.clone())
+                .with_fetch_size(4_096)
+                .with_cache_capacity(64 * 1_024),
+        )
+    };
+
+    let config = |fs: &Arc<TieredFilesystem>| {
+        Config::new(&folder, SequenceNumberCounter::default()).with_filesystem(fs.clone())
+    };
+    // NOTE: Incompressible values, so the table is large even if compressed
+    let values = (0..1_000)
+        .map(|_| {
+            let mut value = vec![0; 1_000];
+            rand::rng().fill_bytes(&mut value);
+            value
+        })
+        .collect::<Vec<_>>();
+
+    {
+        let fs = new_fs();
+        let tree = config(&fs).open()?;
+
+        for (x, value) in (0..1_000u64).zip(&values) {
+            tree.insert(x.to_be_bytes(), value, x);
+        }
+        tree.flush_active_memtable(0)?;
+        tree.major_compact(u64::MAX, 1_000)?;
+
+        assert_eq!(1, offload_cold_files(&tree, &fs)?);
+    }
+
+    let object_size = remote
+        .objects
+        .lock()
+        .unwrap()
+        .values()
+        .next()
+        .unwrap()
+        .len() as u64;
+
+    let fs = new_fs();
+    let tree = config(&fs).open()?;
+
+    // NOTE: Recovering the tree only fetches the metadata of the table
+    let fetched = remote.downloaded_bytes.load(Ordering::Relaxed);
+    assert!(fetched < object_size / 10);
+
+    assert_eq!(
+        Some(values[500].as_slice().into()),
+        tree.get(500u64.to_be_bytes(), u64::MAX)?,
+    );
+    assert!(remote.downloaded_bytes.load(Ordering::Relaxed) - fetched < object_size / 10);
+
+    // NOTE: The cache is bounded, even if the whole table is read
+    assert_eq!(1_000, tree.len(u64::MAX, None)?);
+    assert!(fs.cached_bytes() <= 64 * 1_024);
+    assert!(remote.downloaded_bytes.load(Ordering::Relaxed) >= object_size);
+
+    // NOTE: Nothing is cached on local disk
+    assert!(local_size(&folder.path().join("tables")) < object_size / 100);
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use lsm_tree::{AbstractTree, Config, Conflict, SequenceNumberCounter, TransactionalTree};
+use test_log::test;
+
+fn open(folder: &tempfile::TempDir) -> lsm_tree::Result<TransactionalTree> {
+    Ok(TransactionalTree::new(
+        Config::new(folder, SequenceNumberCounter::default()).open()?,
+    ))
+}
+
+#[test]
+fn tree_transaction_read_your_writes() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open(&folder)?;
+
+    let mut tx = tree.begin();
+    tx.insert("a", "1");
+    tx.insert("b", "2");
+    tx.remove("b");
+    assert_eq!(Some("1".as_bytes().into()), tx.get("a")?);
+    assert!(!tx.contains_key("b")?);
+    assert_eq!(2, tx.write_count());
+
+    // NOTE: Nothing is visible before commit
+    assert!(tree.get("a")?.is_none());
+
+    let instant = tree.instant();
+    assert_eq!(Ok(()), tx.commit()?);
+    assert!(tree.instant() > instant);
+
+    assert_eq!(Some("1".as_bytes().into()), tree.get("a")?);
+    assert!(tree.get("b")?.is_none());
+
+    // NOTE: Both writes share a sequence number
+    assert_eq!(Some(instant), tree.tree().get_highest_seqno());
+
+    // NOTE: Dropped transactions are discarded
+    let mut tx = tree.begin();
+    tx.insert("c", "3");
+    drop(tx);
+    assert!(tree.get("c")?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn tree_transaction_conflict() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = open(&folder)?;
+
+    let mut tx = tree.begin();
+    tx.insert("a", "0");
+    tx.commit()?.expect("should not conflict");
+
+    // NOTE: Read-write conflict
+    let mut tx1 = tree.begin();
+    let mut tx2 = tree.begin();
+    assert!(tx1.get("a")?.is_some());
+    tx1.insert("b", "1");
+    tx2.insert("a", "2");
+    assert_eq!(Ok(()), tx2.commit()?);
+    assert_eq!(Err(Conflict), tx1.commit()?);
+    assert!(tree.get("b")?.is_none());
+
+    // NOTE: Write-write conflict
+    let mut tx1 = tree.begin();
+    let mut tx2 = tree.begin();
+    tx1.insert("c", "1");
+    tx2.remove("c");
+    assert_eq!(Ok(()), tx1.commit()?);
+    assert_eq!(Err(Conflict), tx2.commit()?);
+    assert_eq!(Some("1".as_bytes().into()), tree.get("c")?);
+
+    // NOTE: Disjoint keys do not conflict
+    let mut tx1 = tree.begin();
+    let mut tx2 = tree.begin();
+    tx1.get("x")?;
+    tx1.insert("x", "1");
+    tx2.get("y")?;
+    tx2.insert("y", "1");
+    assert_eq!(Ok(()), tx1.commit()?);
+    assert_eq!(Ok(()), tx2.commit()?);
+
+    // NOTE: Reading a key that is removed concurrently conflicts
+    let mut tx1 = tree.begin();
+    let mut tx2 = tree.begin();
+    tx1.get("x")?;
+    tx2.remove("x");
+    assert_eq!(Ok(()), tx2.commit()?);
+    assert_eq!(Err(Conflict), tx1.commit()?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_transaction_counter() -> lsm_tree::Result<()> {
+    const THREADS: u8 = 4;
+    const INCREMENTS: u8 = 25;
+
+    let folder = tempfile::tempdir()?;
+    let tree = open(&folder)?;
+
+    let handles = (0..THREADS)
+        .map(|_| {
+            let tree = tree.clone();
+
+            std::thread::spawn(move || -> lsm_tree::Result<()> {
+                for _ in 0..INCREMENTS {
+                    loop {
+                        let mut tx = tree.begin();
+                        let count = tx.get("count")?.map_or(0, |v| v[0]);
+                        tx.insert("count", [count + 1]);
+
+                        if tx.commit()?.is_ok() {
+                            break;
+                        }
+                    }
+                }
+
+                Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.join().expect("should join")?;
+    }
+
+    assert_eq!(
+        Some([THREADS * INCREMENTS].as_slice().into()),
+        tree.get("count")?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_transaction_journal_replay() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = TransactionalTree::new(
+            Config::new(&folder, SequenceNumberCounter::default())
+                .use_journal(true)
+                .open()?,
+        );
+
+        let mut tx = tree.begin();
+        tx.insert("a", "1");
+        tx.insert("b", "2");
+        tx.remove("c");
+        assert_eq!(Ok(()), tx.commit()?);
+    }
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .use_journal(true)
+        .open()?;
+
+    // NOTE: The commit is replayed as a whole, with its shared sequence number
+    assert_eq!(Some(0), tree.get_highest_seqno());
+    assert_eq!(Some("1".as_bytes().into()), tree.get("a", 1)?);
+    assert_eq!(Some("2".as_bytes().into()), tree.get("b", 1)?);
+    assert_eq!(2, tree.len(1, None)?);
+
+    Ok(())
+}
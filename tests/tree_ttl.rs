@@ -0,0 +1,217 @@
+use lsm_tree::{
+    AbstractTree, Clock, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter, ValueType,
+};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Arc,
+    },
+    time::Duration,
+};
+use test_log::test;
+
+/// Clock that only moves when advanced manually
+#[derive(Debug)]
+struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    fn advance(&self, secs: u64) {
+        self.0.fetch_add(secs, Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_secs(self.0.load(Relaxed))
+    }
+}
+
+#[test]
+fn tree_ttl_expires_on_read() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_clock(clock.clone())
+        .open()?;
+
+    tree.insert("a", "old", seqno.next());
+    tree.insert_with_ttl("a", "new", seqno.next(), Duration::from_secs(10));
+    tree.insert_with_ttl("b", "short", seqno.next(), Duration::from_secs(5));
+    tree.insert("c", "forever", seqno.next());
+
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+    assert_eq!(Some("short".as_bytes().into()), tree.get("b", SeqNo::MAX)?);
+    assert_eq!(3, tree.len(SeqNo::MAX, None)?);
+
+    clock.advance(5);
+    assert!(!tree.contains_key("b", SeqNo::MAX)?);
+    assert_eq!(2, tree.len(SeqNo::MAX, None)?);
+
+    // NOTE: Expired values also hide their older versions, in memtables and tables
+    tree.flush_active_memtable(0)?;
+    clock.advance(5);
+    assert_eq!(None, tree.get("a", SeqNo::MAX)?);
+    assert_eq!(1, tree.len(SeqNo::MAX, None)?);
+    assert_eq!(
+        Some("forever".as_bytes().into()),
+        tree.get("c", SeqNo::MAX)?
+    );
+
+    // NOTE: Overwriting an expiring value with a normal value revives it
+    tree.insert("a", "revived", seqno.next());
+    assert_eq!(
+        Some("revived".as_bytes().into()),
+        tree.get("a", SeqNo::MAX)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_ttl_get_versions() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+
+    for kv_separation in [
+        None,
+        Some(KvSeparationOptions::default().separation_threshold(1)),
+    ] {
+        let folder = folder.path().join(format!("{}", kv_separation.is_some()));
+
+        let tree = Config::new(&folder, SequenceNumberCounter::default())
+            .with_clock(clock.clone())
+            .with_kv_separation(kv_separation)
+            .open()?;
+
+        tree.insert("a", "old", 0);
+        tree.insert_with_ttl("a", "short", 1, Duration::from_secs(5));
+        tree.insert_with_ttl("a", "long", 2, Duration::from_secs(60));
+
+        let versions = tree.get_versions("a", 10)?;
+        assert_eq!(3, versions.len());
+        assert_eq!((2, ValueType::Value), (versions[0].0, versions[0].1));
+        assert_eq!(b"long", &*versions[0].2);
+        assert_eq!(b"short", &*versions[1].2);
+
+        // NOTE: Expired versions are listed as tombstones, before and after flushing
+        clock.advance(5);
+
+        for _ in 0..2 {
+            let versions = tree.get_versions("a", 10)?;
+            assert_eq!(
+                vec![
+                    (2, ValueType::Value, "long".as_bytes()),
+                    (1, ValueType::Tombstone, b""),
+                    (0, ValueType::Value, "old".as_bytes()),
+                ],
+                versions
+                    .iter()
+                    .map(|(seqno, value_type, value)| (*seqno, *value_type, &**value))
+                    .collect::<Vec<_>>(),
+            );
+
+            tree.flush_active_memtable(0)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_ttl_dropped_by_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_clock(clock.clone())
+        .open()?;
+
+    for i in 0..100u32 {
+        tree.insert(format!("{i:0>3}"), "v", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+
+    for i in 0..50u32 {
+        tree.insert_with_ttl(
+            format!("{i:0>3}"),
+            "v",
+            seqno.next(),
+            Duration::from_secs(60),
+        );
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(100, tree.len(SeqNo::MAX, None)?);
+
+    clock.advance(60);
+    assert_eq!(50, tree.len(SeqNo::MAX, None)?);
+
+    // NOTE: The expired values and the older versions they shadow are physically dropped
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    assert_eq!(1, tree.table_count());
+    assert_eq!(50, tree.approximate_len());
+    assert_eq!(0, tree.tombstone_count());
+    assert_eq!(50, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_ttl_survives_recovery() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder, seqno.clone())
+            .with_clock(clock.clone())
+            .open()?;
+
+        tree.insert_with_ttl("a", "abc", seqno.next(), Duration::from_secs(60));
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Not expired yet, so compaction keeps the value and its expiry timestamp
+        tree.major_compact(u64::MAX, SeqNo::MAX)?;
+    }
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_clock(clock.clone())
+        .open()?;
+
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("a", SeqNo::MAX)?);
+
+    clock.advance(60);
+    assert_eq!(None, tree.get("a", SeqNo::MAX)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_ttl() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let clock = Arc::new(ManualClock(AtomicU64::new(1_000)));
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .with_clock(clock.clone())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .open()?;
+
+    let big = "a".repeat(1_000);
+
+    tree.insert("a", &big, seqno.next());
+    tree.insert_with_ttl("b", &big, seqno.next(), Duration::from_secs(60));
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(Some(big.as_bytes().into()), tree.get("b", SeqNo::MAX)?);
+    assert_eq!(2, tree.len(SeqNo::MAX, None)?);
+
+    clock.advance(60);
+    assert_eq!(None, tree.get("b", SeqNo::MAX)?);
+    assert_eq!(1, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
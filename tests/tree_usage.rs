@@ -0,0 +1,78 @@
+use lsm_tree::{AbstractTree, Cache, Config, KvSeparationOptions, SeqNo, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn tree_disk_usage() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+    let tree = Config::new(&folder, seqno.clone())
+        .use_journal(true)
+        .open()?;
+
+    let usage = tree.disk_usage()?;
+    assert_eq!(0, usage.table_bytes());
+    assert_eq!(0, usage.blob_bytes);
+
+    for x in 0..1_000u64 {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), seqno.next());
+    }
+    assert!(tree.disk_usage()?.journal_bytes > 100_000);
+
+    tree.flush_active_memtable(0)?;
+    tree.compact(Arc::new(lsm_tree::compaction::PullDown(0, 6)), 0)?;
+
+    let usage = tree.disk_usage()?;
+    assert_eq!(tree.disk_space(), usage.table_bytes());
+    assert_eq!(0, usage.levels[0]);
+    assert!(usage.levels[6] > 0);
+    assert_eq!(usage.table_bytes() + usage.journal_bytes, usage.total());
+
+    Ok(())
+}
+
+#[test]
+fn tree_memory_usage() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default()).open()?;
+
+    tree.insert("a", "a".repeat(1_000), 0);
+    let usage = tree.memory_usage();
+    assert_eq!(tree.active_memtable_size(), usage.active_memtable_bytes);
+    assert_eq!(0, usage.sealed_memtable_bytes);
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(0, tree.memory_usage().memtable_bytes());
+
+    tree.insert("b", "a".repeat(1_000), 1);
+    tree.rotate_memtable();
+    let usage = tree.memory_usage();
+    assert_eq!(0, usage.active_memtable_bytes);
+    assert!(usage.sealed_memtable_bytes > 1_000);
+
+    tree.get("a", SeqNo::MAX)?;
+    assert!(tree.memory_usage().block_cache_bytes > 0);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_usage() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_kv_separation(Some(KvSeparationOptions::default().separation_threshold(1)))
+        .use_blob_cache(Arc::new(Cache::with_capacity_bytes(64 * 1_024 * 1_024)))
+        .open()?;
+
+    tree.insert("a", "a".repeat(1_000), 0);
+    tree.flush_active_memtable(0)?;
+
+    let usage = tree.disk_usage()?;
+    assert!(usage.blob_bytes > 0);
+    assert_eq!(tree.disk_space(), usage.table_bytes() + usage.blob_bytes);
+
+    tree.get("a", SeqNo::MAX)?;
+    assert!(tree.memory_usage().blob_cache_bytes > 0);
+
+    Ok(())
+}
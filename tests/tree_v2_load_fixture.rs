@@ -1,11 +1,14 @@
+mod common;
+
+use common::copy_fixture;
 use lsm_tree::{Config, SequenceNumberCounter};
 use test_log::test;
 
 #[test]
 fn tree_load_v2() -> lsm_tree::Result<()> {
-    let folder = "test_fixture/v2_tree";
+    let folder = copy_fixture("v2_tree")?;
 
-    let result = Config::new(folder, SequenceNumberCounter::default()).open();
+    let result = Config::new(&folder, SequenceNumberCounter::default()).open();
 
     matches!(result, Err(lsm_tree::Error::InvalidVersion(2)));
 
@@ -14,9 +17,9 @@ fn tree_load_v2() -> lsm_tree::Result<()> {
 
 #[test]
 fn tree_load_v2_corrupt() -> lsm_tree::Result<()> {
-    let folder = "test_fixture/v2_tree_corrupt";
+    let folder = copy_fixture("v2_tree_corrupt")?;
 
-    let result = Config::new(folder, SequenceNumberCounter::default()).open();
+    let result = Config::new(&folder, SequenceNumberCounter::default()).open();
 
     matches!(result, Err(lsm_tree::Error::InvalidVersion(2)));
 
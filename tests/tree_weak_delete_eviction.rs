@@ -22,3 +22,35 @@ fn tree_weak_remove_flush_eviction() -> lsm_tree::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn tree_weak_remove_queue_no_tombstones() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = lsm_tree::Config::new(folder.path(), seqno.clone()).open()?;
+
+    for round in 0..5u64 {
+        for x in 0..100u64 {
+            tree.insert((round * 100 + x).to_be_bytes(), "job", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        for x in 0..100u64 {
+            tree.remove_weak((round * 100 + x).to_be_bytes(), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert!(tree.is_empty(SeqNo::MAX, None)?);
+    assert_eq!(500, tree.weak_tombstone_count());
+    assert_eq!(500, tree.tombstone_count());
+
+    // NOTE: Weak tombstones are dropped together with the values they delete
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert!(tree.is_empty(SeqNo::MAX, None)?);
+    assert_eq!(0, tree.tombstone_count());
+    assert_eq!(0, tree.table_count());
+
+    Ok(())
+}
@@ -27,6 +27,7 @@ fn tree_write_and_read() -> lsm_tree::Result<()> {
     assert_eq!(item.key.seqno, 2);
 
     tree.flush_active_memtable(0)?;
+    drop(tree);
 
     let tree = Config::new(folder, SequenceNumberCounter::default()).open()?;
 
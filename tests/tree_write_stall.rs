@@ -0,0 +1,101 @@
+use lsm_tree::{
+    AbstractTree, Config, SeqNo, SequenceNumberCounter, WriteStallCondition, WriteStallListener,
+    WriteStallOptions,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use test_log::test;
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<WriteStallCondition>>);
+
+impl WriteStallListener for Recorder {
+    fn on_write_stall(&self, condition: WriteStallCondition) {
+        self.0.lock().expect("lock is poisoned").push(condition);
+    }
+}
+
+#[test]
+fn tree_write_stall_l0() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let recorder = Arc::new(Recorder::default());
+
+    let tree = Config::new(&folder, SequenceNumberCounter::default())
+        .with_write_stall(Some(
+            WriteStallOptions::default()
+                .l0_slowdown(2)
+                .l0_stop(3)
+                .delay(Duration::from_millis(1)),
+        ))
+        .with_write_stall_listener(Some(recorder.clone()))
+        .open()?;
+
+    for i in 0..3u8 {
+        tree.insert([i], "v", u64::from(i));
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(WriteStallCondition::Delayed, tree.write_stall_condition());
+
+    // NOTE: Blocks until L0 is compacted
+    let writer = std::thread::spawn({
+        let tree = tree.clone();
+        move || tree.insert("x", "v", 3)
+    });
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(!writer.is_finished());
+    assert_eq!(WriteStallCondition::Stopped, tree.write_stall_condition());
+
+    tree.major_compact(u64::MAX, 0)?;
+    writer.join().expect("should join");
+
+    assert_eq!(WriteStallCondition::Normal, tree.write_stall_condition());
+    assert!(tree.contains_key("x", SeqNo::MAX)?);
+
+    assert_eq!(
+        vec![
+            WriteStallCondition::Delayed,
+            WriteStallCondition::Stopped,
+            WriteStallCondition::Normal,
+        ],
+        *recorder.0.lock().expect("lock is poisoned"),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_write_stall_background_work() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder, seqno.clone())
+        .max_memtable_size(4_096)
+        .background_threads(2)
+        .background_gc_watermark(seqno)
+        .with_write_stall(Some(WriteStallOptions::default().l0_slowdown(2).l0_stop(4)))
+        .open()?;
+
+    // NOTE: The writer is throttled to the speed of the background workers, but never blocked forever
+    for i in 0..2_000u32 {
+        tree.insert_auto(i.to_be_bytes(), "v".repeat(50));
+    }
+
+    assert_eq!(2_000, tree.len(SeqNo::MAX, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_write_stall_invalid_config() {
+    let folder = tempfile::tempdir().expect("should create folder");
+
+    let result = Config::new(&folder, SequenceNumberCounter::default())
+        .with_write_stall(Some(WriteStallOptions::default().l0_slowdown(8).l0_stop(4)))
+        .open();
+
+    assert!(matches!(result, Err(lsm_tree::Error::InvalidConfig(_))));
+}